@@ -1,6 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::time::Duration;
+
+use device_id::DeviceId;
 use ostd::sync::{Mutex, WaitQueue};
+use spin::Once;
 
 use super::{
     bio::{BioEnqueueError, BioType, SubmittedBio},
@@ -8,7 +12,65 @@ use super::{
 };
 use crate::prelude::*;
 
-/// A simple block I/O request queue backed by one internal FIFO queue.
+/// A throttler of the `SubmittedBio`s entering the request queues.
+///
+/// The throttler decides when a `SubmittedBio` can be dispatched and how the
+/// block device is shared among the submitters, e.g., according to the I/O
+/// limits and weights of the cgroup of the submitting task.
+pub trait BioThrottler: Send + Sync {
+    /// Classifies a `SubmittedBio` submitted to the block device of `device_id`.
+    ///
+    /// This method is called in the context of the task that submits the
+    /// `SubmittedBio`. It must not sleep.
+    fn classify(&self, device_id: DeviceId, bio: &SubmittedBio) -> BioClass;
+
+    /// Returns the current time, which is comparable with
+    /// [`BioClass::dispatch_time`].
+    fn now(&self) -> Duration;
+
+    /// Waits on `wait_queue` until `cond` returns `true` or until the current
+    /// time reaches `deadline`.
+    fn wait_until_or_deadline(
+        &self,
+        wait_queue: &WaitQueue,
+        cond: &dyn Fn() -> bool,
+        deadline: Duration,
+    );
+}
+
+/// The class of a `SubmittedBio` given by a [`BioThrottler`].
+#[derive(Clone, Copy, Debug)]
+pub struct BioClass {
+    /// The group of the submitter.
+    ///
+    /// When the block device is busy, the requests of different groups are
+    /// dispatched in proportion to the weights of the groups.
+    pub group: usize,
+    /// The weight of the group.
+    pub weight: u32,
+    /// The earliest time at which the `SubmittedBio` can be dispatched.
+    pub dispatch_time: Duration,
+}
+
+impl BioClass {
+    /// The class of the `SubmittedBio`s when there is no throttler.
+    const UNTHROTTLED: Self = Self {
+        group: 0,
+        weight: 1,
+        dispatch_time: Duration::ZERO,
+    };
+}
+
+static BIO_THROTTLER: Once<&'static dyn BioThrottler> = Once::new();
+
+/// Registers the throttler of the `SubmittedBio`s.
+///
+/// The throttler can only be registered once.
+pub fn register_bio_throttler(throttler: &'static dyn BioThrottler) {
+    BIO_THROTTLER.call_once(|| throttler);
+}
+
+/// A simple block I/O request queue.
 ///
 /// It is a FIFO producer-consumer queue, where the producer (e.g., filesystem)
 /// submits requests to the queue, and the consumer (e.g., block device driver)
@@ -16,24 +78,39 @@ use crate::prelude::*;
 ///
 /// It supports merging the new request with the front request if if the type
 /// is same and the sector range is contiguous.
+///
+/// If a [`BioThrottler`] is registered, the `SubmittedBio`s are queued per
+/// group. The requests of each group are consumed in the FIFO order, while the
+/// groups are served in proportion to their weights. A `SubmittedBio` that
+/// cannot be dispatched yet is held in this queue until its dispatch time,
+/// so the producer never sleeps for throttling.
 pub struct BioRequestSingleQueue {
-    queue: Mutex<VecDeque<BioRequest>>,
+    /// The ID of the block device that owns this queue.
+    device_id: DeviceId,
+    queue: Mutex<GroupedQueue>,
     num_requests: AtomicUsize,
+    num_throttled: AtomicUsize,
     wait_queue: WaitQueue,
     max_nr_segments_per_bio: usize,
 }
 
 impl BioRequestSingleQueue {
-    /// Creates an empty queue.
-    pub fn new() -> Self {
-        Self::with_max_nr_segments_per_bio(usize::MAX)
+    /// Creates an empty queue for the block device of `device_id`.
+    pub fn new(device_id: DeviceId) -> Self {
+        Self::with_max_nr_segments_per_bio(device_id, usize::MAX)
     }
 
-    /// Creates an empty queue with the upper bound for the number of segments in a bio.
-    pub fn with_max_nr_segments_per_bio(max_nr_segments_per_bio: usize) -> Self {
+    /// Creates an empty queue for the block device of `device_id` with the upper
+    /// bound for the number of segments in a bio.
+    pub fn with_max_nr_segments_per_bio(
+        device_id: DeviceId,
+        max_nr_segments_per_bio: usize,
+    ) -> Self {
         Self {
-            queue: Mutex::new(VecDeque::new()),
+            device_id,
+            queue: Mutex::new(GroupedQueue::default()),
             num_requests: AtomicUsize::new(0),
+            num_throttled: AtomicUsize::new(0),
             wait_queue: WaitQueue::new(),
             max_nr_segments_per_bio,
         }
@@ -45,34 +122,43 @@ impl BioRequestSingleQueue {
     }
 
     /// Returns the number of requests currently in this queue.
+    ///
+    /// The `SubmittedBio`s that are held for throttling are not counted.
     pub fn num_requests(&self) -> usize {
         self.num_requests.load(Ordering::Relaxed)
     }
 
     /// Enqueues a `SubmittedBio` to this queue.
     ///
-    /// When enqueueing the `SubmittedBio`, try to insert it into the last request if the
-    /// type is same and the sector range is contiguous.
+    /// When enqueueing the `SubmittedBio`, try to insert it into the last request of
+    /// its group if the type is same and the sector range is contiguous.
     /// Otherwise, creates and inserts a new request for the `SubmittedBio`.
     ///
+    /// If the `SubmittedBio` cannot be dispatched yet according to the registered
+    /// [`BioThrottler`], it is held until its dispatch time.
+    ///
     /// This method will wake up the waiter if a new `BioRequest` is enqueued.
     pub fn enqueue(&self, bio: SubmittedBio) -> Result<(), BioEnqueueError> {
         if bio.segments().len() >= self.max_nr_segments_per_bio {
             return Err(BioEnqueueError::TooBig);
         }
 
+        let throttler = BIO_THROTTLER.get();
+        let class = throttler.map_or(BioClass::UNTHROTTLED, |throttler| {
+            throttler.classify(self.device_id, &bio)
+        });
+
         let mut queue = self.queue.lock();
-        if let Some(request) = queue.front_mut()
-            && request.can_merge(&bio)
-            && request.num_segments() + bio.segments().len() <= self.max_nr_segments_per_bio
+        if let Some(throttler) = throttler
+            && class.dispatch_time > throttler.now()
         {
-            request.merge_bio(bio);
+            queue.hold(class, bio);
+            self.num_throttled.fetch_add(1, Ordering::Relaxed);
+        } else if queue.push(class, bio, self.max_nr_segments_per_bio) {
+            self.inc_num_requests();
+        } else {
             return Ok(());
         }
-
-        let new_request = BioRequest::from(bio);
-        queue.push_front(new_request);
-        self.inc_num_requests();
         drop(queue);
 
         self.wait_queue.wake_all();
@@ -83,25 +169,45 @@ impl BioRequestSingleQueue {
     ///
     /// This method will wait until one request can be retrieved.
     pub fn dequeue(&self) -> BioRequest {
-        let mut num_requests = self.num_requests();
-
         loop {
-            if num_requests > 0 {
+            let num_throttled = self.num_throttled.load(Ordering::Relaxed);
+            let next_dispatch_time = {
                 let mut queue = self.queue.lock();
-                if let Some(request) = queue.pop_back() {
+                if num_throttled > 0
+                    && let Some(throttler) = BIO_THROTTLER.get()
+                {
+                    self.dispatch_throttled(&mut queue, throttler.now());
+                }
+                if let Some(request) = queue.pop() {
                     self.dec_num_requests();
                     return request;
                 }
+                queue.next_dispatch_time()
+            };
+
+            // Wake up if a new request is enqueued or a new `SubmittedBio` is held,
+            // which may have an earlier dispatch time.
+            let cond = || {
+                self.num_requests() > 0
+                    || self.num_throttled.load(Ordering::Relaxed) != num_throttled
+            };
+            match (next_dispatch_time, BIO_THROTTLER.get()) {
+                (Some(deadline), Some(throttler)) => {
+                    throttler.wait_until_or_deadline(&self.wait_queue, &cond, deadline)
+                }
+                _ => self.wait_queue.wait_until(|| cond().then_some(())),
             }
+        }
+    }
 
-            num_requests = self.wait_queue.wait_until(|| {
-                let num_requests = self.num_requests();
-                if num_requests > 0 {
-                    Some(num_requests)
-                } else {
-                    None
-                }
-            });
+    /// Moves the held `SubmittedBio`s whose dispatch times have been reached
+    /// to their groups.
+    fn dispatch_throttled(&self, queue: &mut GroupedQueue, now: Duration) {
+        while let Some((class, bio)) = queue.unhold(now) {
+            self.num_throttled.fetch_sub(1, Ordering::Relaxed);
+            if queue.push(class, bio, self.max_nr_segments_per_bio) {
+                self.inc_num_requests();
+            }
         }
     }
 
@@ -114,21 +220,122 @@ impl BioRequestSingleQueue {
     }
 }
 
-impl Default for BioRequestSingleQueue {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 impl Debug for BioRequestSingleQueue {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("BioRequestSingleQueue")
+            .field("device_id", &self.device_id)
             .field("num_requests", &self.num_requests())
+            .field("num_throttled", &self.num_throttled.load(Ordering::Relaxed))
             .field("queue", &self.queue.lock())
             .finish()
     }
 }
 
+/// The requests in a [`BioRequestSingleQueue`], grouped by [`BioClass::group`].
+///
+/// The groups are served by their virtual times. Dispatching a request of a
+/// group advances the virtual time of the group by the number of sectors of the
+/// request divided by the weight of the group. The group with the smallest
+/// virtual time is served first.
+#[derive(Debug, Default)]
+struct GroupedQueue {
+    /// The groups that have requests.
+    groups: BTreeMap<usize, RequestGroup>,
+    /// The virtual time of the last served group.
+    ///
+    /// A group that becomes active starts from this time, so it cannot claim
+    /// the share of the device that it did not use while being idle.
+    vtime: u64,
+    /// The `SubmittedBio`s held for throttling, ordered by their dispatch times
+    /// and then by their arrival.
+    throttled: BTreeMap<(Duration, u64), (BioClass, SubmittedBio)>,
+    /// The sequence number of the next held `SubmittedBio`.
+    next_seq: u64,
+}
+
+/// The requests of a group in a [`GroupedQueue`].
+#[derive(Debug)]
+struct RequestGroup {
+    weight: u32,
+    vtime: u64,
+    requests: VecDeque<BioRequest>,
+}
+
+/// The scale of virtual times, so that heavy weights still advance them.
+const VTIME_SCALE: u64 = 1 << 16;
+
+impl GroupedQueue {
+    /// Pushes a `SubmittedBio` to its group.
+    ///
+    /// Returns whether a new request is created for the `SubmittedBio`, instead of
+    /// merging it into the last request.
+    fn push(&mut self, class: BioClass, bio: SubmittedBio, max_nr_segments_per_bio: usize) -> bool {
+        let vtime = self.vtime;
+        let weight = class.weight.max(1);
+        let group = self
+            .groups
+            .entry(class.group)
+            .or_insert_with(|| RequestGroup {
+                weight,
+                vtime,
+                requests: VecDeque::new(),
+            });
+        group.weight = weight;
+
+        if let Some(request) = group.requests.front_mut()
+            && request.can_merge(&bio)
+            && request.num_segments() + bio.segments().len() <= max_nr_segments_per_bio
+        {
+            request.merge_bio(bio);
+            return false;
+        }
+
+        group.requests.push_front(BioRequest::from(bio));
+        true
+    }
+
+    /// Pops the first request of the group with the smallest virtual time.
+    fn pop(&mut self) -> Option<BioRequest> {
+        let (&group_id, group) = self
+            .groups
+            .iter_mut()
+            .min_by_key(|(_, group)| group.vtime)?;
+
+        let request = group.requests.pop_back().unwrap();
+        self.vtime = group.vtime;
+        group.vtime += request.num_sectors() as u64 * VTIME_SCALE / group.weight as u64;
+        if group.requests.is_empty() {
+            self.groups.remove(&group_id);
+        }
+
+        Some(request)
+    }
+
+    /// Holds a `SubmittedBio` until its dispatch time.
+    fn hold(&mut self, class: BioClass, bio: SubmittedBio) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.throttled
+            .insert((class.dispatch_time, seq), (class, bio));
+    }
+
+    /// Takes a held `SubmittedBio` whose dispatch time is not after `now`.
+    fn unhold(&mut self, now: Duration) -> Option<(BioClass, SubmittedBio)> {
+        let entry = self.throttled.first_entry()?;
+        if entry.key().0 > now {
+            return None;
+        }
+        Some(entry.remove())
+    }
+
+    /// Returns the earliest dispatch time of the held `SubmittedBio`s.
+    fn next_dispatch_time(&self) -> Option<Duration> {
+        self.throttled
+            .first_key_value()
+            .map(|((dispatch_time, _), _)| *dispatch_time)
+    }
+}
+
 /// A block I/O request dequeued from [`BioRequestSingleQueue`].
 ///
 /// This `BioRequest` type is more friendly to storage medium than `SubmittedBio` for two reasons.
@@ -106,7 +106,7 @@ impl NvmeBlockDevice {
 
         let block_device = Arc::new(Self {
             device,
-            queue: BioRequestSingleQueue::new(),
+            queue: BioRequestSingleQueue::new(id),
             name,
             id,
         });
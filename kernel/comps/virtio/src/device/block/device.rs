@@ -97,6 +97,7 @@ impl BlockDevice {
             // Each bio request includes an additional 1 request and 1 response descriptor,
             // therefore this upper bound is set to (QUEUE_SIZE - 2).
             queue: BioRequestSingleQueue::with_max_nr_segments_per_bio(
+                id,
                 (DeviceInner::QUEUE_SIZE - 2) as usize,
             ),
             id,
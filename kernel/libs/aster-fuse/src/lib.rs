@@ -37,6 +37,7 @@ pub use self::{
     ids::{FuseFileHandle, FuseGeneration, FuseNodeId, FuseUnique, LookupCount},
     operation::{FuseOpcode, FuseOperation, ReplyExpectation},
     ops::{
+        copy_file_range::{CopyFileRangeOperation, CopyFileRangeReq},
         create::{CreateOperation, CreateReq},
         forget::{ForgetOperation, ForgetReq},
         getattr::{FuseAttrReply, GetattrFlags, GetattrOperation, GetattrReq},
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! `FUSE_COPY_FILE_RANGE` copies a byte range between two open file handles
+//! sitting at server-side.
+//!
+//! The request body contains [`CopyFileRangeReq`] and is sent to the source
+//! inode. The reply body contains [`WriteReply`], and the operation returns the
+//! number of bytes copied by the server.
+
+use ostd::mm::{Infallible, VmReader, VmWriter};
+
+use super::{util::read_payload, write::WriteReply};
+use crate::{
+    FuseError, FuseFileHandle, FuseNodeId, FuseOpcode, FuseOperation, FuseResult, ReplyExpectation,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CopyFileRangeReq {
+    /// File handle to copy from.
+    fh_in: FuseFileHandle,
+    /// File offset to start reading at.
+    offset_in: u64,
+    /// Inode to copy to.
+    nodeid_out: FuseNodeId,
+    /// File handle to copy to.
+    fh_out: FuseFileHandle,
+    /// File offset to start writing at.
+    offset_out: u64,
+    /// Number of bytes to copy.
+    len: u64,
+    /// Flags passed to `copy_file_range`, which must be zero for now.
+    flags: u64,
+}
+
+impl CopyFileRangeReq {
+    pub const fn new(
+        fh_in: FuseFileHandle,
+        offset_in: u64,
+        nodeid_out: FuseNodeId,
+        fh_out: FuseFileHandle,
+        offset_out: u64,
+        len: u64,
+    ) -> Self {
+        Self {
+            fh_in,
+            offset_in,
+            nodeid_out,
+            fh_out,
+            offset_out,
+            len,
+            flags: 0,
+        }
+    }
+}
+
+pub struct CopyFileRangeOperation {
+    copy_file_range_req: CopyFileRangeReq,
+}
+
+impl CopyFileRangeOperation {
+    pub fn new(copy_file_range_req: CopyFileRangeReq) -> Self {
+        Self {
+            copy_file_range_req,
+        }
+    }
+}
+
+impl FuseOperation for CopyFileRangeOperation {
+    type Output = WriteReply;
+
+    fn opcode(&self) -> FuseOpcode {
+        FuseOpcode::CopyFileRange
+    }
+
+    fn body_len(&self) -> usize {
+        size_of::<CopyFileRangeReq>()
+    }
+
+    fn write_body(&mut self, writer: &mut VmWriter<'_, Infallible>) -> FuseResult<()> {
+        writer
+            .write_val(&self.copy_file_range_req)
+            .map_err(|_| FuseError::BufferTooSmall)
+    }
+
+    fn reply_expectation(&self) -> ReplyExpectation {
+        ReplyExpectation::payload(size_of::<WriteReply>())
+    }
+
+    fn parse_reply(
+        payload_len: usize,
+        reader: &mut VmReader<'_, Infallible>,
+    ) -> FuseResult<Self::Output> {
+        read_payload(payload_len, reader)
+    }
+}
@@ -14,6 +14,7 @@
 
 mod util;
 
+pub mod copy_file_range;
 pub mod create;
 pub mod forget;
 pub mod getattr;
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod cpu;
+pub mod ptrace;
 pub mod signal;
 
 pub fn init() {}
@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! RISC-V ptrace ABI.
+
+use ostd::{
+    arch::cpu::context::{FpuContext, GeneralRegs},
+    mm::MAX_USERSPACE_VADDR,
+};
+
+use crate::prelude::*;
+
+/// Mirror of Linux's `struct user_regs_struct` for RISC-V.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/riscv/include/uapi/asm/ptrace.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct CUserRegsStruct {
+    pub pc: usize,
+    pub ra: usize,
+    pub sp: usize,
+    pub gp: usize,
+    pub tp: usize,
+    pub t0: usize,
+    pub t1: usize,
+    pub t2: usize,
+    pub s0: usize,
+    pub s1: usize,
+    pub a0: usize,
+    pub a1: usize,
+    pub a2: usize,
+    pub a3: usize,
+    pub a4: usize,
+    pub a5: usize,
+    pub a6: usize,
+    pub a7: usize,
+    pub s2: usize,
+    pub s3: usize,
+    pub s4: usize,
+    pub s5: usize,
+    pub s6: usize,
+    pub s7: usize,
+    pub s8: usize,
+    pub s9: usize,
+    pub s10: usize,
+    pub s11: usize,
+    pub t3: usize,
+    pub t4: usize,
+    pub t5: usize,
+    pub t6: usize,
+}
+
+impl CUserRegsStruct {
+    /// Builds the register snapshot from saved register state.
+    pub fn from_regs(regs: &GeneralRegs, pc: usize) -> Self {
+        Self {
+            pc,
+            ra: regs.ra,
+            sp: regs.sp,
+            gp: regs.gp,
+            tp: regs.tp,
+            t0: regs.t0,
+            t1: regs.t1,
+            t2: regs.t2,
+            s0: regs.s0,
+            s1: regs.s1,
+            a0: regs.a0,
+            a1: regs.a1,
+            a2: regs.a2,
+            a3: regs.a3,
+            a4: regs.a4,
+            a5: regs.a5,
+            a6: regs.a6,
+            a7: regs.a7,
+            s2: regs.s2,
+            s3: regs.s3,
+            s4: regs.s4,
+            s5: regs.s5,
+            s6: regs.s6,
+            s7: regs.s7,
+            s8: regs.s8,
+            s9: regs.s9,
+            s10: regs.s10,
+            s11: regs.s11,
+            t3: regs.t3,
+            t4: regs.t4,
+            t5: regs.t5,
+            t6: regs.t6,
+        }
+    }
+
+    /// Validates the user-supplied `regs` and then applies it into saved register state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EIO` on any invalid value.
+    pub fn apply_to(&self, regs: &mut GeneralRegs, pc: &mut usize) -> Result<()> {
+        // This is more strict than Linux.
+        if self.pc >= MAX_USERSPACE_VADDR {
+            return_errno_with_message!(Errno::EIO, "invalid register value");
+        }
+
+        *pc = self.pc;
+        *regs = GeneralRegs {
+            zero: 0,
+            ra: self.ra,
+            sp: self.sp,
+            gp: self.gp,
+            tp: self.tp,
+            t0: self.t0,
+            t1: self.t1,
+            t2: self.t2,
+            s0: self.s0,
+            s1: self.s1,
+            a0: self.a0,
+            a1: self.a1,
+            a2: self.a2,
+            a3: self.a3,
+            a4: self.a4,
+            a5: self.a5,
+            a6: self.a6,
+            a7: self.a7,
+            s2: self.s2,
+            s3: self.s3,
+            s4: self.s4,
+            s5: self.s5,
+            s6: self.s6,
+            s7: self.s7,
+            s8: self.s8,
+            s9: self.s9,
+            s10: self.s10,
+            s11: self.s11,
+            t3: self.t3,
+            t4: self.t4,
+            t5: self.t5,
+            t6: self.t6,
+        };
+        Ok(())
+    }
+}
+
+/// Mirror of Linux's `struct __riscv_d_ext_state`.
+///
+/// Linux reports the floating-point registers in this layout
+/// if the D extension is available.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/riscv/include/uapi/asm/ptrace.h>
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct CUserFpregsStruct {
+    pub f: [u64; 32],
+    pub fcsr: u32,
+}
+
+impl CUserFpregsStruct {
+    /// Builds the floating-point register snapshot from saved FPU state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EOPNOTSUPP` if the FPU state is not in the D extension layout.
+    pub fn from_fpu(fpu: &FpuContext) -> Result<Self> {
+        let FpuContext::D(_) = fpu else {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "only the D extension floating-point registers are supported"
+            );
+        };
+
+        Ok(Self::from_first_bytes(fpu.as_bytes()))
+    }
+
+    /// Applies the user-supplied `fpregs` into saved FPU state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EOPNOTSUPP` if the FPU state is not in the D extension layout.
+    pub fn apply_to(&self, fpu: &mut FpuContext) -> Result<()> {
+        let FpuContext::D(_) = fpu else {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "only the D extension floating-point registers are supported"
+            );
+        };
+
+        // Copy the registers but not the trailing padding bytes.
+        let len = core::mem::offset_of!(Self, fcsr) + size_of::<u32>();
+        fpu.as_bytes_mut()[..len].copy_from_slice(&self.as_bytes()[..len]);
+        Ok(())
+    }
+}
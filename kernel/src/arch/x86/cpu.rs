@@ -9,6 +9,7 @@ use ostd::{
             context::{CpuException, PageFaultErrorCode, RawPageFaultInfo, UserContext},
             cpuid::cpuid,
         },
+        trap::{USER_SS_VALUE, USER32_CS_VALUE},
         tsc_freq,
     },
     cpu::{PinCurrentCpu, num_cpus},
@@ -24,6 +25,10 @@ use crate::{
 
 impl LinuxAbi for UserContext {
     fn syscall_num(&self) -> usize {
+        if self.is_ia32_syscall() {
+            return self.rax() as u32 as usize;
+        }
+
         self.rax()
     }
 
@@ -36,6 +41,20 @@ impl LinuxAbi for UserContext {
     }
 
     fn syscall_args(&self) -> [usize; 6] {
+        // The 32-bit system calls issued by `int 0x80` pass the arguments in different registers,
+        // of which only the low 32 bits are meaningful.
+        if self.is_ia32_syscall() {
+            return [
+                self.rbx(),
+                self.rcx(),
+                self.rdx(),
+                self.rsi(),
+                self.rdi(),
+                self.rbp(),
+            ]
+            .map(|arg| arg as u32 as usize);
+        }
+
         [
             self.rdi(),
             self.rsi(),
@@ -132,6 +151,114 @@ impl SigContext {
     }
 }
 
+/// Represents the context of a signal handler for 32-bit programs.
+///
+/// This is the 32-bit version of [`SigContext`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/uapi/asm/sigcontext.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct SigContext32 {
+    gs: u16,
+    __gsh: u16,
+    fs: u16,
+    __fsh: u16,
+    es: u16,
+    __esh: u16,
+    ds: u16,
+    __dsh: u16,
+    edi: u32,
+    esi: u32,
+    ebp: u32,
+    esp: u32,
+    ebx: u32,
+    edx: u32,
+    ecx: u32,
+    eax: u32,
+    trap_num: u32,
+    error_code: u32,
+    eip: u32,
+    cs: u16,
+    __csh: u16,
+    eflags: u32,
+    esp_at_signal: u32,
+    ss: u16,
+    __ssh: u16,
+    // A stack pointer to FPU context.
+    fpu_context_addr: u32,
+    old_mask: u32,
+    page_fault_addr: u32,
+}
+
+impl SigContext32 {
+    pub fn copy_user_regs_to(&self, dst: &mut UserContext) {
+        // The flags that can be modified by the user space.
+        //
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/kernel/signal_32.c>
+        const FIX_EFLAGS: usize = (1 << 18) // AC
+            | (1 << 16) // RF
+            | (1 << 11) // OF
+            | (1 << 10) // DF
+            | (1 << 8) // TF
+            | (1 << 7) // SF
+            | (1 << 6) // ZF
+            | (1 << 4) // AF
+            | (1 << 2) // PF
+            | (1 << 0); // CF
+
+        let gp_regs = dst.general_regs_mut();
+        gp_regs.rax = self.eax as usize;
+        gp_regs.rbx = self.ebx as usize;
+        gp_regs.rcx = self.ecx as usize;
+        gp_regs.rdx = self.edx as usize;
+        gp_regs.rsi = self.esi as usize;
+        gp_regs.rdi = self.edi as usize;
+        gp_regs.rbp = self.ebp as usize;
+        gp_regs.rsp = self.esp as usize;
+        gp_regs.rip = self.eip as usize;
+        gp_regs.rflags = (gp_regs.rflags & !FIX_EFLAGS) | (self.eflags as usize & FIX_EFLAGS);
+    }
+
+    pub fn copy_user_regs_from(&mut self, src: &UserContext) {
+        let gp_regs = src.general_regs();
+        self.eax = gp_regs.rax as u32;
+        self.ebx = gp_regs.rbx as u32;
+        self.ecx = gp_regs.rcx as u32;
+        self.edx = gp_regs.rdx as u32;
+        self.esi = gp_regs.rsi as u32;
+        self.edi = gp_regs.rdi as u32;
+        self.ebp = gp_regs.rbp as u32;
+        self.esp = gp_regs.rsp as u32;
+        self.esp_at_signal = gp_regs.rsp as u32;
+        self.eip = gp_regs.rip as u32;
+        self.eflags = gp_regs.rflags as u32;
+
+        // The data segment selectors are not tracked, so they are reported as flat segments.
+        self.cs = USER32_CS_VALUE as u16;
+        self.ss = USER_SS_VALUE as u16;
+        self.ds = USER_SS_VALUE as u16;
+        self.es = USER_SS_VALUE as u16;
+
+        // TODO: Fill exception information in `SigContext32`.
+    }
+
+    pub fn fpu_context_addr(&self) -> Vaddr {
+        self.fpu_context_addr as Vaddr
+    }
+
+    pub fn set_fpu_context_addr(&mut self, addr: Vaddr) {
+        self.fpu_context_addr = addr as u32;
+    }
+
+    pub fn old_mask(&self) -> u32 {
+        self.old_mask
+    }
+
+    pub fn set_old_mask(&mut self, old_mask: u32) {
+        self.old_mask = old_mask;
+    }
+}
+
 impl From<&RawPageFaultInfo> for PageFaultInfo {
     fn from(raw_info: &RawPageFaultInfo) -> Self {
         let required_perms = if raw_info
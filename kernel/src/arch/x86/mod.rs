@@ -4,6 +4,8 @@ pub mod cpu;
 mod power;
 pub mod ptrace;
 pub mod signal;
+pub mod tls;
+pub mod vdso32;
 
 pub fn init() {
     power::init();
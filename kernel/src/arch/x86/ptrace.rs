@@ -6,7 +6,7 @@ use core::ops::Range;
 
 use ostd::{
     arch::{
-        cpu::context::{FsBase, GeneralRegs, GsBase},
+        cpu::context::{FpuContext, FsBase, GeneralRegs, GsBase},
         trap::{USER_CS_VALUE, USER_SS_VALUE},
     },
     mm::MAX_USERSPACE_VADDR,
@@ -120,6 +120,80 @@ impl CUserRegsStruct {
     }
 }
 
+/// Mirror of Linux's `struct user_i387_struct` for x86-64.
+///
+/// This is the layout of the legacy region of the FXSAVE area.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/asm/user_64.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CUserFpregsStruct {
+    pub cwd: u16,
+    pub swd: u16,
+    pub ftw: u16,
+    pub fop: u16,
+    pub rip: u64,
+    pub rdp: u64,
+    pub mxcsr: u32,
+    pub mxcr_mask: u32,
+    pub st_space: [u32; 32],
+    pub xmm_space: [u32; 64],
+    pub padding: [u32; 24],
+}
+
+const _: () = {
+    assert!(size_of::<CUserFpregsStruct>() == FXSAVE_AREA_SIZE);
+};
+
+impl CUserFpregsStruct {
+    /// Builds the floating-point register snapshot from saved FPU state.
+    pub fn from_fpu(fpu: &FpuContext) -> Self {
+        Self::from_first_bytes(fpu.as_bytes())
+    }
+
+    /// Validates the user-supplied `fpregs` and then applies it into saved FPU state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EINVAL` if `mxcsr` contains reserved bits.
+    pub fn apply_to(&self, fpu: &mut FpuContext) -> Result<()> {
+        let current = Self::from_fpu(fpu);
+        let mxcsr_mask = match current.mxcr_mask {
+            0 => DEFAULT_MXCSR_MASK,
+            mask => mask,
+        };
+        if self.mxcsr & !mxcsr_mask != 0 {
+            return_errno_with_message!(Errno::EINVAL, "invalid MXCSR value");
+        }
+
+        let mut new_fpregs = *self;
+        new_fpregs.mxcr_mask = current.mxcr_mask;
+
+        let bytes = fpu.as_bytes_mut();
+        bytes[..FXSAVE_AREA_SIZE].copy_from_slice(new_fpregs.as_bytes());
+        // Mark the x87 and SSE states as present, so that XRSTOR does not reset them to their
+        // initial values.
+        if bytes.len() > FXSAVE_AREA_SIZE {
+            let xstate_bv = &mut bytes[FXSAVE_AREA_SIZE..FXSAVE_AREA_SIZE + size_of::<u64>()];
+            let value = u64::from_ne_bytes(xstate_bv.try_into().unwrap()) | XFEATURE_MASK_FPSSE;
+            xstate_bv.copy_from_slice(&value.to_ne_bytes());
+        }
+
+        Ok(())
+    }
+}
+
+/// The size of the legacy region of the FXSAVE area.
+const FXSAVE_AREA_SIZE: usize = 512;
+
+/// The MXCSR mask to use if the processor reports zero.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/kernel/fpu/init.c#L116-L130>
+const DEFAULT_MXCSR_MASK: u32 = 0x0000_ffbf;
+
+/// The `XSTATE_BV` bits of the x87 and SSE states.
+const XFEATURE_MASK_FPSSE: u64 = 0b11;
+
 /// Reads one word from the x86-64 USER area at `offset`.
 pub fn read_user_word(
     general_regs: &GeneralRegs,
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Thread-local storage (TLS) segments of 32-bit programs.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/kernel/tls.c>
+
+use ostd::arch::cpu::context::TlsEntries;
+
+use crate::prelude::*;
+
+/// A segment descriptor in the user-space format (`struct user_desc`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct UserDesc {
+    pub entry_number: u32,
+    pub base_addr: u32,
+    pub limit: u32,
+    flags: u32,
+}
+
+// The bit fields in `UserDesc::flags`.
+const SEG_32BIT: u32 = 1 << 0;
+const CONTENTS_SHIFT: u32 = 1;
+const CONTENTS_MASK: u32 = 3 << CONTENTS_SHIFT;
+const READ_EXEC_ONLY: u32 = 1 << 3;
+const LIMIT_IN_PAGES: u32 = 1 << 4;
+const SEG_NOT_PRESENT: u32 = 1 << 5;
+const USEABLE: u32 = 1 << 6;
+const LM: u32 = 1 << 7;
+
+// The bit fields in segment descriptors.
+const DESC_LIMIT0_MASK: u64 = 0xffff;
+const DESC_BASE0_SHIFT: u32 = 16;
+const DESC_TYPE_SHIFT: u32 = 40;
+const DESC_TYPE_ACCESSED: u64 = 1 << 40;
+const DESC_TYPE_WRITABLE: u64 = 1 << 41;
+const DESC_S: u64 = 1 << 44;
+const DESC_DPL3: u64 = 3 << 45;
+const DESC_P: u64 = 1 << 47;
+const DESC_LIMIT1_SHIFT: u32 = 48;
+const DESC_AVL: u64 = 1 << 52;
+const DESC_L: u64 = 1 << 53;
+const DESC_D: u64 = 1 << 54;
+const DESC_G: u64 = 1 << 55;
+const DESC_BASE2_SHIFT: u32 = 56;
+
+/// The entry number that asks the kernel to allocate a free entry.
+const ENTRY_NUMBER_ALLOCATE: u32 = u32::MAX;
+
+impl UserDesc {
+    /// Installs the segment descriptor into the TLS entries.
+    ///
+    /// If the entry number is `-1` and `can_allocate` is true, a free entry will be allocated and
+    /// the entry number will be updated accordingly.
+    pub fn install(&mut self, tls_entries: &mut TlsEntries, can_allocate: bool) -> Result<()> {
+        if !self.is_valid() {
+            return_errno_with_message!(Errno::EINVAL, "the segment descriptor is invalid");
+        }
+
+        if self.entry_number == ENTRY_NUMBER_ALLOCATE && can_allocate {
+            let Some(index) = (0..TlsEntries::LEN).find(|&index| tls_entries.get(index) == 0)
+            else {
+                return_errno_with_message!(Errno::ESRCH, "there are no free TLS entries");
+            };
+            self.entry_number = (TlsEntries::FIRST_INDEX + index) as u32;
+        }
+
+        let index = tls_index(self.entry_number)?;
+        tls_entries.set(index, self.to_desc());
+
+        Ok(())
+    }
+
+    /// Reads the segment descriptor from the TLS entry of `entry_number`.
+    pub fn read_from(tls_entries: &TlsEntries, entry_number: u32) -> Result<Self> {
+        let desc = tls_entries.get(tls_index(entry_number)?);
+
+        // An empty entry is reported as an empty descriptor, whose fields are zeros except for
+        // the entry number.
+        if desc == 0 {
+            return Ok(Self {
+                entry_number,
+                ..Default::default()
+            });
+        }
+
+        let base_addr =
+            ((desc >> DESC_BASE0_SHIFT) & 0xff_ffff) | (((desc >> DESC_BASE2_SHIFT) & 0xff) << 24);
+        let limit = (desc & DESC_LIMIT0_MASK) | (((desc >> DESC_LIMIT1_SHIFT) & 0xf) << 16);
+        let type_ = ((desc >> DESC_TYPE_SHIFT) & 0xf) as u32;
+
+        let mut flags = (type_ >> 2) << CONTENTS_SHIFT;
+        let bits = [
+            (desc & DESC_D != 0, SEG_32BIT),
+            (desc & DESC_TYPE_WRITABLE == 0, READ_EXEC_ONLY),
+            (desc & DESC_G != 0, LIMIT_IN_PAGES),
+            (desc & DESC_P == 0, SEG_NOT_PRESENT),
+            (desc & DESC_AVL != 0, USEABLE),
+            (desc & DESC_L != 0, LM),
+        ];
+        for (is_set, bit) in bits {
+            if is_set {
+                flags |= bit;
+            }
+        }
+
+        Ok(Self {
+            entry_number,
+            base_addr: base_addr as u32,
+            limit: limit as u32,
+            flags,
+        })
+    }
+
+    /// Returns whether the descriptor clears the entry.
+    fn is_empty(&self) -> bool {
+        // Linux treats both an all-zero descriptor and the descriptor below as empty ones.
+        const EMPTY_FLAGS: u32 = READ_EXEC_ONLY | SEG_NOT_PRESENT;
+
+        self.base_addr == 0 && self.limit == 0 && (self.flags == 0 || self.flags == EMPTY_FLAGS)
+    }
+
+    /// Returns whether the descriptor can be installed into the TLS entries.
+    fn is_valid(&self) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        // Only 32-bit and present data segments are allowed.
+        self.flags & SEG_32BIT != 0
+            && (self.flags & CONTENTS_MASK) >> CONTENTS_SHIFT <= 1
+            && self.flags & SEG_NOT_PRESENT == 0
+    }
+
+    /// Converts the descriptor to the hardware format.
+    fn to_desc(&self) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let base_addr = self.base_addr as u64;
+        let limit = self.limit as u64;
+        let contents = ((self.flags & CONTENTS_MASK) >> CONTENTS_SHIFT) as u64;
+
+        let mut desc = (limit & DESC_LIMIT0_MASK)
+            | ((base_addr & 0xff_ffff) << DESC_BASE0_SHIFT)
+            | (((limit >> 16) & 0xf) << DESC_LIMIT1_SHIFT)
+            | ((base_addr >> 24) << DESC_BASE2_SHIFT)
+            | (contents << (DESC_TYPE_SHIFT + 2))
+            // Set the accessed bit so that the segment can be mapped as read-only.
+            | DESC_TYPE_ACCESSED
+            | DESC_S
+            | DESC_DPL3;
+        let bits = [
+            (self.flags & READ_EXEC_ONLY == 0, DESC_TYPE_WRITABLE),
+            (self.flags & SEG_NOT_PRESENT == 0, DESC_P),
+            (self.flags & USEABLE != 0, DESC_AVL),
+            (self.flags & SEG_32BIT != 0, DESC_D),
+            (self.flags & LIMIT_IN_PAGES != 0, DESC_G),
+        ];
+        for (is_set, bit) in bits {
+            if is_set {
+                desc |= bit;
+            }
+        }
+
+        desc
+    }
+}
+
+/// Converts the entry number to the index of the TLS entries.
+fn tls_index(entry_number: u32) -> Result<usize> {
+    let entry_number = entry_number as usize;
+    if !(TlsEntries::FIRST_INDEX..TlsEntries::FIRST_INDEX + TlsEntries::LEN).contains(&entry_number)
+    {
+        return_errno_with_message!(Errno::EINVAL, "the TLS entry number is invalid");
+    }
+
+    Ok(entry_number - TlsEntries::FIRST_INDEX)
+}
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The vDSO of 32-bit programs.
+//!
+//! Unlike the vDSO of 64-bit programs, this is not an ELF image. It only contains the code of
+//! `__kernel_vsyscall`, whose address is passed to 32-bit programs as `AT_SYSINFO`. The C library
+//! calls `__kernel_vsyscall` to issue system calls with the `sysenter` instruction if the CPU
+//! supports it, or with the `int 0x80` instruction otherwise.
+//!
+//! The `sysenter` instruction saves neither the user instruction pointer nor the user stack
+//! pointer. So, like Linux, `__kernel_vsyscall` saves the registers that it clobbers on the user
+//! stack and passes the user stack pointer in `ebp`. The system call always returns to the landing
+//! pad after the `int 0x80` instruction, which restores the registers and returns to the caller.
+//! Restarting the system call then executes the `int 0x80` instruction.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/entry/vdso/vdso32/system_call.S>
+
+use core::{arch::x86_64::CpuidResult, ops::Range};
+
+use ostd::{
+    arch::cpu::{context::UserContext, cpuid::cpuid},
+    mm::VmIo,
+};
+use spin::Once;
+
+use crate::{
+    cpu::LinuxAbi,
+    prelude::*,
+    vm::{
+        page_cache::{Vmo, VmoOptions},
+        perms::VmPerms,
+        vmar::Vmar,
+    },
+};
+
+/// The code of `__kernel_vsyscall`.
+const KERNEL_VSYSCALL: [u8; 13] = [
+    0x51, // push ecx
+    0x52, // push edx
+    0x55, // push ebp
+    0x89, 0xe5, // mov ebp, esp
+    0x0f, 0x34, // sysenter
+    0xcd, 0x80, // int 0x80
+    0x5d, // pop ebp
+    0x5a, // pop edx
+    0x59, // pop ecx
+    0xc3, // ret
+];
+
+/// The range of `mov ebp, esp; sysenter` in [`KERNEL_VSYSCALL`].
+///
+/// The instructions are replaced with `nop`s if the CPU does not support the `sysenter`
+/// instruction in the 32-bit compatibility mode.
+const SYSENTER_SEQUENCE: Range<usize> = 3..7;
+
+/// The offset of the landing pad, which follows the `int 0x80` instruction.
+const INT80_LANDING_PAD_OFFSET: usize = 9;
+
+static VDSO32_VMO: Once<Arc<Vmo>> = Once::new();
+
+/// Maps the vDSO of 32-bit programs to `vmar`.
+///
+/// Returns the base address of the vDSO, which is also the address of `__kernel_vsyscall`.
+pub fn map_vdso32_to_vmar(vmar: &Vmar) -> Result<Vaddr> {
+    let vmo = VDSO32_VMO.call_once(|| {
+        let mut code = KERNEL_VSYSCALL;
+        if !has_sysenter32() {
+            code[SYSENTER_SEQUENCE].fill(0x90);
+        }
+
+        let vmo = VmoOptions::new(PAGE_SIZE).alloc().unwrap();
+        vmo.write_bytes(0, &code).unwrap();
+        vmo
+    });
+
+    let vdso32_base = vmar
+        .new_map(PAGE_SIZE, VmPerms::READ | VmPerms::EXEC)?
+        .vmo(vmo.clone())
+        .build()?;
+
+    Ok(vdso32_base)
+}
+
+/// Prepares the user context of a 32-bit system call for being handled.
+///
+/// If the system call is issued by the `sysenter` instruction, the user stack pointer is taken
+/// from `ebp`, and the original `ebp`, which is the sixth argument, is read from the user stack.
+/// The instruction pointer is set to the landing pad of `__kernel_vsyscall`.
+///
+/// Returns whether the system call should be handled. Otherwise, the return value of the system
+/// call has been set.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/entry/syscall_32.c>
+pub fn prepare_ia32_syscall(ctx: &Context, user_ctx: &mut UserContext) -> bool {
+    if !user_ctx.is_ia32_sysenter() {
+        return true;
+    }
+
+    let vdso32_base = ctx.user_space().vmar().process_vm().vdso32_base();
+    let gp_regs = user_ctx.general_regs_mut();
+    gp_regs.rsp = gp_regs.rbp as u32 as usize;
+    gp_regs.rip = vdso32_base + INT80_LANDING_PAD_OFFSET;
+
+    match ctx.user_space().read_val::<u32>(gp_regs.rsp) {
+        Ok(ebp) => {
+            gp_regs.rbp = ebp as usize;
+            true
+        }
+        Err(err) => {
+            user_ctx.set_syscall_ret(-(err.error() as i32) as usize);
+            false
+        }
+    }
+}
+
+/// Returns whether the CPU supports the `sysenter` instruction in the 32-bit compatibility mode.
+///
+/// AMD CPUs support the instruction only in the legacy mode. Linux uses the `syscall` instruction
+/// on them instead, which is not supported here (see `syscall32_entry` in OSTD).
+fn has_sysenter32() -> bool {
+    let Some(CpuidResult { ebx, ecx, edx, .. }) = cpuid(0, 0) else {
+        return false;
+    };
+
+    let vendor = [ebx.to_le_bytes(), edx.to_le_bytes(), ecx.to_le_bytes()];
+    vendor.as_flattened() == b"GenuineIntel"
+}
@@ -15,7 +15,7 @@ use crate::{
     prelude::*,
     process::signal::Pollable,
     util::ioctl::RawIoctl,
-    vm::page_cache::Vmo,
+    vm::{hugetlb::HugetlbFile, page_cache::Vmo},
 };
 
 /// The basic operations defined on a file
@@ -213,4 +213,6 @@ pub enum Mappable {
     Vmo(Arc<Vmo>),
     /// An MMIO region.
     IoMem(IoMem),
+    /// Huge pages from the hugetlb pool (i.e., a hugetlbfs file).
+    Hugetlb(Arc<HugetlbFile>),
 }
@@ -177,6 +177,21 @@ impl FileTable {
         (self.table.put(entry) as RawFileDesc).try_into().unwrap()
     }
 
+    /// Inserts `item` onto the exact descriptor number `fd`.
+    ///
+    /// The file previously at `fd`, if any, is closed and returned.
+    pub fn insert_at(
+        &mut self,
+        fd: FileDesc,
+        item: Arc<dyn FileLike>,
+        flags: FdFlags,
+    ) -> Option<Arc<dyn FileLike>> {
+        let entry = FileTableEntry::new(item, flags);
+        let closed_file = self.close_file(fd);
+        self.table.put_at(fd.into(), entry);
+        closed_file
+    }
+
     pub fn close_file(&mut self, fd: FileDesc) -> Option<Arc<dyn FileLike>> {
         let removed_entry = self.table.remove(fd.into())?;
         // POSIX record locks are process-associated and Linux drops them when any fd for the inode is
@@ -193,6 +208,20 @@ impl FileTable {
         self.close_files(|entry| entry.flags().contains(FdFlags::CLOEXEC))
     }
 
+    /// Returns the lowest-numbered descriptor that will be available after the files with
+    /// `FD_CLOEXEC` are closed.
+    pub fn min_free_fd_on_exec(&self) -> FileDesc {
+        let min_free_fd = (0..self.len())
+            .find(|&idx| {
+                self.table
+                    .get(idx)
+                    .is_none_or(|entry| entry.flags().contains(FdFlags::CLOEXEC))
+            })
+            .unwrap_or(self.len());
+        // Resource limits guarantee the table never exceeds `i32::MAX` entries.
+        (min_free_fd as RawFileDesc).try_into().unwrap()
+    }
+
     fn close_files<F>(&mut self, should_close: F) -> Vec<Arc<dyn FileLike>>
     where
         F: Fn(&FileTableEntry) -> bool,
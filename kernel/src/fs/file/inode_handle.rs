@@ -24,6 +24,7 @@ use crate::{
     },
     prelude::*,
     process::signal::{PollHandle, Pollable},
+    security::lsm::hooks::{self as lsm_hooks, PathOperation},
     util::ioctl::RawIoctl,
 };
 
@@ -36,6 +37,8 @@ pub struct InodeHandle {
     offset: Mutex<usize>,
     status_flags: AtomicStatusFlags,
     rights: Rights,
+    /// Whether FS events are suppressed for this handle (`FMODE_NONOTIFY` in Linux).
+    is_fs_notify_disabled: bool,
 }
 
 impl InodeHandle {
@@ -62,6 +65,11 @@ impl InodeHandle {
         } else if inode.type_() == InodeType::Dir && access_mode.is_writable() {
             return_errno_with_message!(Errno::EISDIR, "a directory cannot be opened writable");
         } else {
+            lsm_hooks::on_current_path_operation(PathOperation::Open {
+                path: &path,
+                access_mode,
+                is_exec: false,
+            })?;
             let open_file = inode.open(access_mode, status_flags).transpose()?;
             let rights = Rights::from(access_mode);
             (open_file, rights)
@@ -73,9 +81,24 @@ impl InodeHandle {
             offset: Mutex::new(0),
             status_flags: AtomicStatusFlags::new(status_flags),
             rights,
+            is_fs_notify_disabled: false,
         })
     }
 
+    /// Suppresses FS events for this handle.
+    ///
+    /// This is used for the file descriptors that fanotify opens for its event records,
+    /// so that the listener does not generate events by accessing them.
+    pub(in crate::fs) fn disable_fs_notify(mut self) -> Self {
+        self.is_fs_notify_disabled = true;
+        self
+    }
+
+    /// Returns whether FS events are suppressed for this handle.
+    pub(in crate::fs) fn is_fs_notify_disabled(&self) -> bool {
+        self.is_fs_notify_disabled
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
@@ -354,6 +377,8 @@ impl FileLike for InodeHandle {
             // If the inode has a page cache, it is a file-backed mapping and
             // we return the VMO as the mappable object.
             Ok(Mappable::Vmo(page_cache.as_vmo().clone()))
+        } else if let Some(hugetlb_file) = inode.hugetlb_file() {
+            Ok(Mappable::Hugetlb(hugetlb_file))
         } else if let Some(ref open_file) = self.open_file {
             // Otherwise, it is a special file (e.g. device file) and we should
             // return the file-specific mappable object.
@@ -0,0 +1,519 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use aster_util::printer::VmPrinter;
+use inherit_methods_macro::inherit_methods;
+use ostd::task::Task;
+
+use super::{BinfmtMiscFs, REGISTER_INO, ROOT_INO, STATUS_INO, rule::BinfmtRule};
+use crate::{
+    fs::{
+        file::{InodeMode, InodeType, StatusFlags, mkmod},
+        utils::DirentVisitor,
+        vfs::{
+            file_system::{FileSystem, SuperBlock},
+            inode::{Extension, FileOps, Inode, Metadata, RevalidationPolicy},
+        },
+    },
+    prelude::*,
+    process::{Gid, Uid},
+    time::clocks::RealTimeCoarseClock,
+};
+
+/// The root directory of the binfmt_misc file system.
+pub(super) struct RootInode {
+    register: Arc<BinfmtInode>,
+    status: Arc<BinfmtInode>,
+    /// The registered rules, from the oldest to the newest.
+    rules: RwMutex<Vec<Arc<BinfmtInode>>>,
+    common: InodeCommon,
+}
+
+impl RootInode {
+    pub(super) fn new(fs: Weak<BinfmtMiscFs>, sb: &SuperBlock) -> Arc<Self> {
+        let new_file = |kind, ino, mode| {
+            let metadata = Metadata::new_file(ino, mode, PAGE_SIZE, sb.container_dev_id);
+            Arc::new(BinfmtInode {
+                kind,
+                common: InodeCommon::new(metadata, fs.clone()),
+            })
+        };
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+        let register = new_file(BinfmtInodeKind::Register, REGISTER_INO, mkmod!(u+w));
+        let status = new_file(BinfmtInodeKind::Status, STATUS_INO, mkmod!(a+r, u+w));
+
+        let metadata =
+            Metadata::new_dir(ROOT_INO, mkmod!(a+rx, u+w), PAGE_SIZE, sb.container_dev_id);
+        Arc::new(Self {
+            register,
+            status,
+            rules: RwMutex::new(Vec::new()),
+            common: InodeCommon::new(metadata, fs),
+        })
+    }
+
+    /// Finds the newest enabled rule that matches the binary.
+    pub(super) fn find_rule(
+        &self,
+        file_first_page: &[u8],
+        file_name: &str,
+    ) -> Option<Arc<BinfmtRule>> {
+        self.rules.read().iter().rev().find_map(|inode| {
+            let BinfmtInodeKind::Rule { rule, is_enabled } = &inode.kind else {
+                unreachable!();
+            };
+            (is_enabled.load(Ordering::Relaxed) && rule.matches(file_first_page, file_name))
+                .then(|| rule.clone())
+        })
+    }
+
+    fn register_rule(&self, rule: BinfmtRule) -> Result<()> {
+        let fs = self.common.fs.upgrade().unwrap();
+
+        let mut rules = self.rules.write();
+        if rules
+            .iter()
+            .any(|inode| inode.rule_name() == Some(rule.name()))
+        {
+            return_errno_with_message!(Errno::EEXIST, "the rule already exists");
+        }
+
+        let metadata = Metadata::new_file(
+            fs.alloc_ino(),
+            mkmod!(a+r, u+w),
+            PAGE_SIZE,
+            fs.sb.container_dev_id,
+        );
+        rules.push(Arc::new(BinfmtInode {
+            kind: BinfmtInodeKind::Rule {
+                rule: Arc::new(rule),
+                is_enabled: AtomicBool::new(true),
+            },
+            common: InodeCommon::new(metadata, Arc::downgrade(&fs)),
+        }));
+        drop(rules);
+
+        self.common.touch_mtime_ctime();
+
+        Ok(())
+    }
+
+    fn remove_rules(&self, should_remove: impl Fn(&BinfmtInode) -> bool) {
+        self.rules.write().retain(|inode| {
+            if !should_remove(inode) {
+                return true;
+            }
+            inode.common.metadata.write().nr_hard_links = 0;
+            false
+        });
+
+        self.common.touch_mtime_ctime();
+    }
+
+    fn find_child(&self, name: &str) -> Option<Arc<BinfmtInode>> {
+        match name {
+            "register" => Some(self.register.clone()),
+            "status" => Some(self.status.clone()),
+            _ => self
+                .rules
+                .read()
+                .iter()
+                .find(|inode| inode.rule_name() == Some(name))
+                .cloned(),
+        }
+    }
+}
+
+impl FileOps for RootInode {
+    fn read_at(
+        &self,
+        _offset: usize,
+        _writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 2 special entries and the 2 control files.
+            let fixed_entries = [
+                (".", self.ino(), self.type_()),
+                ("..", self.ino(), self.type_()),
+                ("register", REGISTER_INO, InodeType::File),
+                ("status", STATUS_INO, InodeType::File),
+            ];
+            for (idx, (name, ino, type_)) in fixed_entries.into_iter().enumerate() {
+                if *offset == idx {
+                    visitor.visit(name, ino, type_, *offset)?;
+                    *offset += 1;
+                }
+            }
+
+            // Read the rules.
+            let rules = self.rules.read();
+            let start_offset = *offset;
+            for (idx, inode) in rules
+                .iter()
+                .enumerate()
+                .map(|(idx, inode)| (idx + fixed_entries.len(), inode))
+                .skip_while(|(idx, _)| idx < &start_offset)
+            {
+                visitor.visit(inode.rule_name().unwrap(), inode.ino(), inode.type_(), idx)?;
+                *offset = idx + 1;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for RootInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn type_(&self) -> InodeType;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn create(&self, _name: &str, _type_: InodeType, _mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn link(&self, _old: &Arc<dyn Inode>, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn unlink(&self, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn rmdir(&self, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::ENOTDIR))
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        self.find_child(name)
+            .map(|inode| inode as Arc<dyn Inode>)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the file does not exist"))
+    }
+
+    fn rename(&self, _old_name: &str, _target: &Arc<dyn Inode>, _new_name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn revalidation_policy(&self) -> RevalidationPolicy {
+        RevalidationPolicy::REVALIDATE_EXISTS | RevalidationPolicy::REVALIDATE_ABSENT
+    }
+
+    fn revalidate_exists(&self, name: &str, child: &dyn Inode) -> bool {
+        // Rules are registered and removed by writing to the control files, not via the VFS.
+        self.find_child(name)
+            .is_some_and(|inode| inode.ino() == child.ino())
+    }
+
+    fn revalidate_absent(&self, name: &str) -> bool {
+        self.find_child(name).is_none()
+    }
+}
+
+/// A file in the binfmt_misc file system.
+struct BinfmtInode {
+    kind: BinfmtInodeKind,
+    common: InodeCommon,
+}
+
+enum BinfmtInodeKind {
+    /// The `register` file, which registers new rules when written.
+    Register,
+    /// The `status` file, which shows and controls whether binfmt_misc is enabled.
+    Status,
+    /// The file of a rule, which shows and controls the rule.
+    Rule {
+        rule: Arc<BinfmtRule>,
+        is_enabled: AtomicBool,
+    },
+}
+
+impl BinfmtInode {
+    fn rule_name(&self) -> Option<&str> {
+        match &self.kind {
+            BinfmtInodeKind::Rule { rule, .. } => Some(rule.name()),
+            BinfmtInodeKind::Register | BinfmtInodeKind::Status => None,
+        }
+    }
+}
+
+impl FileOps for BinfmtInode {
+    fn read_at(
+        &self,
+        offset: usize,
+        writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        match &self.kind {
+            BinfmtInodeKind::Register => {
+                return_errno_with_message!(Errno::EINVAL, "the register file cannot be read")
+            }
+            BinfmtInodeKind::Status => {
+                let fs = self.common.fs.upgrade().unwrap();
+                let status = if fs.is_enabled() {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                writeln!(printer, "{}", status)?;
+            }
+            BinfmtInodeKind::Rule { rule, is_enabled } => {
+                rule.write_status(is_enabled.load(Ordering::Relaxed), &mut printer)?;
+            }
+        }
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let fs = self.common.fs.upgrade().unwrap();
+        let root = &fs.root;
+
+        let len = reader.remain();
+        if matches!(self.kind, BinfmtInodeKind::Register) {
+            if len > super::rule::MAX_REGISTER_LEN {
+                return_errno_with_message!(Errno::EINVAL, "the rule is too long");
+            }
+            let mut buf = vec![0u8; len];
+            reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+
+            let rule = {
+                let current = Task::current().unwrap();
+                let fs_ref = current.as_thread_local().unwrap().borrow_fs();
+                let path_resolver = fs_ref.resolver().read();
+                BinfmtRule::parse(&buf, &path_resolver)?
+            };
+            root.register_rule(rule)?;
+
+            return Ok(len);
+        }
+
+        let Some(command) = parse_command(reader)? else {
+            return Ok(len);
+        };
+        match (&self.kind, command) {
+            (BinfmtInodeKind::Status, Command::Disable) => fs.set_enabled(false),
+            (BinfmtInodeKind::Status, Command::Enable) => fs.set_enabled(true),
+            (BinfmtInodeKind::Status, Command::Remove) => root.remove_rules(|_| true),
+            (BinfmtInodeKind::Rule { is_enabled, .. }, Command::Disable) => {
+                is_enabled.store(false, Ordering::Relaxed)
+            }
+            (BinfmtInodeKind::Rule { is_enabled, .. }, Command::Enable) => {
+                is_enabled.store(true, Ordering::Relaxed)
+            }
+            (BinfmtInodeKind::Rule { .. }, Command::Remove) => {
+                root.remove_rules(|inode| core::ptr::eq(inode, self))
+            }
+            (BinfmtInodeKind::Register, _) => unreachable!(),
+        }
+
+        Ok(len)
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for BinfmtInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn type_(&self) -> InodeType;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        // Opening the files with `O_TRUNC` should succeed.
+        Ok(())
+    }
+}
+
+/// A command written to the `status` file or the file of a rule.
+enum Command {
+    Disable,
+    Enable,
+    Remove,
+}
+
+/// Parses a command written to the `status` file or the file of a rule.
+///
+/// Returns `None` if nothing is written.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+fn parse_command(reader: &mut VmReader) -> Result<Option<Command>> {
+    let len = reader.remain();
+    if len > 3 {
+        return_errno_with_message!(Errno::EINVAL, "the command is too long");
+    }
+
+    let mut buf = [0u8; 3];
+    reader.read_fallible(&mut VmWriter::from(&mut buf[..len]))?;
+
+    let command = buf[..len].strip_suffix(b"\n").unwrap_or(&buf[..len]);
+    match command {
+        _ if len == 0 => Ok(None),
+        b"0" => Ok(Some(Command::Disable)),
+        b"1" => Ok(Some(Command::Enable)),
+        b"-1" => Ok(Some(Command::Remove)),
+        _ => return_errno_with_message!(Errno::EINVAL, "the command is invalid"),
+    }
+}
+
+/// The common parts of the inodes in the binfmt_misc file system.
+struct InodeCommon {
+    metadata: RwLock<Metadata>,
+    extension: Extension,
+    fs: Weak<BinfmtMiscFs>,
+}
+
+impl InodeCommon {
+    fn new(metadata: Metadata, fs: Weak<BinfmtMiscFs>) -> Self {
+        Self {
+            metadata: RwLock::new(metadata),
+            extension: Extension::new(),
+            fs,
+        }
+    }
+
+    fn touch_mtime_ctime(&self) {
+        let now = RealTimeCoarseClock::get().read_time();
+        let mut metadata = self.metadata.write();
+        metadata.last_modify_at = now;
+        metadata.last_meta_change_at = now;
+    }
+
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn extension(&self) -> &Extension {
+        &self.extension
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        self.metadata.read().type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().last_access_at
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().last_access_at = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().last_modify_at
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().last_modify_at = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().last_meta_change_at
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().last_meta_change_at = time;
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+}
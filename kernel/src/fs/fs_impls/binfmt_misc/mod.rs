@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The binfmt_misc file system.
+//!
+//! The file system allows user space to register rules that hand binaries to interpreters.
+//! A binary is recognized either by the magic bytes at its beginning or by the extension of its
+//! file name. Rules are registered by writing to the `register` file, and each rule is shown as a
+//! file in the root directory, which can be written to enable, disable, or remove the rule.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/binfmt-misc.html>
+
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use spin::Once;
+
+use self::inode::RootInode;
+pub use self::rule::{BinfmtFlags, BinfmtRule};
+use crate::{
+    fs::{
+        pseudofs::AnonDeviceId,
+        utils::NAME_MAX,
+        vfs::{
+            file_system::{FileSystem, FsEventSubscriberStats, SuperBlock},
+            inode::Inode,
+            registry::{FsCreationCtx, FsProperties, FsType},
+        },
+    },
+    prelude::*,
+};
+
+mod inode;
+mod rule;
+
+const BINFMTFS_MAGIC: u64 = 0x42494e4d;
+
+const ROOT_INO: u64 = 1;
+const REGISTER_INO: u64 = 2;
+const STATUS_INO: u64 = 3;
+
+static SINGLETON: Once<Arc<BinfmtMiscFs>> = Once::new();
+
+/// The binfmt_misc file system.
+pub struct BinfmtMiscFs {
+    _anon_device_id: AnonDeviceId,
+    sb: SuperBlock,
+    root: Arc<RootInode>,
+    is_enabled: AtomicBool,
+    next_ino: AtomicU64,
+    fs_event_subscriber_stats: FsEventSubscriberStats,
+}
+
+impl BinfmtMiscFs {
+    /// Returns the `BinfmtMiscFs` singleton.
+    fn singleton() -> Result<&'static Arc<BinfmtMiscFs>> {
+        SINGLETON.try_call_once(Self::new)
+    }
+
+    fn new() -> Result<Arc<Self>> {
+        let anon_device_id = AnonDeviceId::acquire().ok_or_else(|| {
+            Error::with_message(Errno::ENODEV, "no device ID is available for binfmt_misc")
+        })?;
+        let sb = SuperBlock::new(BINFMTFS_MAGIC, PAGE_SIZE, NAME_MAX, anon_device_id.id());
+
+        Ok(Arc::new_cyclic(|weak_self| Self {
+            _anon_device_id: anon_device_id,
+            sb: sb.clone(),
+            root: RootInode::new(weak_self.clone(), &sb),
+            is_enabled: AtomicBool::new(true),
+            next_ino: AtomicU64::new(STATUS_INO + 1),
+            fs_event_subscriber_stats: FsEventSubscriberStats::new(),
+        }))
+    }
+
+    fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.is_enabled.load(Ordering::Relaxed)
+    }
+
+    fn set_enabled(&self, is_enabled: bool) {
+        self.is_enabled.store(is_enabled, Ordering::Relaxed);
+    }
+}
+
+impl FileSystem for BinfmtMiscFs {
+    fn name(&self) -> &'static str {
+        "binfmt_misc"
+    }
+
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn fs_event_subscriber_stats(&self) -> &FsEventSubscriberStats {
+        &self.fs_event_subscriber_stats
+    }
+}
+
+struct BinfmtMiscFsType;
+
+impl FsType for BinfmtMiscFsType {
+    fn name(&self) -> &'static str {
+        "binfmt_misc"
+    }
+
+    fn properties(&self) -> FsProperties {
+        FsProperties::empty()
+    }
+
+    fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        // All mounts share the same rules.
+        Ok(BinfmtMiscFs::singleton()?.clone())
+    }
+
+    fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
+        None
+    }
+}
+
+pub(super) fn init() {
+    crate::fs::vfs::registry::register(&BinfmtMiscFsType).unwrap();
+}
+
+/// Finds the rule that hands the binary to an interpreter.
+///
+/// `file_first_page` is the first page of the binary, padded with zeros, and `file_name` is the
+/// name of the binary. If multiple enabled rules match the binary, the most recently registered
+/// one is returned.
+pub fn find_rule(file_first_page: &[u8], file_name: &str) -> Option<Arc<BinfmtRule>> {
+    // No rules can be registered before the file system is mounted.
+    let fs = SINGLETON.get()?;
+    if !fs.is_enabled() {
+        return None;
+    }
+
+    fs.root.find_rule(file_first_page, file_name)
+}
@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Write;
+
+use crate::{
+    fs::vfs::path::{FsPath, Path, PathResolver},
+    prelude::*,
+};
+
+/// The minimum length of a registration string.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+const MIN_REGISTER_LEN: usize = 11;
+
+/// The maximum length of a registration string.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+pub(super) const MAX_REGISTER_LEN: usize = 1920;
+
+/// The number of bytes at the beginning of a binary that can be matched against a magic.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/binfmts.h>
+const BINPRM_BUF_SIZE: usize = 256;
+
+/// A rule that hands matching binaries to an interpreter.
+pub struct BinfmtRule {
+    name: String,
+    matcher: Matcher,
+    interpreter: String,
+    flags: BinfmtFlags,
+    /// The interpreter opened at registration time if [`BinfmtFlags::FIX_BINARY`] is specified.
+    interpreter_file: Option<Path>,
+}
+
+/// How a rule recognizes binaries.
+enum Matcher {
+    /// Matches the bytes at `offset` against `magic`, with the bits not in `mask` ignored.
+    Magic {
+        offset: usize,
+        magic: Vec<u8>,
+        mask: Option<Vec<u8>>,
+    },
+    /// Matches the extension of the file name.
+    Extension(String),
+}
+
+bitflags! {
+    /// The flags of a [`BinfmtRule`].
+    pub struct BinfmtFlags: u8 {
+        /// Preserves `argv[0]` of the original program instead of replacing it with the path.
+        const PRESERVE_ARGV0 = 1 << 0;
+        /// Opens the binary and passes its file descriptor to the interpreter via `AT_EXECFD`.
+        const OPEN_BINARY    = 1 << 1;
+        /// Computes the credentials of the new program from the binary instead of the
+        /// interpreter. This implies [`Self::OPEN_BINARY`].
+        const CREDENTIALS    = 1 << 2;
+        /// Opens the interpreter when the rule is registered.
+        const FIX_BINARY     = 1 << 3;
+    }
+}
+
+impl BinfmtRule {
+    /// Parses a rule from a string written to the `register` file.
+    ///
+    /// The string has the format `:name:type:offset:magic:mask:interpreter:flags`, where the
+    /// first character can be any delimiter.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+    pub(super) fn parse(buf: &[u8], path_resolver: &PathResolver) -> Result<Self> {
+        let invalid_rule = || Error::with_message(Errno::EINVAL, "the rule is invalid");
+
+        if buf.len() < MIN_REGISTER_LEN || buf.len() > MAX_REGISTER_LEN {
+            return_errno_with_message!(Errno::EINVAL, "the rule length is out of range");
+        }
+
+        let delimiter = buf[0];
+        let mut fields = buf[1..].split(|&c| c == delimiter);
+        let mut next_field = || fields.next().ok_or_else(invalid_rule);
+
+        let name = parse_str(next_field()?)?;
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return_errno_with_message!(Errno::EINVAL, "the rule name is invalid");
+        }
+
+        let type_ = next_field()?;
+        let offset = next_field()?;
+        let magic = next_field()?;
+        let mask = next_field()?;
+        let matcher = match type_ {
+            b"M" => {
+                let offset = if offset.is_empty() {
+                    0
+                } else {
+                    parse_str(offset)?
+                        .parse::<usize>()
+                        .map_err(|_| invalid_rule())?
+                };
+
+                let magic = unescape_hex(magic);
+                if magic.is_empty() || offset.saturating_add(magic.len()) > BINPRM_BUF_SIZE {
+                    return_errno_with_message!(Errno::EINVAL, "the magic is invalid");
+                }
+
+                let mask = if mask.is_empty() {
+                    None
+                } else {
+                    let mask = unescape_hex(mask);
+                    if mask.len() != magic.len() {
+                        return_errno_with_message!(Errno::EINVAL, "the mask is invalid");
+                    }
+                    Some(mask)
+                };
+
+                Matcher::Magic {
+                    offset,
+                    magic,
+                    mask,
+                }
+            }
+            b"E" => {
+                // The offset and the mask are ignored.
+                let extension = parse_str(magic)?;
+                if extension.is_empty() || extension.contains('/') {
+                    return_errno_with_message!(Errno::EINVAL, "the extension is invalid");
+                }
+
+                Matcher::Extension(extension.to_string())
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "the rule type is invalid"),
+        };
+
+        let interpreter = parse_str(next_field()?)?;
+        if interpreter.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the interpreter is empty");
+        }
+
+        // The flags are optional and can be followed by a newline.
+        let flags = fields.next().unwrap_or_default();
+        let flags = flags.strip_suffix(b"\n").unwrap_or(flags);
+        if fields.next().is_some() {
+            return_errno_with_message!(Errno::EINVAL, "there are too many fields");
+        }
+        let flags = flags.iter().try_fold(BinfmtFlags::empty(), |flags, flag| {
+            let flag = match flag {
+                b'P' => BinfmtFlags::PRESERVE_ARGV0,
+                b'O' => BinfmtFlags::OPEN_BINARY,
+                b'C' => BinfmtFlags::CREDENTIALS | BinfmtFlags::OPEN_BINARY,
+                b'F' => BinfmtFlags::FIX_BINARY,
+                _ => return Err(invalid_rule()),
+            };
+            Ok(flags | flag)
+        })?;
+
+        let interpreter_file = if flags.contains(BinfmtFlags::FIX_BINARY) {
+            let fs_path = FsPath::try_from(interpreter)?;
+            Some(path_resolver.lookup(&fs_path)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            matcher,
+            interpreter: interpreter.to_string(),
+            flags,
+            interpreter_file,
+        })
+    }
+
+    /// Returns the name of the rule.
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of the interpreter.
+    pub fn interpreter(&self) -> &str {
+        &self.interpreter
+    }
+
+    /// Returns the flags of the rule.
+    pub fn flags(&self) -> BinfmtFlags {
+        self.flags
+    }
+
+    /// Returns the interpreter opened at registration time, if any.
+    pub fn interpreter_file(&self) -> Option<&Path> {
+        self.interpreter_file.as_ref()
+    }
+
+    /// Checks whether the binary matches the rule.
+    ///
+    /// `file_first_page` is the beginning of the binary, padded with zeros, and `file_name` is
+    /// the name of the binary.
+    pub(super) fn matches(&self, file_first_page: &[u8], file_name: &str) -> bool {
+        match &self.matcher {
+            Matcher::Magic {
+                offset,
+                magic,
+                mask,
+            } => {
+                let Some(bytes) = file_first_page.get(*offset..*offset + magic.len()) else {
+                    return false;
+                };
+                match mask {
+                    Some(mask) => bytes
+                        .iter()
+                        .zip(magic)
+                        .zip(mask)
+                        .all(|((byte, magic), mask)| (byte ^ magic) & mask == 0),
+                    None => bytes == magic.as_slice(),
+                }
+            }
+            Matcher::Extension(extension) => file_name
+                .rsplit_once('.')
+                .is_some_and(|(_, file_extension)| file_extension == extension),
+        }
+    }
+
+    /// Writes the status of the rule as shown in its file.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+    pub(super) fn write_status(&self, is_enabled: bool, f: &mut impl Write) -> core::fmt::Result {
+        writeln!(f, "{}", if is_enabled { "enabled" } else { "disabled" })?;
+        writeln!(f, "interpreter {}", self.interpreter)?;
+
+        write!(f, "flags: ")?;
+        for (flag, ch) in [
+            (BinfmtFlags::PRESERVE_ARGV0, 'P'),
+            (BinfmtFlags::OPEN_BINARY, 'O'),
+            (BinfmtFlags::CREDENTIALS, 'C'),
+            (BinfmtFlags::FIX_BINARY, 'F'),
+        ] {
+            if self.flags.contains(flag) {
+                f.write_char(ch)?;
+            }
+        }
+        writeln!(f)?;
+
+        match &self.matcher {
+            Matcher::Magic {
+                offset,
+                magic,
+                mask,
+            } => {
+                writeln!(f, "offset {}", offset)?;
+                writeln!(f, "magic {}", HexBytes(magic))?;
+                if let Some(mask) = mask {
+                    writeln!(f, "mask {}", HexBytes(mask))?;
+                }
+            }
+            Matcher::Extension(extension) => writeln!(f, "extension .{}", extension)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a field of the rule as a string.
+fn parse_str(field: &[u8]) -> Result<&str> {
+    core::str::from_utf8(field)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the rule is not valid UTF-8"))
+}
+
+/// Replaces the `\xHH` escape sequences with the bytes that they represent.
+fn unescape_hex(field: &[u8]) -> Vec<u8> {
+    let hex_value = |c: u8| (c as char).to_digit(16).map(|digit| digit as u8);
+
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        if field[i] == b'\\'
+            && field.get(i + 1) == Some(&b'x')
+            && let Some(high) = field.get(i + 2).copied().and_then(hex_value)
+        {
+            // Like Linux, one or two hex digits are accepted.
+            match field.get(i + 3).copied().and_then(hex_value) {
+                Some(low) => {
+                    bytes.push((high << 4) | low);
+                    i += 4;
+                }
+                None => {
+                    bytes.push(high);
+                    i += 3;
+                }
+            }
+            continue;
+        }
+
+        bytes.push(field[i]);
+        i += 1;
+    }
+
+    bytes
+}
+
+struct HexBytes<'a>(&'a [u8]);
+
+impl core::fmt::Display for HexBytes<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
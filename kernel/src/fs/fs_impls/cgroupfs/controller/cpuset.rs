@@ -1,23 +1,115 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt::{self, Display};
 
-use aster_systree::{Error, Result, SysAttrSetBuilder, SysPerms, SysStr};
+use aster_systree::{Error, MAX_ATTR_SIZE, Result, SysAttrSetBuilder, SysPerms, SysStr};
 use aster_util::printer::VmPrinter;
 use ostd::{
-    cpu::num_cpus,
+    cpu::{CpuId, CpuSet, num_cpus},
     mm::{VmReader, VmWriter},
+    sync::SpinLock,
+    util::id_set::Id,
 };
 
-/// A sub-controller responsible for CPU resource management in the cgroup subsystem.
+use super::SubControlStatic;
+use crate::{fs::cgroupfs::CgroupSysNode, process::Process, thread::AsThread, util::ReadCString};
+
+/// A sub-controller responsible for CPU placement in the cgroup subsystem.
+///
+/// The tasks in a cgroup can only run on the effective CPUs of the cgroup. They are
+/// the CPUs in `cpuset.cpus` that are available in the parent cgroup. If `cpuset.cpus`
+/// is empty, or none of its CPUs is available, all the available CPUs are effective.
+///
+/// A cgroup can be turned into a partition root via `cpuset.cpus.partition`. The CPUs
+/// of a valid partition root are exclusive, so they are no longer available for the
+/// parent cgroup and the sibling cgroups.
+///
+/// Reference: <https://docs.kernel.org/admin-guide/cgroup-v2.html#cpuset>
 pub struct CpuSetController {
-    _private: (),
+    state: SpinLock<CpuSetState>,
+}
+
+struct CpuSetState {
+    /// The CPUs requested by `cpuset.cpus`.
+    cpus: CpuSet,
+    /// The CPUs that the tasks in the cgroup can run on.
+    effective_cpus: CpuSet,
+    /// The CPUs given to the valid partition roots among the child cgroups.
+    subpartition_cpus: CpuSet,
+    partition: PartitionType,
+    /// The reason why the partition root is invalid.
+    ///
+    /// This is `None` if the cgroup is a valid partition root or not a
+    /// partition root.
+    partition_error: Option<PartitionError>,
+}
+
+impl CpuSetState {
+    fn is_valid_partition_root(&self) -> bool {
+        self.partition != PartitionType::Member && self.partition_error.is_none()
+    }
+
+    /// Returns the CPUs that are available for the child cgroups.
+    fn cpus_for_children(&self) -> CpuSet {
+        difference(&self.effective_cpus, &self.subpartition_cpus)
+    }
+}
+
+/// The type of a cgroup in a cpuset partition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PartitionType {
+    /// A member of the partition of its parent.
+    Member,
+    /// A partition root.
+    Root,
+    /// A partition root without load balancing.
+    ///
+    /// Tasks are never moved between CPUs to balance the load, so an
+    /// isolated partition behaves the same as a normal partition root.
+    Isolated,
+}
+
+impl PartitionType {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Member => "member",
+            Self::Root => "root",
+            Self::Isolated => "isolated",
+        }
+    }
+}
+
+/// The reason why a partition root is invalid.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/cgroup/cpuset.c>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PartitionError {
+    NotPartitionRoot,
+    CpusEmpty,
+    NotExclusive,
+    NoCpusLeft,
+}
+
+impl PartitionError {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::NotPartitionRoot => "Parent is not a partition root",
+            Self::CpusEmpty => "cpuset.cpus is empty",
+            Self::NotExclusive => "Cpu list in cpuset.cpus not exclusive",
+            Self::NoCpusLeft => "Parent unable to distribute cpu downstream",
+        }
+    }
 }
 
 impl CpuSetController {
     pub(super) fn init_attr_set(builder: &mut SysAttrSetBuilder, is_root: bool) {
         if !is_root {
             builder.add(SysStr::from("cpuset.cpus"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+            builder.add(
+                SysStr::from("cpuset.cpus.partition"),
+                SysPerms::DEFAULT_RW_ATTR_PERMS,
+            );
             builder.add(SysStr::from("cpuset.mems"), SysPerms::DEFAULT_RW_ATTR_PERMS);
         }
 
@@ -36,32 +128,102 @@ impl super::SubControl for CpuSetController {
     fn read_attr_at(&self, name: &str, offset: usize, writer: &mut VmWriter) -> Result<usize> {
         let mut printer = VmPrinter::new_skip(writer, offset);
         match name {
+            "cpuset.cpus" => {
+                let cpus = self.state.lock().cpus.clone();
+                writeln!(printer, "{}", CpuList(&cpus))?;
+            }
             "cpuset.cpus.effective" => {
-                let num_cpus = num_cpus();
-                if num_cpus == 1 {
-                    writeln!(printer, "0")?;
-                } else {
-                    writeln!(printer, "0-{}", num_cpus - 1)?;
+                let effective_cpus = self.state.lock().effective_cpus.clone();
+                writeln!(printer, "{}", CpuList(&effective_cpus))?;
+            }
+            "cpuset.cpus.partition" => {
+                let (partition, error) = {
+                    let state = self.state.lock();
+                    (state.partition, state.partition_error)
+                };
+                match error {
+                    Some(error) => writeln!(
+                        printer,
+                        "{} invalid ({})",
+                        partition.as_str(),
+                        error.as_str()
+                    )?,
+                    None => writeln!(printer, "{}", partition.as_str())?,
                 }
             }
             // Currently we only support a single memory node.
-            "cpuset.mems.effective" => writeln!(printer, "0")?,
-            // TODO: Add support for reading other attributes.
+            "cpuset.mems" | "cpuset.mems.effective" => writeln!(printer, "0")?,
             _ => return Err(Error::AttributeError),
         }
 
         Ok(printer.bytes_written())
     }
 
-    fn write_attr(&self, _name: &str, _reader: &mut VmReader) -> Result<usize> {
-        // TODO: Add support for writing attributes.
-        Err(Error::AttributeError)
+    fn write_attr(&self, name: &str, reader: &mut VmReader) -> Result<usize> {
+        let (content, len) = reader
+            .read_cstring_until_end(MAX_ATTR_SIZE)
+            .map_err(|_| Error::PageFault)?;
+        let value = content
+            .to_str()
+            .map_err(|_| Error::InvalidOperation)?
+            .trim();
+
+        // The new value takes effect after the partitions and the effective CPUs
+        // in the hierarchy are updated. See `Controller::update_cpusets`.
+        match name {
+            "cpuset.cpus" => {
+                let cpus = parse_cpu_list(value).ok_or(Error::InvalidOperation)?;
+                self.state.lock().cpus = cpus;
+            }
+            "cpuset.cpus.partition" => {
+                let partition = match value {
+                    "member" => PartitionType::Member,
+                    "root" => PartitionType::Root,
+                    "isolated" => PartitionType::Isolated,
+                    _ => return Err(Error::InvalidOperation),
+                };
+                self.state.lock().partition = partition;
+            }
+            // Currently we only support a single memory node.
+            "cpuset.mems" => {
+                if !matches!(value, "" | "0") {
+                    return Err(Error::InvalidOperation);
+                }
+            }
+            _ => return Err(Error::AttributeError),
+        }
+
+        Ok(len)
     }
 }
 
 impl super::SubControlStatic for CpuSetController {
-    fn new(_is_root: bool, _is_active: bool) -> Self {
-        Self { _private: () }
+    fn new(is_root: bool, _is_active: bool) -> Self {
+        // The root cgroup is always a valid partition root with all CPUs. The
+        // effective CPUs of other cgroups are set by `inherit_from`.
+        let (cpus, effective_cpus, partition) = if is_root {
+            (CpuSet::new_full(), CpuSet::new_full(), PartitionType::Root)
+        } else {
+            (
+                CpuSet::new_empty(),
+                CpuSet::new_empty(),
+                PartitionType::Member,
+            )
+        };
+
+        Self {
+            state: SpinLock::new(CpuSetState {
+                cpus,
+                effective_cpus,
+                subpartition_cpus: CpuSet::new_empty(),
+                partition,
+                partition_error: None,
+            }),
+        }
+    }
+
+    fn inherit_from(&self, parent: &super::SubController<Self>) {
+        self.state.lock().effective_cpus = parent.cpus_for_children();
     }
 
     fn type_() -> super::SubCtrlType {
@@ -72,3 +234,187 @@ impl super::SubControlStatic for CpuSetController {
         controller.cpuset.read().get().clone()
     }
 }
+
+impl super::SubController<CpuSetController> {
+    /// Returns the CPUs that the tasks in the cgroup can run on.
+    fn effective_cpus(&self) -> CpuSet {
+        match (&self.inner, &self.parent) {
+            (Some(inner), _) => inner.state.lock().effective_cpus.clone(),
+            (None, Some(parent)) => parent.cpus_for_children(),
+            (None, None) => CpuSet::new_full(),
+        }
+    }
+
+    /// Returns the CPUs that are available for the child cgroups.
+    fn cpus_for_children(&self) -> CpuSet {
+        match (&self.inner, &self.parent) {
+            (Some(inner), _) => inner.state.lock().cpus_for_children(),
+            (None, _) => self.effective_cpus(),
+        }
+    }
+
+    /// Validates the partition roots among `children`, and updates the
+    /// effective CPUs of `children`.
+    ///
+    /// This cgroup must have been updated before.
+    pub(super) fn update_children(&self, children: &[Arc<Self>]) {
+        let Some(inner) = self.inner.as_ref() else {
+            // The cpuset sub-controllers of the children are inactive as well.
+            return;
+        };
+        let (is_partition_root, mut available) = {
+            let state = inner.state.lock();
+            (
+                self.parent.is_none() || state.is_valid_partition_root(),
+                state.effective_cpus.clone(),
+            )
+        };
+
+        // Distribute the exclusive CPUs to the partition roots first.
+        let mut subpartition_cpus = CpuSet::new_empty();
+        for child in children.iter().filter_map(|child| child.inner.as_ref()) {
+            let mut state = child.state.lock();
+            if state.partition == PartitionType::Member {
+                state.partition_error = None;
+                continue;
+            }
+
+            let error = if !is_partition_root {
+                Some(PartitionError::NotPartitionRoot)
+            } else if state.cpus.is_empty() {
+                Some(PartitionError::CpusEmpty)
+            } else if !is_subset(&state.cpus, &available) {
+                Some(PartitionError::NotExclusive)
+            } else if state.cpus.count() == available.count() {
+                Some(PartitionError::NoCpusLeft)
+            } else {
+                None
+            };
+            state.partition_error = error;
+            if error.is_none() {
+                available = difference(&available, &state.cpus);
+                for cpu in state.cpus.iter() {
+                    subpartition_cpus.add(cpu);
+                }
+                state.effective_cpus = state.cpus.clone();
+            }
+        }
+        inner.state.lock().subpartition_cpus = subpartition_cpus;
+
+        // Then the other children share the remaining CPUs.
+        for child in children.iter().filter_map(|child| child.inner.as_ref()) {
+            let mut state = child.state.lock();
+            if state.is_valid_partition_root() {
+                continue;
+            }
+
+            let effective_cpus = intersection(&state.cpus, &available);
+            state.effective_cpus = if effective_cpus.is_empty() {
+                available.clone()
+            } else {
+                effective_cpus
+            };
+        }
+    }
+}
+
+/// Returns the CPUs that the tasks of `process` can run on.
+pub fn cpuset_cpus_allowed(process: &Process) -> CpuSet {
+    let cgroup_guard = process.cgroup();
+    let Some(cgroup) = cgroup_guard.get() else {
+        return CpuSet::new_full();
+    };
+
+    CpuSetController::read_from(cgroup.controller()).effective_cpus()
+}
+
+/// Restricts the CPU affinity of the threads of `process` to the CPUs that are
+/// allowed by its cpuset.
+///
+/// The threads running on other CPUs will migrate before returning to the
+/// user space.
+pub fn update_cpu_affinity(process: &Process) {
+    let allowed_cpus = cpuset_cpus_allowed(process);
+    for task in process.tasks().lock().as_slice() {
+        task.as_thread()
+            .unwrap()
+            .restrict_cpu_affinity(&allowed_cpus);
+    }
+}
+
+/// A CPU set that is displayed in the format of a CPU list, e.g., `0-2,4`.
+struct CpuList<'a>(&'a CpuSet);
+
+impl Display for CpuList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ranges = Vec::new();
+        for cpu in self.0.iter().map(|cpu| cpu.as_usize()) {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == cpu => *end = cpu,
+                _ => ranges.push((cpu, cpu)),
+            }
+        }
+
+        for (i, (start, end)) in ranges.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if start == end {
+                write!(f, "{}", start)?;
+            } else {
+                write!(f, "{}-{}", start, end)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a CPU list, e.g., `0-2,4`.
+///
+/// Returns `None` if the list is malformed or contains CPUs that do not exist.
+fn parse_cpu_list(list: &str) -> Option<CpuSet> {
+    let mut cpus = CpuSet::new_empty();
+    if list.is_empty() {
+        return Some(cpus);
+    }
+
+    for item in list.split(',') {
+        let (start, end) = match item.trim().split_once('-') {
+            Some((start, end)) => (start.parse::<usize>().ok()?, end.parse::<usize>().ok()?),
+            None => {
+                let cpu = item.trim().parse::<usize>().ok()?;
+                (cpu, cpu)
+            }
+        };
+        if start > end || end >= num_cpus() {
+            return None;
+        }
+
+        for cpu in start..=end {
+            cpus.add(CpuId::try_from(cpu).ok()?);
+        }
+    }
+
+    Some(cpus)
+}
+
+fn intersection(cpus: &CpuSet, other: &CpuSet) -> CpuSet {
+    let mut result = CpuSet::new_empty();
+    for cpu in cpus.iter().filter(|cpu| other.contains(*cpu)) {
+        result.add(cpu);
+    }
+    result
+}
+
+fn difference(cpus: &CpuSet, other: &CpuSet) -> CpuSet {
+    let mut result = CpuSet::new_empty();
+    for cpu in cpus.iter().filter(|cpu| !other.contains(*cpu)) {
+        result.add(cpu);
+    }
+    result
+}
+
+fn is_subset(cpus: &CpuSet, other: &CpuSet) -> bool {
+    cpus.iter().all(|cpu| other.contains(cpu))
+}
@@ -0,0 +1,544 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{collections::btree_map::BTreeMap, sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use aster_block::{
+    bio::{BioType, SubmittedBio},
+    request_queue::{BioClass, BioThrottler, register_bio_throttler},
+};
+use aster_systree::{Error, MAX_ATTR_SIZE, Result, SysAttrSetBuilder, SysPerms, SysStr};
+use aster_util::printer::VmPrinter;
+use device_id::{DeviceId, MajorId, MinorId};
+use ostd::{
+    mm::{VmReader, VmWriter},
+    sync::{SpinLock, WaitQueue},
+};
+
+use super::SubControlStatic;
+use crate::{
+    fs::cgroupfs::systree_node::{CgroupSysNode, CgroupSystem},
+    process::Process,
+    time::{clocks::MonotonicClock, wait::WaitTimeout},
+    util::ReadCString,
+};
+
+/// A sub-controller responsible for block I/O resource management in the cgroup subsystem.
+///
+/// The bios submitted by the processes in a cgroup are throttled according to the
+/// per-device limits in `io.max`, and are recorded in `io.stat`. A throttled bio is
+/// held in the request queue of the block device until it can be dispatched. When
+/// a block device is busy, the cgroups share it in proportion to their weights in
+/// `io.weight`.
+///
+/// Like Linux, the limits and the statistics are keyed by the device IDs of whole
+/// disks. The bios submitted to partitions are accounted to the disks that contain
+/// the partitions.
+pub struct IoController {
+    /// The default weight in `io.weight`.
+    default_weight: AtomicU32,
+    /// The per-device states.
+    devices: SpinLock<BTreeMap<DeviceNumber, IoDeviceState>>,
+}
+
+/// The state of an I/O sub-controller for a single block device.
+#[derive(Default)]
+struct IoDeviceState {
+    /// The limits in `io.max`, indexed by `IoLimitKind`.
+    ///
+    /// `None` means no limit.
+    limits: [Option<u64>; IoLimitKind::COUNT],
+    /// The weight in `io.weight`, which overrides the default weight.
+    weight: Option<u32>,
+    /// The statistics in `io.stat`.
+    stat: IoStat,
+    /// The earliest times at which the next bios can be dispatched without
+    /// exceeding the limits, indexed by `IoLimitKind`.
+    next_dispatch_times: [Duration; IoLimitKind::COUNT],
+}
+
+/// The kind of an I/O limit in `io.max`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum IoLimitKind {
+    /// The read bytes per second.
+    Rbps,
+    /// The write bytes per second.
+    Wbps,
+    /// The read I/O operations per second.
+    Riops,
+    /// The write I/O operations per second.
+    Wiops,
+}
+
+impl IoLimitKind {
+    const COUNT: usize = 4;
+
+    const ALL: [Self; Self::COUNT] = [Self::Rbps, Self::Wbps, Self::Riops, Self::Wiops];
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Rbps => "rbps",
+            Self::Wbps => "wbps",
+            Self::Riops => "riops",
+            Self::Wiops => "wiops",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == s)
+    }
+}
+
+/// The direction of a bio.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum IoDirection {
+    Read,
+    Write,
+}
+
+impl IoDirection {
+    /// Returns the limit kinds that apply to the bios of this direction, and
+    /// the amounts that a bio of `nbytes` bytes charges to them.
+    fn limits_and_amounts(self, nbytes: u64) -> [(IoLimitKind, u64); 2] {
+        match self {
+            Self::Read => [(IoLimitKind::Rbps, nbytes), (IoLimitKind::Riops, 1)],
+            Self::Write => [(IoLimitKind::Wbps, nbytes), (IoLimitKind::Wiops, 1)],
+        }
+    }
+}
+
+/// The I/O statistics of a cgroup for a single block device.
+#[derive(Clone, Copy, Default)]
+struct IoStat {
+    rbytes: u64,
+    wbytes: u64,
+    rios: u64,
+    wios: u64,
+}
+
+impl IoStat {
+    fn is_empty(&self) -> bool {
+        self.rios == 0 && self.wios == 0
+    }
+
+    fn record(&mut self, direction: IoDirection, nbytes: u64) {
+        match direction {
+            IoDirection::Read => {
+                self.rbytes += nbytes;
+                self.rios += 1;
+            }
+            IoDirection::Write => {
+                self.wbytes += nbytes;
+                self.wios += 1;
+            }
+        }
+    }
+}
+
+/// The default weight and the range of weights in `io.weight`.
+///
+/// Reference: <https://docs.kernel.org/admin-guide/cgroup-v2.html#io-interface-files>
+const DEFAULT_WEIGHT: u32 = 100;
+const MIN_WEIGHT: u32 = 1;
+const MAX_WEIGHT: u32 = 10_000;
+
+impl IoController {
+    pub(super) fn init_attr_set(builder: &mut SysAttrSetBuilder, is_root: bool) {
+        builder.add(SysStr::from("io.stat"), SysPerms::DEFAULT_RO_ATTR_PERMS);
+
+        if !is_root {
+            builder.add(SysStr::from("io.max"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+            builder.add(SysStr::from("io.weight"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+        }
+    }
+
+    /// Charges a bio of `nbytes` bytes to the block device of `device_id`.
+    ///
+    /// The bio cannot be dispatched before `earliest`. Returns the earliest time
+    /// at which the bio can be dispatched without exceeding the limits of this
+    /// cgroup, which is reserved for the bio.
+    fn charge(
+        &self,
+        device_id: DeviceId,
+        direction: IoDirection,
+        nbytes: u64,
+        earliest: Duration,
+    ) -> Duration {
+        let mut devices = self.devices.lock();
+        let state = devices.entry(DeviceNumber::from(device_id)).or_default();
+        state.stat.record(direction, nbytes);
+
+        let mut dispatch_time = earliest;
+        for (kind, amount) in direction.limits_and_amounts(nbytes) {
+            let Some(limit) = state.limits[kind as usize] else {
+                continue;
+            };
+
+            // The unused budget is not accumulated while the device is idle.
+            let next_dispatch_time = &mut state.next_dispatch_times[kind as usize];
+            let start = (*next_dispatch_time).max(earliest);
+            let cost_ns = (amount as u128 * 1_000_000_000 / limit as u128).min(u64::MAX as u128);
+            *next_dispatch_time = start + Duration::from_nanos(cost_ns as u64);
+
+            dispatch_time = dispatch_time.max(start);
+        }
+
+        dispatch_time
+    }
+
+    /// Returns the weight of this cgroup for the block device of `device_id`.
+    fn weight(&self, device_id: DeviceId) -> u32 {
+        let device_weight = self
+            .devices
+            .lock()
+            .get(&DeviceNumber::from(device_id))
+            .and_then(|state| state.weight);
+        device_weight.unwrap_or_else(|| self.default_weight.load(Ordering::Relaxed))
+    }
+
+    fn read_max(&self, printer: &mut VmPrinter) -> Result<()> {
+        // Take a snapshot to avoid writing to the printer with the spin lock held.
+        let all_limits: Vec<_> = self
+            .devices
+            .lock()
+            .iter()
+            .filter(|(_, state)| state.limits.iter().any(Option::is_some))
+            .map(|(device, state)| (*device, state.limits))
+            .collect();
+
+        for (device, limits) in all_limits {
+            write!(printer, "{}", device)?;
+            for kind in IoLimitKind::ALL {
+                match limits[kind as usize] {
+                    Some(limit) => write!(printer, " {}={}", kind.as_str(), limit)?,
+                    None => write!(printer, " {}=max", kind.as_str())?,
+                }
+            }
+            writeln!(printer)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_weight(&self, printer: &mut VmPrinter) -> Result<()> {
+        let default_weight = self.default_weight.load(Ordering::Relaxed);
+        writeln!(printer, "default {}", default_weight)?;
+
+        let weights: Vec<_> = self
+            .devices
+            .lock()
+            .iter()
+            .filter_map(|(device, state)| Some((*device, state.weight?)))
+            .collect();
+        for (device, weight) in weights {
+            writeln!(printer, "{} {}", device, weight)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_stat(&self, printer: &mut VmPrinter) -> Result<()> {
+        let stats: Vec<_> = self
+            .devices
+            .lock()
+            .iter()
+            .filter(|(_, state)| !state.stat.is_empty())
+            .map(|(device, state)| (*device, state.stat))
+            .collect();
+
+        for (device, stat) in stats {
+            // Discarding sectors is not supported yet, so `dbytes` and `dios`
+            // are always zero.
+            writeln!(
+                printer,
+                "{} rbytes={} wbytes={} rios={} wios={} dbytes=0 dios=0",
+                device, stat.rbytes, stat.wbytes, stat.rios, stat.wios
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `io.max`, whose format is `$MAJ:$MIN [$KEY=$VALUE]...`.
+    ///
+    /// The value of a key is either a positive number or `max`, which removes
+    /// the limit. The keys that are not specified are left unchanged.
+    fn write_max(&self, content: &str) -> Result<()> {
+        let mut tokens = content.split_whitespace();
+        let device = parse_whole_disk(tokens.next().ok_or(Error::InvalidOperation)?)?;
+
+        let mut new_limits = [None; IoLimitKind::COUNT];
+        for token in tokens {
+            let (key, value) = token.split_once('=').ok_or(Error::InvalidOperation)?;
+            let kind = IoLimitKind::from_str(key).ok_or(Error::InvalidOperation)?;
+            let limit = if value == "max" {
+                None
+            } else {
+                match value.parse::<u64>() {
+                    Ok(limit) if limit > 0 => Some(limit),
+                    _ => return Err(Error::InvalidOperation),
+                }
+            };
+            new_limits[kind as usize] = Some(limit);
+        }
+
+        let mut devices = self.devices.lock();
+        let state = devices.entry(device).or_default();
+        for (limit, new_limit) in state.limits.iter_mut().zip(new_limits) {
+            if let Some(new_limit) = new_limit {
+                *limit = new_limit;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes `io.weight`, whose format is `[default] $WEIGHT` for the default
+    /// weight, or `$MAJ:$MIN $WEIGHT` for a device.
+    ///
+    /// The weight of a device can be `default`, which removes the weight of the
+    /// device so that the default weight is used.
+    fn write_weight(&self, content: &str) -> Result<()> {
+        fn parse_weight(value: &str) -> Result<u32> {
+            match value.parse::<u32>() {
+                Ok(weight) if (MIN_WEIGHT..=MAX_WEIGHT).contains(&weight) => Ok(weight),
+                _ => Err(Error::InvalidOperation),
+            }
+        }
+
+        let mut tokens = content.split_whitespace();
+        let (first, second) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(first), second, None) => (first, second),
+            _ => return Err(Error::InvalidOperation),
+        };
+
+        match (first, second) {
+            ("default", Some(weight)) | (weight, None) => {
+                let weight = parse_weight(weight)?;
+                self.default_weight.store(weight, Ordering::Relaxed);
+            }
+            (device, Some(weight)) => {
+                let device = parse_whole_disk(device)?;
+                let weight = if weight == "default" {
+                    None
+                } else {
+                    Some(parse_weight(weight)?)
+                };
+                self.devices.lock().entry(device).or_default().weight = weight;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of a block device, which is displayed as `$MAJ:$MIN`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct DeviceNumber {
+    major: u16,
+    minor: u32,
+}
+
+impl From<DeviceId> for DeviceNumber {
+    fn from(device_id: DeviceId) -> Self {
+        Self {
+            major: device_id.major().get(),
+            minor: device_id.minor().get(),
+        }
+    }
+}
+
+impl core::fmt::Display for DeviceNumber {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.major, self.minor)
+    }
+}
+
+/// Parses a device number in the form of `$MAJ:$MIN`.
+///
+/// Returns an error if the device is not a registered block device, or if the
+/// device is a partition.
+fn parse_whole_disk(value: &str) -> Result<DeviceNumber> {
+    let (major, minor) = value.split_once(':').ok_or(Error::InvalidOperation)?;
+    let major = major
+        .parse::<u16>()
+        .ok()
+        .and_then(|major| MajorId::try_from(major).ok())
+        .ok_or(Error::InvalidOperation)?;
+    let minor = minor
+        .parse::<u32>()
+        .ok()
+        .and_then(|minor| MinorId::try_from(minor).ok())
+        .ok_or(Error::InvalidOperation)?;
+    let device_id = DeviceId::new(major, minor);
+
+    match aster_block::lookup(device_id) {
+        Some(device) if !device.is_partition() => Ok(DeviceNumber::from(device_id)),
+        _ => Err(Error::NotFound),
+    }
+}
+
+impl super::SubControl for IoController {
+    fn read_attr_at(&self, name: &str, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        match name {
+            "io.max" => self.read_max(&mut printer)?,
+            "io.stat" => self.read_stat(&mut printer)?,
+            "io.weight" => self.read_weight(&mut printer)?,
+            _ => return Err(Error::AttributeError),
+        }
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_attr(&self, name: &str, reader: &mut VmReader) -> Result<usize> {
+        let (content, len) = reader
+            .read_cstring_until_end(MAX_ATTR_SIZE)
+            .map_err(|_| Error::PageFault)?;
+        let content = content
+            .to_str()
+            .map_err(|_| Error::InvalidOperation)?
+            .trim();
+
+        match name {
+            "io.max" => self.write_max(content)?,
+            "io.weight" => self.write_weight(content)?,
+            _ => return Err(Error::AttributeError),
+        }
+
+        Ok(len)
+    }
+}
+
+impl super::SubControlStatic for IoController {
+    fn new(_is_root: bool, _is_active: bool) -> Self {
+        Self {
+            default_weight: AtomicU32::new(DEFAULT_WEIGHT),
+            devices: SpinLock::new(BTreeMap::new()),
+        }
+    }
+
+    fn type_() -> super::SubCtrlType {
+        super::SubCtrlType::Io
+    }
+
+    fn read_from(controller: &super::Controller) -> Arc<super::SubController<Self>> {
+        controller.io.read().get().clone()
+    }
+}
+
+impl super::SubController<IoController> {
+    /// Charges a bio across the hierarchy, including the root cgroup.
+    ///
+    /// Returns the earliest time at which the bio can be dispatched without
+    /// exceeding the limits of any cgroup in the hierarchy.
+    fn charge_hierarchy(
+        &self,
+        device_id: DeviceId,
+        direction: IoDirection,
+        nbytes: u64,
+        now: Duration,
+    ) -> Duration {
+        let mut dispatch_time = now;
+
+        let mut current = Some(self);
+        while let Some(node) = current {
+            if let Some(io_controller) = node.inner.as_ref() {
+                dispatch_time = io_controller.charge(device_id, direction, nbytes, dispatch_time);
+            }
+            current = node.parent.as_deref();
+        }
+
+        dispatch_time
+    }
+}
+
+/// The throttler of the bios according to the I/O sub-controllers.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/block/blk-throttle.c>
+//
+// TODO: Like Linux, distribute the share of a cgroup among its children
+// according to their weights. Currently, the weights of all the cgroups that
+// submit bios to a device are compared with each other.
+struct IoThrottler;
+
+impl BioThrottler for IoThrottler {
+    /// Charges a bio submitted by the current process according to `io.max`,
+    /// and records it in `io.stat`.
+    ///
+    /// The bios of a cgroup are grouped together, with the weight of the cgroup
+    /// in `io.weight`.
+    fn classify(&self, device_id: DeviceId, bio: &SubmittedBio) -> BioClass {
+        let now = self.now();
+        let Some(process) = Process::current() else {
+            return BioClass {
+                group: 0,
+                weight: DEFAULT_WEIGHT,
+                dispatch_time: now,
+            };
+        };
+
+        let io = {
+            let cgroup_guard = process.cgroup();
+            if let Some(cgroup) = cgroup_guard.get() {
+                IoController::read_from(cgroup.controller())
+            } else {
+                IoController::read_from(CgroupSystem::singleton().controller())
+            }
+        };
+        let group = Arc::as_ptr(&io) as usize;
+        let weight = io.inner.as_ref().map_or(DEFAULT_WEIGHT, |io_controller| {
+            io_controller.weight(device_id)
+        });
+
+        let direction = match bio.type_() {
+            BioType::Read => IoDirection::Read,
+            BioType::Write => IoDirection::Write,
+            BioType::Flush => {
+                return BioClass {
+                    group,
+                    weight,
+                    dispatch_time: now,
+                };
+            }
+        };
+        let nbytes = bio
+            .segments()
+            .iter()
+            .map(|segment| segment.nbytes() as u64)
+            .sum();
+        let dispatch_time = io.charge_hierarchy(device_id, direction, nbytes, now);
+
+        BioClass {
+            group,
+            weight,
+            dispatch_time,
+        }
+    }
+
+    fn now(&self) -> Duration {
+        MonotonicClock::get().read_time()
+    }
+
+    fn wait_until_or_deadline(
+        &self,
+        wait_queue: &WaitQueue,
+        cond: &dyn Fn() -> bool,
+        deadline: Duration,
+    ) {
+        let now = self.now();
+        if deadline <= now {
+            return;
+        }
+        let _ = wait_queue.wait_until_or_timeout(|| cond().then_some(()), &(deadline - now));
+    }
+}
+
+/// Registers the throttler of the bios according to the I/O sub-controllers.
+pub(in crate::fs::fs_impls::cgroupfs) fn init() {
+    register_bio_throttler(&IoThrottler);
+}
@@ -1,18 +1,130 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use alloc::sync::Arc;
+use core::{
+    fmt::Debug,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
 
-use aster_systree::{Error, Result, SysAttrSetBuilder, SysPerms, SysStr};
-use ostd::mm::{VmReader, VmWriter};
+use aster_systree::{Error, MAX_ATTR_SIZE, Result, SysAttrSetBuilder, SysObj, SysPerms, SysStr};
+use aster_util::printer::VmPrinter;
+use ostd::{
+    mm::{PAGE_SIZE, VmReader, VmWriter},
+    sync::Waiter,
+    task::Task,
+};
+
+use super::{SubControlStatic, TryChargeError};
+use crate::{
+    fs::cgroupfs::{CgroupNode, CgroupSysNode},
+    process::{
+        Process,
+        posix_thread::{AsPosixThread, AsThreadLocal},
+        signal::{HandlePendingSignal, Pause},
+    },
+    util::ReadCString,
+    vm::{
+        oom::{OomConstraint, out_of_memory},
+        reclaim,
+    },
+};
 
 /// A sub-controller responsible for memory resource management in the cgroup subsystem.
 ///
+/// Pages are charged to the memory cgroup of the process that allocates them, and are
+/// uncharged when they are freed. Charges are not moved when a process migrates to
+/// another cgroup, which is the same as Linux.
+///
+/// The pages of a cgroup are not reclaimed while its usage is within `memory.min`.
+/// They are not reclaimed either while its usage is within `memory.low`, unless
+/// no other pages can be reclaimed.
+///
 /// Note that even if the controller is inactive, it still provides some interfaces
 /// like "memory.pressure" for usage.
 pub struct MemoryController {
-    _private: (),
+    /// The number of pages charged to this cgroup's subtree.
+    usage: AtomicUsize,
+    /// The peak number of pages ever charged to this cgroup's subtree.
+    peak: AtomicUsize,
+    /// The hard protection of memory usage, in pages.
+    min: AtomicUsize,
+    /// The best-effort protection of memory usage, in pages.
+    low: AtomicUsize,
+    /// The throttling limit of memory usage, in pages.
+    high: AtomicUsize,
+    /// The hard limit of memory usage, in pages.
+    max: AtomicUsize,
+    /// The number of charged pages of each kind.
+    stats: [AtomicUsize; MemChargeKind::COUNT],
+    /// The number of occurrences of each memory event.
+    events: [AtomicUsize; MemoryEvent::COUNT],
+}
+
+/// The kind of a charged page, which is reported in `memory.stat`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemChargeKind {
+    /// An anonymous page.
+    Anon,
+    /// A page in the page cache.
+    File,
+}
+
+impl MemChargeKind {
+    const COUNT: usize = 2;
+}
+
+/// An event of a memory cgroup, which is reported in `memory.events`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryEvent {
+    /// The memory usage is below `memory.low` but the memory is reclaimed.
+    Low,
+    /// The memory usage exceeds `memory.high`.
+    High,
+    /// The memory usage is about to exceed `memory.max`.
+    Max,
+    /// The OOM killer is invoked because the memory usage reaches `memory.max`.
+    Oom,
+    /// A process in the cgroup is killed by the OOM killer.
+    OomKill,
+}
+
+impl MemoryEvent {
+    const COUNT: usize = 5;
+
+    const ALL: [Self; Self::COUNT] = [Self::Low, Self::High, Self::Max, Self::Oom, Self::OomKill];
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::High => "high",
+            Self::Max => "max",
+            Self::Oom => "oom",
+            Self::OomKill => "oom_kill",
+        }
+    }
 }
 
+/// The value of a memory limit that means no limit.
+const NO_LIMIT: usize = usize::MAX;
+
+/// The maximum time that a process is throttled at a time for exceeding `memory.high`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/memcontrol.c>
+const MAX_HIGH_DELAY: Duration = Duration::from_secs(2);
+
+/// The minimum throttling time that is worth sleeping for.
+const MIN_HIGH_DELAY: Duration = Duration::from_millis(10);
+
+/// The maximum number of rounds of page reclamation for exceeding `memory.high`.
+const MAX_HIGH_RECLAIM_ROUNDS: usize = 16;
+
+/// The maximum number of failed rounds of page reclamation after `memory.high`
+/// or `memory.max` is lowered below the usage.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/memcontrol.c>
+const MAX_RECLAIM_RETRIES: usize = 16;
+
 impl MemoryController {
     pub(super) fn init_attr_set(builder: &mut SysAttrSetBuilder, is_root: bool) {
         // These attributes only exist on the non-root cgroup nodes.
@@ -21,31 +133,176 @@ impl MemoryController {
         //
         // Reference: <https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html#memory-interface-files>
         if !is_root {
+            builder.add(
+                SysStr::from("memory.current"),
+                SysPerms::DEFAULT_RO_ATTR_PERMS,
+            );
             builder.add(
                 SysStr::from("memory.events"),
                 SysPerms::DEFAULT_RO_ATTR_PERMS,
             );
-            builder.add(SysStr::from("memory.max"), SysPerms::DEFAULT_RO_ATTR_PERMS);
+            builder.add(SysStr::from("memory.high"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+            builder.add(SysStr::from("memory.low"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+            builder.add(SysStr::from("memory.max"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+            builder.add(SysStr::from("memory.min"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+            builder.add(SysStr::from("memory.peak"), SysPerms::DEFAULT_RO_ATTR_PERMS);
             builder.add(SysStr::from("memory.stat"), SysPerms::DEFAULT_RO_ATTR_PERMS);
         }
     }
+
+    /// Tries to charge one page, enforcing the `memory.max` limit.
+    ///
+    /// Returns `None` if the limit would be exceeded; the charge is rolled back.
+    /// Otherwise, returns whether the usage exceeds `memory.high`.
+    fn try_charge(&self, kind: MemChargeKind) -> Option<bool> {
+        let new_usage = self.usage.fetch_add(1, Ordering::Relaxed) + 1;
+        if new_usage > self.max.load(Ordering::Relaxed) {
+            self.usage.fetch_sub(1, Ordering::Relaxed);
+            return None;
+        }
+
+        self.peak.fetch_max(new_usage, Ordering::Relaxed);
+        self.stats[kind as usize].fetch_add(1, Ordering::Relaxed);
+        let is_over_high = new_usage > self.high.load(Ordering::Relaxed);
+        if is_over_high {
+            self.events[MemoryEvent::High as usize].fetch_add(1, Ordering::Relaxed);
+        }
+        Some(is_over_high)
+    }
+
+    /// Uncharges one page.
+    fn uncharge(&self, kind: MemChargeKind) {
+        self.stats[kind as usize].fetch_sub(1, Ordering::Relaxed);
+        let old_usage = self.usage.fetch_sub(1, Ordering::Relaxed);
+        debug_assert!(old_usage > 0, "memory usage underflow");
+    }
+
+    /// Returns the number of pages by which the usage exceeds `memory.high`,
+    /// and the value of `memory.high`.
+    fn high_overage(&self) -> Option<(usize, usize)> {
+        let high = self.high.load(Ordering::Relaxed);
+        let usage = self.usage.load(Ordering::Relaxed);
+        (usage > high).then(|| (usage - high, high))
+    }
+
+    /// Returns whether charging one more page would exceed `memory.max`.
+    fn is_at_max(&self) -> bool {
+        self.usage.load(Ordering::Relaxed) >= self.max.load(Ordering::Relaxed)
+    }
+
+    /// Returns the protection of the pages charged to this cgroup.
+    fn protection(&self) -> MemProtection {
+        let usage = self.usage.load(Ordering::Relaxed);
+        if usage <= self.min.load(Ordering::Relaxed) {
+            MemProtection::Min
+        } else if usage <= self.low.load(Ordering::Relaxed) {
+            MemProtection::Low
+        } else {
+            MemProtection::None
+        }
+    }
+
+    fn read_limit(limit: &AtomicUsize, printer: &mut VmPrinter) -> Result<()> {
+        let limit = limit.load(Ordering::Relaxed);
+        if limit == NO_LIMIT {
+            writeln!(printer, "max")?;
+        } else {
+            writeln!(printer, "{}", limit * PAGE_SIZE)?;
+        }
+        Ok(())
+    }
+
+    fn write_limit(limit: &AtomicUsize, reader: &mut VmReader) -> Result<usize> {
+        let (content, len) = reader
+            .read_cstring_until_end(MAX_ATTR_SIZE)
+            .map_err(|_| Error::PageFault)?;
+        let value = content
+            .to_str()
+            .map_err(|_| Error::InvalidOperation)?
+            .trim();
+
+        let nr_pages = if value == "max" {
+            NO_LIMIT
+        } else {
+            parse_size(value).ok_or(Error::InvalidOperation)? / PAGE_SIZE
+        };
+        limit.store(nr_pages, Ordering::Relaxed);
+
+        Ok(len)
+    }
+}
+
+/// Parses a size in bytes, which may have a suffix of `K`, `M`, `G`, or `T`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/lib/cmdline.c>
+fn parse_size(value: &str) -> Option<usize> {
+    let (digits, shift) = match value.as_bytes().last()? {
+        b'k' | b'K' => (&value[..value.len() - 1], 10),
+        b'm' | b'M' => (&value[..value.len() - 1], 20),
+        b'g' | b'G' => (&value[..value.len() - 1], 30),
+        b't' | b'T' => (&value[..value.len() - 1], 40),
+        _ => (value, 0),
+    };
+    digits.parse::<usize>().ok()?.checked_mul(1 << shift)
 }
 
 impl super::SubControl for MemoryController {
-    fn read_attr_at(&self, _name: &str, _offset: usize, _writer: &mut VmWriter) -> Result<usize> {
-        // TODO: Add support for reading attributes.
-        Err(Error::AttributeError)
+    fn read_attr_at(&self, name: &str, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        match name {
+            "memory.current" => {
+                let usage = self.usage.load(Ordering::Relaxed);
+                writeln!(printer, "{}", usage * PAGE_SIZE)?;
+            }
+            "memory.events" => {
+                for event in MemoryEvent::ALL {
+                    let count = self.events[event as usize].load(Ordering::Relaxed);
+                    writeln!(printer, "{} {}", event.as_str(), count)?;
+                }
+            }
+            "memory.high" => Self::read_limit(&self.high, &mut printer)?,
+            "memory.low" => Self::read_limit(&self.low, &mut printer)?,
+            "memory.max" => Self::read_limit(&self.max, &mut printer)?,
+            "memory.min" => Self::read_limit(&self.min, &mut printer)?,
+            "memory.peak" => {
+                let peak = self.peak.load(Ordering::Relaxed);
+                writeln!(printer, "{}", peak * PAGE_SIZE)?;
+            }
+            "memory.stat" => {
+                let anon = self.stats[MemChargeKind::Anon as usize].load(Ordering::Relaxed);
+                let file = self.stats[MemChargeKind::File as usize].load(Ordering::Relaxed);
+                writeln!(printer, "anon {}", anon * PAGE_SIZE)?;
+                writeln!(printer, "file {}", file * PAGE_SIZE)?;
+            }
+            _ => return Err(Error::AttributeError),
+        }
+
+        Ok(printer.bytes_written())
     }
 
-    fn write_attr(&self, _name: &str, _reader: &mut VmReader) -> Result<usize> {
-        // TODO: Add support for writing attributes.
-        Err(Error::AttributeError)
+    fn write_attr(&self, name: &str, reader: &mut VmReader) -> Result<usize> {
+        match name {
+            "memory.high" => Self::write_limit(&self.high, reader),
+            "memory.low" => Self::write_limit(&self.low, reader),
+            "memory.max" => Self::write_limit(&self.max, reader),
+            "memory.min" => Self::write_limit(&self.min, reader),
+            _ => Err(Error::AttributeError),
+        }
     }
 }
 
 impl super::SubControlStatic for MemoryController {
     fn new(_is_root: bool, _is_active: bool) -> Self {
-        Self { _private: () }
+        Self {
+            usage: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            min: AtomicUsize::new(0),
+            low: AtomicUsize::new(0),
+            high: AtomicUsize::new(NO_LIMIT),
+            max: AtomicUsize::new(NO_LIMIT),
+            stats: [const { AtomicUsize::new(0) }; MemChargeKind::COUNT],
+            events: [const { AtomicUsize::new(0) }; MemoryEvent::COUNT],
+        }
     }
 
     fn type_() -> super::SubCtrlType {
@@ -56,3 +313,329 @@ impl super::SubControlStatic for MemoryController {
         controller.memory.read().get().clone()
     }
 }
+
+/// Hierarchical memory charge/uncharge operations.
+///
+/// The root cgroup is not limited, so pages are not charged to it.
+impl super::SubController<MemoryController> {
+    /// Returns an iterator over the active memory sub-controllers from this
+    /// cgroup up to the root cgroup, excluding the root cgroup.
+    fn iter_hierarchy(&self) -> impl Iterator<Item = &MemoryController> {
+        let mut current = Some(self);
+        core::iter::from_fn(move || {
+            loop {
+                let node = current?;
+                // The root cgroup is the only one without a parent.
+                node.parent.as_ref()?;
+                current = node.parent.as_deref();
+                if let Some(inner) = node.inner.as_ref() {
+                    return Some(inner);
+                }
+            }
+        })
+    }
+
+    /// Tries to charge one page across the hierarchy with limit checking.
+    ///
+    /// If any level exceeds its `memory.max` limit, all previously charged
+    /// levels are rolled back and `Err` is returned. Otherwise, returns whether
+    /// any level exceeds its `memory.high` limit.
+    fn try_charge_hierarchy(&self, kind: MemChargeKind) -> Result<bool, TryChargeError> {
+        let mut is_over_high = false;
+        for (nr_charged, memory_controller) in self.iter_hierarchy().enumerate() {
+            let Some(is_level_over_high) = memory_controller.try_charge(kind) else {
+                memory_controller.events[MemoryEvent::Max as usize].fetch_add(1, Ordering::Relaxed);
+                for charged in self.iter_hierarchy().take(nr_charged) {
+                    charged.uncharge(kind);
+                }
+                return Err(TryChargeError);
+            };
+            is_over_high |= is_level_over_high;
+        }
+
+        Ok(is_over_high)
+    }
+
+    /// Uncharges one page across the hierarchy.
+    fn uncharge_hierarchy(&self, kind: MemChargeKind) {
+        for memory_controller in self.iter_hierarchy() {
+            memory_controller.uncharge(kind);
+        }
+    }
+
+    /// Records a memory event in this cgroup and all of its ancestors.
+    fn record_event_hierarchy(&self, event: MemoryEvent) {
+        for memory_controller in self.iter_hierarchy() {
+            memory_controller.events[event as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A page charged to a memory cgroup hierarchy.
+///
+/// The page is uncharged when this is dropped. So it is kept in the metadata
+/// of the charged frame, and is dropped when the frame is freed.
+pub struct MemCharge {
+    memory: Arc<super::SubController<MemoryController>>,
+    kind: MemChargeKind,
+}
+
+impl MemCharge {
+    /// Charges one page to the memory cgroup of the current process.
+    ///
+    /// Returns `None` if the current process is in the root cgroup, or if
+    /// there is no current process.
+    ///
+    /// If the charge makes a cgroup exceed its `memory.high` limit, the
+    /// current thread will be throttled before returning to the user space
+    /// (see [`throttle_memory_over_high`]).
+    ///
+    /// This method does not block, so it can be called in atomic mode.
+    pub fn try_charge_current(kind: MemChargeKind) -> Result<Option<Self>, TryChargeError> {
+        let Some(process) = Process::current() else {
+            return Ok(None);
+        };
+        let cgroup_guard = process.cgroup();
+        let Some(cgroup) = cgroup_guard.get() else {
+            return Ok(None);
+        };
+
+        let memory = MemoryController::read_from(cgroup.controller());
+        let is_over_high = memory.try_charge_hierarchy(kind)?;
+        if is_over_high
+            && let Some(task) = Task::current()
+            && let Some(thread_local) = task.as_thread_local()
+        {
+            thread_local.set_memcg_over_high();
+        }
+
+        Ok(Some(Self { memory, kind }))
+    }
+
+    /// Returns whether the page is charged to `memcg` or one of its descendants.
+    pub fn is_charged_to(&self, memcg: &MemCgroup) -> bool {
+        let Some(target) = memcg.0.inner.as_ref() else {
+            return false;
+        };
+        self.memory
+            .iter_hierarchy()
+            .any(|memory_controller| core::ptr::eq(memory_controller, target))
+    }
+
+    /// Returns the protection of the page from a reclamation of `memcg`, or
+    /// from a global reclamation if `memcg` is `None`.
+    ///
+    /// The protection is given by the cgroups from the one charged with the
+    /// page up to, but excluding, `memcg`. A cgroup does not protect its pages
+    /// from the reclamation that targets the cgroup itself.
+    pub fn protection(&self, memcg: Option<&MemCgroup>) -> MemProtection {
+        let target = memcg.and_then(|memcg| memcg.0.inner.as_ref());
+        self.memory
+            .iter_hierarchy()
+            .take_while(|memory_controller| {
+                target.is_none_or(|target| !core::ptr::eq(*memory_controller, target))
+            })
+            .map(MemoryController::protection)
+            .max()
+            .unwrap_or(MemProtection::None)
+    }
+
+    /// Records that the page is reclaimed even though it is protected by
+    /// `memory.low`.
+    pub fn record_low_reclaim(&self) {
+        for memory_controller in self.memory.iter_hierarchy() {
+            if memory_controller.protection() == MemProtection::Low {
+                memory_controller.events[MemoryEvent::Low as usize].fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// The protection of a page from reclamation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemProtection {
+    /// The page is not protected.
+    None,
+    /// The page is protected by `memory.low`, so it is reclaimed only if no
+    /// unprotected pages can be reclaimed.
+    Low,
+    /// The page is protected by `memory.min`, so it is never reclaimed.
+    Min,
+}
+
+/// A memory cgroup as the target of page reclamation.
+///
+/// Only the pages charged to the cgroup or its descendants are reclaimed.
+pub struct MemCgroup(Arc<super::SubController<MemoryController>>);
+
+impl MemCgroup {
+    /// Returns the memory cgroup of `cgroup`.
+    pub fn of(cgroup: &CgroupNode) -> Self {
+        Self(MemoryController::read_from(cgroup.controller()))
+    }
+}
+
+impl Debug for MemCharge {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MemCharge")
+            .field("kind", &self.kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for MemCharge {
+    fn drop(&mut self) {
+        self.memory.uncharge_hierarchy(self.kind);
+    }
+}
+
+// For memory sub-controller
+impl CgroupNode {
+    /// Records a memory event in this cgroup and all of its ancestors.
+    pub fn record_memory_event(&self, event: MemoryEvent) {
+        MemoryController::read_from(self.controller()).record_event_hierarchy(event);
+    }
+
+    /// Returns the cgroup that prevents `process` from charging more pages,
+    /// and the `memory.max` limit of the cgroup in pages.
+    ///
+    /// The cgroup is `process`'s cgroup or one of its ancestors.
+    pub fn find_memory_limited(process: &Process) -> Option<(Arc<CgroupNode>, usize)> {
+        let mut current = process.cgroup().get()?.clone();
+        loop {
+            let memory = MemoryController::read_from(current.controller());
+            if let Some(inner) = memory.inner.as_ref()
+                && inner.is_at_max()
+            {
+                let limit = inner.max.load(Ordering::Relaxed);
+                return Some((current, limit));
+            }
+
+            // The parent of a top-level cgroup is the root cgroup, which is
+            // not limited.
+            current = Arc::downcast::<CgroupNode>(current.parent()?).ok()?;
+        }
+    }
+
+    /// Reduces the memory usage of this cgroup after `memory.high` or
+    /// `memory.max` is written.
+    ///
+    /// Pages are reclaimed from the cgroup until the usage is within both
+    /// limits. If the usage still exceeds `memory.max`, the OOM killer is
+    /// invoked to kill processes in the cgroup. The writer stops early if it
+    /// has pending signals.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/memcontrol.c>
+    pub fn reduce_memory_usage(self: &Arc<Self>) {
+        let memory = MemoryController::read_from(self.controller());
+        let Some(inner) = memory.inner.as_ref() else {
+            return;
+        };
+        let memcg = MemCgroup(memory.clone());
+        let current_thread = current_thread!();
+        let current_posix_thread = current_thread.as_posix_thread().unwrap();
+
+        let mut nr_retries = 0;
+        loop {
+            let usage = inner.usage.load(Ordering::Relaxed);
+            let max = inner.max.load(Ordering::Relaxed);
+            let limit = inner.high.load(Ordering::Relaxed).min(max);
+            if usage <= limit || current_posix_thread.has_pending() {
+                return;
+            }
+
+            if nr_retries < MAX_RECLAIM_RETRIES {
+                if reclaim::reclaim_memcg(&memcg, usage - limit, true) == 0 {
+                    nr_retries += 1;
+                }
+                continue;
+            }
+
+            // Exceeding `memory.high` only causes throttling.
+            if usage <= max {
+                return;
+            }
+            let constraint = OomConstraint::Cgroup {
+                cgroup: self.clone(),
+                limit: max,
+            };
+            if !out_of_memory(&constraint) {
+                return;
+            }
+        }
+    }
+}
+
+/// Throttles the current process if its memory cgroup exceeds `memory.high`.
+///
+/// The process first reclaims pages from each cgroup that exceeds the limit.
+/// If the usage still exceeds the limit, the process sleeps for a while, which
+/// grows quadratically with the overage.
+///
+/// This should be called before returning to the user space, where no locks
+/// are held, if a charge of the current thread has exceeded `memory.high`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/memcontrol.c>
+pub fn throttle_memory_over_high(process: &Process) {
+    let memory = {
+        let cgroup_guard = process.cgroup();
+        let Some(cgroup) = cgroup_guard.get() else {
+            return;
+        };
+        MemoryController::read_from(cgroup.controller())
+    };
+
+    // Returns the largest ratio of the overage to `memory.high` in the
+    // hierarchy, which is in units of 1/1024.
+    let max_overage_ratio = || {
+        memory
+            .iter_hierarchy()
+            .filter_map(MemoryController::high_overage)
+            .map(|(overage, high)| overage.saturating_mul(1024) / high.max(1))
+            .max()
+    };
+
+    for _ in 0..MAX_HIGH_RECLAIM_ROUNDS {
+        if max_overage_ratio().is_none() || reclaim_high(&memory) == 0 {
+            break;
+        }
+    }
+    let Some(overage_ratio) = max_overage_ratio() else {
+        return;
+    };
+
+    let max_delay_ms = MAX_HIGH_DELAY.as_millis() as usize;
+    let delay_ms = overage_ratio
+        .saturating_mul(overage_ratio)
+        .saturating_mul(max_delay_ms)
+        >> 20;
+    let delay = Duration::from_millis(delay_ms.min(max_delay_ms) as u64);
+    if delay < MIN_HIGH_DELAY {
+        return;
+    }
+
+    // The sleep can be interrupted by signals, e.g., by `SIGKILL`.
+    let _ = Waiter::new_pair().0.pause_timeout(&(&delay).into());
+}
+
+/// Reclaims pages from `memory` and its ancestors that exceed `memory.high`.
+///
+/// Returns the number of reclaimed pages.
+fn reclaim_high(memory: &Arc<super::SubController<MemoryController>>) -> usize {
+    let mut nr_reclaimed = 0;
+
+    let mut current = memory.clone();
+    // The root cgroup is the only one without a parent, and it is not limited.
+    while let Some(parent) = current.parent.clone() {
+        if let Some((overage, _)) = current
+            .inner
+            .as_ref()
+            .and_then(MemoryController::high_overage)
+        {
+            nr_reclaimed += reclaim::reclaim_memcg(&MemCgroup(current.clone()), overage, true);
+        }
+        current = parent;
+    }
+
+    nr_reclaimed
+}
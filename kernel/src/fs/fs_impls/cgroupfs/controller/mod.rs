@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{collections::vec_deque::VecDeque, sync::Arc};
+use alloc::{collections::vec_deque::VecDeque, sync::Arc, vec::Vec};
 use core::{
     fmt::Display,
     str::FromStr,
@@ -15,18 +15,25 @@ use ostd::{
     sync::Rcu,
 };
 
-use crate::fs::cgroupfs::{
-    CgroupMembership, CgroupNode,
-    controller::{
-        cpu::CpuController, cpuset::CpuSetController, memory::MemoryController,
-        pids::PidsController,
+use crate::{
+    fs::cgroupfs::{
+        CgroupMembership, CgroupNode, CgroupSystem,
+        controller::{
+            cpu::CpuController,
+            cpuset::{CpuSetController, update_cpu_affinity},
+            io::IoController,
+            memory::MemoryController,
+            pids::PidsController,
+        },
+        systree_node::CgroupSysNode,
     },
-    systree_node::CgroupSysNode,
+    process::{Process, pid_table},
 };
 
 pub(super) mod cpu;
-mod cpuset;
-mod memory;
+pub(super) mod cpuset;
+pub(super) mod io;
+pub(super) mod memory;
 mod pids;
 
 /// A trait to abstract all individual cgroup sub-controllers.
@@ -50,6 +57,11 @@ trait SubControlStatic: SubControl + Sized + 'static {
 
     /// Reads and clones the `Arc` of this sub-controller in the given `Controller`.
     fn read_from(controller: &Controller) -> Arc<SubController<Self>>;
+
+    /// Initializes the states that are inherited from the parent sub-controller.
+    ///
+    /// This is called when an active sub-controller is created.
+    fn inherit_from(&self, _parent: &SubController<Self>) {}
 }
 
 /// The type of a sub-controller in the cgroup subsystem.
@@ -57,18 +69,20 @@ trait SubControlStatic: SubControl + Sized + 'static {
 pub(super) enum SubCtrlType {
     CpuSet,
     Cpu,
+    Io,
     Memory,
     Pids,
 }
 
 impl SubCtrlType {
     // Keep this in the Linux-visible controller order used by `Display`.
-    const ALL: [Self; 4] = [Self::CpuSet, Self::Cpu, Self::Memory, Self::Pids];
+    const ALL: [Self; 5] = [Self::CpuSet, Self::Cpu, Self::Io, Self::Memory, Self::Pids];
 
     const fn as_str(self) -> &'static str {
         match self {
             Self::CpuSet => "cpuset",
             Self::Cpu => "cpu",
+            Self::Io => "io",
             Self::Memory => "memory",
             Self::Pids => "pids",
         }
@@ -82,6 +96,7 @@ impl FromStr for SubCtrlType {
         match s {
             "cpuset" => Ok(SubCtrlType::CpuSet),
             "cpu" => Ok(SubCtrlType::Cpu),
+            "io" => Ok(SubCtrlType::Io),
             "memory" => Ok(SubCtrlType::Memory),
             "pids" => Ok(SubCtrlType::Pids),
             _ => Err(Error::NotFound),
@@ -96,6 +111,7 @@ bitflags! {
         const CPU = 1 << 1;
         const MEMORY = 1 << 2;
         const PIDS = 1 << 3;
+        const IO = 1 << 4;
     }
 }
 
@@ -142,6 +158,7 @@ impl From<SubCtrlType> for SubCtrlSet {
         match ctrl_type {
             SubCtrlType::CpuSet => Self::CPUSET,
             SubCtrlType::Cpu => Self::CPU,
+            SubCtrlType::Io => Self::IO,
             SubCtrlType::Memory => Self::MEMORY,
             SubCtrlType::Pids => Self::PIDS,
         }
@@ -195,6 +212,9 @@ impl<T: SubControlStatic> SubController<T> {
         };
 
         let parent = parent_controller.map(T::read_from);
+        if let (Some(inner), Some(parent)) = (inner.as_ref(), parent.as_ref()) {
+            inner.inherit_from(parent);
+        }
 
         Self { inner, parent }
     }
@@ -231,6 +251,7 @@ pub struct Controller {
 
     cpuset: Rcu<Arc<SubController<CpuSetController>>>,
     cpu: Rcu<Arc<SubController<CpuController>>>,
+    io: Rcu<Arc<SubController<IoController>>>,
     memory: Rcu<Arc<SubController<MemoryController>>>,
     pids: Rcu<Arc<SubController<PidsController>>>,
 }
@@ -240,6 +261,7 @@ impl Controller {
     pub(super) fn new(parent_controller: Option<&Controller>) -> Self {
         let cpuset_controller = Arc::new(SubController::new(parent_controller));
         let cpu_controller = Arc::new(SubController::new(parent_controller));
+        let io_controller = Arc::new(SubController::new(parent_controller));
         let memory_controller = Arc::new(SubController::new(parent_controller));
         let pids_controller = Arc::new(SubController::new(parent_controller));
 
@@ -247,6 +269,7 @@ impl Controller {
             active_set: AtomicSubCtrlSet::new(SubCtrlSet::empty()),
             cpuset: Rcu::new(cpuset_controller),
             cpu: Rcu::new(cpu_controller),
+            io: Rcu::new(io_controller),
             memory: Rcu::new(memory_controller),
             pids: Rcu::new(pids_controller),
         }
@@ -255,6 +278,7 @@ impl Controller {
     pub(super) fn init_attr_set(builder: &mut SysAttrSetBuilder, is_root: bool) {
         CpuSetController::init_attr_set(builder, is_root);
         CpuController::init_attr_set(builder, is_root);
+        IoController::init_attr_set(builder, is_root);
         MemoryController::init_attr_set(builder, is_root);
         PidsController::init_attr_set(builder, is_root);
     }
@@ -263,6 +287,7 @@ impl Controller {
         match ctrl_type {
             SubCtrlType::CpuSet => CpuSetController::read_from(self),
             SubCtrlType::Cpu => CpuController::read_from(self),
+            SubCtrlType::Io => IoController::read_from(self),
             SubCtrlType::Memory => MemoryController::read_from(self),
             SubCtrlType::Pids => PidsController::read_from(self),
         }
@@ -413,6 +438,10 @@ impl Controller {
                     }
                     child_node.controller().cpu.update(Arc::new(new_controller));
                 }
+                SubCtrlType::Io => {
+                    let new_controller = Arc::new(SubController::new(Some(parent_controller)));
+                    child_node.controller().io.update(new_controller);
+                }
                 SubCtrlType::Memory => {
                     let new_controller = Arc::new(SubController::new(Some(parent_controller)));
                     child_node.controller().memory.update(new_controller);
@@ -471,6 +500,10 @@ impl Controller {
                 Some(())
             });
         }
+
+        if ctrl_type == SubCtrlType::CpuSet {
+            Self::update_cpusets(cgroup_membership);
+        }
     }
 
     pub(super) fn active_set(&self) -> SubCtrlSet {
@@ -478,6 +511,42 @@ impl Controller {
     }
 }
 
+// For cpuset sub-controller
+impl Controller {
+    /// Updates the partitions and the effective CPUs of the cpuset sub-controllers
+    /// in the whole hierarchy, and restricts the CPU affinity of all processes
+    /// accordingly.
+    ///
+    /// This should be called after the requested CPUs or partitions of any cgroup
+    /// change, or after the cpuset sub-control is activated or deactivated.
+    pub(super) fn update_cpusets(_cgroup_membership: &mut CgroupMembership) {
+        let mut descents: VecDeque<Arc<dyn CgroupSysNode>> = VecDeque::new();
+        descents.push_back(CgroupSystem::singleton().clone());
+
+        // Update the cgroups from top to bottom, since the effective CPUs of a
+        // cgroup depend on those of its parent.
+        while let Some(node) = descents.pop_front() {
+            let mut children = Vec::new();
+            node.visit_children_with(0, &mut |child_node| {
+                let child_node = Arc::downcast::<CgroupNode>(child_node.clone()).unwrap();
+                children.push(CpuSetController::read_from(child_node.controller()));
+                descents.push_back(child_node);
+                Some(())
+            });
+
+            CpuSetController::read_from(node.controller()).update_children(&children);
+        }
+
+        // The processes that are forked concurrently inherit the CPU affinity
+        // of their parents. This is fine because forking holds the read lock of
+        // `CgroupMembership`, so the parents have already been updated.
+        let processes: Vec<Arc<Process>> = pid_table::pid_table_mut().iter_processes().collect();
+        for process in processes {
+            update_cpu_affinity(&process);
+        }
+    }
+}
+
 // For pids sub-controller
 impl Controller {
     /// Charges a process in the pids sub-controller hierarchy.
@@ -1,7 +1,13 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub use cgroup_ns::CgroupNamespace;
-pub use controller::cpu::{CpuStatKind, charge_cpu_time};
+pub use controller::{
+    cpu::{CpuStatKind, charge_cpu_time},
+    cpuset::{cpuset_cpus_allowed, update_cpu_affinity},
+    memory::{
+        MemCgroup, MemCharge, MemChargeKind, MemProtection, MemoryEvent, throttle_memory_over_high,
+    },
+};
 use fs::CgroupFsType;
 pub(in crate::fs) use systree_node::CgroupSystem;
 pub use systree_node::{CgroupMembership, CgroupNode, CgroupSysNode};
@@ -23,4 +29,5 @@ mod systree_node;
 // _after_ `aster_systree::init`.
 pub(super) fn init() {
     crate::fs::vfs::registry::register(&CgroupFsType).unwrap();
+    controller::io::init();
 }
@@ -51,7 +51,7 @@
 
 use core::{
     str::FromStr,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
 use aster_systree::{
@@ -64,9 +64,16 @@ use ostd::sync::{RwMutexReadGuard, RwMutexWriteGuard};
 use spin::Once;
 
 use crate::{
-    fs::cgroupfs::controller::{Controller, PidsPreCharge, SubCtrlSet, SubCtrlType},
+    fs::cgroupfs::controller::{
+        Controller, PidsPreCharge, SubCtrlSet, SubCtrlType, cpuset::update_cpu_affinity,
+    },
     prelude::*,
-    process::{Pid, Process, pid_table},
+    process::{
+        Pid, Process, pid_table,
+        posix_thread::AsPosixThread,
+        signal::{constants::SIGKILL, signals::kernel::KernelSignal},
+    },
+    thread::AsThread,
 };
 
 /// A type that provides synchronized access to cgroup membership and sub-controller state.
@@ -118,30 +125,46 @@ impl CgroupMembership {
     /// processes.
     pub(super) fn count_subtree_processes(&mut self, cgroup_node: &CgroupNode) -> u32 {
         let mut total: u32 = 0;
-        let mut stack: Vec<Arc<dyn SysObj>> = vec![];
-
-        total += cgroup_node
-            .with_inner(|procs| procs.len() as u32)
-            .unwrap_or(0);
-        cgroup_node.visit_children_with(0, &mut |child| {
-            stack.push(child.clone());
-            Some(())
+        cgroup_node.for_each_in_subtree(|node| {
+            total += node.with_inner(|procs| procs.len() as u32).unwrap_or(0);
         });
 
-        while let Some(node) = stack.pop() {
-            let cgroup_node = Arc::downcast::<CgroupNode>(node).unwrap();
-
-            total += cgroup_node
-                .with_inner(|procs| procs.len() as u32)
-                .unwrap_or(0);
+        total
+    }
 
-            cgroup_node.visit_children_with(0, &mut |child| {
-                stack.push(child.clone());
-                Some(())
-            });
-        }
+    /// Sends `SIGKILL` to every process in a cgroup subtree.
+    ///
+    /// Since forking takes the read side of the membership lock, no processes
+    /// can join the subtree while the write side is held. A process that
+    /// forks afterwards will find its pending `SIGKILL` and fail.
+    fn kill_subtree_processes(&mut self, cgroup_node: &CgroupNode) {
+        cgroup_node.for_each_in_subtree(|node| {
+            for process in node.processes() {
+                process.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+            }
+        });
+    }
 
-        total
+    /// Sets whether a cgroup node is frozen by its `cgroup.freeze`.
+    ///
+    /// The freezing states of its descendants are updated accordingly, and
+    /// the threads whose freezing states change are woken up, so that they
+    /// can be parked or resumed.
+    fn set_freeze(&mut self, cgroup_node: &CgroupNode, freeze: bool) {
+        cgroup_node.self_freeze.store(freeze, Ordering::Relaxed);
+
+        cgroup_node.for_each_in_subtree(|node| {
+            let is_parent_freezing = node.depth > 1
+                && Arc::downcast::<CgroupNode>(node.parent().unwrap())
+                    .unwrap()
+                    .is_freezing();
+            let is_freezing = node.self_freeze.load(Ordering::Relaxed) || is_parent_freezing;
+            if node.is_freezing.swap(is_freezing, Ordering::Relaxed) != is_freezing {
+                for process in node.processes() {
+                    wake_up_for_freezer(&process);
+                }
+            }
+        });
     }
 
     /// Moves a process to the new cgroup node via explicit migration.
@@ -154,6 +177,7 @@ impl CgroupMembership {
         process: Arc<Process>,
         new_cgroup: &CgroupNode,
     ) -> Result<()> {
+        let was_freezing = process.is_freezing();
         let old_cgroup = if let Some(old_cgroup) = process.cgroup().get() {
             // Fast path: If the process is already in this cgroup, do nothing.
             if new_cgroup.id() == old_cgroup.id() {
@@ -187,6 +211,12 @@ impl CgroupMembership {
             old_cgroup.controller.uncharge_pids();
         }
 
+        update_cpu_affinity(&process);
+
+        if process.is_freezing() != was_freezing {
+            wake_up_for_freezer(&process);
+        }
+
         Ok(())
     }
 
@@ -241,6 +271,7 @@ impl CgroupMembership {
             return;
         };
 
+        let was_freezing = old_cgroup.is_freezing();
         process.set_cgroup(None);
 
         old_cgroup
@@ -257,6 +288,12 @@ impl CgroupMembership {
 
         // Uncharge the pids sub-controller for the old cgroup.
         old_cgroup.controller.uncharge_pids();
+
+        update_cpu_affinity(process);
+
+        if was_freezing {
+            wake_up_for_freezer(process);
+        }
     }
 }
 
@@ -304,6 +341,13 @@ pub struct CgroupNode {
     /// either on itself or in any of its descendant nodes. Consequently,
     /// a count > 0 indicates that this node is populated.
     populated_count: AtomicUsize,
+    /// Whether this node is frozen by its `cgroup.freeze`.
+    self_freeze: AtomicBool,
+    /// Whether this node is being frozen, either by itself or by one of its
+    /// ancestors.
+    ///
+    /// This is only updated with the write side of the membership lock held.
+    is_freezing: AtomicBool,
 }
 
 impl Debug for CgroupNode {
@@ -312,6 +356,7 @@ impl Debug for CgroupNode {
             .field("fields", &self.fields)
             .field("populated_count", &self.populated_count)
             .field("depth", &self.depth)
+            .field("is_freezing", &self.is_freezing)
             .finish_non_exhaustive()
     }
 }
@@ -388,7 +433,12 @@ impl CgroupSysNode for CgroupSystem {
 }
 
 impl CgroupNode {
-    pub(self) fn new(name: SysStr, depth: usize, parent_controller: &Controller) -> Arc<Self> {
+    pub(self) fn new(
+        name: SysStr,
+        depth: usize,
+        parent_controller: &Controller,
+        is_parent_freezing: bool,
+    ) -> Arc<Self> {
         let mut builder = SysAttrSetBuilder::new();
         // TODO: Add more attributes as needed. The normal cgroup node may have
         // more attributes than the unified one.
@@ -404,6 +454,7 @@ impl CgroupNode {
             SysStr::from("cgroup.freeze"),
             SysPerms::DEFAULT_RW_ATTR_PERMS,
         );
+        builder.add(SysStr::from("cgroup.kill"), SysPerms::OWNER_W);
         builder.add(
             SysStr::from("cgroup.max.depth"),
             SysPerms::DEFAULT_RW_ATTR_PERMS,
@@ -432,6 +483,8 @@ impl CgroupNode {
                 inner: RwMutex::new(Some(Inner::default())),
                 depth,
                 populated_count: AtomicUsize::new(0),
+                self_freeze: AtomicBool::new(false),
+                is_freezing: AtomicBool::new(is_parent_freezing),
             }
         })
     }
@@ -489,6 +542,52 @@ impl CgroupNode {
         }
     }
 
+    /// Returns whether this node is `ancestor` itself or one of its descendants.
+    pub fn is_same_or_descendant_of(&self, ancestor: &CgroupNode) -> bool {
+        if self.depth <= ancestor.depth {
+            return core::ptr::eq(self, ancestor);
+        }
+
+        let mut current = Arc::downcast::<CgroupNode>(self.parent().unwrap()).unwrap();
+        while current.depth > ancestor.depth {
+            current = Arc::downcast::<CgroupNode>(current.parent().unwrap()).unwrap();
+        }
+
+        core::ptr::eq(current.as_ref(), ancestor)
+    }
+
+    /// Returns the processes bound to this node.
+    fn processes(&self) -> Vec<Arc<Process>> {
+        self.with_inner(|processes| processes.values().filter_map(Weak::upgrade).collect())
+            .unwrap_or_default()
+    }
+
+    /// Calls `op` on this node and each of its descendants.
+    ///
+    /// A node is always visited before its children.
+    fn for_each_in_subtree<F>(&self, mut op: F)
+    where
+        F: FnMut(&CgroupNode),
+    {
+        let mut stack: Vec<Arc<dyn SysObj>> = vec![];
+
+        op(self);
+        self.visit_children_with(0, &mut |child| {
+            stack.push(child.clone());
+            Some(())
+        });
+
+        while let Some(node) = stack.pop() {
+            let cgroup_node = Arc::downcast::<CgroupNode>(node).unwrap();
+
+            op(&cgroup_node);
+            cgroup_node.visit_children_with(0, &mut |child| {
+                stack.push(child.clone());
+                Some(())
+            });
+        }
+    }
+
     /// Performs a read-only operation on the inner data.
     ///
     /// If the cgroup node is dead, returns `None`.
@@ -546,6 +645,47 @@ impl CgroupNode {
     }
 }
 
+// For the freezer
+impl CgroupNode {
+    /// Returns whether this node is being frozen.
+    ///
+    /// The threads in a freezing cgroup are parked before returning to user
+    /// space, until the cgroup is thawed.
+    pub fn is_freezing(&self) -> bool {
+        self.is_freezing.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether this node is frozen.
+    ///
+    /// A node is frozen if it is being frozen and all the threads in its
+    /// subtree have been parked.
+    fn is_frozen(&self) -> bool {
+        if !self.is_freezing() {
+            return false;
+        }
+
+        let mut is_frozen = true;
+        self.for_each_in_subtree(|node| {
+            is_frozen &= node.processes().iter().all(|process| {
+                process.tasks().lock().as_slice().iter().all(|task| {
+                    task.as_thread().unwrap().is_exited()
+                        || task.as_posix_thread().unwrap().is_frozen()
+                })
+            });
+        });
+
+        is_frozen
+    }
+}
+
+/// Wakes up the threads of `process`, so that they can be parked or resumed
+/// after the freezing state of the process changes.
+fn wake_up_for_freezer(process: &Process) {
+    for task in process.tasks().lock().as_slice() {
+        task.as_posix_thread().unwrap().wake_signalled_waker();
+    }
+}
+
 inherit_sys_branch_node!(CgroupSystem, fields, {
     fn is_root(&self) -> bool {
         true
@@ -570,10 +710,13 @@ inherit_sys_branch_node!(CgroupSystem, fields, {
                 writeln!(printer, "{}", SubCtrlSet::all())?;
             }
             "cgroup.procs" => {
+                let pid_ns = current!().pid_ns().clone();
                 let pid_table = pid_table::pid_table_mut();
                 for process in pid_table.iter_processes() {
-                    if process.cgroup().is_none() {
-                        writeln!(printer, "{}", process.pid())?;
+                    if process.cgroup().is_none()
+                        && let Some(pid) = pid_ns.local_id_of(process.pid())
+                    {
+                        writeln!(printer, "{}", pid)?;
                     }
                 }
             }
@@ -635,7 +778,7 @@ inherit_sys_branch_node!(CgroupSystem, fields, {
         // The child node's content depends on our sub-controller activation
         // state. Take a read lock to prevent any state changes.
         let _cgroup_read_guard = CgroupMembership::read_lock();
-        let new_child = CgroupNode::new(name.to_string().into(), 1, &self.controller);
+        let new_child = CgroupNode::new(name.to_string().into(), 1, &self.controller, false);
         self.add_child(new_child.clone())?;
         Ok(new_child)
     }
@@ -666,8 +809,9 @@ inherit_sys_branch_node!(CgroupNode, fields, {
                 })
                 .ok_or(Error::IsDead)?
             }
-            "cgroup.events" => self
-                .with_inner(|_| {
+            "cgroup.events" => {
+                let is_frozen = self.is_frozen();
+                self.with_inner(|_| {
                     let res = if self.populated_count.load(Ordering::Relaxed) > 0 {
                         1
                     } else {
@@ -675,24 +819,30 @@ inherit_sys_branch_node!(CgroupNode, fields, {
                     };
 
                     writeln!(printer, "populated {}", res)?;
-                    // Currently we have not enabled writing to the "cgroup.freeze"
-                    // attribute so the "frozen" field is always zero.
-                    writeln!(printer, "frozen {}", 0)?;
+                    writeln!(printer, "frozen {}", is_frozen as u8)?;
 
                     Ok::<usize, Error>(printer.bytes_written())
                 })
-                .ok_or(Error::IsDead)?,
+                .ok_or(Error::IsDead)?
+            }
             "cgroup.freeze" => self
                 .with_inner(|_| {
-                    writeln!(printer, "0")?;
+                    writeln!(
+                        printer,
+                        "{}",
+                        self.self_freeze.load(Ordering::Relaxed) as u8
+                    )?;
 
                     Ok::<usize, Error>(printer.bytes_written())
                 })
                 .ok_or(Error::IsDead)?,
             "cgroup.procs" => self
                 .with_inner(|processes| {
+                    let pid_ns = current!().pid_ns().clone();
                     for pid in processes.keys() {
-                        writeln!(printer, "{}", pid)?;
+                        if let Some(pid) = pid_ns.local_id_of(*pid) {
+                            writeln!(printer, "{}", pid)?;
+                        }
                     }
 
                     Ok::<usize, Error>(printer.bytes_written())
@@ -768,6 +918,55 @@ inherit_sys_branch_node!(CgroupNode, fields, {
                 })
                 .ok_or(Error::IsDead)?
             }
+            "cgroup.freeze" => {
+                let (freeze, len) = read_bool_from_reader(reader)?;
+
+                let mut cgroup_guard = CgroupMembership::write_lock();
+                if self.with_inner(|_| ()).is_none() {
+                    return Err(Error::IsDead);
+                }
+                cgroup_guard.set_freeze(self, freeze);
+
+                Ok(len)
+            }
+            "cgroup.kill" => {
+                let (kill, len) = read_bool_from_reader(reader)?;
+                if !kill {
+                    return Err(Error::InvalidOperation);
+                }
+
+                let mut cgroup_guard = CgroupMembership::write_lock();
+                if self.with_inner(|_| ()).is_none() {
+                    return Err(Error::IsDead);
+                }
+                cgroup_guard.kill_subtree_processes(self);
+
+                Ok(len)
+            }
+            "cpuset.cpus" | "cpuset.cpus.partition" => {
+                // Hold the write lock to serialize the updates of cpusets with
+                // process migration.
+                let mut cgroup_guard = CgroupMembership::write_lock();
+                let len = self
+                    .with_inner(|_| self.controller.write_attr(name, reader))
+                    .ok_or(Error::IsDead)??;
+                Controller::update_cpusets(&mut cgroup_guard);
+
+                Ok(len)
+            }
+            "memory.high" | "memory.max" => {
+                let len = self
+                    .with_inner(|_| self.controller.write_attr(name, reader))
+                    .ok_or(Error::IsDead)??;
+                // Like Linux, reclaim the pages over the new limit.
+                self.fields
+                    .weak_self()
+                    .upgrade()
+                    .unwrap()
+                    .reduce_memory_usage();
+
+                Ok(len)
+            }
             // TODO: Add support for writing other attributes.
             _ => self
                 // This write may target a stale controller if the cgroup's sub-controllers
@@ -787,8 +986,12 @@ inherit_sys_branch_node!(CgroupNode, fields, {
         // state. Take a read lock to prevent any state changes.
         let _cgroup_read_guard = CgroupMembership::read_lock();
         self.with_inner(|_| {
-            let new_child =
-                CgroupNode::new(name.to_string().into(), self.depth + 1, &self.controller);
+            let new_child = CgroupNode::new(
+                name.to_string().into(),
+                self.depth + 1,
+                &self.controller,
+                self.is_freezing(),
+            );
             self.add_child(new_child.clone())?;
             Ok(new_child as _)
         })
@@ -808,11 +1011,14 @@ fn with_process_cgroup_locked<F>(pid: Pid, op: F) -> Result<()>
 where
     F: FnOnce(Arc<Process>, &mut CgroupMembership) -> Result<()>,
 {
+    let current = current!();
     let process = if pid == 0 {
-        current!()
+        current
     } else {
-        pid_table::pid_table_mut()
-            .get_process(pid)
+        current
+            .pid_ns()
+            .global_id_of(pid)
+            .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
             .ok_or(Error::InvalidOperation)?
     };
 
@@ -824,6 +1030,22 @@ where
     op(process, &mut cgroup_guard)
 }
 
+/// Reads a boolean value, which is either "0" or "1", from the given reader.
+///
+/// Returns the value along with the number of bytes read.
+fn read_bool_from_reader(reader: &mut VmReader) -> Result<(bool, usize)> {
+    let (content, len) = reader
+        .read_cstring_until_end(MAX_ATTR_SIZE)
+        .map_err(|_| Error::PageFault)?;
+    let value = match content.to_str().map(str::trim) {
+        Ok("0") => false,
+        Ok("1") => true,
+        _ => return Err(Error::InvalidOperation),
+    };
+
+    Ok((value, len))
+}
+
 /// Reads the actions for sub-control from the given reader.
 ///
 /// Returns the sets of controllers to be activated and deactivated,
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        ramfs::RamFs,
+        vfs::{
+            file_system::FileSystem,
+            registry::{FsCreationCtx, FsProperties, FsType},
+        },
+    },
+    prelude::*,
+};
+
+/// The huge page file system (hugetlbfs) structure.
+//
+// TODO: `HugetlbFs` currently aliases `RamFs` and relies on
+// `RamFs::new_hugetlbfs()` to create hugetlbfs-flavored ramfs instances. Mount
+// options such as `pagesize`, `size`, and `nr_inodes` are not supported yet.
+pub type HugetlbFs = RamFs;
+
+pub(super) struct HugetlbFsType;
+
+impl FsType for HugetlbFsType {
+    fn name(&self) -> &'static str {
+        "hugetlbfs"
+    }
+
+    fn properties(&self) -> FsProperties {
+        FsProperties::empty()
+    }
+
+    fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        Ok(HugetlbFs::new_hugetlbfs()?)
+    }
+
+    fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
+        None
+    }
+}
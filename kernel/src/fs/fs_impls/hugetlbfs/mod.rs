@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Huge page file system (hugetlbfs) based on ramfs.
+//!
+//! The regular files in hugetlbfs are backed by huge pages from the hugetlb
+//! pool (see [`crate::vm::hugetlb`]). They can be mapped and read, but not
+//! written with `write`.
+
+use fs::HugetlbFsType;
+
+mod fs;
+
+pub(super) const HUGETLBFS_MAGIC: u64 = 0x9584_58f6;
+
+pub(super) fn init() {
+    crate::fs::vfs::registry::register(&HugetlbFsType).unwrap();
+}
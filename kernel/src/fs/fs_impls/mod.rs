@@ -4,11 +4,14 @@
 //!
 //! This module contains all the specific file system implementations supported by the kernel.
 
+pub mod binfmt_misc;
 pub mod cgroupfs;
 pub mod configfs;
 pub mod devpts;
 pub mod exfat;
 pub mod ext2;
+pub mod hugetlbfs;
+pub mod mqueuefs;
 pub mod overlayfs;
 pub mod procfs;
 pub mod pseudofs;
@@ -21,10 +24,13 @@ pub(super) fn init() {
     sysfs::init();
     procfs::init();
     cgroupfs::init();
+    binfmt_misc::init();
     configfs::init();
     ramfs::init();
     tmpfs::init();
+    hugetlbfs::init();
     devpts::init();
+    mqueuefs::init();
     pseudofs::init();
 
     ext2::init();
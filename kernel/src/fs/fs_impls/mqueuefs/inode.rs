@@ -0,0 +1,463 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use aster_util::{printer::VmPrinter, slot_vec::SlotVec};
+use inherit_methods_macro::inherit_methods;
+
+use super::{MqueueFs, ROOT_INO};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{AccessMode, InodeMode, InodeType, PerOpenFileOps, StatusFlags, chmod},
+        utils::{DirEntryVecExt, DirentVisitor},
+        vfs::{
+            file_system::{FileSystem, SuperBlock},
+            inode::{Extension, FileOps, Inode, Metadata, MknodType, RevalidationPolicy},
+        },
+    },
+    ipc::mqueue::{MqAttr, QUEUES_MAX, msg_queue::PosixMsgQueue},
+    prelude::*,
+    process::{
+        Gid, Uid,
+        credentials::capabilities::CapSet,
+        signal::{PollHandle, Pollable},
+    },
+    security::lsm::hooks as lsm_hooks,
+    time::clocks::RealTimeCoarseClock,
+};
+
+/// The size of a queue file.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/mqueue.c>
+const FILENT_SIZE: usize = 80;
+
+/// The root directory of the mqueue file system.
+pub(super) struct RootInode {
+    queues: RwMutex<SlotVec<(String, Arc<dyn Inode>)>>,
+    common: InodeCommon,
+}
+
+impl RootInode {
+    pub(super) fn new(fs: Weak<MqueueFs>, sb: &SuperBlock) -> Arc<Self> {
+        let metadata = Metadata::new_dir(
+            ROOT_INO,
+            chmod!(InodeMode::S_ISVTX, a+rwx),
+            PAGE_SIZE,
+            sb.container_dev_id,
+        );
+
+        Arc::new(Self {
+            queues: RwMutex::new(SlotVec::new()),
+            common: InodeCommon::new(metadata, fs),
+        })
+    }
+
+    /// Creates a message queue named `name`.
+    ///
+    /// The new queue is owned by the FS user ID and the FS group ID of the current thread.
+    pub(super) fn create_queue(
+        &self,
+        name: &str,
+        mode: InodeMode,
+        attr: Option<&MqAttr>,
+    ) -> Result<Arc<dyn Inode>> {
+        let fs = self.common.fs.upgrade().unwrap();
+        let current_thread = current_thread!();
+        let posix_thread = current_thread.as_posix_thread().unwrap();
+        let is_privileged = || {
+            lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+                &fs.owner,
+                posix_thread,
+                CapSet::SYS_RESOURCE,
+            ))
+            .is_ok()
+        };
+
+        let mut queues = self.queues.write();
+        if queues.find_entry_by_name(name).is_some() {
+            return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+        }
+        if queues.len() >= QUEUES_MAX && !is_privileged() {
+            return_errno_with_message!(Errno::ENOSPC, "too many message queues");
+        }
+
+        let queue = PosixMsgQueue::new(attr, is_privileged)?;
+
+        let mut metadata =
+            Metadata::new_file(fs.alloc_ino(), mode, PAGE_SIZE, fs.sb.container_dev_id);
+        metadata.size = FILENT_SIZE;
+        let credentials = posix_thread.credentials();
+        metadata.uid = credentials.fsuid();
+        metadata.gid = credentials.fsgid();
+
+        let inode: Arc<dyn Inode> = Arc::new(QueueInode {
+            queue: Arc::new(queue),
+            common: InodeCommon::new(metadata, Arc::downgrade(&fs)),
+        });
+        queues.put((name.to_string(), inode.clone()));
+        drop(queues);
+
+        self.common.touch_mtime_ctime();
+
+        Ok(inode)
+    }
+}
+
+impl FileOps for RootInode {
+    fn read_at(
+        &self,
+        _offset: usize,
+        _writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 2 special entries.
+            if *offset == 0 {
+                visitor.visit(".", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+            if *offset == 1 {
+                visitor.visit("..", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+
+            // Read the queues.
+            let queues = self.queues.read();
+            let start_offset = *offset;
+            for (idx, (name, inode)) in queues
+                .idxes_and_items()
+                .map(|(idx, (name, inode))| (idx + 2, (name, inode)))
+                .skip_while(|(idx, _)| idx < &start_offset)
+            {
+                visitor.visit(name.as_ref(), inode.ino(), inode.type_(), idx)?;
+                *offset = idx + 1;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for RootInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn type_(&self) -> InodeType;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        if type_ != InodeType::File {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "only message queues can be created under mqueue"
+            );
+        }
+
+        self.create_queue(name, mode, None)
+    }
+
+    fn mknod(&self, _name: &str, _mode: InodeMode, _type_: MknodType) -> Result<Arc<dyn Inode>> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn link(&self, _old: &Arc<dyn Inode>, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        let Some((_, inode)) = self.queues.write().remove_entry_by_name(name) else {
+            return_errno_with_message!(Errno::ENOENT, "the message queue does not exist");
+        };
+
+        let inode = inode.downcast_ref::<QueueInode>().unwrap();
+        inode.common.metadata.write().nr_hard_links = 0;
+        self.common.touch_mtime_ctime();
+
+        Ok(())
+    }
+
+    fn rmdir(&self, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::ENOTDIR))
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        self.queues
+            .read()
+            .find_entry_by_name(name)
+            .cloned()
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the message queue does not exist"))
+    }
+
+    fn rename(&self, _old_name: &str, _target: &Arc<dyn Inode>, _new_name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn revalidation_policy(&self) -> RevalidationPolicy {
+        RevalidationPolicy::REVALIDATE_EXISTS | RevalidationPolicy::REVALIDATE_ABSENT
+    }
+
+    fn revalidate_exists(&self, name: &str, child: &dyn Inode) -> bool {
+        // The file system can be mounted multiple times, where each mount has its own dentry
+        // cache. Queues created or removed via one mount are not reflected in the others.
+        self.queues
+            .read()
+            .find_entry_by_name(name)
+            .is_some_and(|inode| inode.ino() == child.ino())
+    }
+
+    fn revalidate_absent(&self, name: &str) -> bool {
+        self.queues.read().find_entry_by_name(name).is_none()
+    }
+}
+
+/// A message queue file.
+struct QueueInode {
+    queue: Arc<PosixMsgQueue>,
+    common: InodeCommon,
+}
+
+impl FileOps for QueueInode {
+    fn read_at(
+        &self,
+        _offset: usize,
+        _writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "the message queue is not opened");
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "the message queue is not opened");
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for QueueInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn type_(&self) -> InodeType;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        return_errno_with_message!(Errno::EINVAL, "message queue files cannot be resized");
+    }
+
+    fn open(
+        &self,
+        _access_mode: AccessMode,
+        _status_flags: StatusFlags,
+    ) -> Option<Result<Box<dyn PerOpenFileOps>>> {
+        let file = MqueueFile {
+            queue: self.queue.clone(),
+        };
+        Some(Ok(Box::new(file)))
+    }
+}
+
+/// An opened message queue.
+pub(super) struct MqueueFile {
+    queue: Arc<PosixMsgQueue>,
+}
+
+impl MqueueFile {
+    /// Returns the message queue.
+    pub(super) fn queue(&self) -> &Arc<PosixMsgQueue> {
+        &self.queue
+    }
+}
+
+impl Pollable for MqueueFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue.poll(mask, poller)
+    }
+}
+
+impl FileOps for MqueueFile {
+    fn read_at(
+        &self,
+        offset: usize,
+        writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let status = self.queue.status(current!().pid_ns());
+        write!(printer, "{}", status)?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "message queue files cannot be written");
+    }
+}
+
+impl PerOpenFileOps for MqueueFile {
+    fn check_seekable(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_offset_aware(&self) -> bool {
+        true
+    }
+}
+
+/// The common parts of the inodes in the mqueue file system.
+struct InodeCommon {
+    metadata: RwLock<Metadata>,
+    extension: Extension,
+    fs: Weak<MqueueFs>,
+}
+
+impl InodeCommon {
+    fn new(metadata: Metadata, fs: Weak<MqueueFs>) -> Self {
+        Self {
+            metadata: RwLock::new(metadata),
+            extension: Extension::new(),
+            fs,
+        }
+    }
+
+    fn touch_mtime_ctime(&self) {
+        let now = RealTimeCoarseClock::get().read_time();
+        let mut metadata = self.metadata.write();
+        metadata.last_modify_at = now;
+        metadata.last_meta_change_at = now;
+    }
+
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn extension(&self) -> &Extension {
+        &self.extension
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        self.metadata.read().type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().last_access_at
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().last_access_at = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().last_modify_at
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().last_modify_at = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().last_meta_change_at
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().last_meta_change_at = time;
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The file system of POSIX message queues.
+//!
+//! Each IPC namespace owns an mqueue file system, where each message queue is represented by a
+//! regular file in the root directory. The `mq_*` system calls operate on the file system through
+//! an internal mount of the IPC namespace. The same file system is also mounted when the user
+//! mounts an mqueue file system, typically at `/dev/mqueue`.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use self::inode::{MqueueFile, RootInode};
+use crate::{
+    fs::{
+        file::{CreationFlags, FileLike, InodeHandle, OpenArgs, Permission},
+        pseudofs::AnonDeviceId,
+        utils::NAME_MAX,
+        vfs::{
+            file_system::{FileSystem, FsEventSubscriberStats, SuperBlock},
+            inode::Inode,
+            path::{Mount, Path, PathResolver},
+            registry::{FsCreationCtx, FsProperties, FsType},
+        },
+    },
+    ipc::mqueue::{MqAttr, msg_queue::PosixMsgQueue},
+    prelude::*,
+    process::UserNamespace,
+};
+
+mod inode;
+
+const MQUEUE_MAGIC: u64 = 0x19800202;
+
+const ROOT_INO: u64 = 1;
+
+/// The mqueue file system.
+pub struct MqueueFs {
+    _anon_device_id: AnonDeviceId,
+    sb: SuperBlock,
+    root: Arc<RootInode>,
+    /// The owner user namespace of the IPC namespace.
+    owner: Arc<UserNamespace>,
+    next_ino: AtomicU64,
+    fs_event_subscriber_stats: FsEventSubscriberStats,
+}
+
+impl MqueueFs {
+    fn new(owner: Arc<UserNamespace>) -> Result<Arc<Self>> {
+        let anon_device_id = AnonDeviceId::acquire().ok_or_else(|| {
+            Error::with_message(Errno::ENODEV, "no device ID is available for mqueue")
+        })?;
+        let sb = SuperBlock::new(MQUEUE_MAGIC, PAGE_SIZE, NAME_MAX, anon_device_id.id());
+
+        Ok(Arc::new_cyclic(|weak_self| Self {
+            _anon_device_id: anon_device_id,
+            sb: sb.clone(),
+            root: RootInode::new(weak_self.clone(), &sb),
+            owner,
+            next_ino: AtomicU64::new(ROOT_INO + 1),
+            fs_event_subscriber_stats: FsEventSubscriberStats::new(),
+        }))
+    }
+
+    fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl FileSystem for MqueueFs {
+    fn name(&self) -> &'static str {
+        "mqueue"
+    }
+
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn fs_event_subscriber_stats(&self) -> &FsEventSubscriberStats {
+        &self.fs_event_subscriber_stats
+    }
+}
+
+struct MqueueFsType;
+
+impl FsType for MqueueFsType {
+    fn name(&self) -> &'static str {
+        "mqueue"
+    }
+
+    fn properties(&self) -> FsProperties {
+        FsProperties::empty()
+    }
+
+    fn create(&self, fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        // Like Linux, mounting an mqueue file system exposes the message queues of the current
+        // IPC namespace.
+        let ns_proxy = fs_creation_ctx.task_ctx().thread_local.borrow_ns_proxy();
+        let ipc_ns = ns_proxy.unwrap().ipc_ns();
+        Ok(ipc_ns.mqueue_mount()?.fs().clone())
+    }
+
+    fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
+        None
+    }
+}
+
+pub(super) fn init() {
+    crate::fs::vfs::registry::register(&MqueueFsType).unwrap();
+}
+
+/// Creates a new mqueue file system and returns its internal mount.
+///
+/// The `owner` is the owner user namespace of the IPC namespace that owns the file system.
+pub fn new_kern_mount(owner: Arc<UserNamespace>) -> Result<Arc<Mount>> {
+    Mount::new_pseudo(MqueueFs::new(owner)?)
+}
+
+/// Opens the message queue named `name` in the mqueue file system of `mount`.
+///
+/// If the queue does not exist and `O_CREAT` is specified, a new queue is created with `attr`, or
+/// with the default attributes if `attr` is `None`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/mqueue.c>
+pub fn open_queue(
+    path_resolver: &PathResolver,
+    mount: &Arc<Mount>,
+    name: &str,
+    open_args: &OpenArgs,
+    attr: Option<&MqAttr>,
+) -> Result<InodeHandle> {
+    check_name(name)?;
+
+    let root_path = Path::new_fs_root(mount.clone());
+    let creation_flags = open_args.creation_flags;
+
+    match path_resolver.lookup_at_path(&root_path, name) {
+        Ok(path) => {
+            if creation_flags.contains(CreationFlags::O_CREAT | CreationFlags::O_EXCL) {
+                return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+            }
+            InodeHandle::new(path, open_args.access_mode, open_args.status_flags)
+        }
+        Err(err)
+            if err.error() == Errno::ENOENT && creation_flags.contains(CreationFlags::O_CREAT) =>
+        {
+            let root_inode = root_path.inode();
+            root_inode.check_permission(Permission::MAY_WRITE | Permission::MAY_EXEC)?;
+            root_inode
+                .downcast_ref::<RootInode>()
+                .unwrap()
+                .create_queue(name, open_args.inode_mode, attr)?;
+
+            // The creator can always open the new queue, regardless of its mode.
+            let path = path_resolver.lookup_at_path(&root_path, name)?;
+            InodeHandle::new_unchecked_access(path, open_args.access_mode, open_args.status_flags)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the message queue opened as `file`.
+pub fn queue_of(file: &dyn FileLike) -> Result<&Arc<PosixMsgQueue>> {
+    let mqueue_file = file
+        .downcast_ref::<InodeHandle>()
+        .and_then(|inode_handle| inode_handle.downcast_open_file::<MqueueFile>().transpose())
+        .transpose()?
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a message queue"))?;
+
+    Ok(mqueue_file.queue())
+}
+
+/// Removes the message queue named `name` from the mqueue file system of `mount`.
+///
+/// The queue is destroyed after all its descriptors are closed.
+pub fn unlink_queue(mount: &Arc<Mount>, name: &str) -> Result<()> {
+    check_name(name)?;
+
+    Path::new_fs_root(mount.clone()).unlink(name)
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "the message queue name is empty");
+    }
+    if name == "." || name == ".." || name.contains('/') {
+        return_errno_with_message!(Errno::EACCES, "the message queue name is invalid");
+    }
+    if name.len() > NAME_MAX {
+        return_errno_with_message!(Errno::ENAMETOOLONG, "the message queue name is too long");
+    }
+
+    Ok(())
+}
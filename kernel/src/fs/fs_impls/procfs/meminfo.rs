@@ -35,20 +35,45 @@ impl ProcFileOps for MemInfoFileOps {
 
         // The total amount of physical memory available to the system.
         let total = crate::vm::mem_total();
+        // The amount of physical memory not used by the system.
+        let free = osdk_frame_allocator::load_total_free_size();
         // An estimation of how much memory is available for starting new
-        // applications, without disk operations.
-        let available = osdk_frame_allocator::load_total_free_size();
+        // applications, without swapping. It includes both free memory and
+        // file pages that can be reclaimed.
+        // The amount of physical memory used by the page cache.
+        let cached = crate::vm::reclaim::nr_file_pages() * PAGE_SIZE;
+        let available = (free + cached).min(total);
 
         // Convert the values to KiB.
         let total = total / 1024;
+        let free = free / 1024;
         let available = available / 1024;
+        let cached = cached / 1024;
+        let swap_total = crate::vm::swap::nr_total_pages() * (PAGE_SIZE / 1024);
+        let swap_free = crate::vm::swap::nr_free_pages() * (PAGE_SIZE / 1024);
+        let anon_huge_pages = crate::vm::thp::nr_anon_huge_pages() * (PAGE_SIZE / 1024);
 
-        // Available memory should include both free memory and cached pages that can be
-        // immediately evicted from main memory. Currently, no pages can be evicted when memory is
-        // allocated, resulting in the two values being reported as the same.
         writeln!(printer, "MemTotal:\t{} kB", total)?;
-        writeln!(printer, "MemFree:\t{} kB", available)?;
+        writeln!(printer, "MemFree:\t{} kB", free)?;
         writeln!(printer, "MemAvailable:\t{} kB", available)?;
+        writeln!(printer, "Cached:\t{} kB", cached)?;
+        writeln!(printer, "SwapTotal:\t{} kB", swap_total)?;
+        writeln!(printer, "SwapFree:\t{} kB", swap_free)?;
+        writeln!(printer, "AnonHugePages:\t{} kB", anon_huge_pages)?;
+
+        if let Some(huge_page_size) = crate::vm::hugetlb::huge_page_size() {
+            let nr_huge_pages = crate::vm::hugetlb::nr_huge_pages();
+            let nr_free_huge_pages = crate::vm::hugetlb::nr_free_huge_pages();
+            let huge_page_size = huge_page_size / 1024;
+
+            writeln!(printer, "HugePages_Total:\t{}", nr_huge_pages)?;
+            writeln!(printer, "HugePages_Free:\t{}", nr_free_huge_pages)?;
+            // Huge pages are neither reserved nor surplus. See `crate::vm::hugetlb`.
+            writeln!(printer, "HugePages_Rsvd:\t0")?;
+            writeln!(printer, "HugePages_Surp:\t0")?;
+            writeln!(printer, "Hugepagesize:\t{} kB", huge_page_size)?;
+            writeln!(printer, "Hugetlb:\t{} kB", nr_huge_pages * huge_page_size)?;
+        }
 
         Ok(printer.bytes_written())
     }
@@ -16,7 +16,9 @@ use self::{
     mounts::MountsSymOps,
     pid::{PidDirOps, TidDirOps},
     self_::SelfSymOps,
+    swaps::SwapsFileOps,
     sys::SysDirOps,
+    sysvipc::SysvIpcDirOps,
     thread_self::ThreadSelfSymOps,
     uptime::UptimeFileOps,
     version::VersionFileOps,
@@ -35,7 +37,7 @@ use crate::{
     },
     prelude::*,
     process::{
-        Pid,
+        Pid, PidNamespace, Process,
         pid_table::{self, PidEntryType},
     },
 };
@@ -49,7 +51,9 @@ mod mounts;
 mod pid;
 mod self_;
 mod stat;
+mod swaps;
 mod sys;
+mod sysvipc;
 mod template;
 mod thread_self;
 mod uptime;
@@ -76,24 +80,41 @@ struct ProcFs {
     root: Arc<dyn Inode>,
     inode_allocator: AtomicU64,
     fs_event_subscriber_stats: FsEventSubscriberStats,
+    /// The PID namespace in which the procfs instance is mounted.
+    ///
+    /// Only the processes visible in this namespace are listed, and their IDs are displayed as
+    /// seen from this namespace.
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl ProcFs {
-    pub(self) fn new() -> Arc<Self> {
+    pub(self) fn new(pid_ns: Arc<PidNamespace>) -> Arc<Self> {
         let anon_device_id = AnonDeviceId::acquire().expect("no device ID is available for procfs");
         let sb = SuperBlock::new(PROC_MAGIC, BLOCK_SIZE, NAME_MAX, anon_device_id.id());
         Arc::new_cyclic(|weak_fs| Self {
             _anon_device_id: anon_device_id,
             sb: sb.clone(),
-            root: RootDirOps::new_inode(weak_fs.clone(), &sb),
+            root: RootDirOps::new_inode(weak_fs.clone(), pid_ns.clone(), &sb),
             inode_allocator: AtomicU64::new(PROC_ROOT_INO + 1),
             fs_event_subscriber_stats: FsEventSubscriberStats::new(),
+            pid_ns,
         })
     }
 
     pub(self) fn alloc_id(&self) -> u64 {
         self.inode_allocator.fetch_add(1, Ordering::Relaxed)
     }
+
+    pub(self) fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
+    /// Returns the PID namespace of the procfs instance that `inode` belongs to.
+    pub(self) fn pid_ns_of(inode: &Weak<dyn Inode>) -> Arc<PidNamespace> {
+        let inode = inode.upgrade().unwrap();
+        let fs = inode.fs();
+        fs.downcast_ref::<ProcFs>().unwrap().pid_ns().clone()
+    }
 }
 
 impl FileSystem for ProcFs {
@@ -130,7 +151,11 @@ impl FsType for ProcFsType {
     }
 
     fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
-        Ok(ProcFs::new())
+        let pid_ns = match Process::current() {
+            Some(process) => process.pid_ns().clone(),
+            None => PidNamespace::get_init_singleton().clone(),
+        };
+        Ok(ProcFs::new(pid_ns))
     }
 
     fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
@@ -139,13 +164,19 @@ impl FsType for ProcFsType {
 }
 
 /// Represents the inode at `/proc`.
-struct RootDirOps;
+struct RootDirOps {
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl RootDirOps {
-    pub fn new_inode(fs: Weak<ProcFs>, sb: &SuperBlock) -> Arc<dyn Inode> {
+    pub fn new_inode(
+        fs: Weak<ProcFs>,
+        pid_ns: Arc<PidNamespace>,
+        sb: &SuperBlock,
+    ) -> Arc<dyn Inode> {
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/root.c#L368>
         let fs: Weak<dyn FileSystem> = fs;
-        ProcDir::new_root(Self, fs, PROC_ROOT_INO, sb, mkmod!(a+rx))
+        ProcDir::new_root(Self { pid_ns }, fs, PROC_ROOT_INO, sb, mkmod!(a+rx))
     }
 
     const STATIC_ENTRIES: &'static [StaticEntry] = &[
@@ -161,7 +192,9 @@ impl RootDirOps {
         ("mounts", InodeType::SymLink, MountsSymOps::new_inode),
         ("self", InodeType::SymLink, SelfSymOps::new_inode),
         ("stat", InodeType::File, StatFileOps::new_inode),
+        ("swaps", InodeType::File, SwapsFileOps::new_inode),
         ("sys", InodeType::Dir, SysDirOps::new_inode),
+        ("sysvipc", InodeType::Dir, SysvIpcDirOps::new_inode),
         (
             "thread-self",
             InodeType::SymLink,
@@ -174,7 +207,11 @@ impl RootDirOps {
 
 impl ProcDirOps for RootDirOps {
     fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
-        if let Ok(pid) = name.parse::<Pid>() {
+        if let Some(pid) = name
+            .parse::<Pid>()
+            .ok()
+            .and_then(|pid| self.pid_ns.global_id_of(pid))
+        {
             let pid_entry = {
                 let pid_table = pid_table::pid_table_mut();
                 pid_table.get_entry(pid)
@@ -183,12 +220,16 @@ impl ProcDirOps for RootDirOps {
                 && let Some(type_) = pid_entry.type_()
             {
                 return Ok(match type_ {
-                    PidEntryType::Process => {
-                        PidDirOps::new_inode(pid_entry, this_dir.this_weak().clone())
-                    }
-                    PidEntryType::Thread => {
-                        TidDirOps::new_inode(pid_entry, this_dir.this_weak().clone())
-                    }
+                    PidEntryType::Process => PidDirOps::new_inode(
+                        pid_entry,
+                        self.pid_ns.clone(),
+                        this_dir.this_weak().clone(),
+                    ),
+                    PidEntryType::Thread => TidDirOps::new_inode(
+                        pid_entry,
+                        self.pid_ns.clone(),
+                        this_dir.this_weak().clone(),
+                    ),
                 });
             }
         }
@@ -213,13 +254,16 @@ impl ProcDirOps for RootDirOps {
         )?;
 
         // Collect PIDs before visiting entries, as `visit_fn` may copy data to user memory.
-        let process_pids = {
+        let mut process_pids = {
             let pid_table = pid_table::pid_table_mut();
             pid_table
                 .iter_processes()
-                .filter_map(|process| usize::try_from(process.pid()).ok())
+                .filter_map(|process| self.pid_ns.local_id_of(process.pid()))
+                .filter_map(|pid| usize::try_from(pid).ok())
                 .collect::<Vec<_>>()
         };
+        // The order of the IDs in a PID namespace may differ from that of the global IDs.
+        process_pids.sort_unstable();
 
         visit_readdir_entries(
             keyed_readdir_entries(offset, FIRST_PID_OFFSET, process_pids, |process_pid| {
@@ -264,6 +308,10 @@ impl ProcDirOps for RootDirOps {
             return true;
         };
 
+        let Some(pid) = self.pid_ns.global_id_of(pid) else {
+            return true;
+        };
+
         let pid_entry = {
             let pid_table = pid_table::pid_table_mut();
             pid_table.get_entry(pid)
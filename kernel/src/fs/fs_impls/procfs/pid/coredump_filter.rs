@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use super::{PidDirOps, TidDirOps};
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::coredump::CoredumpFilter,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/coredump_filter`.
+pub struct CoredumpFilterFileOps(TidDirOps);
+
+impl CoredumpFilterFileOps {
+    pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c>
+        ProcFile::new(Self(dir.tid_dir_ops().clone()), parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for CoredumpFilterFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let Some(process) = self.0.process() else {
+            return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+        };
+        let vmar_guard = process.lock_vmar();
+        let Some(vmar) = vmar_guard.as_ref() else {
+            // According to Linux behavior, return an empty file
+            // if the process is a zombie process.
+            return Ok(0);
+        };
+        writeln!(
+            printer,
+            "{:08x}",
+            vmar.process_vm().coredump_filter().bits()
+        )?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        /// Worst case buffer size needed for holding the value.
+        ///
+        /// The longest possible string is an octal `u32` with a leading
+        /// zero and a trailing newline, e.g., `"037777777777\n"`.
+        const BUF_SIZE: usize = 14;
+
+        let (cstr, read_bytes) = reader.read_cstring_until_end(BUF_SIZE - 1)?;
+        let val = cstr
+            .to_str()
+            .ok()
+            .and_then(|str| parse_u32_with_radix_prefix(str.trim()))
+            .ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the value is not a valid integer")
+            })?;
+
+        let Some(process) = self.0.process() else {
+            return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+        };
+        let vmar_guard = process.lock_vmar();
+        let Some(vmar) = vmar_guard.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        };
+        // Bits that do not correspond to any filter are silently ignored.
+        vmar.process_vm()
+            .set_coredump_filter(CoredumpFilter::from_bits_truncate(val));
+
+        Ok(read_bytes)
+    }
+}
+
+/// Parses an unsigned integer in the same way as Linux's `kstrtouint(str, 0, ...)`.
+///
+/// The radix is 16 if the string starts with `0x`, 8 if it starts with `0`,
+/// and 10 otherwise.
+fn parse_u32_with_radix_prefix(str: &str) -> Option<u32> {
+    if let Some(hex) = str.strip_prefix("0x").or_else(|| str.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if str.len() > 1
+        && let Some(oct) = str.strip_prefix('0')
+    {
+        u32::from_str_radix(oct, 8).ok()
+    } else {
+        str.parse::<u32>().ok()
+    }
+}
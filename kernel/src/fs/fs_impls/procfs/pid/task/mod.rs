@@ -23,7 +23,7 @@ use crate::{
         vfs::inode::{Inode, RevalidationPolicy},
     },
     prelude::*,
-    process::{Process, pid_table, pid_table::PidEntry, posix_thread::AsPosixThread},
+    process::{PidNamespace, Process, pid_table, pid_table::PidEntry, posix_thread::AsPosixThread},
     thread::{Thread, Tid},
 };
 
@@ -47,16 +47,23 @@ mod status;
 mod uid_map;
 
 /// Represents the inode at `/proc/[pid]/task`.
-pub struct TaskDirOps(Arc<PidEntry>);
+pub struct TaskDirOps {
+    pid_entry: Arc<PidEntry>,
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl TaskDirOps {
     pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let this = Self {
+            pid_entry: dir.pid_entry().clone(),
+            pid_ns: dir.tid_dir_ops().pid_ns().clone(),
+        };
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3316>
-        ProcDir::new(Self(dir.pid_entry().clone()), parent, mkmod!(a+rx))
+        ProcDir::new(this, parent, mkmod!(a+rx))
     }
 
     fn process(&self) -> Option<Arc<Process>> {
-        self.0.process_of_thread()
+        self.pid_entry.process_of_thread()
     }
 }
 
@@ -64,16 +71,22 @@ impl TaskDirOps {
 #[derive(Clone)]
 pub struct TidDirOps {
     pid_entry: Arc<PidEntry>,
+    /// The PID namespace of the procfs instance, in which the IDs are displayed.
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl TidDirOps {
-    pub fn new(pid_entry: Arc<PidEntry>) -> Self {
-        Self { pid_entry }
+    pub fn new(pid_entry: Arc<PidEntry>, pid_ns: Arc<PidNamespace>) -> Self {
+        Self { pid_entry, pid_ns }
     }
 
-    pub fn new_inode(pid_entry: Arc<PidEntry>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+    pub fn new_inode(
+        pid_entry: Arc<PidEntry>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
         ProcDir::new(
-            Self { pid_entry },
+            Self { pid_entry, pid_ns },
             parent,
             // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3796>
             mkmod!(a+rx),
@@ -84,6 +97,10 @@ impl TidDirOps {
         &self.pid_entry
     }
 
+    pub(super) fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
     pub(super) fn process(&self) -> Option<Arc<Process>> {
         self.pid_entry.process_of_thread()
     }
@@ -185,11 +202,15 @@ impl TidDirOps {
 
 impl ProcDirOps for TaskDirOps {
     fn owner_thread(&self) -> Option<Arc<Thread>> {
-        self.0.thread()
+        self.pid_entry.thread()
     }
 
     fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
-        let Ok(tid) = name.parse::<Tid>() else {
+        let Some(tid) = name
+            .parse::<Tid>()
+            .ok()
+            .and_then(|tid| self.pid_ns.global_id_of(tid))
+        else {
             return_errno_with_message!(Errno::ENOENT, "the name is not a valid TID");
         };
 
@@ -227,6 +248,7 @@ impl ProcDirOps for TaskDirOps {
 
         Ok(TidDirOps::new_inode(
             pid_entry,
+            self.pid_ns.clone(),
             this_dir.this_weak().clone(),
         ))
     }
@@ -240,13 +262,19 @@ impl ProcDirOps for TaskDirOps {
         };
 
         // Collect TIDs before visiting entries, as `visit_fn` may copy data to user memory.
-        let tids = process
+        let mut tids = process
             .tasks()
             .lock()
             .as_slice()
             .iter()
-            .filter_map(|task| usize::try_from(task.as_posix_thread().unwrap().tid()).ok())
+            .filter_map(|task| {
+                self.pid_ns
+                    .local_id_of(task.as_posix_thread().unwrap().tid())
+            })
+            .filter_map(|tid| usize::try_from(tid).ok())
             .collect::<Vec<_>>();
+        // The order of the IDs in a PID namespace may differ from that of the global IDs.
+        tids.sort_unstable();
 
         visit_readdir_entries(
             keyed_readdir_entries(offset, 2, tids, |tid| {
@@ -281,6 +309,10 @@ impl ProcDirOps for TaskDirOps {
             return true;
         };
 
+        let Some(tid) = self.pid_ns.global_id_of(tid) else {
+            return true;
+        };
+
         let Some(process) = self.process() else {
             return true;
         };
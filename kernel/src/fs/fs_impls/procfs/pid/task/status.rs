@@ -32,6 +32,10 @@ use crate::{
 /// - Uid:    Real, effective, saved set, and filesystem UIDs.
 /// - Gid:    Real, effective, saved set, and filesystem GIDs.
 /// - FDSize: The number of file descriptor slots currently allocated.
+/// - NStgid: The thread group IDs in the nested PID namespaces.
+/// - NSpid:  The thread IDs in the nested PID namespaces.
+/// - NSpgid: The process group IDs in the nested PID namespaces.
+/// - NSsid:  The session IDs in the nested PID namespaces.
 /// - Groups: Supplementary group IDs.
 /// - VmPeak: Peak virtual memory size.
 /// - VmSize: Current virtual memory size.
@@ -57,7 +61,9 @@ use crate::{
 /// - CapEff: Effective capabilities.
 /// - CapBnd: Bounding set.
 /// - CapAmb: Ambient capabilities.
+/// - NoNewPrivs: Whether the `no_new_privs` attribute is set.
 /// - Seccomp: Seccomp mode.
+/// - Seccomp_filters: Number of seccomp filters.
 /// - Cpus_allowed: CPUs allowed for this process.
 /// - Cpus_allowed_list: List of CPUs allowed for this process.
 /// - Mems_allowed: Memory nodes allowed for this process.
@@ -113,15 +119,19 @@ impl ProcFileOps for StatusFileOps {
         };
         writeln!(printer, "State:\t{}", state)?;
 
-        writeln!(printer, "Tgid:\t{}", process.pid())?;
-        writeln!(printer, "Pid:\t{}", posix_thread.tid())?;
-        writeln!(printer, "PPid:\t{}", process.parent().pid())?;
+        // The IDs are displayed as seen from the PID namespace of the procfs instance.
+        let pid_ns = self.0.pid_ns();
+        let local_id_of = |id| pid_ns.local_id_of(id).unwrap_or(0);
+
+        writeln!(printer, "Tgid:\t{}", local_id_of(process.pid()))?;
+        writeln!(printer, "Pid:\t{}", local_id_of(posix_thread.tid()))?;
+        writeln!(printer, "PPid:\t{}", local_id_of(process.parent().pid()))?;
         writeln!(
             printer,
             "TracerPid:\t{}",
             posix_thread
                 .tracer()
-                .map(|tracer| tracer.as_posix_thread().unwrap().tid())
+                .map(|tracer| local_id_of(tracer.as_posix_thread().unwrap().tid()))
                 .unwrap_or(0)
         )?;
 
@@ -153,15 +163,31 @@ impl ProcFileOps for StatusFileOps {
                 .unwrap_or(0)
         )?;
 
+        let process_pid_ns = process.pid_ns();
+        for (name, id) in [
+            ("NStgid", process.pid()),
+            ("NSpid", posix_thread.tid()),
+            ("NSpgid", process.pgid()),
+            ("NSsid", process.sid()),
+        ] {
+            write!(printer, "{}:", name)?;
+            for nested_id in process_pid_ns.nested_ids_of(pid_ns, id) {
+                write!(printer, "\t{}", nested_id)?;
+            }
+            writeln!(printer)?;
+        }
+
         if let Some(vmar_ref) = process.lock_vmar().as_ref() {
             let vsize = vmar_ref.get_mappings_total_size();
             let anon = vmar_ref.get_rss_counter(RssType::Anon) * (PAGE_SIZE / 1024);
             let file = vmar_ref.get_rss_counter(RssType::File) * (PAGE_SIZE / 1024);
             let rss = anon + file;
+            let swap = vmar_ref.get_rss_counter(RssType::Swap) * (PAGE_SIZE / 1024);
+            let hugetlb = vmar_ref.get_rss_counter(RssType::Hugetlb) * (PAGE_SIZE / 1024);
             writeln!(
                 printer,
-                "VmSize:\t{} kB\nVmRSS:\t{} kB\nRssAnon:\t{} kB\nRssFile:\t{} kB",
-                vsize, rss, anon, file
+                "VmSize:\t{} kB\nVmRSS:\t{} kB\nRssAnon:\t{} kB\nRssFile:\t{} kB\nVmSwap:\t{} kB\nHugetlbPages:\t{} kB",
+                vsize, rss, anon, file, swap, hugetlb
             )?;
         }
 
@@ -193,6 +219,15 @@ impl ProcFileOps for StatusFileOps {
         )?;
         writeln!(printer, "CapAmb:\t{:016x}", AMBIENT_CAPSET.bits())?;
 
+        writeln!(
+            printer,
+            "NoNewPrivs:\t{}",
+            posix_thread.no_new_privs() as u8
+        )?;
+        let seccomp = posix_thread.seccomp();
+        writeln!(printer, "Seccomp:\t{}", seccomp.mode() as u8)?;
+        writeln!(printer, "Seccomp_filters:\t{}", seccomp.nr_filters())?;
+
         Ok(printer.bytes_written())
     }
 }
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use super::{PidDirOps, TidDirOps};
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::{TimeNamespace, TimeNsClock, TimeNsOffset, posix_thread::AsPosixThread},
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/timens_offsets`.
+///
+/// The file shows and sets the clock offsets of the time namespace for the children of the
+/// process.
+pub struct TimensOffsetsFileOps(TidDirOps);
+
+impl TimensOffsetsFileOps {
+    pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c>
+        ProcFile::new(Self(dir.tid_dir_ops().clone()), parent, mkmod!(a+r, u+w))
+    }
+
+    fn time_ns_for_children(&self) -> Result<Arc<TimeNamespace>> {
+        let Some(thread) = self.0.thread() else {
+            return_errno_with_message!(Errno::ESRCH, "the thread does not exist");
+        };
+
+        let ns_proxy = thread.as_posix_thread().unwrap().ns_proxy().lock();
+        let Some(ns_proxy) = ns_proxy.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the thread has exited");
+        };
+        Ok(ns_proxy.time_ns_for_children().clone())
+    }
+}
+
+impl ProcFileOps for TimensOffsetsFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let offsets = self.time_ns_for_children()?.offsets();
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/time/namespace.c>
+        for (name, offset) in [
+            ("monotonic", offsets.monotonic),
+            ("boottime", offsets.boottime),
+        ] {
+            writeln!(
+                printer,
+                "{:<10} {:>10} {:>9}",
+                name,
+                offset.secs(),
+                offset.nanos()
+            )?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (cstr, read_bytes) = reader.read_cstring_until_end(PAGE_SIZE)?;
+        let str = cstr
+            .to_str()
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the offsets are not valid UTF-8"))?;
+
+        let (new_offsets, consumed_bytes) = parse_offsets(str)?;
+
+        let current = current_thread!();
+        self.time_ns_for_children()?
+            .set_offsets(&new_offsets, current.as_posix_thread().unwrap())?;
+
+        Ok(consumed_bytes.unwrap_or(read_bytes))
+    }
+}
+
+/// The maximum number of offsets that can be set by a single write.
+const MAX_NR_OFFSETS: usize = 2;
+
+/// Parses the offsets written to the file.
+///
+/// Each line has the format `<clock> <secs> <nanos>`, where `<clock>` is either the name of the
+/// clock or the clock ID.
+///
+/// If there are more lines after [`MAX_NR_OFFSETS`] offsets, the number of bytes consumed is also
+/// returned.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c>
+fn parse_offsets(str: &str) -> Result<(Vec<(TimeNsClock, TimeNsOffset)>, Option<usize>)> {
+    let mut offsets = Vec::with_capacity(MAX_NR_OFFSETS);
+    let mut rest = str;
+
+    loop {
+        let (line, next_line) = match rest.split_once('\n') {
+            Some((line, next_line)) if !next_line.is_empty() => (line, Some(next_line)),
+            Some((line, _)) => (line, None),
+            None => (rest, None),
+        };
+
+        offsets.push(parse_offset(line)?);
+
+        let Some(next_line) = next_line else {
+            return Ok((offsets, None));
+        };
+        if offsets.len() == MAX_NR_OFFSETS {
+            return Ok((offsets, Some(str.len() - next_line.len())));
+        }
+        rest = next_line;
+    }
+}
+
+fn parse_offset(line: &str) -> Result<(TimeNsClock, TimeNsOffset)> {
+    let invalid_offset = || Error::with_message(Errno::EINVAL, "the offset is invalid");
+
+    let mut fields = line.split_whitespace();
+    let (Some(clock), Some(secs), Some(nanos)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid_offset());
+    };
+
+    let secs = secs.parse::<i64>().map_err(|_| invalid_offset())?;
+    let nanos = nanos.parse::<u64>().map_err(|_| invalid_offset())?;
+    let offset = TimeNsOffset::new(secs, nanos)?;
+
+    // The clock can also be specified by its ID, i.e., `CLOCK_MONOTONIC` (1) or
+    // `CLOCK_BOOTTIME` (7).
+    let clock = match clock {
+        "monotonic" | "1" => TimeNsClock::Monotonic,
+        "boottime" | "7" => TimeNsClock::Boottime,
+        _ => return Err(invalid_offset()),
+    };
+
+    Ok((clock, offset))
+}
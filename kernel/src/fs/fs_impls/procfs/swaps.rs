@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/swaps` file support, which tells the user space
+//! about the enabled swap areas.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_swaps.5.html>
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    vm::swap,
+};
+
+/// Represents the inode at `/proc/swaps`.
+pub struct SwapsFileOps;
+
+impl SwapsFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference:
+        // <https://elixir.bootlin.com/linux/v6.16.5/source/mm/swapfile.c>
+        // <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/generic.c#L549-L550>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for SwapsFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // The format follows `swap_show` in Linux.
+        //
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/swapfile.c>
+        writeln!(printer, "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority")?;
+        for info in swap::swap_area_infos() {
+            let size = info.nr_pages * (PAGE_SIZE / 1024);
+            let used = info.nr_used * (PAGE_SIZE / 1024);
+            // The name is padded to 40 characters, with at least one space.
+            let padding = 40usize.saturating_sub(info.name.len()).max(1);
+            writeln!(
+                printer,
+                "{}{:padding$}{}\t{}\t{}{}\t{}{}",
+                info.name,
+                "",
+                if info.is_partition {
+                    "partition"
+                } else {
+                    "file\t"
+                },
+                size,
+                if size < 10000000 { "\t" } else { "" },
+                used,
+                if used < 10000000 { "\t" } else { "" },
+                info.priority
+            )?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
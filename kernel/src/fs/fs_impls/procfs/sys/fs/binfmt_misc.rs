@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            ProcDir,
+            template::{ProcDirOps, ReaddirEntry, visit_listed_entries},
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/sys/fs/binfmt_misc`.
+///
+/// The directory is always empty. It serves as the mount point of the binfmt_misc file system.
+pub struct BinfmtMiscDirOps;
+
+impl BinfmtMiscDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/proc_sysctl.c>
+        ProcDir::new(Self, parent, mkmod!(a+rx))
+    }
+}
+
+impl ProcDirOps for BinfmtMiscDirOps {
+    fn lookup_child(&self, _this_dir: &ProcDir<Self>, _name: &str) -> Result<Arc<dyn Inode>> {
+        return_errno_with_message!(Errno::ENOENT, "the file does not exist");
+    }
+
+    fn visit_entries_from_offset<'a, F>(&'a self, offset: usize, visit_fn: F) -> Result<()>
+    where
+        F: FnMut(ReaddirEntry<'a>) -> Result<()>,
+    {
+        visit_listed_entries(offset, [], visit_fn)
+    }
+}
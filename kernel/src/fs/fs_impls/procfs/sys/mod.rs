@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use self::{fs::FsDirOps, kernel::KernelDirOps};
+use self::{fs::FsDirOps, kernel::KernelDirOps, vm::VmDirOps};
 use super::{
     StaticEntry,
     template::{ReaddirEntry, listed_entries_from_table, visit_listed_entries},
@@ -16,6 +16,7 @@ use crate::{
 
 mod fs;
 mod kernel;
+mod vm;
 
 /// Represents the inode at `/proc/sys`.
 pub struct SysDirOps;
@@ -31,6 +32,7 @@ impl SysDirOps {
     const STATIC_ENTRIES: &'static [StaticEntry] = &[
         ("fs", InodeType::Dir, FsDirOps::new_inode),
         ("kernel", InodeType::Dir, KernelDirOps::new_inode),
+        ("vm", InodeType::Dir, VmDirOps::new_inode),
     ];
 }
 
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps, read_i32_from},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    vm::hugetlb,
+};
+
+/// Represents the inode at `/proc/sys/vm/nr_hugepages`.
+pub struct NrHugepagesFileOps;
+
+impl NrHugepagesFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/hugetlb.c>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for NrHugepagesFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(printer, "{}", hugetlb::nr_huge_pages())?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (val, read_bytes) = read_i32_from(reader)?;
+        let nr_pages = usize::try_from(val).map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the number of huge pages is negative")
+        })?;
+
+        hugetlb::set_nr_huge_pages(nr_pages);
+
+        Ok(read_bytes)
+    }
+}
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/sysvipc/msg` file support, which lists the System V message queues
+//! in the IPC namespace of the current thread.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_sysvipc.5.html>
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            ProcFs,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    ipc::msg::sysvipc_proc_show,
+    prelude::*,
+    process::{PidNamespace, posix_thread::AsPosixThread},
+};
+
+/// Represents the inode at `/proc/sysvipc/msg`.
+pub struct MsgFileOps {
+    /// The PID namespace of the procfs instance, in which the PIDs are displayed.
+    pid_ns: Arc<PidNamespace>,
+}
+
+impl MsgFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let pid_ns = ProcFs::pid_ns_of(&parent);
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L168>
+        ProcFile::new(Self { pid_ns }, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for MsgFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let current = current_thread!();
+        let ns_proxy = current.as_posix_thread().unwrap().ns_proxy().lock();
+        let Some(ns_proxy) = ns_proxy.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the current thread has exited");
+        };
+        let contents = sysvipc_proc_show(ns_proxy.ipc_ns(), &self.pid_ns);
+        write!(printer, "{}", contents)?;
+
+        Ok(printer.bytes_written())
+    }
+}
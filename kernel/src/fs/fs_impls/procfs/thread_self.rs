@@ -5,27 +5,41 @@ use alloc::format;
 use crate::{
     fs::{
         file::mkmod,
-        procfs::template::{ProcSym, ProcSymOps},
+        procfs::{
+            ProcFs,
+            template::{ProcSym, ProcSymOps},
+        },
         vfs::inode::{Inode, SymbolicLink},
     },
     prelude::*,
-    process::posix_thread::AsPosixThread,
+    process::{PidNamespace, posix_thread::AsPosixThread},
 };
 
 /// Represents the inode at `/proc/self-thread`.
-pub struct ThreadSelfSymOps;
+pub struct ThreadSelfSymOps {
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl ThreadSelfSymOps {
     pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let pid_ns = ProcFs::pid_ns_of(&parent);
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/thread_self.c#L50>
-        ProcSym::new(Self, parent, mkmod!(a+rwx))
+        ProcSym::new(Self { pid_ns }, parent, mkmod!(a+rwx))
     }
 }
 
 impl ProcSymOps for ThreadSelfSymOps {
     fn read_link(&self) -> Result<SymbolicLink> {
-        let pid = current!().pid();
-        let tid = current_thread!().as_posix_thread().unwrap().tid();
+        let (Some(pid), Some(tid)) = (
+            self.pid_ns.local_id_of(current!().pid()),
+            self.pid_ns
+                .local_id_of(current_thread!().as_posix_thread().unwrap().tid()),
+        ) else {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the current thread is not visible in the PID namespace of procfs"
+            );
+        };
         Ok(SymbolicLink::Plain(format!("{}/task/{}", pid, tid)))
     }
 }
@@ -14,6 +14,8 @@ use crate::{
         vfs::inode::Inode,
     },
     prelude::*,
+    process::posix_thread::AsPosixThread,
+    syscall::ClockId,
     time::cpu_time_stats::CpuTimeStatsManager,
 };
 
@@ -29,7 +31,17 @@ impl UptimeFileOps {
     }
 
     fn print_uptime(printer: &mut VmPrinter) -> Result<()> {
-        let uptime = aster_time::read_monotonic_time().as_secs_f32();
+        let uptime = {
+            let current = current_thread!();
+            let ns_proxy = current.as_posix_thread().unwrap().ns_proxy().lock();
+            let Some(ns_proxy) = ns_proxy.as_ref() else {
+                return_errno_with_message!(Errno::ESRCH, "the current thread has exited");
+            };
+            ns_proxy
+                .time_ns()
+                .to_ns_time(ClockId::CLOCK_BOOTTIME, aster_time::read_monotonic_time())
+                .as_secs_f32()
+        };
 
         let cpustat = CpuTimeStatsManager::singleton();
         let idle_time = cpustat
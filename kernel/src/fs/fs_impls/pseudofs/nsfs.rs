@@ -388,11 +388,8 @@ pub enum NsType {
     Cgroup,
     Ipc,
     Mnt,
-    #[expect(unused)]
     Net,
-    #[expect(unused)]
     Pid,
-    #[expect(unused)]
     Time,
     User,
     Uts,
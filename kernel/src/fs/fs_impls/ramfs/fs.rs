@@ -20,6 +20,7 @@ use crate::{
     device::{self, DeviceType},
     fs::{
         file::{AccessMode, InodeMode, InodeType, PerOpenFileOps, Permission, StatusFlags, mkmod},
+        hugetlbfs::HUGETLBFS_MAGIC,
         pipe::Pipe,
         pseudofs::AnonDeviceId,
         tmpfs::{self, TMPFS_MAGIC},
@@ -38,7 +39,10 @@ use crate::{
     prelude::*,
     process::{Gid, Uid},
     time::clocks::RealTimeCoarseClock,
-    vm::page_cache::PageCache,
+    vm::{
+        hugetlb::{self, HugetlbFile},
+        page_cache::PageCache,
+    },
 };
 
 /// A volatile file system whose data and metadata exists only in memory.
@@ -83,6 +87,25 @@ impl RamFs {
         Self::new_internal_with_sb("tmpfs", anon_device_id, sb)
     }
 
+    // TODO: Remove this hugetlbfs-specific constructor once `HugetlbFs` no
+    // longer aliases `RamFs`.
+    pub fn new_hugetlbfs() -> Result<Arc<Self>> {
+        let Some(huge_page_size) = hugetlb::huge_page_size() else {
+            return_errno_with_message!(Errno::ENODEV, "huge pages are not supported");
+        };
+
+        let anon_device_id = AnonDeviceId::acquire().ok_or_else(|| {
+            Error::with_message(Errno::ENODEV, "no device ID is available for hugetlbfs")
+        })?;
+        let sb = SuperBlock::new(
+            HUGETLBFS_MAGIC,
+            huge_page_size,
+            NAME_MAX,
+            anon_device_id.id(),
+        );
+        Ok(Self::new_internal_with_sb("hugetlbfs", anon_device_id, sb))
+    }
+
     fn new_internal(name: &'static str) -> Arc<Self> {
         let anon_device_id = AnonDeviceId::acquire().expect("no device ID is available for ramfs");
         let sb = SuperBlock::new(RAMFS_MAGIC, BLOCK_SIZE, NAME_MAX, anon_device_id.id());
@@ -123,6 +146,12 @@ impl RamFs {
     fn alloc_id(&self) -> u64 {
         self.inode_allocator.fetch_add(1, Ordering::Relaxed)
     }
+
+    /// Returns whether the regular files are backed by huge pages from the
+    /// hugetlb pool.
+    fn is_hugetlbfs(&self) -> bool {
+        self.sb.magic == HUGETLBFS_MAGIC
+    }
 }
 
 impl FileSystem for RamFs {
@@ -180,6 +209,7 @@ pub(super) struct RamInode {
 enum Inner {
     Dir(RwLock<DirEntry>),
     File(Mutex<PageCache>),
+    HugetlbFile(Arc<HugetlbFile>),
     SymLink(SpinLock<String>),
     BlockDevice(u64),
     CharDevice(u64),
@@ -192,8 +222,12 @@ impl Inner {
         Self::Dir(RwLock::new(DirEntry::new(this, parent)))
     }
 
-    pub(self) fn new_file() -> Self {
-        Self::File(Mutex::new(PageCache::new_anon(0).unwrap()))
+    pub(self) fn new_file(is_hugetlb: bool) -> Self {
+        if is_hugetlb {
+            Self::HugetlbFile(HugetlbFile::new(0))
+        } else {
+            Self::File(Mutex::new(PageCache::new_anon(0).unwrap()))
+        }
     }
 
     pub(self) fn new_symlink() -> Self {
@@ -216,8 +250,8 @@ impl Inner {
         Self::NamedPipe(Pipe::new())
     }
 
-    pub(self) fn new_file_in_memfd() -> Self {
-        Self::File(Mutex::new(PageCache::new_anon(0).unwrap()))
+    pub(self) fn new_file_in_memfd(is_hugetlb: bool) -> Self {
+        Self::new_file(is_hugetlb)
     }
 
     fn as_direntry(&self) -> Option<&RwLock<DirEntry>> {
@@ -234,6 +268,13 @@ impl Inner {
         }
     }
 
+    fn as_hugetlb_file(&self) -> Option<&Arc<HugetlbFile>> {
+        match self {
+            Self::HugetlbFile(file) => Some(file),
+            _ => None,
+        }
+    }
+
     fn as_symlink(&self) -> Option<&SpinLock<String>> {
         match self {
             Self::SymLink(link) => Some(link),
@@ -526,7 +567,7 @@ impl RamInode {
 
     fn new_file(fs: &Arc<RamFs>, mode: InodeMode, uid: Uid, gid: Gid) -> Arc<Self> {
         Arc::new_cyclic(|weak_self| RamInode {
-            inner: Inner::new_file(),
+            inner: Inner::new_file(fs.is_hugetlbfs()),
             metadata: SpinLock::new(InodeMeta::new(mode, uid, gid)),
             ino: fs.alloc_id(),
             typ: InodeType::File,
@@ -547,7 +588,7 @@ impl RamInode {
         hard_linkability: HardLinkability,
     ) -> Arc<Self> {
         Arc::new_cyclic(|weak_self| RamInode {
-            inner: Inner::new_file(),
+            inner: Inner::new_file(fs.is_hugetlbfs()),
             metadata: SpinLock::new(InodeMeta::new_tmpfile(mode, uid, gid)),
             ino: fs.alloc_id(),
             typ: InodeType::File,
@@ -561,15 +602,18 @@ impl RamInode {
     }
 
     /// Creates a `RamInode` that is detached from any `RamFs`, and resides in a `MemfdInode`.
+    ///
+    /// If `is_hugetlb` is true, the file is backed by huge pages from the hugetlb pool.
     pub(super) fn new_file_detached_in_memfd(
         weak_self: &Weak<MemfdInode>,
         dev_id: DeviceId,
         mode: InodeMode,
         uid: Uid,
         gid: Gid,
+        is_hugetlb: bool,
     ) -> Self {
         Self {
-            inner: Inner::new_file_in_memfd(),
+            inner: Inner::new_file_in_memfd(is_hugetlb),
             metadata: SpinLock::new(InodeMeta::new(mode, uid, gid)),
             ino: weak_self.as_ptr() as u64,
             typ: InodeType::File,
@@ -688,6 +732,7 @@ impl FileOps for RamInode {
                 page_cache.lock().read(offset, writer)?;
                 read_len
             }
+            Inner::HugetlbFile(hugetlb_file) => hugetlb_file.read(offset, writer)?,
             _ => return_errno_with_message!(Errno::EISDIR, "read is not supported"),
         };
 
@@ -703,6 +748,11 @@ impl FileOps for RamInode {
         reader: &mut VmReader,
         _status_flags: StatusFlags,
     ) -> Result<usize> {
+        if self.inner.as_hugetlb_file().is_some() {
+            // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/hugetlbfs/inode.c>
+            return_errno_with_message!(Errno::EINVAL, "hugetlbfs files cannot be written");
+        }
+
         let written_len = match self.typ {
             InodeType::File => {
                 let now = now();
@@ -760,6 +810,10 @@ impl Inode for RamInode {
             .map(|page_cache| page_cache.lock().clone())
     }
 
+    fn hugetlb_file(&self) -> Option<Arc<HugetlbFile>> {
+        self.inner.as_hugetlb_file().cloned()
+    }
+
     fn size(&self) -> usize {
         self.metadata.lock().size
     }
@@ -772,6 +826,19 @@ impl Inode for RamInode {
             return_errno_with_message!(Errno::EINVAL, "the inode is not a regular file");
         }
 
+        if let Some(hugetlb_file) = self.inner.as_hugetlb_file() {
+            let mut inode_meta = self.metadata.lock();
+            if inode_meta.size == new_size {
+                return Ok(());
+            }
+            hugetlb_file.resize(new_size)?;
+            let now = now();
+            inode_meta.set_mtime(now);
+            inode_meta.set_ctime(now);
+            inode_meta.resize(new_size);
+            return Ok(());
+        }
+
         let page_cache = self.inner.as_file().unwrap().lock();
         let mut inode_meta = self.metadata.lock();
         let file_size = inode_meta.size;
@@ -1318,6 +1385,14 @@ impl Inode for RamInode {
     }
 
     fn fallocate(&self, mode: FallocMode, offset: usize, len: usize) -> Result<()> {
+        // TODO: Support `fallocate` on hugetlbfs files.
+        if self.inner.as_hugetlb_file().is_some() {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "fallocate is not supported on hugetlbfs files"
+            );
+        }
+
         // The support for flags is consistent with Linux
         match mode {
             FallocMode::Allocate => {
@@ -24,7 +24,7 @@ use crate::{
     },
     prelude::*,
     process::{Gid, Uid},
-    vm::{page_cache::PageCache, perms::VmPerms},
+    vm::{hugetlb::HugetlbFile, page_cache::PageCache, perms::VmPerms},
 };
 
 pub(super) fn init() {
@@ -62,7 +62,14 @@ impl MemfdInode {
         }
 
         if new_seals.contains(FileSeals::F_SEAL_WRITE) {
-            let page_cache = self.page_cache().unwrap();
+            // TODO: Track the writable mappings of hugetlb memfds so that they
+            // can be sealed against writing.
+            let Some(page_cache) = self.page_cache() else {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "hugetlb memfds cannot be sealed against writing"
+                );
+            };
             page_cache.writable_mapping_status().deny()?;
         }
 
@@ -164,6 +171,7 @@ impl Inode for MemfdInode {
     fn group(&self) -> Result<Gid>;
     fn set_group(&self, gid: Gid) -> Result<()>;
     fn page_cache(&self) -> Option<PageCache>;
+    fn hugetlb_file(&self) -> Option<Arc<HugetlbFile>>;
     fn extension(&self) -> &Extension;
     fn set_xattr(
         &self,
@@ -252,6 +260,7 @@ impl MemfdInodeHandle for InodeHandle {
                 mode,
                 Uid::new_root(),
                 Gid::new_root(),
+                memfd_flags.contains(MemfdFlags::MFD_HUGETLB),
             );
 
             let mut seals = FileSeals::empty();
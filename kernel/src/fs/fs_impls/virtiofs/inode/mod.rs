@@ -471,6 +471,20 @@ impl Inode for VirtioFsInode {
         Ok(())
     }
 
+    fn copy_range_to(
+        &self,
+        offset: usize,
+        dst: &dyn Inode,
+        dst_offset: usize,
+        len: usize,
+    ) -> Result<usize> {
+        let Some(dst) = dst.downcast_ref::<VirtioFsInode>() else {
+            return_errno_with_message!(Errno::EXDEV, "the destination is not a virtiofs inode");
+        };
+
+        self.copy_file_range(offset, dst, dst_offset, len)
+    }
+
     fn sync_data(&self) -> Result<()> {
         let inner = self.inner.write();
         let Some(page_cache) = &inner.page_cache else {
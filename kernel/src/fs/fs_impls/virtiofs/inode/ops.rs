@@ -8,6 +8,7 @@ use aster_fuse::{
     EntryReply, FuseAttrReply, FuseDirEntry, FuseFileHandle, FuseOpenFlags, GetattrFlags, ReadReq,
     ReleaseFlags, ReleaseKind, SetattrReq, SetattrValid, WriteFlags, WriteReq,
     ops::{
+        copy_file_range::{CopyFileRangeOperation, CopyFileRangeReq},
         getattr::{GetattrOperation, GetattrReq},
         lookup::LookupOperation,
         open::{OpenOperation, OpenReq, OpendirOperation},
@@ -220,6 +221,77 @@ impl VirtioFsInode {
         Ok(written)
     }
 
+    /// Copies file data to `dst` on the server with `FUSE_COPY_FILE_RANGE`.
+    ///
+    /// If the server does not implement the operation, `EOPNOTSUPP` is returned so that
+    /// the caller can fall back to copying the data through the kernel.
+    pub(super) fn copy_file_range(
+        &self,
+        offset: usize,
+        dst: &VirtioFsInode,
+        dst_offset: usize,
+        len: usize,
+    ) -> Result<usize> {
+        // The request carries the server-provided handles of both files. They exist as long
+        // as the files are open, which is the case for `copy_file_range`.
+        let (Some(src_handle), Some(dst_handle)) = (
+            self.open_handles.find_readable_handle(),
+            dst.open_handles.find_writable_handle(),
+        ) else {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "virtiofs copy_file_range requires open file handles"
+            );
+        };
+
+        let mut dst_inner = dst.inner.write();
+        let dst_end = dst_offset
+            .checked_add(len)
+            .ok_or_else(|| Error::with_message(Errno::EOVERFLOW, "virtiofs copy size overflow"))?;
+
+        // Cached writes are written through, so the server already has the source data.
+        // Only the destination range in the page cache becomes stale.
+        if let Some(page_cache) = dst_inner.page_cache() {
+            page_cache.invalidate_range(dst_offset..dst_end)?;
+        }
+
+        let fs = self.fs_ref();
+        let copy_reply = fs
+            .session()
+            .do_fuse_op(
+                self.nodeid(),
+                CopyFileRangeOperation::new(CopyFileRangeReq::new(
+                    src_handle.fh(),
+                    offset as u64,
+                    dst.nodeid(),
+                    dst_handle.fh(),
+                    dst_offset as u64,
+                    len as u64,
+                )),
+            )
+            .map_err(|err| match Error::from(err) {
+                err if err.error() == Errno::ENOSYS => Error::with_message(
+                    Errno::EOPNOTSUPP,
+                    "the server does not support FUSE_COPY_FILE_RANGE",
+                ),
+                err => err,
+            })?;
+
+        let copied = copy_reply.size();
+        if copied > len {
+            return_errno_with_message!(Errno::EIO, "virtiofs copy response is too large");
+        }
+
+        if copied > 0 {
+            let new_size = dst.size().max(dst_offset + copied);
+            let attr_version = fs.session().bump_attr_version();
+            dst_inner.commit_local_write(new_size, attr_version);
+            dst.set_size(new_size);
+        }
+
+        Ok(copied)
+    }
+
     fn resolve_write_offset(&self, write_offset: WriteOffset) -> usize {
         match write_offset {
             WriteOffset::Absolute(offset) => offset,
@@ -9,7 +9,8 @@ pub mod utils;
 pub mod vfs;
 
 pub use fs_impls::{
-    cgroupfs, configfs, devpts, exfat, ext2, procfs, pseudofs, ramfs, sysfs, tmpfs,
+    binfmt_misc, cgroupfs, configfs, devpts, exfat, ext2, hugetlbfs, mqueuefs, procfs, pseudofs,
+    ramfs, sysfs, tmpfs,
 };
 
 use crate::{
@@ -5,7 +5,7 @@ use core::{
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use ostd::sync::WaitQueue;
+use ostd::{mm::io::util::HasVmReaderWriter, sync::WaitQueue};
 
 use crate::{
     events::IoEvents,
@@ -60,6 +60,84 @@ impl PipeHandle {
     fn bytes_to_read(&self) -> usize {
         self.inner.reader.buffer_len()
     }
+
+    /// Passes up to `max_len` bytes in the pipe to `write`.
+    ///
+    /// The bytes accepted by `write` are consumed from the pipe if `consume` is true.
+    /// Otherwise, they are left in the pipe, which is the semantics of `tee`.
+    ///
+    /// Errors reported by the pipe and errors reported by `write` are distinguished: only the
+    /// former can cause the operation to wait until the pipe becomes readable.
+    pub(super) fn splice_to(
+        &self,
+        max_len: usize,
+        consume: bool,
+        is_nonblocking: bool,
+        write: &mut dyn FnMut(&mut VmReader) -> Result<usize>,
+    ) -> Result<usize> {
+        debug_assert!(self.access_mode.is_readable());
+
+        let mut try_splice = || {
+            self.inner
+                .reader
+                .try_splice_to(max_len, consume, &mut *write)
+        };
+        if is_nonblocking {
+            try_splice()?
+        } else {
+            self.wait_events(IoEvents::IN, None, try_splice)?
+        }
+    }
+
+    /// Fills up to `max_len` bytes of free space in the pipe with `read`.
+    ///
+    /// Like [`Self::splice_to`], only errors reported by the pipe can cause the operation to
+    /// wait until the pipe becomes writable.
+    pub(super) fn splice_from(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        read: &mut dyn FnMut(&mut VmWriter) -> Result<usize>,
+    ) -> Result<usize> {
+        debug_assert!(self.access_mode.is_writable());
+
+        let mut try_splice = || self.inner.writer.try_splice_from(max_len, &mut *read);
+        if is_nonblocking {
+            try_splice()?
+        } else {
+            self.wait_events(IoEvents::OUT, None, try_splice)?
+        }
+    }
+
+    /// Moves up to `max_len` bytes from this pipe to `dst`.
+    ///
+    /// If `consume` is false, the bytes are copied instead of moved, as `tee` does.
+    pub(super) fn splice_to_pipe(
+        &self,
+        dst: &PipeHandle,
+        max_len: usize,
+        consume: bool,
+        is_nonblocking: bool,
+    ) -> Result<usize> {
+        debug_assert!(dst.access_mode.is_writable());
+
+        if Arc::ptr_eq(&self.inner, &dst.inner) {
+            return_errno_with_message!(Errno::EINVAL, "the input and output pipes are the same");
+        }
+
+        // Wait for space in `dst` before waiting for data in `self`, as Linux does in
+        // `splice_pipe_to_pipe` and `link_pipe`.
+        let check_space = || dst.inner.writer.check_space();
+        if is_nonblocking {
+            check_space()?;
+        } else {
+            dst.wait_events(IoEvents::OUT, None, check_space)?;
+        }
+
+        self.splice_to(max_len, consume, is_nonblocking, &mut |reader| {
+            dst.inner.writer.write_spliced(reader)
+        })
+    }
 }
 
 impl Pollable for PipeHandle {
@@ -412,6 +490,62 @@ impl PipeReader {
         Ok(read_len)
     }
 
+    fn try_splice_to(
+        &self,
+        max_len: usize,
+        consume: bool,
+        write: &mut dyn FnMut(&mut VmReader) -> Result<usize>,
+    ) -> Result<Result<usize>> {
+        // This must be recorded before the actual operation to avoid race conditions.
+        let is_shutdown = self.state.is_peer_shutdown();
+
+        let mut consumer = self.consumer.lock();
+        let len = consumer.len().min(max_len);
+        if len == 0 {
+            if is_shutdown {
+                return Ok(Ok(0));
+            }
+            return_errno_with_message!(Errno::EAGAIN, "the channel is empty");
+        }
+
+        let capacity = consumer.capacity();
+        let head = consumer.head();
+        let mut spliced_len = 0;
+        let mut write_res = Ok(());
+        while spliced_len < len {
+            // The data may wrap around the end of the buffer.
+            let offset = (head + Wrapping(spliced_len)).0 & (capacity - 1);
+            let chunk_len = (len - spliced_len).min(capacity - offset);
+
+            let mut reader = consumer.segment().reader().to_fallible();
+            reader.skip(offset).limit(chunk_len);
+            match write(&mut reader) {
+                Ok(written_len) => {
+                    spliced_len += written_len;
+                    if written_len < chunk_len {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    write_res = Err(err);
+                    break;
+                }
+            }
+        }
+
+        if consume && spliced_len > 0 {
+            consumer.commit_read(spliced_len);
+            drop(consumer);
+            self.state.notify_read();
+        }
+
+        if spliced_len > 0 {
+            Ok(Ok(spliced_len))
+        } else {
+            Ok(write_res.map(|()| 0))
+        }
+    }
+
     fn buffer_len(&self) -> usize {
         self.consumer.lock().len()
     }
@@ -470,20 +604,92 @@ impl PipeWriter {
         if res.is_ok() {
             self.state.notify_write();
         }
-        if res.is_err_and(|e| e.error() == Errno::EPIPE)
-            && let Some(posix_thread) = current_thread!().as_posix_thread()
-        {
-            posix_thread.enqueue_signal(Box::new(UserSignal::new(
-                SIGPIPE,
-                UserSignalKind::Kill,
-                posix_thread.process().pid(),
-                posix_thread.credentials().ruid(),
-            )));
+        if res.is_err_and(|e| e.error() == Errno::EPIPE) {
+            send_sigpipe();
         }
 
         res
     }
 
+    fn try_splice_from(
+        &self,
+        max_len: usize,
+        read: &mut dyn FnMut(&mut VmWriter) -> Result<usize>,
+    ) -> Result<Result<usize>> {
+        self.check_shutdown()?;
+
+        let mut producer = self.producer.lock();
+        let len = producer.free_len().min(max_len);
+        if len == 0 {
+            return_errno_with_message!(Errno::EAGAIN, "the channel is full");
+        }
+
+        let capacity = producer.capacity();
+        let tail = producer.tail();
+        let mut spliced_len = 0;
+        let mut read_res = Ok(());
+        while spliced_len < len {
+            // The free space may wrap around the end of the buffer.
+            let offset = (tail + Wrapping(spliced_len)).0 & (capacity - 1);
+            let chunk_len = (len - spliced_len).min(capacity - offset);
+
+            let mut writer = producer.segment().writer().to_fallible();
+            writer.skip(offset).limit(chunk_len);
+            match read(&mut writer) {
+                Ok(read_len) => {
+                    spliced_len += read_len;
+                    if read_len < chunk_len {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    read_res = Err(err);
+                    break;
+                }
+            }
+        }
+
+        if spliced_len > 0 {
+            producer.commit_write(spliced_len);
+            drop(producer);
+            self.state.notify_write();
+            Ok(Ok(spliced_len))
+        } else {
+            Ok(read_res.map(|()| 0))
+        }
+    }
+
+    /// Writes as many bytes as possible without waiting for space.
+    fn write_spliced(&self, reader: &mut VmReader) -> Result<usize> {
+        self.check_shutdown()?;
+
+        let written_len = self.producer.lock().write_fallible(reader)?;
+        if written_len > 0 {
+            self.state.notify_write();
+        }
+
+        Ok(written_len)
+    }
+
+    fn check_space(&self) -> Result<()> {
+        self.check_shutdown()?;
+
+        if self.producer.lock().is_full() {
+            return_errno_with_message!(Errno::EAGAIN, "the channel is full");
+        }
+
+        Ok(())
+    }
+
+    fn check_shutdown(&self) -> Result<()> {
+        if self.state.is_shutdown() {
+            send_sigpipe();
+            return_errno_with_message!(Errno::EPIPE, "the channel is shut down");
+        }
+
+        Ok(())
+    }
+
     fn shutdown(&self) {
         self.state.shutdown();
     }
@@ -510,6 +716,17 @@ impl Pollable for PipeWriter {
     }
 }
 
+fn send_sigpipe() {
+    if let Some(posix_thread) = current_thread!().as_posix_thread() {
+        posix_thread.enqueue_signal(Box::new(UserSignal::new(
+            SIGPIPE,
+            UserSignalKind::Kill,
+            posix_thread.process().pid(),
+            posix_thread.credentials().ruid(),
+        )));
+    }
+}
+
 #[cfg(ktest)]
 mod test {
     use core::sync::atomic::{self, AtomicBool};
@@ -649,6 +866,47 @@ mod test {
         );
     }
 
+    #[ktest]
+    fn tee_and_splice() {
+        let open_pair = || {
+            let pipe = Pipe::new();
+            let reader = pipe
+                .open_anon(AccessMode::O_RDONLY, StatusFlags::empty())
+                .unwrap();
+            let writer = pipe
+                .open_anon(AccessMode::O_WRONLY, StatusFlags::empty())
+                .unwrap();
+            (reader, writer)
+        };
+        let as_pipe_handle =
+            |ops: &dyn PerOpenFileOps| (ops as &dyn Any).downcast_ref::<PipeHandle>().unwrap();
+
+        let (src_reader, src_writer) = open_pair();
+        let (dst_reader, dst_writer) = open_pair();
+        let src = as_pipe_handle(src_reader.as_ref());
+        let dst = as_pipe_handle(dst_writer.as_ref());
+
+        assert_eq!(write(src_writer.as_ref(), &[1, 2]).unwrap(), 2);
+
+        // Copying the data with `tee` leaves the data in the source pipe.
+        assert_eq!(src.splice_to_pipe(dst, 2, false, true).unwrap(), 2);
+        assert_eq!(
+            src.splice_to_pipe(dst, 2, true, true).unwrap_err().error(),
+            Errno::EAGAIN
+        );
+
+        let mut buf = [0; 2];
+        assert_eq!(read(dst_reader.as_ref(), &mut buf).unwrap(), 2);
+        assert_eq!(&buf, &[1, 2]);
+
+        // Moving the data with `splice` consumes the data in the source pipe.
+        assert_eq!(src.splice_to_pipe(dst, 1, true, true).unwrap(), 1);
+        assert_eq!(read(src_reader.as_ref(), &mut buf).unwrap(), 1);
+        assert_eq!(&buf[..1], &[2]);
+        assert_eq!(read(dst_reader.as_ref(), &mut buf).unwrap(), 1);
+        assert_eq!(&buf[..1], &[1]);
+    }
+
     fn read(reader: &dyn PerOpenFileOps, buf: &mut [u8]) -> crate::prelude::Result<usize> {
         reader.read_at(
             0,
@@ -2,11 +2,14 @@
 
 //! Pipes implementation.
 //!
-//! This module provides both anonymous and named pipes for inter-process communication.
+//! This module provides both anonymous and named pipes for inter-process communication,
+//! as well as `splice`-like operations that transfer data between pipes and other files.
 
 pub(super) use anon_pipe::AnonPipeInode;
 pub use anon_pipe::new_file_pair;
 pub(super) use common::{Pipe, PipeHandle, check_status_flags};
+pub use splice::{SpliceFlags, splice, tee, vmsplice_from_pipe, vmsplice_to_pipe};
 
 mod anon_pipe;
 mod common;
+mod splice;
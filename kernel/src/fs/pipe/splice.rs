@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Data transfer between pipes and other files without going through user space.
+//!
+//! This is the backend of `splice`, `tee`, and `vmsplice`. Data is copied directly between
+//! the pipe buffer and the other file, so no intermediate buffer is needed.
+
+use super::PipeHandle;
+use crate::{
+    fs::{
+        file::{FileLike, InodeHandle, InodeType, StatusFlags},
+        vfs::inode::FileOps,
+    },
+    prelude::*,
+};
+
+bitflags! {
+    /// Flags for `splice`, `tee`, and `vmsplice`.
+    pub struct SpliceFlags: u32 {
+        /// Moves pages instead of copying them. This is only a hint.
+        const MOVE     = 1 << 0;
+        /// Does not block on pipe I/O.
+        const NONBLOCK = 1 << 1;
+        /// More data will be coming in a subsequent splice. This is only a hint.
+        const MORE     = 1 << 2;
+        /// Gives the user pages to the kernel. This is only a hint.
+        const GIFT     = 1 << 3;
+    }
+}
+
+/// Moves up to `len` bytes from `in_file` to `out_file`, at least one of which must be a pipe.
+///
+/// For the file that is not a pipe, the data is transferred at the given offset, which is
+/// then advanced, if there is one. Otherwise, the data is transferred at the file position.
+pub fn splice(
+    in_file: &dyn FileLike,
+    mut in_offset: Option<&mut usize>,
+    out_file: &dyn FileLike,
+    mut out_offset: Option<&mut usize>,
+    len: usize,
+    flags: SpliceFlags,
+) -> Result<usize> {
+    check_access_modes(in_file, out_file)?;
+
+    match (as_pipe(in_file), as_pipe(out_file)) {
+        (Some(in_pipe), Some(out_pipe)) => {
+            if in_offset.is_some() || out_offset.is_some() {
+                return_errno_with_message!(Errno::ESPIPE, "pipes do not have offsets");
+            }
+
+            let is_nonblocking = is_nonblocking(in_file, flags) || is_nonblocking(out_file, flags);
+            in_pipe.splice_to_pipe(out_pipe, len, true, is_nonblocking)
+        }
+        (Some(in_pipe), None) => {
+            if in_offset.is_some() {
+                return_errno_with_message!(Errno::ESPIPE, "pipes do not have offsets");
+            }
+            if out_file.status_flags().contains(StatusFlags::O_APPEND) {
+                return_errno_with_message!(Errno::EINVAL, "out_file is opened with O_APPEND");
+            }
+
+            in_pipe.splice_to(len, true, is_nonblocking(in_file, flags), &mut |reader| {
+                write_file(out_file, out_offset.as_deref_mut(), reader)
+            })
+        }
+        (None, Some(out_pipe)) => {
+            if out_offset.is_some() {
+                return_errno_with_message!(Errno::ESPIPE, "pipes do not have offsets");
+            }
+            // Linux returns `EINVAL` because directories do not implement `splice_read`.
+            if in_file
+                .downcast_ref::<InodeHandle>()
+                .is_some_and(|inode_handle| inode_handle.path().inode().type_() == InodeType::Dir)
+            {
+                return_errno_with_message!(Errno::EINVAL, "in_file is a directory");
+            }
+
+            out_pipe.splice_from(len, is_nonblocking(out_file, flags), &mut |writer| {
+                read_file(in_file, in_offset.as_deref_mut(), writer)
+            })
+        }
+        (None, None) => return_errno_with_message!(Errno::EINVAL, "neither file is a pipe"),
+    }
+}
+
+/// Copies up to `len` bytes from `in_file` to `out_file` without consuming them,
+/// where both files must be pipes.
+pub fn tee(
+    in_file: &dyn FileLike,
+    out_file: &dyn FileLike,
+    len: usize,
+    flags: SpliceFlags,
+) -> Result<usize> {
+    check_access_modes(in_file, out_file)?;
+
+    let (Some(in_pipe), Some(out_pipe)) = (as_pipe(in_file), as_pipe(out_file)) else {
+        return_errno_with_message!(Errno::EINVAL, "tee requires two pipes");
+    };
+
+    let is_nonblocking = is_nonblocking(in_file, flags) || is_nonblocking(out_file, flags);
+    in_pipe.splice_to_pipe(out_pipe, len, false, is_nonblocking)
+}
+
+/// Writes the data in `readers` to `file`, which must be a pipe.
+pub fn vmsplice_to_pipe(
+    file: &dyn FileLike,
+    readers: &mut [VmReader],
+    flags: SpliceFlags,
+) -> Result<usize> {
+    let pipe = vmsplice_pipe(file)?;
+    let status_flags = vmsplice_status_flags(file, flags);
+
+    let mut total_len = 0;
+    for reader in readers {
+        match pipe.write_at(0, reader, status_flags) {
+            Ok(write_len) => total_len += write_len,
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        }
+        if reader.has_remain() {
+            break;
+        }
+    }
+
+    Ok(total_len)
+}
+
+/// Reads the data in `file`, which must be a pipe, to `writers`.
+pub fn vmsplice_from_pipe(
+    file: &dyn FileLike,
+    writers: &mut [VmWriter],
+    flags: SpliceFlags,
+) -> Result<usize> {
+    let pipe = vmsplice_pipe(file)?;
+    let status_flags = vmsplice_status_flags(file, flags);
+
+    let mut total_len = 0;
+    for writer in writers {
+        match pipe.read_at(0, writer, status_flags) {
+            Ok(read_len) => total_len += read_len,
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        }
+        if writer.has_avail() {
+            break;
+        }
+    }
+
+    Ok(total_len)
+}
+
+fn as_pipe(file: &dyn FileLike) -> Option<&PipeHandle> {
+    file.downcast_ref::<InodeHandle>()?
+        .downcast_open_file::<PipeHandle>()
+        .ok()
+        .flatten()
+}
+
+fn check_access_modes(in_file: &dyn FileLike, out_file: &dyn FileLike) -> Result<()> {
+    if !in_file.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "in_file is not readable");
+    }
+    if !out_file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "out_file is not writable");
+    }
+
+    Ok(())
+}
+
+fn is_nonblocking(pipe_file: &dyn FileLike, flags: SpliceFlags) -> bool {
+    flags.contains(SpliceFlags::NONBLOCK)
+        || pipe_file.status_flags().contains(StatusFlags::O_NONBLOCK)
+}
+
+fn vmsplice_pipe(file: &dyn FileLike) -> Result<&PipeHandle> {
+    as_pipe(file).ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a pipe"))
+}
+
+fn vmsplice_status_flags(file: &dyn FileLike, flags: SpliceFlags) -> StatusFlags {
+    if is_nonblocking(file, flags) {
+        StatusFlags::O_NONBLOCK
+    } else {
+        StatusFlags::empty()
+    }
+}
+
+fn read_file(
+    file: &dyn FileLike,
+    offset: Option<&mut usize>,
+    writer: &mut VmWriter,
+) -> Result<usize> {
+    let Some(offset) = offset else {
+        return file.read(writer);
+    };
+
+    let read_len = file.read_at(*offset, writer)?;
+    *offset += read_len;
+    Ok(read_len)
+}
+
+fn write_file(
+    file: &dyn FileLike,
+    offset: Option<&mut usize>,
+    reader: &mut VmReader,
+) -> Result<usize> {
+    let Some(offset) = offset else {
+        return file.write(reader);
+    };
+
+    let written_len = file.write_at(*offset, reader)?;
+    *offset += written_len;
+    Ok(written_len)
+}
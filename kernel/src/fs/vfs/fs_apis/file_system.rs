@@ -6,7 +6,7 @@ use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 use device_id::DeviceId;
 
 use super::inode::Inode;
-use crate::prelude::*;
+use crate::{fs::vfs::notify::FsEventPublisher, prelude::*};
 
 /// Common interface implemented by each concrete file system instance.
 pub trait FileSystem: Any + Sync + Send {
@@ -167,15 +167,26 @@ define_atomic_version_of_integer_like_type!(FsFlags, {
 pub struct FsEventSubscriberStats {
     // The number of subscribers to this file system.
     num_subscribers: AtomicI64,
+    // The publisher for the subscribers to the whole file system (e.g., fanotify
+    // filesystem marks).
+    publisher: FsEventPublisher,
 }
 
 impl FsEventSubscriberStats {
     pub fn new() -> Self {
         Self {
             num_subscribers: AtomicI64::new(0),
+            publisher: FsEventPublisher::new(),
         }
     }
 
+    /// Returns the publisher for the events on all the inodes of the file system.
+    ///
+    /// The subscribers attached to it must also be counted by [`Self::add_subscriber`].
+    pub fn fs_event_publisher(&self) -> &FsEventPublisher {
+        &self.publisher
+    }
+
     pub fn add_subscriber(&self) {
         self.num_subscribers.fetch_add(1, Ordering::Release);
     }
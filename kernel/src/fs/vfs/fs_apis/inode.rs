@@ -29,7 +29,7 @@ use crate::{
     },
     security::lsm::hooks as lsm_hooks,
     time::clocks::RealTimeCoarseClock,
-    vm::page_cache::PageCache,
+    vm::{hugetlb::HugetlbFile, page_cache::PageCache},
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -261,6 +261,15 @@ impl MknodType {
             MknodType::BlockDevice(_) => Some(DeviceType::Block),
         }
     }
+
+    /// Returns the type of the inode to be created.
+    pub fn inode_type(&self) -> InodeType {
+        match self {
+            MknodType::NamedPipe => InodeType::NamedPipe,
+            MknodType::CharDevice(_) => InodeType::CharDevice,
+            MknodType::BlockDevice(_) => InodeType::BlockDevice,
+        }
+    }
 }
 
 bitflags! {
@@ -391,6 +400,11 @@ pub trait Inode: Any + FileOps + Send + Sync {
         None
     }
 
+    /// Returns the huge pages backing the inode if it is a hugetlbfs file.
+    fn hugetlb_file(&self) -> Option<Arc<HugetlbFile>> {
+        None
+    }
+
     fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
         Err(Error::new(Errno::ENOTDIR))
     }
@@ -457,6 +471,22 @@ pub trait Inode: Any + FileOps + Send + Sync {
         return_errno!(Errno::EOPNOTSUPP);
     }
 
+    /// Copies up to `len` bytes starting at `offset` of this file to `dst_offset` of `dst`,
+    /// which belongs to the same file system.
+    ///
+    /// This is the fast path of `copy_file_range`. File systems that cannot copy data
+    /// without reading it out (e.g., by offloading the copy to a remote server) should not
+    /// implement it, and the data will be copied through the kernel instead.
+    fn copy_range_to(
+        &self,
+        offset: usize,
+        dst: &dyn Inode,
+        dst_offset: usize,
+        len: usize,
+    ) -> Result<usize> {
+        return_errno!(Errno::EOPNOTSUPP);
+    }
+
     fn fs(&self) -> Arc<dyn FileSystem>;
 
     /// Returns the revalidation policy for cached children of this directory.
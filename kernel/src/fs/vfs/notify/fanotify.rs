@@ -0,0 +1,1021 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! fanotify, which notifies the user space of the accesses to files.
+//!
+//! Unlike inotify, fanotify can watch not only inodes, but also whole mounts and whole file
+//! systems (by marks with `FAN_MARK_MOUNT` and `FAN_MARK_FILESYSTEM`). Each event record
+//! identifies the accessed object either by a file descriptor opened on it, or by a file
+//! handle if `FAN_REPORT_FID` is enabled.
+//!
+//! A group in the content classes (`FAN_CLASS_CONTENT` and `FAN_CLASS_PRE_CONTENT`) can also
+//! receive permission events (`FAN_OPEN_PERM` and `FAN_ACCESS_PERM`). The accessing thread is
+//! blocked until the user space writes a response (`FAN_ALLOW` or `FAN_DENY`) to the fanotify
+//! file.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/fanotify.7.html>
+//
+// TODO: Support ignore masks, directory entry events (e.g., `FAN_CREATE`), `FAN_OPEN_EXEC`,
+// `FAN_REPORT_DIR_FID`, `FAN_REPORT_NAME`, and `FAN_REPORT_PIDFD`.
+
+use core::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
+
+use bitflags::bitflags;
+use ostd::{sync::WaitQueue, task::Task};
+
+use super::{AtomicFsEvents, FsEventPublisher, FsEventSubscriber, FsEvents};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{
+            AccessMode, CreationFlags, FileLike, InodeHandle, StatusFlags,
+            file_table::{FdFlags, FileDesc},
+        },
+        pseudofs::AnonInodeFs,
+        vfs::{
+            file_system::FileSystem,
+            inode::Inode,
+            inode_ext::InodeExt,
+            path::{Mount, Path},
+        },
+    },
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable, Pollee},
+    },
+    util::ioctl::{RawIoctl, dispatch_ioctl},
+};
+
+/// A file-like object that provides fanotify functionality.
+pub struct FanotifyFile {
+    group: Arc<FanotifyGroup>,
+    /// The pseudo path associated with this fanotify file.
+    pseudo_path: Path,
+}
+
+impl FanotifyFile {
+    /// Creates a new fanotify file.
+    ///
+    /// `event_f_flags` specifies the flags of the file descriptors opened for the event
+    /// records.
+    pub fn new(init_flags: FanotifyInitFlags, event_f_flags: u32) -> Result<Self> {
+        if init_flags
+            .contains(FanotifyInitFlags::CLASS_CONTENT | FanotifyInitFlags::CLASS_PRE_CONTENT)
+        {
+            return_errno_with_message!(Errno::EINVAL, "multiple notification classes are given");
+        }
+        if init_flags.contains(FanotifyInitFlags::REPORT_FID)
+            && init_flags
+                .intersects(FanotifyInitFlags::CLASS_CONTENT | FanotifyInitFlags::CLASS_PRE_CONTENT)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "`FAN_REPORT_FID` cannot be used with the content classes"
+            );
+        }
+
+        if event_f_flags & !EVENT_F_FLAGS_MASK != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the event file flags are invalid");
+        }
+        let event_access_mode = AccessMode::from_u32(event_f_flags)?;
+        let event_status_flags = StatusFlags::from_bits_truncate(event_f_flags);
+        let event_fd_flags = if CreationFlags::from_bits_truncate(event_f_flags)
+            .contains(CreationFlags::O_CLOEXEC)
+        {
+            FdFlags::CLOEXEC
+        } else {
+            FdFlags::empty()
+        };
+
+        let group = Arc::new(FanotifyGroup {
+            init_flags,
+            event_f_flags,
+            event_access_mode,
+            event_status_flags,
+            event_fd_flags,
+            is_nonblocking: AtomicBool::new(init_flags.contains(FanotifyInitFlags::NONBLOCK)),
+            marks: Mutex::new(Vec::new()),
+            state: SpinLock::new(State {
+                queue: VecDeque::new(),
+                pending_responses: Vec::new(),
+                is_released: false,
+            }),
+            read_mutex: Mutex::new(()),
+            pollee: Pollee::new(),
+            perm_wait_queue: WaitQueue::new(),
+        });
+
+        Ok(Self {
+            group,
+            pseudo_path: AnonInodeFs::new_path(|_| "anon_inode:[fanotify]".to_string()),
+        })
+    }
+
+    /// Adds the events to the mark on the path, creating the mark if it does not exist.
+    pub fn add_mark(&self, path: &Path, mark_type: FanotifyMarkType, mask: FsEvents) -> Result<()> {
+        self.group.check_mask(mark_type, mask)?;
+
+        let target = MarkTarget::new(path, mark_type);
+        let mut marks = self.group.marks.lock();
+
+        if let Some(mark) = marks.iter().find(|mark| mark.target.is_same(&target)) {
+            // There are no races because the mask is only updated under the `marks` lock.
+            mark.mask.store(mark.mask() | mask, Ordering::Relaxed);
+            target.with_publisher(|publisher, _| publisher.update_subscriber_events());
+            return Ok(());
+        }
+
+        if !self
+            .group
+            .init_flags
+            .contains(FanotifyInitFlags::UNLIMITED_MARKS)
+            && marks.len() >= DEFAULT_MAX_MARKS
+        {
+            return_errno_with_message!(Errno::ENOSPC, "the fanotify mark limit is reached");
+        }
+
+        let mark = Arc::new(FanotifyMark {
+            group: self.group.clone(),
+            target,
+            mask: AtomicFsEvents::new(mask),
+        });
+        let is_added = mark.target.with_publisher(|publisher, fs| {
+            if !publisher.add_subscriber(mark.clone()) {
+                return false;
+            }
+            fs.fs_event_subscriber_stats().add_subscriber();
+            true
+        });
+        if is_added != Some(true) {
+            // FIXME: This can be triggered by `unlink()` race conditions. See the comments in
+            // `InotifyFile::add_watch` for details.
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "adding a fanotify mark to a deleted inode is not supported yet"
+            );
+        }
+        marks.push(mark);
+
+        Ok(())
+    }
+
+    /// Removes the events from the mark on the path, destroying the mark if no events remain.
+    pub fn remove_mark(
+        &self,
+        path: &Path,
+        mark_type: FanotifyMarkType,
+        mask: FsEvents,
+    ) -> Result<()> {
+        self.group.check_mask(mark_type, mask)?;
+
+        let target = MarkTarget::new(path, mark_type);
+        let mut marks = self.group.marks.lock();
+
+        let Some(index) = marks.iter().position(|mark| mark.target.is_same(&target)) else {
+            return_errno_with_message!(Errno::ENOENT, "the fanotify mark does not exist");
+        };
+
+        let mark = &marks[index];
+        let new_mask = mark.mask() - mask;
+        if new_mask.is_empty() {
+            marks.swap_remove(index).detach();
+        } else {
+            mark.mask.store(new_mask, Ordering::Relaxed);
+            target.with_publisher(|publisher, _| publisher.update_subscriber_events());
+        }
+
+        Ok(())
+    }
+
+    /// Removes all the marks of the type.
+    pub fn flush_marks(&self, mark_type: FanotifyMarkType) {
+        let mut marks = self.group.marks.lock();
+
+        marks.retain(|mark| {
+            if mark.target.type_() != mark_type {
+                return true;
+            }
+            mark.detach();
+            false
+        });
+    }
+}
+
+impl Drop for FanotifyFile {
+    fn drop(&mut self) {
+        self.group.release();
+    }
+}
+
+impl Pollable for FanotifyFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.group
+            .pollee
+            .poll_with(mask, poller, || self.group.check_io_events())
+    }
+}
+
+impl FileLike for FanotifyFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if self.group.is_nonblocking.load(Ordering::Relaxed) {
+            self.group.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.group.try_read(writer))
+        }
+    }
+
+    fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        let response_len = size_of::<FanotifyResponse>();
+        if reader.remain() < response_len {
+            return_errno_with_message!(Errno::EINVAL, "the response buffer is too small");
+        }
+
+        let response = reader.read_val::<FanotifyResponse>()?;
+        self.group.respond(&response)?;
+
+        Ok(response_len)
+    }
+
+    fn ioctl(&self, raw_ioctl: RawIoctl) -> Result<i32> {
+        use crate::util::ioctl::common_defs::GetNumBytesToRead;
+
+        dispatch_ioctl!(match raw_ioctl {
+            cmd @ GetNumBytesToRead => {
+                let size = self.group.get_all_event_size() as i32;
+
+                cmd.write(&size)?;
+                Ok(0)
+            }
+            _ => return_errno_with_message!(Errno::ENOTTY, "the ioctl command is unknown"),
+        })
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.group.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.group.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/notify/fanotify/fanotify_user.c>
+        AccessMode::O_RDWR
+    }
+
+    fn path(&self) -> &Path {
+        &self.pseudo_path
+    }
+
+    fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
+        struct FdInfo {
+            inner: Arc<FanotifyFile>,
+            fd_flags: FdFlags,
+        }
+
+        impl Display for FdInfo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                let mut flags = self.inner.status_flags().bits() | self.inner.access_mode() as u32;
+                if self.fd_flags.contains(FdFlags::CLOEXEC) {
+                    flags |= CreationFlags::O_CLOEXEC.bits();
+                }
+
+                let group = &self.inner.group;
+
+                writeln!(f, "pos:\t{}", 0)?;
+                writeln!(f, "flags:\t0{:o}", flags)?;
+                writeln!(f, "mnt_id:\t{}", AnonInodeFs::mount_node().id())?;
+                writeln!(f, "ino:\t{}", AnonInodeFs::shared_inode().ino())?;
+                writeln!(
+                    f,
+                    "fanotify flags:{:x} event-flags:{:x}",
+                    group.init_flags.bits(),
+                    group.event_f_flags
+                )?;
+
+                for mark in group.marks.lock().iter() {
+                    let mask = mark.mask().bits();
+                    match &mark.target {
+                        MarkTarget::Inode(inode) => {
+                            let Some(inode) = inode.upgrade() else {
+                                continue;
+                            };
+                            writeln!(
+                                f,
+                                "fanotify ino:{:x} sdev:{:x} mflags:0 mask:{:x} ignored_mask:0",
+                                inode.ino(),
+                                inode.fs().sb().fsid,
+                                mask
+                            )?;
+                        }
+                        MarkTarget::Mount(mount) => {
+                            let Some(mount) = mount.upgrade() else {
+                                continue;
+                            };
+                            writeln!(
+                                f,
+                                "fanotify mnt_id:{:x} mflags:0 mask:{:x} ignored_mask:0",
+                                mount.id(),
+                                mask
+                            )?;
+                        }
+                        MarkTarget::Filesystem(fs) => {
+                            let Some(fs) = fs.upgrade() else {
+                                continue;
+                            };
+                            writeln!(
+                                f,
+                                "fanotify sdev:{:x} mflags:0 mask:{:x} ignored_mask:0",
+                                fs.sb().fsid,
+                                mask
+                            )?;
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        Box::new(FdInfo {
+            inner: self,
+            fd_flags,
+        })
+    }
+}
+
+/// A fanotify group, which receives the events from its marks.
+///
+/// The marks keep a reference to the group, so the group outlives the fanotify file until
+/// all the marks are detached when the file is closed.
+pub struct FanotifyGroup {
+    init_flags: FanotifyInitFlags,
+    event_f_flags: u32,
+    event_access_mode: AccessMode,
+    event_status_flags: StatusFlags,
+    event_fd_flags: FdFlags,
+    is_nonblocking: AtomicBool,
+    marks: Mutex<Vec<Arc<FanotifyMark>>>,
+    state: SpinLock<State>,
+    /// A mutex to synchronize `read()` operations.
+    read_mutex: Mutex<()>,
+    pollee: Pollee,
+    /// The wait queue for the threads waiting for the responses to permission events.
+    perm_wait_queue: WaitQueue,
+}
+
+struct State {
+    /// The events that have not been read.
+    queue: VecDeque<FanotifyEvent>,
+    /// The permission events that have been read and wait for responses, along with the
+    /// file descriptors reported in their event records.
+    pending_responses: Vec<(i32, Arc<AtomicU32>)>,
+    /// Whether the fanotify file has been closed.
+    is_released: bool,
+}
+
+struct FanotifyEvent {
+    mask: FsEvents,
+    /// The path of the accessed object, or `None` for an overflow event.
+    path: Option<Path>,
+    pid: u32,
+    /// The response to the permission event, or `None` if the event is not a permission
+    /// event.
+    ///
+    /// The response is zero if the user space has not responded.
+    response: Option<Arc<AtomicU32>>,
+}
+
+impl FanotifyGroup {
+    fn is_fid_mode(&self) -> bool {
+        self.init_flags.contains(FanotifyInitFlags::REPORT_FID)
+    }
+
+    fn check_mask(&self, mark_type: FanotifyMarkType, mask: FsEvents) -> Result<()> {
+        let mut supported = FANOTIFY_EVENTS | FsEvents::ISDIR | FsEvents::EVENT_ON_CHILD;
+        if self.is_fid_mode() && mark_type != FanotifyMarkType::Mount {
+            supported |= FsEvents::ATTRIB;
+        }
+        if !self
+            .init_flags
+            .intersects(FanotifyInitFlags::CLASS_CONTENT | FanotifyInitFlags::CLASS_PRE_CONTENT)
+        {
+            supported -= FANOTIFY_PERM_EVENTS;
+        }
+
+        if !supported.contains(mask) {
+            return_errno_with_message!(Errno::EINVAL, "the events are not supported");
+        }
+        Ok(())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.state.lock().queue.is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+
+    /// Returns the mask reported in the event record.
+    fn reported_mask(&self, events: FsEvents) -> FsEvents {
+        // `FAN_ONDIR` is reported only if `FAN_REPORT_FID` is enabled.
+        if self.is_fid_mode() {
+            events
+        } else {
+            events - FsEvents::ISDIR
+        }
+    }
+
+    /// Returns the PID (or TID if `FAN_REPORT_TID` is enabled) of the current thread.
+    fn current_pid(&self) -> u32 {
+        let Some(task) = Task::current() else {
+            return 0;
+        };
+        let Some(posix_thread) = task.as_posix_thread() else {
+            return 0;
+        };
+
+        if self.init_flags.contains(FanotifyInitFlags::REPORT_TID) {
+            posix_thread.tid()
+        } else {
+            posix_thread.process().pid()
+        }
+    }
+
+    /// Queues an event.
+    ///
+    /// If the event can be merged with the last event in the queue, it will be merged.
+    fn receive_event(&self, events: FsEvents, path: &Path) {
+        let mask = self.reported_mask(events);
+        let pid = self.current_pid();
+
+        {
+            let mut state = self.state.lock();
+            if state.is_released {
+                return;
+            }
+
+            if let Some(last_event) = state.queue.back_mut()
+                && last_event.response.is_none()
+                && last_event.pid == pid
+                && last_event.path.as_ref() == Some(path)
+            {
+                last_event.mask |= mask;
+                return;
+            }
+
+            if !self.init_flags.contains(FanotifyInitFlags::UNLIMITED_QUEUE)
+                && state.queue.len() >= DEFAULT_MAX_QUEUED_EVENTS
+            {
+                // Queue an overflow event to alert the user, if it has not been queued.
+                if state
+                    .queue
+                    .back()
+                    .is_some_and(|last_event| last_event.path.is_none())
+                {
+                    return;
+                }
+                state.queue.push_back(FanotifyEvent {
+                    mask: FsEvents::Q_OVERFLOW,
+                    path: None,
+                    pid: 0,
+                    response: None,
+                });
+            } else {
+                state.queue.push_back(FanotifyEvent {
+                    mask,
+                    path: Some(path.clone()),
+                    pid,
+                    response: None,
+                });
+            }
+        }
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    /// Queues a permission event and waits for the response.
+    ///
+    /// If the user space denies the access, an [`EPERM`] error will be returned. The waiting
+    /// can be interrupted by signals, in which case an [`EINTR`] error will be returned.
+    ///
+    /// [`EPERM`]: Errno::EPERM
+    /// [`EINTR`]: Errno::EINTR
+    pub(super) fn request_perm(&self, events: FsEvents, path: &Path) -> Result<()> {
+        let response = Arc::new(AtomicU32::new(0));
+
+        {
+            let mut state = self.state.lock();
+            if state.is_released {
+                return Ok(());
+            }
+
+            // Permission events are never dropped or merged.
+            state.queue.push_back(FanotifyEvent {
+                mask: self.reported_mask(events),
+                path: Some(path.clone()),
+                pid: self.current_pid(),
+                response: Some(response.clone()),
+            });
+        }
+        self.pollee.notify(IoEvents::IN);
+
+        let res = self.perm_wait_queue.pause_until(|| {
+            let response = response.load(Ordering::Acquire);
+            (response != 0).then_some(response)
+        });
+
+        match res {
+            Ok(FAN_DENY) => {
+                return_errno_with_message!(Errno::EPERM, "the access is denied by fanotify")
+            }
+            Ok(_) => Ok(()),
+            Err(err) => {
+                // No one needs to respond to the event anymore.
+                let mut state = self.state.lock();
+                state.queue.retain(|event| {
+                    !event
+                        .response
+                        .as_ref()
+                        .is_some_and(|other| Arc::ptr_eq(other, &response))
+                });
+                state
+                    .pending_responses
+                    .retain(|(_, other)| !Arc::ptr_eq(other, &response));
+                if state.queue.is_empty() {
+                    self.pollee.invalidate();
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Handles a response to a permission event written by the user space.
+    fn respond(&self, response: &FanotifyResponse) -> Result<()> {
+        if response.response != FAN_ALLOW && response.response != FAN_DENY {
+            return_errno_with_message!(Errno::EINVAL, "the response is invalid");
+        }
+        if response.fd < 0 {
+            return_errno_with_message!(Errno::EINVAL, "the file descriptor is invalid");
+        }
+
+        let mut state = self.state.lock();
+        let Some(index) = state
+            .pending_responses
+            .iter()
+            .position(|(fd, _)| *fd == response.fd)
+        else {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "no permission events are waiting for the response"
+            );
+        };
+        let (_, pending_response) = state.pending_responses.swap_remove(index);
+        drop(state);
+
+        pending_response.store(response.response, Ordering::Release);
+        self.perm_wait_queue.wake_all();
+
+        Ok(())
+    }
+
+    /// Returns the length of the event record.
+    fn event_len(&self, event: &FanotifyEvent) -> usize {
+        if self.is_fid_mode() && event.path.is_some() {
+            size_of::<FanotifyEventMetadata>() + size_of::<FanotifyEventInfoFid>()
+        } else {
+            size_of::<FanotifyEventMetadata>()
+        }
+    }
+
+    /// Gets the total size of all event records in the queue.
+    fn get_all_event_size(&self) -> usize {
+        let state = self.state.lock();
+
+        state.queue.iter().map(|event| self.event_len(event)).sum()
+    }
+
+    /// Tries to read event records from the queue.
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        // This ensures that we report continuous events even when the user program attempts to
+        // call `read()` concurrently.
+        let _guard = self.read_mutex.lock();
+
+        let mut size = 0;
+
+        loop {
+            let event = {
+                let mut state = self.state.lock();
+
+                let Some(event) = state.queue.front() else {
+                    break;
+                };
+                if self.event_len(event) > writer.avail() {
+                    if size == 0 {
+                        return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+                    }
+                    break;
+                }
+
+                let event = state.queue.pop_front().unwrap();
+                // Invalidate when the queue is empty.
+                if state.queue.is_empty() {
+                    self.pollee.invalidate();
+                }
+                event
+            };
+
+            match self.copy_event_to_user(&event, writer) {
+                Ok(event_len) => size += event_len,
+                Err(err) => {
+                    // The event record cannot be reported, so the access is denied, as Linux
+                    // does.
+                    if let Some(response) = event.response {
+                        response.store(FAN_DENY, Ordering::Release);
+                        self.perm_wait_queue.wake_all();
+                    }
+                    if size == 0 {
+                        return Err(err);
+                    }
+                    break;
+                }
+            }
+        }
+
+        if size == 0 {
+            return_errno_with_message!(Errno::EAGAIN, "no fanotify events are available");
+        }
+
+        Ok(size)
+    }
+
+    fn copy_event_to_user(&self, event: &FanotifyEvent, writer: &mut VmWriter) -> Result<usize> {
+        let event_len = self.event_len(event);
+
+        let fd = match event.path.as_ref() {
+            Some(path) if !self.is_fid_mode() => Some(self.open_event_fd(path)?),
+            _ => None,
+        };
+
+        let metadata = FanotifyEventMetadata {
+            event_len: event_len as u32,
+            vers: FANOTIFY_METADATA_VERSION,
+            reserved: 0,
+            metadata_len: size_of::<FanotifyEventMetadata>() as u16,
+            mask: event.mask.bits() as u64,
+            fd: fd.map_or(FAN_NOFD, i32::from),
+            pid: event.pid as i32,
+        };
+        let res = writer.write_val(&metadata).and_then(|_| {
+            if let Some(path) = event.path.as_ref()
+                && self.is_fid_mode()
+            {
+                writer.write_val(&FanotifyEventInfoFid::new(path.inode()))?;
+            }
+            Ok(())
+        });
+
+        if let Err(err) = res {
+            if let Some(fd) = fd {
+                close_event_fd(fd);
+            }
+            return Err(err);
+        }
+
+        if let Some(fd) = fd
+            && let Some(response) = event.response.as_ref()
+        {
+            self.state
+                .lock()
+                .pending_responses
+                .push((fd.into(), response.clone()));
+        }
+
+        Ok(event_len)
+    }
+
+    /// Opens a file descriptor on the path for an event record.
+    fn open_event_fd(&self, path: &Path) -> Result<FileDesc> {
+        let file = InodeHandle::new_unchecked_access(
+            path.clone(),
+            self.event_access_mode,
+            self.event_status_flags,
+        )?
+        .disable_fs_notify();
+
+        let current = Task::current().unwrap();
+        let file_table = current.as_thread_local().unwrap().borrow_file_table();
+        let fd = file_table
+            .unwrap()
+            .write()
+            .insert(Arc::new(file), self.event_fd_flags);
+
+        Ok(fd)
+    }
+
+    /// Releases the group after the fanotify file is closed.
+    fn release(&self) {
+        let marks = core::mem::take(&mut *self.marks.lock());
+        for mark in marks {
+            mark.detach();
+        }
+
+        // Allow all the accesses that wait for responses.
+        {
+            let mut state = self.state.lock();
+            state.is_released = true;
+
+            for event in state.queue.drain(..) {
+                if let Some(response) = event.response {
+                    response.store(FAN_ALLOW, Ordering::Release);
+                }
+            }
+            for (_, response) in state.pending_responses.drain(..) {
+                response.store(FAN_ALLOW, Ordering::Release);
+            }
+        }
+        self.perm_wait_queue.wake_all();
+    }
+}
+
+/// Closes a file descriptor opened for an event record.
+fn close_event_fd(fd: FileDesc) {
+    let current = Task::current().unwrap();
+    let file_table = current.as_thread_local().unwrap().borrow_file_table();
+    file_table.unwrap().write().close_file(fd);
+}
+
+/// The type of a fanotify mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FanotifyMarkType {
+    /// A mark on an inode (the default).
+    Inode,
+    /// A mark on a mount (`FAN_MARK_MOUNT`).
+    Mount,
+    /// A mark on a file system (`FAN_MARK_FILESYSTEM`).
+    Filesystem,
+}
+
+/// The object on which a fanotify mark is placed.
+enum MarkTarget {
+    Inode(Weak<dyn Inode>),
+    Mount(Weak<Mount>),
+    Filesystem(Weak<dyn FileSystem>),
+}
+
+impl MarkTarget {
+    fn new(path: &Path, mark_type: FanotifyMarkType) -> Self {
+        match mark_type {
+            FanotifyMarkType::Inode => Self::Inode(Arc::downgrade(path.inode())),
+            FanotifyMarkType::Mount => Self::Mount(Arc::downgrade(path.mount_node())),
+            FanotifyMarkType::Filesystem => Self::Filesystem(Arc::downgrade(&path.fs())),
+        }
+    }
+
+    fn type_(&self) -> FanotifyMarkType {
+        match self {
+            Self::Inode(_) => FanotifyMarkType::Inode,
+            Self::Mount(_) => FanotifyMarkType::Mount,
+            Self::Filesystem(_) => FanotifyMarkType::Filesystem,
+        }
+    }
+
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Inode(this), Self::Inode(other)) => Weak::ptr_eq(this, other),
+            (Self::Mount(this), Self::Mount(other)) => Weak::ptr_eq(this, other),
+            (Self::Filesystem(this), Self::Filesystem(other)) => Weak::ptr_eq(this, other),
+            _ => false,
+        }
+    }
+
+    /// Calls `f` with the publisher of the object and the file system of the object.
+    ///
+    /// Returns `None` if the object is dead.
+    fn with_publisher<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&FsEventPublisher, &dyn FileSystem) -> R,
+    {
+        match self {
+            Self::Inode(inode) => {
+                let inode = inode.upgrade()?;
+                Some(f(inode.fs_event_publisher_or_init(), inode.fs().as_ref()))
+            }
+            Self::Mount(mount) => {
+                let mount = mount.upgrade()?;
+                Some(f(mount.fs_event_publisher(), mount.fs().as_ref()))
+            }
+            Self::Filesystem(fs) => {
+                let fs = fs.upgrade()?;
+                Some(f(
+                    fs.fs_event_subscriber_stats().fs_event_publisher(),
+                    fs.as_ref(),
+                ))
+            }
+        }
+    }
+}
+
+/// A fanotify mark, which subscribes to the events on its object for a fanotify group.
+struct FanotifyMark {
+    group: Arc<FanotifyGroup>,
+    target: MarkTarget,
+    /// The events and the flags (`FAN_ONDIR` and `FAN_EVENT_ON_CHILD`) of the mark.
+    mask: AtomicFsEvents,
+}
+
+impl FanotifyMark {
+    fn mask(&self) -> FsEvents {
+        self.mask.load(Ordering::Relaxed)
+    }
+
+    /// Returns the events that should be reported to the group.
+    ///
+    /// `is_child` indicates whether the events occur on a child of the marked directory.
+    fn match_events(&self, events: FsEvents, is_child: bool) -> Option<FsEvents> {
+        let mask = self.mask();
+
+        if is_child && !mask.contains(FsEvents::EVENT_ON_CHILD) {
+            return None;
+        }
+        if events.contains(FsEvents::ISDIR) && !mask.contains(FsEvents::ISDIR) {
+            return None;
+        }
+
+        let matched = events & mask & FANOTIFY_EVENTS;
+        if matched.is_empty() {
+            return None;
+        }
+        Some(matched | (events & FsEvents::ISDIR))
+    }
+
+    /// Detaches the mark from its object.
+    fn detach(self: &Arc<Self>) {
+        self.target.with_publisher(|publisher, fs| {
+            if publisher.remove_subscriber(&(self.clone() as _)) {
+                fs.fs_event_subscriber_stats().remove_subscriber();
+            }
+        });
+    }
+}
+
+impl FsEventSubscriber for FanotifyMark {
+    fn deliver_event(&self, events: FsEvents, name: Option<String>, path: Option<&Path>) -> bool {
+        // Events that are not associated with a path (e.g., `FsEvents::IN_IGNORED`) are not
+        // reported.
+        let Some(path) = path else {
+            return false;
+        };
+
+        // Only the publisher of the parent directory delivers events with names.
+        if let Some(events) = self.match_events(events, name.is_some()) {
+            self.group.receive_event(events, path);
+        }
+
+        false
+    }
+
+    fn interesting_events(&self) -> FsEvents {
+        self.mask() - FsEvents::ISDIR - FsEvents::EVENT_ON_CHILD
+    }
+
+    fn match_perm_event(
+        &self,
+        events: FsEvents,
+        _path: &Path,
+        is_child: bool,
+    ) -> Option<Arc<FanotifyGroup>> {
+        self.match_events(events, is_child)
+            .map(|_| self.group.clone())
+    }
+}
+
+/// The default maximum number of queued events.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/notify/fanotify/fanotify_user.c>
+const DEFAULT_MAX_QUEUED_EVENTS: usize = 16384;
+
+/// The default maximum number of marks in a group.
+const DEFAULT_MAX_MARKS: usize = 8192;
+
+/// The events that can be reported by fanotify.
+const FANOTIFY_EVENTS: FsEvents = FsEvents::ACCESS
+    .union(FsEvents::MODIFY)
+    .union(FsEvents::ATTRIB)
+    .union(FsEvents::CLOSE_WRITE)
+    .union(FsEvents::CLOSE_NOWRITE)
+    .union(FsEvents::OPEN)
+    .union(FANOTIFY_PERM_EVENTS);
+
+/// The permission events that can be reported by fanotify.
+const FANOTIFY_PERM_EVENTS: FsEvents = FsEvents::OPEN_PERM.union(FsEvents::ACCESS_PERM);
+
+/// The flags that can be specified in `event_f_flags`.
+const EVENT_F_FLAGS_MASK: u32 = 0b11 // O_ACCMODE
+    | StatusFlags::O_APPEND.bits()
+    | StatusFlags::O_NONBLOCK.bits()
+    | StatusFlags::O_SYNC.bits()
+    | StatusFlags::O_DSYNC.bits()
+    | StatusFlags::O_NOATIME.bits()
+    | CreationFlags::O_CLOEXEC.bits()
+    | O_LARGEFILE;
+
+/// The `O_LARGEFILE` flag, which is implied on 64-bit platforms.
+const O_LARGEFILE: u32 = 0o100000;
+
+const FANOTIFY_METADATA_VERSION: u8 = 3;
+
+const FAN_NOFD: i32 = -1;
+
+const FAN_ALLOW: u32 = 0x01;
+const FAN_DENY: u32 = 0x02;
+
+const FAN_EVENT_INFO_TYPE_FID: u8 = 1;
+
+/// The file handle type for a 64-bit inode number and a 32-bit generation number.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/exportfs.h>
+const FILEID_INO64_GEN: i32 = 0x81;
+
+bitflags! {
+    /// The flags of `fanotify_init`.
+    pub struct FanotifyInitFlags: u32 {
+        const CLOEXEC           = 0x0000_0001;
+        const NONBLOCK          = 0x0000_0002;
+        const CLASS_CONTENT     = 0x0000_0004;
+        const CLASS_PRE_CONTENT = 0x0000_0008;
+        const UNLIMITED_QUEUE   = 0x0000_0010;
+        const UNLIMITED_MARKS   = 0x0000_0020;
+        const REPORT_TID        = 0x0000_0100;
+        const REPORT_FID        = 0x0000_0200;
+    }
+}
+
+/// The metadata of an event record (`struct fanotify_event_metadata`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct FanotifyEventMetadata {
+    event_len: u32,
+    vers: u8,
+    reserved: u8,
+    metadata_len: u16,
+    mask: u64,
+    fd: i32,
+    pid: i32,
+}
+
+/// The information record of a file handle (`struct fanotify_event_info_fid`).
+///
+/// The file handle (`struct file_handle`) is inlined, which consists of a 64-bit inode number
+/// and a 32-bit generation number.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct FanotifyEventInfoFid {
+    info_type: u8,
+    pad: u8,
+    len: u16,
+    fsid: [u32; 2],
+    handle_bytes: u32,
+    handle_type: i32,
+    f_handle: [u8; 12],
+}
+
+impl FanotifyEventInfoFid {
+    fn new(inode: &Arc<dyn Inode>) -> Self {
+        let fsid = inode.fs().sb().fsid;
+
+        // The generation number is always zero.
+        let mut f_handle = [0u8; 12];
+        f_handle[..8].copy_from_slice(&inode.ino().to_le_bytes());
+
+        Self {
+            info_type: FAN_EVENT_INFO_TYPE_FID,
+            pad: 0,
+            len: size_of::<Self>() as u16,
+            fsid: [fsid as u32, (fsid >> 32) as u32],
+            handle_bytes: f_handle.len() as u32,
+            handle_type: FILEID_INO64_GEN,
+            f_handle,
+        }
+    }
+}
+
+/// A response to a permission event (`struct fanotify_response`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct FanotifyResponse {
+    fd: i32,
+    response: u32,
+}
@@ -524,7 +524,7 @@ impl InotifySubscriber {
 
 impl FsEventSubscriber for InotifySubscriber {
     /// Sends FS events to the inotify file.
-    fn deliver_event(&self, event: FsEvents, name: Option<String>) -> bool {
+    fn deliver_event(&self, event: FsEvents, name: Option<String>, _path: Option<&Path>) -> bool {
         let (interesting, options) = self.interesting_and_controls();
 
         if !event.contains(FsEvents::IN_IGNORED) && !is_interesting(interesting, event) {
@@ -7,14 +7,17 @@ use bitflags::bitflags;
 
 use crate::{
     fs::{
-        file::{AccessMode, FileLike, InodeType, StatusFlags},
+        file::{AccessMode, FileLike, InodeHandle, InodeType, StatusFlags},
         vfs::path::Path,
     },
     prelude::*,
 };
 
+pub mod fanotify;
 pub mod inotify;
 
+use fanotify::FanotifyGroup;
+
 use crate::fs::vfs::{inode::Inode, inode_ext::InodeExt};
 
 /// Publishes filesystem events to subscribers.
@@ -82,7 +85,7 @@ impl FsEventPublisher {
 
         let removed = subscribers.len() != orig_len;
         if removed {
-            subscriber.deliver_event(FsEvents::IN_IGNORED, None);
+            subscriber.deliver_event(FsEvents::IN_IGNORED, None, None);
         }
 
         removed
@@ -93,7 +96,7 @@ impl FsEventPublisher {
         let mut subscribers = self.subscribers.write();
 
         for subscriber in subscribers.iter() {
-            subscriber.deliver_event(FsEvents::IN_IGNORED, None);
+            subscriber.deliver_event(FsEvents::IN_IGNORED, None, None);
         }
 
         let num_subscribers = subscribers.len();
@@ -116,7 +119,9 @@ impl FsEventPublisher {
     }
 
     /// Broadcasts an event to all the subscribers of this publisher.
-    pub fn publish_event(&self, events: FsEvents, name: Option<String>) {
+    ///
+    /// `path` is the path of the object on which the event occurs, if it is available.
+    pub fn publish_event(&self, events: FsEvents, name: Option<String>, path: Option<&Path>) {
         let interesting = self.all_interesting_events.load(Ordering::Relaxed);
         if !interesting.intersects(events) {
             return;
@@ -125,7 +130,7 @@ impl FsEventPublisher {
         let subscribers = self.subscribers.read();
         let mut has_oneshot = false;
         for subscriber in subscribers.iter() {
-            has_oneshot |= subscriber.deliver_event(events, name.clone());
+            has_oneshot |= subscriber.deliver_event(events, name.clone(), path);
         }
         drop(subscribers);
 
@@ -137,6 +142,34 @@ impl FsEventPublisher {
         }
     }
 
+    /// Collects the fanotify groups of the subscribers that are interested in a permission
+    /// event.
+    ///
+    /// `is_child` indicates whether the publisher belongs to the parent directory of the
+    /// object on which the event occurs.
+    pub fn collect_perm_groups(
+        &self,
+        events: FsEvents,
+        path: &Path,
+        is_child: bool,
+        groups: &mut Vec<Arc<FanotifyGroup>>,
+    ) {
+        let interesting = self.all_interesting_events.load(Ordering::Relaxed);
+        if !interesting.intersects(events) {
+            return;
+        }
+
+        let subscribers = self.subscribers.read();
+        for subscriber in subscribers.iter() {
+            let Some(group) = subscriber.match_perm_event(events, path, is_child) else {
+                continue;
+            };
+            if !groups.iter().any(|other| Arc::ptr_eq(other, &group)) {
+                groups.push(group);
+            }
+        }
+    }
+
     /// Updates the aggregated events when a subscriber's interesting events change.
     pub fn update_subscriber_events(&self) {
         // Take a write lock to avoid race conditions that may change `all_interesting_events` to
@@ -201,9 +234,29 @@ pub trait FsEventSubscriber: Any + Send + Sync {
     /// delivered. If there are no one-shot subscribers, simply return `false` here.
     /// Otherwise, [`Self::is_oneshot_and_dead`] should be implemented correspondingly.
     ///
+    /// `path` is the path of the object on which the event occurs. It is `None` if the
+    /// event is not associated with a path (e.g., when a directory entry is deleted).
+    ///
     /// Invariant: This method must not sleep or perform blocking operations. The publisher
     /// may hold a spin lock when calling this method.
-    fn deliver_event(&self, events: FsEvents, name: Option<String>) -> bool;
+    fn deliver_event(&self, events: FsEvents, name: Option<String>, path: Option<&Path>) -> bool;
+
+    /// Returns the fanotify group that should decide on a permission event, if the
+    /// subscriber is interested in the event.
+    ///
+    /// `is_child` indicates whether the subscriber watches the parent directory of the
+    /// object on which the event occurs.
+    ///
+    /// Invariant: This method must not sleep or perform blocking operations, as with
+    /// [`Self::deliver_event`].
+    fn match_perm_event(
+        &self,
+        _events: FsEvents,
+        _path: &Path,
+        _is_child: bool,
+    ) -> Option<Arc<FanotifyGroup>> {
+        None
+    }
 
     /// Returns the events that this subscriber is interested in.
     fn interesting_events(&self) -> FsEvents;
@@ -271,24 +324,52 @@ define_atomic_version_of_integer_like_type!(FsEvents, {
 
 /// Notifies that a file was accessed.
 pub fn on_access(file: &Arc<dyn FileLike>) {
-    let path = file.path();
-
-    if !path.fs().fs_event_subscriber_stats().has_any_subscribers() {
+    let Some(path) = notifiable_path(file) else {
         return;
-    }
+    };
     notify_parent(path, FsEvents::ACCESS);
 }
 
 /// Notifies that a file was modified.
 pub fn on_modify(file: &Arc<dyn FileLike>) {
-    let path = file.path();
-
-    if !path.fs().fs_event_subscriber_stats().has_any_subscribers() {
+    let Some(path) = notifiable_path(file) else {
         return;
-    }
+    };
     notify_parent(path, FsEvents::MODIFY);
 }
 
+/// Asks for the permission to open a file.
+///
+/// This blocks until all the fanotify groups interested in `FAN_OPEN_PERM` events have
+/// responded. If any of them denies the access, an [`EPERM`] error will be returned.
+///
+/// [`EPERM`]: Errno::EPERM
+pub fn check_open_perm(file: &Arc<dyn FileLike>) -> Result<()> {
+    let Some(path) = notifiable_path(file) else {
+        return Ok(());
+    };
+    if !matches!(path.type_(), InodeType::File | InodeType::Dir) {
+        return Ok(());
+    }
+    check_perm(path, FsEvents::OPEN_PERM)
+}
+
+/// Asks for the permission to read a file.
+///
+/// This blocks until all the fanotify groups interested in `FAN_ACCESS_PERM` events have
+/// responded. If any of them denies the access, an [`EPERM`] error will be returned.
+///
+/// [`EPERM`]: Errno::EPERM
+pub fn check_access_perm(file: &Arc<dyn FileLike>) -> Result<()> {
+    let Some(path) = notifiable_path(file) else {
+        return Ok(());
+    };
+    if path.type_() != InodeType::File {
+        return Ok(());
+    }
+    check_perm(path, FsEvents::ACCESS_PERM)
+}
+
 /// Notifies that a path's content was changed.
 pub fn on_change(path: &Path) {
     if !path.fs().fs_event_subscriber_stats().has_any_subscribers() {
@@ -393,12 +474,19 @@ pub fn on_close(file: &Arc<dyn FileLike>) {
 /// Returns the file's path if open/close events should be emitted for it.
 ///
 /// `O_PATH` file descriptors carry `FMODE_NONOTIFY` in Linux's `f_mode` and
-/// thus suppress all fsnotify events, so they yield `None`. `None` is also
+/// thus suppress all fsnotify events, so they yield `None`. So do the file
+/// descriptors opened by fanotify for its event records. `None` is also
 /// returned when the filesystem has no event subscribers.
 fn notifiable_path(file: &Arc<dyn FileLike>) -> Option<&Path> {
     if file.status_flags().contains(StatusFlags::O_PATH) {
         return None;
     }
+    if file
+        .downcast_ref::<InodeHandle>()
+        .is_some_and(|inode_handle| inode_handle.is_fs_notify_disabled())
+    {
+        return None;
+    }
     let path = file.path();
     if !path.fs().fs_event_subscriber_stats().has_any_subscribers() {
         return None;
@@ -423,16 +511,61 @@ pub fn on_attr_change(path: &Path) {
 ///
 /// The child's real name (from `path.name()`) is used to notify the parent, since
 /// FS events do not cross mount boundaries.
+///
+/// The subscribers of the path's mount and file system (i.e., fanotify mount and
+/// filesystem marks) are notified as well.
 fn notify_parent(path: &Path, mut events: FsEvents) {
     if path.inode().type_() == InodeType::Dir {
         events |= FsEvents::ISDIR;
     }
 
     let parent = path.parent_within_mount();
-    if let Some(parent) = parent {
-        notify_inode_with_name(parent.inode(), events, || path.name());
+    if let Some(parent) = parent
+        && let Some(publisher) = parent.inode().fs_event_publisher()
+    {
+        publisher.publish_event(events, Some(path.name()), Some(path));
+    }
+    if let Some(publisher) = path.inode().fs_event_publisher() {
+        publisher.publish_event(events, None, Some(path));
+    }
+    path.mount_node()
+        .fs_event_publisher()
+        .publish_event(events, None, Some(path));
+    path.fs()
+        .fs_event_subscriber_stats()
+        .fs_event_publisher()
+        .publish_event(events, None, Some(path));
+}
+
+/// Asks the fanotify groups interested in a permission event on a path for the
+/// permission.
+fn check_perm(path: &Path, mut events: FsEvents) -> Result<()> {
+    if path.inode().type_() == InodeType::Dir {
+        events |= FsEvents::ISDIR;
     }
-    notify_inode(path.inode(), events);
+
+    let mut groups = Vec::new();
+    if let Some(parent) = path.parent_within_mount()
+        && let Some(publisher) = parent.inode().fs_event_publisher()
+    {
+        publisher.collect_perm_groups(events, path, true, &mut groups);
+    }
+    if let Some(publisher) = path.inode().fs_event_publisher() {
+        publisher.collect_perm_groups(events, path, false, &mut groups);
+    }
+    path.mount_node()
+        .fs_event_publisher()
+        .collect_perm_groups(events, path, false, &mut groups);
+    path.fs()
+        .fs_event_subscriber_stats()
+        .fs_event_publisher()
+        .collect_perm_groups(events, path, false, &mut groups);
+
+    for group in groups {
+        group.request_perm(events, path)?;
+    }
+
+    Ok(())
 }
 
 /// Sends a filesystem notification event to all subscribers of an inode.
@@ -442,7 +575,7 @@ fn notify_parent(path: &Path, mut events: FsEvents) {
 /// to all registered subscribers through the inode's publisher.
 fn notify_inode(inode: &Arc<dyn Inode>, events: FsEvents) {
     if let Some(publisher) = inode.fs_event_publisher() {
-        publisher.publish_event(events, None);
+        publisher.publish_event(events, None, None);
     }
 }
 
@@ -452,6 +585,6 @@ fn notify_inode(inode: &Arc<dyn Inode>, events: FsEvents) {
 /// child name information (e.g., CREATE, DELETE).
 fn notify_inode_with_name(inode: &Arc<dyn Inode>, events: FsEvents, name: impl FnOnce() -> String) {
     if let Some(publisher) = inode.fs_event_publisher() {
-        publisher.publish_event(events, Some(name()));
+        publisher.publish_event(events, Some(name()), None);
     }
 }
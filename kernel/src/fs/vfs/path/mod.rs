@@ -30,7 +30,7 @@ use crate::{
     process::{
         Gid, Uid, UserNamespace, credentials::capabilities::CapSet, posix_thread::AsPosixThread,
     },
-    security::lsm::hooks as lsm_hooks,
+    security::lsm::hooks::{self as lsm_hooks, PathOperation},
 };
 
 mod dentry;
@@ -67,6 +67,7 @@ impl Path {
     pub fn new_fs_child(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Self> {
         let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
         self.check_dir_entry_mutation()?;
+        lsm_hooks::on_current_path_operation(PathOperation::Create { dir: self, type_ })?;
         let new_child_dentry = dir_dentry.create(name, type_, mode)?;
         Ok(Self::new(self.mount.clone(), new_child_dentry))
     }
@@ -151,6 +152,8 @@ impl Path {
             );
         }
 
+        let inode_handle = InodeHandle::new(self.clone(), open_args.access_mode, *status_flags)?;
+
         if inode_type.is_regular_file()
             && creation_flags.contains(CreationFlags::O_TRUNC)
             && !status_flags.contains(StatusFlags::O_PATH)
@@ -158,7 +161,7 @@ impl Path {
             self.resize(0)?;
         }
 
-        InodeHandle::new(self.clone(), open_args.access_mode, *status_flags)
+        Ok(inode_handle)
     }
 
     /// Gets the parent `Path` within the same mount.
@@ -173,6 +176,21 @@ impl Path {
         Some(Self::new(self.mount.clone(), parent))
     }
 
+    /// Gets the parent `Path`, crossing mount boundaries if necessary.
+    ///
+    /// If the current path is the root of a mount, the parent of its mount point is returned.
+    /// Returns `None` if the current path is the root of the VFS tree.
+    pub fn effective_parent(&self) -> Option<Self> {
+        let mut current = self.clone();
+        while current.is_mount_root() {
+            let parent_mount = current.mount.parent()?.upgrade()?;
+            let mountpoint = current.mount.mountpoint()?;
+            current = Self::new(parent_mount, mountpoint);
+        }
+
+        current.parent_within_mount()
+    }
+
     /// Gets the top `Path` of the current.
     ///
     /// Used when different file systems are mounted on the same mount point.
@@ -244,7 +262,7 @@ impl Path {
     }
 
     /// Returns true if the `Path` represents a pseudo file.
-    fn is_pseudo(&self) -> bool {
+    pub fn is_pseudo(&self) -> bool {
         self.dentry.is_pseudo()
     }
 
@@ -582,6 +600,10 @@ impl Path {
     pub fn mknod(&self, name: &str, mode: InodeMode, type_: MknodType) -> Result<Self> {
         let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
         self.check_dir_entry_mutation()?;
+        lsm_hooks::on_current_path_operation(PathOperation::Create {
+            dir: self,
+            type_: type_.inode_type(),
+        })?;
         let inner = dir_dentry.mknod(name, mode, type_)?;
         Ok(Self::new(self.mount.clone(), inner))
     }
@@ -595,6 +617,7 @@ impl Path {
         let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
         old.check_hardlink_source()?;
         self.check_dir_entry_mutation()?;
+        lsm_hooks::on_current_path_operation(PathOperation::Link { old, new_dir: self })?;
         dir_dentry.link(old.inode(), name)
     }
 
@@ -602,6 +625,10 @@ impl Path {
     pub fn unlink(&self, name: &str) -> Result<()> {
         let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
         self.check_dir_entry_mutation()?;
+        lsm_hooks::on_current_path_operation(PathOperation::Remove {
+            dir: self,
+            is_dir: false,
+        })?;
         dir_dentry.unlink(name)
     }
 
@@ -609,6 +636,10 @@ impl Path {
     pub fn rmdir(&self, name: &str) -> Result<()> {
         let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
         self.check_dir_entry_mutation()?;
+        lsm_hooks::on_current_path_operation(PathOperation::Remove {
+            dir: self,
+            is_dir: true,
+        })?;
         dir_dentry.rmdir(name)
     }
 
@@ -626,6 +657,21 @@ impl Path {
             new_dir.check_dir_entry_mutation()?;
         }
 
+        if is_dot_or_dotdot(old_name) || is_dot_or_dotdot(new_name) {
+            return_errno_with_message!(Errno::EISDIR, "old_name or new_name is a directory");
+        }
+        let old_type = old_dir_dentry.lookup_child(old_name)?.type_();
+        let replaced_type = new_dir_dentry
+            .lookup_child(new_name)
+            .ok()
+            .map(|dentry| dentry.type_());
+        lsm_hooks::on_current_path_operation(PathOperation::Rename {
+            old_dir: self,
+            old_type,
+            new_dir,
+            replaced_type,
+        })?;
+
         old_dir_dentry.rename(old_name, &new_dir_dentry, new_name)
     }
 }
@@ -668,6 +714,7 @@ impl Path {
     pub fn resize(&self, size: usize) -> Result<()> {
         let inode = self.inode();
         inode.check_permission(Permission::MAY_WRITE)?;
+        lsm_hooks::on_current_path_operation(PathOperation::Truncate { path: self })?;
         inode.resize(size)
     }
 }
@@ -13,6 +13,7 @@ use crate::{
         file::InodeType,
         vfs::{
             file_system::{FileSystem, FsFlags},
+            notify::FsEventPublisher,
             path::{
                 Path,
                 dentry::{Dentry, DentryKey},
@@ -204,6 +205,9 @@ pub struct Mount {
     propagation: RwLock<MountPropType>,
     /// The flags of this mount.
     flags: AtomicPerMountFlags,
+    /// The publisher for the events on all the inodes under this mount (e.g., for fanotify
+    /// mount marks).
+    fs_event_publisher: FsEventPublisher,
     /// Reference to self.
     this: Weak<Self>,
 }
@@ -260,6 +264,7 @@ impl Mount {
             mnt_ns,
             propagation: RwLock::new(MountPropType::default()),
             flags: AtomicPerMountFlags::new(flags),
+            fs_event_publisher: FsEventPublisher::new(),
             this: weak_self.clone(),
         }))
     }
@@ -351,6 +356,7 @@ impl Mount {
             mnt_ns: new_ns.clone(),
             propagation: RwLock::new(MountPropType::default()),
             flags: AtomicPerMountFlags::new(self.flags.load(Ordering::Relaxed)),
+            fs_event_publisher: FsEventPublisher::new(),
             this: weak_self.clone(),
         }))
     }
@@ -571,6 +577,14 @@ impl Mount {
         &self.fs
     }
 
+    /// Gets the FS event publisher of this mount.
+    ///
+    /// The subscribers attached to it must also be counted by the FS event subscriber stats
+    /// of the associated FS.
+    pub fn fs_event_publisher(&self) -> &FsEventPublisher {
+        &self.fs_event_publisher
+    }
+
     /// Gets the associated mount flags.
     pub fn flags(&self) -> PerMountFlags {
         self.flags.load(Ordering::Relaxed)
@@ -634,6 +648,11 @@ impl Debug for Mount {
 
 impl Drop for Mount {
     fn drop(&mut self) {
+        let removed_nr_subscribers = self.fs_event_publisher.remove_all_subscribers();
+        self.fs
+            .fs_event_subscriber_stats()
+            .remove_subscribers(removed_nr_subscribers);
+
         self.clear_mountpoint();
         ID_ALLOCATOR.get().unwrap().lock().free(self.id);
     }
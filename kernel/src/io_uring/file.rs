@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    cell::RefCell,
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use ostd::sync::WaitQueue;
+
+use super::{
+    IORING_MAX_CQ_ENTRIES, IORING_MAX_ENTRIES, IoUringFeatures, IoUringParams, IoUringSetupFlags,
+    op::{Op, SqeFlags},
+    pending::{Outcome, PendingKind, PendingOp, Wait},
+    ring::{Cqe, Rings},
+    worker::IoWorkers,
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{AccessMode, CreationFlags, FileLike, Mappable, StatusFlags, file_table::FdFlags},
+        pseudofs::AnonInodeFs,
+        vfs::path::Path,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    syscall::EventFile,
+    thread::{
+        Tid,
+        kernel_thread::{AsKernelThread, KernelThread},
+    },
+};
+
+/// A file representing an io_uring instance.
+pub struct IoUringFile {
+    ring: Arc<IoRing>,
+    /// The pseudo path associated with this io_uring file.
+    pseudo_path: Path,
+}
+
+impl IoUringFile {
+    /// Creates a new io_uring instance.
+    ///
+    /// The parameters are validated and updated with the actual sizes, the supported features,
+    /// and the offsets of the fields in the rings.
+    pub fn new(params: &mut IoUringParams) -> Result<Self> {
+        let flags = IoUringSetupFlags::from_bits(params.flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid setup flags"))?;
+        let supported_flags =
+            IoUringSetupFlags::IORING_SETUP_CQSIZE | IoUringSetupFlags::IORING_SETUP_CLAMP;
+        if !supported_flags.contains(flags) {
+            return_errno_with_message!(Errno::EINVAL, "the setup flags are not supported");
+        }
+        if params.resv.iter().any(|resv| *resv != 0) {
+            return_errno_with_message!(Errno::EINVAL, "the reserved fields are not zero");
+        }
+
+        let is_clamped = flags.contains(IoUringSetupFlags::IORING_SETUP_CLAMP);
+        let clamp = |entries: u32, max: u32| {
+            if entries <= max {
+                Ok(entries)
+            } else if is_clamped {
+                Ok(max)
+            } else {
+                return_errno_with_message!(Errno::EINVAL, "too many entries are requested");
+            }
+        };
+
+        if params.sq_entries == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the number of SQ entries is zero");
+        }
+        let sq_entries = clamp(params.sq_entries, IORING_MAX_ENTRIES)?.next_power_of_two();
+
+        let cq_entries = if flags.contains(IoUringSetupFlags::IORING_SETUP_CQSIZE) {
+            if params.cq_entries == 0 {
+                return_errno_with_message!(Errno::EINVAL, "the number of CQ entries is zero");
+            }
+            let cq_entries = clamp(params.cq_entries, IORING_MAX_CQ_ENTRIES)?.next_power_of_two();
+            if cq_entries < sq_entries {
+                return_errno_with_message!(Errno::EINVAL, "the CQ cannot be smaller than the SQ");
+            }
+            cq_entries
+        } else {
+            sq_entries * 2
+        };
+
+        let rings = Rings::new(sq_entries, cq_entries)?;
+
+        params.sq_entries = sq_entries;
+        params.cq_entries = cq_entries;
+        params.features = (IoUringFeatures::IORING_FEAT_NODROP
+            | IoUringFeatures::IORING_FEAT_SUBMIT_STABLE
+            | IoUringFeatures::IORING_FEAT_RW_CUR_POS)
+            .bits();
+        params.sq_off = Rings::sq_offsets();
+        params.cq_off = Rings::cq_offsets();
+
+        let ring = Arc::new(IoRing {
+            rings,
+            fixed_files: Mutex::new(None),
+            eventfd: Mutex::new(None),
+            pending: Mutex::new(Vec::new()),
+            workers: IoWorkers::new(sq_entries),
+            nr_completions: AtomicU64::new(0),
+            cq_wait_queue: WaitQueue::new(),
+            pollee: Pollee::new(),
+        });
+        let pseudo_path = AnonInodeFs::new_path(|_| "anon_inode:[io_uring]".to_string());
+
+        Ok(Self { ring, pseudo_path })
+    }
+
+    /// Submits at most `to_submit` SQEs and returns the number of consumed SQEs.
+    pub fn submit(&self, to_submit: u32, ctx: &Context) -> Result<u32> {
+        ctx.thread_local.io_uring_task().add_ring(&self.ring);
+        self.ring.submit(to_submit, ctx)
+    }
+
+    /// Waits until at least `min_complete` CQEs are available.
+    pub fn wait_cqes(&self, min_complete: u32) -> Result<()> {
+        self.ring.wait_cqes(min_complete)
+    }
+
+    /// Registers the files that can be used by SQEs with `IOSQE_FIXED_FILE`.
+    ///
+    /// A `None` entry leaves the slot empty.
+    pub fn register_files(&self, files: Vec<Option<Arc<dyn FileLike>>>) -> Result<()> {
+        if files
+            .iter()
+            .flatten()
+            .any(|file| file.downcast_ref::<IoUringFile>().is_some())
+        {
+            return_errno_with_message!(Errno::EBADF, "io_uring files cannot be registered");
+        }
+
+        let mut fixed_files = self.ring.fixed_files.lock();
+        if fixed_files.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "the files have already been registered");
+        }
+        *fixed_files = Some(files.into_boxed_slice());
+
+        Ok(())
+    }
+
+    /// Unregisters the files registered by [`Self::register_files`].
+    pub fn unregister_files(&self) -> Result<()> {
+        if self.ring.fixed_files.lock().take().is_none() {
+            return_errno_with_message!(Errno::ENXIO, "no files have been registered");
+        }
+
+        Ok(())
+    }
+
+    /// Registers an eventfd to be signaled when CQEs are posted.
+    pub fn register_eventfd(&self, file: Arc<dyn FileLike>) -> Result<()> {
+        if file.downcast_ref::<EventFile>().is_none() {
+            return_errno_with_message!(Errno::EINVAL, "the file is not an eventfd");
+        }
+
+        let mut eventfd = self.ring.eventfd.lock();
+        if eventfd.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "an eventfd has already been registered");
+        }
+        *eventfd = Some(file);
+
+        Ok(())
+    }
+
+    /// Unregisters the eventfd registered by [`Self::register_eventfd`].
+    pub fn unregister_eventfd(&self) -> Result<()> {
+        if self.ring.eventfd.lock().take().is_none() {
+            return_errno_with_message!(Errno::ENXIO, "no eventfd has been registered");
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for IoUringFile {
+    fn drop(&mut self) {
+        // Running operations may still hold the ring. Make sure that they finish as soon as
+        // possible and that the ring does not keep other files alive.
+        self.ring.close();
+    }
+}
+
+impl Pollable for IoUringFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        // The user space consumes CQEs without notifying us, so the cached events are stale.
+        self.ring.pollee.invalidate();
+        self.ring
+            .pollee
+            .poll_with(mask, poller, || self.ring.check_io_events())
+    }
+}
+
+impl FileLike for IoUringFile {
+    fn mappable(&self) -> Result<Mappable> {
+        Ok(Mappable::Vmo(self.ring.rings.vmo().clone()))
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::O_RDWR
+    }
+
+    fn path(&self) -> &Path {
+        &self.pseudo_path
+    }
+
+    fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
+        struct FdInfo {
+            flags: u32,
+            sq_entries: u32,
+            cq_entries: u32,
+        }
+
+        impl Display for FdInfo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                writeln!(f, "pos:\t{}", 0)?;
+                writeln!(f, "flags:\t0{:o}", self.flags)?;
+                writeln!(f, "mnt_id:\t{}", AnonInodeFs::mount_node().id())?;
+                writeln!(f, "ino:\t{}", AnonInodeFs::shared_inode().ino())?;
+                writeln!(f, "SqMask:\t0x{:x}", self.sq_entries - 1)?;
+                writeln!(f, "CqMask:\t0x{:x}", self.cq_entries - 1)
+            }
+        }
+
+        let mut flags = self.status_flags().bits() | self.access_mode() as u32;
+        if fd_flags.contains(FdFlags::CLOEXEC) {
+            flags |= CreationFlags::O_CLOEXEC.bits();
+        }
+
+        Box::new(FdInfo {
+            flags,
+            sq_entries: self.ring.rings.sq_entries(),
+            cq_entries: self.ring.rings.cq_entries(),
+        })
+    }
+}
+
+/// The io_uring instances that a thread has submitted operations to.
+///
+/// When the thread exits, its operations in these instances are cancelled.
+#[derive(Default)]
+pub struct IoUringTask {
+    rings: RefCell<Vec<Weak<IoRing>>>,
+}
+
+impl IoUringTask {
+    fn add_ring(&self, ring: &Arc<IoRing>) {
+        let mut rings = self.rings.borrow_mut();
+        if rings
+            .iter()
+            .any(|other| core::ptr::eq(other.as_ptr(), Arc::as_ptr(ring)))
+        {
+            return;
+        }
+
+        // Forget the instances that have gone.
+        rings.retain(|other| other.strong_count() > 0);
+        rings.push(Arc::downgrade(ring));
+    }
+
+    /// Cancels the operations submitted by the thread of `tid`.
+    pub fn cancel_all(&self, tid: Tid) {
+        let rings = self.rings.take();
+        for ring in rings.iter().filter_map(Weak::upgrade) {
+            ring.cancel_by_submitter(tid);
+        }
+    }
+}
+
+/// The shared context of an io_uring instance.
+///
+/// Unlike [`IoUringFile`], this context may outlive the file descriptors, since the running
+/// operations hold it until they complete.
+pub(super) struct IoRing {
+    rings: Rings,
+    fixed_files: Mutex<Option<Box<[Option<Arc<dyn FileLike>>]>>>,
+    eventfd: Mutex<Option<Arc<dyn FileLike>>>,
+    /// The operations that are waiting for events or timeouts.
+    pending: Mutex<Vec<Arc<PendingOp>>>,
+    workers: IoWorkers,
+    /// The number of completed operations, excluding timeouts.
+    nr_completions: AtomicU64,
+    cq_wait_queue: WaitQueue,
+    pollee: Pollee,
+}
+
+impl IoRing {
+    fn submit(self: &Arc<Self>, to_submit: u32, ctx: &Context) -> Result<u32> {
+        if !self.rings.flush_overflow() {
+            return_errno_with_message!(Errno::EBUSY, "the overflowed CQEs cannot be flushed");
+        }
+
+        let sqes = self.rings.consume_sqes(to_submit);
+
+        let mut chain = VecDeque::new();
+        let mut is_chain_failed = false;
+        for sqe in sqes.iter() {
+            let is_linked = SqeFlags::from_bits_truncate(sqe.flags)
+                .intersects(SqeFlags::IOSQE_IO_LINK | SqeFlags::IOSQE_IO_HARDLINK);
+
+            if is_chain_failed {
+                self.post_cqe(sqe.user_data, -(Errno::ECANCELED as i32), true);
+            } else {
+                match Op::prepare(sqe, self, ctx) {
+                    Ok(op) => chain.push_back(op),
+                    Err(err) => {
+                        self.cancel_chain(&mut chain);
+                        self.post_cqe(sqe.user_data, -(err.error() as i32), true);
+                        is_chain_failed = is_linked;
+                    }
+                }
+            }
+
+            if !is_linked {
+                is_chain_failed = false;
+                if !chain.is_empty() {
+                    self.issue(core::mem::take(&mut chain));
+                }
+            }
+        }
+
+        // A chain that is not terminated in this batch is issued as is.
+        if !chain.is_empty() {
+            self.issue(chain);
+        }
+
+        Ok(sqes.len() as u32)
+    }
+
+    /// Issues a chain of linked operations.
+    ///
+    /// The operations are executed in the current context as long as they can complete without
+    /// waiting or blocking. A waiting operation parks the rest of the chain, and a blocking
+    /// operation punts the rest of the chain to the workers.
+    fn issue(self: &Arc<Self>, chain: VecDeque<Op>) {
+        if chain.iter().any(Op::needs_submitter) {
+            self.advance(chain, Executor::Submitter);
+        } else {
+            self.advance(chain, Executor::Nonblocking);
+        }
+    }
+
+    /// Executes the operations in a chain until the chain is parked, punted, or finished.
+    fn advance(self: &Arc<Self>, mut chain: VecDeque<Op>, executor: Executor<'_>) {
+        while let Some(op) = chain.front() {
+            if let Executor::Worker(worker) = executor
+                && worker.is_interrupted()
+            {
+                self.cancel_chain(&mut chain);
+                return;
+            }
+
+            if let Some((kind, wait)) = op.wait() {
+                if !matches!(executor, Executor::Submitter) {
+                    self.park(chain, kind, wait);
+                    return;
+                }
+
+                let op = chain.pop_front().unwrap();
+                let (res, is_failed) = match self.wait_inline(&op, kind, wait) {
+                    Ok(outcome) => op.execute_after(outcome, self),
+                    Err(err) => (-(err.error() as i32), true),
+                };
+                self.finish(op, res, is_failed, &mut chain);
+                continue;
+            }
+
+            if matches!(executor, Executor::Nonblocking) && op.may_block() {
+                self.punt(chain);
+                return;
+            }
+
+            let op = chain.pop_front().unwrap();
+            let (res, is_failed) = op.execute(self);
+            self.finish(op, res, is_failed, &mut chain);
+        }
+    }
+
+    /// Parks a chain until what its first operation waits for arrives.
+    fn park(self: &Arc<Self>, mut chain: VecDeque<Op>, kind: PendingKind, wait: Wait) {
+        let pending = PendingOp::new(kind, chain.front().unwrap(), &wait, self);
+        if !self.add_pending(&pending) {
+            self.cancel_chain(&mut chain);
+            return;
+        }
+
+        pending.park(chain, wait);
+    }
+
+    /// Waits in the current context until what `op` waits for arrives.
+    fn wait_inline(self: &Arc<Self>, op: &Op, kind: PendingKind, wait: Wait) -> Result<Outcome> {
+        let pending = PendingOp::new(kind, op, &wait, self);
+        if !self.add_pending(&pending) {
+            return Ok(Outcome::Cancelled);
+        }
+
+        let res = pending.wait_inline(wait);
+        self.remove_pending(&pending);
+
+        res
+    }
+
+    /// Resumes a parked chain.
+    pub(super) fn resume(
+        self: &Arc<Self>,
+        mut chain: VecDeque<Op>,
+        kind: PendingKind,
+        outcome: Outcome,
+    ) {
+        // The I/O operation may block if the file becomes unready again, so it cannot be executed
+        // in the context of the work queue.
+        if kind == PendingKind::Io && matches!(outcome, Outcome::Ready(_)) {
+            self.punt(chain);
+            return;
+        }
+
+        let op = chain.pop_front().unwrap();
+        let (res, is_failed) = op.execute_after(outcome, self);
+        self.finish(op, res, is_failed, &mut chain);
+
+        self.advance(chain, Executor::Nonblocking);
+    }
+
+    fn punt(self: &Arc<Self>, chain: VecDeque<Op>) {
+        if let Err(mut chain) = self.workers.enqueue(self, chain) {
+            self.cancel_chain(&mut chain);
+        }
+    }
+
+    /// Runs a worker until the ring is closed.
+    pub(super) fn run_worker(self: &Arc<Self>) {
+        let thread = current_thread!();
+        let kernel_thread = thread.as_kernel_thread().unwrap();
+
+        self.workers.start_worker(&thread);
+        while let Some(chain) = self.workers.wait_for_work(&thread) {
+            self.advance(chain, Executor::Worker(kernel_thread));
+            self.workers.finish_work(&thread);
+        }
+    }
+
+    /// Completes an operation and cancels the rest of the chain if the operation fails.
+    fn finish(&self, op: Op, res: i32, is_failed: bool, rest: &mut VecDeque<Op>) {
+        self.complete(&op, res, is_failed);
+
+        if is_failed && !op.flags().contains(SqeFlags::IOSQE_IO_HARDLINK) {
+            self.cancel_chain(rest);
+        }
+    }
+
+    fn cancel_chain(&self, chain: &mut VecDeque<Op>) {
+        for op in chain.drain(..) {
+            self.complete(&op, -(Errno::ECANCELED as i32), true);
+        }
+    }
+
+    fn complete(&self, op: &Op, res: i32, is_failed: bool) {
+        if is_failed || !op.flags().contains(SqeFlags::IOSQE_CQE_SKIP_SUCCESS) {
+            self.post_cqe(op.user_data(), res, !op.is_timeout());
+        }
+    }
+
+    fn post_cqe(&self, user_data: u64, res: i32, is_counted: bool) {
+        self.rings.post_cqe(Cqe {
+            user_data,
+            res,
+            flags: 0,
+        });
+
+        // Timeouts waiting for a number of completions do not count themselves.
+        if is_counted {
+            let nr_completions = self.nr_completions.fetch_add(1, Ordering::Relaxed) + 1;
+            self.pending
+                .lock()
+                .iter()
+                .for_each(|op| op.on_completions(nr_completions));
+        }
+
+        self.pollee.notify(IoEvents::IN);
+        self.cq_wait_queue.wake_all();
+
+        if let Some(eventfd) = self.eventfd.lock().as_ref() {
+            eventfd.downcast_ref::<EventFile>().unwrap().signal();
+        }
+    }
+
+    fn wait_cqes(&self, min_complete: u32) -> Result<()> {
+        let min_complete = min_complete.min(self.rings.cq_entries());
+
+        self.cq_wait_queue.pause_until(|| {
+            self.rings.flush_overflow();
+            (self.rings.nr_ready_cqes() >= min_complete).then_some(())
+        })
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+
+        if self.rings.nr_ready_cqes() > 0 {
+            events |= IoEvents::IN;
+        }
+        if !self.rings.is_sq_full() {
+            events |= IoEvents::OUT;
+        }
+
+        events
+    }
+
+    fn close(&self) {
+        // The workers must be closed first, so that no more chains can be parked.
+        for mut chain in self.workers.close() {
+            self.cancel_chain(&mut chain);
+        }
+
+        let pending = core::mem::take(&mut *self.pending.lock());
+        for op in pending {
+            if let Some(mut chain) = op.cancel_now() {
+                self.cancel_chain(&mut chain);
+            }
+        }
+
+        *self.fixed_files.lock() = None;
+        *self.eventfd.lock() = None;
+    }
+
+    /// Cancels the operations submitted by the thread of `tid`.
+    ///
+    /// This method is called when the thread exits.
+    pub(super) fn cancel_by_submitter(&self, tid: Tid) {
+        for op in self.pending.lock().iter() {
+            if op.submitter() == tid {
+                op.cancel();
+            }
+        }
+
+        for mut chain in self.workers.cancel_by_submitter(tid) {
+            self.cancel_chain(&mut chain);
+        }
+    }
+
+    /// Returns the registered file at `index`.
+    pub(super) fn fixed_file(&self, index: i32) -> Result<Arc<dyn FileLike>> {
+        let fixed_files = self.fixed_files.lock();
+
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| fixed_files.as_ref()?.get(index)?.clone())
+            .ok_or_else(|| Error::with_message(Errno::EBADF, "the registered file does not exist"))
+    }
+
+    /// Records a pending operation.
+    ///
+    /// This method returns `false` if the ring has been closed.
+    fn add_pending(&self, op: &Arc<PendingOp>) -> bool {
+        let mut pending = self.pending.lock();
+        // See `Self::close` for why checking the workers is enough.
+        if self.workers.is_closed() {
+            return false;
+        }
+        pending.push(op.clone());
+        true
+    }
+
+    /// Removes a pending operation after it is resumed.
+    pub(super) fn remove_pending(&self, op: &Arc<PendingOp>) {
+        self.pending.lock().retain(|other| !Arc::ptr_eq(other, op));
+    }
+
+    /// Cancels the operation of `kind` that is identified by `user_data`.
+    pub(super) fn cancel_pending(&self, user_data: u64, kind: PendingKind) -> Result<()> {
+        let pending = self.pending.lock();
+
+        let Some(op) = pending
+            .iter()
+            .find(|op| op.user_data() == user_data && op.kind() == kind)
+        else {
+            return_errno_with_message!(Errno::ENOENT, "the operation to cancel does not exist");
+        };
+        if !op.cancel() {
+            return_errno_with_message!(Errno::EALREADY, "the operation is being cancelled");
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of completed operations, excluding timeouts.
+    pub(super) fn nr_completions(&self) -> u64 {
+        self.nr_completions.load(Ordering::Relaxed)
+    }
+}
+
+/// The context where the operations of a chain are executed.
+#[derive(Clone, Copy)]
+enum Executor<'a> {
+    /// A context that must not block, e.g., the context of the submitter or a work item.
+    Nonblocking,
+    /// The context of the submitter, where the whole chain must be executed.
+    Submitter,
+    /// A worker of the ring.
+    Worker(&'a KernelThread),
+}
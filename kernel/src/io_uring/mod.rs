@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The io_uring asynchronous I/O interface.
+//!
+//! An io_uring instance consists of a submission queue (SQ) and a completion queue (CQ), both of
+//! which are ring buffers shared between the kernel and the user space via `mmap`. The user space
+//! puts submission queue entries (SQEs) into the SQ and calls `io_uring_enter` to submit them.
+//! Each SQE is turned into an operation that eventually produces a completion queue entry (CQE)
+//! in the CQ.
+//!
+//! Operations that can complete without waiting are issued directly in the context of
+//! `io_uring_enter`. Polls, timeouts, and I/O operations on files that are not ready wait for
+//! events or timers without occupying any threads (see [`pending`]). Operations that may block
+//! (including all operations on disk I/O) are punted to the workers of the io_uring instance
+//! (see [`worker`]). Since the workers are kernel threads, they access the user buffers of the
+//! submitter via the alien access methods of [`Vmar`].
+//!
+//! The in-flight operations are cancelled when the io_uring instance is closed or when their
+//! submitter exits.
+//!
+//! [`Vmar`]: crate::vm::vmar::Vmar
+
+pub use self::file::{IoUringFile, IoUringTask};
+use crate::prelude::*;
+
+mod file;
+mod op;
+mod pending;
+mod ring;
+mod worker;
+
+/// The parameters of an io_uring instance (`struct io_uring_params`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/io_uring.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: IoSqringOffsets,
+    pub cq_off: IoCqringOffsets,
+}
+
+/// The offsets of the fields in the SQ ring (`struct io_sqring_offsets`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct IoSqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// The offsets of the fields in the CQ ring (`struct io_cqring_offsets`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct IoCqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+bitflags! {
+    /// The flags of `io_uring_setup`.
+    pub struct IoUringSetupFlags: u32 {
+        const IORING_SETUP_IOPOLL = 1 << 0;
+        const IORING_SETUP_SQPOLL = 1 << 1;
+        const IORING_SETUP_SQ_AFF = 1 << 2;
+        const IORING_SETUP_CQSIZE = 1 << 3;
+        const IORING_SETUP_CLAMP = 1 << 4;
+        const IORING_SETUP_ATTACH_WQ = 1 << 5;
+        const IORING_SETUP_R_DISABLED = 1 << 6;
+        const IORING_SETUP_SUBMIT_ALL = 1 << 7;
+    }
+}
+
+bitflags! {
+    /// The features reported by `io_uring_setup`.
+    pub struct IoUringFeatures: u32 {
+        const IORING_FEAT_SINGLE_MMAP = 1 << 0;
+        const IORING_FEAT_NODROP = 1 << 1;
+        const IORING_FEAT_SUBMIT_STABLE = 1 << 2;
+        const IORING_FEAT_RW_CUR_POS = 1 << 3;
+    }
+}
+
+bitflags! {
+    /// The flags of `io_uring_enter`.
+    pub struct IoUringEnterFlags: u32 {
+        const IORING_ENTER_GETEVENTS = 1 << 0;
+        const IORING_ENTER_SQ_WAKEUP = 1 << 1;
+        const IORING_ENTER_SQ_WAIT = 1 << 2;
+        const IORING_ENTER_EXT_ARG = 1 << 3;
+    }
+}
+
+/// The opcodes of `io_uring_register`.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum IoUringRegisterOp {
+    RegisterFiles = 2,
+    UnregisterFiles = 3,
+    RegisterEventfd = 4,
+    UnregisterEventfd = 5,
+}
+
+/// The maximum number of SQ entries.
+pub const IORING_MAX_ENTRIES: u32 = 32768;
+/// The maximum number of CQ entries.
+pub const IORING_MAX_CQ_ENTRIES: u32 = 2 * IORING_MAX_ENTRIES;
+/// The maximum number of registered files.
+pub const IORING_MAX_FIXED_FILES: u32 = 1 << 15;
@@ -0,0 +1,771 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The operations of io_uring.
+//!
+//! An SQE is first _prepared_ in the context of `io_uring_enter`, where everything that depends
+//! on the submitter (e.g., file descriptors and the arguments in the user space) is resolved.
+//! Then the operation is _executed_ either in the same context or by a worker, which does not
+//! need to know about the submitter anymore.
+
+use core::time::Duration;
+
+use ostd::{mm::VmIo, sync::RwArc};
+
+use super::{
+    file::IoRing,
+    pending::{Outcome, PendingKind, Wait},
+    ring::Sqe,
+};
+use crate::{
+    events::IoEvents,
+    fs::file::{
+        CreationFlags, FileLike, InodeHandle, StatusFlags,
+        file_table::{FdFlags, FileDesc, FileTable, get_file_fast},
+    },
+    net::socket::util::{MessageHeader, SendRecvFlags, SocketAddr},
+    prelude::*,
+    thread::Tid,
+    time::{clocks::MonotonicClock, timespec_t},
+    util::{
+        net::{read_socket_addr_from_user, socket_addr_to_c_bytes},
+        read_io_vecs_from_user,
+    },
+    vm::vmar::Vmar,
+};
+
+/// The opcodes of the supported operations.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/io_uring.h>.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum OpCode {
+    Nop = 0,
+    Readv = 1,
+    Writev = 2,
+    Fsync = 3,
+    PollAdd = 6,
+    PollRemove = 7,
+    Timeout = 11,
+    TimeoutRemove = 12,
+    Accept = 13,
+    Connect = 16,
+    Read = 22,
+    Write = 23,
+    Send = 26,
+    Recv = 27,
+}
+
+bitflags! {
+    /// The flags of an SQE.
+    pub(super) struct SqeFlags: u8 {
+        /// Uses a registered file instead of a file descriptor.
+        const IOSQE_FIXED_FILE = 1 << 0;
+        /// Issues after all in-flight operations complete.
+        const IOSQE_IO_DRAIN = 1 << 1;
+        /// Links the next SQE.
+        const IOSQE_IO_LINK = 1 << 2;
+        /// Links the next SQE, regardless of the result of this operation.
+        const IOSQE_IO_HARDLINK = 1 << 3;
+        /// Always issues the operation asynchronously.
+        const IOSQE_ASYNC = 1 << 4;
+        /// Selects a buffer from a provided buffer group.
+        const IOSQE_BUFFER_SELECT = 1 << 5;
+        /// Does not post a CQE if the operation succeeds.
+        const IOSQE_CQE_SKIP_SUCCESS = 1 << 6;
+    }
+}
+
+/// The `off` value that indicates the current file position.
+const CURRENT_POSITION: u64 = u64::MAX;
+/// The `fsync_flags` that only flushes the data.
+const IORING_FSYNC_DATASYNC: u32 = 1 << 0;
+/// The `timeout_flags` that indicates an absolute timeout.
+const IORING_TIMEOUT_ABS: u32 = 1 << 0;
+
+/// The maximum number of bytes that a read or write operation transfers at a time.
+///
+/// The data is copied through a kernel buffer, so this limits the size of the buffer.
+const MAX_RW_CHUNK: usize = 1024 * 1024;
+/// The maximum number of bytes that a read or write operation transfers in total.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/fs.h>.
+const MAX_RW_COUNT: usize = i32::MAX as usize & !(PAGE_SIZE - 1);
+
+/// An operation that is ready to execute.
+pub(super) struct Op {
+    user_data: u64,
+    flags: SqeFlags,
+    /// The thread that submits the operation.
+    submitter: Tid,
+    kind: OpKind,
+}
+
+enum OpKind {
+    Nop,
+    Read {
+        file: Arc<dyn FileLike>,
+        bufs: UserBufs,
+        offset: Option<usize>,
+    },
+    Write {
+        file: Arc<dyn FileLike>,
+        bufs: UserBufs,
+        offset: Option<usize>,
+    },
+    Fsync {
+        file: Arc<dyn FileLike>,
+        is_datasync: bool,
+    },
+    PollAdd {
+        file: Arc<dyn FileLike>,
+        mask: IoEvents,
+    },
+    PollRemove {
+        target: u64,
+    },
+    Timeout {
+        deadline: Duration,
+        nr_completions: Option<u64>,
+    },
+    TimeoutRemove {
+        target: u64,
+    },
+    Accept {
+        file: Arc<dyn FileLike>,
+        addr: Vaddr,
+        addr_len: Vaddr,
+        flags: AcceptFlags,
+        file_table: RwArc<FileTable>,
+        vmar: Weak<Vmar>,
+    },
+    Connect {
+        file: Arc<dyn FileLike>,
+        addr: SocketAddr,
+    },
+    Send {
+        file: Arc<dyn FileLike>,
+        bufs: UserBufs,
+        flags: SendRecvFlags,
+    },
+    Recv {
+        file: Arc<dyn FileLike>,
+        bufs: UserBufs,
+        flags: SendRecvFlags,
+    },
+}
+
+bitflags! {
+    struct AcceptFlags: u32 {
+        const SOCK_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+        const SOCK_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+    }
+}
+
+impl Op {
+    /// Prepares an operation from an SQE.
+    pub(super) fn prepare(sqe: &Sqe, ring: &IoRing, ctx: &Context) -> Result<Self> {
+        let flags = SqeFlags::from_bits(sqe.flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid SQE flags"))?;
+        if flags.intersects(SqeFlags::IOSQE_IO_DRAIN | SqeFlags::IOSQE_BUFFER_SELECT) {
+            return_errno_with_message!(Errno::EINVAL, "the SQE flags are not supported");
+        }
+
+        let opcode = OpCode::try_from(sqe.opcode)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the opcode is not supported"))?;
+        if sqe.ioprio != 0 || sqe.buf_index != 0 || sqe.personality != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the SQE fields are not supported");
+        }
+
+        let get_file = || -> Result<Arc<dyn FileLike>> {
+            if flags.contains(SqeFlags::IOSQE_FIXED_FILE) {
+                return ring.fixed_file(sqe.fd);
+            }
+            let mut file_table = ctx.thread_local.borrow_file_table_mut();
+            Ok(get_file_fast!(&mut file_table, sqe.fd.try_into()?).into_owned())
+        };
+        let offset = || {
+            if sqe.off == CURRENT_POSITION {
+                Ok(None)
+            } else if sqe.off > isize::MAX as u64 {
+                return_errno_with_message!(Errno::EINVAL, "the offset is too large");
+            } else {
+                Ok(Some(sqe.off as usize))
+            }
+        };
+        let rw_flags = || {
+            if sqe.op_flags != 0 {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "the RW flags are not supported");
+            }
+            Ok(())
+        };
+
+        let kind = match opcode {
+            OpCode::Nop => OpKind::Nop,
+            OpCode::Read | OpCode::Readv | OpCode::Write | OpCode::Writev => {
+                rw_flags()?;
+                let file = get_file()?;
+                let bufs = if matches!(opcode, OpCode::Read | OpCode::Write) {
+                    UserBufs::new_single(sqe.addr as Vaddr, sqe.len as usize, ctx)
+                } else {
+                    UserBufs::new_vectored(sqe.addr as Vaddr, sqe.len as usize, ctx)?
+                };
+                let offset = offset()?;
+                if matches!(opcode, OpCode::Read | OpCode::Readv) {
+                    OpKind::Read { file, bufs, offset }
+                } else {
+                    OpKind::Write { file, bufs, offset }
+                }
+            }
+            OpCode::Fsync => {
+                if sqe.op_flags & !IORING_FSYNC_DATASYNC != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "invalid fsync flags");
+                }
+                // TODO: Support syncing a range of the file.
+                OpKind::Fsync {
+                    file: get_file()?,
+                    is_datasync: sqe.op_flags & IORING_FSYNC_DATASYNC != 0,
+                }
+            }
+            OpCode::PollAdd => {
+                if sqe.len != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "multishot polls are not supported");
+                }
+                let file = get_file()?;
+                let mask = IoEvents::from_bits_truncate(sqe.op_flags);
+                OpKind::PollAdd { file, mask }
+            }
+            OpCode::PollRemove => {
+                if sqe.len != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "poll updates are not supported");
+                }
+                OpKind::PollRemove { target: sqe.addr }
+            }
+            OpCode::Timeout => {
+                if sqe.len != 1 {
+                    return_errno_with_message!(Errno::EINVAL, "invalid timeout length");
+                }
+                if sqe.op_flags & !IORING_TIMEOUT_ABS != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "invalid timeout flags");
+                }
+                let duration = {
+                    let timespec: timespec_t = ctx.user_space().read_val(sqe.addr as Vaddr)?;
+                    Duration::try_from(timespec)?
+                };
+                let deadline = if sqe.op_flags & IORING_TIMEOUT_ABS != 0 {
+                    duration
+                } else {
+                    MonotonicClock::get().read_time() + duration
+                };
+                let nr_completions =
+                    (sqe.off != 0).then(|| ring.nr_completions().saturating_add(sqe.off));
+                OpKind::Timeout {
+                    deadline,
+                    nr_completions,
+                }
+            }
+            OpCode::TimeoutRemove => {
+                if sqe.op_flags != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "timeout updates are not supported");
+                }
+                OpKind::TimeoutRemove { target: sqe.addr }
+            }
+            OpCode::Accept => {
+                if sqe.splice_fd_in != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "direct descriptors are not supported"
+                    );
+                }
+                let flags = AcceptFlags::from_bits(sqe.op_flags)
+                    .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid accept flags"))?;
+                OpKind::Accept {
+                    file: get_file()?,
+                    addr: sqe.addr as Vaddr,
+                    addr_len: sqe.off as Vaddr,
+                    flags,
+                    file_table: ctx.thread_local.borrow_file_table().unwrap().clone(),
+                    vmar: current_vmar(ctx),
+                }
+            }
+            OpCode::Connect => {
+                let addr = read_socket_addr_from_user(sqe.addr as Vaddr, sqe.off as usize)?;
+                OpKind::Connect {
+                    file: get_file()?,
+                    addr,
+                }
+            }
+            OpCode::Send | OpCode::Recv => {
+                let file = get_file()?;
+                let bufs = UserBufs::new_single(sqe.addr as Vaddr, sqe.len as usize, ctx);
+                let flags = SendRecvFlags::from_bits_truncate(sqe.op_flags as i32);
+                if opcode == OpCode::Send {
+                    OpKind::Send { file, bufs, flags }
+                } else {
+                    OpKind::Recv { file, bufs, flags }
+                }
+            }
+        };
+
+        Ok(Self {
+            user_data: sqe.user_data,
+            flags,
+            submitter: ctx.posix_thread.tid(),
+            kind,
+        })
+    }
+
+    pub(super) fn user_data(&self) -> u64 {
+        self.user_data
+    }
+
+    pub(super) fn flags(&self) -> SqeFlags {
+        self.flags
+    }
+
+    pub(super) fn submitter(&self) -> Tid {
+        self.submitter
+    }
+
+    /// Returns whether the operation is an `IORING_OP_TIMEOUT` operation.
+    pub(super) fn is_timeout(&self) -> bool {
+        matches!(self.kind, OpKind::Timeout { .. })
+    }
+
+    /// Returns whether the operation must be executed in the context of the submitter.
+    ///
+    /// Connecting a socket may bind it to an ephemeral address, which checks the privileges of
+    /// the current thread. Sending data to UNIX or netlink sockets may also record the
+    /// credentials or the PID of the current thread. The workers have neither of them.
+    pub(super) fn needs_submitter(&self) -> bool {
+        let file = match &self.kind {
+            OpKind::Connect { .. } => return true,
+            OpKind::Write { file, .. } | OpKind::Send { file, .. } => file,
+            _ => return false,
+        };
+
+        file.as_socket().is_some_and(|socket| {
+            matches!(
+                socket.addr(),
+                Ok(SocketAddr::Unix(_) | SocketAddr::Netlink(_))
+            )
+        })
+    }
+
+    /// Returns what the operation waits for before it can be executed, if anything.
+    ///
+    /// Polls and timeouts always wait. I/O operations wait until the file is ready, so that they
+    /// do not occupy a worker while waiting.
+    pub(super) fn wait(&self) -> Option<(PendingKind, Wait)> {
+        let wait_for_io = |file: &Arc<dyn FileLike>, mask: IoEvents| {
+            // Operations on inode-backed files block on disk I/O instead of waiting for events.
+            if file.downcast_ref::<InodeHandle>().is_some() || !file.poll(mask, None).is_empty() {
+                return None;
+            }
+            let wait = Wait::Events {
+                file: file.clone(),
+                mask,
+            };
+            Some((PendingKind::Io, wait))
+        };
+
+        match &self.kind {
+            OpKind::Read { file, .. } | OpKind::Recv { file, .. } | OpKind::Accept { file, .. } => {
+                wait_for_io(file, IoEvents::IN)
+            }
+            OpKind::Write { file, .. } | OpKind::Send { file, .. } => {
+                wait_for_io(file, IoEvents::OUT)
+            }
+            OpKind::PollAdd { file, mask } => {
+                let wait = Wait::Events {
+                    file: file.clone(),
+                    mask: *mask,
+                };
+                Some((PendingKind::Poll, wait))
+            }
+            OpKind::Timeout {
+                deadline,
+                nr_completions,
+            } => {
+                let wait = Wait::Timeout {
+                    deadline: *deadline,
+                    nr_completions: *nr_completions,
+                };
+                Some((PendingKind::Timeout, wait))
+            }
+            OpKind::Nop
+            | OpKind::Fsync { .. }
+            | OpKind::PollRemove { .. }
+            | OpKind::TimeoutRemove { .. }
+            | OpKind::Connect { .. } => None,
+        }
+    }
+
+    /// Returns whether the operation may block, in which case it is executed by a worker.
+    pub(super) fn may_block(&self) -> bool {
+        if self.flags.contains(SqeFlags::IOSQE_ASYNC) {
+            return true;
+        }
+
+        match &self.kind {
+            OpKind::Read { file, .. } | OpKind::Write { file, .. } => {
+                file.downcast_ref::<InodeHandle>().is_some()
+            }
+            OpKind::Fsync { .. } | OpKind::Connect { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Executes the operation.
+    ///
+    /// This method returns the result in the CQE and whether the operation is considered failed,
+    /// in which case the linked operations will be cancelled.
+    pub(super) fn execute(&self, ring: &IoRing) -> (i32, bool) {
+        let (res, expected_len) = match &self.kind {
+            OpKind::Nop => (Ok(0), None),
+            OpKind::Read { file, bufs, offset } => {
+                (do_read(file.as_ref(), bufs, *offset), Some(bufs.len()))
+            }
+            OpKind::Write { file, bufs, offset } => {
+                (do_write(file.as_ref(), bufs, *offset), Some(bufs.len()))
+            }
+            OpKind::Fsync { file, is_datasync } => (do_fsync(file.as_ref(), *is_datasync), None),
+            OpKind::PollRemove { target } => (
+                ring.cancel_pending(*target, PendingKind::Poll).map(|_| 0),
+                None,
+            ),
+            OpKind::TimeoutRemove { target } => (
+                ring.cancel_pending(*target, PendingKind::Timeout)
+                    .map(|_| 0),
+                None,
+            ),
+            OpKind::Accept {
+                file,
+                addr,
+                addr_len,
+                flags,
+                file_table,
+                vmar,
+            } => (
+                do_accept(file.as_ref(), *addr, *addr_len, *flags, file_table, vmar),
+                None,
+            ),
+            OpKind::Connect { file, addr } => (do_connect(file.as_ref(), addr), None),
+            OpKind::Send { file, bufs, flags } => (do_send(file.as_ref(), bufs, *flags), None),
+            OpKind::Recv { file, bufs, flags } => (do_recv(file.as_ref(), bufs, *flags), None),
+            OpKind::PollAdd { .. } | OpKind::Timeout { .. } => {
+                unreachable!("polls and timeouts are completed by `Self::execute_after`")
+            }
+        };
+
+        match res {
+            Ok(len) => {
+                // Short reads and writes break the links.
+                let is_failed = expected_len.is_some_and(|expected_len| len < expected_len);
+                (len as i32, is_failed)
+            }
+            Err(err) => {
+                let errno = match err.error() {
+                    // The operation cannot be restarted, so we report it as interrupted.
+                    Errno::ERESTARTSYS => Errno::EINTR,
+                    errno => errno,
+                };
+                (-(errno as i32), true)
+            }
+        }
+    }
+
+    /// Executes the operation after what it waits for has arrived.
+    ///
+    /// See [`Self::execute`] for the return value.
+    pub(super) fn execute_after(&self, outcome: Outcome, ring: &IoRing) -> (i32, bool) {
+        match (outcome, &self.kind) {
+            (Outcome::Cancelled, _) => (-(Errno::ECANCELED as i32), true),
+            (Outcome::Expired, _) => (-(Errno::ETIME as i32), true),
+            (Outcome::Ready(events), OpKind::PollAdd { .. }) => (events.bits() as i32, false),
+            (Outcome::Ready(_), OpKind::Timeout { .. }) => (0, false),
+            (Outcome::Ready(_), _) => self.execute(ring),
+        }
+    }
+}
+
+fn current_vmar(ctx: &Context) -> Weak<Vmar> {
+    ctx.thread_local
+        .vmar()
+        .borrow()
+        .as_ref()
+        .map(|vmar| vmar.clone_weak())
+        .unwrap_or_default()
+}
+
+/// Buffers in the user space of the submitter.
+///
+/// The buffers are accessed via the alien access methods, so they can be accessed by the workers.
+struct UserBufs {
+    vmar: Weak<Vmar>,
+    iovs: Box<[(Vaddr, usize)]>,
+}
+
+impl UserBufs {
+    fn new_single(addr: Vaddr, len: usize, ctx: &Context) -> Self {
+        let iovs: Box<[(Vaddr, usize)]> = if len == 0 {
+            Box::default()
+        } else {
+            Box::new([(addr, len)])
+        };
+
+        Self {
+            vmar: current_vmar(ctx),
+            iovs,
+        }
+    }
+
+    fn new_vectored(addr: Vaddr, count: usize, ctx: &Context) -> Result<Self> {
+        let iovs = read_io_vecs_from_user(&ctx.user_space(), addr, count)?;
+
+        Ok(Self {
+            vmar: current_vmar(ctx),
+            iovs,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.iovs
+            .iter()
+            .fold(0usize, |total, (_, len)| total.saturating_add(*len))
+            .min(MAX_RW_COUNT)
+    }
+
+    fn vmar(&self) -> Result<Arc<Vmar>> {
+        self.vmar
+            .upgrade()
+            .ok_or_else(|| Error::with_message(Errno::EFAULT, "the address space has gone"))
+    }
+
+    /// Copies the bytes after the first `skip` bytes of the buffers to `dst`.
+    ///
+    /// This method returns the number of bytes copied. It fails only if no bytes can be copied.
+    fn gather(&self, mut skip: usize, dst: &mut [u8]) -> Result<usize> {
+        let vmar = self.vmar()?;
+
+        let mut copied = 0;
+        for &(base, len) in self.iovs.iter() {
+            if skip >= len {
+                skip -= len;
+                continue;
+            }
+            if copied == dst.len() {
+                break;
+            }
+
+            let copy_len = (len - skip).min(dst.len() - copied);
+            let mut writer = VmWriter::from(&mut dst[copied..copied + copy_len]).to_fallible();
+            let res = vmar.read_alien(base + skip, &mut writer);
+            skip = 0;
+
+            match res {
+                Ok(len) => copied += len,
+                Err((err, len)) => {
+                    copied += len;
+                    if copied == 0 {
+                        return Err(err);
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(copied)
+    }
+
+    /// Copies the bytes in `src` to the buffers.
+    ///
+    /// This method returns the number of bytes copied. It fails only if no bytes can be copied.
+    fn scatter(&self, src: &[u8]) -> Result<usize> {
+        let vmar = self.vmar()?;
+
+        let mut copied = 0;
+        for &(base, len) in self.iovs.iter() {
+            if copied == src.len() {
+                break;
+            }
+
+            let copy_len = len.min(src.len() - copied);
+            let mut reader = VmReader::from(&src[copied..copied + copy_len]).to_fallible();
+
+            match vmar.write_alien(base, &mut reader) {
+                Ok(len) => copied += len,
+                Err((err, len)) => {
+                    copied += len;
+                    if copied == 0 {
+                        return Err(err);
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(copied)
+    }
+}
+
+fn do_read(file: &dyn FileLike, bufs: &UserBufs, offset: Option<usize>) -> Result<usize> {
+    let mut buf = vec![0u8; bufs.len().min(MAX_RW_CHUNK)];
+    let mut writer = VmWriter::from(buf.as_mut_slice()).to_fallible();
+
+    let read_len = match offset {
+        // The offset is ignored for non-seekable files, as Linux does.
+        Some(offset) => match file.read_at(offset, &mut writer) {
+            Err(err) if err.error() == Errno::ESPIPE => file.read(&mut writer),
+            res => res,
+        },
+        None => file.read(&mut writer),
+    }?;
+
+    bufs.scatter(&buf[..read_len])
+}
+
+fn do_write(file: &dyn FileLike, bufs: &UserBufs, offset: Option<usize>) -> Result<usize> {
+    let total_len = bufs.len();
+    let mut buf = vec![0u8; total_len.min(MAX_RW_CHUNK)];
+
+    let mut written_len = 0;
+    loop {
+        let copied_len = match bufs.gather(written_len, &mut buf) {
+            Ok(copied_len) => copied_len,
+            Err(err) if written_len == 0 => return Err(err),
+            Err(_) => break,
+        };
+
+        let mut reader = VmReader::from(&buf[..copied_len]).to_fallible();
+        let res = match offset {
+            Some(offset) => match file.write_at(offset + written_len, &mut reader) {
+                Err(err) if err.error() == Errno::ESPIPE => file.write(&mut reader),
+                res => res,
+            },
+            None => file.write(&mut reader),
+        };
+
+        match res {
+            Ok(len) => written_len += len,
+            Err(err) if written_len == 0 => return Err(err),
+            Err(_) => break,
+        }
+
+        if written_len >= total_len || reader.has_remain() || copied_len < buf.len() {
+            break;
+        }
+    }
+
+    Ok(written_len)
+}
+
+fn do_fsync(file: &dyn FileLike, is_datasync: bool) -> Result<usize> {
+    let path = file.as_inode_handle_or_err()?.path();
+    if is_datasync {
+        path.sync_data()?;
+    } else {
+        path.sync_all()?;
+    }
+    Ok(0)
+}
+
+fn do_accept(
+    file: &dyn FileLike,
+    addr: Vaddr,
+    addr_len: Vaddr,
+    flags: AcceptFlags,
+    file_table: &RwArc<FileTable>,
+    vmar: &Weak<Vmar>,
+) -> Result<usize> {
+    let socket = file.as_socket_or_err()?;
+    let (connected_socket, socket_addr) = socket.accept()?;
+
+    if flags.contains(AcceptFlags::SOCK_NONBLOCK) {
+        connected_socket.set_status_flags(StatusFlags::O_NONBLOCK)?;
+    }
+    let fd_flags = if flags.contains(AcceptFlags::SOCK_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+
+    if addr != 0 {
+        let vmar = vmar
+            .upgrade()
+            .ok_or_else(|| Error::with_message(Errno::EFAULT, "the address space has gone"))?;
+        write_socket_addr_alien(&vmar, &socket_addr, addr, addr_len)?;
+    }
+
+    let fd: FileDesc = file_table.write().insert(connected_socket, fd_flags);
+    Ok(u32::from(fd) as usize)
+}
+
+/// Writes a socket address and its length to the user space of another process.
+///
+/// See [`write_socket_addr_to_user`] for the semantics.
+///
+/// [`write_socket_addr_to_user`]: crate::util::net::write_socket_addr_to_user
+fn write_socket_addr_alien(
+    vmar: &Vmar,
+    socket_addr: &SocketAddr,
+    addr: Vaddr,
+    addr_len: Vaddr,
+) -> Result<()> {
+    let mut max_len = 0i32;
+    read_alien_exact(vmar, addr_len, max_len.as_mut_bytes())?;
+    if max_len < 0 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the socket address length cannot be negative"
+        );
+    }
+
+    let bytes = socket_addr_to_c_bytes(socket_addr);
+    let written_len = bytes.len().min(max_len as usize);
+    write_alien_exact(vmar, addr, &bytes[..written_len])?;
+
+    let actual_len = bytes.len() as i32;
+    write_alien_exact(vmar, addr_len, actual_len.as_bytes())
+}
+
+fn read_alien_exact(vmar: &Vmar, addr: Vaddr, buf: &mut [u8]) -> Result<()> {
+    let len = buf.len();
+    match vmar.read_alien(addr, &mut VmWriter::from(buf).to_fallible()) {
+        Ok(read_len) if read_len == len => Ok(()),
+        _ => return_errno_with_message!(Errno::EFAULT, "failed to read the user memory"),
+    }
+}
+
+fn write_alien_exact(vmar: &Vmar, addr: Vaddr, buf: &[u8]) -> Result<()> {
+    match vmar.write_alien(addr, &mut VmReader::from(buf).to_fallible()) {
+        Ok(written_len) if written_len == buf.len() => Ok(()),
+        _ => return_errno_with_message!(Errno::EFAULT, "failed to write the user memory"),
+    }
+}
+
+fn do_connect(file: &dyn FileLike, addr: &SocketAddr) -> Result<usize> {
+    file.as_socket_or_err()?.connect(addr.clone())?;
+    Ok(0)
+}
+
+fn do_send(file: &dyn FileLike, bufs: &UserBufs, flags: SendRecvFlags) -> Result<usize> {
+    let socket = file.as_socket_or_err()?;
+
+    let mut buf = vec![0u8; bufs.len().min(MAX_RW_CHUNK)];
+    let copied_len = bufs.gather(0, &mut buf)?;
+
+    let mut reader = VmReader::from(&buf[..copied_len]).to_fallible();
+    socket.sendmsg(&mut reader, MessageHeader::new(None, Vec::new()), flags)
+}
+
+fn do_recv(file: &dyn FileLike, bufs: &UserBufs, flags: SendRecvFlags) -> Result<usize> {
+    let socket = file.as_socket_or_err()?;
+
+    let mut buf = vec![0u8; bufs.len().min(MAX_RW_CHUNK)];
+    let mut writer = VmWriter::from(buf.as_mut_slice()).to_fallible();
+    let (recv_len, _) = socket.recvmsg(&mut writer, flags)?;
+
+    // The received length may exceed the buffer length if `MSG_TRUNC` is specified.
+    bufs.scatter(&buf[..recv_len.min(buf.len())])?;
+    Ok(recv_len)
+}
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The operations that wait for events or timeouts.
+//!
+//! Such an operation does not occupy a worker while it is waiting. Instead, its chain is _parked_
+//! in a [`PendingOp`], which observes the pollee of the file or owns a timer. The observers and
+//! the timers may run in the interrupt context, so they only schedule a work item in the global
+//! work queue, which _resumes_ the chain in the process context.
+//!
+//! A chain that must be executed in the context of the submitter cannot be parked. Its operations
+//! wait in the submitter's context instead, which can be interrupted by signals.
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use ostd::sync::WaitQueue;
+
+use super::{file::IoRing, op::Op};
+use crate::{
+    events::{IoEvents, Observer},
+    fs::file::FileLike,
+    prelude::*,
+    process::signal::{Pause, PollHandle},
+    thread::{
+        Tid,
+        work_queue::{WorkPriority, submit_work_item, work_item::WorkItem},
+    },
+    time::{
+        clocks::MonotonicClock,
+        timer::{Timeout, Timer},
+    },
+};
+
+/// The kind of an operation that waits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum PendingKind {
+    /// An `IORING_OP_POLL_ADD` operation.
+    Poll,
+    /// An `IORING_OP_TIMEOUT` operation.
+    Timeout,
+    /// An I/O operation that waits until the file is ready.
+    Io,
+}
+
+/// What an operation waits for.
+#[derive(Clone)]
+pub(super) enum Wait {
+    /// Some events of the file.
+    Events {
+        file: Arc<dyn FileLike>,
+        mask: IoEvents,
+    },
+    /// A timeout, or a number of completions if specified.
+    Timeout {
+        deadline: Duration,
+        nr_completions: Option<u64>,
+    },
+}
+
+/// Why a parked chain is resumed.
+pub(super) enum Outcome {
+    /// The events have arrived, or the number of completions has been reached.
+    Ready(IoEvents),
+    /// The timeout has expired.
+    Expired,
+    /// The operation has been cancelled.
+    Cancelled,
+}
+
+/// An operation that waits for events or a timeout.
+pub(super) struct PendingOp {
+    user_data: u64,
+    kind: PendingKind,
+    submitter: Tid,
+    /// The number of completions that the timeout waits for.
+    nr_completions: Option<u64>,
+    ring: Weak<IoRing>,
+    parked: SpinLock<Option<Parked>>,
+    is_expired: AtomicBool,
+    is_cancelled: AtomicBool,
+    /// The work item to resume the chain.
+    work_item: Arc<WorkItem>,
+    /// The wait queue of the submitter if the operation waits in its context.
+    wait_queue: WaitQueue,
+}
+
+/// A parked chain whose first operation is waiting.
+struct Parked {
+    chain: VecDeque<Op>,
+    wait: Wait,
+    poll_handle: Option<PollHandle>,
+    timer: Option<Arc<Timer>>,
+}
+
+impl PendingOp {
+    /// Creates a pending operation for `op`, which waits for `wait`.
+    pub(super) fn new(kind: PendingKind, op: &Op, wait: &Wait, ring: &Arc<IoRing>) -> Arc<Self> {
+        let nr_completions = match wait {
+            Wait::Timeout { nr_completions, .. } => *nr_completions,
+            Wait::Events { .. } => None,
+        };
+
+        Arc::new_cyclic(|weak_self: &Weak<Self>| {
+            let weak_self = weak_self.clone();
+            let work_item = WorkItem::new(Box::new(move || {
+                if let Some(pending) = weak_self.upgrade() {
+                    pending.resume();
+                }
+            }));
+
+            Self {
+                user_data: op.user_data(),
+                kind,
+                submitter: op.submitter(),
+                nr_completions,
+                ring: Arc::downgrade(ring),
+                parked: SpinLock::new(None),
+                is_expired: AtomicBool::new(false),
+                is_cancelled: AtomicBool::new(false),
+                work_item,
+                wait_queue: WaitQueue::new(),
+            }
+        })
+    }
+
+    pub(super) fn user_data(&self) -> u64 {
+        self.user_data
+    }
+
+    pub(super) fn kind(&self) -> PendingKind {
+        self.kind
+    }
+
+    pub(super) fn submitter(&self) -> Tid {
+        self.submitter
+    }
+
+    /// Parks `chain` until what the first operation waits for arrives.
+    ///
+    /// If it has already arrived, the chain is resumed immediately.
+    pub(super) fn park(self: &Arc<Self>, chain: VecDeque<Op>, wait: Wait) {
+        // The chain must be parked before the observer is registered. Otherwise, the events that
+        // arrive in between will find nothing to resume.
+        *self.parked.disable_irq().lock() = Some(Parked {
+            chain,
+            wait: wait.clone(),
+            poll_handle: None,
+            timer: None,
+        });
+
+        let is_ready = match wait {
+            Wait::Events { file, mask } => {
+                let mut poll_handle = PollHandle::new(Arc::downgrade(self) as _);
+                let events = file.poll(mask, Some(&mut poll_handle));
+
+                let mut parked = self.parked.disable_irq().lock();
+                let unused_handle = match parked.as_mut() {
+                    Some(parked) => {
+                        parked.poll_handle = Some(poll_handle);
+                        None
+                    }
+                    None => Some(poll_handle),
+                };
+                drop(parked);
+                // Unregister the observer after releasing the lock.
+                drop(unused_handle);
+
+                !events.is_empty()
+            }
+            Wait::Timeout { deadline, .. } => {
+                let timer = self.start_timer(deadline);
+
+                let mut parked = self.parked.disable_irq().lock();
+                match parked.as_mut() {
+                    Some(parked) => parked.timer = Some(timer),
+                    None => timer.lock().cancel(),
+                }
+                drop(parked);
+
+                // The completions may have been posted before the timeout is parked.
+                self.ring
+                    .upgrade()
+                    .is_some_and(|ring| self.is_nr_completions_reached(ring.nr_completions()))
+            }
+        };
+
+        if is_ready {
+            self.resume();
+        }
+    }
+
+    /// Waits in the current context until what the operation waits for arrives.
+    ///
+    /// Unlike [`Self::park`], this method does not resume any chains. It fails with `EINTR` if
+    /// the current thread is interrupted by a signal.
+    pub(super) fn wait_inline(self: &Arc<Self>, wait: Wait) -> Result<Outcome> {
+        let ring = self.ring.upgrade();
+        let is_nr_completions_reached = || {
+            ring.as_ref()
+                .is_some_and(|ring| self.is_nr_completions_reached(ring.nr_completions()))
+        };
+
+        match wait {
+            Wait::Events { file, mask } => {
+                let mut poll_handle = PollHandle::new(Arc::downgrade(self) as _);
+                let mut is_registered = false;
+
+                self.wait_queue.pause_until(|| {
+                    if self.is_cancelled.load(Ordering::Acquire) {
+                        return Some(Outcome::Cancelled);
+                    }
+
+                    let events = if is_registered {
+                        file.poll(mask, None)
+                    } else {
+                        is_registered = true;
+                        file.poll(mask, Some(&mut poll_handle))
+                    };
+                    (!events.is_empty()).then_some(Outcome::Ready(events))
+                })
+            }
+            Wait::Timeout { deadline, .. } => {
+                let timer = self.start_timer(deadline);
+
+                let res = self.wait_queue.pause_until(|| {
+                    if self.is_cancelled.load(Ordering::Acquire) {
+                        Some(Outcome::Cancelled)
+                    } else if self.is_expired.load(Ordering::Acquire) {
+                        Some(Outcome::Expired)
+                    } else if is_nr_completions_reached() {
+                        Some(Outcome::Ready(IoEvents::empty()))
+                    } else {
+                        None
+                    }
+                });
+
+                timer.lock().cancel();
+                res
+            }
+        }
+    }
+
+    /// Starts a timer that expires the operation at `deadline`.
+    fn start_timer(self: &Arc<Self>, deadline: Duration) -> Arc<Timer> {
+        let weak_self = Arc::downgrade(self);
+        let timer = MonotonicClock::timer_manager().create_timer(move |_guard| {
+            if let Some(pending) = weak_self.upgrade() {
+                pending.is_expired.store(true, Ordering::Release);
+                pending.schedule();
+            }
+        });
+        timer.lock().set_timeout(Timeout::When(deadline));
+
+        timer
+    }
+
+    /// Cancels the operation.
+    ///
+    /// The chain will be resumed later with [`Outcome::Cancelled`]. This method returns `false` if
+    /// the operation has already been cancelled.
+    pub(super) fn cancel(&self) -> bool {
+        let was_cancelled = self.is_cancelled.swap(true, Ordering::AcqRel);
+        self.schedule();
+        !was_cancelled
+    }
+
+    /// Cancels the operation and returns the parked chain immediately.
+    ///
+    /// This method returns `None` if the chain has already been resumed.
+    pub(super) fn cancel_now(&self) -> Option<VecDeque<Op>> {
+        self.is_cancelled.store(true, Ordering::Release);
+        self.take_parked().map(|parked| parked.chain)
+    }
+
+    /// Notifies the operation that `nr_completions` operations have completed.
+    pub(super) fn on_completions(&self, nr_completions: u64) {
+        if self.is_nr_completions_reached(nr_completions) {
+            self.schedule();
+        }
+    }
+
+    fn is_nr_completions_reached(&self, nr_completions: u64) -> bool {
+        self.nr_completions.is_some_and(|nr| nr_completions >= nr)
+    }
+
+    /// Schedules the work item to resume the chain, or wakes up the submitter if the operation
+    /// waits in its context.
+    ///
+    /// This method can be called in the interrupt context.
+    fn schedule(&self) {
+        submit_work_item(self.work_item.clone(), WorkPriority::Normal);
+        self.wait_queue.wake_all();
+    }
+
+    /// Resumes the parked chain if what the operation waits for has arrived.
+    fn resume(self: &Arc<Self>) {
+        let Some(ring) = self.ring.upgrade() else {
+            return;
+        };
+
+        let wait = match self.parked.disable_irq().lock().as_ref() {
+            Some(parked) => parked.wait.clone(),
+            None => return,
+        };
+
+        let outcome = if self.is_cancelled.load(Ordering::Acquire) {
+            Outcome::Cancelled
+        } else {
+            match wait {
+                Wait::Events { file, mask } => {
+                    let events = file.poll(mask, None);
+                    if events.is_empty() {
+                        return;
+                    }
+                    Outcome::Ready(events)
+                }
+                Wait::Timeout { .. } => {
+                    if self.is_expired.load(Ordering::Acquire) {
+                        Outcome::Expired
+                    } else if self.is_nr_completions_reached(ring.nr_completions()) {
+                        Outcome::Ready(IoEvents::empty())
+                    } else {
+                        return;
+                    }
+                }
+            }
+        };
+
+        // Another resumption may have taken the chain.
+        let Some(parked) = self.take_parked() else {
+            return;
+        };
+        ring.remove_pending(self);
+        ring.resume(parked.chain, self.kind, outcome);
+    }
+
+    fn take_parked(&self) -> Option<Parked> {
+        let parked = self.parked.disable_irq().lock().take()?;
+        if let Some(timer) = parked.timer.as_ref() {
+            timer.lock().cancel();
+        }
+        Some(parked)
+    }
+}
+
+impl Observer<IoEvents> for PendingOp {
+    fn on_events(&self, _events: &IoEvents) {
+        self.schedule();
+    }
+}
@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The SQ and CQ rings shared with the user space.
+//!
+//! All the shared memory is backed by a single VMO, which is mapped by the user space at the
+//! magic offsets ([`IORING_OFF_SQ_RING`], [`IORING_OFF_CQ_RING`], and [`IORING_OFF_SQES`]). The
+//! pages of the rings are committed when the rings are created, so the kernel can access them
+//! without going through the VMO.
+
+use core::sync::atomic::{Ordering, fence};
+
+use align_ext::AlignExt;
+use ostd::mm::{Infallible, io::util::HasVmReaderWriter};
+
+use super::{IoCqringOffsets, IoSqringOffsets};
+use crate::{
+    prelude::*,
+    vm::page_cache::{CachePage, Vmo, VmoOptions},
+};
+
+/// The `mmap` offset of the SQ ring.
+pub(super) const IORING_OFF_SQ_RING: usize = 0;
+/// The `mmap` offset of the CQ ring.
+pub(super) const IORING_OFF_CQ_RING: usize = 0x8000000;
+/// The `mmap` offset of the SQE array.
+pub(super) const IORING_OFF_SQES: usize = 0x10000000;
+
+// The layout of the SQ ring.
+const SQ_HEAD: usize = 0;
+const SQ_TAIL: usize = 4;
+const SQ_RING_MASK: usize = 8;
+const SQ_RING_ENTRIES: usize = 12;
+const SQ_FLAGS: usize = 16;
+const SQ_DROPPED: usize = 20;
+const SQ_ARRAY: usize = 64;
+
+// The layout of the CQ ring.
+const CQ_HEAD: usize = 0;
+const CQ_TAIL: usize = 4;
+const CQ_RING_MASK: usize = 8;
+const CQ_RING_ENTRIES: usize = 12;
+const CQ_OVERFLOW: usize = 16;
+const CQ_FLAGS: usize = 20;
+const CQ_CQES: usize = 64;
+
+/// The CQ ring has overflowed, so some CQEs are kept in the kernel.
+const IORING_SQ_CQ_OVERFLOW: u32 = 1 << 1;
+
+/// A submission queue entry (`struct io_uring_sqe`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub(super) struct Sqe {
+    pub(super) opcode: u8,
+    pub(super) flags: u8,
+    pub(super) ioprio: u16,
+    pub(super) fd: i32,
+    /// The file offset, or `addr2` for some operations.
+    pub(super) off: u64,
+    pub(super) addr: u64,
+    pub(super) len: u32,
+    /// The operation-specific flags (e.g., `rw_flags`, `poll32_events`, `timeout_flags`).
+    pub(super) op_flags: u32,
+    pub(super) user_data: u64,
+    pub(super) buf_index: u16,
+    pub(super) personality: u16,
+    /// The `splice_fd_in` or `file_index` field.
+    pub(super) splice_fd_in: i32,
+    pub(super) addr3: u64,
+    pub(super) _pad: u64,
+}
+
+/// A completion queue entry (`struct io_uring_cqe`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub(super) struct Cqe {
+    pub(super) user_data: u64,
+    pub(super) res: i32,
+    pub(super) flags: u32,
+}
+
+/// The SQ and CQ rings of an io_uring instance.
+pub(super) struct Rings {
+    vmo: Arc<Vmo>,
+    sq_ring: Box<[CachePage]>,
+    cq_ring: Box<[CachePage]>,
+    sqes: Box<[CachePage]>,
+    sq_entries: u32,
+    cq_entries: u32,
+    /// The kernel-side SQ head, which also serializes the consumers of the SQ.
+    sq_head: Mutex<u32>,
+    cq: Mutex<CompletionQueue>,
+}
+
+struct CompletionQueue {
+    /// The kernel-side CQ tail.
+    tail: u32,
+    /// The CQEs that cannot be posted because the CQ ring is full.
+    overflow: VecDeque<Cqe>,
+}
+
+impl Rings {
+    /// Creates the rings.
+    ///
+    /// `sq_entries` and `cq_entries` must be powers of two.
+    pub(super) fn new(sq_entries: u32, cq_entries: u32) -> Result<Self> {
+        debug_assert!(sq_entries.is_power_of_two() && cq_entries.is_power_of_two());
+
+        let vmo = VmoOptions::new(IORING_OFF_SQES + Self::sqes_size(sq_entries)).alloc()?;
+        let sq_ring = commit_pages(&vmo, IORING_OFF_SQ_RING, Self::sq_ring_size(sq_entries))?;
+        let cq_ring = commit_pages(&vmo, IORING_OFF_CQ_RING, Self::cq_ring_size(cq_entries))?;
+        let sqes = commit_pages(&vmo, IORING_OFF_SQES, Self::sqes_size(sq_entries))?;
+
+        write_u32(&sq_ring, SQ_RING_MASK, sq_entries - 1);
+        write_u32(&sq_ring, SQ_RING_ENTRIES, sq_entries);
+        write_u32(&cq_ring, CQ_RING_MASK, cq_entries - 1);
+        write_u32(&cq_ring, CQ_RING_ENTRIES, cq_entries);
+
+        Ok(Self {
+            vmo,
+            sq_ring,
+            cq_ring,
+            sqes,
+            sq_entries,
+            cq_entries,
+            sq_head: Mutex::new(0),
+            cq: Mutex::new(CompletionQueue {
+                tail: 0,
+                overflow: VecDeque::new(),
+            }),
+        })
+    }
+
+    fn sq_ring_size(sq_entries: u32) -> usize {
+        SQ_ARRAY + sq_entries as usize * size_of::<u32>()
+    }
+
+    fn cq_ring_size(cq_entries: u32) -> usize {
+        CQ_CQES + cq_entries as usize * size_of::<Cqe>()
+    }
+
+    fn sqes_size(sq_entries: u32) -> usize {
+        sq_entries as usize * size_of::<Sqe>()
+    }
+
+    /// Returns the VMO that backs the rings.
+    pub(super) fn vmo(&self) -> &Arc<Vmo> {
+        &self.vmo
+    }
+
+    pub(super) fn sq_entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    pub(super) fn cq_entries(&self) -> u32 {
+        self.cq_entries
+    }
+
+    /// Returns the offsets of the fields in the SQ ring.
+    pub(super) fn sq_offsets() -> IoSqringOffsets {
+        IoSqringOffsets {
+            head: SQ_HEAD as u32,
+            tail: SQ_TAIL as u32,
+            ring_mask: SQ_RING_MASK as u32,
+            ring_entries: SQ_RING_ENTRIES as u32,
+            flags: SQ_FLAGS as u32,
+            dropped: SQ_DROPPED as u32,
+            array: SQ_ARRAY as u32,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the offsets of the fields in the CQ ring.
+    pub(super) fn cq_offsets() -> IoCqringOffsets {
+        IoCqringOffsets {
+            head: CQ_HEAD as u32,
+            tail: CQ_TAIL as u32,
+            ring_mask: CQ_RING_MASK as u32,
+            ring_entries: CQ_RING_ENTRIES as u32,
+            overflow: CQ_OVERFLOW as u32,
+            cqes: CQ_CQES as u32,
+            flags: CQ_FLAGS as u32,
+            ..Default::default()
+        }
+    }
+
+    /// Consumes at most `max_nr` SQEs from the SQ.
+    ///
+    /// The SQEs are copied out of the shared memory, so the user space can reuse the slots once
+    /// this method returns. If the user space puts an invalid index in the SQ array, the index is
+    /// consumed and counted as dropped, and no more SQEs are consumed.
+    pub(super) fn consume_sqes(&self, max_nr: u32) -> Vec<Sqe> {
+        let mut sq_head = self.sq_head.lock();
+
+        let tail = read_u32(&self.sq_ring, SQ_TAIL);
+        // Pairs with the release operation that the user space performs before updating the tail.
+        fence(Ordering::Acquire);
+
+        let nr_pending = tail.wrapping_sub(*sq_head).min(self.sq_entries);
+        let mut sqes = Vec::with_capacity(nr_pending.min(max_nr) as usize);
+
+        for _ in 0..nr_pending.min(max_nr) {
+            let array_offset = SQ_ARRAY + (*sq_head & (self.sq_entries - 1)) as usize * 4;
+            let index = read_u32(&self.sq_ring, array_offset);
+            *sq_head = sq_head.wrapping_add(1);
+
+            if index >= self.sq_entries {
+                let dropped = read_u32(&self.sq_ring, SQ_DROPPED);
+                write_u32(&self.sq_ring, SQ_DROPPED, dropped.wrapping_add(1));
+                break;
+            }
+
+            let mut reader = page_reader(&self.sqes, index as usize * size_of::<Sqe>());
+            sqes.push(reader.read_val::<Sqe>().unwrap());
+        }
+
+        // Make sure that the SQEs are read before the slots are released to the user space.
+        fence(Ordering::Release);
+        write_u32(&self.sq_ring, SQ_HEAD, *sq_head);
+
+        sqes
+    }
+
+    /// Returns whether the SQ ring is full.
+    pub(super) fn is_sq_full(&self) -> bool {
+        let tail = read_u32(&self.sq_ring, SQ_TAIL);
+        tail.wrapping_sub(*self.sq_head.lock()) >= self.sq_entries
+    }
+
+    /// Posts a CQE to the CQ.
+    ///
+    /// If the CQ ring is full, the CQE is kept in the kernel until there is room in the ring.
+    pub(super) fn post_cqe(&self, cqe: Cqe) {
+        let mut cq = self.cq.lock();
+
+        self.flush_overflow_locked(&mut cq);
+        if !cq.overflow.is_empty() || !self.push_cqe_locked(&mut cq, &cqe) {
+            if cq.overflow.is_empty() {
+                self.update_sq_flags(IORING_SQ_CQ_OVERFLOW, true);
+            }
+            cq.overflow.push_back(cqe);
+        }
+    }
+
+    /// Moves the overflowed CQEs to the CQ ring as many as possible.
+    ///
+    /// This method returns whether there are no overflowed CQEs left.
+    pub(super) fn flush_overflow(&self) -> bool {
+        let mut cq = self.cq.lock();
+        self.flush_overflow_locked(&mut cq)
+    }
+
+    fn flush_overflow_locked(&self, cq: &mut CompletionQueue) -> bool {
+        if cq.overflow.is_empty() {
+            return true;
+        }
+
+        while let Some(cqe) = cq.overflow.front().copied() {
+            if !self.push_cqe_locked(cq, &cqe) {
+                return false;
+            }
+            cq.overflow.pop_front();
+        }
+
+        self.update_sq_flags(IORING_SQ_CQ_OVERFLOW, false);
+        true
+    }
+
+    fn push_cqe_locked(&self, cq: &mut CompletionQueue, cqe: &Cqe) -> bool {
+        let head = read_u32(&self.cq_ring, CQ_HEAD);
+        if cq.tail.wrapping_sub(head) >= self.cq_entries {
+            return false;
+        }
+
+        let offset = CQ_CQES + (cq.tail & (self.cq_entries - 1)) as usize * size_of::<Cqe>();
+        page_writer(&self.cq_ring, offset).write_val(cqe).unwrap();
+        cq.tail = cq.tail.wrapping_add(1);
+
+        // Make sure that the CQE is visible before the new tail.
+        fence(Ordering::Release);
+        write_u32(&self.cq_ring, CQ_TAIL, cq.tail);
+
+        true
+    }
+
+    fn update_sq_flags(&self, flag: u32, is_set: bool) {
+        let flags = read_u32(&self.sq_ring, SQ_FLAGS);
+        let new_flags = if is_set { flags | flag } else { flags & !flag };
+        write_u32(&self.sq_ring, SQ_FLAGS, new_flags);
+    }
+
+    /// Returns the number of CQEs that are ready to be reaped, including the overflowed ones.
+    pub(super) fn nr_ready_cqes(&self) -> u32 {
+        let cq = self.cq.lock();
+        let head = read_u32(&self.cq_ring, CQ_HEAD);
+        cq.tail
+            .wrapping_sub(head)
+            .min(self.cq_entries)
+            .saturating_add(cq.overflow.len() as u32)
+    }
+}
+
+/// Commits the pages of a region in the VMO.
+fn commit_pages(vmo: &Vmo, offset: usize, size: usize) -> Result<Box<[CachePage]>> {
+    let size = size.align_up(PAGE_SIZE);
+    (offset / PAGE_SIZE..(offset + size) / PAGE_SIZE)
+        .map(|page_idx| vmo.commit_on(page_idx))
+        .collect()
+}
+
+fn page_reader(pages: &[CachePage], offset: usize) -> VmReader<'_, Infallible> {
+    let mut reader = pages[offset / PAGE_SIZE].reader();
+    reader.skip(offset % PAGE_SIZE);
+    reader
+}
+
+fn page_writer(pages: &[CachePage], offset: usize) -> VmWriter<'_, Infallible> {
+    let mut writer = pages[offset / PAGE_SIZE].writer();
+    writer.skip(offset % PAGE_SIZE);
+    writer
+}
+
+fn read_u32(pages: &[CachePage], offset: usize) -> u32 {
+    page_reader(pages, offset).read_once::<u32>().unwrap()
+}
+
+fn write_u32(pages: &[CachePage], offset: usize, val: u32) {
+    page_writer(pages, offset).write_once(&val).unwrap();
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The workers of an io_uring instance.
+//!
+//! Each io_uring instance has its own workers, which are kernel threads that execute the chains
+//! that may block. The workers are spawned on demand up to a limit, and they exit when the
+//! instance is closed. A busy worker can be interrupted by [`KernelThread::interrupt`], which
+//! fails the blocking operation with `EINTR`, so that the chain is cancelled promptly.
+//!
+//! [`KernelThread::interrupt`]: crate::thread::kernel_thread::KernelThread::interrupt
+
+use ostd::{cpu::num_cpus, sync::WaitQueue};
+
+use super::{file::IoRing, op::Op};
+use crate::{
+    prelude::*,
+    thread::{
+        Thread, Tid,
+        kernel_thread::{AsKernelThread, ThreadOptions},
+    },
+};
+
+/// The maximum number of workers per CPU for an io_uring instance.
+const MAX_WORKERS_PER_CPU: usize = 4;
+
+/// The workers of an io_uring instance.
+pub(super) struct IoWorkers {
+    state: SpinLock<WorkersState>,
+    /// The wait queue of the idle workers.
+    wait_queue: WaitQueue,
+    max_workers: usize,
+}
+
+struct WorkersState {
+    /// The chains that wait for a worker.
+    queue: VecDeque<VecDeque<Op>>,
+    /// The workers that have started.
+    workers: Vec<Worker>,
+    /// The number of workers, including those that have not started.
+    nr_workers: usize,
+    /// The number of workers that are not executing any chains.
+    nr_idle: usize,
+    is_closed: bool,
+}
+
+struct Worker {
+    thread: Arc<Thread>,
+    /// The submitter of the chain that is being executed.
+    submitter: Option<Tid>,
+}
+
+impl IoWorkers {
+    /// Creates the workers for an io_uring instance with `sq_entries` SQ entries.
+    ///
+    /// No workers are spawned until a chain is enqueued.
+    pub(super) fn new(sq_entries: u32) -> Self {
+        let max_workers = (sq_entries as usize).min(MAX_WORKERS_PER_CPU * num_cpus());
+
+        Self {
+            state: SpinLock::new(WorkersState {
+                queue: VecDeque::new(),
+                workers: Vec::new(),
+                nr_workers: 0,
+                nr_idle: 0,
+                is_closed: false,
+            }),
+            wait_queue: WaitQueue::new(),
+            max_workers,
+        }
+    }
+
+    /// Enqueues a chain to be executed by a worker.
+    ///
+    /// A new worker is spawned if all the workers are busy and the limit is not reached. If the
+    /// workers have been closed, the chain is returned.
+    pub(super) fn enqueue(
+        &self,
+        ring: &Arc<IoRing>,
+        chain: VecDeque<Op>,
+    ) -> core::result::Result<(), VecDeque<Op>> {
+        let mut state = self.state.lock();
+        if state.is_closed {
+            return Err(chain);
+        }
+
+        state.queue.push_back(chain);
+        let should_spawn = state.queue.len() > state.nr_idle && state.nr_workers < self.max_workers;
+        if should_spawn {
+            state.nr_workers += 1;
+            state.nr_idle += 1;
+        }
+        drop(state);
+
+        if should_spawn {
+            let ring = ring.clone();
+            ThreadOptions::new(move || ring.run_worker()).spawn();
+        } else {
+            self.wait_queue.wake_one();
+        }
+
+        Ok(())
+    }
+
+    /// Registers the current thread as a worker.
+    pub(super) fn start_worker(&self, thread: &Arc<Thread>) {
+        self.state.lock().workers.push(Worker {
+            thread: thread.clone(),
+            submitter: None,
+        });
+    }
+
+    /// Waits for a chain to execute.
+    ///
+    /// This method returns `None` if the workers have been closed, in which case the current
+    /// worker should exit.
+    pub(super) fn wait_for_work(&self, thread: &Arc<Thread>) -> Option<VecDeque<Op>> {
+        self.wait_queue.wait_until(|| {
+            let mut state = self.state.lock();
+
+            if let Some(chain) = state.queue.pop_front() {
+                let submitter = chain.front().map(Op::submitter);
+                state.worker_mut(thread).submitter = submitter;
+                state.nr_idle -= 1;
+                // The interruption for the previous chain, if any, does not apply to this chain.
+                // This must be done with the lock held, so that a concurrent cancellation of this
+                // chain will not be missed.
+                thread.as_kernel_thread().unwrap().clear_interrupt();
+                return Some(Some(chain));
+            }
+
+            if state.is_closed {
+                state
+                    .workers
+                    .retain(|worker| !Arc::ptr_eq(&worker.thread, thread));
+                state.nr_workers -= 1;
+                state.nr_idle -= 1;
+                return Some(None);
+            }
+
+            None
+        })
+    }
+
+    /// Marks the current worker as idle after it executes a chain.
+    pub(super) fn finish_work(&self, thread: &Arc<Thread>) {
+        let mut state = self.state.lock();
+        state.worker_mut(thread).submitter = None;
+        state.nr_idle += 1;
+    }
+
+    /// Closes the workers.
+    ///
+    /// The busy workers are interrupted, and all the workers will exit. This method returns the
+    /// chains that have not been executed, which should be cancelled.
+    pub(super) fn close(&self) -> Vec<VecDeque<Op>> {
+        let mut state = self.state.lock();
+        state.is_closed = true;
+
+        let chains = state.queue.drain(..).collect();
+        for worker in state.workers.iter() {
+            worker.thread.as_kernel_thread().unwrap().interrupt();
+        }
+        drop(state);
+
+        self.wait_queue.wake_all();
+
+        chains
+    }
+
+    /// Returns whether the workers have been closed.
+    pub(super) fn is_closed(&self) -> bool {
+        self.state.lock().is_closed
+    }
+
+    /// Cancels the chains submitted by the thread of `tid`.
+    ///
+    /// The workers that are executing such chains are interrupted. This method returns the
+    /// chains that have not been executed, which should be cancelled.
+    pub(super) fn cancel_by_submitter(&self, tid: Tid) -> Vec<VecDeque<Op>> {
+        let mut state = self.state.lock();
+
+        let (cancelled, remaining): (VecDeque<_>, _) = state
+            .queue
+            .drain(..)
+            .partition(|chain| chain.front().is_some_and(|op| op.submitter() == tid));
+        state.queue = remaining;
+
+        for worker in state.workers.iter() {
+            if worker.submitter == Some(tid) {
+                worker.thread.as_kernel_thread().unwrap().interrupt();
+            }
+        }
+
+        cancelled.into()
+    }
+}
+
+impl WorkersState {
+    fn worker_mut(&mut self, thread: &Arc<Thread>) -> &mut Worker {
+        self.workers
+            .iter_mut()
+            .find(|worker| Arc::ptr_eq(&worker.thread, thread))
+            .unwrap()
+    }
+}
@@ -3,6 +3,7 @@
 use aster_util::ranged_integer::RangedU32;
 use id_alloc::IdAlloc;
 
+use super::{IPC_PRIVATE, IpcFlags, IpcKey};
 use crate::prelude::*;
 
 /// An IPC ID.
@@ -13,10 +14,19 @@ pub type IpcId = RangedU32<1, { i32::MAX as u32 }>;
 /// Lock ordering:
 /// `objects` -> `id_allocator`.
 pub(super) struct IpcIds<T> {
-    objects: RwMutex<BTreeMap<IpcId, T>>,
+    objects: RwMutex<IpcObjects<T>>,
     id_allocator: SpinLock<IdAlloc>,
 }
 
+struct IpcObjects<T> {
+    /// Objects and their keys, indexed by IDs.
+    by_id: BTreeMap<IpcId, (IpcKey, T)>,
+    /// IDs of objects, indexed by keys.
+    ///
+    /// Objects whose keys are private are not in this map.
+    by_key: BTreeMap<IpcKey, IpcId>,
+}
+
 impl<T> IpcIds<T> {
     /// Creates an IPC ID table with IDs in `1..=max_id`.
     pub(super) fn new(max_id: IpcId) -> Self {
@@ -24,8 +34,13 @@ impl<T> IpcIds<T> {
         // Remove the first index 0 (IPC IDs start from 1).
         id_allocator.alloc_specific(0).unwrap();
 
+        let objects = IpcObjects {
+            by_id: BTreeMap::new(),
+            by_key: BTreeMap::new(),
+        };
+
         Self {
-            objects: RwMutex::new(BTreeMap::new()),
+            objects: RwMutex::new(objects),
             id_allocator: SpinLock::new(id_allocator),
         }
     }
@@ -37,13 +52,25 @@ impl<T> IpcIds<T> {
     {
         let objects = self.objects.read();
 
-        let Some(object) = objects.get(&id) else {
+        let Some((_, object)) = objects.by_id.get(&id) else {
             return Err(IdNotExistError);
         };
 
         Ok(op(object))
     }
 
+    /// Calls `op` with each object and its ID in ascending order of IDs.
+    pub(super) fn for_each<F>(&self, mut op: F)
+    where
+        F: FnMut(IpcId, &T),
+    {
+        let objects = self.objects.read();
+
+        for (id, (_, object)) in objects.by_id.iter() {
+            op(*id, object);
+        }
+    }
+
     /// Removes the object identified by `id`.
     pub(super) fn remove<F>(&self, id: IpcId, may_remove: F) -> Result<()>
     where
@@ -53,61 +80,108 @@ impl<T> IpcIds<T> {
 
         let mut objects = self.objects.write();
 
-        let Entry::Occupied(entry) = objects.entry(id) else {
+        let Entry::Occupied(entry) = objects.by_id.entry(id) else {
             return_errno_with_message!(Errno::EINVAL, "the ID does not exist");
         };
 
-        may_remove(entry.get())?;
-        entry.remove();
+        may_remove(&entry.get().1)?;
+        let (key, _) = entry.remove();
+        if objects.by_key.get(&key) == Some(&id) {
+            objects.by_key.remove(&key);
+        }
 
         self.id_allocator.lock().free(id.get() as usize);
 
         Ok(())
     }
 
-    /// Inserts a new object with an automatically allocated ID.
-    pub(super) fn insert_auto<F>(&self, new_object_fn: F) -> Result<IpcId>
-    where
-        F: FnOnce(IpcId) -> Result<T>,
-    {
+    /// Makes the key of the object identified by `id` private.
+    ///
+    /// After this, the object can no longer be looked up by its key, and a new object with the
+    /// same key can be created.
+    pub(super) fn make_key_private(&self, id: IpcId) {
         let mut objects = self.objects.write();
 
-        let Some(id) = self
-            .id_allocator
-            .lock()
-            .alloc()
-            .map(|id| IpcId::new(id as u32))
-        else {
-            return_errno_with_message!(Errno::ENOSPC, "all IDs are exhausted");
+        let Some((key, _)) = objects.by_id.get_mut(&id) else {
+            return;
         };
+        let old_key = core::mem::replace(key, IPC_PRIVATE);
+        if old_key != IPC_PRIVATE {
+            objects.by_key.remove(&old_key);
+        }
+    }
 
-        let object = match new_object_fn(id) {
-            Ok(object) => object,
-            Err(err) => {
-                self.id_allocator.lock().free(id.get() as usize);
-                return Err(err);
+    /// Returns the ID of the object with `key` or inserts a new object with `key`.
+    ///
+    /// If the object exists, `check_existing` is called to check whether the object can be
+    /// returned. Otherwise, a new object is created with `new_object_fn` if `IPC_CREAT` is
+    /// specified in `flags`.
+    ///
+    /// `key` must not be [`IPC_PRIVATE`]. To create private objects, use [`Self::insert_auto`].
+    pub(super) fn get_or_insert<F, G>(
+        &self,
+        key: IpcKey,
+        flags: IpcFlags,
+        check_existing: F,
+        new_object_fn: G,
+    ) -> Result<IpcId>
+    where
+        F: FnOnce(&T) -> Result<()>,
+        G: FnOnce(IpcId) -> Result<T>,
+    {
+        debug_assert_ne!(key, IPC_PRIVATE);
+
+        let mut objects = self.objects.write();
+
+        if let Some(id) = objects.by_key.get(&key).copied() {
+            if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+                return_errno_with_message!(
+                    Errno::EEXIST,
+                    "the IPC object already exists with IPC_EXCL"
+                );
             }
-        };
-        objects.insert(id, object);
+
+            check_existing(&objects.by_id.get(&id).unwrap().1)?;
+            return Ok(id);
+        }
+
+        if !flags.contains(IpcFlags::IPC_CREAT) {
+            return_errno_with_message!(Errno::ENOENT, "the key does not exist");
+        }
+
+        let id = self.insert_locked(&mut objects, key, new_object_fn)?;
+        objects.by_key.insert(key, id);
 
         Ok(id)
     }
 
-    /// Inserts a new object at `id`.
-    pub(super) fn insert_at<F>(&self, id: IpcId, new_object_fn: F) -> Result<()>
+    /// Inserts a new object with a private key and an automatically allocated ID.
+    pub(super) fn insert_auto<F>(&self, new_object_fn: F) -> Result<IpcId>
     where
         F: FnOnce(IpcId) -> Result<T>,
     {
         let mut objects = self.objects.write();
 
-        if self
+        self.insert_locked(&mut objects, IPC_PRIVATE, new_object_fn)
+    }
+
+    fn insert_locked<F>(
+        &self,
+        objects: &mut IpcObjects<T>,
+        key: IpcKey,
+        new_object_fn: F,
+    ) -> Result<IpcId>
+    where
+        F: FnOnce(IpcId) -> Result<T>,
+    {
+        let Some(id) = self
             .id_allocator
             .lock()
-            .alloc_specific(id.get() as usize)
-            .is_none()
-        {
-            return_errno_with_message!(Errno::EEXIST, "the ID already exists");
-        }
+            .alloc()
+            .map(|id| IpcId::new(id as u32))
+        else {
+            return_errno_with_message!(Errno::ENOSPC, "all IDs are exhausted");
+        };
 
         let object = match new_object_fn(id) {
             Ok(object) => object,
@@ -116,9 +190,9 @@ impl<T> IpcIds<T> {
                 return Err(err);
             }
         };
-        objects.insert(id, object);
+        objects.by_id.insert(id, (key, object));
 
-        Ok(())
+        Ok(id)
     }
 }
 
@@ -2,26 +2,37 @@
 
 //! Defines the IPC namespace abstraction.
 //!
-//! An IPC namespace isolates System V IPC resources from other namespaces.
-//! It currently manages semaphore sets only, while message queues and shared
-//! memory remain to be added.
+//! An IPC namespace isolates System V IPC resources and POSIX message queues
+//! from other namespaces. It manages semaphore sets, message queues, and
+//! shared memory segments.
 //!
-//! Each namespace stores its semaphore sets in a per-namespace map keyed by
-//! IPC key and uses a dedicated ID allocator to assign semaphore identifiers.
+//! Each namespace stores each kind of System V IPC object in a per-namespace
+//! map keyed by IPC ID and uses a dedicated ID allocator to assign the
+//! identifiers. POSIX message queues are stored in a per-namespace mqueue
+//! file system.
 
 use aster_rights::ReadOp;
 use spin::Once;
 
 use super::{
-    IPC_PRIVATE, IpcFlags, IpcId, IpcKey,
+    IPC_PRIVATE, IpcFlags, IpcId, IpcKey, PermissionMode,
     ipc_ids::IpcIds,
-    semaphore::system_v::{
-        PermissionMode,
-        sem_set::{SEMMNI, SemaphoreSet},
+    msg::{
+        MSGMNB, MSGMNI,
+        msg_queue::{MessageQueue, MsqidDs},
+    },
+    semaphore::system_v::sem_set::{SEMMNI, SemaphoreSet},
+    shm::{
+        SHMMAX, SHMMIN, SHMMNI,
+        shm_segment::{ShmSegment, ShmidDs},
     },
 };
 use crate::{
-    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    fs::{
+        mqueuefs,
+        pseudofs::{NsCommonOps, NsType, StashedDentry},
+        vfs::path::Mount,
+    },
     prelude::*,
     process::{
         Credentials, UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread,
@@ -32,15 +43,24 @@ use crate::{
 /// The IPC namespace.
 ///
 /// An IPC namespace isolates System V IPC objects
-/// (semaphores, message queues, shared memory).
+/// (semaphores, message queues, shared memory)
+/// and POSIX message queues.
 /// Each namespace maintains its own independent set
 /// of IPC resources and identifier allocator.
 ///
 /// Lock ordering:
-/// `sem_ids` -> `SemaphoreSet::inner`.
+/// - `sem_ids` -> `SemaphoreSet::inner`.
+/// - `msg_ids` -> `MessageQueue::inner`.
+/// - `Vmar::inner` -> `shm_ids` -> `ShmSegment::inner`.
 pub struct IpcNamespace {
     /// Semaphore sets within this namespace.
     sem_ids: IpcIds<SemaphoreSet>,
+    /// Message queues within this namespace.
+    msg_ids: IpcIds<Arc<MessageQueue>>,
+    /// Shared memory segments within this namespace.
+    shm_ids: IpcIds<Arc<ShmSegment>>,
+    /// Internal mount of the mqueue file system for POSIX message queues.
+    mqueue_mount: Once<Arc<Mount>>,
     /// Owner user namespace.
     owner: Arc<UserNamespace>,
     /// Stashed dentry for nsfs.
@@ -64,11 +84,26 @@ impl IpcNamespace {
             IpcId::new(SEMMNI as u32)
         };
 
+        const MAX_MSG_ID: IpcId = {
+            assert!(MSGMNI <= u32::MAX as usize);
+            IpcId::new(MSGMNI as u32)
+        };
+
+        const MAX_SHM_ID: IpcId = {
+            assert!(SHMMNI <= u32::MAX as usize);
+            IpcId::new(SHMMNI as u32)
+        };
+
         let sem_ids = IpcIds::new(MAX_SEM_ID);
+        let msg_ids = IpcIds::new(MAX_MSG_ID);
+        let shm_ids = IpcIds::new(MAX_SHM_ID);
         let stashed_dentry = StashedDentry::new();
 
         Arc::new(Self {
             sem_ids,
+            msg_ids,
+            shm_ids,
+            mqueue_mount: Once::new(),
             owner,
             stashed_dentry,
         })
@@ -127,51 +162,20 @@ impl IpcNamespace {
             return self.create_sem_set(num_sems, mode, credentials);
         }
 
-        // For now, we compute `semid` by hashing `key`. If the hash conflicts, we will simply
-        // return an error. See the TODO below.
-        const { assert!(SEMMNI <= u32::MAX as usize) };
-        let semid = IpcId::new(key.cast_unsigned() % SEMMNI as u32 + 1);
-
-        loop {
-            match self.sem_ids.with(semid, |sem_set| {
-                if sem_set.permission().key() != key {
-                    if flags.contains(IpcFlags::IPC_CREAT) {
-                        // TODO: Manage all keys in a data structure (e.g., a map)
-                        return_errno_with_message!(Errno::ENOSPC, "key hashes conflict");
-                    }
-                    return_errno_with_message!(Errno::ENOENT, "the key does not exist");
-                }
-
+        self.sem_ids.get_or_insert(
+            key,
+            flags,
+            |sem_set| {
                 Self::validate_sem_set(sem_set, PermissionMode::ALTER | PermissionMode::READ)?;
 
-                if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
-                    return_errno_with_message!(
-                        Errno::EEXIST,
-                        "the semaphore set already exists with IPC_EXCL"
-                    );
-                }
-
                 if sem_set.num_sems() < num_sems {
                     return_errno_with_message!(Errno::EINVAL, "the semaphore set is too small");
                 }
 
-                Ok(semid)
-            }) {
-                Err(_id_not_exist) if flags.contains(IpcFlags::IPC_CREAT) => {}
-                Err(_id_not_exist) => {
-                    return_errno_with_message!(Errno::ENOENT, "the key does not exist");
-                }
-                Ok(result) => return result,
-            }
-
-            match self.sem_ids.insert_at(semid, |_| {
-                SemaphoreSet::new(key, num_sems, mode, &credentials)
-            }) {
-                Ok(()) => return Ok(semid),
-                Err(err) if err.error() == Errno::EEXIST => continue,
-                Err(err) => return Err(err),
-            }
-        }
+                Ok(())
+            },
+            |_| SemaphoreSet::new(key, num_sems, mode, &credentials),
+        )
     }
 
     fn validate_sem_set(_sem_set: &SemaphoreSet, required_perm: PermissionMode) -> Result<()> {
@@ -193,6 +197,235 @@ impl IpcNamespace {
         self.sem_ids
             .insert_auto(|_| SemaphoreSet::new(IPC_PRIVATE, num_sems, mode, &credentials))
     }
+
+    /// Returns the message queue identified by `msqid`.
+    ///
+    /// The message queue is returned as an [`Arc`] so that the caller can wait on it without
+    /// holding any locks of the namespace.
+    pub fn get_msg_queue(
+        &self,
+        msqid: IpcId,
+        required_perm: PermissionMode,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<MessageQueue>> {
+        self.msg_ids.with(msqid, |msg_queue| {
+            msg_queue
+                .permission()
+                .check_access(required_perm, &self.owner, posix_thread)?;
+            Ok(msg_queue.clone())
+        })?
+    }
+
+    /// Removes the message queue identified by `msqid`.
+    ///
+    /// All the threads waiting on the queue will fail with `EIDRM`.
+    pub fn remove_msg_queue(&self, msqid: IpcId, posix_thread: &PosixThread) -> Result<()> {
+        self.msg_ids.remove(msqid, |msg_queue| {
+            msg_queue
+                .permission()
+                .check_owner(&self.owner, posix_thread)?;
+            msg_queue.mark_removed();
+            Ok(())
+        })
+    }
+
+    /// Updates the message queue identified by `msqid` from `msqid_ds`.
+    pub fn set_msg_queue(
+        &self,
+        msqid: IpcId,
+        msqid_ds: &MsqidDs,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        self.msg_ids.with(msqid, |msg_queue| {
+            let stat = msg_queue.stat();
+            stat.permission.check_owner(&self.owner, posix_thread)?;
+
+            let new_max_bytes = msqid_ds.qbytes();
+            if new_max_bytes > MSGMNB as u64 && new_max_bytes > stat.max_bytes as u64 {
+                lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+                    &self.owner,
+                    posix_thread,
+                    CapSet::SYS_RESOURCE,
+                ))
+                .map_err(|_| {
+                    Error::with_message(
+                        Errno::EPERM,
+                        "raising the queue size beyond MSGMNB requires CAP_SYS_RESOURCE",
+                    )
+                })?;
+            }
+
+            msg_queue.set_msqid_ds(msqid_ds);
+            Ok(())
+        })?
+    }
+
+    /// Calls `op` with each message queue and its ID.
+    pub fn for_each_msg_queue<F>(&self, op: F)
+    where
+        F: FnMut(IpcId, &Arc<MessageQueue>),
+    {
+        self.msg_ids.for_each(op);
+    }
+
+    /// Returns the existing message queue or creates a new one.
+    pub fn get_or_create_msg_queue(
+        &self,
+        key: IpcKey,
+        flags: IpcFlags,
+        mode: u16,
+        posix_thread: &PosixThread,
+    ) -> Result<IpcId> {
+        let credentials = posix_thread.credentials();
+
+        if key == IPC_PRIVATE {
+            return self
+                .msg_ids
+                .insert_auto(|_| Ok(Arc::new(MessageQueue::new(IPC_PRIVATE, mode, &credentials))));
+        }
+
+        self.msg_ids.get_or_insert(
+            key,
+            flags,
+            |msg_queue| {
+                // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L365>
+                let required_perm = PermissionMode::from_bits_truncate(mode >> 6);
+                msg_queue
+                    .permission()
+                    .check_access(required_perm, &self.owner, posix_thread)
+            },
+            |_| Ok(Arc::new(MessageQueue::new(key, mode, &credentials))),
+        )
+    }
+
+    /// Returns the shared memory segment identified by `shmid`.
+    pub fn get_shm_segment(
+        &self,
+        shmid: IpcId,
+        required_perm: PermissionMode,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<ShmSegment>> {
+        self.shm_ids.with(shmid, |segment| {
+            segment
+                .permission()
+                .check_access(required_perm, &self.owner, posix_thread)?;
+            Ok(segment.clone())
+        })?
+    }
+
+    /// Removes the shared memory segment identified by `shmid`.
+    ///
+    /// If the segment is still attached, it is destroyed on its last detach. Before that, the
+    /// segment can still be accessed by its ID, but no longer by its key.
+    pub fn remove_shm_segment(&self, shmid: IpcId, posix_thread: &PosixThread) -> Result<()> {
+        self.shm_ids.with(shmid, |segment| {
+            segment
+                .permission()
+                .check_owner(&self.owner, posix_thread)?;
+            segment.mark_removed();
+            Ok(())
+        })??;
+
+        self.shm_ids.make_key_private(shmid);
+        self.destroy_shm_segment_if_unused(shmid);
+
+        Ok(())
+    }
+
+    /// Destroys the shared memory segment identified by `shmid` if it has been removed and is no
+    /// longer attached.
+    pub(super) fn destroy_shm_segment_if_unused(&self, shmid: IpcId) {
+        let _ = self.shm_ids.remove(shmid, |segment| {
+            if !segment.is_removed_and_detached() {
+                return_errno_with_message!(Errno::EBUSY, "the segment is still attached");
+            }
+            Ok(())
+        });
+    }
+
+    /// Updates the shared memory segment identified by `shmid` from `shmid_ds`.
+    pub fn set_shm_segment(
+        &self,
+        shmid: IpcId,
+        shmid_ds: &ShmidDs,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        self.shm_ids.with(shmid, |segment| {
+            segment
+                .permission()
+                .check_owner(&self.owner, posix_thread)?;
+            segment.set_shmid_ds(shmid_ds);
+            Ok(())
+        })?
+    }
+
+    /// Calls `op` with each shared memory segment and its ID.
+    pub fn for_each_shm_segment<F>(&self, op: F)
+    where
+        F: FnMut(IpcId, &Arc<ShmSegment>),
+    {
+        self.shm_ids.for_each(op);
+    }
+
+    /// Returns the existing shared memory segment or creates a new one.
+    pub fn get_or_create_shm_segment(
+        self: &Arc<Self>,
+        key: IpcKey,
+        size: usize,
+        flags: IpcFlags,
+        mode: u16,
+        posix_thread: &PosixThread,
+    ) -> Result<IpcId> {
+        let credentials = posix_thread.credentials();
+        let pid = posix_thread.process().pid();
+
+        let new_segment = |shmid| {
+            if !(SHMMIN..=SHMMAX).contains(&size) {
+                return_errno_with_message!(Errno::EINVAL, "the segment size is invalid");
+            }
+            let segment = ShmSegment::new(
+                key,
+                size,
+                mode,
+                &credentials,
+                pid,
+                Arc::downgrade(self),
+                shmid,
+            )?;
+            Ok(Arc::new(segment))
+        };
+
+        if key == IPC_PRIVATE {
+            return self.shm_ids.insert_auto(new_segment);
+        }
+
+        self.shm_ids.get_or_insert(
+            key,
+            flags,
+            |segment| {
+                // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L365>
+                let required_perm = PermissionMode::from_bits_truncate(mode >> 6);
+                segment
+                    .permission()
+                    .check_access(required_perm, &self.owner, posix_thread)?;
+
+                if segment.size() < size {
+                    return_errno_with_message!(Errno::EINVAL, "the segment is too small");
+                }
+
+                Ok(())
+            },
+            new_segment,
+        )
+    }
+
+    /// Returns the internal mount of the mqueue file system.
+    ///
+    /// The file system is created on first use.
+    pub fn mqueue_mount(&self) -> Result<&Arc<Mount>> {
+        self.mqueue_mount
+            .try_call_once(|| mqueuefs::new_kern_mount(self.owner.clone()))
+    }
 }
 
 impl NsCommonOps for IpcNamespace {
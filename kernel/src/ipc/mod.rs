@@ -2,12 +2,18 @@
 
 use crate::{
     prelude::*,
-    process::{Gid, Uid},
+    process::{
+        Gid, Uid, UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread,
+    },
+    security::lsm::hooks as lsm_hooks,
 };
 
 mod ipc_ids;
 mod ipc_ns;
+pub mod mqueue;
+pub mod msg;
 pub mod semaphore;
+pub mod shm;
 
 pub use ipc_ids::IpcId;
 pub use ipc_ns::IpcNamespace;
@@ -42,7 +48,7 @@ bitflags! {
 #[derive(Clone, Copy, Debug, TryFromInt)]
 pub enum IpcControlCmd {
     IPC_RMID = 0,
-    // IPC_SET = 1,
+    IPC_SET = 1,
     IPC_STAT = 2,
 
     SEM_GETPID = 11,
@@ -54,7 +60,17 @@ pub enum IpcControlCmd {
     // SEM_SETALL = 17,
 }
 
-#[derive(Debug)]
+bitflags! {
+    /// The access permissions of IPC objects.
+    pub struct PermissionMode: u16 {
+        const EXEC   = 0o001;
+        const ALTER  = 0o002;
+        const WRITE  = 0o002;
+        const READ   = 0o004;
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct IpcPermission {
     key: IpcKey,
     /// Owner's UID
@@ -99,7 +115,7 @@ impl IpcPermission {
         self.mode
     }
 
-    pub(self) fn new_sem_perm(key: IpcKey, uid: Uid, gid: Gid, mode: u16) -> Self {
+    pub(self) fn new(key: IpcKey, uid: Uid, gid: Gid, mode: u16) -> Self {
         Self {
             key,
             uid,
@@ -109,4 +125,116 @@ impl IpcPermission {
             mode,
         }
     }
+
+    /// Sets the owner's UID, the owner's GID, and the permission bits.
+    ///
+    /// Only the lowest 9 bits of `mode` are used.
+    pub(self) fn set_owner_and_mode(&mut self, uid: Uid, gid: Gid, mode: u16) {
+        self.uid = uid;
+        self.gid = gid;
+        self.mode = (self.mode & !0o777) | (mode & 0o777);
+    }
+
+    /// Checks whether the thread has the `required` permission on the IPC object.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L528>
+    pub(self) fn check_access(
+        &self,
+        required: PermissionMode,
+        user_ns: &UserNamespace,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        let requested = required.bits();
+        let granted = {
+            let credentials = posix_thread.credentials();
+            let euid = credentials.euid();
+            if euid == self.uid || euid == self.cuid {
+                self.mode >> 6
+            } else if [self.gid, self.cguid]
+                .iter()
+                .any(|gid| *gid == credentials.egid() || credentials.groups().contains(gid))
+            {
+                self.mode >> 3
+            } else {
+                self.mode
+            }
+        };
+
+        if requested & !granted & 0o007 == 0 {
+            return Ok(());
+        }
+
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            user_ns,
+            posix_thread,
+            CapSet::IPC_OWNER,
+        ))
+        .map_err(|_| Error::with_message(Errno::EACCES, "the IPC object cannot be accessed"))
+    }
+
+    /// Checks whether the thread is allowed to change or remove the IPC object.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L766>
+    pub(self) fn check_owner(
+        &self,
+        user_ns: &UserNamespace,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        let euid = posix_thread.credentials().euid();
+        if euid == self.uid || euid == self.cuid {
+            return Ok(());
+        }
+
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            user_ns,
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))
+        .map_err(|_| {
+            Error::with_message(
+                Errno::EPERM,
+                "the thread is neither the owner nor the creator of the IPC object",
+            )
+        })
+    }
+
+    /// Returns the `ipc64_perm` structure to be copied to the user space.
+    pub(self) fn to_ipc_perm(&self) -> IpcPerm {
+        IpcPerm {
+            key: self.key.cast_unsigned(),
+            uid: self.uid.into(),
+            gid: self.gid.into(),
+            cuid: self.cuid.into(),
+            cgid: self.cguid.into(),
+            mode: self.mode,
+            ..IpcPerm::default()
+        }
+    }
+
+    /// Updates the permission from the `ipc64_perm` structure copied from the user space.
+    pub(self) fn set_from_ipc_perm(&mut self, ipc_perm: &IpcPerm) {
+        self.set_owner_and_mode(
+            Uid::new(ipc_perm.uid),
+            Gid::new(ipc_perm.gid),
+            ipc_perm.mode,
+        );
+    }
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/ipcbuf.h#L22>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct IpcPerm {
+    key: u32,
+    uid: u32,
+    gid: u32,
+    cuid: u32,
+    cgid: u32,
+    mode: u16,
+    _pad1: u16,
+    seq: u16,
+    _pad2: u16,
+    _unused1: u64,
+    _unused2: u64,
 }
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! POSIX message queue.
+
+use crate::{
+    prelude::*,
+    process::signal::{c_types::sigval_t, sig_num::SigNum},
+};
+
+pub mod msg_queue;
+
+// The following constant values are derived from the default values in Linux.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/ipc_namespace.h>
+
+/// Maximum number of message queues in an IPC namespace.
+pub const QUEUES_MAX: usize = 256;
+/// Maximum number of messages in a queue for unprivileged users.
+pub const MSG_MAX: usize = 10;
+/// Maximum size of a message in bytes for unprivileged users.
+pub const MSGSIZE_MAX: usize = 8192;
+/// Default number of messages in a queue.
+pub const MSG_DEFAULT: usize = 10;
+/// Default size of a message in bytes.
+pub const MSGSIZE_DEFAULT: usize = 8192;
+/// Maximum number of messages in a queue for privileged users.
+pub const HARD_MSGMAX: usize = 65536;
+/// Maximum size of a message in bytes for privileged users.
+pub const HARD_MSGSIZEMAX: usize = 16 * 1024 * 1024;
+/// The exclusive upper bound of message priorities.
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// The attributes of a POSIX message queue (`struct mq_attr`).
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct MqAttr {
+    /// Flags of the message queue description (`0` or `O_NONBLOCK`)
+    pub mq_flags: i64,
+    /// Maximum number of messages in the queue
+    pub mq_maxmsg: i64,
+    /// Maximum size of a message in bytes
+    pub mq_msgsize: i64,
+    /// Number of messages currently in the queue
+    pub mq_curmsgs: i64,
+    _reserved: [i64; 4],
+}
+
+/// How to notify a process when a message arrives at an empty queue.
+#[derive(Clone, Copy)]
+pub enum MqNotifyKind {
+    /// Do nothing, but the registration is still consumed (`SIGEV_NONE`).
+    None,
+    /// Send a signal with the value (`SIGEV_SIGNAL`).
+    Signal { signum: SigNum, value: sigval_t },
+}
@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use super::{
+    HARD_MSGMAX, HARD_MSGSIZEMAX, MSG_DEFAULT, MSG_MAX, MSGSIZE_DEFAULT, MSGSIZE_MAX, MqAttr,
+    MqNotifyKind,
+};
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::{
+        Pid, PidNamespace, Process, Uid,
+        signal::{
+            PollHandle, Pollable, Pollee, c_types::siginfo_t, constants::SI_MESGQ,
+            signals::raw::RawSignal,
+        },
+    },
+};
+
+/// A POSIX message queue.
+pub struct PosixMsgQueue {
+    /// Maximum number of messages allowed in the queue
+    max_messages: usize,
+    /// Maximum size of a message in bytes
+    max_msg_size: usize,
+    inner: Mutex<PosixMsgQueueInner>,
+    pollee: Pollee,
+    /// The number of receivers waiting for messages.
+    num_waiting_receivers: AtomicUsize,
+}
+
+struct PosixMsgQueueInner {
+    /// Messages grouped by priorities, in the order they were sent
+    messages: BTreeMap<u32, VecDeque<Box<[u8]>>>,
+    /// Total number of messages in the queue
+    num_messages: usize,
+    /// Total number of bytes of the messages in the queue
+    num_bytes: usize,
+    /// The registered notification
+    notification: Option<MqNotification>,
+}
+
+struct MqNotification {
+    owner: Weak<Process>,
+    owner_pid: Pid,
+    kind: MqNotifyKind,
+}
+
+impl PosixMsgQueue {
+    /// Creates a message queue with `attr`, or with the default attributes if `attr` is `None`.
+    ///
+    /// The attributes are checked against the limits of privileged users if `is_privileged`
+    /// returns true, or against the limits of unprivileged users otherwise.
+    pub fn new(attr: Option<&MqAttr>, is_privileged: impl FnOnce() -> bool) -> Result<Self> {
+        let (max_messages, max_msg_size) = match attr {
+            None => (MSG_DEFAULT, MSGSIZE_DEFAULT),
+            Some(attr) => {
+                if attr.mq_maxmsg <= 0 || attr.mq_msgsize <= 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the attributes are invalid");
+                }
+
+                let max_messages = attr.mq_maxmsg as usize;
+                let max_msg_size = attr.mq_msgsize as usize;
+                let (max_messages_limit, max_msg_size_limit) = if is_privileged() {
+                    (HARD_MSGMAX, HARD_MSGSIZEMAX)
+                } else {
+                    (MSG_MAX, MSGSIZE_MAX)
+                };
+                if max_messages > max_messages_limit || max_msg_size > max_msg_size_limit {
+                    return_errno_with_message!(Errno::EINVAL, "the attributes exceed the limits");
+                }
+
+                (max_messages, max_msg_size)
+            }
+        };
+
+        Ok(Self {
+            max_messages,
+            max_msg_size,
+            inner: Mutex::new(PosixMsgQueueInner {
+                messages: BTreeMap::new(),
+                num_messages: 0,
+                num_bytes: 0,
+                notification: None,
+            }),
+            pollee: Pollee::new(),
+            num_waiting_receivers: AtomicUsize::new(0),
+        })
+    }
+
+    /// Sends a message with `priority` to the queue.
+    ///
+    /// If the queue is full, this method will block until there is enough space or `timeout`
+    /// expires, unless `is_nonblocking` is true, in which case `EAGAIN` is returned.
+    ///
+    /// The sender is identified by `sender_pid` and `sender_uid` in the notification signal.
+    pub fn send(
+        &self,
+        message: Box<[u8]>,
+        priority: u32,
+        is_nonblocking: bool,
+        timeout: Option<&Duration>,
+        sender_pid: Pid,
+        sender_uid: Uid,
+    ) -> Result<()> {
+        if message.len() > self.max_msg_size() {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+        }
+
+        let mut message = Some(message);
+        let mut try_send = || self.try_send(&mut message, priority, sender_pid, sender_uid);
+
+        if is_nonblocking {
+            try_send()
+        } else {
+            self.wait_events(IoEvents::OUT, timeout, try_send)
+        }
+    }
+
+    fn try_send(
+        &self,
+        message: &mut Option<Box<[u8]>>,
+        priority: u32,
+        sender_pid: Pid,
+        sender_uid: Uid,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+
+        if inner.num_messages >= self.max_messages {
+            return_errno_with_message!(Errno::EAGAIN, "the message queue is full");
+        }
+
+        let message = message.take().unwrap();
+        inner.num_messages += 1;
+        inner.num_bytes += message.len();
+        inner
+            .messages
+            .entry(priority)
+            .or_default()
+            .push_back(message);
+
+        // Like Linux, the notification is delivered only if the queue was empty and no receivers
+        // are waiting for the message.
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/mqueue.c>
+        if inner.num_messages == 1
+            && self.num_waiting_receivers.load(Ordering::Relaxed) == 0
+            && let Some(notification) = inner.notification.take()
+        {
+            notification.deliver(sender_pid, sender_uid);
+        }
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN | IoEvents::RDNORM);
+
+        Ok(())
+    }
+
+    /// Receives the oldest message with the highest priority from the queue.
+    ///
+    /// If the queue is empty, this method will block until a message arrives or `timeout`
+    /// expires, unless `is_nonblocking` is true, in which case `EAGAIN` is returned.
+    ///
+    /// On success, the message and its priority are returned.
+    pub fn receive(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        timeout: Option<&Duration>,
+    ) -> Result<(Box<[u8]>, u32)> {
+        if max_len < self.max_msg_size() {
+            return_errno_with_message!(Errno::EMSGSIZE, "the buffer is too small");
+        }
+
+        if is_nonblocking {
+            return self.try_receive();
+        }
+
+        self.num_waiting_receivers.fetch_add(1, Ordering::Relaxed);
+        let result = self.wait_events(IoEvents::IN, timeout, || self.try_receive());
+        self.num_waiting_receivers.fetch_sub(1, Ordering::Relaxed);
+
+        result
+    }
+
+    fn try_receive(&self) -> Result<(Box<[u8]>, u32)> {
+        let mut inner = self.inner.lock();
+
+        let Some(mut entry) = inner.messages.last_entry() else {
+            return_errno_with_message!(Errno::EAGAIN, "the message queue is empty");
+        };
+        let priority = *entry.key();
+        let message = entry.get_mut().pop_front().unwrap();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+
+        inner.num_messages -= 1;
+        inner.num_bytes -= message.len();
+        drop(inner);
+
+        self.pollee.notify(IoEvents::OUT | IoEvents::WRNORM);
+
+        Ok((message, priority))
+    }
+
+    /// Registers `process` to be notified when a message arrives at the empty queue.
+    ///
+    /// If `kind` is `None`, the registration of `process` is removed instead.
+    pub fn set_notification(
+        &self,
+        kind: Option<MqNotifyKind>,
+        process: &Arc<Process>,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+
+        // A registration is effectively removed if its owner has exited.
+        let owner = inner
+            .notification
+            .as_ref()
+            .and_then(|notification| notification.owner.upgrade());
+
+        let Some(kind) = kind else {
+            if owner.is_some_and(|owner| Arc::ptr_eq(&owner, process)) {
+                inner.notification = None;
+            }
+            return Ok(());
+        };
+
+        if owner.is_some() {
+            return_errno_with_message!(
+                Errno::EBUSY,
+                "another process has registered for notification"
+            );
+        }
+
+        inner.notification = Some(MqNotification {
+            owner: Arc::downgrade(process),
+            owner_pid: process.pid(),
+            kind,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the maximum size of a message in bytes.
+    pub fn max_msg_size(&self) -> usize {
+        self.max_msg_size
+    }
+
+    /// Returns the attributes of the queue.
+    ///
+    /// The `mq_flags` field is always zero because it belongs to the queue descriptions.
+    pub fn attr(&self) -> MqAttr {
+        MqAttr {
+            mq_maxmsg: self.max_messages as i64,
+            mq_msgsize: self.max_msg_size as i64,
+            mq_curmsgs: self.inner.lock().num_messages as i64,
+            ..MqAttr::default()
+        }
+    }
+
+    /// Returns the contents of the queue file.
+    ///
+    /// The PID is displayed as seen from `pid_ns`.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/mqueue.c>
+    pub fn status(&self, pid_ns: &PidNamespace) -> String {
+        let inner = self.inner.lock();
+
+        let (notify, signo, notify_pid) = match inner.notification.as_ref() {
+            Some(notification) => {
+                let pid = pid_ns.local_id_of(notification.owner_pid).unwrap_or(0);
+                match notification.kind {
+                    MqNotifyKind::Signal { signum, .. } => (0, signum.as_u8(), pid),
+                    MqNotifyKind::None => (1, 0, pid),
+                }
+            }
+            None => (0, 0, 0),
+        };
+
+        let mut status = String::new();
+        let _ = writeln!(
+            status,
+            "QSIZE:{:<10} NOTIFY:{:<5} SIGNO:{:<5} NOTIFY_PID:{:<6}",
+            inner.num_bytes, notify, signo, notify_pid
+        );
+
+        status
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let inner = self.inner.lock();
+
+        let mut events = IoEvents::empty();
+        if inner.num_messages > 0 {
+            events |= IoEvents::IN | IoEvents::RDNORM;
+        }
+        if inner.num_messages < self.max_messages {
+            events |= IoEvents::OUT | IoEvents::WRNORM;
+        }
+
+        events
+    }
+}
+
+impl Pollable for PosixMsgQueue {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl MqNotification {
+    fn deliver(self, sender_pid: Pid, sender_uid: Uid) {
+        let MqNotifyKind::Signal { signum, value } = self.kind else {
+            return;
+        };
+        let Some(owner) = self.owner.upgrade() else {
+            return;
+        };
+
+        // The sender's PID is reported in the PID namespace of the owner.
+        let pid = owner.pid_ns().local_id_of(sender_pid).unwrap_or(0);
+
+        let mut info = siginfo_t::new(signum, SI_MESGQ);
+        info.set_pid_uid(pid, sender_uid);
+        info.set_value(value);
+        owner.enqueue_signal(Box::new(RawSignal::new(info)));
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V message queue.
+
+use core::fmt::Write;
+
+use crate::{
+    ipc::IpcNamespace,
+    prelude::*,
+    process::{Pid, PidNamespace},
+};
+
+pub mod msg_queue;
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum number of message queues.
+pub const MSGMNI: usize = 32000;
+/// Maximum size of a message in bytes.
+pub const MSGMAX: usize = 8192;
+/// Default maximum number of bytes in a message queue.
+pub const MSGMNB: usize = 16384;
+
+bitflags! {
+    /// Flags for `msgrcv`.
+    pub struct MsgRcvFlags: u32 {
+        /// Return error if no message of the desired type is available.
+        const IPC_NOWAIT = 1 << 11;
+        /// Truncate the message if it is too long.
+        const MSG_NOERROR = 1 << 12;
+        /// Receive any message except for the specified type.
+        const MSG_EXCEPT = 1 << 13;
+        /// Copy the message instead of removing it.
+        const MSG_COPY = 1 << 14;
+    }
+}
+
+/// Returns the contents of `/proc/sysvipc/msg` for the message queues in `ipc_ns`.
+///
+/// The PIDs are displayed as seen from `pid_ns`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/msg.c#L1322>
+pub fn sysvipc_proc_show(ipc_ns: &IpcNamespace, pid_ns: &PidNamespace) -> String {
+    let mut output = String::from(
+        "       key      msqid perms      cbytes       qnum lspid lrpid   uid   gid  cuid  cgid      stime      rtime      ctime\n",
+    );
+
+    let local_pid_of = |pid: Pid| pid_ns.local_id_of(pid).unwrap_or(0);
+
+    ipc_ns.for_each_msg_queue(|msqid, msg_queue| {
+        let stat = msg_queue.stat();
+        let permission = &stat.permission;
+        let _ = writeln!(
+            output,
+            "{:>10} {:>10}  {:>4o}  {:>10} {:>10} {:>5} {:>5} {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10}",
+            permission.key(),
+            msqid.get(),
+            permission.mode(),
+            stat.num_bytes,
+            stat.num_messages,
+            local_pid_of(stat.last_send_pid),
+            local_pid_of(stat.last_recv_pid),
+            u32::from(permission.uid()),
+            u32::from(permission.gid()),
+            u32::from(permission.cuid()),
+            u32::from(permission.cguid()),
+            stat.stime,
+            stat.rtime,
+            stat.ctime,
+        );
+    });
+
+    output
+}
@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_rights::ReadOp;
+use ostd::sync::WaitQueue;
+
+use super::{MSGMNB, MsgRcvFlags};
+use crate::{
+    ipc::{IpcKey, IpcPerm, IpcPermission},
+    prelude::*,
+    process::{Credentials, Pid, PidNamespace},
+    time::clocks::RealTimeCoarseClock,
+};
+
+/// A System V message queue.
+pub struct MessageQueue {
+    inner: Mutex<MsgQueueInner>,
+    /// Whether the queue has been removed by `IPC_RMID`.
+    is_removed: AtomicBool,
+    /// The senders waiting for free space in the queue.
+    send_wait_queue: WaitQueue,
+    /// The receivers waiting for messages.
+    recv_wait_queue: WaitQueue,
+}
+
+struct MsgQueueInner {
+    /// Message queue permission
+    permission: IpcPermission,
+    /// Messages in the order they were sent
+    messages: VecDeque<Message>,
+    /// Total number of bytes of the messages in the queue
+    num_bytes: usize,
+    /// Maximum number of bytes allowed in the queue
+    max_bytes: usize,
+    /// PID of the last `msgsnd`
+    last_send_pid: Pid,
+    /// PID of the last `msgrcv`
+    last_recv_pid: Pid,
+    /// Last `msgsnd` time
+    stime: u64,
+    /// Last `msgrcv` time
+    rtime: u64,
+    /// Creation time or last modification via `msgctl`
+    ctime: u64,
+}
+
+/// A message in a [`MessageQueue`].
+pub struct Message {
+    mtype: i64,
+    data: Box<[u8]>,
+}
+
+impl Message {
+    pub fn new(mtype: i64, data: Box<[u8]>) -> Self {
+        Self { mtype, data }
+    }
+
+    pub fn mtype(&self) -> i64 {
+        self.mtype
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A snapshot of the status of a [`MessageQueue`].
+pub struct MsgQueueStat {
+    pub permission: IpcPermission,
+    pub num_bytes: usize,
+    pub num_messages: usize,
+    pub max_bytes: usize,
+    pub last_send_pid: Pid,
+    pub last_recv_pid: Pid,
+    pub stime: u64,
+    pub rtime: u64,
+    pub ctime: u64,
+}
+
+// Both x86_64 and the generic 64-bit architectures adopt the same layout of `msqid64_ds`.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/msgbuf.h#L28>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct MsqidDs {
+    msg_perm: IpcPerm,
+    msg_stime: u64,
+    msg_rtime: u64,
+    msg_ctime: u64,
+    msg_cbytes: u64,
+    msg_qnum: u64,
+    msg_qbytes: u64,
+    msg_lspid: i32,
+    msg_lrpid: i32,
+    _unused4: u64,
+    _unused5: u64,
+}
+
+impl MsqidDs {
+    /// Returns the maximum number of bytes in the queue.
+    pub fn qbytes(&self) -> u64 {
+        self.msg_qbytes
+    }
+}
+
+impl MessageQueue {
+    pub(in crate::ipc) fn new(key: IpcKey, mode: u16, credentials: &Credentials<ReadOp>) -> Self {
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Self {
+            inner: Mutex::new(MsgQueueInner {
+                permission,
+                messages: VecDeque::new(),
+                num_bytes: 0,
+                max_bytes: MSGMNB,
+                last_send_pid: 0,
+                last_recv_pid: 0,
+                stime: 0,
+                rtime: 0,
+                ctime: now_secs(),
+            }),
+            is_removed: AtomicBool::new(false),
+            send_wait_queue: WaitQueue::new(),
+            recv_wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Sends a message to the queue.
+    ///
+    /// If the queue is full, this method will block until there is enough space, unless
+    /// `is_nonblocking` is true, in which case `EAGAIN` is returned.
+    pub fn send(&self, message: Message, is_nonblocking: bool, pid: Pid) -> Result<()> {
+        let mut message = Some(message);
+
+        if is_nonblocking {
+            self.try_send(&mut message, pid)?;
+        } else {
+            self.send_wait_queue
+                .pause_until(|| match self.try_send(&mut message, pid) {
+                    Err(err) if err.error() == Errno::EAGAIN => None,
+                    result => Some(result),
+                })??;
+        }
+
+        self.recv_wait_queue.wake_all();
+
+        Ok(())
+    }
+
+    fn try_send(&self, message: &mut Option<Message>, pid: Pid) -> Result<()> {
+        self.check_removed()?;
+
+        let mut inner = self.inner.lock();
+
+        let len = message.as_ref().unwrap().data.len();
+        // Like Linux, the number of messages is also limited by `max_bytes`, so that zero-sized
+        // messages cannot consume unlimited memory.
+        if inner.num_bytes + len > inner.max_bytes || inner.messages.len() + 1 > inner.max_bytes {
+            return_errno_with_message!(Errno::EAGAIN, "the message queue is full");
+        }
+
+        inner.messages.push_back(message.take().unwrap());
+        inner.num_bytes += len;
+        inner.last_send_pid = pid;
+        inner.stime = now_secs();
+
+        Ok(())
+    }
+
+    /// Receives a message from the queue.
+    ///
+    /// The message is selected according to `mtype`:
+    /// - If `mtype` is zero, the first message in the queue is received.
+    /// - If `mtype` is positive, the first message of type `mtype` is received, or the first
+    ///   message whose type is not `mtype` if `MSG_EXCEPT` is specified.
+    /// - If `mtype` is negative, the first message with the lowest type that is less than or
+    ///   equal to the absolute value of `mtype` is received.
+    ///
+    /// If the message is longer than `max_len`, it is truncated if `MSG_NOERROR` is specified.
+    /// Otherwise, `E2BIG` is returned and the message remains in the queue.
+    pub fn receive(
+        &self,
+        mtype: i64,
+        max_len: usize,
+        flags: MsgRcvFlags,
+        pid: Pid,
+    ) -> Result<Message> {
+        let message = if flags.contains(MsgRcvFlags::IPC_NOWAIT) {
+            self.try_receive(mtype, max_len, flags, pid)?
+        } else {
+            self.recv_wait_queue.pause_until(|| {
+                match self.try_receive(mtype, max_len, flags, pid) {
+                    Err(err) if err.error() == Errno::ENOMSG => None,
+                    result => Some(result),
+                }
+            })??
+        };
+
+        self.send_wait_queue.wake_all();
+
+        Ok(message)
+    }
+
+    fn try_receive(
+        &self,
+        mtype: i64,
+        max_len: usize,
+        flags: MsgRcvFlags,
+        pid: Pid,
+    ) -> Result<Message> {
+        self.check_removed()?;
+
+        let mut inner = self.inner.lock();
+
+        let Some(index) = find_message(&inner.messages, mtype, flags) else {
+            return_errno_with_message!(
+                Errno::ENOMSG,
+                "no message of the desired type is in the queue"
+            );
+        };
+
+        if inner.messages[index].data.len() > max_len && !flags.contains(MsgRcvFlags::MSG_NOERROR) {
+            return_errno_with_message!(Errno::E2BIG, "the message is too long");
+        }
+
+        let mut message = inner.messages.remove(index).unwrap();
+        inner.num_bytes -= message.data.len();
+        inner.last_recv_pid = pid;
+        inner.rtime = now_secs();
+
+        if message.data.len() > max_len {
+            message.data = message.data[..max_len].into();
+        }
+
+        Ok(message)
+    }
+
+    fn check_removed(&self) -> Result<()> {
+        if self.is_removed.load(Ordering::Relaxed) {
+            return_errno_with_message!(Errno::EIDRM, "the message queue is removed");
+        }
+
+        Ok(())
+    }
+
+    /// Marks the queue as removed and wakes up all the waiting senders and receivers.
+    pub(in crate::ipc) fn mark_removed(&self) {
+        self.is_removed.store(true, Ordering::Relaxed);
+        self.send_wait_queue.wake_all();
+        self.recv_wait_queue.wake_all();
+    }
+
+    /// Returns a copy of the queue permission.
+    pub fn permission(&self) -> IpcPermission {
+        self.inner.lock().permission.clone()
+    }
+
+    /// Returns a snapshot of the queue status.
+    pub fn stat(&self) -> MsgQueueStat {
+        let inner = self.inner.lock();
+
+        MsgQueueStat {
+            permission: inner.permission.clone(),
+            num_bytes: inner.num_bytes,
+            num_messages: inner.messages.len(),
+            max_bytes: inner.max_bytes,
+            last_send_pid: inner.last_send_pid,
+            last_recv_pid: inner.last_recv_pid,
+            stime: inner.stime,
+            rtime: inner.rtime,
+            ctime: inner.ctime,
+        }
+    }
+
+    /// Returns the `msqid64_ds` structure to be copied to the user space.
+    ///
+    /// The PIDs are translated into the IDs in `pid_ns`.
+    pub fn msqid_ds(&self, pid_ns: &PidNamespace) -> MsqidDs {
+        let stat = self.stat();
+        let local_pid_of = |pid| pid_ns.local_id_of(pid).unwrap_or(0);
+
+        MsqidDs {
+            msg_perm: stat.permission.to_ipc_perm(),
+            msg_stime: stat.stime,
+            msg_rtime: stat.rtime,
+            msg_ctime: stat.ctime,
+            msg_cbytes: stat.num_bytes as u64,
+            msg_qnum: stat.num_messages as u64,
+            msg_qbytes: stat.max_bytes as u64,
+            msg_lspid: local_pid_of(stat.last_send_pid).cast_signed(),
+            msg_lrpid: local_pid_of(stat.last_recv_pid).cast_signed(),
+            ..MsqidDs::default()
+        }
+    }
+
+    /// Updates the queue from the `msqid64_ds` structure copied from the user space.
+    ///
+    /// The caller must have checked the permission to perform `IPC_SET`.
+    pub(in crate::ipc) fn set_msqid_ds(&self, msqid_ds: &MsqidDs) {
+        let mut inner = self.inner.lock();
+        inner.permission.set_from_ipc_perm(&msqid_ds.msg_perm);
+        inner.max_bytes = msqid_ds.msg_qbytes as usize;
+        inner.ctime = now_secs();
+        drop(inner);
+
+        // The senders may be able to proceed if the queue is enlarged.
+        self.send_wait_queue.wake_all();
+    }
+}
+
+/// Finds the index of the message to receive.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/msg.c#L1042>
+fn find_message(messages: &VecDeque<Message>, mtype: i64, flags: MsgRcvFlags) -> Option<usize> {
+    if mtype == 0 {
+        return if messages.is_empty() { None } else { Some(0) };
+    }
+
+    if mtype > 0 {
+        let is_except = flags.contains(MsgRcvFlags::MSG_EXCEPT);
+        return messages
+            .iter()
+            .position(|message| (message.mtype == mtype) != is_except);
+    }
+
+    // `mtype` is negative. Find the first message with the lowest type.
+    let max_type = mtype.checked_neg().unwrap_or(i64::MAX);
+    let mut found: Option<(usize, i64)> = None;
+    for (index, message) in messages.iter().enumerate() {
+        if message.mtype > max_type {
+            continue;
+        }
+        if found.is_none_or(|(_, lowest_type)| message.mtype < lowest_type) {
+            found = Some((index, message.mtype));
+        }
+    }
+
+    found.map(|(index, _)| index)
+}
+
+fn now_secs() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
@@ -9,12 +9,9 @@ use core::{
 use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 use ostd::sync::{Waiter, Waker};
 
-use super::{
-    PermissionMode,
-    sem_set::{SEMVMX, SemSetInner},
-};
+use super::sem_set::{SEMVMX, SemSetInner};
 use crate::{
-    ipc::{IpcFlags, IpcId, IpcNamespace},
+    ipc::{IpcFlags, IpcId, IpcNamespace, PermissionMode},
     prelude::*,
     process::Pid,
 };
@@ -8,7 +8,7 @@ use super::sem::{
     PendingBlocker, PendingOp, Semaphore, Status, update_pending_alter, wake_const_ops,
 };
 use crate::{
-    ipc::{IpcKey, IpcPermission},
+    ipc::{IpcKey, IpcPerm, IpcPermission},
     prelude::*,
     process::{Credentials, Pid},
     time::clocks::RealTimeCoarseClock,
@@ -45,24 +45,6 @@ pub struct SemaphoreSet {
     sem_otime: AtomicU64,
 }
 
-// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/ipcbuf.h#L22>.
-#[padding_struct]
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Pod)]
-struct IpcPerm {
-    key: u32,
-    uid: u32,
-    gid: u32,
-    cuid: u32,
-    cgid: u32,
-    mode: u16,
-    _pad1: u16,
-    seq: u16,
-    _pad2: u16,
-    _unused1: u64,
-    _unused2: u64,
-}
-
 // In Linux, most popular 64-bit architectures except x86_64 adopt the same
 // layout of `semid_ds`.
 // Reference: <https://elixir.bootlin.com/linux/v6.16.9/A/ident/semid64_ds>.
@@ -248,8 +230,7 @@ impl SemaphoreSet {
             sems.push(Semaphore::new(0));
         }
 
-        let permission =
-            IpcPermission::new_sem_perm(key, credentials.euid(), credentials.egid(), mode);
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
 
         Ok(Self {
             num_sems,
@@ -265,18 +246,8 @@ impl SemaphoreSet {
     }
 
     pub fn semid_ds(&self) -> SemidDs {
-        let ipc_perm = IpcPerm {
-            key: self.permission.key().cast_unsigned(),
-            uid: self.permission.uid().into(),
-            gid: self.permission.gid().into(),
-            cuid: self.permission.cuid().into(),
-            cgid: self.permission.cguid().into(),
-            mode: self.permission.mode(),
-            ..IpcPerm::default()
-        };
-
         SemidDs {
-            sem_perm: ipc_perm,
+            sem_perm: self.permission.to_ipc_perm(),
             sem_otime: self.sem_otime.load(Ordering::Relaxed),
             sem_ctime: self.sem_ctime.load(Ordering::Relaxed),
             sem_nsems: self.num_sems as u64,
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V shared memory.
+
+use core::fmt::Write;
+
+use align_ext::AlignExt;
+
+use self::shm_segment::ShmSegment;
+use crate::{
+    ipc::IpcNamespace,
+    prelude::*,
+    process::{Pid, PidNamespace},
+    vm::vmar::{VmMapping, Vmar},
+};
+
+pub mod shm_segment;
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum number of shared memory segments.
+pub const SHMMNI: usize = 4096;
+/// Minimum size of a shared memory segment in bytes.
+pub const SHMMIN: usize = 1;
+/// Maximum size of a shared memory segment in bytes.
+pub const SHMMAX: usize = usize::MAX - (1 << 24);
+
+/// The mode bit indicating that the segment will be destroyed on its last detach.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/shm.h#L20>
+const SHM_DEST: u16 = 0o1000;
+
+bitflags! {
+    /// Flags for `shmat`.
+    pub struct ShmFlags: u32 {
+        /// Attach the segment for read-only access.
+        const SHM_RDONLY = 0o10000;
+        /// Round the attach address down to a multiple of `SHMLBA`.
+        const SHM_RND = 0o20000;
+        /// Replace the existing mappings in the address range.
+        const SHM_REMAP = 0o40000;
+        /// Allow the contents of the segment to be executed.
+        const SHM_EXEC = 0o100000;
+    }
+}
+
+/// Detaches the shared memory segment attached at `addr` from `vmar`.
+///
+/// All the mappings created by attaching the segment at `addr` are removed, even if they have
+/// been split since then.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/shm.c#L1752>
+pub fn detach(vmar: &Vmar, addr: Vaddr) -> Result<()> {
+    if !addr.is_multiple_of(PAGE_SIZE) {
+        return_errno_with_message!(Errno::EINVAL, "the address is not page-aligned");
+    }
+
+    // Returns the segment if the mapping is a part of the segment attached at `addr`.
+    let segment_attached_at_addr = |vm_mapping: &VmMapping| {
+        let (_, offset) = vm_mapping.vmo_and_offset()?;
+        if vm_mapping.map_to_addr().checked_sub(addr) != Some(offset) {
+            return None;
+        }
+
+        let observer = vm_mapping.observer()?;
+        let segment = (&**observer as &dyn Any).downcast_ref::<ShmSegment>()?;
+        Some((observer.clone(), segment.size()))
+    };
+
+    let Some((observer, size)) = vmar
+        .query(addr..addr.saturating_add(PAGE_SIZE))
+        .iter()
+        .find_map(segment_attached_at_addr)
+    else {
+        return_errno_with_message!(Errno::EINVAL, "no segment is attached at the address");
+    };
+
+    let end = addr.saturating_add(size.align_up(PAGE_SIZE));
+    let ranges_to_unmap = vmar
+        .query(addr..end)
+        .iter()
+        .filter(|vm_mapping| {
+            vm_mapping.map_end() <= end
+                && segment_attached_at_addr(vm_mapping)
+                    .is_some_and(|(other, _)| Arc::ptr_eq(&other, &observer))
+        })
+        .map(|vm_mapping| vm_mapping.map_to_addr()..vm_mapping.map_end())
+        .collect::<Vec<_>>();
+
+    for range in ranges_to_unmap {
+        vmar.remove_mapping(range)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the contents of `/proc/sysvipc/shm` for the shared memory segments in `ipc_ns`.
+///
+/// The PIDs are displayed as seen from `pid_ns`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/shm.c#L1852>
+pub fn sysvipc_proc_show(ipc_ns: &IpcNamespace, pid_ns: &PidNamespace) -> String {
+    let mut output = String::from(
+        "       key      shmid perms                  size  cpid  lpid nattch   uid   gid  cuid  cgid      atime      dtime      ctime                   rss                  swap\n",
+    );
+
+    let local_pid_of = |pid: Pid| pid_ns.local_id_of(pid).unwrap_or(0);
+
+    ipc_ns.for_each_shm_segment(|shmid, segment| {
+        let stat = segment.stat();
+        let permission = &stat.permission;
+        let mode = if stat.is_removed {
+            permission.mode() | SHM_DEST
+        } else {
+            permission.mode()
+        };
+        let _ = writeln!(
+            output,
+            "{:>10} {:>10}  {:>4o} {:>21} {:>5} {:>5}  {:>5} {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10} {:>21} {:>21}",
+            permission.key(),
+            shmid.get(),
+            mode,
+            stat.size,
+            local_pid_of(stat.creator_pid),
+            local_pid_of(stat.last_pid),
+            stat.num_attaches,
+            u32::from(permission.uid()),
+            u32::from(permission.gid()),
+            u32::from(permission.cuid()),
+            u32::from(permission.cguid()),
+            stat.atime,
+            stat.dtime,
+            stat.ctime,
+            // TODO: Report the resident and swapped sizes of the segment.
+            0,
+            0,
+        );
+    });
+
+    output
+}
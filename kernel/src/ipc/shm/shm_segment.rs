@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+use aster_rights::ReadOp;
+
+use super::{SHM_DEST, ShmFlags};
+use crate::{
+    ipc::{IpcId, IpcKey, IpcNamespace, IpcPerm, IpcPermission},
+    prelude::*,
+    process::{Credentials, Pid, PidNamespace, Process},
+    time::clocks::RealTimeCoarseClock,
+    vm::{
+        page_cache::{Vmo, VmoOptions},
+        perms::VmPerms,
+        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, Vmar, VmarMapOffset, VmoMappingObserver},
+    },
+};
+
+/// A System V shared memory segment.
+///
+/// The segment tracks the mappings of its VMO as attaches. A segment removed by `IPC_RMID` is
+/// destroyed when it is no longer attached.
+#[derive(Debug)]
+pub struct ShmSegment {
+    /// The memory of the segment.
+    vmo: Arc<Vmo>,
+    /// The size of the segment in bytes, as requested by `shmget`.
+    size: usize,
+    inner: Mutex<ShmSegmentInner>,
+    /// The IPC namespace that the segment belongs to.
+    ipc_ns: Weak<IpcNamespace>,
+    /// The ID of the segment in the IPC namespace.
+    shmid: IpcId,
+}
+
+#[derive(Debug)]
+struct ShmSegmentInner {
+    /// Segment permission
+    permission: IpcPermission,
+    /// Number of current attaches
+    num_attaches: usize,
+    /// Whether the segment has been removed by `IPC_RMID`
+    is_removed: bool,
+    /// PID of the creator
+    creator_pid: Pid,
+    /// PID of the last `shmat` or `shmdt`
+    last_pid: Pid,
+    /// Last `shmat` time
+    atime: u64,
+    /// Last `shmdt` time
+    dtime: u64,
+    /// Creation time or last modification via `shmctl`
+    ctime: u64,
+}
+
+/// A snapshot of the status of a [`ShmSegment`].
+pub struct ShmSegmentStat {
+    pub permission: IpcPermission,
+    pub size: usize,
+    pub num_attaches: usize,
+    pub is_removed: bool,
+    pub creator_pid: Pid,
+    pub last_pid: Pid,
+    pub atime: u64,
+    pub dtime: u64,
+    pub ctime: u64,
+}
+
+// Both x86_64 and the generic 64-bit architectures adopt the same layout of `shmid64_ds`.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/shmbuf.h#L27>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct ShmidDs {
+    shm_perm: IpcPerm,
+    shm_segsz: u64,
+    shm_atime: u64,
+    shm_dtime: u64,
+    shm_ctime: u64,
+    shm_cpid: i32,
+    shm_lpid: i32,
+    shm_nattch: u64,
+    _unused4: u64,
+    _unused5: u64,
+}
+
+impl ShmSegment {
+    pub(in crate::ipc) fn new(
+        key: IpcKey,
+        size: usize,
+        mode: u16,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+        ipc_ns: Weak<IpcNamespace>,
+        shmid: IpcId,
+    ) -> Result<Self> {
+        let vmo = VmoOptions::new(size).alloc()?;
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Ok(Self {
+            vmo,
+            size,
+            inner: Mutex::new(ShmSegmentInner {
+                permission,
+                num_attaches: 0,
+                is_removed: false,
+                creator_pid: pid,
+                last_pid: 0,
+                atime: 0,
+                dtime: 0,
+                ctime: now_secs(),
+            }),
+            ipc_ns,
+            shmid,
+        })
+    }
+
+    /// Returns the size of the segment in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns a copy of the segment permission.
+    pub fn permission(&self) -> IpcPermission {
+        self.inner.lock().permission.clone()
+    }
+
+    /// Attaches the segment to `vmar`.
+    ///
+    /// If `addr` is `None`, the segment is attached at an address chosen automatically. Otherwise,
+    /// the segment is attached at `addr`, which must be page-aligned.
+    ///
+    /// On success, the address where the segment is attached is returned.
+    pub fn attach(
+        self: &Arc<Self>,
+        vmar: &Vmar,
+        addr: Option<Vaddr>,
+        flags: ShmFlags,
+    ) -> Result<Vaddr> {
+        let map_size = self.size.align_up(PAGE_SIZE);
+
+        let mut perms = VmPerms::READ;
+        let mut may_perms = VmPerms::ALL_MAY_PERMS;
+        if flags.contains(ShmFlags::SHM_RDONLY) {
+            may_perms.remove(VmPerms::MAY_WRITE);
+        } else {
+            perms |= VmPerms::WRITE;
+        }
+        if flags.contains(ShmFlags::SHM_EXEC) {
+            perms |= VmPerms::EXEC;
+        }
+
+        let offset = match addr {
+            None => VmarMapOffset::Any,
+            Some(addr) => {
+                if addr < VMAR_LOWEST_ADDR
+                    || VMAR_CAP_ADDR
+                        .checked_sub(addr)
+                        .is_none_or(|gap| gap < map_size)
+                {
+                    return_errno_with_message!(Errno::EINVAL, "the address is out of range");
+                }
+
+                if flags.contains(ShmFlags::SHM_REMAP) {
+                    VmarMapOffset::FixedReplace(addr)
+                } else {
+                    if vmar.query(addr..addr + map_size).iter().next().is_some() {
+                        return_errno_with_message!(
+                            Errno::EINVAL,
+                            "the address range overlaps with existing mappings"
+                        );
+                    }
+                    VmarMapOffset::FixedNoReplace(addr)
+                }
+            }
+        };
+
+        vmar.new_map(map_size, perms)?
+            .may_perms(may_perms)
+            .vmo(self.vmo.clone())
+            .observer(self.clone())
+            .is_shared(true)
+            .offset(offset)
+            .build()
+    }
+
+    /// Marks the segment as removed.
+    ///
+    /// The segment will be destroyed on its last detach.
+    pub(in crate::ipc) fn mark_removed(&self) {
+        self.inner.lock().is_removed = true;
+    }
+
+    /// Returns whether the segment has been removed and is no longer attached.
+    pub(in crate::ipc) fn is_removed_and_detached(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.is_removed && inner.num_attaches == 0
+    }
+
+    /// Returns a snapshot of the segment status.
+    pub fn stat(&self) -> ShmSegmentStat {
+        let inner = self.inner.lock();
+
+        ShmSegmentStat {
+            permission: inner.permission.clone(),
+            size: self.size,
+            num_attaches: inner.num_attaches,
+            is_removed: inner.is_removed,
+            creator_pid: inner.creator_pid,
+            last_pid: inner.last_pid,
+            atime: inner.atime,
+            dtime: inner.dtime,
+            ctime: inner.ctime,
+        }
+    }
+
+    /// Returns the `shmid64_ds` structure to be copied to the user space.
+    ///
+    /// The PIDs are translated into the IDs in `pid_ns`.
+    pub fn shmid_ds(&self, pid_ns: &PidNamespace) -> ShmidDs {
+        let stat = self.stat();
+        let local_pid_of = |pid| pid_ns.local_id_of(pid).unwrap_or(0);
+
+        let mut shm_perm = stat.permission.to_ipc_perm();
+        if stat.is_removed {
+            shm_perm.mode |= SHM_DEST;
+        }
+
+        ShmidDs {
+            shm_perm,
+            shm_segsz: stat.size as u64,
+            shm_atime: stat.atime,
+            shm_dtime: stat.dtime,
+            shm_ctime: stat.ctime,
+            shm_cpid: local_pid_of(stat.creator_pid).cast_signed(),
+            shm_lpid: local_pid_of(stat.last_pid).cast_signed(),
+            shm_nattch: stat.num_attaches as u64,
+            ..ShmidDs::default()
+        }
+    }
+
+    /// Updates the segment from the `shmid64_ds` structure copied from the user space.
+    ///
+    /// The caller must have checked the permission to perform `IPC_SET`.
+    pub(in crate::ipc) fn set_shmid_ds(&self, shmid_ds: &ShmidDs) {
+        let mut inner = self.inner.lock();
+        inner.permission.set_from_ipc_perm(&shmid_ds.shm_perm);
+        inner.ctime = now_secs();
+    }
+}
+
+impl VmoMappingObserver for ShmSegment {
+    fn on_map(&self) {
+        let mut inner = self.inner.lock();
+        inner.num_attaches += 1;
+        inner.atime = now_secs();
+        if let Some(process) = Process::current() {
+            inner.last_pid = process.pid();
+        }
+    }
+
+    fn on_unmap(&self) {
+        let mut inner = self.inner.lock();
+        inner.num_attaches -= 1;
+        inner.dtime = now_secs();
+        if let Some(process) = Process::current() {
+            inner.last_pid = process.pid();
+        }
+        let should_destroy = inner.is_removed && inner.num_attaches == 0;
+        drop(inner);
+
+        if should_destroy && let Some(ipc_ns) = self.ipc_ns.upgrade() {
+            ipc_ns.destroy_shm_segment_if_unused(self.shmid);
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
@@ -1,64 +1,51 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::slice::Iter;
-
 use aster_bigtcp::{
     device::WithDevice,
     iface::{InterfaceFlags, InterfaceType},
 };
 use aster_softirq::BottomHalfDisabled;
-use spin::Once;
 
 use super::{Iface, poll::poll_ifaces};
 use crate::{
-    net::iface::{broadcast, sched::PollScheduler},
+    net::{iface::sched::PollScheduler, net_ns::NetNamespace},
     prelude::*,
 };
 
-static IFACES: Once<Vec<Arc<Iface>>> = Once::new();
-
-pub fn loopback_iface() -> &'static Arc<Iface> {
-    &IFACES.get().unwrap()[0]
-}
-
-pub fn virtio_iface() -> Option<&'static Arc<Iface>> {
-    IFACES.get().unwrap().get(1)
-}
-
-pub fn iter_all_ifaces() -> Iter<'static, Arc<Iface>> {
-    IFACES.get().unwrap().iter()
-}
-
 // TODO: Support multiple network devices and avoid the hardcoded device name.
 const VIRTIO_DEVICE_NAME: &str = aster_virtio::device::network::DEVICE_NAME;
 
 pub fn init() {
-    IFACES.call_once(|| {
-        let mut ifaces = Vec::with_capacity(2);
-
-        // Initialize loopback before virtio
-        // to ensure the loopback interface index is ahead of virtio.
-        ifaces.push(new_loopback());
+    let init_net_ns = NetNamespace::get_init_singleton();
 
-        if let Some(iface_virtio) = new_virtio() {
-            ifaces.push(iface_virtio);
-        }
-
-        ifaces
-    });
-
-    if let Some(iface_virtio) = virtio_iface() {
+    // The virtio interface, if any, always follows the loopback interface.
+    // See `new_init_ifaces()` for details.
+    if let Some(iface_virtio) = init_net_ns.iter_ifaces().nth(1) {
         let callback = || iface_virtio.poll();
         aster_network::register_recv_callback(VIRTIO_DEVICE_NAME, callback);
         aster_network::register_send_callback(VIRTIO_DEVICE_NAME, callback);
     }
 
-    broadcast::init();
+    poll_ifaces(init_net_ns);
+}
+
+/// Creates the interfaces of the initial network namespace.
+pub(in crate::net) fn new_init_ifaces() -> Vec<Arc<Iface>> {
+    let mut ifaces = Vec::with_capacity(2);
+
+    // Initialize loopback before virtio
+    // to ensure the loopback interface index is ahead of virtio.
+    ifaces.push(new_loopback());
+
+    if let Some(iface_virtio) = new_virtio() {
+        ifaces.push(iface_virtio);
+    }
 
-    poll_ifaces();
+    ifaces
 }
 
-fn new_loopback() -> Arc<Iface> {
+/// Creates a new loopback interface.
+pub(in crate::net) fn new_loopback() -> Arc<Iface> {
     use aster_bigtcp::{
         device::{Loopback, Medium},
         iface::IpIface,
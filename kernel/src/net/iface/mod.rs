@@ -6,9 +6,10 @@ mod init;
 mod poll;
 mod sched;
 
-pub use broadcast::is_broadcast_endpoint;
-pub use init::{init, iter_all_ifaces, loopback_iface, virtio_iface};
-pub(super) use poll::init_in_first_kthread;
+pub(super) use broadcast::collect_broadcast_addrs;
+pub use init::init;
+pub(super) use init::{new_init_ifaces, new_loopback};
+pub(super) use poll::{init_in_first_kthread, spawn_background_poll_thread};
 
 pub type Iface = dyn aster_bigtcp::iface::Iface<ext::BigtcpExt>;
 pub type BoundTcpPort = aster_bigtcp::iface::BoundTcpPort<ext::BigtcpExt>;
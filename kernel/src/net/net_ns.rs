@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Defines the network namespace abstraction.
+//!
+//! A network namespace owns a private list of network interfaces. Since each interface keeps its
+//! own socket table (see `aster_bigtcp::socket_table`), sockets created in different network
+//! namespaces never share TCP/UDP ports, even if they bind to the same address.
+//!
+//! Netlink route sockets are also bound per namespace, so they can only talk to the kernel socket
+//! of their own namespace.
+//!
+//! The initial network namespace owns the loopback interface and the virtio-net interface (if
+//! any). A newly created network namespace starts with only a private loopback interface.
+
+use core::net::Ipv4Addr;
+
+use aster_bigtcp::{
+    iface::InterfaceType,
+    wire::{IpAddress, IpEndpoint},
+};
+use spin::Once;
+
+use super::{
+    iface::{self, Iface},
+    socket::netlink::NetlinkRouteKernelSocket,
+};
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread},
+    security::lsm::hooks as lsm_hooks,
+};
+
+/// The network namespace.
+pub struct NetNamespace {
+    /// The network interfaces in this namespace.
+    ///
+    /// The first interface is always the loopback interface.
+    ifaces: Vec<Arc<Iface>>,
+    /// All known broadcast addresses in this namespace.
+    // FIXME: This information should be maintained in the routing table,
+    // since a broadcast address might change if an interface's IP
+    // or netmask changes, or if an interface is added/removed.
+    broadcast_addrs: BTreeSet<Ipv4Addr>,
+    /// The netlink route kernel socket.
+    netlink_route_kernel: NetlinkRouteKernelSocket,
+    /// Owner user namespace.
+    owner: Arc<UserNamespace>,
+    /// Stashed dentry for nsfs.
+    stashed_dentry: StashedDentry,
+}
+
+impl NetNamespace {
+    /// Returns a reference to the singleton initial network namespace.
+    pub fn get_init_singleton() -> &'static Arc<NetNamespace> {
+        static INIT: Once<Arc<NetNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let owner = UserNamespace::get_init_singleton().clone();
+            Self::new(iface::new_init_ifaces(), owner)
+        })
+    }
+
+    fn new(ifaces: Vec<Arc<Iface>>, owner: Arc<UserNamespace>) -> Arc<Self> {
+        let broadcast_addrs = iface::collect_broadcast_addrs(&ifaces);
+        let stashed_dentry = StashedDentry::new();
+
+        Arc::new(Self {
+            ifaces,
+            broadcast_addrs,
+            netlink_route_kernel: NetlinkRouteKernelSocket::new(),
+            owner,
+            stashed_dentry,
+        })
+    }
+
+    /// Clones a new network namespace from `self`.
+    ///
+    /// The new namespace starts with only a private loopback interface.
+    pub fn new_clone(
+        &self,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            owner.as_ref(),
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))?;
+
+        let new_ns = Self::new(vec![iface::new_loopback()], owner);
+        for iface in new_ns.ifaces.iter() {
+            iface::spawn_background_poll_thread(iface.clone());
+        }
+
+        Ok(new_ns)
+    }
+
+    /// Creates a network namespace with only a loopback interface for kernel mode testing.
+    ///
+    /// Unlike [`Self::get_init_singleton`], this method does not require any network devices.
+    #[cfg(ktest)]
+    pub(in crate::net) fn new_for_ktest() -> Arc<Self> {
+        let owner = UserNamespace::get_init_singleton().clone();
+        Self::new(vec![iface::new_loopback()], owner)
+    }
+
+    /// Returns the loopback interface of this namespace.
+    pub fn loopback_iface(&self) -> &Arc<Iface> {
+        &self.ifaces[0]
+    }
+
+    /// Returns an iterator over all the interfaces in this namespace.
+    pub fn iter_ifaces(&self) -> core::slice::Iter<'_, Arc<Iface>> {
+        self.ifaces.iter()
+    }
+
+    /// Returns the interface whose IP address is `ip_addr`.
+    pub(super) fn iface_by_addr(&self, ip_addr: &IpAddress) -> Option<&Arc<Iface>> {
+        match *ip_addr {
+            IpAddress::Ipv4(ipv4_addr) => self
+                .ifaces
+                .iter()
+                .find(|iface| iface.ipv4_addr().is_some_and(|addr| addr == ipv4_addr)),
+            IpAddress::Ipv6(ipv6_addr) => self
+                .ifaces
+                .iter()
+                .find(|iface| iface.ipv6_addr().is_some_and(|addr| addr == ipv6_addr)),
+        }
+    }
+
+    /// Returns the default interface for IPv4 traffic to remote hosts.
+    //
+    // FIXME: Instead of hardcoding the rules here, we should choose the
+    // default interface according to the routing table.
+    pub(super) fn default_ipv4_iface(&self) -> &Arc<Iface> {
+        self.ifaces
+            .iter()
+            .find(|iface| iface.type_() != InterfaceType::LOOPBACK)
+            .unwrap_or_else(|| self.loopback_iface())
+    }
+
+    /// Returns the default interface for IPv6 traffic to remote hosts.
+    ///
+    /// Non-loopback interfaces with an IPv6 address are preferred.
+    pub(super) fn default_ipv6_iface(&self) -> &Arc<Iface> {
+        self.ifaces
+            .iter()
+            .find(|iface| iface.type_() != InterfaceType::LOOPBACK && iface.ipv6_addr().is_some())
+            .unwrap_or_else(|| self.loopback_iface())
+    }
+
+    /// Returns the netlink route kernel socket of this namespace.
+    pub(super) fn netlink_route_kernel(&self) -> &NetlinkRouteKernelSocket {
+        &self.netlink_route_kernel
+    }
+
+    /// Determines if a given IP endpoint's address is a known broadcast address.
+    ///
+    /// IPv6 has no broadcast; multicast (`ff00::/8`) handles fan-out instead and
+    /// is intentionally not covered by this method.
+    pub fn is_broadcast_endpoint(&self, endpoint: &IpEndpoint) -> bool {
+        let IpAddress::Ipv4(ipv4_addr) = &endpoint.addr else {
+            return false;
+        };
+        self.broadcast_addrs.contains(ipv4_addr)
+    }
+}
+
+impl Drop for NetNamespace {
+    fn drop(&mut self) {
+        // No process or socket can use the interfaces anymore,
+        // so the background polling threads can stop now.
+        for iface in self.ifaces.iter() {
+            iface.sched_poll().stop();
+        }
+    }
+}
+
+impl NsCommonOps for NetNamespace {
+    const TYPE: NsType = NsType::Net;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "a network namespace does not have a parent namespace"
+        );
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
@@ -7,76 +7,35 @@ use aster_bigtcp::{
 };
 
 use crate::{
-    net::{
-        iface::{Iface, iter_all_ifaces, loopback_iface, virtio_iface},
-        socket::util::check_port_privilege,
-    },
+    net::{iface::Iface, net_ns::NetNamespace, socket::util::check_port_privilege},
     prelude::*,
 };
 
-fn get_iface_to_bind(ip_addr: &IpAddress) -> Option<Arc<Iface>> {
-    match *ip_addr {
-        IpAddress::Ipv4(ipv4_addr) => iter_all_ifaces()
-            .find(|iface| iface.ipv4_addr().is_some_and(|addr| addr == ipv4_addr))
-            .map(Clone::clone),
-        IpAddress::Ipv6(ipv6_addr) => iter_all_ifaces()
-            .find(|iface| iface.ipv6_addr().is_some_and(|addr| addr == ipv6_addr))
-            .map(Clone::clone),
-    }
-}
-
 /// Get a suitable iface to deal with sendto/connect request if the socket is not bound to an iface.
 /// If the remote address is the same as that of some iface, we will use the iface.
 /// Otherwise, we will use a default interface.
-fn get_ephemeral_iface(remote_ip_addr: &IpAddress) -> Arc<Iface> {
-    match remote_ip_addr {
-        IpAddress::Ipv4(remote_ipv4_addr) => {
-            if let Some(iface) = iter_all_ifaces().find(|iface| {
-                iface
-                    .ipv4_addr()
-                    .is_some_and(|addr| addr == *remote_ipv4_addr)
-            }) {
-                return iface.clone();
-            }
-
-            // FIXME: Instead of hardcoding the rules here, we should choose the
-            // default interface according to the routing table.
-            if let Some(virtio_iface) = virtio_iface() {
-                virtio_iface.clone()
-            } else {
-                loopback_iface().clone()
-            }
-        }
-        IpAddress::Ipv6(remote_ipv6_addr) => {
-            if let Some(iface) = iter_all_ifaces().find(|iface| {
-                iface
-                    .ipv6_addr()
-                    .is_some_and(|addr| addr == *remote_ipv6_addr)
-            }) {
-                return iface.clone();
-            }
-
-            // Fall back to an interface with an IPv6 address.
-            // Prefer virtio over loopback for external traffic.
-            if let Some(virtio_iface) = virtio_iface()
-                && virtio_iface.ipv6_addr().is_some()
-            {
-                return virtio_iface.clone();
-            }
+fn get_ephemeral_iface<'a>(net_ns: &'a NetNamespace, remote_ip_addr: &IpAddress) -> &'a Arc<Iface> {
+    if let Some(iface) = net_ns.iface_by_addr(remote_ip_addr) {
+        return iface;
+    }
 
-            loopback_iface().clone()
-        }
+    match remote_ip_addr {
+        IpAddress::Ipv4(_) => net_ns.default_ipv4_iface(),
+        // Fall back to an interface with an IPv6 address.
+        // Prefer other interfaces over loopback for external traffic.
+        IpAddress::Ipv6(_) => net_ns.default_ipv6_iface(),
     }
 }
 
 pub(super) fn resolve_bind_iface_and_config(
+    net_ns: &NetNamespace,
     endpoint: &IpEndpoint,
     can_reuse: bool,
 ) -> Result<(Arc<Iface>, BindPortConfig)> {
     check_port_privilege(endpoint.port)?;
 
-    let iface = match get_iface_to_bind(&endpoint.addr) {
-        Some(iface) => iface,
+    let iface = match net_ns.iface_by_addr(&endpoint.addr) {
+        Some(iface) => iface.clone(),
         None => {
             return_errno_with_message!(
                 Errno::EADDRNOTAVAIL,
@@ -103,8 +62,11 @@ impl From<BindError> for Error {
     }
 }
 
-pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> Option<IpEndpoint> {
-    let iface = get_ephemeral_iface(&remote_endpoint.addr);
+pub(super) fn get_ephemeral_endpoint(
+    net_ns: &NetNamespace,
+    remote_endpoint: &IpEndpoint,
+) -> Option<IpEndpoint> {
+    let iface = get_ephemeral_iface(net_ns, &remote_endpoint.addr);
     match remote_endpoint.addr {
         IpAddress::Ipv4(_) => {
             let ip_addr = iface.ipv4_addr()?;
@@ -11,7 +11,7 @@ use crate::{
     events::IoEvents,
     fs::{pseudofs::SockFs, vfs::path::Path},
     net::{
-        iface::is_broadcast_endpoint,
+        net_ns::NetNamespace,
         socket::{
             Socket,
             ip::options::{IpOptionSet, SetIpLevelOption},
@@ -41,6 +41,7 @@ pub struct DatagramSocket {
     is_nonblocking: AtomicBool,
     pollee: Pollee,
     pseudo_path: Path,
+    net_ns: Arc<NetNamespace>,
 }
 
 #[derive(Clone, Debug)]
@@ -59,14 +60,15 @@ impl OptionSet {
 }
 
 impl DatagramSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        let unbound_datagram = UnboundDatagram::new();
+    pub fn new(is_nonblocking: bool, net_ns: Arc<NetNamespace>) -> Arc<Self> {
+        let unbound_datagram = UnboundDatagram::new(net_ns.clone());
         Arc::new(Self {
             inner: RwMutex::new(Inner::Unbound(unbound_datagram)),
             options: RwLock::new(OptionSet::new()),
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
             pseudo_path: SockFs::new_path(),
+            net_ns,
         })
     }
 
@@ -149,7 +151,7 @@ impl Socket for DatagramSocket {
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
         let endpoint = socket_addr.try_into()?;
         let can_broadcast = self.options.read().socket.broadcast();
-        if !can_broadcast && is_broadcast_endpoint(&endpoint) {
+        if !can_broadcast && self.net_ns.is_broadcast_endpoint(&endpoint) {
             return_errno_with_message!(
                 Errno::EACCES,
                 "connecting to a broadcast address without SO_BROADCAST is not allowed"
@@ -201,7 +203,7 @@ impl Socket for DatagramSocket {
 
         if let Some(endpoint) = endpoint.as_ref() {
             let can_broadcast = self.options.read().socket.broadcast();
-            if !can_broadcast && is_broadcast_endpoint(endpoint) {
+            if !can_broadcast && self.net_ns.is_broadcast_endpoint(endpoint) {
                 return_errno_with_message!(
                     Errno::EACCES,
                     "sending to a broadcast address without SO_BROADCAST is not allowed"
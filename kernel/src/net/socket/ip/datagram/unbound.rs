@@ -7,6 +7,7 @@ use crate::{
     events::IoEvents,
     net::{
         iface::BoundUdpPort,
+        net_ns::NetNamespace,
         socket::{
             ip::common::{get_ephemeral_endpoint, resolve_bind_iface_and_config},
             util::datagram_common,
@@ -17,12 +18,12 @@ use crate::{
 };
 
 pub(super) struct UnboundDatagram {
-    _private: (),
+    net_ns: Arc<NetNamespace>,
 }
 
 impl UnboundDatagram {
-    pub(super) fn new() -> Self {
-        Self { _private: () }
+    pub(super) fn new(net_ns: Arc<NetNamespace>) -> Self {
+        Self { net_ns }
     }
 }
 
@@ -42,7 +43,7 @@ impl datagram_common::Unbound for UnboundDatagram {
         pollee: &Pollee,
         options: BindOptions,
     ) -> Result<Self::Bound> {
-        let bound_port = bind_port(endpoint, options.can_reuse)?;
+        let bound_port = bind_port(&self.net_ns, endpoint, options.can_reuse)?;
 
         let bound_socket =
             match UdpSocket::new_bind(bound_port, DatagramObserver::new(pollee.clone())) {
@@ -60,7 +61,7 @@ impl datagram_common::Unbound for UnboundDatagram {
         remote_endpoint: &Self::Endpoint,
         pollee: &Pollee,
     ) -> Result<Self::Bound> {
-        let endpoint = get_ephemeral_endpoint(remote_endpoint).ok_or_else(|| {
+        let endpoint = get_ephemeral_endpoint(&self.net_ns, remote_endpoint).ok_or_else(|| {
             Error::with_message(
                 Errno::EADDRNOTAVAIL,
                 "no interface has an address for the specified family",
@@ -74,7 +75,11 @@ impl datagram_common::Unbound for UnboundDatagram {
     }
 }
 
-fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundUdpPort> {
-    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse)?;
+fn bind_port(
+    net_ns: &NetNamespace,
+    endpoint: &IpEndpoint,
+    can_reuse: bool,
+) -> Result<BoundUdpPort> {
+    let (iface, config) = resolve_bind_iface_and_config(net_ns, endpoint, can_reuse)?;
     Ok(iface.bind_udp(config)?)
 }
@@ -12,6 +12,7 @@ use crate::{
     events::IoEvents,
     net::{
         iface::BoundTcpPort,
+        net_ns::NetNamespace,
         socket::{
             ip::{
                 addr::IpAddressFamily,
@@ -85,7 +86,12 @@ impl InitStream {
         self.family
     }
 
-    pub(super) fn bind(&mut self, endpoint: &IpEndpoint, can_reuse: bool) -> Result<()> {
+    pub(super) fn bind(
+        &mut self,
+        net_ns: &NetNamespace,
+        endpoint: &IpEndpoint,
+        can_reuse: bool,
+    ) -> Result<()> {
         if self.bound_port.is_some() {
             return_errno_with_message!(Errno::EINVAL, "the socket is already bound to an address");
         }
@@ -99,7 +105,7 @@ impl InitStream {
             );
         }
 
-        self.bound_port = Some(bind_port(endpoint, can_reuse)?);
+        self.bound_port = Some(bind_port(net_ns, endpoint, can_reuse)?);
 
         Ok(())
     }
@@ -110,6 +116,7 @@ impl InitStream {
 
     pub(super) fn connect(
         self,
+        net_ns: &NetNamespace,
         remote_endpoint: &IpEndpoint,
         option: &RawTcpOption,
         can_reuse: bool,
@@ -135,7 +142,7 @@ impl InitStream {
         let bound_port = if let Some(bound_port) = self.bound_port {
             bound_port
         } else {
-            let endpoint = match get_ephemeral_endpoint(remote_endpoint) {
+            let endpoint = match get_ephemeral_endpoint(net_ns, remote_endpoint) {
                 Some(ep) => ep,
                 None => {
                     return Err((
@@ -147,7 +154,7 @@ impl InitStream {
                     ));
                 }
             };
-            match bind_port(&endpoint, can_reuse) {
+            match bind_port(net_ns, &endpoint, can_reuse) {
                 Ok(bound_port) => bound_port,
                 Err(err) => return Err((err, self)),
             }
@@ -273,7 +280,11 @@ impl InitStream {
     }
 }
 
-fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundTcpPort> {
-    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse)?;
+fn bind_port(
+    net_ns: &NetNamespace,
+    endpoint: &IpEndpoint,
+    can_reuse: bool,
+) -> Result<BoundTcpPort> {
+    let (iface, config) = resolve_bind_iface_and_config(net_ns, endpoint, can_reuse)?;
     Ok(iface.bind_tcp(config)?)
 }
@@ -29,6 +29,7 @@ use crate::{
     fs::{file::FileLike, pseudofs::SockFs, vfs::path::Path},
     net::{
         iface::Iface,
+        net_ns::NetNamespace,
         socket::{
             Socket,
             options::{
@@ -43,8 +44,12 @@ use crate::{
         },
     },
     prelude::*,
-    process::signal::{PollHandle, Pollable, Pollee},
-    util::{MultiRead, MultiWrite},
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable, Pollee},
+    },
+    security::lsm::hooks as lsm_hooks,
+    util::{MultiRead, MultiWrite, net::SockType},
 };
 
 mod connected;
@@ -65,6 +70,7 @@ pub struct StreamSocket {
     is_nonblocking: AtomicBool,
     pollee: Pollee,
     pseudo_path: Path,
+    net_ns: Arc<NetNamespace>,
 }
 
 enum State {
@@ -104,7 +110,11 @@ impl OptionSet {
 }
 
 impl StreamSocket {
-    pub fn new(is_nonblocking: bool, family: IpAddressFamily) -> Arc<Self> {
+    pub fn new(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
         let init_stream = InitStream::new(family);
         Arc::new(Self {
             state: RwLock::new(Takeable::new(State::Init(init_stream))),
@@ -112,10 +122,15 @@ impl StreamSocket {
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
             pseudo_path: SockFs::new_path(),
+            net_ns,
         })
     }
 
-    fn new_accepted(connected_stream: ConnectedStream, listener_options: &OptionSet) -> Arc<Self> {
+    fn new_accepted(
+        connected_stream: ConnectedStream,
+        listener_options: &OptionSet,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
         let options = connected_stream.raw_with(|raw_tcp_socket| {
             let mut options = OptionSet::new();
 
@@ -156,6 +171,7 @@ impl StreamSocket {
             is_nonblocking: AtomicBool::new(false),
             pollee,
             pseudo_path: SockFs::new_path(),
+            net_ns,
         })
     }
 
@@ -256,6 +272,7 @@ impl StreamSocket {
             }
 
             let (target_state, iface_to_poll) = match init_stream.connect(
+                &self.net_ns,
                 remote_endpoint,
                 &raw_option,
                 options.socket.reuse_addr(),
@@ -322,7 +339,8 @@ impl StreamSocket {
         let accepted = listen_stream.try_accept().map(|connected_stream| {
             let remote_endpoint = connected_stream.remote_endpoint();
             let listener_options = self.options.read();
-            let accepted_socket = Self::new_accepted(connected_stream, &listener_options);
+            let accepted_socket =
+                Self::new_accepted(connected_stream, &listener_options, self.net_ns.clone());
             (accepted_socket as _, remote_endpoint.into())
         });
         let iface_to_poll = listen_stream.iface().clone();
@@ -448,6 +466,11 @@ impl SocketPrivate for StreamSocket {
 
 impl Socket for StreamSocket {
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        lsm_hooks::on_socket_bind(lsm_hooks::SocketAddrContext::new(
+            current_thread!().as_posix_thread().unwrap(),
+            SockType::SOCK_STREAM,
+            &socket_addr,
+        ))?;
         let endpoint = socket_addr.try_into()?;
 
         let mut state = self.write_updated_state();
@@ -456,10 +479,15 @@ impl Socket for StreamSocket {
         };
 
         let can_reuse = self.options.read().socket.reuse_addr();
-        init_stream.bind(&endpoint, can_reuse)
+        init_stream.bind(&self.net_ns, &endpoint, can_reuse)
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        lsm_hooks::on_socket_connect(lsm_hooks::SocketAddrContext::new(
+            current_thread!().as_posix_thread().unwrap(),
+            SockType::SOCK_STREAM,
+            &socket_addr,
+        ))?;
         let remote_endpoint = socket_addr.try_into()?;
 
         if let Some(result) = self.start_connect(&remote_endpoint) {
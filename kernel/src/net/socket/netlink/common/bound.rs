@@ -2,8 +2,11 @@
 
 use crate::{
     events::IoEvents,
-    net::socket::netlink::{
-        GroupIdSet, NetlinkSocketAddr, receiver::MessageQueue, table::BoundHandle,
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            GroupIdSet, NetlinkSocketAddr, receiver::MessageQueue, table::BoundHandle,
+        },
     },
     prelude::*,
 };
@@ -12,17 +15,20 @@ pub struct BoundNetlink<Message: 'static> {
     pub(in crate::net::socket::netlink) handle: BoundHandle<Message>,
     pub(in crate::net::socket::netlink) remote_addr: NetlinkSocketAddr,
     pub(in crate::net::socket::netlink) receive_queue: Arc<Mutex<MessageQueue<Message>>>,
+    pub(in crate::net::socket::netlink) net_ns: Arc<NetNamespace>,
 }
 
 impl<Message: 'static> BoundNetlink<Message> {
     pub(super) fn new(
         handle: BoundHandle<Message>,
         message_queue: Arc<Mutex<MessageQueue<Message>>>,
+        net_ns: Arc<NetNamespace>,
     ) -> Self {
         Self {
             handle,
             remote_addr: NetlinkSocketAddr::new_unspecified(),
             receive_queue: message_queue,
+            net_ns,
         }
     }
 
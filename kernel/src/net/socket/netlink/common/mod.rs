@@ -9,18 +9,21 @@ use super::{GroupIdSet, NetlinkSocketAddr};
 use crate::{
     events::IoEvents,
     fs::{pseudofs::SockFs, vfs::path::Path},
-    net::socket::{
-        Socket,
-        netlink::{AddMembership, DropMembership, table::SupportedNetlinkProtocol},
-        options::{
-            Error as SocketError, SocketOption,
-            macros::{sock_option_mut, sock_option_ref},
-        },
-        private::SocketPrivate,
-        util::{
-            MessageHeader, SendRecvFlags, SocketAddr,
-            datagram_common::{Bound, Inner, select_remote_and_bind},
-            options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            netlink::{AddMembership, DropMembership, table::SupportedNetlinkProtocol},
+            options::{
+                Error as SocketError, SocketOption,
+                macros::{sock_option_mut, sock_option_ref},
+            },
+            private::SocketPrivate,
+            util::{
+                MessageHeader, SendRecvFlags, SocketAddr,
+                datagram_common::{Bound, Inner, select_remote_and_bind},
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
         },
     },
     prelude::*,
@@ -57,8 +60,8 @@ impl<P: SupportedNetlinkProtocol> NetlinkSocket<P>
 where
     BoundNetlink<P::Message>: Bound<Endpoint = NetlinkSocketAddr>,
 {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        let unbound = UnboundNetlink::new();
+    pub fn new(is_nonblocking: bool, net_ns: Arc<NetNamespace>) -> Arc<Self> {
+        let unbound = UnboundNetlink::new(net_ns);
         Arc::new(Self {
             inner: RwMutex::new(Inner::Unbound(unbound)),
             options: RwLock::new(OptionSet::new()),
@@ -4,12 +4,15 @@ use core::marker::PhantomData;
 
 use crate::{
     events::IoEvents,
-    net::socket::{
-        netlink::{
-            GroupIdSet, NetlinkSocketAddr, common::bound::BoundNetlink, receiver::MessageQueue,
-            table::SupportedNetlinkProtocol,
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            netlink::{
+                GroupIdSet, NetlinkSocketAddr, common::bound::BoundNetlink, receiver::MessageQueue,
+                table::SupportedNetlinkProtocol,
+            },
+            util::datagram_common,
         },
-        util::datagram_common,
     },
     prelude::*,
     process::signal::Pollee,
@@ -17,13 +20,15 @@ use crate::{
 
 pub(super) struct UnboundNetlink<P: SupportedNetlinkProtocol> {
     groups: GroupIdSet,
+    net_ns: Arc<NetNamespace>,
     phantom: PhantomData<BoundNetlink<P::Message>>,
 }
 
 impl<P: SupportedNetlinkProtocol> UnboundNetlink<P> {
-    pub(super) const fn new() -> Self {
+    pub(super) fn new(net_ns: Arc<NetNamespace>) -> Self {
         Self {
             groups: GroupIdSet::new_empty(),
+            net_ns,
             phantom: PhantomData,
         }
     }
@@ -62,10 +67,14 @@ impl<P: SupportedNetlinkProtocol> datagram_common::Unbound for UnboundNetlink<P>
                 endpoint.add_groups(self.groups);
                 endpoint
             };
-            <P as SupportedNetlinkProtocol>::bind(&endpoint, message_receiver)?
+            <P as SupportedNetlinkProtocol>::bind(&self.net_ns, &endpoint, message_receiver)?
         };
 
-        Ok(BoundNetlink::new(bound_handle, message_queue))
+        Ok(BoundNetlink::new(
+            bound_handle,
+            message_queue,
+            self.net_ns.clone(),
+        ))
     }
 
     fn bind_ephemeral(
@@ -82,10 +91,14 @@ impl<P: SupportedNetlinkProtocol> datagram_common::Unbound for UnboundNetlink<P>
                 endpoint.add_groups(self.groups);
                 endpoint
             };
-            <P as SupportedNetlinkProtocol>::bind(&endpoint, message_receiver)?
+            <P as SupportedNetlinkProtocol>::bind(&self.net_ns, &endpoint, message_receiver)?
         };
 
-        Ok(BoundNetlink::new(bound_handle, message_queue))
+        Ok(BoundNetlink::new(
+            bound_handle,
+            message_queue,
+            self.net_ns.clone(),
+        ))
     }
 
     fn check_io_events(&self) -> IoEvents {
@@ -50,6 +50,7 @@ pub use addr::{GroupIdSet, NetlinkSocketAddr};
 pub use kobject_uevent::NetlinkUeventSocket;
 pub use options::{AddMembership, DropMembership};
 pub(super) use receiver::NETLINK_DEFAULT_BUF_SIZE;
+pub(in crate::net) use route::NetlinkRouteKernelSocket;
 pub use route::NetlinkRouteSocket;
 pub use table::{StandardNetlinkProtocol, is_valid_protocol};
 
@@ -8,7 +8,6 @@ use crate::{
             NetlinkSocketAddr,
             common::BoundNetlink,
             message::{ContinueRead, ProtocolSegment},
-            route::kernel::get_netlink_route_kernel,
         },
         util::{SendRecvFlags, datagram_common},
     },
@@ -59,7 +58,7 @@ impl datagram_common::Bound for BoundNetlinkRoute {
         let sum_lens = reader.sum_lens();
 
         let local_port = self.handle.port();
-        let rtnl_kernel = get_netlink_route_kernel();
+        let rtnl_kernel = self.net_ns.netlink_route_kernel();
 
         loop {
             let mut segment = match RtnlSegment::read_from(reader) {
@@ -89,7 +88,7 @@ impl datagram_common::Bound for BoundNetlinkRoute {
                 header.pid = local_port;
             }
 
-            rtnl_kernel.handle_request(&segment, local_port);
+            rtnl_kernel.handle_request(&segment, local_port, &self.net_ns);
         }
 
         Ok(sum_lens)
@@ -3,14 +3,15 @@
 //! This module defines the kernel socket,
 //! which is responsible for handling requests from user space.
 
-use core::marker::PhantomData;
-
 use super::message::{RtnlMessage, RtnlSegment};
 use crate::{
-    net::socket::netlink::{
-        addr::PortNum,
-        message::{ErrorSegment, ProtocolSegment},
-        table::{NetlinkRouteProtocol, SupportedNetlinkProtocol},
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::PortNum,
+            message::{ErrorSegment, ProtocolSegment},
+            table::ProtocolSocketTable,
+        },
     },
     prelude::*,
 };
@@ -19,25 +20,42 @@ mod addr;
 mod link;
 mod util;
 
-pub(super) struct NetlinkRouteKernelSocket {
-    _private: PhantomData<()>,
+/// The kernel socket of a network namespace.
+///
+/// Each network namespace has its own kernel socket, which keeps the port table of the netlink
+/// route sockets in the namespace.
+pub struct NetlinkRouteKernelSocket {
+    socket_table: Arc<RwMutex<ProtocolSocketTable<RtnlMessage>>>,
 }
 
 impl NetlinkRouteKernelSocket {
-    const fn new() -> Self {
+    pub(in crate::net) fn new() -> Self {
         Self {
-            _private: PhantomData,
+            socket_table: Arc::new(RwMutex::new(ProtocolSocketTable::new())),
         }
     }
 
-    pub(super) fn handle_request(&self, request: &RtnlSegment, dst_port: PortNum) {
+    /// Returns the table of the netlink route sockets bound in the network namespace.
+    pub(in crate::net::socket::netlink) fn socket_table(
+        &self,
+    ) -> &Arc<RwMutex<ProtocolSocketTable<RtnlMessage>>> {
+        &self.socket_table
+    }
+
+    /// Handles a request from a netlink route socket in the network namespace `net_ns`.
+    pub(super) fn handle_request(
+        &self,
+        request: &RtnlSegment,
+        dst_port: PortNum,
+        net_ns: &NetNamespace,
+    ) {
         debug!("netlink route request: {:?}", request);
 
         let request_header = request.header();
 
         let response_segments = match request {
-            RtnlSegment::GetLink(request_segment) => link::do_get_link(request_segment),
-            RtnlSegment::GetAddr(request_segment) => addr::do_get_addr(request_segment),
+            RtnlSegment::GetLink(request_segment) => link::do_get_link(request_segment, net_ns),
+            RtnlSegment::GetAddr(request_segment) => addr::do_get_addr(request_segment, net_ns),
             _ => Err(Error::with_message(
                 Errno::EOPNOTSUPP,
                 "the netlink route request is not supported",
@@ -58,7 +76,10 @@ impl NetlinkRouteKernelSocket {
 
         debug!("netlink route response: {:?}", response);
 
-        NetlinkRouteProtocol::unicast(dst_port, response).unwrap();
+        self.socket_table
+            .read()
+            .unicast(dst_port, response)
+            .unwrap();
     }
 
     pub(super) fn report_error(&self, err_segment: ErrorSegment, dst_port: PortNum) {
@@ -66,13 +87,9 @@ impl NetlinkRouteKernelSocket {
 
         debug!("netlink route error: {:?}", response);
 
-        NetlinkRouteProtocol::unicast(dst_port, response).unwrap();
+        self.socket_table
+            .read()
+            .unicast(dst_port, response)
+            .unwrap();
     }
 }
-
-/// FIXME: NETLINK_ROUTE_KERNEL should be a per-network namespace socket
-static NETLINK_ROUTE_KERNEL: NetlinkRouteKernelSocket = NetlinkRouteKernelSocket::new();
-
-pub(super) fn get_netlink_route_kernel() -> &'static NetlinkRouteKernelSocket {
-    &NETLINK_ROUTE_KERNEL
-}
@@ -2,6 +2,7 @@
 
 //! Netlink Route Socket.
 
+pub use kernel::NetlinkRouteKernelSocket;
 pub(super) use message::RtnlMessage;
 
 use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkRouteProtocol};
@@ -9,9 +9,12 @@ use super::{
     receiver::QueueableMessage,
 };
 use crate::{
-    net::socket::netlink::{
-        addr::UNSPECIFIED_PORT, kobject_uevent::UeventMessage, receiver::MessageReceiver,
-        route::RtnlMessage,
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::UNSPECIFIED_PORT, kobject_uevent::UeventMessage, receiver::MessageReceiver,
+            route::RtnlMessage,
+        },
     },
     prelude::*,
     util::random::getrandom,
@@ -21,17 +24,18 @@ mod multicast;
 
 static NETLINK_SOCKET_TABLE: Once<NetlinkSocketTable> = Once::new();
 
-/// All bound netlink sockets.
+/// All bound netlink sockets that are shared by all network namespaces.
+///
+/// The bound sockets of the `NETLINK_ROUTE` protocol are not in this table. They are kept by the
+/// route kernel socket of each network namespace.
 struct NetlinkSocketTable {
-    route: RwMutex<ProtocolSocketTable<RtnlMessage>>,
-    uevent: RwMutex<ProtocolSocketTable<UeventMessage>>,
+    uevent: Arc<RwMutex<ProtocolSocketTable<UeventMessage>>>,
 }
 
 impl NetlinkSocketTable {
     fn new() -> Self {
         Self {
-            route: RwMutex::new(ProtocolSocketTable::new()),
-            uevent: RwMutex::new(ProtocolSocketTable::new()),
+            uevent: Arc::new(RwMutex::new(ProtocolSocketTable::new())),
         }
     }
 }
@@ -39,30 +43,28 @@ impl NetlinkSocketTable {
 pub trait SupportedNetlinkProtocol {
     type Message: 'static + Send;
 
-    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>>;
+    /// Returns the socket table used by the sockets in the network namespace `net_ns`.
+    fn socket_table(net_ns: &NetNamespace) -> &Arc<RwMutex<ProtocolSocketTable<Self::Message>>>;
 
     fn bind(
+        net_ns: &NetNamespace,
         addr: &NetlinkSocketAddr,
         receiver: MessageReceiver<Self::Message>,
     ) -> Result<BoundHandle<Self::Message>> {
-        let mut socket_table = Self::socket_table().write();
-        socket_table.bind(Self::socket_table(), addr, receiver)
-    }
-
-    fn unicast(dst_port: PortNum, message: Self::Message) -> Result<()>
-    where
-        Self::Message: QueueableMessage,
-    {
-        let socket_table = Self::socket_table().read();
-        socket_table.unicast(dst_port, message)
+        let socket_table = Self::socket_table(net_ns);
+        socket_table.write().bind(socket_table, addr, receiver)
     }
 
     #[cfg_attr(not(ktest), expect(dead_code))]
-    fn multicast(dst_groups: GroupIdSet, message: Self::Message) -> Result<()>
+    fn multicast(
+        net_ns: &NetNamespace,
+        dst_groups: GroupIdSet,
+        message: Self::Message,
+    ) -> Result<()>
     where
         Self::Message: MulticastMessage,
     {
-        let socket_table = Self::socket_table().read();
+        let socket_table = Self::socket_table(net_ns).read();
         socket_table.multicast(dst_groups, message)
     }
 }
@@ -72,8 +74,8 @@ pub enum NetlinkRouteProtocol {}
 impl SupportedNetlinkProtocol for NetlinkRouteProtocol {
     type Message = RtnlMessage;
 
-    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
-        &NETLINK_SOCKET_TABLE.get().unwrap().route
+    fn socket_table(net_ns: &NetNamespace) -> &Arc<RwMutex<ProtocolSocketTable<Self::Message>>> {
+        net_ns.netlink_route_kernel().socket_table()
     }
 }
 
@@ -82,7 +84,9 @@ pub enum NetlinkUeventProtocol {}
 impl SupportedNetlinkProtocol for NetlinkUeventProtocol {
     type Message = UeventMessage;
 
-    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
+    // TODO: Kobject uevents should be broadcast only to the network namespaces that own the
+    // corresponding devices.
+    fn socket_table(_net_ns: &NetNamespace) -> &Arc<RwMutex<ProtocolSocketTable<Self::Message>>> {
         &NETLINK_SOCKET_TABLE.get().unwrap().uevent
     }
 }
@@ -98,7 +102,7 @@ pub struct ProtocolSocketTable<Message> {
 
 impl<Message: 'static> ProtocolSocketTable<Message> {
     /// Creates a new table.
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         let multicast_groups = (0u32..MAX_GROUPS).map(|_| MulticastGroup::new()).collect();
         Self {
             unicast_sockets: BTreeMap::new(),
@@ -119,7 +123,7 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
     /// as specified in `addr.groups()`.
     fn bind(
         &mut self,
-        socket_table: &'static RwMutex<ProtocolSocketTable<Message>>,
+        socket_table: &Arc<RwMutex<ProtocolSocketTable<Message>>>,
         addr: &NetlinkSocketAddr,
         receiver: MessageReceiver<Message>,
     ) -> Result<BoundHandle<Message>> {
@@ -145,10 +149,10 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
             group.add_member(port);
         }
 
-        Ok(BoundHandle::new(socket_table, port, addr.groups()))
+        Ok(BoundHandle::new(socket_table.clone(), port, addr.groups()))
     }
 
-    fn unicast(&self, dst_port: PortNum, message: Message) -> Result<()>
+    pub(super) fn unicast(&self, dst_port: PortNum, message: Message) -> Result<()>
     where
         Message: QueueableMessage,
     {
@@ -187,14 +191,14 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
 /// When dropping a `BoundHandle`,
 /// the port will be automatically released.
 pub struct BoundHandle<Message: 'static> {
-    socket_table: &'static RwMutex<ProtocolSocketTable<Message>>,
+    socket_table: Arc<RwMutex<ProtocolSocketTable<Message>>>,
     port: PortNum,
     groups: GroupIdSet,
 }
 
 impl<Message: 'static> BoundHandle<Message> {
     fn new(
-        socket_table: &'static RwMutex<ProtocolSocketTable<Message>>,
+        socket_table: Arc<RwMutex<ProtocolSocketTable<Message>>>,
         port: PortNum,
         groups: GroupIdSet,
     ) -> Self {
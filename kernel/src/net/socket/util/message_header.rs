@@ -129,6 +129,95 @@ impl ControlMessage {
             Self::Unix(msg) => msg.write_to(writer),
         }
     }
+
+    /// Reads the control messages of 32-bit programs.
+    ///
+    /// The payloads of the supported control messages have the same layout for 32-bit programs,
+    /// so only the headers are translated.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/compat.c>
+    #[cfg(target_arch = "x86_64")]
+    pub fn read_all_from_compat(reader: &mut VmReader) -> Result<Vec<Self>> {
+        let mut buf = Vec::new();
+
+        while reader.has_remain() {
+            let header = reader.read_val::<CompatControlHeader>()?;
+            if (header.len as usize) < size_of::<CompatControlHeader>()
+                || header.payload_len() > reader.remain()
+            {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the size of the control message is invalid"
+                );
+            }
+
+            let native_header = CControlHeader {
+                len: header.payload_len() + size_of::<CControlHeader>(),
+                level: header.level,
+                type_: header.type_,
+            };
+            if buf.len() + native_header.total_len_with_padding() > MAX_COMPAT_CONTROL_LEN {
+                return_errno_with_message!(Errno::ENOBUFS, "the control messages are too large");
+            }
+
+            buf.extend_from_slice(native_header.as_bytes());
+            let payload_start = buf.len();
+            buf.resize(payload_start + header.payload_len(), 0);
+            reader.read_fallible(&mut VmWriter::from(&mut buf[payload_start..]))?;
+            buf.resize(buf.len().align_up(CMSG_ALIGN), 0);
+
+            let padding_len = header.padding_len().min(reader.remain());
+            reader.skip(padding_len);
+        }
+
+        Self::read_all_from(&mut VmReader::from(buf.as_slice()).to_fallible())
+    }
+
+    /// Writes the control messages of 32-bit programs.
+    ///
+    /// This is the 32-bit version of [`Self::write_all_to`].
+    #[cfg(target_arch = "x86_64")]
+    pub fn write_all_to_compat(msgs: &[Self], writer: &mut VmWriter) -> usize {
+        const HEADER_LEN_DIFF: usize =
+            size_of::<CControlHeader>() - size_of::<CompatControlHeader>();
+
+        let mut len = 0;
+
+        for msg in msgs.iter() {
+            // Write the message with the native header to a buffer, where the space for the
+            // payload is the same as the space in `writer`.
+            let buf_len = writer.avail().min(MAX_COMPAT_CONTROL_LEN) + HEADER_LEN_DIFF;
+            let mut buf = vec![0u8; buf_len];
+            let header = match msg.write_to(&mut VmWriter::from(buf.as_mut_slice()).to_fallible()) {
+                Ok(header) => header,
+                Err(_) => {
+                    warn!("setting MSG_CTRUNC is not supported");
+                    break;
+                }
+            };
+
+            let compat_header = CompatControlHeader {
+                len: (header.total_len() - HEADER_LEN_DIFF) as u32,
+                level: header.level,
+                type_: header.type_,
+            };
+            let payload = &buf[size_of::<CControlHeader>()..header.total_len()];
+            if writer.write_val(&compat_header).is_err()
+                || writer.write_fallible(&mut VmReader::from(payload)).is_err()
+            {
+                warn!("setting MSG_CTRUNC is not supported");
+                break;
+            }
+
+            len += compat_header.len as usize;
+
+            let padding_len = compat_header.padding_len().min(writer.avail());
+            writer.skip(padding_len);
+            len += padding_len;
+        }
+
+        len
+    }
 }
 
 /// `cmsghdr` in Linux.
@@ -197,3 +286,39 @@ impl CControlHeader {
         self.len.align_up(CMSG_ALIGN)
     }
 }
+
+/// `compat_cmsghdr` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/net/compat.h>
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CompatControlHeader {
+    /// Data byte count, including hdr
+    len: u32,
+    /// Originating protocol
+    level: i32,
+    /// Protocol-specific type
+    type_: i32,
+}
+
+/// The maximum length of the translated control messages of 32-bit programs.
+///
+/// This is the default value of `sysctl_optmem_max` in Linux.
+#[cfg(target_arch = "x86_64")]
+const MAX_COMPAT_CONTROL_LEN: usize = 128 * 1024;
+
+/// Alignment of control messages of 32-bit programs.
+#[cfg(target_arch = "x86_64")]
+const COMPAT_CMSG_ALIGN: usize = size_of::<u32>();
+
+#[cfg(target_arch = "x86_64")]
+impl CompatControlHeader {
+    fn payload_len(&self) -> usize {
+        self.len as usize - size_of::<Self>()
+    }
+
+    fn padding_len(&self) -> usize {
+        (self.len as usize).align_up(COMPAT_CMSG_ALIGN) - self.len as usize
+    }
+}
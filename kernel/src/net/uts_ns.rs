@@ -206,6 +206,16 @@ impl UtsName {
             }
         }
     };
+
+    /// Returns the node name (i.e., the hostname) without the trailing NUL bytes.
+    pub fn nodename(&self) -> &[u8] {
+        let len = self
+            .nodename
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(UTS_FIELD_LEN);
+        &self.nodename[..len]
+    }
 }
 
 impl NsCommonOps for UtsNamespace {
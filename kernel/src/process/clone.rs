@@ -4,31 +4,33 @@ use alloc::borrow::Cow;
 use core::{num::NonZeroU64, sync::atomic::Ordering};
 
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GsBase};
+use ostd::arch::cpu::context::{FsBase, GsBase, TlsEntries};
 use ostd::{
     arch::cpu::context::UserContext, cpu::CpuId, mm::VmIo, sync::RwArc, task::Task,
     user::UserContextApi,
 };
 
 use super::{
-    Credentials, Pid, Process, pid_table,
+    Credentials, INIT_PROCESS_PID, Pid, Process, pid_table,
     posix_thread::{AsPosixThread, PosixThreadBuilder},
     rlimit::ResourceLimits,
-    signal::{constants::SIGCHLD, sig_disposition::SigDispositions, sig_num::SigNum},
+    signal::{
+        HandlePendingSignal, constants::SIGCHLD, sig_disposition::SigDispositions, sig_num::SigNum,
+    },
 };
 use crate::{
     context::current_userspace,
     cpu::LinuxAbi,
     fs::{
-        cgroupfs::{CgroupMembership, CgroupSysNode},
+        cgroupfs::{CgroupMembership, CgroupSysNode, cpuset_cpus_allowed, update_cpu_affinity},
         file::file_table::{FdFlags, FileTable, RawFileDesc},
         thread_info::ThreadFsInfo,
     },
     prelude::*,
     process::{
-        NsProxy, UserNamespace,
+        NsProxy, PidNamespace, UserNamespace,
         pid_file::PidFile,
-        posix_thread::{PosixThread, ThreadLocal, allocate_posix_tid},
+        posix_thread::{PosixThread, ThreadLocal, allocate_posix_tid, ptrace::PtraceEvent},
         stats::PROCESS_CREATION_COUNTER,
     },
     sched::Nice,
@@ -203,7 +205,7 @@ impl CloneArgs {
                 );
             }
 
-            if ctx.process.is_init_process() {
+            if ctx.process.is_child_reaper() {
                 return_errno_with_message!(
                     Errno::EINVAL,
                     "`CLONE_PARENT` cannot be used if the process is the init process"
@@ -226,6 +228,39 @@ impl CloneArgs {
                     "`CLONE_THREAD` cannot be used together with `CLONE_PIDFD` or `CLONE_NEWUSER`"
                 );
             }
+
+            // A thread must belong to the same PID namespace as the other threads in the process.
+            if clone_flags.contains(CloneFlags::CLONE_NEWPID) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "`CLONE_THREAD` cannot be used together with `CLONE_NEWPID`"
+                );
+            }
+            let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+            if !Arc::ptr_eq(
+                ns_proxy.unwrap().pid_ns_for_children(),
+                ctx.process.pid_ns(),
+            ) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "`CLONE_THREAD` cannot be used after the PID namespace for children is changed"
+                );
+            }
+        }
+
+        // A thread or a process sharing the virtual memory must belong to the same time namespace
+        // as the current thread, since they share the same vDSO mappings. Note that
+        // `CLONE_THREAD` requires `CLONE_VM`, as checked above.
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.9/source/kernel/fork.c>.
+        if clone_flags.contains(CloneFlags::CLONE_VM) {
+            let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+            let ns_proxy = ns_proxy.unwrap();
+            if !Arc::ptr_eq(ns_proxy.time_ns(), ns_proxy.time_ns_for_children()) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "`CLONE_VM` cannot be used after the time namespace for children is changed"
+                );
+            }
         }
 
         // Reject invalid argument combinations related to the CLONE_SIGHAND flag.
@@ -238,16 +273,6 @@ impl CloneArgs {
             );
         }
 
-        // TODO: Support clone-family ptrace events and remove this check.
-        if !clone_flags.contains(CloneFlags::CLONE_UNTRACED)
-            && ctx.posix_thread.needs_ptrace_clone_stop(self)
-        {
-            return_errno_with_message!(
-                Errno::EOPNOTSUPP,
-                "ptrace clone events are not supported currently"
-            );
-        }
-
         Ok(())
     }
 }
@@ -276,6 +301,8 @@ impl CloneFlags {
             | CloneFlags::CLONE_VFORK
             | CloneFlags::CLONE_NEWCGROUP
             | CloneFlags::CLONE_NEWNS
+            | CloneFlags::CLONE_NEWPID
+            | CloneFlags::CLONE_NEWTIME
             | CloneFlags::CLONE_NEWUTS
             | CloneFlags::CLONE_PARENT;
         let unsupported_flags = *self - supported_flags;
@@ -292,23 +319,49 @@ impl CloneFlags {
 /// but this may not be the expected behavior.
 pub fn clone_child(
     ctx: &Context,
-    parent_context: &UserContext,
+    parent_context: &mut UserContext,
     clone_args: CloneArgs,
 ) -> Result<Tid> {
     clone_args.check(ctx)?;
 
     if clone_args.flags.contains(CloneFlags::CLONE_THREAD) {
+        // Hold the read lock to ensure the cpuset of the current process won't
+        // change before the CPU affinity of the child thread is restricted.
+        let cgroup_read_guard = CgroupMembership::read_lock();
+
         let child_task = clone_child_task(ctx, parent_context, clone_args)?;
         let child_thread = child_task.as_thread().unwrap();
-        child_thread.run();
+        child_thread.restrict_cpu_affinity(&cpuset_cpus_allowed(&ctx.process));
+        drop(cgroup_read_guard);
 
+        // Translate the TID before running the thread, since the ID is freed once the thread
+        // exits.
         let child_tid = child_thread.as_posix_thread().unwrap().tid();
-        Ok(child_tid)
+        let child_local_tid = ctx.process.pid_ns().local_id_of(child_tid).unwrap();
+
+        let ptrace_event = ctx
+            .posix_thread
+            .ptrace_trace_child(child_thread, &clone_args);
+
+        child_thread.run();
+
+        if let Some(event) = ptrace_event {
+            ctx.posix_thread
+                .ptrace_may_stop_on(event, ctx, parent_context);
+        }
+
+        Ok(child_local_tid)
     } else {
         // Hold the read lock before charge to ensure the cgroup of current process
         // won't change during the charge and the subsequent move operation.
         let cgroup_read_guard = CgroupMembership::read_lock();
 
+        // The child would escape `cgroup.kill` if the current process is killed
+        // before the child joins the cgroup.
+        if ctx.has_pending_sigkill() {
+            return_errno_with_message!(Errno::EINTR, "the current process is being killed");
+        }
+
         // Pre-charge the pids sub-controller before creating the child process.
         // This enforces `pids.max` at fork time per cgroupv2 semantics.
         // The charge must happen before process creation so that on failure
@@ -339,12 +392,25 @@ pub fn clone_child(
         } else {
             drop(pids_charge);
         }
+        update_cpu_affinity(&child_process);
         drop(cgroup_read_guard);
 
         if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
             child_process.status().set_vfork_child(true);
         }
 
+        // Translate the PID before running the process, since the ID is freed once the process
+        // is reaped.
+        let child_local_pid = ctx
+            .process
+            .pid_ns()
+            .local_id_of(child_process.pid())
+            .unwrap();
+
+        let ptrace_event = ctx
+            .posix_thread
+            .ptrace_trace_child(&child_process.main_thread(), &clone_args);
+
         child_process.run();
 
         PROCESS_CREATION_COUNTER
@@ -353,14 +419,24 @@ pub fn clone_child(
             // Race conditions are fine as we don't really care which CPU creates a process.
             .add_on_cpu(CpuId::current_racy(), 1);
 
-        if child_process.status().is_vfork_child() {
+        if let Some(event) = ptrace_event {
+            ctx.posix_thread
+                .ptrace_may_stop_on(event, ctx, parent_context);
+        }
+
+        if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
             let cond = || (!child_process.status().is_vfork_child()).then_some(());
             let current = ctx.process.as_ref();
             current.children_wait_queue().wait_until(cond);
+
+            ctx.posix_thread.ptrace_may_stop_on(
+                PtraceEvent::VforkDone(child_process.pid()),
+                ctx,
+                parent_context,
+            );
         }
 
-        let child_pid = child_process.pid();
-        Ok(child_pid)
+        Ok(child_local_pid)
     }
 }
 
@@ -426,7 +502,8 @@ fn clone_child_task(
     ));
 
     #[cfg(target_arch = "x86_64")]
-    let (child_fs_base, child_gs_base) = clone_tls_regs(thread_local, clone_flags, clone_args.tls);
+    let (child_fs_base, child_gs_base, child_tls_entries) =
+        clone_tls_regs(thread_local, clone_flags, clone_args.tls)?;
     #[cfg(not(target_arch = "x86_64"))]
     clone_tls_pointer(child_user_ctx.as_mut(), clone_flags, clone_args.tls);
 
@@ -436,7 +513,13 @@ fn clone_child_task(
     // Inherit the thread name.
     let thread_name = posix_thread.thread_name().lock().clone();
 
+    // Inherit the seccomp state and the `no_new_privs` attribute.
+    let seccomp = posix_thread.seccomp().new_inherited();
+    let no_new_privs = posix_thread.no_new_privs();
+
     let child_tid = allocate_posix_tid();
+    let child_pid_ns = process.pid_ns();
+    let child_local_tid = child_pid_ns.alloc_ids(child_tid)?;
     let child_task = {
         let credentials = {
             let credentials = ctx.posix_thread.credentials();
@@ -457,14 +540,21 @@ fn clone_child_task(
         .fpu_context(child_fpu_context)
         .user_ns(child_user_ns)
         .ns_proxy(child_ns_proxy)
-        .default_timer_slack_ns(default_timer_slack_ns);
+        .default_timer_slack_ns(default_timer_slack_ns)
+        .seccomp(seccomp)
+        .no_new_privs(no_new_privs)
+        .cpu_affinity(ctx.thread.user_cpu_affinity());
         #[cfg(target_arch = "x86_64")]
         {
-            thread_builder = thread_builder.fs_base(child_fs_base).gs_base(child_gs_base);
+            thread_builder = thread_builder
+                .fs_base(child_fs_base)
+                .gs_base(child_gs_base)
+                .tls_entries(child_tls_entries);
         }
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        clone_parent_settid(child_local_tid, clone_args.parent_tid, clone_flags)
+            .inspect_err(|_| child_pid_ns.free_ids(child_tid))?;
         thread_builder = clone_child_cleartid(thread_builder, clone_args.child_tid, clone_flags);
         thread_builder = clone_child_settid(thread_builder, clone_args.child_tid, clone_flags);
 
@@ -476,6 +566,7 @@ fn clone_child_task(
         .lock()
         .insert(child_task.clone())
         .map_err(|_| {
+            child_pid_ns.free_ids(child_tid);
             Error::with_message(
                 Errno::EINTR,
                 "the process has exited or has already executed a new program",
@@ -514,7 +605,8 @@ fn clone_child_process(
     ));
 
     #[cfg(target_arch = "x86_64")]
-    let (child_fs_base, child_gs_base) = clone_tls_regs(thread_local, clone_flags, clone_args.tls);
+    let (child_fs_base, child_gs_base, child_tls_entries) =
+        clone_tls_regs(thread_local, clone_flags, clone_args.tls)?;
     #[cfg(not(target_arch = "x86_64"))]
     clone_tls_pointer(child_user_ctx.as_mut(), clone_flags, clone_args.tls);
 
@@ -553,6 +645,13 @@ fn clone_child_process(
             .switch_to_mnt_ns(child_ns_proxy.mnt_ns())?;
     }
 
+    if !Arc::ptr_eq(
+        child_ns_proxy.time_ns(),
+        thread_local.borrow_ns_proxy().unwrap().time_ns(),
+    ) {
+        child_ns_proxy.time_ns().enter(&child_vmar)?;
+    }
+
     // Inherit the parent's signal mask
     let child_sig_mask = posix_thread.sig_mask().into();
 
@@ -565,7 +664,15 @@ fn clone_child_process(
     // Inherit the parent's OOM score adjustment
     let child_oom_score_adj = process.oom_score_adj().load(Ordering::Relaxed);
 
+    // Inherit the parent's seccomp state and `no_new_privs` attribute
+    let child_seccomp = posix_thread.seccomp().new_inherited();
+    let child_no_new_privs = posix_thread.no_new_privs();
+
+    // Allocate the PID in the PID namespace for children and all its ancestors.
     let child_tid = allocate_posix_tid();
+    let child_pid_ns = child_ns_proxy.pid_ns_for_children().clone();
+    child_pid_ns.alloc_ids(child_tid)?;
+    let parent_local_tid = process.pid_ns().local_id_of(child_tid).unwrap();
 
     let child = {
         let child_vmar_arc = child_vmar.clone_arc();
@@ -593,16 +700,21 @@ fn clone_child_process(
             .user_ns(child_user_ns.clone())
             .ns_proxy(child_ns_proxy)
             .default_timer_slack_ns(default_timer_slack_ns)
+            .seccomp(child_seccomp)
+            .no_new_privs(child_no_new_privs)
+            .cpu_affinity(ctx.thread.user_cpu_affinity())
         };
         #[cfg(target_arch = "x86_64")]
         {
             child_thread_builder = child_thread_builder
                 .fs_base(child_fs_base)
-                .gs_base(child_gs_base);
+                .gs_base(child_gs_base)
+                .tls_entries(child_tls_entries);
         }
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        clone_parent_settid(parent_local_tid, clone_args.parent_tid, clone_flags)
+            .inspect_err(|_| child_pid_ns.free_ids(child_tid))?;
         child_thread_builder =
             clone_child_cleartid(child_thread_builder, clone_args.child_tid, clone_flags);
         child_thread_builder =
@@ -615,12 +727,25 @@ fn clone_child_process(
             child_nice,
             child_oom_score_adj,
             child_sig_dispositions,
+            child_pid_ns.clone(),
             child_user_ns,
             child_thread_builder,
         )
     };
 
-    clone_pidfd(ctx, &child, clone_flags, clone_args.pidfd)?;
+    if let Err(err) = clone_pidfd(ctx, &child, clone_flags, clone_args.pidfd) {
+        // The child is not yet visible in the PID table or to its parent, and the pidfd only
+        // holds a weak reference. Drop the child before freeing its IDs so that no process still
+        // refers to them once they can be allocated again.
+        drop(child);
+        child_pid_ns.free_ids(child_tid);
+        return Err(err);
+    }
+
+    // The first process in a new PID namespace becomes the init process of the namespace.
+    if !child_pid_ns.is_init() && child_pid_ns.local_id_of(child_tid) == Some(INIT_PROCESS_PID) {
+        child_pid_ns.set_child_reaper(&child);
+    }
 
     if let Some(sig) = clone_args.exit_signal {
         child.set_exit_signal(sig);
@@ -706,16 +831,27 @@ fn clone_tls_regs(
     thread_local: &ThreadLocal,
     clone_flags: CloneFlags,
     tls: u64,
-) -> (FsBase, GsBase) {
+) -> Result<(FsBase, GsBase, TlsEntries)> {
+    use crate::arch::tls::UserDesc;
+
     let supp = thread_local.supp_user_context();
-    let child_fs_base = if clone_flags.contains(CloneFlags::CLONE_SETTLS) {
-        FsBase::new(tls as usize)
-    } else {
-        supp.fs_base().get()
-    };
-    let child_gs_base = supp.gs_base().get();
+    let mut child_fs_base = supp.fs_base().get();
+    let mut child_gs_base = supp.gs_base().get();
+    let mut child_tls_entries = supp.tls_entries().get();
 
-    (child_fs_base, child_gs_base)
+    if clone_flags.contains(CloneFlags::CLONE_SETTLS) {
+        if thread_local.in_compat_syscall() {
+            // For 32-bit programs, `tls` points to a `struct user_desc`, which describes the
+            // segment to be installed into the TLS entries. The segment is accessed via `%gs`.
+            let mut user_desc = current_userspace!().read_val::<UserDesc>(tls as Vaddr)?;
+            user_desc.install(&mut child_tls_entries, false)?;
+            child_gs_base = GsBase::new(user_desc.base_addr as usize);
+        } else {
+            child_fs_base = FsBase::new(tls as usize);
+        }
+    }
+
+    Ok((child_fs_base, child_gs_base, child_tls_entries))
 }
 
 #[cfg(not(target_arch = "x86_64"))]
@@ -834,6 +970,7 @@ fn create_child_process(
     nice: Nice,
     oom_score_adj: i16,
     sig_dispositions: Arc<Mutex<SigDispositions>>,
+    pid_ns: Arc<PidNamespace>,
     user_ns: Arc<UserNamespace>,
     thread_builder: PosixThreadBuilder,
 ) -> Arc<Process> {
@@ -844,6 +981,7 @@ fn create_child_process(
         nice,
         oom_score_adj,
         sig_dispositions,
+        pid_ns,
         user_ns,
     );
 
@@ -853,7 +991,11 @@ fn create_child_process(
     child_proc
 }
 
-fn set_parent_and_group(clone_flags: CloneFlags, parent: &Arc<Process>, child: &Arc<Process>) {
+pub(super) fn set_parent_and_group(
+    clone_flags: CloneFlags,
+    parent: &Arc<Process>,
+    child: &Arc<Process>,
+) {
     loop {
         let real_parent = clone_parent(clone_flags, parent);
 
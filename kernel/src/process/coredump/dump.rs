@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The process of dumping core to a file or a pipe.
+
+use ostd::arch::cpu::context::UserContext;
+
+use super::{
+    CoreState, Dumpable,
+    elf::ElfCore,
+    pattern::{CoreTarget, expand_core_pattern},
+};
+use crate::{
+    fs::{
+        self,
+        file::{AccessMode, FileLike, InodeHandle, InodeType, StatusFlags, mkmod},
+        vfs::path::{FsPath, SplitPath},
+    },
+    prelude::*,
+    process::{
+        ResourceType,
+        posix_thread::ContextPthreadAdminApi,
+        process::spawn_usermode_helper,
+        signal::{sig_mask::SigMask, signals::Signal},
+    },
+};
+
+/// Dumps core for the current process according to the core pattern.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c>
+pub(super) fn dump_core(signal: &dyn Signal, ctx: &Context, user_ctx: &UserContext) -> Result<()> {
+    let user_space = ctx.user_space();
+    let vmar = user_space.vmar();
+    let process_vm = vmar.process_vm();
+
+    let dumpable = process_vm.dumpable();
+    if dumpable == Dumpable::Disable {
+        return_errno_with_message!(Errno::EPERM, "the process is not dumpable");
+    }
+
+    let core_limit = ctx
+        .process
+        .resource_limits()
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .get_cur();
+
+    // Stop the other threads, so that the core dump describes a consistent
+    // state of the process. The threads are released when the guard is
+    // dropped.
+    let core_dump_guard = CoreState::zap_threads(signal.num(), ctx)?;
+    let thread_notes = core_dump_guard.wait_for_threads();
+
+    // Collect the process state before blocking signals below, so that the
+    // blocked signals are reported correctly.
+    let elf_core = ElfCore::collect(
+        signal,
+        process_vm.coredump_filter(),
+        ctx,
+        user_ctx,
+        &thread_notes,
+    )?;
+
+    // Writing to the core file or the pipe can be interrupted by signals. Since
+    // the process is going to be terminated anyway, block all signals (except
+    // `SIGKILL` and `SIGSTOP`, which cannot be blocked).
+    ctx.set_sig_mask(SigMask::new_full());
+
+    let mut core_file = match expand_core_pattern(signal.num(), dumpable, core_limit, ctx) {
+        CoreTarget::File(path) => {
+            if core_limit < PAGE_SIZE as u64 {
+                return_errno_with_message!(Errno::EFBIG, "`RLIMIT_CORE` is too small");
+            }
+            if dumpable == Dumpable::Root && !path.starts_with('/') {
+                return_errno_with_message!(
+                    Errno::EPERM,
+                    "the core file of a privileged process must have an absolute path"
+                );
+            }
+            CoreFile::new(create_core_file(&path, ctx)?, true, core_limit)
+        }
+        CoreTarget::Pipe(args) => {
+            // `RLIMIT_CORE` is ignored when piping to a user-mode helper, except
+            // that a limit of one byte is used to prevent recursive core dumps.
+            if core_limit == 1 {
+                return_errno_with_message!(
+                    Errno::EFBIG,
+                    "`RLIMIT_CORE` disables piping to the core dump helper"
+                );
+            }
+            CoreFile::new(spawn_core_helper(args)?, false, u64::MAX)
+        }
+    };
+
+    elf_core.write_to(&mut core_file, vmar)?;
+    core_file.finish()
+}
+
+/// Creates the core file at `path`.
+fn create_core_file(path: &str, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+    let (dir_name, file_name) = path
+        .split_dirname_and_basename()
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the core file path is invalid"))?;
+
+    let dir_path = {
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let path_resolver = fs_ref.resolver().read();
+        path_resolver.lookup(&FsPath::try_from(dir_name)?)?
+    };
+
+    // Remove the old file instead of truncating it, so that the core dump is
+    // never written through a hard link or a symbolic link.
+    match dir_path.unlink(file_name) {
+        Ok(()) => (),
+        Err(err) if err.error() == Errno::ENOENT => (),
+        Err(err) => return Err(err),
+    }
+    let core_path = dir_path.new_fs_child(file_name, InodeType::File, mkmod!(u+rw))?;
+    fs::vfs::notify::on_create(&dir_path, || file_name.to_string());
+
+    Ok(Arc::new(InodeHandle::new_unchecked_access(
+        core_path,
+        AccessMode::O_WRONLY,
+        StatusFlags::empty(),
+    )?))
+}
+
+/// Spawns the user-mode helper that receives the core dump from a pipe.
+///
+/// Returns the write end of the pipe.
+fn spawn_core_helper(args: Vec<String>) -> Result<Arc<dyn FileLike>> {
+    let executable_path = args[0].clone();
+    let argv = args
+        .into_iter()
+        .map(|arg| CString::new(arg).unwrap())
+        .collect();
+
+    let (reader, writer) = fs::pipe::new_file_pair(StatusFlags::empty())?;
+    spawn_usermode_helper(&executable_path, argv, Vec::new(), Some(reader))?;
+
+    Ok(writer)
+}
+
+/// The destination of a core dump.
+pub(super) struct CoreFile {
+    file: Arc<dyn FileLike>,
+    /// Whether skipped bytes can be left as holes by seeking.
+    is_seekable: bool,
+    /// The maximum number of bytes that can be written.
+    limit: u64,
+    /// The number of bytes that have been written.
+    written: u64,
+    /// The current position in the core dump.
+    pos: u64,
+}
+
+impl CoreFile {
+    fn new(file: Arc<dyn FileLike>, is_seekable: bool, limit: u64) -> Self {
+        Self {
+            file,
+            is_seekable,
+            limit,
+            written: 0,
+            pos: 0,
+        }
+    }
+
+    /// Writes `buf` at the current position.
+    pub(super) fn emit(&mut self, mut buf: &[u8]) -> Result<()> {
+        if self.written + buf.len() as u64 > self.limit {
+            return_errno_with_message!(Errno::EFBIG, "the core dump exceeds `RLIMIT_CORE`");
+        }
+
+        self.written += buf.len() as u64;
+        self.pos += buf.len() as u64;
+
+        while !buf.is_empty() {
+            let len = self.file.write_bytes(buf)?;
+            if len == 0 {
+                return_errno_with_message!(Errno::EIO, "the core dump cannot be written");
+            }
+            buf = &buf[len..];
+        }
+
+        Ok(())
+    }
+
+    /// Skips `len` bytes, which are read as zeros.
+    pub(super) fn skip(&mut self, len: usize) -> Result<()> {
+        if !self.is_seekable {
+            const ZEROS: [u8; 512] = [0; 512];
+
+            let mut remain = len;
+            while remain > 0 {
+                let chunk_len = remain.min(ZEROS.len());
+                self.emit(&ZEROS[..chunk_len])?;
+                remain -= chunk_len;
+            }
+            return Ok(());
+        }
+
+        self.file.seek(fs::file::SeekFrom::Current(len as isize))?;
+        self.pos += len as u64;
+
+        Ok(())
+    }
+
+    /// Skips to the position `pos`.
+    pub(super) fn skip_to(&mut self, pos: u64) -> Result<()> {
+        debug_assert!(pos >= self.pos);
+        self.skip((pos - self.pos) as usize)
+    }
+
+    /// Finishes the core dump.
+    pub(super) fn finish(self) -> Result<()> {
+        // Extend the file if it ends with skipped bytes.
+        if self.is_seekable {
+            self.file.resize(self.pos as usize)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,639 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The ELF core file format.
+//!
+//! A core file consists of an ELF header, the program headers, a `PT_NOTE`
+//! segment that describes the process and its threads, and a `PT_LOAD` segment
+//! for each memory mapping.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_elf.c>
+
+use core::{ops::Range, sync::atomic::Ordering};
+
+use align_ext::AlignExt;
+use ostd::arch::cpu::context::UserContext;
+#[cfg(target_arch = "riscv64")]
+use ostd::user::UserContextApi;
+
+use super::{CoredumpFilter, dump::CoreFile};
+use crate::{
+    arch::ptrace as arch_ptrace,
+    fs::vfs::path::Path,
+    prelude::*,
+    process::signal::{HandlePendingSignal, sig_num::SigNum, signals::Signal},
+    time::timeval_t,
+    vm::{
+        perms::VmPerms,
+        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, VmMapping, Vmar},
+    },
+};
+
+/// The collected state of a process to be written into a core file.
+pub(super) struct ElfCore {
+    notes: Vec<u8>,
+    segments: Vec<Segment>,
+}
+
+/// A memory mapping to be written as a `PT_LOAD` segment.
+struct Segment {
+    range: Range<Vaddr>,
+    perms: VmPerms,
+    dump: SegmentDump,
+}
+
+/// How the content of a [`Segment`] is dumped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SegmentDump {
+    /// No content is dumped.
+    None,
+    /// Only the first page is dumped.
+    FirstPage,
+    /// Only the populated pages are dumped, and the others are left as holes.
+    Populated,
+    /// All the pages are dumped.
+    All,
+}
+
+impl Segment {
+    /// Returns the number of bytes of the segment in the core file.
+    fn file_size(&self) -> usize {
+        match self.dump {
+            SegmentDump::None => 0,
+            SegmentDump::FirstPage => PAGE_SIZE,
+            SegmentDump::Populated | SegmentDump::All => self.range.len(),
+        }
+    }
+}
+
+/// A file-backed memory mapping to be described in the `NT_FILE` note.
+struct FileMapping {
+    range: Range<Vaddr>,
+    file_offset: usize,
+    path: Path,
+}
+
+impl ElfCore {
+    /// Collects the state of the current process and thread.
+    ///
+    /// `thread_notes` describe the other threads in the process (see
+    /// [`thread_notes_of`]).
+    pub(super) fn collect(
+        signal: &dyn Signal,
+        filter: CoredumpFilter,
+        ctx: &Context,
+        user_ctx: &UserContext,
+        thread_notes: &[u8],
+    ) -> Result<Self> {
+        let user_space = ctx.user_space();
+        let vmar = user_space.vmar();
+
+        let mut segments = Vec::new();
+        let mut file_mappings = Vec::new();
+        {
+            let query_guard = vmar.query(VMAR_LOWEST_ADDR..VMAR_CAP_ADDR);
+            for mapping in query_guard.iter() {
+                segments.push(Segment {
+                    range: mapping.map_to_addr()..mapping.map_end(),
+                    perms: mapping.perms(),
+                    dump: segment_dump_of(mapping, filter),
+                });
+                if let Some(path) = mapping.path() {
+                    file_mappings.push(FileMapping {
+                        range: mapping.map_to_addr()..mapping.map_end(),
+                        file_offset: mapping.file_offset(),
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+
+        // Only dump the first page of a file-backed mapping if it starts with
+        // ELF headers. This allows the build ID to be found in the core file.
+        for segment in segments.iter_mut() {
+            if segment.dump == SegmentDump::FirstPage && !starts_with_elf_magic(vmar, segment) {
+                segment.dump = SegmentDump::None;
+            }
+        }
+
+        let (auxv, argv) = {
+            let vmar_guard = ctx.process.lock_vmar();
+            let Some(init_stack_reader) = vmar_guard.init_stack_reader() else {
+                return_errno_with_message!(Errno::ESRCH, "the process has exited");
+            };
+            (
+                read_to_end(|offset, writer| init_stack_reader.auxv(offset, writer))?,
+                read_to_end(|offset, writer| init_stack_reader.argv(offset, writer))?,
+            )
+        };
+
+        let mut notes = Vec::new();
+
+        let siginfo = signal.to_info();
+        let pr_info = elf_siginfo {
+            si_signo: siginfo.si_signo,
+            si_code: siginfo.si_code,
+            si_errno: siginfo.si_errno,
+        };
+        let (prstatus, fpregs) = prstatus_of(pr_info, signal.num(), ctx, user_ctx);
+        push_note(&mut notes, NT_PRSTATUS, prstatus.as_bytes());
+
+        let credentials = ctx.posix_thread.credentials();
+        let mut prpsinfo = elf_prpsinfo {
+            pr_state: 0,
+            pr_sname: b'R',
+            pr_zomb: 0,
+            pr_nice: ctx.process.nice().load(Ordering::Relaxed).value().get(),
+            pr_flag: 0,
+            pr_uid: u32::from(credentials.ruid()),
+            pr_gid: u32::from(credentials.rgid()),
+            pr_pid: local_id_of(ctx, ctx.process.pid()),
+            pr_ppid: local_id_of(ctx, ctx.process.parent().pid()),
+            pr_pgrp: local_id_of(ctx, ctx.process.pgid()),
+            pr_sid: local_id_of(ctx, ctx.process.sid()),
+            ..elf_prpsinfo::new_zeroed()
+        };
+        {
+            let thread_name = ctx.posix_thread.thread_name().lock();
+            let name = thread_name.name().to_bytes();
+            let len = name.len().min(prpsinfo.pr_fname.len() - 1);
+            prpsinfo.pr_fname[..len].copy_from_slice(&name[..len]);
+        }
+        {
+            // The arguments are separated by spaces instead of nul bytes.
+            let len = argv.len().min(prpsinfo.pr_psargs.len() - 1);
+            for (dst, &src) in prpsinfo.pr_psargs.iter_mut().zip(&argv[..len]) {
+                *dst = if src == 0 { b' ' } else { src };
+            }
+            let args = &mut prpsinfo.pr_psargs[..len];
+            let trimmed_len = args.len() - args.iter().rev().take_while(|&&b| b == b' ').count();
+            args[trimmed_len..].fill(0);
+        }
+        push_note(&mut notes, NT_PRPSINFO, prpsinfo.as_bytes());
+
+        push_note(&mut notes, NT_SIGINFO, siginfo.as_bytes());
+        push_note(&mut notes, NT_AUXV, &auxv);
+        push_note(&mut notes, NT_FILE, &file_note_of(&file_mappings, ctx));
+        if let Some(fpregs) = fpregs {
+            push_note(&mut notes, NT_PRFPREG, fpregs.as_bytes());
+        }
+        notes.extend_from_slice(thread_notes);
+
+        Ok(Self { notes, segments })
+    }
+
+    /// Writes the core file.
+    pub(super) fn write_to(&self, core_file: &mut CoreFile, vmar: &Vmar) -> Result<()> {
+        let Ok(phnum) = u16::try_from(self.segments.len() + 1) else {
+            return_errno_with_message!(Errno::EFBIG, "there are too many memory mappings");
+        };
+
+        let notes_offset = size_of::<Elf64Ehdr>() + size_of::<Elf64Phdr>() * phnum as usize;
+        let data_offset = (notes_offset + self.notes.len()).align_up(PAGE_SIZE);
+
+        let ehdr = Elf64Ehdr {
+            e_ident: ELF_IDENT,
+            e_type: ET_CORE,
+            e_machine: ELF_MACHINE,
+            e_version: EV_CURRENT as u32,
+            e_phoff: size_of::<Elf64Ehdr>() as u64,
+            e_ehsize: size_of::<Elf64Ehdr>() as u16,
+            e_phentsize: size_of::<Elf64Phdr>() as u16,
+            e_phnum: phnum,
+            ..Elf64Ehdr::new_zeroed()
+        };
+        core_file.emit(ehdr.as_bytes())?;
+
+        let note_phdr = Elf64Phdr {
+            p_type: PT_NOTE,
+            p_offset: notes_offset as u64,
+            p_filesz: self.notes.len() as u64,
+            p_align: 4,
+            ..Elf64Phdr::new_zeroed()
+        };
+        core_file.emit(note_phdr.as_bytes())?;
+
+        let mut offset = data_offset;
+        for segment in self.segments.iter() {
+            let load_phdr = Elf64Phdr {
+                p_type: PT_LOAD,
+                p_flags: segment_flags_of(segment.perms),
+                p_offset: offset as u64,
+                p_vaddr: segment.range.start as u64,
+                p_paddr: 0,
+                p_filesz: segment.file_size() as u64,
+                p_memsz: segment.range.len() as u64,
+                p_align: PAGE_SIZE as u64,
+            };
+            core_file.emit(load_phdr.as_bytes())?;
+            offset += segment.file_size();
+        }
+
+        core_file.emit(&self.notes)?;
+        core_file.skip_to(data_offset as u64)?;
+
+        let mut page_buf = vec![0u8; PAGE_SIZE];
+        for segment in self.segments.iter() {
+            let dump_range = segment.range.start..segment.range.start + segment.file_size();
+            for page_addr in dump_range.step_by(PAGE_SIZE) {
+                let is_dumped = if segment.dump == SegmentDump::Populated
+                    && !vmar.is_page_populated(page_addr).unwrap_or(false)
+                {
+                    false
+                } else {
+                    let mut writer = VmWriter::from(page_buf.as_mut_slice()).to_fallible();
+                    matches!(vmar.read_alien(page_addr, &mut writer), Ok(len) if len == PAGE_SIZE)
+                };
+
+                // Pages that cannot be read are left as holes, which are read
+                // as zeros.
+                if is_dumped {
+                    core_file.emit(&page_buf)?;
+                } else {
+                    core_file.skip(PAGE_SIZE)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decides how a memory mapping is dumped according to `filter`.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c>
+fn segment_dump_of(mapping: &VmMapping, filter: CoredumpFilter) -> SegmentDump {
+    let dump_all_if = |bit: CoredumpFilter| {
+        if filter.contains(bit) {
+            SegmentDump::All
+        } else {
+            SegmentDump::None
+        }
+    };
+
+    if mapping.is_dont_dump() || mapping.is_device() {
+        return SegmentDump::None;
+    }
+
+    if mapping.is_hugetlb() {
+        return if mapping.is_shared() {
+            dump_all_if(CoredumpFilter::HUGETLB_SHARED)
+        } else {
+            dump_all_if(CoredumpFilter::HUGETLB_PRIVATE)
+        };
+    }
+
+    if mapping.is_shared() {
+        return if mapping.path().is_none() {
+            dump_all_if(CoredumpFilter::ANON_SHARED)
+        } else {
+            dump_all_if(CoredumpFilter::MAPPED_SHARED)
+        };
+    }
+
+    if mapping.path().is_none() {
+        return if filter.contains(CoredumpFilter::ANON_PRIVATE) {
+            SegmentDump::Populated
+        } else {
+            SegmentDump::None
+        };
+    }
+
+    if filter.contains(CoredumpFilter::MAPPED_PRIVATE) {
+        return SegmentDump::All;
+    }
+
+    // A writable private file-backed mapping may contain anonymous pages that
+    // are copied on write.
+    //
+    // TODO: Dump only the copied pages instead of the whole mapping.
+    if filter.contains(CoredumpFilter::ANON_PRIVATE) && mapping.perms().contains(VmPerms::WRITE) {
+        return SegmentDump::All;
+    }
+
+    if filter.contains(CoredumpFilter::ELF_HEADERS)
+        && mapping.file_offset() == 0
+        && mapping.perms().contains(VmPerms::READ)
+    {
+        return SegmentDump::FirstPage;
+    }
+
+    SegmentDump::None
+}
+
+fn starts_with_elf_magic(vmar: &Vmar, segment: &Segment) -> bool {
+    let mut magic = [0u8; 4];
+    let mut writer = VmWriter::from(magic.as_mut_slice()).to_fallible();
+    matches!(vmar.read_alien(segment.range.start, &mut writer), Ok(len) if len == magic.len())
+        && magic == ELF_IDENT[..magic.len()]
+}
+
+fn segment_flags_of(perms: VmPerms) -> u32 {
+    let mut flags = 0;
+    if perms.contains(VmPerms::READ) {
+        flags |= PF_R;
+    }
+    if perms.contains(VmPerms::WRITE) {
+        flags |= PF_W;
+    }
+    if perms.contains(VmPerms::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+/// Builds the description of the `NT_FILE` note.
+///
+/// The description consists of the number of mappings, the page size, an
+/// array of `(start, end, page_offset)` tuples, and the nul-terminated paths.
+fn file_note_of(file_mappings: &[FileMapping], ctx: &Context) -> Vec<u8> {
+    let mut desc = Vec::new();
+    desc.extend_from_slice(&(file_mappings.len() as u64).to_ne_bytes());
+    desc.extend_from_slice(&(PAGE_SIZE as u64).to_ne_bytes());
+    for mapping in file_mappings.iter() {
+        desc.extend_from_slice(&(mapping.range.start as u64).to_ne_bytes());
+        desc.extend_from_slice(&(mapping.range.end as u64).to_ne_bytes());
+        desc.extend_from_slice(&((mapping.file_offset / PAGE_SIZE) as u64).to_ne_bytes());
+    }
+
+    let fs_ref = ctx.thread_local.borrow_fs();
+    let path_resolver = fs_ref.resolver().read();
+    for mapping in file_mappings.iter() {
+        let abs_path = path_resolver.make_abs_path(&mapping.path).into_string();
+        desc.extend_from_slice(abs_path.as_bytes());
+        desc.push(0);
+    }
+
+    desc
+}
+
+/// Builds the notes that describe the current thread, which is not the
+/// dumping thread, in a core dump caused by `signum`.
+///
+/// The notes are `NT_PRSTATUS` and, if available, `NT_PRFPREG`.
+pub(super) fn thread_notes_of(signum: SigNum, ctx: &Context, user_ctx: &UserContext) -> Vec<u8> {
+    let pr_info = elf_siginfo {
+        si_signo: signum.as_u8() as i32,
+        si_code: 0,
+        si_errno: 0,
+    };
+    let (prstatus, fpregs) = prstatus_of(pr_info, signum, ctx, user_ctx);
+
+    let mut notes = Vec::new();
+    push_note(&mut notes, NT_PRSTATUS, prstatus.as_bytes());
+    if let Some(fpregs) = fpregs {
+        push_note(&mut notes, NT_PRFPREG, fpregs.as_bytes());
+    }
+    notes
+}
+
+/// Returns the status and the floating-point registers of the current thread.
+fn prstatus_of(
+    pr_info: elf_siginfo,
+    signum: SigNum,
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> (elf_prstatus, Option<arch_ptrace::CUserFpregsStruct>) {
+    let (pr_reg, fpregs) = current_regs(ctx, user_ctx);
+
+    // Like Linux, the main thread reports the CPU times of the whole process.
+    let prof_clock = if ctx.posix_thread.tid() == ctx.process.pid() {
+        ctx.process.prof_clock()
+    } else {
+        ctx.posix_thread.prof_clock()
+    };
+
+    let prstatus = elf_prstatus {
+        pr_info,
+        pr_cursig: signum.as_u8() as u16,
+        pr_sigpend: u64::from(ctx.pending_signals()),
+        pr_sighold: u64::from(ctx.posix_thread.sig_mask()),
+        pr_pid: local_id_of(ctx, ctx.posix_thread.tid()),
+        pr_ppid: local_id_of(ctx, ctx.process.parent().pid()),
+        pr_pgrp: local_id_of(ctx, ctx.process.pgid()),
+        pr_sid: local_id_of(ctx, ctx.process.sid()),
+        pr_utime: prof_clock.user_clock().read_time().into(),
+        pr_stime: prof_clock.kernel_clock().read_time().into(),
+        pr_cutime: ctx.process.reaped_children_stats().lock().get().0.into(),
+        pr_cstime: ctx.process.reaped_children_stats().lock().get().1.into(),
+        pr_reg,
+        pr_fpvalid: fpregs.is_some() as i32,
+        ..elf_prstatus::new_zeroed()
+    };
+
+    (prstatus, fpregs)
+}
+
+/// Appends a note with the name `CORE`.
+fn push_note(notes: &mut Vec<u8>, note_type: u32, desc: &[u8]) {
+    const NAME: &[u8] = b"CORE\0";
+
+    let nhdr = Elf64Nhdr {
+        n_namesz: NAME.len() as u32,
+        n_descsz: desc.len() as u32,
+        n_type: note_type,
+    };
+    notes.extend_from_slice(nhdr.as_bytes());
+    notes.extend_from_slice(NAME);
+    notes.resize(notes.len().align_up(4), 0);
+    notes.extend_from_slice(desc);
+    notes.resize(notes.len().align_up(4), 0);
+}
+
+/// Reads all the bytes with `read_at` until it returns zero.
+fn read_to_end<F>(read_at: F) -> Result<Vec<u8>>
+where
+    F: Fn(usize, &mut VmWriter) -> Result<usize>,
+{
+    let mut bytes = Vec::new();
+    let mut buf = [0u8; 256];
+    loop {
+        let len = read_at(
+            bytes.len(),
+            &mut VmWriter::from(buf.as_mut_slice()).to_fallible(),
+        )?;
+        if len == 0 {
+            return Ok(bytes);
+        }
+        bytes.extend_from_slice(&buf[..len]);
+    }
+}
+
+fn local_id_of(ctx: &Context, id: u32) -> u32 {
+    ctx.process.pid_ns().local_id_of(id).unwrap_or(0)
+}
+
+/// Returns the general-purpose and floating-point registers of the current
+/// thread.
+#[cfg(target_arch = "x86_64")]
+fn current_regs(
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> (
+    arch_ptrace::CUserRegsStruct,
+    Option<arch_ptrace::CUserFpregsStruct>,
+) {
+    let supp = ctx.thread_local.supp_user_context();
+
+    let mut regs = arch_ptrace::CUserRegsStruct::from_regs(
+        user_ctx.general_regs(),
+        supp.fs_base().get(),
+        supp.gs_base().get(),
+    );
+    regs.orig_rax = ctx.thread_local.orig_syscall_ret().unwrap_or(usize::MAX);
+    let fpregs = arch_ptrace::CUserFpregsStruct::from_fpu(&supp.fpu().get());
+
+    (regs, Some(fpregs))
+}
+
+/// Returns the general-purpose and floating-point registers of the current
+/// thread.
+#[cfg(target_arch = "riscv64")]
+fn current_regs(
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> (
+    arch_ptrace::CUserRegsStruct,
+    Option<arch_ptrace::CUserFpregsStruct>,
+) {
+    let supp = ctx.thread_local.supp_user_context();
+
+    let regs = arch_ptrace::CUserRegsStruct::from_regs(
+        user_ctx.general_regs(),
+        user_ctx.instruction_pointer(),
+    );
+    let fpregs = arch_ptrace::CUserFpregsStruct::from_fpu(&supp.fpu().get()).ok();
+
+    (regs, fpregs)
+}
+
+const ELF_IDENT: [u8; 16] = [
+    0x7f,
+    b'E',
+    b'L',
+    b'F',
+    ELFCLASS64,
+    ELFDATA2LSB,
+    EV_CURRENT,
+    ELFOSABI_NONE,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+];
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+const ELFOSABI_NONE: u8 = 0;
+
+const ET_CORE: u16 = 4;
+
+#[cfg(target_arch = "x86_64")]
+const ELF_MACHINE: u16 = 62; // EM_X86_64
+#[cfg(target_arch = "riscv64")]
+const ELF_MACHINE: u16 = 243; // EM_RISCV
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+
+const PF_X: u32 = 1 << 0;
+const PF_W: u32 = 1 << 1;
+const PF_R: u32 = 1 << 2;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRFPREG: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_SIGINFO: u32 = 0x5349_4749;
+const NT_FILE: u32 = 0x4649_4c45;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct Elf64Ehdr {
+    e_ident: [u8; 16],
+    e_type: u16,
+    e_machine: u16,
+    e_version: u32,
+    e_entry: u64,
+    e_phoff: u64,
+    e_shoff: u64,
+    e_flags: u32,
+    e_ehsize: u16,
+    e_phentsize: u16,
+    e_phnum: u16,
+    e_shentsize: u16,
+    e_shnum: u16,
+    e_shstrndx: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct Elf64Phdr {
+    p_type: u32,
+    p_flags: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_paddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+    p_align: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct Elf64Nhdr {
+    n_namesz: u32,
+    n_descsz: u32,
+    n_type: u32,
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/elfcore.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct elf_siginfo {
+    si_signo: i32,
+    si_code: i32,
+    si_errno: i32,
+}
+
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct elf_prstatus {
+    pr_info: elf_siginfo,
+    pr_cursig: u16,
+    pr_sigpend: u64,
+    pr_sighold: u64,
+    pr_pid: u32,
+    pr_ppid: u32,
+    pr_pgrp: u32,
+    pr_sid: u32,
+    pr_utime: timeval_t,
+    pr_stime: timeval_t,
+    pr_cutime: timeval_t,
+    pr_cstime: timeval_t,
+    pr_reg: arch_ptrace::CUserRegsStruct,
+    pr_fpvalid: i32,
+}
+
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct elf_prpsinfo {
+    pr_state: i8,
+    pr_sname: u8,
+    pr_zomb: i8,
+    pr_nice: i8,
+    pr_flag: u64,
+    pr_uid: u32,
+    pr_gid: u32,
+    pr_pid: u32,
+    pr_ppid: u32,
+    pr_pgrp: u32,
+    pr_sid: u32,
+    pr_fname: [u8; 16],
+    pr_psargs: [u8; 80],
+}
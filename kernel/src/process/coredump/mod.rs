@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Core dumps.
+//!
+//! When a process is terminated by a signal whose default action is to dump
+//! core (e.g., `SIGSEGV` or `SIGABRT`), an ELF core file that describes the
+//! state of the process is written, either to a file or to the standard input
+//! of a user-mode helper, as specified by `/proc/sys/kernel/core_pattern`.
+//!
+//! No core dump is written if the process is not dumpable (see
+//! `PR_SET_DUMPABLE`) or if `RLIMIT_CORE` is too small. Which memory mappings
+//! are included is controlled by `/proc/[pid]/coredump_filter` and
+//! `madvise(MADV_DONTDUMP)`.
+//!
+//! Like Linux, the dumping thread kills the other threads in the process and
+//! waits for them before dumping core (see [`CoreState`]). So the core dump
+//! describes the registers of every thread.
+
+use alloc::borrow::Cow;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicUsize, Ordering};
+
+use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
+use bitflags::bitflags;
+use ostd::{arch::cpu::context::UserContext, sync::WaitQueue};
+
+use crate::{
+    prelude::*,
+    process::{
+        TermStatus,
+        posix_thread::sigkill_other_threads,
+        signal::{sig_num::SigNum, signals::Signal},
+    },
+};
+
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+mod dump;
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+mod elf;
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+mod pattern;
+
+/// Whether a process can dump core.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum Dumpable {
+    /// The process cannot dump core.
+    Disable = 0,
+    /// The process dumps core as the user of the process.
+    User = 1,
+    /// The process dumps core as root.
+    Root = 2,
+}
+
+define_atomic_version_of_integer_like_type!(Dumpable, try_from = true, {
+    /// An atomic version of `Dumpable`.
+    #[derive(Debug)]
+    pub struct AtomicDumpable(AtomicU8);
+});
+
+impl From<Dumpable> for u8 {
+    fn from(value: Dumpable) -> Self {
+        value as _
+    }
+}
+
+bitflags! {
+    /// The types of memory mappings to be written into core dumps.
+    ///
+    /// Mappings excluded by `madvise(MADV_DONTDUMP)` and device mappings are
+    /// never written, regardless of the filter.
+    //
+    // Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+    pub struct CoredumpFilter: u32 {
+        /// Private anonymous mappings.
+        const ANON_PRIVATE = 1 << 0;
+        /// Shared anonymous mappings.
+        const ANON_SHARED = 1 << 1;
+        /// Private file-backed mappings.
+        const MAPPED_PRIVATE = 1 << 2;
+        /// Shared file-backed mappings.
+        const MAPPED_SHARED = 1 << 3;
+        /// The first page of private file-backed mappings that start with ELF headers.
+        const ELF_HEADERS = 1 << 4;
+        /// Private huge page mappings.
+        const HUGETLB_PRIVATE = 1 << 5;
+        /// Shared huge page mappings.
+        const HUGETLB_SHARED = 1 << 6;
+        /// Private DAX mappings.
+        const DAX_PRIVATE = 1 << 7;
+        /// Shared DAX mappings.
+        const DAX_SHARED = 1 << 8;
+    }
+}
+
+impl Default for CoredumpFilter {
+    fn default() -> Self {
+        Self::ANON_PRIVATE | Self::ANON_SHARED | Self::ELF_HEADERS | Self::HUGETLB_PRIVATE
+    }
+}
+
+impl From<u32> for CoredumpFilter {
+    fn from(value: u32) -> Self {
+        Self::from_bits_truncate(value)
+    }
+}
+
+impl From<CoredumpFilter> for u32 {
+    fn from(value: CoredumpFilter) -> Self {
+        value.bits()
+    }
+}
+
+define_atomic_version_of_integer_like_type!(CoredumpFilter, {
+    /// An atomic version of `CoredumpFilter`.
+    #[derive(Debug)]
+    pub struct AtomicCoredumpFilter(AtomicU32);
+});
+
+/// The maximum length of the core pattern, including the trailing nul byte.
+pub const CORENAME_MAX_SIZE: usize = 128;
+
+static CORE_PATTERN: RwLock<Cow<'static, str>> = RwLock::new(Cow::Borrowed("core"));
+
+/// Returns the core pattern.
+pub fn core_pattern() -> String {
+    CORE_PATTERN.read().to_string()
+}
+
+/// Sets the core pattern.
+///
+/// The pattern is truncated if it is longer than [`CORENAME_MAX_SIZE`] - 1 bytes.
+pub fn set_core_pattern(pattern: &str) {
+    let mut len = pattern.len().min(CORENAME_MAX_SIZE - 1);
+    while !pattern.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    *CORE_PATTERN.write() = Cow::Owned(pattern[..len].to_string());
+}
+
+/// Dumps core for the current process, which is being terminated by `signal`.
+///
+/// Returns whether the core has been dumped.
+pub(in crate::process) fn do_coredump(
+    signal: &dyn Signal,
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))] {
+            match dump::dump_core(signal, ctx, user_ctx) {
+                Ok(()) => {
+                    ctx.process
+                        .status()
+                        .set_exit_code(TermStatus::Dumped(signal.num()).as_u32());
+                    true
+                }
+                Err(err) => {
+                    debug!("PID {}: no core dumped: {:?}", ctx.process.pid(), err);
+                    false
+                }
+            }
+        } else {
+            // The ELF core format requires the register layouts of the
+            // architecture, which are not available yet.
+            let _ = (signal, ctx, user_ctx);
+            false
+        }
+    }
+}
+
+/// The state of a core dump in progress.
+///
+/// Before dumping core, the dumping thread kills the other threads in the
+/// process. On their way to exit, the threads report their registers and wait
+/// until the core dump is finished, so that their exits do not change the
+/// memory of the process (e.g., by clearing `clear_child_tid`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c>
+pub(in crate::process) struct CoreState {
+    signum: SigNum,
+    /// The number of threads that have not reported their registers.
+    nr_running: AtomicUsize,
+    /// The notes that describe the threads other than the dumping thread.
+    thread_notes: Mutex<Vec<u8>>,
+    is_done: AtomicBool,
+    wait_queue: WaitQueue,
+}
+
+impl CoreState {
+    /// Kills the other threads in the current process for a core dump.
+    ///
+    /// Like `exit_group`, no more threads can be created afterward. The exit
+    /// code of the process is set to the one of being killed by `signum`.
+    ///
+    /// The returned guard finishes the core dump when it is dropped.
+    fn zap_threads(signum: SigNum, ctx: &Context) -> Result<CoreDumpGuard> {
+        let mut tasks = ctx.process.tasks().lock();
+        if tasks.has_exited_group() || tasks.in_execve() || tasks.core_state().is_some() {
+            return_errno_with_message!(Errno::EAGAIN, "the process is already exiting");
+        }
+
+        let nr_running = tasks
+            .as_slice()
+            .iter()
+            .enumerate()
+            .filter(|(index, task)| {
+                !core::ptr::eq(task.as_ref(), ctx.task) && (*index != 0 || !tasks.has_exited_main())
+            })
+            .count();
+        let core_state = Arc::new(Self {
+            signum,
+            nr_running: AtomicUsize::new(nr_running),
+            thread_notes: Mutex::new(Vec::new()),
+            is_done: AtomicBool::new(false),
+            wait_queue: WaitQueue::new(),
+        });
+
+        tasks.set_core_state(core_state.clone());
+        sigkill_other_threads(ctx.task, &tasks);
+        tasks.set_exited_group();
+        ctx.process
+            .status()
+            .set_exit_code(TermStatus::Killed(signum).as_u32());
+
+        Ok(CoreDumpGuard(core_state))
+    }
+
+    /// Reports the registers of the current thread, which is exiting, and
+    /// waits until the core dump is finished.
+    pub(in crate::process) fn report_and_wait(&self, ctx: &Context, user_ctx: &UserContext) {
+        if self.is_done.load(Ordering::Acquire) {
+            return;
+        }
+
+        #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+        self.thread_notes
+            .lock()
+            .extend_from_slice(&elf::thread_notes_of(self.signum, ctx, user_ctx));
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "riscv64")))]
+        let _ = (self.signum, ctx, user_ctx);
+
+        if self.nr_running.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.wait_queue.wake_all();
+        }
+        self.wait_queue
+            .wait_until(|| self.is_done.load(Ordering::Acquire).then_some(()));
+    }
+}
+
+/// A guard of a core dump in progress, which is created by the dumping thread.
+struct CoreDumpGuard(Arc<CoreState>);
+
+impl CoreDumpGuard {
+    /// Waits for the other threads to report their registers.
+    ///
+    /// Returns the notes that describe the other threads.
+    fn wait_for_threads(&self) -> Vec<u8> {
+        let core_state = &self.0;
+        core_state
+            .wait_queue
+            .wait_until(|| (core_state.nr_running.load(Ordering::Acquire) == 0).then_some(()));
+        core::mem::take(&mut *core_state.thread_notes.lock())
+    }
+}
+
+impl Drop for CoreDumpGuard {
+    fn drop(&mut self) {
+        self.0.is_done.store(true, Ordering::Release);
+        self.0.wait_queue.wake_all();
+    }
+}
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The core pattern, which determines where core dumps are written to.
+//!
+//! The pattern is configured by `/proc/sys/kernel/core_pattern`. If the
+//! pattern starts with `|`, the rest of the pattern is a command line of a
+//! user-mode helper, which receives the core dump from its standard input.
+//! Otherwise, the pattern is the path of the core file.
+//!
+//! The pattern may contain the following `%` specifiers:
+//! - `%%`: A single `%` character.
+//! - `%p`: The PID of the dumping process, in its own PID namespace.
+//! - `%P`: The PID of the dumping process, in the initial PID namespace.
+//! - `%i`: The TID of the dumping thread, in its own PID namespace.
+//! - `%I`: The TID of the dumping thread, in the initial PID namespace.
+//! - `%u`: The real UID of the dumping process.
+//! - `%g`: The real GID of the dumping process.
+//! - `%d`: The dumpable mode of the dumping process.
+//! - `%s`: The number of the signal that causes the dump.
+//! - `%t`: The time of the dump, in seconds since the Epoch.
+//! - `%h`: The hostname.
+//! - `%e`: The name of the dumping thread.
+//! - `%f`: The file name of the executable.
+//! - `%E`: The path of the executable, with `/` replaced by `!`.
+//! - `%c`: The soft resource limit of the core file size.
+//!
+//! Unknown specifiers are dropped.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+
+use super::{Dumpable, core_pattern};
+use crate::{prelude::*, process::signal::sig_num::SigNum, time::clocks::RealTimeCoarseClock};
+
+/// The target of a core dump.
+pub(super) enum CoreTarget {
+    /// A core file at the path.
+    File(String),
+    /// A user-mode helper with the arguments, the first of which is the path
+    /// of the helper program.
+    Pipe(Vec<String>),
+}
+
+/// Expands the core pattern for the current thread.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c>
+pub(super) fn expand_core_pattern(
+    sig_num: SigNum,
+    dumpable: Dumpable,
+    core_limit: u64,
+    ctx: &Context,
+) -> CoreTarget {
+    let pattern = core_pattern();
+    let (is_pipe, pattern) = match pattern.strip_prefix('|') {
+        Some(command_line) => (true, command_line),
+        None => (false, pattern.as_str()),
+    };
+
+    let mut args = vec![String::new()];
+    let mut has_output = false;
+    let mut was_space = false;
+
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        // The command line of a user-mode helper is split into arguments at
+        // the spaces in the pattern. Spaces from the specifiers are kept.
+        if is_pipe {
+            if ch.is_ascii_whitespace() {
+                was_space |= has_output;
+                continue;
+            } else if was_space {
+                was_space = false;
+                args.push(String::new());
+            }
+        }
+        has_output = true;
+
+        let arg = args.last_mut().unwrap();
+        if ch != '%' {
+            arg.push(ch);
+            continue;
+        }
+
+        let Some(specifier) = chars.next() else {
+            break;
+        };
+        expand_specifier(arg, specifier, sig_num, dumpable, core_limit, ctx);
+    }
+
+    if is_pipe {
+        CoreTarget::Pipe(args)
+    } else {
+        CoreTarget::File(args.pop().unwrap())
+    }
+}
+
+fn expand_specifier(
+    arg: &mut String,
+    specifier: char,
+    sig_num: SigNum,
+    dumpable: Dumpable,
+    core_limit: u64,
+    ctx: &Context,
+) {
+    let pid_ns = ctx.process.pid_ns();
+
+    match specifier {
+        '%' => arg.push('%'),
+        'p' => {
+            let pid = pid_ns.local_id_of(ctx.process.pid()).unwrap();
+            arg.push_str(&pid.to_string());
+        }
+        'P' => arg.push_str(&ctx.process.pid().to_string()),
+        'i' => {
+            let tid = pid_ns.local_id_of(ctx.posix_thread.tid()).unwrap();
+            arg.push_str(&tid.to_string());
+        }
+        'I' => arg.push_str(&ctx.posix_thread.tid().to_string()),
+        'u' => {
+            let uid = ctx.posix_thread.credentials().ruid();
+            arg.push_str(&u32::from(uid).to_string());
+        }
+        'g' => {
+            let gid = ctx.posix_thread.credentials().rgid();
+            arg.push_str(&u32::from(gid).to_string());
+        }
+        'd' => arg.push_str(&(dumpable as u8).to_string()),
+        's' => arg.push_str(&sig_num.as_u8().to_string()),
+        't' => {
+            let now = RealTimeCoarseClock::get().read_time();
+            arg.push_str(&now.as_secs().to_string());
+        }
+        'h' => {
+            let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+            let uts_name = ns_proxy.unwrap().uts_ns().uts_name();
+            push_escaped(arg, &String::from_utf8_lossy(uts_name.nodename()));
+        }
+        'e' => {
+            let thread_name = ctx.posix_thread.thread_name().lock();
+            push_escaped(arg, &thread_name.name().to_string_lossy());
+        }
+        'f' | 'E' => {
+            let executable_path = {
+                let user_space = ctx.user_space();
+                let executable_file = user_space.vmar().process_vm().executable_file().clone();
+                let fs_ref = ctx.thread_local.borrow_fs();
+                let path_resolver = fs_ref.resolver().read();
+                path_resolver.make_abs_path(&executable_file).into_string()
+            };
+            if specifier == 'f' {
+                let file_name = executable_path.rsplit('/').next().unwrap();
+                push_escaped(arg, file_name);
+            } else {
+                push_escaped(arg, &executable_path);
+            }
+        }
+        'c' => arg.push_str(&core_limit.to_string()),
+        _ => {}
+    }
+}
+
+/// Pushes a component that is not allowed to change the directory of the
+/// core file.
+///
+/// All `/` characters are replaced by `!`. In addition, a component cannot be
+/// empty, `.`, or `..`.
+fn push_escaped(arg: &mut String, component: &str) {
+    match component {
+        "" => arg.push('!'),
+        "." => arg.push('!'),
+        ".." => arg.push_str("!."),
+        _ => arg.extend(component.chars().map(|ch| if ch == '/' { '!' } else { ch })),
+    }
+}
@@ -13,6 +13,7 @@ use crate::{
         AMBIENT_CAPSET,
         capabilities::{AtomicCapSet, CapSet},
     },
+    security::lsm::landlock::LandlockDomain,
 };
 
 #[derive(Debug)]
@@ -75,6 +76,9 @@ pub(super) struct Credentials_ {
 
     /// Secure bits.
     securebits: AtomicSecureBits,
+
+    /// The Landlock domain enforced on the thread, if any.
+    landlock_domain: RwLock<Option<Arc<LandlockDomain>>>,
 }
 
 impl Credentials_ {
@@ -100,6 +104,7 @@ impl Credentials_ {
             effective_capset: AtomicCapSet::new(capset),
             bounding_capset: AtomicCapSet::new(CapSet::all()),
             securebits: AtomicSecureBits::new(SecureBits::new_empty()),
+            landlock_domain: RwLock::new(None),
         }
     }
 
@@ -589,6 +594,16 @@ impl Credentials_ {
 
         self.securebits.try_store(securebits, Ordering::Relaxed)
     }
+
+    //  ******* Landlock methods *******
+
+    pub(super) fn landlock_domain(&self) -> Option<Arc<LandlockDomain>> {
+        self.landlock_domain.read().clone()
+    }
+
+    pub(super) fn set_landlock_domain(&self, domain: Arc<LandlockDomain>) {
+        *self.landlock_domain.write() = Some(domain);
+    }
 }
 
 impl Clone for Credentials_ {
@@ -608,6 +623,7 @@ impl Clone for Credentials_ {
             effective_capset: self.effective_capset.clone(),
             bounding_capset: self.bounding_capset.clone(),
             securebits: self.securebits.clone(),
+            landlock_domain: RwLock::new(self.landlock_domain.read().clone()),
         }
     }
 }
@@ -26,7 +26,8 @@ use crate::prelude::*;
 /// - filesystem user ID and group ID (Linux-specific);
 /// - supplementary group IDs;
 /// - Linux capabilities;
-/// - secure bits.
+/// - secure bits;
+/// - the Landlock domain.
 pub struct Credentials<R = FullOp>(Arc<Credentials_>, R);
 
 // TODO: Support the ambient capability set.
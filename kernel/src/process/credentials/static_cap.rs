@@ -5,7 +5,7 @@ use aster_rights_proc::require;
 use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 
 use super::{Credentials, Gid, SecureBits, Uid, capabilities::CapSet, credentials_::Credentials_};
-use crate::prelude::*;
+use crate::{prelude::*, security::lsm::landlock::LandlockDomain};
 
 impl<R: TRights> Credentials<R> {
     /// Creates a root `Credentials`.
@@ -371,4 +371,22 @@ impl<R: TRights> Credentials<R> {
     pub fn set_securebits(&self, securebits: SecureBits) -> Result<()> {
         self.0.set_securebits(securebits)
     }
+
+    // *********** Landlock methods **********
+
+    /// Gets the Landlock domain.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn landlock_domain(&self) -> Option<Arc<LandlockDomain>> {
+        self.0.landlock_domain()
+    }
+
+    /// Sets the Landlock domain.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn set_landlock_domain(&self, domain: Arc<LandlockDomain>) {
+        self.0.set_landlock_domain(domain)
+    }
 }
@@ -2,7 +2,7 @@
 
 use aster_rights::ReadWriteOp;
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GsBase};
+use ostd::arch::cpu::context::{FsBase, GsBase, TlsEntries};
 use ostd::{
     arch::cpu::context::{FpuContext, GeneralRegs, UserContext},
     mm::VmIo,
@@ -12,10 +12,18 @@ use ostd::{
 
 use super::process_vm::activate_vmar;
 use crate::{
-    fs::vfs::{inode::Inode, path::Path},
+    fs::{
+        file::{
+            AccessMode, FileLike, InodeHandle, StatusFlags,
+            file_table::{FdFlags, FileDesc},
+        },
+        vfs::{inode::Inode, path::Path},
+    },
     prelude::*,
     process::{
-        ContextUnshareAdminApi, Credentials, Process, pid_table,
+        ContextSetNsAdminApi, ContextUnshareAdminApi, Credentials, NsProxy, Process, TimeNamespace,
+        coredump::Dumpable,
+        pid_table,
         posix_thread::{
             AsPosixThread, ContextPthreadAdminApi, ThreadLocal, ThreadName, ptrace::PtraceEvent,
             sigkill_other_threads,
@@ -57,12 +65,46 @@ pub fn do_execve(
         envp
     );
 
-    let program_to_load =
+    let mut program_to_load =
         ProgramToLoad::build_from_file(elf_file.clone(), &path_resolver, argv, envp)?;
+    let creds_file = program_to_load.creds_file().clone();
+
+    // Open the binary for its interpreter if a binfmt_misc rule requires it. The descriptor is
+    // the lowest one that will be free once the files with `FD_CLOEXEC` are closed.
+    let exec_file = if let Some(binary) = program_to_load.binary_to_open() {
+        // Like Linux, the binary is opened with the execute permission, so the read permission
+        // is not required.
+        let file: Arc<dyn FileLike> = Arc::new(InodeHandle::new_unchecked_access(
+            binary.clone(),
+            AccessMode::O_RDONLY,
+            StatusFlags::empty(),
+        )?);
+        let fd = ctx
+            .thread_local
+            .borrow_file_table()
+            .unwrap()
+            .read()
+            .min_free_fd_on_exec();
+        program_to_load.set_exec_fd(fd);
+        Some((fd, file))
+    } else {
+        None
+    };
 
-    let new_vmar = VmarHandle::new(ProcessVm::new(elf_file.clone()));
+    let new_vmar = VmarHandle::new(ProcessVm::new(
+        elf_file.clone(),
+        program_to_load.vmar_cap_addr(),
+    ));
     let elf_load_info = program_to_load.load_to_vmar(&new_vmar, &path_resolver)?;
 
+    // The new program runs in the time namespace for children.
+    let new_ns_proxy = ctx.thread_local.borrow_ns_proxy().unwrap().new_exec();
+    let new_time_ns = new_ns_proxy.time_ns();
+    // The vDSO has been mapped for the initial time namespace.
+    if !Arc::ptr_eq(new_time_ns, TimeNamespace::get_init_singleton()) {
+        new_time_ns.enter(&new_vmar)?;
+    }
+
     // Ensure no other thread is concurrently performing exit_group or execve.
     // If such an operation is in progress, return EAGAIN.
     let mut task_set = ctx.process.tasks().lock();
@@ -86,10 +128,12 @@ pub fn do_execve(
     let res = do_execve_no_return(
         ctx,
         user_context,
-        elf_file,
+        creds_file,
+        exec_file,
         thread_name,
         new_vmar,
         &elf_load_info,
+        new_ns_proxy,
     );
 
     if res.is_ok() {
@@ -119,10 +163,21 @@ fn read_cstring_vec(
     let mut res = Vec::new();
     let mut read_addr = array_ptr;
 
+    // The pointers of 32-bit programs are 32-bit.
+    let ptr_size = if ctx.thread_local.in_compat_syscall() {
+        size_of::<u32>()
+    } else {
+        size_of::<usize>()
+    };
+
     let user_space = ctx.user_space();
     for _ in 0..max_string_number {
-        let cstring_ptr = user_space.read_val::<usize>(read_addr)?;
-        read_addr += 8;
+        let cstring_ptr = if ptr_size == size_of::<u32>() {
+            user_space.read_val::<u32>(read_addr)? as usize
+        } else {
+            user_space.read_val::<usize>(read_addr)?
+        };
+        read_addr += ptr_size;
 
         if cstring_ptr == 0 {
             return Ok(res);
@@ -146,10 +201,12 @@ fn read_cstring_vec(
 fn do_execve_no_return(
     ctx: &Context,
     user_context: &mut UserContext,
-    elf_file: Path,
+    creds_file: Path,
+    exec_file: Option<(FileDesc, Arc<dyn FileLike>)>,
     thread_name: ThreadName,
     new_vmar: VmarHandle,
     elf_load_info: &ElfLoadInfo,
+    new_ns_proxy: Arc<NsProxy>,
 ) -> Result<()> {
     let Context {
         process,
@@ -168,10 +225,21 @@ fn do_execve_no_return(
     // This prevents race conditions when checking access permissions while opening
     // `/proc/[pid]/mem` or `/proc/[pid]/maps`.
     let (vmar_guard, old_vmar) = activate_vmar(ctx, new_vmar);
-    apply_caps_from_exec(process, ctx.credentials_mut(), elf_file.inode())?;
+    apply_caps_from_exec(
+        process,
+        ctx.credentials_mut(),
+        creds_file.inode(),
+        posix_thread.no_new_privs(),
+    )?;
+    inherit_coredump_settings(ctx, vmar_guard.unwrap().process_vm(), old_vmar.process_vm());
     drop(vmar_guard);
     drop(old_vmar);
 
+    // Switch to the new namespaces, which only differ in the time namespace.
+    if !Arc::ptr_eq(&new_ns_proxy, thread_local.borrow_ns_proxy().unwrap()) {
+        ctx.set_ns_proxy(new_ns_proxy);
+    }
+
     // After the program has been successfully loaded, the virtual memory of the current process
     // is initialized. Hence, it is necessary to clear the previously recorded robust list.
     *thread_local.robust_list().borrow_mut() = None;
@@ -184,7 +252,7 @@ fn do_execve_no_return(
     reset_vfork_child(process);
 
     // Unshare file descriptor table and close files with O_CLOEXEC flag.
-    unshare_and_close_files(ctx);
+    unshare_and_close_files(ctx, exec_file);
 
     // Set the thread name.
     *posix_thread.thread_name().lock() = thread_name;
@@ -291,10 +359,15 @@ fn set_cpu_context(
     {
         supp.fs_base().set(FsBase::default());
         supp.gs_base().set(GsBase::default());
+        supp.tls_entries().set(TlsEntries::default());
     }
     #[cfg(not(target_arch = "x86_64"))]
     user_context.set_tls_pointer(0);
 
+    // Run 32-bit programs in the compatibility mode.
+    #[cfg(target_arch = "x86_64")]
+    user_context.set_compat_mode(elf_load_info.is_compat);
+
     // Set the new instruction pointer to the ELF entry point.
     user_context.set_instruction_pointer(elf_load_info.entry_point as _);
     debug!("entry_point: 0x{:x}", elf_load_info.entry_point);
@@ -305,14 +378,16 @@ fn set_cpu_context(
 
 /// Sets the UID and GID in the credentials according to the ELF inode.
 ///
-/// The capabilities will be updated accordingly.
+/// The capabilities will be updated accordingly. If `no_new_privs` is set,
+/// the `set_uid` and `set_gid` bits of the ELF inode are ignored.
 fn apply_caps_from_exec(
     process: &Process,
     credentials: Credentials<ReadWriteOp>,
     elf_inode: &Arc<dyn Inode>,
+    no_new_privs: bool,
 ) -> Result<()> {
-    set_uid_from_elf(process, &credentials, elf_inode)?;
-    set_gid_from_elf(process, &credentials, elf_inode)?;
+    set_uid_from_elf(process, &credentials, elf_inode, no_new_privs)?;
+    set_gid_from_elf(process, &credentials, elf_inode, no_new_privs)?;
     credentials.set_keep_capabilities(false)?;
 
     Ok(())
@@ -326,8 +401,9 @@ fn set_uid_from_elf(
     current: &Process,
     credentials: &Credentials<ReadWriteOp>,
     elf_inode: &Arc<dyn Inode>,
+    no_new_privs: bool,
 ) -> Result<()> {
-    if elf_inode.mode()?.has_set_uid() {
+    if !no_new_privs && elf_inode.mode()?.has_set_uid() {
         let uid = elf_inode.owner()?;
         credentials.set_euid(uid);
 
@@ -347,8 +423,9 @@ fn set_gid_from_elf(
     current: &Process,
     credentials: &Credentials<ReadWriteOp>,
     elf_inode: &Arc<dyn Inode>,
+    no_new_privs: bool,
 ) -> Result<()> {
-    if elf_inode.mode()?.has_set_gid() {
+    if !no_new_privs && elf_inode.mode()?.has_set_gid() {
         let gid = elf_inode.group()?;
         credentials.set_egid(gid);
 
@@ -360,6 +437,31 @@ fn set_gid_from_elf(
     Ok(())
 }
 
+/// Sets up the core dump settings of the new program.
+///
+/// The core dump filter is inherited from the old program. The new program
+/// cannot dump core if it gains privileges from the set-user-ID or
+/// set-group-ID bits.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/exec.c>
+fn inherit_coredump_settings(
+    ctx: &Context,
+    new_process_vm: &ProcessVm,
+    old_process_vm: &ProcessVm,
+) {
+    new_process_vm.set_coredump_filter(old_process_vm.coredump_filter());
+
+    let credentials = ctx.posix_thread.credentials();
+    let dumpable =
+        if credentials.euid() == credentials.ruid() && credentials.egid() == credentials.rgid() {
+            Dumpable::User
+        } else {
+            // This corresponds to the default value of Linux's `fs.suid_dumpable` sysctl.
+            Dumpable::Disable
+        };
+    new_process_vm.set_dumpable(dumpable);
+}
+
 fn reset_vfork_child(process: &Process) {
     if process.status().is_vfork_child() {
         // Resumes the parent process.
@@ -369,14 +471,17 @@ fn reset_vfork_child(process: &Process) {
     }
 }
 
-fn unshare_and_close_files(ctx: &Context) {
+fn unshare_and_close_files(ctx: &Context, exec_file: Option<(FileDesc, Arc<dyn FileLike>)>) {
     ctx.unshare_files();
 
-    ctx.thread_local
-        .borrow_file_table()
-        .unwrap()
-        .write()
-        .close_files_on_exec();
+    let file_table = ctx.thread_local.borrow_file_table();
+    let mut file_table_locked = file_table.unwrap().write();
+    file_table_locked.close_files_on_exec();
+
+    // Install the binary opened for its interpreter.
+    if let Some((fd, file)) = exec_file {
+        file_table_locked.insert_at(fd, file, FdFlags::empty());
+    }
 }
 
 fn unshare_and_reset_sigdispositions(process: &Process) {
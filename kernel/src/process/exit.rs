@@ -2,10 +2,12 @@
 
 use core::sync::atomic::Ordering;
 
-use super::{INIT_PROCESS_PID, Pid, Process, pid_table};
+use super::{Pid, Process, pid_table, wait::reap_zombie_child};
 use crate::{
-    events::IoEvents, fs::cgroupfs::CgroupMembership, prelude::*,
-    process::signal::signals::kernel::KernelSignal,
+    events::IoEvents,
+    fs::{cgroupfs::CgroupMembership, pseudofs::NsCommonOps},
+    prelude::*,
+    process::signal::{constants::SIGKILL, signals::kernel::KernelSignal},
 };
 
 /// Exits the current POSIX process.
@@ -73,6 +75,10 @@ fn move_children_to_reaper_process(current_process: &Process) -> BTreeMap<Pid, A
         return BTreeMap::new();
     }
 
+    if current_process.is_child_reaper() {
+        return zap_pid_ns_processes(current_process);
+    }
+
     while let Some(reaper_process) = find_reaper_process(current_process) {
         if let Ok(children) = move_process_children(current_process, &reaper_process) {
             reaper_process.children_wait_queue().wake_all();
@@ -80,13 +86,75 @@ fn move_children_to_reaper_process(current_process: &Process) -> BTreeMap<Pid, A
         }
     }
 
-    let init_process = pid_table::pid_table_mut()
-        .get_process(INIT_PROCESS_PID)
-        .unwrap();
-    let children = move_process_children(current_process, &init_process).unwrap();
-    init_process.children_wait_queue().wake_all();
+    // Fall back to the init process of the PID namespace. If it has exited, try the init process
+    // of the parent namespace, and so on. This loop always terminates because the children of the
+    // init process of the initial PID namespace are never cleared.
+    let mut pid_ns = current_process.pid_ns();
+    loop {
+        if let Some(child_reaper) = pid_ns.child_reaper()
+            && let Ok(children) = move_process_children(current_process, &child_reaper)
+        {
+            child_reaper.children_wait_queue().wake_all();
+            return children;
+        }
+
+        pid_ns = pid_ns.parent().unwrap();
+    }
+}
+
+/// Kills all the other processes in the PID namespace of `current_process` and reaps its children.
+///
+/// This is called when `current_process`, the init process of a non-initial PID namespace, exits.
+/// Since no process can be reparented to `current_process` afterwards, the returned children are
+/// always empty.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid_namespace.c#L190>
+//
+// FIXME: Linux also waits for the processes in the namespace that are not descendants of
+// `current_process` (e.g., processes created after `setns`) to be reaped by their parents.
+fn zap_pid_ns_processes(current_process: &Process) -> BTreeMap<Pid, Arc<Process>> {
+    let pid_ns = current_process.pid_ns();
+
+    // Prevent new processes from being created in the namespace.
+    pid_ns.disable_alloc();
+
+    // Kill all the processes in the namespace and its descendant namespaces.
+    let processes: Vec<Arc<Process>> = pid_table::pid_table_mut()
+        .iter_processes()
+        .filter(|process| {
+            !core::ptr::eq(process.as_ref(), current_process)
+                && pid_ns.is_same_or_ancestor_of(process.pid_ns())
+        })
+        .collect();
+    for process in processes {
+        process.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+    }
+
+    // Reap all the children, including the orphans that are reparented to `current_process` while
+    // we are waiting.
+    current_process.children_wait_queue().wait_until(|| {
+        let mut children = current_process.children().lock();
+        let children_mut = children.as_mut().unwrap();
 
-    children
+        let zombie_pids: Vec<Pid> = children_mut
+            .iter()
+            .filter(|(_, child)| child.status().is_zombie())
+            .map(|(pid, _)| *pid)
+            .collect();
+        for pid in zombie_pids {
+            reap_zombie_child(pid, children_mut, current_process.reaped_children_stats());
+        }
+
+        if !children_mut.is_empty() {
+            return None;
+        }
+
+        // No new children can be added after this point.
+        *children = None;
+        Some(())
+    });
+
+    BTreeMap::new()
 }
 
 /// Finds a reaper process for `current_process`.
@@ -99,11 +167,23 @@ fn find_reaper_process(current_process: &Process) -> Option<Arc<Process>> {
     // itself. Therefore, the parent is always alive and `upgrade` cannot fail.
     let mut parent = current_process.parent().lock().process().upgrade().unwrap();
 
+    // Orphans in a PID namespace are never reparented to processes outside the namespace, unless
+    // the init process of the namespace has exited.
+    let ns_reaper = current_process.pid_ns().child_reaper();
+
     loop {
         if parent.is_init_process() {
             return Some(parent);
         }
 
+        if ns_reaper
+            .as_ref()
+            .is_some_and(|ns_reaper| Arc::ptr_eq(ns_reaper, &parent))
+            && !parent.status().is_zombie()
+        {
+            return Some(parent);
+        }
+
         if !parent.has_child_subreaper.load(Ordering::Acquire) {
             return None;
         }
@@ -3,7 +3,12 @@
 use super::{
     Pgid, Pid, Process, pid_table,
     posix_thread::AsPosixThread,
-    signal::{constants::SIGCONT, sig_num::SigNum, signals::Signal},
+    signal::{
+        constants::{SIGCONT, SIGKILL, SIGSTOP},
+        sig_action::SigAction,
+        sig_num::SigNum,
+        signals::Signal,
+    },
 };
 use crate::{
     prelude::*,
@@ -110,6 +115,12 @@ pub fn tgkill(
         return Ok(());
     }
 
+    if let Some(signum) = signum
+        && is_unkillable_ns_init(&target_posix_thread.process(), signum, ctx)
+    {
+        return Ok(());
+    }
+
     if let Some(signal) = signal {
         // We've checked the permission issues above.
         // FIXME: We should take some lock while checking the permission to avoid race conditions.
@@ -122,13 +133,23 @@ pub fn tgkill(
 /// Sends a signal to all processes except current process and init process, using
 /// the current process as the sender.
 ///
+/// Only the processes that are visible in the PID namespace of the current process are
+/// affected, and the init process of that namespace is excluded.
+///
 /// The credentials of the current process will be checked to determine
 /// if it is authorized to send the signal to the target group.
 pub fn kill_all<S: Signal + Clone>(signal: Option<S>, ctx: &Context) -> Result<()> {
     let mut result = Ok(());
 
+    let pid_ns = ctx.process.pid_ns();
     for process in pid_table::pid_table_mut().iter_processes() {
-        if Arc::ptr_eq(&ctx.process, &process) || process.is_init_process() {
+        if Arc::ptr_eq(&ctx.process, &process)
+            || process.is_init_process()
+            || !pid_ns.is_same_or_ancestor_of(process.pid_ns())
+            || pid_ns
+                .child_reaper()
+                .is_some_and(|reaper| Arc::ptr_eq(&reaper, &process))
+        {
             continue;
         }
 
@@ -150,6 +171,12 @@ fn kill_process(process: &Process, signal: Option<Box<dyn Signal>>, ctx: &Contex
     let target_main_thread = process.main_thread();
     check_signal_perm(target_main_thread.as_posix_thread().unwrap(), ctx, signum)?;
 
+    if let Some(signum) = signum
+        && is_unkillable_ns_init(process, signum, ctx)
+    {
+        return Ok(());
+    }
+
     if let Some(signal) = signal {
         process.enqueue_signal(signal);
     }
@@ -157,6 +184,29 @@ fn kill_process(process: &Process, signal: Option<Box<dyn Signal>>, ctx: &Contex
     Ok(())
 }
 
+/// Returns whether the signal should be discarded because the target is the init process of a
+/// non-initial PID namespace and the init process has not installed a handler for the signal.
+///
+/// The only exceptions are `SIGKILL` and `SIGSTOP` sent from an ancestor PID namespace, which are
+/// treated as usual. See the Linux man pages "pid_namespaces(7)" for details.
+///
+/// The init process of the initial PID namespace is handled when the signal is dequeued.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/signal.c#L82-L83>
+fn is_unkillable_ns_init(target: &Process, signum: SigNum, ctx: &Context) -> bool {
+    if target.pid_ns().is_init() || !target.is_child_reaper() {
+        return false;
+    }
+
+    let is_from_ancestor_ns = !target.pid_ns().is_same_or_ancestor_of(ctx.process.pid_ns());
+    if is_from_ancestor_ns && (signum == SIGKILL || signum == SIGSTOP) {
+        return false;
+    }
+
+    let sig_dispositions = target.sig_dispositions().lock();
+    matches!(sig_dispositions.lock().get(signum), SigAction::Dfl)
+}
+
 // Reference: <https://elixir.bootlin.com/linux/v6.17/source/kernel/signal.c#L799>.
 fn check_signal_perm(target: &PosixThread, ctx: &Context, signum: Option<SigNum>) -> Result<()> {
     let target_process = target.process();
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod clone;
+pub mod coredump;
 pub mod credentials;
 mod execve;
 mod exit;
@@ -29,6 +30,8 @@ pub use execve::do_execve;
 pub use kill::{kill, kill_all, kill_group, tgkill};
 pub use namespace::{
     nsproxy::{ContextSetNsAdminApi, NsProxy, NsProxyBuilder, check_unsupported_ns_flags},
+    pid_ns::PidNamespace,
+    time_ns::{TimeNamespace, TimeNsClock, TimeNsOffset, TimeNsOffsets},
     unshare::ContextUnshareAdminApi,
     user_ns::UserNamespace,
 };
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub(super) mod nsproxy;
+pub(super) mod pid_ns;
+pub(super) mod time_ns;
 pub(super) mod unshare;
 pub(super) mod user_ns;
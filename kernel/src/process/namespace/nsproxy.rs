@@ -5,9 +5,11 @@ use spin::Once;
 use crate::{
     fs::{cgroupfs::CgroupNamespace, vfs::path::MountNamespace},
     ipc::IpcNamespace,
-    net::uts_ns::UtsNamespace,
+    net::{net_ns::NetNamespace, uts_ns::UtsNamespace},
     prelude::*,
-    process::{CloneFlags, Process, UserNamespace, posix_thread::PosixThread},
+    process::{
+        CloneFlags, PidNamespace, Process, TimeNamespace, UserNamespace, posix_thread::PosixThread,
+    },
 };
 
 /// A struct that acts as a per-thread proxy to give access to most namespaces.
@@ -16,11 +18,20 @@ use crate::{
 /// and keeps a local copy in `ThreadLocal` for fast access.
 /// `NsProxy` contains all types of namespaces except
 /// 1. The user namespace, which is included in the `Process` struct.
-/// 2. The PID namespace, which is included in the `Process` struct (TODO).
+/// 2. The PID namespace of the process itself, which is included in the `Process` struct.
+///    `NsProxy` only contains the PID namespace for the children of the thread.
+///
+/// Like the PID namespace, the time namespace for the children of the thread can differ from the
+/// time namespace of the thread itself (e.g., after `unshare(CLONE_NEWTIME)`). Unlike the PID
+/// namespace, the thread enters the time namespace for children when it executes a new program.
 pub struct NsProxy {
     cgroup_ns: Arc<CgroupNamespace>,
     ipc_ns: Arc<IpcNamespace>,
     mnt_ns: Arc<MountNamespace>,
+    net_ns: Arc<NetNamespace>,
+    pid_ns_for_children: Arc<PidNamespace>,
+    time_ns: Arc<TimeNamespace>,
+    time_ns_for_children: Arc<TimeNamespace>,
     uts_ns: Arc<UtsNamespace>,
 }
 
@@ -33,21 +44,64 @@ impl NsProxy {
                 cgroup_ns: CgroupNamespace::get_init_singleton().clone(),
                 ipc_ns: IpcNamespace::get_init_singleton().clone(),
                 mnt_ns: MountNamespace::get_init_singleton().clone(),
+                net_ns: NetNamespace::get_init_singleton().clone(),
+                pid_ns_for_children: PidNamespace::get_init_singleton().clone(),
+                time_ns: TimeNamespace::get_init_singleton().clone(),
+                time_ns_for_children: TimeNamespace::get_init_singleton().clone(),
                 uts_ns: UtsNamespace::get_init_singleton().clone(),
             })
         })
     }
 
+    /// Creates a new `NsProxy` for the child created by `clone()`.
+    ///
+    /// If the child does not share the virtual memory with the thread (i.e., without
+    /// `CLONE_VM`), it enters the time namespace for children. Note that the offsets of the time
+    /// namespace should then be frozen by the caller via [`TimeNamespace::enter`].
+    pub(in crate::process) fn new_clone(
+        self: &Arc<Self>,
+        user_ns: &Arc<UserNamespace>,
+        process: &Process,
+        posix_thread: &PosixThread,
+        clone_flags: CloneFlags,
+    ) -> Result<Arc<Self>> {
+        let new_proxy = self.new_with_namespaces(user_ns, process, posix_thread, clone_flags)?;
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/nsproxy.c>
+        if clone_flags.contains(CloneFlags::CLONE_VM) {
+            return Ok(new_proxy);
+        }
+        Ok(new_proxy.enter_time_ns_for_children())
+    }
+
+    /// Creates a new `NsProxy` for `unshare()`.
+    ///
+    /// Unlike other namespaces, the thread does not enter the new time namespace. The new time
+    /// namespace will only be used for its children.
+    pub(in crate::process) fn new_unshare(
+        self: &Arc<Self>,
+        user_ns: &Arc<UserNamespace>,
+        process: &Process,
+        posix_thread: &PosixThread,
+        unshare_flags: CloneFlags,
+    ) -> Result<Arc<Self>> {
+        self.new_with_namespaces(user_ns, process, posix_thread, unshare_flags)
+    }
+
+    /// Creates a new `NsProxy` for `execve()`.
+    ///
+    /// The thread enters the time namespace for children. Note that the offsets of the time
+    /// namespace should then be frozen by the caller via [`TimeNamespace::enter`].
+    pub(in crate::process) fn new_exec(self: &Arc<Self>) -> Arc<Self> {
+        self.enter_time_ns_for_children()
+    }
+
     /// Creates a new `NsProxy` by cloning from an existing `NsProxy`.
     ///
     /// If no namespaces need to be cloned, this method simply clones `self` and returns.
     /// Otherwise, a new `NsProxy` will be created
     /// by selectively cloning fields from the proxy and newly created namespaces.
-    //
-    // FIXME: This method is currently used by both `unshare()` and `clone()`.
-    // Once we support PID and time namespaces, their semantics diverge.
-    // We will need to refactor (or split) this method accordingly.
-    pub(in crate::process) fn new_clone(
+    fn new_with_namespaces(
         self: &Arc<Self>,
         user_ns: &Arc<UserNamespace>,
         process: &Process,
@@ -86,16 +140,50 @@ impl NsProxy {
             builder.mnt_ns(new_mnt_ns);
         }
 
+        if clone_ns_flags.contains(CloneFlags::CLONE_NEWNET) {
+            let new_net_ns = self.net_ns.new_clone(user_ns.clone(), posix_thread)?;
+            builder.net_ns(new_net_ns);
+        }
+
+        if clone_ns_flags.contains(CloneFlags::CLONE_NEWPID) {
+            // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid_namespace.c#L168-L169>
+            if !Arc::ptr_eq(&self.pid_ns_for_children, process.pid_ns()) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the PID namespace for children has already been changed"
+                );
+            }
+            let new_pid_ns = self
+                .pid_ns_for_children
+                .new_child(user_ns.clone(), posix_thread)?;
+            builder.pid_ns_for_children(new_pid_ns);
+        }
+
+        if clone_ns_flags.contains(CloneFlags::CLONE_NEWTIME) {
+            let new_time_ns = self
+                .time_ns_for_children
+                .new_clone(user_ns.clone(), posix_thread)?;
+            builder.time_ns_for_children(new_time_ns);
+        }
+
         if clone_ns_flags.contains(CloneFlags::CLONE_NEWUTS) {
             let new_uts_ns = self.uts_ns.new_clone(user_ns.clone(), posix_thread)?;
             builder.uts_ns(new_uts_ns);
         }
 
-        // TODO: Support other namespaces.
-
         Ok(Arc::new(builder.build()))
     }
 
+    fn enter_time_ns_for_children(self: &Arc<Self>) -> Arc<Self> {
+        if Arc::ptr_eq(&self.time_ns, &self.time_ns_for_children) {
+            return self.clone();
+        }
+
+        let mut builder = NsProxyBuilder::new(self);
+        builder.time_ns(self.time_ns_for_children.clone());
+        Arc::new(builder.build())
+    }
+
     /// Returns the associated cgroup namespace.
     pub fn cgroup_ns(&self) -> &Arc<CgroupNamespace> {
         &self.cgroup_ns
@@ -111,6 +199,26 @@ impl NsProxy {
         &self.mnt_ns
     }
 
+    /// Returns the associated network namespace.
+    pub fn net_ns(&self) -> &Arc<NetNamespace> {
+        &self.net_ns
+    }
+
+    /// Returns the PID namespace for the children created by the thread.
+    pub fn pid_ns_for_children(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns_for_children
+    }
+
+    /// Returns the associated time namespace.
+    pub fn time_ns(&self) -> &Arc<TimeNamespace> {
+        &self.time_ns
+    }
+
+    /// Returns the time namespace for the children created by the thread.
+    pub fn time_ns_for_children(&self) -> &Arc<TimeNamespace> {
+        &self.time_ns_for_children
+    }
+
     /// Returns the associated UTS namespace.
     pub fn uts_ns(&self) -> &Arc<UtsNamespace> {
         &self.uts_ns
@@ -126,6 +234,10 @@ pub struct NsProxyBuilder<'a> {
     cgroup_ns: Option<Arc<CgroupNamespace>>,
     ipc_ns: Option<Arc<IpcNamespace>>,
     mnt_ns: Option<Arc<MountNamespace>>,
+    net_ns: Option<Arc<NetNamespace>>,
+    pid_ns_for_children: Option<Arc<PidNamespace>>,
+    time_ns: Option<Arc<TimeNamespace>>,
+    time_ns_for_children: Option<Arc<TimeNamespace>>,
     uts_ns: Option<Arc<UtsNamespace>>,
 }
 
@@ -137,6 +249,10 @@ impl<'a> NsProxyBuilder<'a> {
             cgroup_ns: None,
             ipc_ns: None,
             mnt_ns: None,
+            net_ns: None,
+            pid_ns_for_children: None,
+            time_ns: None,
+            time_ns_for_children: None,
             uts_ns: None,
         }
     }
@@ -159,6 +275,30 @@ impl<'a> NsProxyBuilder<'a> {
         self
     }
 
+    /// Sets the new network namespace for the context being built.
+    pub fn net_ns(&mut self, net_ns: Arc<NetNamespace>) -> &mut Self {
+        self.net_ns = Some(net_ns);
+        self
+    }
+
+    /// Sets the new PID namespace for children for the context being built.
+    pub fn pid_ns_for_children(&mut self, pid_ns: Arc<PidNamespace>) -> &mut Self {
+        self.pid_ns_for_children = Some(pid_ns);
+        self
+    }
+
+    /// Sets the new time namespace for the context being built.
+    pub fn time_ns(&mut self, time_ns: Arc<TimeNamespace>) -> &mut Self {
+        self.time_ns = Some(time_ns);
+        self
+    }
+
+    /// Sets the new time namespace for children for the context being built.
+    pub fn time_ns_for_children(&mut self, time_ns: Arc<TimeNamespace>) -> &mut Self {
+        self.time_ns_for_children = Some(time_ns);
+        self
+    }
+
     /// Sets the new UTS namespace for the context being built.
     pub fn uts_ns(&mut self, uts_ns: Arc<UtsNamespace>) -> &mut Self {
         self.uts_ns = Some(uts_ns);
@@ -172,18 +312,32 @@ impl<'a> NsProxyBuilder<'a> {
             cgroup_ns: new_cgroup,
             ipc_ns: new_ipc,
             mnt_ns: new_mnt,
+            net_ns: new_net,
+            pid_ns_for_children: new_pid_for_children,
+            time_ns: new_time,
+            time_ns_for_children: new_time_for_children,
             uts_ns: new_uts,
         } = self;
 
         let new_cgroup = new_cgroup.unwrap_or_else(|| old_proxy.cgroup_ns.clone());
         let new_ipc = new_ipc.unwrap_or_else(|| old_proxy.ipc_ns.clone());
         let new_mnt = new_mnt.unwrap_or_else(|| old_proxy.mnt_ns.clone());
+        let new_net = new_net.unwrap_or_else(|| old_proxy.net_ns.clone());
+        let new_pid_for_children =
+            new_pid_for_children.unwrap_or_else(|| old_proxy.pid_ns_for_children.clone());
+        let new_time = new_time.unwrap_or_else(|| old_proxy.time_ns.clone());
+        let new_time_for_children =
+            new_time_for_children.unwrap_or_else(|| old_proxy.time_ns_for_children.clone());
         let new_uts = new_uts.unwrap_or_else(|| old_proxy.uts_ns.clone());
 
         NsProxy {
             cgroup_ns: new_cgroup,
             ipc_ns: new_ipc,
             mnt_ns: new_mnt,
+            net_ns: new_net,
+            pid_ns_for_children: new_pid_for_children,
+            time_ns: new_time,
+            time_ns_for_children: new_time_for_children,
             uts_ns: new_uts,
         }
     }
@@ -196,6 +350,9 @@ pub fn check_unsupported_ns_flags(flags: CloneFlags) -> Result<()> {
     const SUPPORTED_FLAGS: CloneFlags = CloneFlags::CLONE_NEWCGROUP
         .union(CloneFlags::CLONE_NEWIPC)
         .union(CloneFlags::CLONE_NEWNS)
+        .union(CloneFlags::CLONE_NEWNET)
+        .union(CloneFlags::CLONE_NEWPID)
+        .union(CloneFlags::CLONE_NEWTIME)
         .union(CloneFlags::CLONE_NEWUTS);
 
     let unsupported_flags =
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Defines the PID namespace abstraction.
+//!
+//! PID namespaces form a tree rooted at the initial PID namespace. A process is visible in the
+//! PID namespace it belongs to and in all ancestor namespaces, and it has a separate ID in each
+//! of them.
+//!
+//! The kernel identifies threads, processes, process groups, and sessions with the IDs in the
+//! initial PID namespace (i.e., the global IDs), which are the keys of the [`PidTable`]. Each
+//! non-initial PID namespace maintains a bidirectional mapping between the global IDs and the IDs
+//! that are visible inside the namespace. System calls translate IDs at the user-kernel boundary.
+//!
+//! [`PidTable`]: crate::process::pid_table::PidTable
+
+use spin::Once;
+
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    prelude::*,
+    process::{
+        Process, UserNamespace,
+        credentials::capabilities::CapSet,
+        posix_thread::{PID_MAX, PosixThread},
+    },
+    security::lsm::hooks as lsm_hooks,
+    thread::Tid,
+};
+
+/// The maximum nesting level of PID namespaces.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/pid_namespace.h#L13>.
+const MAX_PID_NS_LEVEL: u32 = 32;
+
+/// The ID of the first process (i.e., the init process) in a PID namespace.
+const FIRST_ID: Tid = 1;
+
+/// The IDs below this value are not reused after the allocation wraps around.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid.c>.
+const RESERVED_IDS: Tid = 300;
+
+/// The PID namespace.
+pub struct PidNamespace {
+    /// The nesting level, where the initial PID namespace is at level zero.
+    level: u32,
+    /// The parent namespace, which is `None` only for the initial PID namespace.
+    parent: Option<Arc<PidNamespace>>,
+    /// The mutable part of the namespace.
+    inner: Mutex<PidNamespaceInner>,
+    /// Owner user namespace.
+    owner: Arc<UserNamespace>,
+    /// Stashed dentry for nsfs.
+    stashed_dentry: StashedDentry,
+}
+
+struct PidNamespaceInner {
+    /// Maps global IDs to the IDs in this namespace.
+    ///
+    /// This map is always empty for the initial PID namespace, where the two kinds of IDs are the
+    /// same.
+    local_ids: BTreeMap<Tid, Tid>,
+    /// Maps the IDs in this namespace to global IDs.
+    global_ids: BTreeMap<Tid, Tid>,
+    /// The ID from which the next allocation starts searching for a free ID.
+    next_id: Tid,
+    /// The init process of this namespace, which reaps orphaned processes in the namespace.
+    child_reaper: Weak<Process>,
+    /// Whether new IDs can be allocated in this namespace.
+    ///
+    /// Allocation is disabled once the init process of the namespace exits.
+    is_alloc_disabled: bool,
+}
+
+impl PidNamespace {
+    /// Returns a reference to the singleton initial PID namespace.
+    pub fn get_init_singleton() -> &'static Arc<PidNamespace> {
+        static INIT: Once<Arc<PidNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let owner = UserNamespace::get_init_singleton().clone();
+            Self::new(0, None, owner)
+        })
+    }
+
+    fn new(level: u32, parent: Option<Arc<PidNamespace>>, owner: Arc<UserNamespace>) -> Arc<Self> {
+        let inner = PidNamespaceInner {
+            local_ids: BTreeMap::new(),
+            global_ids: BTreeMap::new(),
+            next_id: FIRST_ID,
+            child_reaper: Weak::new(),
+            is_alloc_disabled: false,
+        };
+        let stashed_dentry = StashedDentry::new();
+
+        Arc::new(Self {
+            level,
+            parent,
+            inner: Mutex::new(inner),
+            owner,
+            stashed_dentry,
+        })
+    }
+
+    /// Creates a new child PID namespace of `self`.
+    ///
+    /// The first process created in the new namespace becomes its init process.
+    pub fn new_child(
+        self: &Arc<Self>,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            owner.as_ref(),
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))?;
+
+        if self.level >= MAX_PID_NS_LEVEL {
+            return_errno_with_message!(
+                Errno::ENOSPC,
+                "the maximum nesting level of PID namespaces is reached"
+            );
+        }
+
+        Ok(Self::new(self.level + 1, Some(self.clone()), owner))
+    }
+
+    /// Returns the nesting level of this namespace.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Returns whether this is the initial PID namespace.
+    pub fn is_init(&self) -> bool {
+        self.level == 0
+    }
+
+    /// Returns whether this namespace is the same as, or an ancestor of, the other namespace.
+    pub fn is_same_or_ancestor_of(&self, other: &PidNamespace) -> bool {
+        let mut ns = other;
+        while ns.level > self.level {
+            ns = ns.parent.as_ref().unwrap();
+        }
+
+        core::ptr::eq(ns, self)
+    }
+
+    /// Translates a global ID to the ID in this namespace.
+    ///
+    /// Returns `None` if the object identified by `global_id` is not visible in this namespace.
+    pub fn local_id_of(&self, global_id: Tid) -> Option<Tid> {
+        if self.is_init() {
+            return Some(global_id);
+        }
+
+        self.inner.lock().local_ids.get(&global_id).copied()
+    }
+
+    /// Translates an ID in this namespace to the global ID.
+    ///
+    /// Returns `None` if no object is identified by `local_id` in this namespace.
+    pub fn global_id_of(&self, local_id: Tid) -> Option<Tid> {
+        if self.is_init() {
+            return Some(local_id);
+        }
+
+        self.inner.lock().global_ids.get(&local_id).copied()
+    }
+
+    /// Returns the IDs of `global_id` in the namespaces from `ancestor` down to this namespace.
+    ///
+    /// The IDs are ordered from the outermost namespace to the innermost one. The namespaces in
+    /// which `global_id` is not visible are skipped.
+    pub fn nested_ids_of(&self, ancestor: &PidNamespace, global_id: Tid) -> Vec<Tid> {
+        let mut ids = Vec::new();
+
+        let mut ns = self;
+        loop {
+            if let Some(id) = ns.local_id_of(global_id) {
+                ids.push(id);
+            }
+            if core::ptr::eq(ns, ancestor) || ns.is_init() {
+                break;
+            }
+            ns = ns.parent.as_ref().unwrap();
+        }
+
+        ids.reverse();
+        ids
+    }
+
+    /// Returns the init process of this namespace.
+    pub fn child_reaper(&self) -> Option<Arc<Process>> {
+        self.inner.lock().child_reaper.upgrade()
+    }
+
+    /// Sets the init process of this namespace.
+    pub(in crate::process) fn set_child_reaper(&self, process: &Arc<Process>) {
+        let mut inner = self.inner.lock();
+        debug_assert!(inner.child_reaper.upgrade().is_none());
+        inner.child_reaper = Arc::downgrade(process);
+    }
+
+    /// Allocates IDs for `global_id` in this namespace and all ancestor namespaces.
+    ///
+    /// Returns the ID in this namespace.
+    ///
+    /// # Errors
+    ///
+    /// This method will return `ENOMEM` if the init process of this namespace or of an ancestor
+    /// namespace has exited, or `EAGAIN` if all IDs in one of the namespaces are in use.
+    pub(in crate::process) fn alloc_ids(&self, global_id: Tid) -> Result<Tid> {
+        let mut allocated: Vec<&PidNamespace> = Vec::new();
+
+        let mut ns = self;
+        while !ns.is_init() {
+            let mut inner = ns.inner.lock();
+
+            let local_id = if inner.is_alloc_disabled {
+                Err(Error::with_message(
+                    Errno::ENOMEM,
+                    "the init process of the PID namespace has exited",
+                ))
+            } else {
+                inner.alloc_local_id().ok_or_else(|| {
+                    Error::with_message(Errno::EAGAIN, "all IDs in the PID namespace are in use")
+                })
+            };
+            let local_id = match local_id {
+                Ok(local_id) => local_id,
+                Err(err) => {
+                    drop(inner);
+                    for allocated_ns in allocated {
+                        allocated_ns.free_id(global_id);
+                    }
+                    return Err(err);
+                }
+            };
+            inner.local_ids.insert(global_id, local_id);
+            inner.global_ids.insert(local_id, global_id);
+            drop(inner);
+
+            allocated.push(ns);
+            ns = ns.parent.as_ref().unwrap();
+        }
+
+        Ok(self.local_id_of(global_id).unwrap())
+    }
+
+    /// Frees the IDs allocated by [`Self::alloc_ids`].
+    pub(in crate::process) fn free_ids(&self, global_id: Tid) {
+        let mut ns = self;
+        while !ns.is_init() {
+            ns.free_id(global_id);
+            ns = ns.parent.as_ref().unwrap();
+        }
+    }
+
+    fn free_id(&self, global_id: Tid) {
+        let mut inner = self.inner.lock();
+        if let Some(local_id) = inner.local_ids.remove(&global_id) {
+            inner.global_ids.remove(&local_id);
+        }
+    }
+
+    /// Disables the allocation of new IDs in this namespace.
+    ///
+    /// This should be called when the init process of this namespace exits.
+    pub(in crate::process) fn disable_alloc(&self) {
+        self.inner.lock().is_alloc_disabled = true;
+    }
+}
+
+impl PidNamespaceInner {
+    /// Allocates the lowest free ID starting from `next_id`.
+    ///
+    /// Like Linux, the search wraps around at [`PID_MAX`] and then skips the IDs below
+    /// [`RESERVED_IDS`], which are usually held by long-running daemons.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid.c>.
+    fn alloc_local_id(&mut self) -> Option<Tid> {
+        let min_id = if self.next_id > RESERVED_IDS {
+            RESERVED_IDS
+        } else {
+            FIRST_ID
+        };
+
+        let local_id = self
+            .lowest_free_id_in(self.next_id, PID_MAX)
+            .or_else(|| self.lowest_free_id_in(min_id, self.next_id))?;
+        self.next_id = local_id + 1;
+
+        Some(local_id)
+    }
+
+    /// Returns the lowest ID in `start..end` that is not in use.
+    fn lowest_free_id_in(&self, start: Tid, end: Tid) -> Option<Tid> {
+        let mut candidate = start;
+        for &used_id in self.global_ids.range(start..end).map(|(id, _)| id) {
+            if used_id != candidate {
+                break;
+            }
+            candidate += 1;
+        }
+
+        (candidate < end).then_some(candidate)
+    }
+}
+
+impl NsCommonOps for PidNamespace {
+    const TYPE: NsType = NsType::Pid;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        // FIXME: Linux also returns `EPERM` if the parent namespace is not visible
+        // in the PID namespace of the current process.
+        self.parent.as_ref().ok_or_else(|| {
+            Error::with_message(
+                Errno::EPERM,
+                "the initial PID namespace does not have a parent namespace",
+            )
+        })
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use spin::Once;
+
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+use crate::vm::page_cache::Vmo;
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread},
+    security::lsm::hooks as lsm_hooks,
+    syscall::ClockId,
+    time::{
+        Timer,
+        clocks::{BootTimeClock, MonotonicClock},
+    },
+    vm::vmar::Vmar,
+};
+
+/// The time namespace.
+///
+/// A time namespace shifts `CLOCK_MONOTONIC` (and its variants) and `CLOCK_BOOTTIME` by
+/// per-namespace offsets. The offsets can be changed via `/proc/[pid]/timens_offsets` until the
+/// first process enters the namespace, after which they are frozen.
+pub struct TimeNamespace {
+    offsets: Mutex<TimeNsOffsets>,
+    frozen: Once<FrozenTimeNs>,
+    owner: Arc<UserNamespace>,
+    stashed_dentry: StashedDentry,
+}
+
+/// The state of a time namespace whose offsets have been frozen.
+struct FrozenTimeNs {
+    offsets: TimeNsOffsets,
+    /// The VMO that contains the vvar page of the namespace.
+    ///
+    /// This is `None` for the initial time namespace, whose processes use the vvar page of the
+    /// vDSO VMO directly.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    vvar_vmo: Option<Arc<Vmo>>,
+}
+
+impl TimeNamespace {
+    /// Returns a reference to the singleton initial time namespace.
+    pub fn get_init_singleton() -> &'static Arc<TimeNamespace> {
+        static INIT: Once<Arc<TimeNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let owner = UserNamespace::get_init_singleton().clone();
+            let init_ns = Self::new(owner);
+            init_ns.frozen.call_once(|| FrozenTimeNs {
+                offsets: TimeNsOffsets::default(),
+                #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+                vvar_vmo: None,
+            });
+            init_ns
+        })
+    }
+
+    fn new(owner: Arc<UserNamespace>) -> Arc<Self> {
+        Arc::new(Self {
+            offsets: Mutex::new(TimeNsOffsets::default()),
+            frozen: Once::new(),
+            owner,
+            stashed_dentry: StashedDentry::new(),
+        })
+    }
+
+    /// Clones a new time namespace from `self`.
+    ///
+    /// The new namespace inherits the offsets of `self`, which can be changed until a process
+    /// enters the new namespace.
+    pub fn new_clone(
+        &self,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            owner.as_ref(),
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))?;
+
+        let new_ns = Self::new(owner);
+        *new_ns.offsets.lock() = *self.offsets.lock();
+        Ok(new_ns)
+    }
+
+    /// Returns the current offsets of the namespace.
+    pub fn offsets(&self) -> TimeNsOffsets {
+        *self.offsets.lock()
+    }
+
+    /// Sets the offsets of the clocks in the namespace.
+    ///
+    /// This method will fail with `EPERM` if the caller does not have the SYS_TIME capability in
+    /// the owner user namespace, with `ERANGE` if an offset would make the clock negative or too
+    /// large, and with `EACCES` if the offsets have been frozen.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/time/namespace.c>
+    pub fn set_offsets(
+        &self,
+        new_offsets: &[(TimeNsClock, TimeNsOffset)],
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            self.owner.as_ref(),
+            posix_thread,
+            CapSet::SYS_TIME,
+        ))?;
+
+        for (clock, offset) in new_offsets {
+            if offset.secs > KTIME_SEC_MAX || offset.secs < -KTIME_SEC_MAX {
+                return_errno_with_message!(Errno::ERANGE, "the offset is out of range");
+            }
+
+            let now = match clock {
+                TimeNsClock::Monotonic => MonotonicClock::get().read_time(),
+                TimeNsClock::Boottime => BootTimeClock::get().read_time(),
+            };
+            let shifted_nanos = now.as_nanos() as i128 + offset.as_nanos();
+            if shifted_nanos < 0 || shifted_nanos / NANOS_PER_SEC > (KTIME_SEC_MAX / 2) as i128 {
+                return_errno_with_message!(
+                    Errno::ERANGE,
+                    "the clock would be out of range with the offset"
+                );
+            }
+        }
+
+        let mut offsets = self.offsets.lock();
+        if self.frozen.is_completed() {
+            return_errno_with_message!(
+                Errno::EACCES,
+                "the offsets cannot be changed after a process has entered the namespace"
+            );
+        }
+
+        for (clock, offset) in new_offsets {
+            match clock {
+                TimeNsClock::Monotonic => offsets.monotonic = *offset,
+                TimeNsClock::Boottime => offsets.boottime = *offset,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Freezes the offsets and maps the vvar page of the namespace into `vmar`.
+    ///
+    /// This method must be called when a process enters the namespace, i.e., when the process is
+    /// created in the namespace, executes a new program, or switches to the namespace.
+    pub fn enter(&self, vmar: &Vmar) -> Result<()> {
+        let frozen = match self.frozen.get() {
+            Some(frozen) => frozen,
+            None => {
+                // Hold the lock so that the offsets cannot be changed while being frozen.
+                let offsets = self.offsets.lock();
+                self.frozen.call_once(|| FrozenTimeNs {
+                    offsets: *offsets,
+                    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+                    vvar_vmo: crate::vdso::new_timens_vvar_vmo(&offsets),
+                })
+            }
+        };
+
+        cfg_if::cfg_if! {
+            if #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))] {
+                crate::vdso::map_vvar_to_vmar(vmar, frozen.vvar_vmo.as_ref())
+            } else {
+                // There is no vDSO to update.
+                let _ = (frozen, vmar);
+                Ok(())
+            }
+        }
+    }
+
+    /// Converts the time of a clock in the initial time namespace to the time in this namespace.
+    pub fn to_ns_time(&self, clock_id: ClockId, host_time: Duration) -> Duration {
+        match self.offset_of(clock_id) {
+            Some(offset) => offset.apply(host_time),
+            None => host_time,
+        }
+    }
+
+    /// Converts the time of a clock in this namespace to the time in the initial time namespace.
+    pub fn to_host_time(&self, clock_id: ClockId, ns_time: Duration) -> Duration {
+        match self.offset_of(clock_id) {
+            Some(offset) => offset.unapply(ns_time),
+            None => ns_time,
+        }
+    }
+
+    /// Converts an absolute expiration time of `timer` in this namespace to the time in the
+    /// initial time namespace.
+    pub fn to_host_timer_time(&self, timer: &Timer, ns_time: Duration) -> Duration {
+        let timer_manager = timer.timer_manager();
+        if Arc::ptr_eq(timer_manager, MonotonicClock::timer_manager()) {
+            self.to_host_time(ClockId::CLOCK_MONOTONIC, ns_time)
+        } else if Arc::ptr_eq(timer_manager, BootTimeClock::timer_manager()) {
+            self.to_host_time(ClockId::CLOCK_BOOTTIME, ns_time)
+        } else {
+            ns_time
+        }
+    }
+
+    fn offset_of(&self, clock_id: ClockId) -> Option<&TimeNsOffset> {
+        // A process can only observe the time of a namespace after entering it,
+        // so the offsets must have been frozen.
+        let offsets = &self.frozen.get()?.offsets;
+
+        match clock_id {
+            ClockId::CLOCK_MONOTONIC
+            | ClockId::CLOCK_MONOTONIC_RAW
+            | ClockId::CLOCK_MONOTONIC_COARSE => Some(&offsets.monotonic),
+            ClockId::CLOCK_BOOTTIME => Some(&offsets.boottime),
+            ClockId::CLOCK_REALTIME
+            | ClockId::CLOCK_REALTIME_COARSE
+            | ClockId::CLOCK_PROCESS_CPUTIME_ID
+            | ClockId::CLOCK_THREAD_CPUTIME_ID => None,
+        }
+    }
+}
+
+impl NsCommonOps for TimeNamespace {
+    const TYPE: NsType = NsType::Time;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "a time namespace does not have a parent namespace"
+        );
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
+
+/// The clocks whose offsets can be set in a time namespace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeNsClock {
+    Monotonic,
+    Boottime,
+}
+
+/// The offsets of the clocks in a time namespace.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeNsOffsets {
+    pub monotonic: TimeNsOffset,
+    pub boottime: TimeNsOffset,
+}
+
+/// A signed offset of a clock, in the same representation as `struct timespec`.
+///
+/// The offset equals `secs` seconds plus `nanos` nanoseconds, where `nanos` is always
+/// non-negative and less than one second.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeNsOffset {
+    secs: i64,
+    nanos: u32,
+}
+
+impl TimeNsOffset {
+    /// Creates a new offset.
+    ///
+    /// This method will fail with `EINVAL` if `nanos` is not less than one second.
+    pub fn new(secs: i64, nanos: u64) -> Result<Self> {
+        if nanos >= NANOS_PER_SEC as u64 {
+            return_errno_with_message!(Errno::EINVAL, "the nanoseconds are out of range");
+        }
+
+        Ok(Self {
+            secs,
+            nanos: nanos as u32,
+        })
+    }
+
+    /// Returns the seconds of the offset.
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Returns the nanoseconds of the offset.
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    fn as_nanos(&self) -> i128 {
+        self.secs as i128 * NANOS_PER_SEC + self.nanos as i128
+    }
+
+    /// Adds the offset to `time`, saturating at zero.
+    fn apply(&self, time: Duration) -> Duration {
+        duration_from_nanos(time.as_nanos() as i128 + self.as_nanos())
+    }
+
+    /// Subtracts the offset from `time`, saturating at zero.
+    fn unapply(&self, time: Duration) -> Duration {
+        duration_from_nanos(time.as_nanos() as i128 - self.as_nanos())
+    }
+}
+
+fn duration_from_nanos(nanos: i128) -> Duration {
+    if nanos <= 0 {
+        return Duration::ZERO;
+    }
+
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).unwrap_or(u64::MAX);
+    Duration::new(secs, (nanos % NANOS_PER_SEC) as u32)
+}
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/time64.h>
+const KTIME_SEC_MAX: i64 = i64::MAX / NANOS_PER_SEC as i64;
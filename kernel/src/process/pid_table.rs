@@ -7,9 +7,9 @@
 //! the kernel objects that share the same numeric identifier, which eliminates
 //! the need for separate per-type lookup tables.
 
-use alloc::collections::btree_map::Entry;
+use alloc::collections::btree_map::{Entry, OccupiedEntry};
 
-use super::{Pgid, Pid, Process, ProcessGroup, Session, Sid};
+use super::{Pgid, Pid, PidNamespace, Process, ProcessGroup, Session, Sid};
 use crate::{
     prelude::*,
     process::posix_thread::AsPosixThread,
@@ -51,10 +51,13 @@ impl PidTable {
     pub(super) fn insert_thread(&mut self, tid: Tid, thread: &Arc<Thread>) {
         debug_assert_eq!(tid, thread.as_posix_thread().unwrap().tid());
 
+        let pid_ns = thread.as_posix_thread().unwrap().process().pid_ns().clone();
+
         let mut entry = self.get_or_create_entry(tid).lock();
         debug_assert!(!entry.has_live_process());
 
         entry.set_thread(thread);
+        entry.set_pid_ns(pid_ns);
     }
 
     /// Removes a non-main thread from the table.
@@ -76,7 +79,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            remove_map_entry(map_entry);
         }
     }
 
@@ -99,7 +102,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            remove_map_entry(map_entry);
         }
 
         Some(thread)
@@ -138,6 +141,7 @@ impl PidTable {
         let mut entry = entry.lock();
         entry.set_process(process);
         entry.set_thread(&process.main_thread());
+        entry.set_pid_ns(process.pid_ns().clone());
     }
 
     /// Removes a process and its main thread from the table.
@@ -163,7 +167,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            remove_map_entry(map_entry);
         }
     }
 
@@ -208,7 +212,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            remove_map_entry(map_entry);
         }
     }
 
@@ -248,7 +252,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            remove_map_entry(map_entry);
         }
     }
 
@@ -258,6 +262,19 @@ impl PidTable {
     }
 }
 
+/// Removes an entry from the B-tree and frees its IDs in the PID namespaces.
+///
+/// The IDs in non-initial PID namespaces are bound to the lifetime of the entry, so that they
+/// remain valid as long as any thread, process, process group, or session uses them.
+fn remove_map_entry(map_entry: OccupiedEntry<'_, u32, Arc<PidEntry>>) {
+    let (id, pid_entry) = map_entry.remove_entry();
+
+    // Lock order: PID table -> PID namespace
+    if let Some(pid_ns) = pid_entry.lock().pid_ns.take() {
+        pid_ns.free_ids(id);
+    }
+}
+
 /// An entry in the unified PID table.
 ///
 /// Each entry stores references to the thread, process, process group, and
@@ -286,6 +303,11 @@ struct PidEntryInner {
     process: Weak<Process>,
     process_group: Weak<ProcessGroup>,
     session: Weak<Session>,
+    /// The PID namespace in which the ID is allocated.
+    ///
+    /// This is `None` for the IDs that are not allocated by any PID namespace (e.g., the IDs of
+    /// the bootstrap process group and session).
+    pid_ns: Option<Arc<PidNamespace>>,
 }
 
 /// The process/thread type represented by a [`PidEntry`].
@@ -368,6 +390,7 @@ impl PidEntryInner {
             process: Weak::new(),
             process_group: Weak::new(),
             session: Weak::new(),
+            pid_ns: None,
         }
     }
 
@@ -389,6 +412,16 @@ impl PidEntryInner {
         self.thread = Arc::downgrade(thread);
     }
 
+    /// Sets the PID namespace in which the ID is allocated.
+    fn set_pid_ns(&mut self, pid_ns: Arc<PidNamespace>) {
+        debug_assert!(
+            self.pid_ns
+                .as_ref()
+                .is_none_or(|old_pid_ns| Arc::ptr_eq(old_pid_ns, &pid_ns))
+        );
+        self.pid_ns = Some(pid_ns);
+    }
+
     /// Sets the process reference.
     fn set_process(&mut self, process: &Arc<Process>) {
         debug_assert!(!self.has_live_process());
@@ -1,9 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, AtomicU64};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
 
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GsBase};
+use ostd::arch::cpu::context::{FsBase, GsBase, TlsEntries};
 use ostd::{
     arch::cpu::context::{FpuContext, UserContext},
     cpu::CpuSet,
@@ -22,6 +22,7 @@ use crate::{
         signal::{sig_mask::AtomicSigMask, sig_queues::SigQueues},
     },
     sched::{Nice, SchedPolicy},
+    security::seccomp::Seccomp,
     thread::{Thread, Tid, task},
     time::{TimerManager, clocks::ProfClock},
     vm::vmar::VmarHandle,
@@ -49,6 +50,9 @@ pub struct PosixThreadBuilder {
     user_ns: Option<Arc<UserNamespace>>,
     ns_proxy: Option<Arc<NsProxy>>,
     default_timer_slack_ns: u64,
+    seccomp: Option<Seccomp>,
+    no_new_privs: bool,
+    cpu_affinity: CpuSet,
 }
 
 impl PosixThreadBuilder {
@@ -77,6 +81,9 @@ impl PosixThreadBuilder {
             user_ns: None,
             ns_proxy: None,
             default_timer_slack_ns: 50_000, // 50 usec default slack
+            seccomp: None,
+            no_new_privs: false,
+            cpu_affinity: CpuSet::new_full(),
         }
     }
 
@@ -133,6 +140,14 @@ impl PosixThreadBuilder {
         }
     }
 
+    #[cfg(target_arch = "x86_64")]
+    pub fn tls_entries(self, tls_entries: TlsEntries) -> Self {
+        Self {
+            supp_user_context: self.supp_user_context.with_tls_entries(tls_entries),
+            ..self
+        }
+    }
+
     pub fn user_ns(mut self, user_ns: Arc<UserNamespace>) -> Self {
         self.user_ns = Some(user_ns);
         self
@@ -148,6 +163,26 @@ impl PosixThreadBuilder {
         self
     }
 
+    pub fn seccomp(mut self, seccomp: Seccomp) -> Self {
+        self.seccomp = Some(seccomp);
+        self
+    }
+
+    pub fn no_new_privs(mut self, no_new_privs: bool) -> Self {
+        self.no_new_privs = no_new_privs;
+        self
+    }
+
+    /// Sets the CPU affinity requested by the user.
+    ///
+    /// The CPU affinity is not restricted to the cpuset of the thread. This
+    /// should be done via [`Thread::restrict_cpu_affinity`] before the thread
+    /// runs.
+    pub fn cpu_affinity(mut self, cpu_affinity: CpuSet) -> Self {
+        self.cpu_affinity = cpu_affinity;
+        self
+    }
+
     pub fn build(self) -> Arc<Task> {
         let Self {
             tid,
@@ -167,6 +202,9 @@ impl PosixThreadBuilder {
             user_ns,
             ns_proxy,
             default_timer_slack_ns,
+            seccomp,
+            no_new_privs,
+            cpu_affinity,
         } = self;
 
         let file_table = file_table.unwrap_or_else(|| RwArc::new(FileTable::new()));
@@ -206,10 +244,11 @@ impl PosixThreadBuilder {
                     tracees: Once::new(),
                     exit_code: AtomicU32::new(0),
                     personality: AtomicU32::new(0),
+                    seccomp: seccomp.unwrap_or_default(),
+                    no_new_privs: AtomicBool::new(no_new_privs),
                 }
             };
 
-            let cpu_affinity = CpuSet::new_full();
             let thread = Arc::new(Thread::new(
                 weak_task.clone(),
                 posix_thread,
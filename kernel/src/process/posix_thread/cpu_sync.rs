@@ -278,3 +278,20 @@ impl UserReg for ostd::arch::cpu::context::GsBase {
         self.load(guard)
     }
 }
+
+#[cfg(target_arch = "x86_64")]
+impl UserReg for ostd::arch::cpu::context::TlsEntries {
+    fn save_from_cpu(&mut self) {
+        // The TLS entries are modified only by the kernel, so the in-memory copy is always
+        // up-to-date.
+    }
+
+    fn restore_to_cpu(&self) {
+        let guard = ostd::irq::disable_local();
+        self.load(&guard);
+    }
+
+    fn restore_to_cpu_with_irq_disabled(&self, guard: &DisabledLocalIrqGuard) {
+        self.load(guard)
+    }
+}
@@ -60,7 +60,7 @@ fn exit_internal(
     let thread_local = current_task.as_thread_local().unwrap();
     let posix_process = posix_thread.process();
 
-    let is_last_thread = {
+    let (is_last_thread, core_state) = {
         let mut tasks = posix_process.tasks().lock();
         let has_exited_group = tasks.has_exited_group();
         let in_evecve = tasks.in_execve();
@@ -85,9 +85,18 @@ fn exit_internal(
         }
         current_thread.exit();
 
-        tasks.remove_exited(&current_task)
+        (
+            tasks.remove_exited(&current_task),
+            tasks.core_state().cloned(),
+        )
     };
 
+    // If the process is dumping core, report the registers and wait for the
+    // core dump to finish before the memory of the process is changed below.
+    if let Some(core_state) = core_state {
+        core_state.report_and_wait(ctx, user_ctx);
+    }
+
     // This is put after `current_thread.exit()`,
     // so `attach_tracee` will observe that the tracer has exited while
     // holding the `tracees` lock, and can not race with `clear_tracees`.
@@ -100,7 +109,15 @@ fn exit_internal(
 
     wake_clear_ctid(thread_local);
 
-    wake_robust_list(thread_local, posix_thread.tid());
+    // The futex words in user space store the TID in the PID namespace of the thread.
+    let local_tid = posix_process
+        .pid_ns()
+        .local_id_of(posix_thread.tid())
+        .unwrap();
+    wake_robust_list(thread_local, local_tid);
+
+    // The io_uring operations submitted by the thread must not outlive it.
+    thread_local.io_uring_task().cancel_all(posix_thread.tid());
 
     // According to Linux behavior, the main thread shouldn't be removed from the table until the
     // process is reaped by its parent.
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 
 use aster_rights::{ReadDupOp, ReadOp, ReadWriteOp};
 use ostd::{
@@ -23,6 +23,7 @@ use crate::{
         posix_thread::ptrace::TraceeStatus,
         signal::{PauseReason, PollHandle, sig_mask::SigMask},
     },
+    security::seccomp::Seccomp,
     thread::{Thread, Tid},
     time::{Timer, TimerManager, clocks::ProfClock, timer::TimerGuard},
 };
@@ -107,6 +108,12 @@ pub struct PosixThread {
 
     /// The personality value for this thread.
     personality: AtomicU32,
+
+    // Security
+    /// The seccomp state of this thread.
+    seccomp: Seccomp,
+    /// Whether `execve` is prohibited from granting privileges.
+    no_new_privs: AtomicBool,
 }
 
 impl PosixThread {
@@ -239,7 +246,10 @@ impl PosixThread {
             signalled_waker.as_ref(),
             task.schedule_info().cpu.get().is_none(),
         ) {
-            (Some((_, PauseReason::Sleep)), true) => SleepingState::Interruptible,
+            // Like Linux, a frozen thread is reported as sleeping.
+            (Some((_, PauseReason::Sleep | PauseReason::Freeze)), true) => {
+                SleepingState::Interruptible
+            }
             (Some((_, PauseReason::StopBySignal)), true) => SleepingState::StopBySignal,
             (Some((_, PauseReason::StopByPtrace)), true) => SleepingState::StopByPtrace,
             (None, true) => SleepingState::Uninterruptible,
@@ -247,6 +257,14 @@ impl PosixThread {
         }
     }
 
+    /// Returns whether this thread is parked by the cgroup freezer.
+    pub fn is_frozen(&self) -> bool {
+        matches!(
+            self.signalled_waker.lock().as_ref(),
+            Some((_, PauseReason::Freeze))
+        )
+    }
+
     /// Wakes up the signalled waker.
     pub fn wake_signalled_waker(&self) {
         if let Some((waker, _)) = &*self.signalled_waker.lock() {
@@ -334,6 +352,23 @@ impl PosixThread {
         self.timer_slack_ns.store(default, Ordering::Relaxed);
     }
 
+    /// Returns the seccomp state of this thread.
+    pub fn seccomp(&self) -> &Seccomp {
+        &self.seccomp
+    }
+
+    /// Returns whether the `no_new_privs` attribute is set.
+    pub fn no_new_privs(&self) -> bool {
+        self.no_new_privs.load(Ordering::Relaxed)
+    }
+
+    /// Sets the `no_new_privs` attribute.
+    ///
+    /// Once set, the attribute cannot be unset.
+    pub fn set_no_new_privs(&self) {
+        self.no_new_privs.store(true, Ordering::Relaxed);
+    }
+
     /// Sets the exit code of this thread.
     pub(super) fn set_exit_code(&self, exit_code: ExitCode) {
         self.exit_code.store(exit_code, Ordering::Relaxed);
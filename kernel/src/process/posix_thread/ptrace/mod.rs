@@ -4,12 +4,16 @@
 
 use core::sync::atomic::{AtomicBool, Ordering};
 
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+use ostd::arch::cpu::context::{FpuContext, GeneralRegs};
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GeneralRegs, GsBase};
+use ostd::arch::cpu::context::{FsBase, GsBase};
+#[cfg(target_arch = "riscv64")]
+use ostd::user::UserContextApi;
 use ostd::{arch::cpu::context::UserContext, sync::Waiter};
 
 use super::{AsPosixThread, PosixThread};
-#[cfg(target_arch = "x86_64")]
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
 use crate::arch::ptrace as arch_ptrace;
 use crate::{
     prelude::*,
@@ -18,7 +22,8 @@ use crate::{
         signal::{
             DequeuedSignal, PauseReason,
             c_types::siginfo_t,
-            constants::{CLD_TRAPPED, SIGCHLD, SIGKILL, SIGTRAP},
+            constants::{CLD_TRAPPED, SIGCHLD, SIGKILL, SIGSTOP, SIGTRAP},
+            sig_num::SigNum,
             signals::{kernel::KernelSignal, raw::RawSignal, user::UserSignal},
         },
     },
@@ -47,9 +52,14 @@ impl PosixThread {
     /// # Errors
     ///
     /// Returns `EPERM` if this thread is already being traced.
-    fn set_tracer(&self, tracer: Weak<Thread>) -> Result<()> {
+    fn set_tracer(
+        &self,
+        tracer: Weak<Thread>,
+        options: PtraceOptions,
+        is_seized: bool,
+    ) -> Result<()> {
         let status = self.tracee_status.call_once(TraceeStatus::new);
-        status.set_tracer(tracer)
+        status.set_tracer(tracer, options, is_seized)
     }
 
     /// Detaches the tracer of this thread.
@@ -96,7 +106,24 @@ impl PosixThread {
         user_ctx: &mut UserContext,
     ) {
         if let Some(status) = self.tracee_status.get() {
-            status.ptrace_may_stop_on(event, ctx, user_ctx)
+            status.ptrace_may_stop_on(event, ctx, user_ctx);
+        }
+    }
+
+    /// Stops this thread at a seccomp event-stop if it is currently traced,
+    /// and the `PTRACE_O_TRACESECCOMP` option is enabled.
+    ///
+    /// Returns a [`PtraceStopResult`] indicating why this ptrace-stop ended.
+    pub fn ptrace_may_stop_on_seccomp(
+        &self,
+        data: u16,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+    ) -> PtraceStopResult {
+        if let Some(status) = self.tracee_status.get() {
+            status.ptrace_may_stop_on(PtraceEvent::Seccomp(data), ctx, user_ctx)
+        } else {
+            PtraceStopResult::NotTraced(None)
         }
     }
 
@@ -115,11 +142,77 @@ impl PosixThread {
         }
     }
 
-    /// Returns whether a clone-family ptrace event would be required for `clone_args`.
-    pub(in crate::process) fn needs_ptrace_clone_stop(&self, clone_args: &CloneArgs) -> bool {
+    /// Stops this thread at a `PTRACE_INTERRUPT` stop if the tracer has requested one.
+    pub(in crate::process) fn ptrace_may_stop_on_interrupt(
+        &self,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+    ) {
+        if let Some(status) = self.tracee_status.get()
+            && status.has_pending_interrupt()
+        {
+            status.ptrace_trap_stop(ctx, user_ctx);
+        }
+    }
+
+    /// Reports the group-stop of the process to the tracer if this thread is traced.
+    ///
+    /// This method should be called when the thread finds its process stopped. It may block in
+    /// the ptrace-stop until the tracer continues the stop, or until a `SIGKILL` interrupts it.
+    ///
+    /// Returns whether the thread should continue running even though the process is stopped,
+    /// which is the case if the tracer restarts the thread from the group-stop.
+    pub fn ptrace_group_stop(&self, ctx: &Context, user_ctx: &mut UserContext) -> bool {
+        if let Some(status) = self.tracee_status.get() {
+            status.group_stop(ctx, user_ctx)
+        } else {
+            false
+        }
+    }
+
+    /// Returns whether the tracer has requested a `PTRACE_INTERRUPT` stop.
+    pub(in crate::process) fn has_pending_ptrace_interrupt(&self) -> bool {
         self.tracee_status
             .get()
-            .is_some_and(|status| status.needs_clone_stop(clone_args))
+            .is_some_and(|status| status.has_pending_interrupt())
+    }
+
+    /// Lets the tracer of this thread trace `child_thread`, which is created by this thread with
+    /// `clone_args`, if the tracer has asked for it.
+    ///
+    /// Returns the ptrace event that this thread should stop on after the child starts running.
+    pub(in crate::process) fn ptrace_trace_child(
+        &self,
+        child_thread: &Arc<Thread>,
+        clone_args: &CloneArgs,
+    ) -> Option<PtraceEvent> {
+        let status = self.tracee_status.get()?;
+        if clone_args.flags.contains(CloneFlags::CLONE_UNTRACED) {
+            return None;
+        }
+
+        let child = child_thread.as_posix_thread().unwrap();
+        let event = PtraceEvent::for_clone(clone_args, child.tid());
+        let (tracer, options, is_seized) = status.tracer_for_event(&event)?;
+
+        let tracer_posix_thread = tracer.as_posix_thread().unwrap();
+        if tracer_posix_thread
+            .attach_to(&tracer, child_thread.clone(), options, is_seized)
+            .is_err()
+            || !child.is_traced()
+        {
+            return None;
+        }
+
+        // The child starts with a `SIGSTOP` signal-delivery-stop, or a `PTRACE_EVENT_STOP` if
+        // the tracer uses `PTRACE_SEIZE`.
+        if is_seized {
+            let _ = child.ptrace_interrupt();
+        } else {
+            child.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+        }
+
+        Some(event)
     }
 
     /// Returns the ptrace-stop status changes for the `wait` syscall.
@@ -146,12 +239,26 @@ impl PosixThread {
         Ok(())
     }
 
+    /// Requests a `PTRACE_INTERRUPT` stop of this thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EIO` if this thread is not attached with `PTRACE_SEIZE`.
+    pub fn ptrace_interrupt(&self) -> Result<()> {
+        let status = self.get_tracee_status()?;
+
+        status.interrupt()?;
+        self.wake_signalled_waker();
+
+        Ok(())
+    }
+
     /// Gets the general-purpose registers of this thread for ptrace.
     ///
     /// # Errors
     ///
     /// Returns `ESRCH` if this thread is not ptrace-stopped.
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
     pub fn ptrace_get_regs(&self) -> Result<arch_ptrace::CUserRegsStruct> {
         let status = self.get_tracee_status()?;
         status.get_regs()
@@ -162,12 +269,34 @@ impl PosixThread {
     /// # Errors
     ///
     /// Returns `ESRCH` if this thread is not ptrace-stopped.
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
     pub fn ptrace_set_regs(&self, regs: arch_ptrace::CUserRegsStruct) -> Result<()> {
         let status = self.get_tracee_status()?;
         status.set_regs(regs)
     }
 
+    /// Gets the floating-point registers of this thread for ptrace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    pub fn ptrace_get_fpregs(&self) -> Result<arch_ptrace::CUserFpregsStruct> {
+        let status = self.get_tracee_status()?;
+        status.get_fpregs()
+    }
+
+    /// Sets the floating-point registers of this thread for ptrace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    pub fn ptrace_set_fpregs(&self, fpregs: arch_ptrace::CUserFpregsStruct) -> Result<()> {
+        let status = self.get_tracee_status()?;
+        status.set_fpregs(fpregs)
+    }
+
     /// Reads one word in the tracee's USER area.
     ///
     /// # Errors
@@ -256,6 +385,8 @@ impl PosixThread {
 impl PosixThread {
     /// Attaches this tracer to the given tracee.
     ///
+    /// `is_seized` indicates whether the tracee is attached with `PTRACE_SEIZE`.
+    ///
     /// # Errors
     ///
     /// Returns `EPERM` if the tracee is already being traced.
@@ -264,7 +395,13 @@ impl PosixThread {
     ///
     /// Panics if `tracer_thread` and `self` do not point to the same thread,
     /// or if `tracee_thread` is not a POSIX thread.
-    pub fn attach_to(&self, tracer_thread: &Arc<Thread>, tracee_thread: Arc<Thread>) -> Result<()> {
+    pub fn attach_to(
+        &self,
+        tracer_thread: &Arc<Thread>,
+        tracee_thread: Arc<Thread>,
+        options: PtraceOptions,
+        is_seized: bool,
+    ) -> Result<()> {
         debug_assert!(core::ptr::eq(
             tracer_thread.as_posix_thread().unwrap(),
             self
@@ -282,12 +419,42 @@ impl PosixThread {
         }
 
         let tracee = tracee_thread.as_posix_thread().unwrap();
-        tracee.set_tracer(Arc::downgrade(tracer_thread))?;
+        tracee.set_tracer(Arc::downgrade(tracer_thread), options, is_seized)?;
         tracees.insert(tracee.tid(), tracee_thread);
 
         Ok(())
     }
 
+    /// Detaches this tracer from the tracee with the given tid.
+    ///
+    /// The tracee is continued with the signal `sig_num` if it is not `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if there is no tracee with the given tid,
+    /// or if the tracee is not ptrace-stopped.
+    pub fn detach_from(&self, tid: Tid, sig_num: Option<SigNum>, ctx: &Context) -> Result<()> {
+        let tracees = self
+            .tracees()
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "no such tracee"))?;
+
+        // Lock order: tracer.tracees -> tracee.tracee_status
+        let mut tracees = tracees.lock();
+        let tracee_thread = tracees
+            .get(&tid)
+            .cloned()
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "no such tracee"))?;
+        let tracee = tracee_thread.as_posix_thread().unwrap();
+
+        tracee.get_tracee_status()?.detach(sig_num, ctx)?;
+        tracees.remove(&tid);
+        drop(tracees);
+
+        tracee.wake_signalled_waker();
+
+        Ok(())
+    }
+
     /// Returns the tracee map of this thread if it is a tracer.
     pub(in crate::process) fn tracees(&self) -> Option<&Mutex<BTreeMap<Tid, Arc<Thread>>>> {
         self.tracees.get()
@@ -338,6 +505,8 @@ impl PosixThread {
 
 pub(super) struct TraceeStatus {
     is_stopped: AtomicBool,
+    /// Whether the tracer has requested a `PTRACE_INTERRUPT` stop.
+    has_pending_interrupt: AtomicBool,
     state: Mutex<TraceeState>,
 }
 
@@ -345,6 +514,7 @@ impl TraceeStatus {
     pub(super) fn new() -> Self {
         Self {
             is_stopped: AtomicBool::new(false),
+            has_pending_interrupt: AtomicBool::new(false),
             state: Mutex::new(TraceeState::new()),
         }
     }
@@ -353,16 +523,36 @@ impl TraceeStatus {
         self.state.lock().tracer()
     }
 
-    fn set_tracer(&self, tracer: Weak<Thread>) -> Result<()> {
+    fn set_tracer(
+        &self,
+        tracer: Weak<Thread>,
+        options: PtraceOptions,
+        is_seized: bool,
+    ) -> Result<()> {
         let mut state = self.state.lock();
         if state.tracer().is_some() {
             return_errno_with_message!(Errno::EPERM, "the thread is already being traced");
         }
         state.tracer = tracer;
+        state.options = options;
+        state.is_seized = is_seized;
+        state.reported_stop_id = None;
 
         Ok(())
     }
 
+    /// Returns the tracer, its options, and whether the tracee is seized,
+    /// if the tracer wants to stop the tracee on `event`.
+    fn tracer_for_event(&self, event: &PtraceEvent) -> Option<(Arc<Thread>, PtraceOptions, bool)> {
+        let state = self.state.lock();
+        let tracer = state.tracer()?;
+        if !state.options.contains(event.option()) {
+            return None;
+        }
+
+        Some((tracer, state.options, state.is_seized))
+    }
+
     fn detach_tracer_with<F>(&self, detach_callback: F)
     where
         F: FnOnce(&TraceeState),
@@ -370,6 +560,28 @@ impl TraceeStatus {
         // Hold the lock first to avoid race conditions.
         let mut state = self.state.lock();
 
+        detach_callback(&state);
+        self.reset_on_detach(&mut state);
+    }
+
+    fn detach(&self, sig_num: Option<SigNum>, ctx: &Context) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        if let Some(sig_num) = sig_num {
+            let signal = Box::new(UserSignal::new_kill(sig_num, ctx));
+            state.signal.inject(signal);
+        } else {
+            state.signal.clear();
+        }
+
+        self.reset_on_detach(&mut state);
+
+        Ok(())
+    }
+
+    fn reset_on_detach(&self, state: &mut TraceeState) {
         state.tracer = Weak::new();
         #[cfg(target_arch = "x86_64")]
         {
@@ -378,7 +590,8 @@ impl TraceeStatus {
             }
         }
         state.is_tracing_syscall = false;
-        detach_callback(&state);
+        state.is_listening = false;
+        self.has_pending_interrupt.store(false, Ordering::Relaxed);
         self.is_stopped.store(false, Ordering::Relaxed);
     }
 
@@ -388,9 +601,6 @@ impl TraceeStatus {
         ctx: &Context,
         user_ctx: &mut UserContext,
     ) -> PtraceStopResult {
-        #[cfg(not(target_arch = "x86_64"))]
-        let _ = user_ctx;
-
         // Hold the lock first to avoid race conditions.
         let state = self.state.lock();
 
@@ -403,12 +613,17 @@ impl TraceeStatus {
         self.do_ptrace_stop(state, tracer, signal, wait_status, None, ctx, user_ctx)
     }
 
-    fn ptrace_may_stop_on(&self, event: PtraceEvent, ctx: &Context, user_ctx: &mut UserContext) {
+    fn ptrace_may_stop_on(
+        &self,
+        event: PtraceEvent,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+    ) -> PtraceStopResult {
         // Hold the lock first to avoid race conditions.
         let state = self.state.lock();
 
         let Some(tracer) = state.tracer() else {
-            return;
+            return PtraceStopResult::NotTraced(None);
         };
 
         if !state.options.contains(event.option()) {
@@ -422,7 +637,7 @@ impl TraceeStatus {
                 ctx.posix_thread
                     .enqueue_signal(Box::new(UserSignal::new_kill(SIGTRAP, ctx)));
             }
-            return;
+            return PtraceStopResult::NotTraced(None);
         }
 
         let siginfo = event.siginfo(ctx);
@@ -438,7 +653,7 @@ impl TraceeStatus {
             Some(event),
             ctx,
             user_ctx,
-        );
+        )
     }
 
     fn ptrace_may_stop_on_syscall(
@@ -463,6 +678,85 @@ impl TraceeStatus {
         self.do_ptrace_stop(state, tracer, signal, wait_status, None, ctx, user_ctx)
     }
 
+    fn has_pending_interrupt(&self) -> bool {
+        self.has_pending_interrupt.load(Ordering::Relaxed)
+    }
+
+    fn interrupt(&self) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+        if state.tracer().is_none() || !state.is_seized {
+            return_errno_with_message!(Errno::EIO, "the thread is not attached with PTRACE_SEIZE");
+        }
+
+        // A ptrace-stopped tracee is not interrupted again.
+        if !self.is_ptrace_stopped() {
+            self.has_pending_interrupt.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    fn group_stop(&self, ctx: &Context, user_ctx: &mut UserContext) -> bool {
+        {
+            // Hold the lock first to avoid race conditions.
+            let state = self.state.lock();
+            if state.tracer().is_none() {
+                return false;
+            }
+
+            // The group-stop has been reported to the tracer, which has restarted this thread.
+            let Some((_, stop_id)) = ctx.process.stop_signal_and_id() else {
+                return true;
+            };
+            if state.reported_stop_id == Some(stop_id) {
+                return !state.is_listening;
+            }
+        }
+
+        if !matches!(
+            self.ptrace_trap_stop(ctx, user_ctx),
+            PtraceStopResult::Continued(_)
+        ) {
+            return false;
+        }
+
+        // The thread stays in the group-stop if the tracer detaches or uses `PTRACE_LISTEN`.
+        let state = self.state.lock();
+        state.tracer().is_some() && !state.is_listening
+    }
+
+    /// Stops this thread at a group-stop or a `PTRACE_INTERRUPT` stop.
+    //
+    // Reference: <https://man7.org/linux/man-pages/man2/ptrace.2.html> (the "Group-stop" and
+    // "PTRACE_EVENT_STOP" sections).
+    fn ptrace_trap_stop(&self, ctx: &Context, user_ctx: &mut UserContext) -> PtraceStopResult {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+
+        self.has_pending_interrupt.store(false, Ordering::Relaxed);
+        let Some(tracer) = state.tracer() else {
+            return PtraceStopResult::NotTraced(None);
+        };
+
+        let sig_num = if let Some((sig_num, stop_id)) = ctx.process.stop_signal_and_id() {
+            state.reported_stop_id = Some(stop_id);
+            sig_num
+        } else {
+            SIGTRAP
+        };
+
+        let siginfo = util::trap_stop_siginfo(sig_num, state.is_seized, ctx);
+        let signal = Box::new(RawSignal::new(siginfo));
+        let signal = DequeuedSignal::FromThread(signal);
+        let wait_status = PtraceWaitStatus::from_trap_stop(sig_num, state.is_seized);
+        state.is_trap_stop = true;
+
+        // The signal injected by the tracer is not delivered after a group-stop or
+        // a `PTRACE_INTERRUPT` stop, so it is dropped by the callers.
+        self.do_ptrace_stop(state, tracer, signal, wait_status, None, ctx, user_ctx)
+    }
+
     #[expect(clippy::too_many_arguments)]
     fn do_ptrace_stop(
         &self,
@@ -474,21 +768,30 @@ impl TraceeStatus {
         ctx: &Context,
         user_ctx: &mut UserContext,
     ) -> PtraceStopResult {
-        #[cfg(not(target_arch = "x86_64"))]
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "riscv64")))]
         let _ = user_ctx;
 
         debug_assert!(!self.is_ptrace_stopped());
 
         state.signal.stop(signal, wait_status);
         state.event = event;
+        #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+        {
+            let supp = ctx.thread_local.supp_user_context();
+            state.general_regs = Some(*user_ctx.general_regs());
+            state.fpu = Some(supp.fpu().get());
+        }
         #[cfg(target_arch = "x86_64")]
         {
             let supp = ctx.thread_local.supp_user_context();
             state.fs_base = Some(supp.fs_base().get());
             state.gs_base = Some(supp.gs_base().get());
-            state.general_regs = Some(*user_ctx.general_regs());
             state.set_orig_syscall_ret(ctx.thread_local.orig_syscall_ret());
         }
+        #[cfg(target_arch = "riscv64")]
+        {
+            state.pc = Some(user_ctx.instruction_pointer());
+        }
         self.is_stopped.store(true, Ordering::Relaxed);
         drop(state);
 
@@ -512,13 +815,22 @@ impl TraceeStatus {
             let mut state = self.state.lock();
             state.signal.clear();
             state.event = None;
-            #[cfg(target_arch = "x86_64")]
+            state.is_trap_stop = false;
+            #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
             {
                 state.general_regs = None;
+                state.fpu = None;
+            }
+            #[cfg(target_arch = "x86_64")]
+            {
                 state.fs_base = None;
                 state.gs_base = None;
                 state.clear_orig_syscall_ret();
             }
+            #[cfg(target_arch = "riscv64")]
+            {
+                state.pc = None;
+            }
             state.is_tracing_syscall = false;
             self.is_stopped.store(false, Ordering::Relaxed);
             return PtraceStopResult::Interrupted;
@@ -527,38 +839,29 @@ impl TraceeStatus {
         let mut state = self.state.lock();
         let signal = state.signal.clear();
         state.event = None;
+        state.is_trap_stop = false;
 
-        #[cfg(target_arch = "x86_64")]
+        #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
         {
             let general_regs = state.general_regs.take().unwrap();
             *user_ctx.general_regs_mut() = general_regs;
+            let supp = ctx.thread_local.supp_user_context();
+            supp.fpu().set(state.fpu.take().unwrap());
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
             let supp = ctx.thread_local.supp_user_context();
             supp.fs_base().set(state.fs_base.take().unwrap());
             supp.gs_base().set(state.gs_base.take().unwrap());
             ctx.thread_local
                 .set_orig_syscall_ret(state.take_orig_syscall_ret());
         }
-
-        PtraceStopResult::Continued(signal)
-    }
-
-    fn needs_clone_stop(&self, clone_args: &CloneArgs) -> bool {
-        let state = self.state.lock();
-        if state.tracer().is_none() {
-            return false;
-        }
-        let options = state.options;
-
-        if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
-            return options.contains(PtraceOptions::PTRACE_O_TRACEVFORK)
-                || options.contains(PtraceOptions::PTRACE_O_TRACEVFORKDONE);
-        }
-
-        if clone_args.exit_signal == Some(SIGCHLD) {
-            return options.contains(PtraceOptions::PTRACE_O_TRACEFORK);
+        #[cfg(target_arch = "riscv64")]
+        {
+            user_ctx.set_instruction_pointer(state.pc.take().unwrap());
         }
 
-        options.contains(PtraceOptions::PTRACE_O_TRACECLONE)
+        PtraceStopResult::Continued(signal)
     }
 
     fn is_ptrace_stopped(&self) -> bool {
@@ -590,6 +893,18 @@ impl TraceeStatus {
         let mut state = self.state.lock();
         self.check_ptrace_stopped(&state)?;
 
+        if matches!(request, PtraceContRequest::Listen) {
+            if !state.is_seized || !state.is_trap_stop {
+                return_errno_with_message!(
+                    Errno::EIO,
+                    "PTRACE_LISTEN only works for seized tracees in group-stops"
+                );
+            }
+            state.is_listening = true;
+        } else {
+            state.is_listening = false;
+        }
+
         if let Some(sig_num) = request.sig_num() {
             let signal = Box::new(UserSignal::new_kill(sig_num, ctx));
             state.signal.inject(signal);
@@ -628,6 +943,19 @@ impl TraceeStatus {
         Ok(regs)
     }
 
+    #[cfg(target_arch = "riscv64")]
+    fn get_regs(&self) -> Result<arch_ptrace::CUserRegsStruct> {
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        let general_regs = state.general_regs.as_ref().unwrap();
+        Ok(arch_ptrace::CUserRegsStruct::from_regs(
+            general_regs,
+            state.pc.unwrap(),
+        ))
+    }
+
     #[cfg(target_arch = "x86_64")]
     fn set_regs(&self, regs: arch_ptrace::CUserRegsStruct) -> Result<()> {
         // Hold the lock first to avoid race conditions.
@@ -650,6 +978,47 @@ impl TraceeStatus {
         Ok(())
     }
 
+    #[cfg(target_arch = "riscv64")]
+    fn set_regs(&self, regs: arch_ptrace::CUserRegsStruct) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        let TraceeState {
+            general_regs, pc, ..
+        } = &mut *state;
+        regs.apply_to(general_regs.as_mut().unwrap(), pc.as_mut().unwrap())
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn get_fpregs(&self) -> Result<arch_ptrace::CUserFpregsStruct> {
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        let fpu = state.fpu.as_ref().unwrap();
+        Ok(arch_ptrace::CUserFpregsStruct::from_fpu(fpu))
+    }
+
+    #[cfg(target_arch = "riscv64")]
+    fn get_fpregs(&self) -> Result<arch_ptrace::CUserFpregsStruct> {
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        let fpu = state.fpu.as_ref().unwrap();
+        arch_ptrace::CUserFpregsStruct::from_fpu(fpu)
+    }
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    fn set_fpregs(&self, fpregs: arch_ptrace::CUserFpregsStruct) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        fpregs.apply_to(state.fpu.as_mut().unwrap())
+    }
+
     #[cfg(target_arch = "x86_64")]
     fn peek_user(&self, offset: usize) -> Result<usize> {
         // Hold the lock first to avoid race conditions.
@@ -765,9 +1134,23 @@ struct TraceeState {
     options: PtraceOptions,
     /// Whether the tracee should stop at the next syscall enter or exit.
     is_tracing_syscall: bool,
+    /// Whether the tracee is attached with `PTRACE_SEIZE`.
+    is_seized: bool,
+    /// Whether the current ptrace-stop is a group-stop or a `PTRACE_INTERRUPT` stop.
+    is_trap_stop: bool,
+    /// Whether the tracee is restarted with `PTRACE_LISTEN` and stays in the group-stop.
+    is_listening: bool,
+    /// The ID of the last group-stop reported to the tracer.
+    reported_stop_id: Option<u64>,
     /// The general-purpose registers of the tracee at the time of ptrace-stop.
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
     general_regs: Option<GeneralRegs>,
+    /// The FPU state of the tracee at the time of ptrace-stop.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    fpu: Option<FpuContext>,
+    /// The program counter of the tracee at the time of ptrace-stop.
+    #[cfg(target_arch = "riscv64")]
+    pc: Option<usize>,
     /// The FS base of the tracee at the time of ptrace-stop.
     #[cfg(target_arch = "x86_64")]
     fs_base: Option<FsBase>,
@@ -788,8 +1171,16 @@ impl TraceeState {
             event: None,
             options: PtraceOptions::empty(),
             is_tracing_syscall: false,
-            #[cfg(target_arch = "x86_64")]
+            is_seized: false,
+            is_trap_stop: false,
+            is_listening: false,
+            reported_stop_id: None,
+            #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
             general_regs: None,
+            #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+            fpu: None,
+            #[cfg(target_arch = "riscv64")]
+            pc: None,
             #[cfg(target_arch = "x86_64")]
             fs_base: None,
             #[cfg(target_arch = "x86_64")]
@@ -5,9 +5,12 @@
 use crate::{
     prelude::*,
     process::{
-        ExitCode, WaitOptions,
+        CloneArgs, CloneFlags, ExitCode, WaitOptions,
         signal::{
-            DequeuedSignal, c_types::siginfo_t, constants::SIGTRAP, sig_num::SigNum,
+            DequeuedSignal,
+            c_types::siginfo_t,
+            constants::{SIGCHLD, SIGTRAP},
+            sig_num::SigNum,
             signals::Signal,
         },
     },
@@ -20,6 +23,8 @@ pub enum PtraceContRequest {
     #[cfg_attr(not(target_arch = "x86_64"), expect(dead_code))]
     SingleStep(Option<SigNum>),
     Syscall(Option<SigNum>),
+    /// Restarts a seized tracee in a group-stop, but keeps it stopped.
+    Listen,
 }
 
 impl PtraceContRequest {
@@ -142,6 +147,8 @@ bitflags! {
         const PTRACE_O_TRACEVFORKDONE = 1 << PtraceEvent::VforkDone(0).code();
         /// Stops the tracee at `exit`.
         const PTRACE_O_TRACEEXIT = 1 << PtraceEvent::Exit(0).code();
+        /// Stops the tracee when a seccomp filter returns `SECCOMP_RET_TRACE`.
+        const PTRACE_O_TRACESECCOMP = 1 << PtraceEvent::Seccomp(0).code();
         /// Send a `SIGKILL` signal to the tracee if the tracer exits.
         const PTRACE_O_EXITKILL = 1 << 20;
     }
@@ -162,9 +169,22 @@ pub enum PtraceEvent {
     VforkDone(Tid),
     /// An `exit` event with the tracee's exit code.
     Exit(ExitCode),
+    /// A seccomp event with the `SECCOMP_RET_DATA` part of the filter's return value.
+    Seccomp(u16),
 }
 
 impl PtraceEvent {
+    /// Returns the event reported when a thread creates `child_tid` with `clone_args`.
+    pub(in crate::process) fn for_clone(clone_args: &CloneArgs, child_tid: Tid) -> Self {
+        if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
+            Self::Vfork(child_tid)
+        } else if clone_args.exit_signal == Some(SIGCHLD) {
+            Self::Fork(child_tid)
+        } else {
+            Self::Clone(child_tid)
+        }
+    }
+
     /// Returns the Linux `PTRACE_EVENT_*` code of this event.
     const fn code(&self) -> u32 {
         match self {
@@ -174,6 +194,7 @@ impl PtraceEvent {
             Self::Exec(_) => 4,
             Self::VforkDone(_) => 5,
             Self::Exit(_) => 6,
+            Self::Seccomp(_) => 7,
         }
     }
 
@@ -191,6 +212,7 @@ impl PtraceEvent {
             | Self::Exec(tid)
             | Self::VforkDone(tid) => *tid as usize,
             Self::Exit(exit_code) => *exit_code as usize,
+            Self::Seccomp(data) => *data as usize,
         }
     }
 
@@ -216,6 +238,17 @@ impl PtraceWaitStatus {
         Self(sig.as_u8() as i32)
     }
 
+    /// Creates the status of a group-stop or a `PTRACE_INTERRUPT` stop.
+    ///
+    /// Only seized tracees can tell these stops from signal-delivery-stops.
+    pub(super) fn from_trap_stop(sig: SigNum, is_seized: bool) -> Self {
+        let mut status = sig.as_u8() as i32;
+        if is_seized {
+            status |= PTRACE_EVENT_STOP << 8;
+        }
+        Self(status)
+    }
+
     pub(super) fn from_syscall(options: &PtraceOptions) -> Self {
         let mut sig = SIGTRAP.as_u8() as i32;
         if options.contains(PtraceOptions::PTRACE_O_TRACESYSGOOD) {
@@ -233,6 +266,17 @@ impl PtraceWaitStatus {
     }
 }
 
+/// The event code of group-stops and `PTRACE_INTERRUPT` stops of seized tracees.
+const PTRACE_EVENT_STOP: i32 = 128;
+
+/// Creates a `siginfo_t` for a group-stop or a `PTRACE_INTERRUPT` stop.
+pub(super) fn trap_stop_siginfo(sig: SigNum, is_seized: bool, ctx: &Context) -> siginfo_t {
+    let code = PtraceWaitStatus::from_trap_stop(sig, is_seized).0;
+    let mut siginfo = siginfo_t::new(sig, code);
+    siginfo.set_pid_uid_by(ctx);
+    siginfo
+}
+
 /// Creates a `siginfo_t` for a syscall-stop.
 pub(super) fn syscall_stop_siginfo(options: &PtraceOptions, ctx: &Context) -> siginfo_t {
     let code = PtraceWaitStatus::from_syscall(options).0;
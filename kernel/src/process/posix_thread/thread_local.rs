@@ -3,7 +3,7 @@
 use core::cell::{Cell, Ref, RefCell, RefMut};
 
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GsBase};
+use ostd::arch::cpu::context::{FsBase, GsBase, TlsEntries};
 use ostd::{
     arch::cpu::context::FpuContext, irq::DisabledLocalIrqGuard, sync::RwArc, task::CurrentTask,
 };
@@ -11,6 +11,7 @@ use ostd::{
 use super::{RobustListHead, cpu_sync::CpuSync};
 use crate::{
     fs::{file::file_table::FileTable, thread_info::ThreadFsInfo},
+    io_uring::IoUringTask,
     prelude::*,
     process::{
         NsProxy, UserNamespace,
@@ -29,6 +30,9 @@ pub struct ThreadLocal {
     // Virtual memory address regions.
     vmar: RefCell<Option<VmarHandle>>,
     page_fault_disabled: Cell<bool>,
+    /// Whether a memory cgroup of the thread has exceeded `memory.high`
+    /// since the thread last returned to the user space.
+    is_memcg_over_high: Cell<bool>,
 
     // Robust futexes.
     // https://man7.org/linux/man-pages/man2/get_robust_list.2.html
@@ -52,10 +56,17 @@ pub struct ThreadLocal {
     /// Original syscall-return register value captured
     /// at the most recent kernel entry, or `None` for non-syscall entries.
     orig_syscall_ret: Cell<Option<usize>>,
+    /// Whether the current system call is a 32-bit system call.
+    #[cfg(target_arch = "x86_64")]
+    in_compat_syscall: Cell<bool>,
 
     // Namespaces.
     user_ns: RefCell<Arc<UserNamespace>>,
     ns_proxy: RefCell<Option<Arc<NsProxy>>>,
+
+    // io_uring.
+    /// The io_uring instances that the thread has submitted operations to.
+    io_uring_task: IoUringTask,
 }
 
 impl ThreadLocal {
@@ -75,6 +86,7 @@ impl ThreadLocal {
             clear_child_tid: Cell::new(clear_child_tid),
             vmar: RefCell::new(Some(vmar)),
             page_fault_disabled: Cell::new(false),
+            is_memcg_over_high: Cell::new(false),
             robust_list: RefCell::new(None),
             file_table: RefCell::new(Some(file_table)),
             fs: RefCell::new(fs),
@@ -82,8 +94,11 @@ impl ThreadLocal {
             sig_stack: RefCell::new(SigStack::default()),
             sig_mask_saved: Cell::new(None),
             orig_syscall_ret: Cell::new(None),
+            #[cfg(target_arch = "x86_64")]
+            in_compat_syscall: Cell::new(false),
             user_ns: RefCell::new(user_ns),
             ns_proxy: RefCell::new(Some(ns_proxy)),
+            io_uring_task: IoUringTask::default(),
         }
     }
 
@@ -188,6 +203,35 @@ impl ThreadLocal {
         self.orig_syscall_ret.set(value);
     }
 
+    /// Returns whether the current system call is a 32-bit system call.
+    ///
+    /// The 32-bit system calls expect the user-space structures in their 32-bit layouts.
+    pub fn in_compat_syscall(&self) -> bool {
+        #[cfg(target_arch = "x86_64")]
+        return self.in_compat_syscall.get();
+        #[cfg(not(target_arch = "x86_64"))]
+        false
+    }
+
+    /// Sets whether the current system call is a 32-bit system call.
+    #[cfg(target_arch = "x86_64")]
+    pub(crate) fn set_in_compat_syscall(&self, in_compat_syscall: bool) {
+        self.in_compat_syscall.set(in_compat_syscall);
+    }
+
+    /// Marks that a memory cgroup of the thread has exceeded `memory.high`.
+    ///
+    /// The thread will be throttled before returning to the user space.
+    pub fn set_memcg_over_high(&self) {
+        self.is_memcg_over_high.set(true);
+    }
+
+    /// Returns whether a memory cgroup of the thread has exceeded `memory.high`
+    /// since the last call, and clears the mark.
+    pub fn take_memcg_over_high(&self) -> bool {
+        self.is_memcg_over_high.replace(false)
+    }
+
     pub fn borrow_user_ns(&self) -> Ref<'_, Arc<UserNamespace>> {
         self.user_ns.borrow()
     }
@@ -199,6 +243,10 @@ impl ThreadLocal {
     pub(in crate::process) fn borrow_ns_proxy_mut(&self) -> NsProxyRefMut<'_> {
         ThreadLocalOptionRefMut(self.ns_proxy.borrow_mut())
     }
+
+    pub fn io_uring_task(&self) -> &IoUringTask {
+        &self.io_uring_task
+    }
 }
 
 /// Supplementary userspace CPU context.
@@ -234,6 +282,8 @@ pub struct SuppUserContext {
     fs_base: CpuSync<FsBase>,
     #[cfg(target_arch = "x86_64")]
     gs_base: CpuSync<GsBase>,
+    #[cfg(target_arch = "x86_64")]
+    tls_entries: CpuSync<TlsEntries>,
 }
 
 impl SuppUserContext {
@@ -244,6 +294,8 @@ impl SuppUserContext {
             fs_base: CpuSync::new(FsBase::default()),
             #[cfg(target_arch = "x86_64")]
             gs_base: CpuSync::new(GsBase::default()),
+            #[cfg(target_arch = "x86_64")]
+            tls_entries: CpuSync::new(TlsEntries::default()),
         }
     }
 
@@ -264,6 +316,12 @@ impl SuppUserContext {
         self
     }
 
+    #[cfg(target_arch = "x86_64")]
+    pub fn with_tls_entries(mut self, tls_entries: TlsEntries) -> Self {
+        self.tls_entries = CpuSync::new(tls_entries);
+        self
+    }
+
     pub fn fpu(&self) -> &CpuSync<FpuContext> {
         &self.fpu
     }
@@ -278,12 +336,18 @@ impl SuppUserContext {
         &self.gs_base
     }
 
+    #[cfg(target_arch = "x86_64")]
+    pub fn tls_entries(&self) -> &CpuSync<TlsEntries> {
+        &self.tls_entries
+    }
+
     pub fn before_schedule(&self, guard: &DisabledLocalIrqGuard) {
         self.fpu.before_schedule(guard);
         #[cfg(target_arch = "x86_64")]
         {
             self.fs_base.before_schedule(guard);
             self.gs_base.before_schedule(guard);
+            self.tls_entries.before_schedule(guard);
         }
     }
 
@@ -293,6 +357,7 @@ impl SuppUserContext {
         {
             self.fs_base.before_user_exec(guard);
             self.gs_base.before_user_exec(guard);
+            self.tls_entries.before_user_exec(guard);
         }
     }
 }
@@ -12,7 +12,7 @@ use crate::{
     },
     prelude::*,
     process::{
-        Credentials, ProcessVm, UserNamespace, pid_table,
+        Credentials, PidNamespace, ProcessVm, UserNamespace, pid_table,
         posix_thread::{PosixThreadBuilder, ThreadName, allocate_posix_tid},
         program_loader::ProgramToLoad,
         rlimit::new_resource_limits_for_init,
@@ -20,7 +20,7 @@ use crate::{
     },
     sched::Nice,
     thread::Tid,
-    vm::vmar::VmarHandle,
+    vm::vmar::{VMAR_CAP_ADDR, VmarHandle},
 };
 
 /// Creates and schedules the init process to run.
@@ -95,11 +95,12 @@ fn create_init_process(
     let elf_path = fs.resolver().read().lookup(&fs_path)?;
 
     let pid = allocate_posix_tid();
-    let vmar = VmarHandle::new(ProcessVm::new(elf_path.clone()));
+    let vmar = VmarHandle::new(ProcessVm::new(elf_path.clone(), VMAR_CAP_ADDR));
     let resource_limits = new_resource_limits_for_init();
     let nice = Nice::default();
     let oom_score_adj = 0;
     let sig_dispositions = Arc::new(Mutex::new(SigDispositions::default()));
+    let pid_ns = PidNamespace::get_init_singleton().clone();
     let user_ns = UserNamespace::get_init_singleton().clone();
 
     let init_proc = Process::new(
@@ -109,8 +110,10 @@ fn create_init_process(
         nice,
         oom_score_adj,
         sig_dispositions,
+        pid_ns.clone(),
         user_ns,
     );
+    pid_ns.set_child_reaper(&init_proc);
 
     let init_task = create_init_task(pid, &init_proc, fs, vmar, elf_path, argv, envp)?;
     init_proc.tasks().lock().insert(init_task).unwrap();
@@ -26,7 +26,7 @@ use crate::{
     fs::cgroupfs::CgroupNode,
     prelude::*,
     process::{
-        UserNamespace, WaitOptions,
+        PidNamespace, UserNamespace, WaitOptions,
         signal::{Pollee, sig_queues::SigQueues},
         status::StopWaitStatus,
     },
@@ -42,6 +42,7 @@ mod process_group;
 mod session;
 mod terminal;
 mod timer_manager;
+mod usermode_helper;
 
 use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 pub use init_proc::spawn_init_process;
@@ -53,6 +54,7 @@ use ostd::{
 pub use process_group::ProcessGroup;
 pub use session::Session;
 pub use terminal::Terminal;
+pub(in crate::process) use usermode_helper::spawn_usermode_helper;
 
 /// Process ID.
 pub type Pid = u32;
@@ -152,6 +154,8 @@ pub struct Process {
     start_time: Jiffies,
 
     // Namespaces
+    /// The PID namespace that the process belongs to.
+    pid_ns: Arc<PidNamespace>,
     /// The user namespace
     user_ns: Mutex<Arc<UserNamespace>>,
 }
@@ -233,6 +237,7 @@ impl Process {
         nice: Nice,
         oom_score_adj: i16,
         sig_dispositions: Arc<Mutex<SigDispositions>>,
+        pid_ns: Arc<PidNamespace>,
         user_ns: Arc<UserNamespace>,
     ) -> Arc<Self> {
         Arc::new_cyclic(|process_ref: &Weak<Process>| {
@@ -267,6 +272,7 @@ impl Process {
                 prof_clock,
                 timer_manager,
                 start_time: Jiffies::elapsed(),
+                pid_ns,
                 user_ns: Mutex::new(user_ns),
             }
         })
@@ -336,6 +342,15 @@ impl Process {
         self.parent.pid() == 0
     }
 
+    /// Returns whether the process is the init process of its PID namespace.
+    ///
+    /// The init process of the initial PID namespace is also the global init process.
+    pub fn is_child_reaper(&self) -> bool {
+        self.pid_ns
+            .child_reaper()
+            .is_some_and(|reaper| core::ptr::eq(reaper.as_ref(), self))
+    }
+
     pub(super) fn children(&self) -> &Mutex<Option<BTreeMap<Pid, Arc<Process>>>> {
         &self.children
     }
@@ -717,6 +732,15 @@ impl Process {
         self.status.stop_status().is_stopped()
     }
 
+    /// Returns the signal that stopped the process and the ID of the stop, if the process is
+    /// stopped.
+    ///
+    /// The ID is different for each stop, so it can be used to tell whether a group-stop has
+    /// already been observed.
+    pub fn stop_signal_and_id(&self) -> Option<(SigNum, u64)> {
+        self.status.stop_status().stop_signal_and_id()
+    }
+
     /// Gets and clears the stop status changes for the `wait` syscall.
     pub(super) fn wait_stopped_or_continued(&self, options: WaitOptions) -> Option<StopWaitStatus> {
         self.status.stop_status().wait(options)
@@ -783,6 +807,11 @@ impl Process {
         }
     }
 
+    /// Returns the PID namespace that the process belongs to.
+    pub fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
     pub fn user_ns(&self) -> &Mutex<Arc<UserNamespace>> {
         &self.user_ns
     }
@@ -808,6 +837,17 @@ impl Process {
     pub fn set_cgroup(&self, cgroup: Option<Arc<CgroupNode>>) {
         self.cgroup.update(cgroup);
     }
+
+    /// Returns whether the process is in a cgroup that is being frozen.
+    ///
+    /// The threads of such a process should be parked before returning to
+    /// user space.
+    pub fn is_freezing(&self) -> bool {
+        self.cgroup
+            .read()
+            .get()
+            .is_some_and(|cgroup| cgroup.is_freezing())
+    }
 }
 
 /// Enqueues a process-directed kernel signal asynchronously.
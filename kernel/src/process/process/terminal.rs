@@ -55,11 +55,13 @@ impl dyn Terminal {
                         .map_or(0, |foreground| foreground.pgid())
                 };
 
+                let current = current!();
                 let pgid = if via_master {
                     operate()
                 } else {
-                    self.is_control_and(&current!(), |_, _| Ok(operate()))?
+                    self.is_control_and(&current, |_, _| Ok(operate()))?
                 };
+                let pgid = current.pid_ns().local_id_of(pgid).unwrap_or(0);
 
                 cmd.write(&pgid)?;
             }
@@ -69,7 +71,14 @@ impl dyn Terminal {
                     return_errno_with_message!(Errno::EINVAL, "negative PGIDs are not valid");
                 }
 
-                self.set_foreground(pgid, &current!())?;
+                let current = current!();
+                let pgid = current.pid_ns().global_id_of(pgid).ok_or_else(|| {
+                    Error::with_message(
+                        Errno::ESRCH,
+                        "the process group to be foreground does not exist",
+                    )
+                })?;
+                self.set_foreground(pgid, &current)?;
             }
 
             // Commands about sessions
@@ -91,6 +100,7 @@ impl dyn Terminal {
                 self.unset_control(&current!())?;
             }
             cmd @ GetControlSid => {
+                let current = current!();
                 let sid = if via_master {
                     self.job_control()
                         .session()
@@ -102,8 +112,9 @@ impl dyn Terminal {
                         })?
                         .sid()
                 } else {
-                    self.is_control_and(&current!(), |session, _| Ok(session.sid()))?
+                    self.is_control_and(&current, |session, _| Ok(session.sid()))?
                 };
+                let sid = current.pid_ns().local_id_of(sid).unwrap_or(0);
 
                 cmd.write(&sid)?;
             }
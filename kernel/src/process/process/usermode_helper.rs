@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines functions related to spawning user-mode helpers.
+//!
+//! A user-mode helper is a user program that is started by the kernel on its
+//! own behalf, e.g., the core dump handler specified in
+//! `/proc/sys/kernel/core_pattern`.
+
+use ostd::{arch::cpu::context::UserContext, sync::RwArc, user::UserContextApi};
+
+use super::Process;
+use crate::{
+    fs::{
+        file::{
+            FileLike,
+            file_table::{FdFlags, FileTable},
+        },
+        thread_info::ThreadFsInfo,
+        vfs::path::{FsPath, MountNamespace},
+    },
+    prelude::*,
+    process::{
+        CloneFlags, Credentials, PidNamespace, ProcessVm, UserNamespace,
+        clone::set_parent_and_group,
+        posix_thread::{PosixThreadBuilder, ThreadName, allocate_posix_tid},
+        program_loader::ProgramToLoad,
+        rlimit::new_resource_limits_for_init,
+        signal::sig_disposition::SigDispositions,
+    },
+    sched::Nice,
+    vm::vmar::{VMAR_CAP_ADDR, VmarHandle},
+};
+
+/// Creates and schedules a user-mode helper process to run.
+///
+/// The helper runs as root in the initial namespaces and becomes a child of
+/// the init process. If `stdin` is provided, it is installed as the standard
+/// input of the helper; other file descriptors are left closed.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/umh.c>
+pub(in crate::process) fn spawn_usermode_helper(
+    executable_path: &str,
+    argv: Vec<CString>,
+    envp: Vec<CString>,
+    stdin: Option<Arc<dyn FileLike>>,
+) -> Result<Arc<Process>> {
+    let Some(init_process) = PidNamespace::get_init_singleton().child_reaper() else {
+        return_errno_with_message!(Errno::ESRCH, "the init process does not exist");
+    };
+
+    let fs = {
+        let fs_resolver = MountNamespace::get_init_singleton().new_path_resolver();
+        ThreadFsInfo::new(fs_resolver)
+    };
+    let fs_path = FsPath::try_from(executable_path)?;
+    let elf_path = fs.resolver().read().lookup(&fs_path)?;
+
+    let pid = allocate_posix_tid();
+    let vmar = VmarHandle::new(ProcessVm::new(elf_path.clone(), VMAR_CAP_ADDR));
+    let sig_dispositions = Arc::new(Mutex::new(SigDispositions::default()));
+
+    let process = Process::new(
+        pid,
+        vmar.clone_arc(),
+        new_resource_limits_for_init(),
+        Nice::default(),
+        0,
+        sig_dispositions,
+        PidNamespace::get_init_singleton().clone(),
+        UserNamespace::get_init_singleton().clone(),
+    );
+
+    let (elf_load_info, elf_abs_path) = {
+        let path_resolver = fs.resolver().read();
+
+        let program_to_load =
+            ProgramToLoad::build_from_file(elf_path.clone(), &path_resolver, argv, envp)?;
+        let vmar = process.lock_vmar();
+        let elf_load_info = program_to_load.load_to_vmar(vmar.unwrap(), &path_resolver)?;
+        let elf_abs_path = path_resolver.make_abs_path(&elf_path).into_string();
+
+        (elf_load_info, elf_abs_path)
+    };
+
+    let mut user_ctx = UserContext::default();
+    user_ctx.set_instruction_pointer(elf_load_info.entry_point as _);
+    user_ctx.set_stack_pointer(elf_load_info.user_stack_top as _);
+
+    let mut file_table = FileTable::new();
+    if let Some(stdin) = stdin {
+        file_table.insert(stdin, FdFlags::empty());
+    }
+
+    let thread_name = ThreadName::new_from_executable_path(&elf_abs_path);
+    let task = PosixThreadBuilder::new(
+        pid,
+        thread_name,
+        Box::new(user_ctx),
+        Credentials::new_root(),
+        vmar,
+    )
+    .process(Arc::downgrade(&process))
+    .file_table(RwArc::new(file_table))
+    .fs(Arc::new(fs))
+    .build();
+    process.tasks().lock().insert(task).unwrap();
+
+    set_parent_and_group(CloneFlags::empty(), &init_process, &process);
+
+    process.run();
+
+    Ok(process)
+}
@@ -7,12 +7,20 @@ use crate::{
     process::PidFile,
 };
 
+/// A filter that selects processes.
+///
+/// The IDs in the filter are global IDs, which are translated from the IDs in the PID namespace of
+/// the current process.
 #[derive(Clone, Debug)]
 pub enum ProcessFilter {
     Any,
     WithPid(Pid),
     WithPgid(Pgid),
     WithPidfd(Arc<PidFile>),
+    /// An ID that is not visible in the PID namespace of the current process.
+    ///
+    /// This filter does not select any process.
+    Invisible,
 }
 
 impl ProcessFilter {
@@ -27,8 +35,8 @@ impl ProcessFilter {
 
         match which {
             P_ALL => Ok(ProcessFilter::Any),
-            P_PID => Ok(ProcessFilter::WithPid(id)),
-            P_PGID => Ok(ProcessFilter::WithPgid(id)),
+            P_PID => Ok(Self::with_local_pid(id, ctx)),
+            P_PGID => Ok(Self::with_local_pgid(id, ctx)),
             P_PIDFD => {
                 let fd = FileDesc::try_from(id.cast_signed())
                     .map_err(|_| Error::with_message(Errno::EINVAL, "the pidfd is invalid"))?;
@@ -46,7 +54,7 @@ impl ProcessFilter {
     }
 
     // For `wait4` and `kill`.
-    pub fn from_id(wait_pid: i32) -> Result<Self> {
+    pub fn from_id(wait_pid: i32, ctx: &Context) -> Result<Self> {
         // Reference:
         // <https://man7.org/linux/man-pages/man2/waitpid.2.html>
         // <https://man7.org/linux/man-pages/man2/kill.2.html>
@@ -56,18 +64,32 @@ impl ProcessFilter {
         } else if wait_pid < -1 {
             // "wait for any child process whose process group ID is equal to the absolute value of
             // `pid`"
-            Ok(ProcessFilter::WithPgid((-wait_pid).cast_unsigned()))
+            Ok(Self::with_local_pgid((-wait_pid).cast_unsigned(), ctx))
         } else if wait_pid == -1 {
             // "wait for any child process"
             Ok(ProcessFilter::Any)
         } else if wait_pid == 0 {
             // "wait for any child process whose process group ID is equal to that of the calling
             // process at the time of the call to `waitpid()`"
-            let pgid = current!().pgid();
+            let pgid = ctx.process.pgid();
             Ok(ProcessFilter::WithPgid(pgid))
         } else {
             // "wait for the child whose process ID is equal to the value of `pid`"
-            Ok(ProcessFilter::WithPid(wait_pid.cast_unsigned()))
+            Ok(Self::with_local_pid(wait_pid.cast_unsigned(), ctx))
+        }
+    }
+
+    fn with_local_pid(pid: Pid, ctx: &Context) -> Self {
+        match ctx.process.pid_ns().global_id_of(pid) {
+            Some(global_pid) => ProcessFilter::WithPid(global_pid),
+            None => ProcessFilter::Invisible,
+        }
+    }
+
+    fn with_local_pgid(pgid: Pgid, ctx: &Context) -> Self {
+        match ctx.process.pid_ns().global_id_of(pgid) {
+            Some(global_pgid) => ProcessFilter::WithPgid(global_pgid),
+            None => ProcessFilter::Invisible,
         }
     }
 }
@@ -24,7 +24,7 @@ use crate::{
     vm::{
         page_cache::{Vmo, VmoOptions},
         perms::VmPerms,
-        vmar::{VMAR_CAP_ADDR, Vmar, VmarMapOffset},
+        vmar::{Vmar, VmarMapOffset},
     },
 };
 
@@ -124,9 +124,10 @@ impl Clone for InitStack {
 }
 
 impl InitStack {
-    pub fn new() -> Self {
+    /// Creates a new `InitStack` that lies below `vmar_cap_addr`.
+    pub fn new(vmar_cap_addr: Vaddr) -> Self {
         let nr_pages_padding = {
-            // We do not want the stack top too close to `VMAR_CAP_ADDR`.
+            // We do not want the stack top too close to `vmar_cap_addr`.
             // So we add this fixed padding. Any small value greater than zero will do.
             const NR_FIXED_PADDING_PAGES: usize = 7;
 
@@ -138,7 +139,7 @@ impl InitStack {
 
             nr_random_padding_pages as usize + NR_FIXED_PADDING_PAGES
         };
-        let initial_top = VMAR_CAP_ADDR - PAGE_SIZE * nr_pages_padding;
+        let initial_top = vmar_cap_addr - PAGE_SIZE * nr_pages_padding;
         let max_size = INIT_STACK_SIZE;
 
         Self {
@@ -171,12 +172,16 @@ impl InitStack {
     }
 
     /// Maps the VMO of the init stack and constructs a writer to initialize its content.
+    ///
+    /// If `is_compat` is true, the pointers and the integers are written as 32-bit values for
+    /// 32-bit programs.
     pub(super) fn map_and_write(
         &self,
         vmar: &Vmar,
         argv: Vec<CString>,
         envp: Vec<CString>,
         auxvec: AuxVec,
+        is_compat: bool,
     ) -> Result<()> {
         self.set_uninitialized();
 
@@ -201,6 +206,11 @@ impl InitStack {
             envp,
             auxvec,
             map_addr: self.initial_top - self.max_size,
+            word_size: if is_compat {
+                size_of::<u32>()
+            } else {
+                size_of::<u64>()
+            },
         };
         let (auxv_range, argv_range, envp_range) = writer.write()?;
 
@@ -247,6 +257,8 @@ struct InitStackWriter<'a> {
     auxvec: AuxVec,
     /// The mapping address of the `InitStack`.
     map_addr: usize,
+    /// The size of the pointers and the integers (e.g., `argc`) in the init stack.
+    word_size: usize,
 }
 
 impl InitStackWriter<'_> {
@@ -283,7 +295,7 @@ impl InitStackWriter<'_> {
 
         // Write argc.
         let argc = self.argv.len();
-        self.write_u64(argc as u64)?;
+        self.write_word(argc as u64)?;
 
         // Ensure the stack top is 16-byte aligned.
         debug_assert_eq!(self.pos() & !0xf, self.pos());
@@ -318,25 +330,26 @@ impl InitStackWriter<'_> {
     /// Ensures that the top address of the user stack is 16-byte aligned.
     ///
     /// The 16-byte alignment is required by x86-64 System V ABI.
-    /// To meet that requirement, this method may write some extra 8-byte `u64`s.
+    /// To meet that requirement, this method may write some extra words.
     fn adjust_stack_alignment(&self, envp_pointers: &[u64], argv_pointers: &[u64]) -> Result<()> {
         // Ensure 8-byte alignment.
         self.write_u64(0)?;
-        let auxvec_size = (self.auxvec.table().len() + 1) * (size_of::<u64>() * 2);
-        let envp_pointers_size = (envp_pointers.len() + 1) * size_of::<u64>();
-        let argv_pointers_size = (argv_pointers.len() + 1) * size_of::<u64>();
-        let argc_size = size_of::<u64>();
+        let word_size = self.word_size;
+        let auxvec_size = (self.auxvec.table().len() + 1) * (word_size * 2);
+        let envp_pointers_size = (envp_pointers.len() + 1) * word_size;
+        let argv_pointers_size = (argv_pointers.len() + 1) * word_size;
+        let argc_size = word_size;
         let to_write_size = auxvec_size + envp_pointers_size + argv_pointers_size + argc_size;
-        if !(self.pos() - to_write_size).is_multiple_of(16) {
-            self.write_u64(0)?;
+        while !(self.pos() - to_write_size).is_multiple_of(16) {
+            self.write_word(0)?;
         }
         Ok(())
     }
 
     fn write_aux_vec(&self) -> Result<()> {
         // Write a NULL auxiliary entry.
-        self.write_u64(0)?;
-        self.write_u64(AuxKey::AT_NULL as u64)?;
+        self.write_word(0)?;
+        self.write_word(AuxKey::AT_NULL as u64)?;
         // Write the auxiliary vector.
         let aux_vec: Vec<_> = self
             .auxvec
@@ -345,34 +358,48 @@ impl InitStackWriter<'_> {
             .map(|(aux_key, aux_value)| (*aux_key, *aux_value))
             .collect();
         for (aux_key, aux_value) in aux_vec.iter() {
-            self.write_u64(*aux_value)?;
-            self.write_u64(*aux_key as u64)?;
+            self.write_word(*aux_value)?;
+            self.write_word(*aux_key as u64)?;
         }
         Ok(())
     }
 
     fn write_envp_pointers(&self, mut envp_pointers: Vec<u64>) -> Result<()> {
         // Write a NULL pointer.
-        self.write_u64(0)?;
+        self.write_word(0)?;
         // Write envp pointers.
         envp_pointers.reverse();
         for envp_pointer in envp_pointers {
-            self.write_u64(envp_pointer)?;
+            self.write_word(envp_pointer)?;
         }
         Ok(())
     }
 
     fn write_argv_pointers(&self, mut argv_pointers: Vec<u64>) -> Result<()> {
         // Write a NULL pointer.
-        self.write_u64(0)?;
+        self.write_word(0)?;
         // Write argv pointers.
         argv_pointers.reverse();
         for argv_pointer in argv_pointers {
-            self.write_u64(argv_pointer)?;
+            self.write_word(argv_pointer)?;
         }
         Ok(())
     }
 
+    /// Writes a word, which is either a `u32` or a `u64`, to the stack.
+    /// Returns the writing address.
+    fn write_word(&self, val: u64) -> Result<u64> {
+        if self.word_size == size_of::<u64>() {
+            return self.write_u64(val);
+        }
+
+        let new_pos = self.reserve_pos(size_of::<u32>(), align_of::<u32>())?;
+        let bytes = (val as u32).to_ne_bytes();
+        let mut reader = VmReader::from(bytes.as_slice()).to_fallible();
+        self.vmo.write(new_pos - self.map_addr, &mut reader)?;
+        Ok(new_pos as u64)
+    }
+
     /// Writes a `u64` to the stack.
     /// Returns the writing address.
     fn write_u64(&self, val: u64) -> Result<u64> {
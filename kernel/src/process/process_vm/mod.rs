@@ -12,9 +12,9 @@
 mod heap;
 mod init_stack;
 
-use core::ops::Range;
-#[cfg(target_arch = "riscv64")]
-use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+use core::sync::atomic::AtomicUsize;
+use core::{ops::Range, sync::atomic::Ordering};
 
 use ostd::task::disable_preempt;
 
@@ -28,6 +28,7 @@ pub use self::{
 use crate::{
     fs::vfs::path::Path,
     prelude::*,
+    process::coredump::{AtomicCoredumpFilter, AtomicDumpable, CoredumpFilter, Dumpable},
     vm::vmar::{Vmar, VmarHandle},
 };
 
@@ -77,22 +78,38 @@ pub struct ProcessVm {
     data_range: SpinLock<Range<Vaddr>>,
     /// The executable file.
     executable_file: Path,
+    /// Whether the process can dump core.
+    dumpable: AtomicDumpable,
+    /// The types of memory mappings to be written into core dumps.
+    coredump_filter: AtomicCoredumpFilter,
+    /// The cap address of the user space, which is lower for 32-bit programs.
+    vmar_cap_addr: Vaddr,
     /// The base address for vDSO segment
-    #[cfg(target_arch = "riscv64")]
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
     vdso_base: AtomicUsize,
+    /// The base address for the vDSO of 32-bit programs
+    #[cfg(target_arch = "x86_64")]
+    vdso32_base: AtomicUsize,
 }
 
 impl ProcessVm {
     /// Creates a new `ProcessVm` without mapping anything.
-    pub(super) fn new(executable_file: Path) -> Self {
+    ///
+    /// The user space of the process will lie below `vmar_cap_addr`.
+    pub(super) fn new(executable_file: Path, vmar_cap_addr: Vaddr) -> Self {
         Self {
-            init_stack: InitStack::new(),
+            init_stack: InitStack::new(vmar_cap_addr),
             heap: Heap::new_uninitialized(),
             code_range: SpinLock::new(0..0),
             data_range: SpinLock::new(0..0),
             executable_file,
-            #[cfg(target_arch = "riscv64")]
+            dumpable: AtomicDumpable::new(Dumpable::User),
+            coredump_filter: AtomicCoredumpFilter::new(CoredumpFilter::default()),
+            vmar_cap_addr,
+            #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
             vdso_base: AtomicUsize::new(0),
+            #[cfg(target_arch = "x86_64")]
+            vdso32_base: AtomicUsize::new(0),
         }
     }
 
@@ -104,8 +121,13 @@ impl ProcessVm {
             code_range: SpinLock::new(process_vm.code_range.lock().clone()),
             data_range: SpinLock::new(process_vm.data_range.lock().clone()),
             executable_file: process_vm.executable_file.clone(),
-            #[cfg(target_arch = "riscv64")]
+            dumpable: AtomicDumpable::new(process_vm.dumpable()),
+            coredump_filter: AtomicCoredumpFilter::new(process_vm.coredump_filter()),
+            vmar_cap_addr: process_vm.vmar_cap_addr,
+            #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
             vdso_base: AtomicUsize::new(process_vm.vdso_base.load(Ordering::Relaxed)),
+            #[cfg(target_arch = "x86_64")]
+            vdso32_base: AtomicUsize::new(process_vm.vdso32_base.load(Ordering::Relaxed)),
         }
     }
 
@@ -134,6 +156,35 @@ impl ProcessVm {
         &self.executable_file
     }
 
+    /// Returns whether the process can dump core.
+    pub fn dumpable(&self) -> Dumpable {
+        self.dumpable.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether the process can dump core.
+    pub fn set_dumpable(&self, dumpable: Dumpable) {
+        self.dumpable.store(dumpable, Ordering::Relaxed);
+    }
+
+    /// Returns the types of memory mappings to be written into core dumps.
+    pub fn coredump_filter(&self) -> CoredumpFilter {
+        self.coredump_filter.load(Ordering::Relaxed)
+    }
+
+    /// Sets the types of memory mappings to be written into core dumps.
+    pub fn set_coredump_filter(&self, filter: CoredumpFilter) {
+        self.coredump_filter.store(filter, Ordering::Relaxed);
+    }
+
+    /// Returns the cap address of the user space.
+    ///
+    /// This is [`VMAR_CAP_ADDR`] unless the process runs a 32-bit program.
+    ///
+    /// [`VMAR_CAP_ADDR`]: crate::vm::vmar::VMAR_CAP_ADDR
+    pub fn vmar_cap_addr(&self) -> Vaddr {
+        self.vmar_cap_addr
+    }
+
     /// Maps and writes the initial portion of the main stack of a process.
     pub(super) fn map_and_write_init_stack(
         &self,
@@ -141,8 +192,10 @@ impl ProcessVm {
         argv: Vec<CString>,
         envp: Vec<CString>,
         aux_vec: AuxVec,
+        is_compat: bool,
     ) -> Result<()> {
-        self.init_stack().map_and_write(vmar, argv, envp, aux_vec)
+        self.init_stack()
+            .map_and_write(vmar, argv, envp, aux_vec, is_compat)
     }
 
     /// Maps and initializes the heap virtual memory.
@@ -167,16 +220,28 @@ impl ProcessVm {
     }
 
     /// Returns the base address for vDSO segment.
-    #[cfg(target_arch = "riscv64")]
-    pub(super) fn vdso_base(&self) -> Vaddr {
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    pub fn vdso_base(&self) -> Vaddr {
         self.vdso_base.load(Ordering::Relaxed)
     }
 
     /// Sets the base address for vDSO segment.
-    #[cfg(target_arch = "riscv64")]
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
     pub(super) fn set_vdso_base(&self, addr: Vaddr) {
         self.vdso_base.store(addr, Ordering::Relaxed);
     }
+
+    /// Returns the base address for the vDSO of 32-bit programs.
+    #[cfg(target_arch = "x86_64")]
+    pub fn vdso32_base(&self) -> Vaddr {
+        self.vdso32_base.load(Ordering::Relaxed)
+    }
+
+    /// Sets the base address for the vDSO of 32-bit programs.
+    #[cfg(target_arch = "x86_64")]
+    pub(super) fn set_vdso32_base(&self, addr: Vaddr) {
+        self.vdso32_base.store(addr, Ordering::Relaxed);
+    }
 }
 
 /// A guard to the [`Vmar`] used by a process.
@@ -4,9 +4,11 @@ use core::ops::Range;
 
 use xmas_elf::{
     header::{self, Header, HeaderPt1, HeaderPt2, HeaderPt2_, Machine_, Type_},
-    program::{self, ProgramHeader64},
+    program::{self, ProgramHeader32, ProgramHeader64},
 };
 
+#[cfg(target_arch = "x86_64")]
+use crate::vm::vmar::COMPAT_VMAR_CAP_ADDR;
 use crate::{
     fs::{utils::PATH_MAX, vfs::inode::Inode},
     prelude::*,
@@ -41,7 +43,12 @@ impl ElfHeaders {
         let ph_count = elf_header.pt2.ph_count;
         let ph_entry_size = elf_header.pt2.ph_entry_size;
         let ph_offset = elf_header.pt2.ph_offset;
-        if ph_entry_size as usize != size_of::<ProgramHeader64>() {
+        let expected_ph_entry_size = if elf_header.is_compat() {
+            size_of::<ProgramHeader32>()
+        } else {
+            size_of::<ProgramHeader64>()
+        };
+        if ph_entry_size as usize != expected_ph_entry_size {
             return_errno_with_message!(
                 Errno::ENOEXEC,
                 "the size of ELF program headers is invalid"
@@ -60,6 +67,7 @@ impl ElfHeaders {
         }
 
         // Parse the ELF program headers.
+        let vmar_cap_addr = elf_header.vmar_cap_addr();
         let mut loadable_phdrs = Vec::with_capacity(ph_count as usize);
         let mut max_load_align = PAGE_SIZE;
         let mut interp_phdr = None;
@@ -70,16 +78,11 @@ impl ElfHeaders {
                 })?;
             let ph64 = match program_header {
                 program::ProgramHeader::Ph64(ph64) => *ph64,
-                program::ProgramHeader::Ph32(_) => {
-                    return_errno_with_message!(
-                        Errno::ENOEXEC,
-                        "the ELF program header is not 64-bit"
-                    )
-                }
+                program::ProgramHeader::Ph32(ph32) => ph32_to_ph64(ph32),
             };
             match ph64.get_type() {
                 Ok(program::Type::Load) => {
-                    loadable_phdrs.push(LoadablePhdr::parse(&ph64)?);
+                    loadable_phdrs.push(LoadablePhdr::parse(&ph64, vmar_cap_addr)?);
                     // Like Linux, we ignore any invalid alignment requirements that are not a
                     // power of two.
                     if ph64.align.is_power_of_two() {
@@ -110,6 +113,16 @@ impl ElfHeaders {
         self.elf_header.pt2.type_.as_type() == header::Type::SharedObject
     }
 
+    /// Returns whether the ELF is a 32-bit program, which runs in the compatibility mode.
+    pub(super) fn is_compat(&self) -> bool {
+        self.elf_header.is_compat()
+    }
+
+    /// Returns the cap address of the user space in which the ELF should be loaded.
+    pub(super) fn vmar_cap_addr(&self) -> Vaddr {
+        self.elf_header.vmar_cap_addr()
+    }
+
     /// Returns the address of the entry point.
     pub(super) fn entry_point(&self) -> Vaddr {
         self.elf_header.pt2.entry_point as Vaddr
@@ -187,44 +200,27 @@ impl ElfHeader {
     pub(self) fn parse(header: Header) -> Result<Self> {
         let pt1 = *header.pt1;
         let pt2 = match header.pt2 {
-            HeaderPt2::Header64(header_pt2) => {
-                let HeaderPt2_ {
-                    type_,
-                    machine,
-                    version,
-                    entry_point,
-                    ph_offset,
-                    sh_offset,
-                    flags,
-                    header_size,
-                    ph_entry_size,
-                    ph_count,
-                    sh_entry_size,
-                    sh_count,
-                    sh_str_index,
-                } = header_pt2;
-                HeaderPt2_64 {
-                    type_: *type_,
-                    machine: *machine,
-                    version: *version,
-                    entry_point: *entry_point,
-                    ph_offset: *ph_offset,
-                    sh_offset: *sh_offset,
-                    flags: *flags,
-                    header_size: *header_size,
-                    ph_entry_size: *ph_entry_size,
-                    ph_count: *ph_count,
-                    sh_entry_size: *sh_entry_size,
-                    sh_count: *sh_count,
-                    sh_str_index: *sh_str_index,
-                }
-            }
-            _ => return_errno_with_message!(Errno::ENOEXEC, "the ELF file is not 64-bit"),
+            HeaderPt2::Header64(header_pt2) => HeaderPt2_64::from_pt2(header_pt2),
+            HeaderPt2::Header32(header_pt2) => HeaderPt2_64::from_pt2(header_pt2),
         };
         Ok(ElfHeader { pt1, pt2 })
     }
+
+    pub(self) fn is_compat(&self) -> bool {
+        self.pt1.class() == header::Class::ThirtyTwo
+    }
+
+    pub(self) fn vmar_cap_addr(&self) -> Vaddr {
+        #[cfg(target_arch = "x86_64")]
+        if self.is_compat() {
+            return COMPAT_VMAR_CAP_ADDR;
+        }
+
+        VMAR_CAP_ADDR
+    }
 }
 
+/// The second part of the ELF header, where the 32-bit fields are extended to 64-bit ones.
 struct HeaderPt2_64 {
     type_: Type_,
     machine: Machine_,
@@ -248,6 +244,55 @@ struct HeaderPt2_64 {
     sh_str_index: u16,
 }
 
+impl HeaderPt2_64 {
+    fn from_pt2<P: Copy + Into<u64>>(header_pt2: &HeaderPt2_<P>) -> Self {
+        let HeaderPt2_ {
+            type_,
+            machine,
+            version,
+            entry_point,
+            ph_offset,
+            sh_offset,
+            flags,
+            header_size,
+            ph_entry_size,
+            ph_count,
+            sh_entry_size,
+            sh_count,
+            sh_str_index,
+        } = header_pt2;
+        Self {
+            type_: *type_,
+            machine: *machine,
+            version: *version,
+            entry_point: (*entry_point).into(),
+            ph_offset: (*ph_offset).into(),
+            sh_offset: (*sh_offset).into(),
+            flags: *flags,
+            header_size: *header_size,
+            ph_entry_size: *ph_entry_size,
+            ph_count: *ph_count,
+            sh_entry_size: *sh_entry_size,
+            sh_count: *sh_count,
+            sh_str_index: *sh_str_index,
+        }
+    }
+}
+
+/// Converts a 32-bit program header to a 64-bit one with the same values.
+fn ph32_to_ph64(ph32: &ProgramHeader32) -> ProgramHeader64 {
+    ProgramHeader64 {
+        type_: ph32.type_,
+        flags: ph32.flags,
+        offset: ph32.offset.into(),
+        virtual_addr: ph32.virtual_addr.into(),
+        physical_addr: ph32.physical_addr.into(),
+        file_size: ph32.file_size.into(),
+        mem_size: ph32.mem_size.into(),
+        align: ph32.align.into(),
+    }
+}
+
 fn check_elf_header(elf_header: &ElfHeader) -> Result<()> {
     #[cfg(target_arch = "x86_64")]
     const EXPECTED_ELF_MACHINE: header::Machine = header::Machine::X86_64;
@@ -257,9 +302,13 @@ fn check_elf_header(elf_header: &ElfHeader) -> Result<()> {
     #[cfg(target_arch = "loongarch64")]
     const EXPECTED_ELF_MACHINE: header::Machine = header::Machine::Other(258);
 
-    if elf_header.pt1.class() != header::Class::SixtyFour {
-        return_errno_with_message!(Errno::ENOEXEC, "the ELF file is not 64-bit");
-    }
+    let expected_elf_machine = match elf_header.pt1.class() {
+        header::Class::SixtyFour => EXPECTED_ELF_MACHINE,
+        // 32-bit x86 programs can run in the compatibility mode.
+        #[cfg(target_arch = "x86_64")]
+        header::Class::ThirtyTwo => header::Machine::X86,
+        _ => return_errno_with_message!(Errno::ENOEXEC, "the ELF file is not 64-bit"),
+    };
 
     if elf_header.pt1.data() != header::Data::LittleEndian {
         return_errno_with_message!(Errno::ENOEXEC, "the ELF file is not in little endian");
@@ -267,7 +316,7 @@ fn check_elf_header(elf_header: &ElfHeader) -> Result<()> {
 
     // TODO: Should we check `pt1.os_abi()` or `pt1.version()`?
 
-    if elf_header.pt2.machine.as_machine() != EXPECTED_ELF_MACHINE {
+    if elf_header.pt2.machine.as_machine() != expected_elf_machine {
         return_errno_with_message!(
             Errno::ENOEXEC,
             "the ELF file is of a different architecture"
@@ -290,12 +339,12 @@ pub(super) struct LoadablePhdr {
 }
 
 impl LoadablePhdr {
-    pub(self) fn parse(phdr: &ProgramHeader64) -> Result<Self> {
+    pub(self) fn parse(phdr: &ProgramHeader64, vmar_cap_addr: Vaddr) -> Result<Self> {
         debug_assert_eq!(phdr.get_type(), Ok(program::Type::Load));
 
         let virt_start = phdr.virtual_addr;
         let virt_end = if let Some(virt_end) = virt_start.checked_add(phdr.mem_size)
-            && virt_end <= VMAR_CAP_ADDR as u64
+            && virt_end <= vmar_cap_addr as u64
         {
             virt_end
         } else {
@@ -11,11 +11,14 @@ use super::{
     relocate::RelocatedRange,
 };
 use crate::{
-    fs::vfs::path::{FsPath, Path, PathResolver},
+    fs::{
+        file::file_table::FileDesc,
+        vfs::path::{FsPath, Path, PathResolver},
+    },
     prelude::*,
     process::{
         process_vm::{AuxKey, AuxVec},
-        program_loader::check_executable_inode,
+        program_loader::check_executable_file,
     },
     util::random::getrandom,
     vm::{
@@ -36,11 +39,19 @@ use crate::{
 /// - loongarch64:  ELF_ET_DYN_BASE = TASK_SIZE / 3 * 2
 const PIE_BASE_ADDR: Vaddr = VMAR_CAP_ADDR / 3 * 2;
 
+/// The base address for PIE loading of 32-bit programs.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/asm/elf.h>
+#[cfg(target_arch = "x86_64")]
+const COMPAT_PIE_BASE_ADDR: Vaddr = 0x40_0000;
+
 pub struct ElfLoadInfo {
     /// The relocated entry point.
     pub entry_point: Vaddr,
     /// The top address of the user stack.
     pub user_stack_top: Vaddr,
+    /// Whether the ELF is a 32-bit program, which runs in the compatibility mode.
+    pub is_compat: bool,
 }
 
 /// Loads an ELF file to the process VMAR.
@@ -54,13 +65,17 @@ pub fn load_elf_to_vmar(
     elf_headers: ElfHeaders,
     argv: Vec<CString>,
     envp: Vec<CString>,
+    exec_fd: Option<FileDesc>,
 ) -> Result<ElfLoadInfo> {
+    if elf_headers.vmar_cap_addr() != vmar.process_vm().vmar_cap_addr() {
+        return_errno_with_message!(
+            Errno::ENOEXEC,
+            "the ELF file cannot be loaded into the address space"
+        );
+    }
+
     let ldso = lookup_and_parse_ldso(&elf_headers, &elf_file, path_resolver)?;
 
-    #[cfg_attr(
-        not(any(target_arch = "x86_64", target_arch = "riscv64")),
-        expect(unused_mut)
-    )]
     let (elf_mapped_info, entry_point, mut aux_vec) =
         map_vmos_and_build_aux_vec(vmar, ldso, &elf_headers, &elf_file)?;
     vmar.process_vm()
@@ -71,15 +86,32 @@ pub fn load_elf_to_vmar(
     // Map the vDSO and set the entry.
     // Since the vDSO does not require being mapped to any specific address,
     // the vDSO is mapped after the ELF file, heap, and stack.
+    #[cfg(target_arch = "x86_64")]
+    if elf_headers.is_compat() {
+        let vdso32_base = crate::arch::vdso32::map_vdso32_to_vmar(vmar)?;
+        vmar.process_vm().set_vdso32_base(vdso32_base);
+        aux_vec.set(AuxKey::AT_SYSINFO, vdso32_base as u64);
+    }
     #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
-    if let Some(vdso_text_base) = map_vdso_to_vmar(vmar) {
-        #[cfg(target_arch = "riscv64")]
+    if !elf_headers.is_compat()
+        && let Some(vdso_text_base) = map_vdso_to_vmar(vmar)
+    {
         vmar.process_vm().set_vdso_base(vdso_text_base);
         aux_vec.set(AuxKey::AT_SYSINFO_EHDR, vdso_text_base as u64);
     }
 
-    vmar.process_vm()
-        .map_and_write_init_stack(vmar, argv, envp, aux_vec)?;
+    // Pass the binary opened for its interpreter, as required by a binfmt_misc rule.
+    if let Some(exec_fd) = exec_fd {
+        aux_vec.set(AuxKey::AT_EXECFD, exec_fd.into());
+    }
+
+    vmar.process_vm().map_and_write_init_stack(
+        vmar,
+        argv,
+        envp,
+        aux_vec,
+        elf_headers.is_compat(),
+    )?;
     vmar.process_vm().map_and_init_heap(
         vmar,
         elf_mapped_info.data_range.len(),
@@ -90,6 +122,7 @@ pub fn load_elf_to_vmar(
     Ok(ElfLoadInfo {
         entry_point,
         user_stack_top,
+        is_compat: elf_headers.is_compat(),
     })
 }
 
@@ -118,8 +151,8 @@ fn lookup_and_parse_ldso(
     };
 
     let ldso_elf = {
+        check_executable_file(&ldso_file)?;
         let inode = ldso_file.inode();
-        check_executable_inode(inode.as_ref())?;
 
         let mut buf = Box::new([0u8; PAGE_SIZE]);
         let len = inode.read_bytes_at(0, &mut *buf)?;
@@ -127,8 +160,16 @@ fn lookup_and_parse_ldso(
             return_errno_with_message!(Errno::EIO, "the interpreter format is invalid");
         }
 
-        ElfHeaders::parse(&buf[..len])
-            .map_err(|_| Error::with_message(Errno::ELIBBAD, "the interpreter format is invalid"))?
+        let ldso_elf = ElfHeaders::parse(&buf[..len]).map_err(|_| {
+            Error::with_message(Errno::ELIBBAD, "the interpreter format is invalid")
+        })?;
+        if ldso_elf.is_compat() != headers.is_compat() {
+            return_errno_with_message!(
+                Errno::ELIBBAD,
+                "the interpreter is of a different architecture"
+            );
+        }
+        ldso_elf
     };
 
     Ok(Some((ldso_file, ldso_elf)))
@@ -270,12 +311,12 @@ fn map_segment_vmos(
                 getrandom(nr_random_padding_pages.as_mut_bytes());
                 nr_random_padding_pages as usize
             };
-            let offset = (PIE_BASE_ADDR + nr_pages_padding * PAGE_SIZE).align_down(align);
+            let offset = (pie_base_addr(elf) + nr_pages_padding * PAGE_SIZE).align_down(align);
 
             if offset < VMAR_LOWEST_ADDR {
                 return_errno_with_message!(Errno::EPERM, "the mapping address is too small");
             }
-            if VMAR_CAP_ADDR - offset < map_size {
+            if vmar.process_vm().vmar_cap_addr() - offset < map_size {
                 return_errno_with_message!(Errno::ENOMEM, "the mapping address is too large");
             }
             vmar.new_map(map_size, VmPerms::empty())?
@@ -291,7 +332,7 @@ fn map_segment_vmos(
             // also be placed in the mmap region.
             //
             // Reference: <https://elixir.bootlin.com/linux/v6.16.9/source/fs/binfmt_elf.c#L1293>
-            heap_base = Some(PIE_BASE_ADDR);
+            heap_base = Some(pie_base_addr(elf));
 
             vmar.new_map(map_size, VmPerms::empty())?.align(align)
         };
@@ -373,6 +414,17 @@ fn map_segment_vmos(
     })
 }
 
+/// Returns the base address for PIE loading (see [`PIE_BASE_ADDR`]).
+#[cfg_attr(not(target_arch = "x86_64"), expect(unused_variables))]
+fn pie_base_addr(elf: &ElfHeaders) -> Vaddr {
+    #[cfg(target_arch = "x86_64")]
+    if elf.is_compat() {
+        return COMPAT_PIE_BASE_ADDR;
+    }
+
+    PIE_BASE_ADDR
+}
+
 /// Creates and maps the segment VMO to the VMAR.
 ///
 /// Additional anonymous mappings will be created to represent trailing bytes, if any. For example,
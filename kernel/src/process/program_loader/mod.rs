@@ -9,13 +9,12 @@ use self::{
 };
 use crate::{
     fs::{
-        file::{InodeType, Permission},
-        vfs::{
-            inode::Inode,
-            path::{FsPath, Path, PathResolver},
-        },
+        binfmt_misc::{self, BinfmtFlags},
+        file::{AccessMode, InodeType, Permission, file_table::FileDesc},
+        vfs::path::{FsPath, Path, PathResolver},
     },
     prelude::*,
+    security::lsm::hooks::{self as lsm_hooks, PathOperation},
     vm::vmar::Vmar,
 };
 
@@ -28,6 +27,12 @@ pub(super) struct ProgramToLoad {
     elf_headers: ElfHeaders,
     argv: Vec<CString>,
     envp: Vec<CString>,
+    /// The file whose set-user-ID and set-group-ID bits apply to the new program.
+    creds_file: Path,
+    /// The binary that should be opened for its interpreter, as required by a binfmt_misc rule.
+    binary_to_open: Option<Path>,
+    /// The file descriptor of the opened binary, which is passed to the interpreter.
+    exec_fd: Option<FileDesc>,
 }
 
 impl ProgramToLoad {
@@ -39,13 +44,23 @@ impl ProgramToLoad {
         mut argv: Vec<CString>,
         envp: Vec<CString>,
     ) -> Result<Self> {
-        check_executable_inode(elf_file.inode().as_ref())?;
+        check_executable_file(&elf_file)?;
 
         // A limit to the recursion depth of shebang executables.
         //
         // If the interpreter is a shebang, then recursion will be triggered. If it loops, we
         // should fail. We follow the same limit as Linux.
         let mut recursive_limit = 5;
+        let mut check_recursive_limit = || {
+            if recursive_limit == 0 {
+                return_errno_with_message!(Errno::ELOOP, "the recursieve limit is reached");
+            }
+            recursive_limit -= 1;
+            Ok(())
+        };
+
+        let mut creds_file = elf_file.clone();
+        let mut binary_to_open = None;
 
         let (file_first_page, len) = loop {
             // Read the first page of the file, which should contain a shebang or an ELF header.
@@ -55,21 +70,64 @@ impl ProgramToLoad {
                 (buffer, len)
             };
 
+            // The binary may be handed to an interpreter by a binfmt_misc rule, which takes
+            // precedence over shebangs and ELF headers. The whole page, padded with zeros, is
+            // matched against the rules.
+            if let Some(rule) = binfmt_misc::find_rule(&*file_first_page, &elf_file.name()) {
+                check_recursive_limit()?;
+
+                let interpreter = match rule.interpreter_file() {
+                    Some(interpreter) => interpreter.clone(),
+                    None => {
+                        let fs_path = FsPath::try_from(rule.interpreter())?;
+                        path_resolver.lookup(&fs_path)?
+                    }
+                };
+                check_executable_file(&interpreter)?;
+
+                // The new arguments are the interpreter, the path of the binary, and the original
+                // arguments, where `argv[0]` is kept only with the `P` flag.
+                // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c>
+                let to_cstring = |arg: String| {
+                    CString::new(arg).map_err(|_| {
+                        Error::with_message(Errno::ENOEXEC, "unexpected nul terminator is found")
+                    })
+                };
+                let mut new_argv = vec![
+                    to_cstring(rule.interpreter().to_string())?,
+                    to_cstring(path_resolver.make_abs_path(&elf_file).into_string())?,
+                ];
+                if !rule.flags().contains(BinfmtFlags::PRESERVE_ARGV0) && !argv.is_empty() {
+                    argv.remove(0);
+                }
+                new_argv.extend(argv);
+                argv = new_argv;
+
+                if rule.flags().contains(BinfmtFlags::OPEN_BINARY) {
+                    binary_to_open = Some(elf_file.clone());
+                }
+                creds_file = if rule.flags().contains(BinfmtFlags::CREDENTIALS) {
+                    elf_file
+                } else {
+                    interpreter.clone()
+                };
+
+                elf_file = interpreter;
+                continue;
+            }
+
             let Some(mut new_argv) = parse_shebang_line(&file_first_page[..len])? else {
                 break (file_first_page, len);
             };
 
-            if recursive_limit == 0 {
-                return_errno_with_message!(Errno::ELOOP, "the recursieve limit is reached");
-            }
-            recursive_limit -= 1;
+            check_recursive_limit()?;
 
             let interpreter = {
                 let filename = new_argv[0].to_str()?.to_string();
                 let fs_path = FsPath::try_from(filename.as_str())?;
                 path_resolver.lookup(&fs_path)?
             };
-            check_executable_inode(interpreter.inode().as_ref())?;
+            check_executable_file(&interpreter)?;
 
             // Update the argument list and the executable inode. Then, try again.
             new_argv.extend(argv);
@@ -84,9 +142,36 @@ impl ProgramToLoad {
             elf_headers,
             argv,
             envp,
+            creds_file,
+            binary_to_open,
+            exec_fd: None,
         })
     }
 
+    /// Returns the file whose set-user-ID and set-group-ID bits apply to the new program.
+    pub(super) fn creds_file(&self) -> &Path {
+        &self.creds_file
+    }
+
+    /// Returns the binary that should be opened for its interpreter, if any.
+    ///
+    /// If this method returns `Some(_)`, the caller should open the binary in the new program and
+    /// report the file descriptor via [`Self::set_exec_fd`].
+    pub(super) fn binary_to_open(&self) -> Option<&Path> {
+        self.binary_to_open.as_ref()
+    }
+
+    /// Sets the file descriptor of the opened binary, which is passed to the interpreter via
+    /// `AT_EXECFD`.
+    pub(super) fn set_exec_fd(&mut self, exec_fd: FileDesc) {
+        self.exec_fd = Some(exec_fd);
+    }
+
+    /// Returns the cap address of the user space in which the executable should be loaded.
+    pub(super) fn vmar_cap_addr(&self) -> Vaddr {
+        self.elf_headers.vmar_cap_addr()
+    }
+
     /// Loads the executable into the specified virtual memory space.
     ///
     /// Returns the information about the ELF loading process.
@@ -102,13 +187,16 @@ impl ProgramToLoad {
             self.elf_headers,
             self.argv,
             self.envp,
+            self.exec_fd,
         )?;
 
         Ok(elf_load_info)
     }
 }
 
-fn check_executable_inode(inode: &dyn Inode) -> Result<()> {
+fn check_executable_file(path: &Path) -> Result<()> {
+    let inode = path.inode();
+
     if inode.type_().is_directory() {
         return_errno_with_message!(Errno::EISDIR, "the inode is a directory");
     }
@@ -125,5 +213,10 @@ fn check_executable_inode(inode: &dyn Inode) -> Result<()> {
         return_errno_with_message!(Errno::EACCES, "the inode is not executable");
     }
 
-    Ok(())
+    // The file is opened for reading and execution.
+    lsm_hooks::on_current_path_operation(PathOperation::Open {
+        path,
+        access_mode: AccessMode::O_RDONLY,
+        is_exec: true,
+    })
 }
@@ -6,7 +6,13 @@
 use inherit_methods_macro::inherit_methods;
 use ostd::arch::cpu::context::UserContext;
 
+#[cfg(target_arch = "x86_64")]
+use super::constants::{
+    SI_KERNEL, SI_SIGIO, SI_USER, SIGBUS, SIGCHLD, SIGFPE, SIGILL, SIGIO, SIGSEGV, SIGSYS, SIGTRAP,
+};
 use super::sig_num::SigNum;
+#[cfg(target_arch = "x86_64")]
+use crate::arch::cpu::SigContext32;
 use crate::{
     arch::cpu::SigContext,
     prelude::*,
@@ -65,7 +71,8 @@ impl siginfo_t {
     }
 
     pub fn set_pid_uid_by(&mut self, ctx: &Context) {
-        self.set_pid_uid(ctx.process.pid(), ctx.posix_thread.credentials().ruid());
+        let pid = ctx.process.pid_ns().local_id_of(ctx.process.pid()).unwrap();
+        self.set_pid_uid(pid, ctx.posix_thread.credentials().ruid());
     }
 
     pub fn set_status(&mut self, status: i32) {
@@ -77,6 +84,18 @@ impl siginfo_t {
             .status_mut() = status;
     }
 
+    pub fn set_value(&mut self, value: sigval_t) {
+        *self.siginfo_fields.common_mut().second.value_mut() = value;
+    }
+
+    pub fn set_sigsys(&mut self, call_addr: Vaddr, syscall: i32, arch: u32) {
+        *self.siginfo_fields.sigsys_mut() = siginfo_sigsys_t {
+            call_addr,
+            syscall,
+            arch,
+        };
+    }
+
     pub fn si_addr(&self) -> Vaddr {
         self.siginfo_fields.sigfault().addr
     }
@@ -89,6 +108,7 @@ union siginfo_fields_t {
     bytes: [u8; 128 - size_of::<i32>() * 4],
     common: siginfo_common_t,
     sigfault: siginfo_sigfault_t,
+    sigsys: siginfo_sigsys_t,
 }
 
 impl Default for siginfo_fields_t {
@@ -192,6 +212,14 @@ union siginfo_addr_bnd_t {
     upper: Vaddr, // *const c_void,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Pod)]
+struct siginfo_sigsys_t {
+    call_addr: Vaddr, // *const c_void
+    syscall: i32,
+    arch: u32,
+}
+
 /// Reference: <https://elixir.bootlin.com/linux/v6.15.7/source/include/uapi/asm-generic/ucontext.h#L5>
 #[cfg(target_arch = "x86_64")]
 #[repr(C)]
@@ -323,3 +351,173 @@ pub struct sigevent_t {
     pub sigev_notify: i32,
     pub sigev_un: _sigev_un,
 }
+
+/// The 32-bit version of [`sigset_t`].
+#[cfg(target_arch = "x86_64")]
+pub type compat_sigset_t = [u32; 2];
+
+#[cfg(target_arch = "x86_64")]
+fn sigset_from_compat(set: compat_sigset_t) -> sigset_t {
+    (set[0] as u64) | ((set[1] as u64) << 32)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn sigset_to_compat(set: sigset_t) -> compat_sigset_t {
+    [set as u32, (set >> 32) as u32]
+}
+
+/// The 32-bit version of [`sigaction_t`].
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct compat_sigaction_t {
+    pub handler_ptr: u32,
+    pub flags: u32,
+    pub restorer_ptr: u32,
+    pub mask: compat_sigset_t,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<compat_sigaction_t> for sigaction_t {
+    fn from(value: compat_sigaction_t) -> Self {
+        Self {
+            handler_ptr: value.handler_ptr as Vaddr,
+            flags: value.flags,
+            restorer_ptr: value.restorer_ptr as Vaddr,
+            mask: sigset_from_compat(value.mask),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<sigaction_t> for compat_sigaction_t {
+    fn from(value: sigaction_t) -> Self {
+        Self {
+            handler_ptr: value.handler_ptr as u32,
+            flags: value.flags,
+            restorer_ptr: value.restorer_ptr as u32,
+            mask: sigset_to_compat(value.mask),
+        }
+    }
+}
+
+/// The 32-bit version of [`stack_t`].
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct compat_stack_t {
+    pub ss_sp: u32,
+    pub ss_flags: i32,
+    pub ss_size: u32,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<compat_stack_t> for stack_t {
+    fn from(value: compat_stack_t) -> Self {
+        Self {
+            ss_sp: value.ss_sp as Vaddr,
+            ss_flags: value.ss_flags,
+            ss_size: value.ss_size as usize,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<stack_t> for compat_stack_t {
+    fn from(value: stack_t) -> Self {
+        Self {
+            ss_sp: value.ss_sp as u32,
+            ss_flags: value.ss_flags,
+            ss_size: value.ss_size as u32,
+        }
+    }
+}
+
+/// The 32-bit version of [`siginfo_t`].
+///
+/// The fields start right after `si_code` because the 32-bit layout has no padding there.
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Pod)]
+pub struct compat_siginfo_t {
+    pub si_signo: i32,
+    pub si_errno: i32,
+    pub si_code: i32,
+    fields: [u32; 29],
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<&siginfo_t> for compat_siginfo_t {
+    /// Converts the 64-bit `siginfo_t` according to the layout of its fields.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/signal.c>
+    fn from(info: &siginfo_t) -> Self {
+        // The fields of the 64-bit `siginfo_t` start at the fifth word.
+        let native = <[u32; 32]>::from_bytes(info.as_bytes());
+
+        // Pairs of the compatible word offset and the native word offset of each field.
+        let field_offsets: &[(usize, usize)] = match info.si_code {
+            SI_KERNEL => &[(0, 0), (1, 1)],
+            code if code > SI_USER && code < SI_KERNEL => {
+                let sig_num = SigNum::try_from(info.si_signo as u8).ok();
+                match sig_num {
+                    // `si_addr`
+                    Some(SIGILL | SIGFPE | SIGSEGV | SIGBUS | SIGTRAP) => &[(0, 0)],
+                    // `si_pid`, `si_uid`, `si_status`, `si_utime`, and `si_stime`
+                    Some(SIGCHLD) => &[(0, 0), (1, 1), (2, 2), (3, 4), (4, 6)],
+                    // `si_band` and `si_fd`
+                    Some(SIGIO) => &[(0, 0), (1, 2)],
+                    // `si_call_addr`, `si_syscall`, and `si_arch`
+                    Some(SIGSYS) => &[(0, 0), (1, 2), (2, 3)],
+                    _ => &[(0, 0), (1, 1)],
+                }
+            }
+            // `si_band` and `si_fd`
+            SI_SIGIO => &[(0, 0), (1, 2)],
+            // `si_tid`, `si_overrun`, and `si_value` for `SI_TIMER`, or `si_pid`, `si_uid`, and
+            // `si_value` otherwise
+            code if code < 0 => &[(0, 0), (1, 1), (2, 2)],
+            // `si_pid` and `si_uid`
+            _ => &[(0, 0), (1, 1)],
+        };
+
+        let mut fields = [0u32; 29];
+        for &(compat_offset, native_offset) in field_offsets {
+            fields[compat_offset] = native[4 + native_offset];
+        }
+
+        Self {
+            si_signo: info.si_signo,
+            si_errno: info.si_errno,
+            si_code: info.si_code,
+            fields,
+        }
+    }
+}
+
+/// The 32-bit version of [`ucontext_t`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/asm/sigframe.h>
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct compat_ucontext_t {
+    pub uc_flags: u32,
+    pub uc_link: u32,
+    pub uc_stack: compat_stack_t,
+    pub uc_mcontext: SigContext32,
+    pub uc_sigmask: compat_sigset_t,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl compat_ucontext_t {
+    pub fn sigmask(&self) -> sigset_t {
+        sigset_from_compat(self.uc_sigmask)
+    }
+
+    pub fn set_sigmask(&mut self, sigmask: sigset_t) {
+        self.uc_sigmask = sigset_to_compat(sigmask);
+    }
+}
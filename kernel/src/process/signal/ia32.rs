@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Signal frames of 32-bit programs.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/kernel/signal_32.c>
+
+use core::mem::offset_of;
+
+use align_ext::AlignExt;
+use ostd::{
+    arch::cpu::context::{FpuContext, UserContext},
+    mm::VmIo,
+    user::UserContextApi,
+};
+
+use super::{
+    SigStackFlags,
+    c_types::{compat_siginfo_t, compat_stack_t, compat_ucontext_t, siginfo_t, stack_t},
+    sig_action::SigActionFlags,
+    sig_mask::SigMask,
+    sig_num::SigNum,
+    use_alternate_signal_stack,
+};
+use crate::{arch::cpu::SigContext32, prelude::*, process::posix_thread::ContextPthreadAdminApi};
+
+/// The system call number of `sigreturn` for 32-bit programs.
+const NR_IA32_SIGRETURN: u32 = 119;
+/// The system call number of `rt_sigreturn` for 32-bit programs.
+const NR_IA32_RT_SIGRETURN: u32 = 173;
+
+/// The size of the legacy FPU state (`struct fregs_state`) in front of the FXSAVE/XSAVE area.
+const FREGS_STATE_SIZE: usize = 112;
+
+/// The signal frame for the signal handlers without `SA_SIGINFO` (`struct sigframe_ia32`).
+#[repr(C)]
+#[derive(Clone, Copy, Pod)]
+struct SigFrame {
+    pretcode: u32,
+    sig: i32,
+    sc: SigContext32,
+    /// The unused FPU state, which is kept to preserve the offset of `extramask`.
+    fpstate_unused: [u8; 624],
+    extramask: [u32; 1],
+    /// The code that calls `sigreturn`, which is unused but is recognized by debuggers.
+    retcode: [u8; 8],
+}
+
+/// The signal frame for the signal handlers with `SA_SIGINFO` (`struct rt_sigframe_ia32`).
+#[repr(C)]
+#[derive(Clone, Copy, Pod)]
+struct RtSigFrame {
+    pretcode: u32,
+    sig: i32,
+    pinfo: u32,
+    puc: u32,
+    info: compat_siginfo_t,
+    uc: compat_ucontext_t,
+    /// The code that calls `rt_sigreturn`, which is unused but is recognized by debuggers.
+    retcode: [u8; 8],
+}
+
+/// Sets up the signal frame for a 32-bit program and prepares to run the signal handler.
+#[expect(clippy::too_many_arguments)]
+pub(super) fn setup_frame(
+    ctx: &Context,
+    sig_num: SigNum,
+    handler_addr: Vaddr,
+    flags: SigActionFlags,
+    restorer_addr: Vaddr,
+    mask_to_restore: SigMask,
+    user_ctx: &mut UserContext,
+    sig_info: &siginfo_t,
+) -> Result<()> {
+    let user_space = ctx.user_space();
+
+    // Set up the signal stack. There is no red zone in the 32-bit ABI.
+    let stack_pointer =
+        use_alternate_signal_stack(flags, ctx.thread_local, user_ctx.stack_pointer())
+            .unwrap_or(user_ctx.stack_pointer());
+
+    // 1. Write the FPU context, which is preceded by the legacy FPU state.
+    //
+    // Align the FPU context address to the 64-byte boundary so that the user program can use the
+    // XSAVE/XRSTOR instructions at that address, if necessary. The legacy FPU state is left as
+    // zeros, whose `magic` field indicates that the FXSAVE/XSAVE area follows.
+    let supp = ctx.thread_local.supp_user_context();
+    let fpu_context = supp.fpu().get();
+    let fpu_context_bytes = fpu_context.as_bytes();
+    supp.fpu().set(FpuContext::new());
+
+    let fpu_context_addr = stack_pointer
+        .checked_sub(fpu_context_bytes.len())
+        .ok_or_else(|| Error::with_message(Errno::EFAULT, "the signal stack overflows"))?
+        .align_down(64);
+    let fpstate_addr = fpu_context_addr - FREGS_STATE_SIZE;
+    user_space.write_bytes(fpu_context_addr, fpu_context_bytes)?;
+    user_space.write_val(fpstate_addr, &[0u8; FREGS_STATE_SIZE])?;
+
+    // 2. Save the general-purpose registers.
+    let mut sig_context = SigContext32::default();
+    sig_context.copy_user_regs_from(user_ctx);
+    sig_context.set_fpu_context_addr(fpstate_addr);
+
+    // 3. Write the signal frame.
+    //
+    // The frame is aligned so that the SP is 16-byte aligned after the signal handler pushes the
+    // return address. This is required by the i386 ABI.
+    let frame_size = if flags.contains(SigActionFlags::SA_SIGINFO) {
+        size_of::<RtSigFrame>()
+    } else {
+        size_of::<SigFrame>()
+    };
+    let frame_addr = (fpstate_addr - frame_size + 4).align_down(16) - 4;
+    if frame_addr >= u32::MAX as usize {
+        return_errno_with_message!(Errno::EFAULT, "the signal frame is not in the 32-bit space");
+    }
+
+    // The restorer is mandatory (see `check_sigaction`), so the code in `retcode` is never
+    // executed.
+    debug_assert!(flags.contains(SigActionFlags::SA_RESTORER));
+    let pretcode = restorer_addr as u32;
+
+    let mask_to_restore = u64::from(mask_to_restore);
+    if flags.contains(SigActionFlags::SA_SIGINFO) {
+        let uc_stack = {
+            let mut sig_stack = ctx.thread_local.sig_stack().borrow_mut();
+            let stack = compat_stack_t::from(stack_t::from(&*sig_stack));
+
+            if sig_stack.flags().contains(SigStackFlags::SS_AUTODISARM) {
+                sig_stack.reset();
+            }
+
+            stack
+        };
+
+        const UC_FP_XSTATE: u32 = 1 << 0;
+        let mut uc = compat_ucontext_t {
+            uc_flags: UC_FP_XSTATE,
+            uc_stack,
+            uc_mcontext: sig_context,
+            ..Default::default()
+        };
+        uc.set_sigmask(mask_to_restore);
+
+        let frame = RtSigFrame {
+            pretcode,
+            sig: sig_num.as_u8() as i32,
+            pinfo: (frame_addr + offset_of!(RtSigFrame, info)) as u32,
+            puc: (frame_addr + offset_of!(RtSigFrame, uc)) as u32,
+            info: compat_siginfo_t::from(sig_info),
+            uc,
+            retcode: retcode(&[], NR_IA32_RT_SIGRETURN),
+        };
+        user_space.write_val(frame_addr, &frame)?;
+
+        user_ctx.set_rdx(frame.pinfo as usize);
+        user_ctx.set_rcx(frame.puc as usize);
+    } else {
+        sig_context.set_old_mask(mask_to_restore as u32);
+
+        let mut frame = SigFrame::new_zeroed();
+        frame.pretcode = pretcode;
+        frame.sig = sig_num.as_u8() as i32;
+        frame.sc = sig_context;
+        frame.extramask = [(mask_to_restore >> 32) as u32];
+        // popl %eax
+        frame.retcode = retcode(&[0x58], NR_IA32_SIGRETURN);
+        user_space.write_val(frame_addr, &frame)?;
+
+        user_ctx.set_rdx(0);
+        user_ctx.set_rcx(0);
+    }
+
+    debug!(
+        "Before calling to signal handler: stack_pointer = 0x{:x}",
+        frame_addr
+    );
+
+    // 4. Set correct register values.
+    user_ctx.set_instruction_pointer(handler_addr);
+    user_ctx.set_stack_pointer(frame_addr);
+    user_ctx.set_rax(sig_num.as_u8() as usize);
+    // Clear the DF flag. This is to conform to i386 calling conventions.
+    const X86_EFLAGS_DF: usize = 1 << 10;
+    user_ctx.general_regs_mut().rflags &= !X86_EFLAGS_DF;
+
+    Ok(())
+}
+
+/// Returns the code that issues the `nr` system call after executing the `prefix` code.
+fn retcode(prefix: &[u8], nr: u32) -> [u8; 8] {
+    let mut code = [0u8; 8];
+    let (prefix_code, syscall_code) = code.split_at_mut(prefix.len());
+    prefix_code.copy_from_slice(prefix);
+
+    // movl $nr, %eax
+    syscall_code[0] = 0xb8;
+    syscall_code[1..5].copy_from_slice(&nr.to_le_bytes());
+    // int $0x80
+    syscall_code[5..7].copy_from_slice(&[0xcd, 0x80]);
+
+    code
+}
+
+/// Restores the user context from the signal frame for `sigreturn`.
+pub fn restore_frame(ctx: &Context, user_ctx: &mut UserContext) -> Result<()> {
+    // The signal handler has returned to the restorer, which pops the signal number.
+    let frame_addr = user_ctx.stack_pointer().wrapping_sub(8);
+    let frame = ctx.user_space().read_val::<SigFrame>(frame_addr)?;
+
+    restore_sig_context(ctx, &frame.sc, user_ctx)?;
+
+    let sig_mask = (frame.sc.old_mask() as u64) | ((frame.extramask[0] as u64) << 32);
+    ctx.set_sig_mask(sig_mask.into());
+
+    Ok(())
+}
+
+/// Restores the user context from the signal frame for `rt_sigreturn`.
+///
+/// Returns the saved signal stack, which should be restored by the caller.
+pub fn restore_rt_frame(ctx: &Context, user_ctx: &mut UserContext) -> Result<stack_t> {
+    // The signal handler has returned to the restorer.
+    let frame_addr = user_ctx.stack_pointer().wrapping_sub(4);
+    let frame = ctx.user_space().read_val::<RtSigFrame>(frame_addr)?;
+
+    restore_sig_context(ctx, &frame.uc.uc_mcontext, user_ctx)?;
+
+    ctx.set_sig_mask(frame.uc.sigmask().into());
+
+    Ok(stack_t::from(frame.uc.uc_stack))
+}
+
+fn restore_sig_context(
+    ctx: &Context,
+    sig_context: &SigContext32,
+    user_ctx: &mut UserContext,
+) -> Result<()> {
+    sig_context.copy_user_regs_to(user_ctx);
+
+    let fpstate_addr = sig_context.fpu_context_addr();
+    let mut fpu_context = FpuContext::new();
+    if fpstate_addr != 0 {
+        ctx.user_space()
+            .read_bytes(fpstate_addr + FREGS_STATE_SIZE, fpu_context.as_bytes_mut())?;
+    }
+    ctx.thread_local.supp_user_context().fpu().set(fpu_context);
+
+    Ok(())
+}
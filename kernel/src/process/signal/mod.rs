@@ -2,6 +2,8 @@
 
 pub mod c_types;
 pub mod constants;
+#[cfg(target_arch = "x86_64")]
+pub mod ia32;
 mod pause;
 mod pending;
 mod poll;
@@ -34,7 +36,7 @@ use crate::{
     cpu::LinuxAbi,
     prelude::*,
     process::{
-        TermStatus,
+        TermStatus, coredump,
         posix_thread::{ContextPthreadAdminApi, do_exit_group, ptrace::PtraceStopResult},
         signal::{c_types::stack_t, constants::SIGKILL},
     },
@@ -61,6 +63,8 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
         None
     };
 
+    ctx.posix_thread.ptrace_may_stop_on_interrupt(ctx, user_ctx);
+
     let mut restore_sig_mask = ctx
         .thread_local
         .sig_mask_saved()
@@ -72,6 +76,7 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
     } else {
         // Fast path: There is no signal mask to restore.
         if restore_sig_mask.is_none() {
+            restart_syscall_if_interrupted(user_ctx, syscall_restart);
             return;
         }
         // Restore the signal mask first.
@@ -81,6 +86,7 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
         if let Some(dequeued_signal) = dequeue_pending_signal(ctx) {
             dequeued_signal
         } else {
+            restart_syscall_if_interrupted(user_ctx, syscall_restart);
             return;
         }
     };
@@ -142,19 +148,8 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
                 sig_dispositions.set_default(sig_num);
             }
 
-            if let Some(orig_syscall_ret) = syscall_restart
-                && flags.contains(SigActionFlags::SA_RESTART)
-            {
-                #[cfg(target_arch = "x86_64")]
-                const SYSCALL_INSTR_LEN: usize = 2; // syscall
-                #[cfg(target_arch = "riscv64")]
-                const SYSCALL_INSTR_LEN: usize = 4; // ecall
-                #[cfg(target_arch = "loongarch64")]
-                const SYSCALL_INSTR_LEN: usize = 4; // syscall
-
-                user_ctx.set_syscall_ret(orig_syscall_ret);
-                user_ctx
-                    .set_instruction_pointer(user_ctx.instruction_pointer() - SYSCALL_INSTR_LEN);
+            if flags.contains(SigActionFlags::SA_RESTART) {
+                restart_syscall_if_interrupted(user_ctx, syscall_restart);
             }
 
             if let Err(e) = handle_user_signal(
@@ -194,8 +189,15 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
                         ctx.process.pid(),
                         sig_num.sig_name()
                     );
+                    let term_status = if sig_default_action == SigDefaultAction::Core
+                        && coredump::do_coredump(signal.as_ref(), ctx, user_ctx)
+                    {
+                        TermStatus::Dumped(sig_num)
+                    } else {
+                        TermStatus::Killed(sig_num)
+                    };
                     // The signal terminates the current process. Therefore, we should exit here.
-                    do_exit_group(TermStatus::Killed(sig_num), ctx, user_ctx);
+                    do_exit_group(term_status, ctx, user_ctx);
                 }
                 SigDefaultAction::Ign => {}
                 SigDefaultAction::Stop => ctx.process.stop(sig_num),
@@ -205,6 +207,28 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
     }
 }
 
+/// Restarts the interrupted syscall if `syscall_restart` holds its original
+/// return value.
+///
+/// Besides signals with `SA_RESTART`, this is needed if the syscall is
+/// interrupted but no signals are delivered, e.g., when the thread is woken up
+/// by the cgroup freezer.
+fn restart_syscall_if_interrupted(user_ctx: &mut UserContext, syscall_restart: Option<usize>) {
+    let Some(orig_syscall_ret) = syscall_restart else {
+        return;
+    };
+
+    #[cfg(target_arch = "x86_64")]
+    const SYSCALL_INSTR_LEN: usize = 2; // syscall or int 0x80
+    #[cfg(target_arch = "riscv64")]
+    const SYSCALL_INSTR_LEN: usize = 4; // ecall
+    #[cfg(target_arch = "loongarch64")]
+    const SYSCALL_INSTR_LEN: usize = 4; // syscall
+
+    user_ctx.set_syscall_ret(orig_syscall_ret);
+    user_ctx.set_instruction_pointer(user_ctx.instruction_pointer() - SYSCALL_INSTR_LEN);
+}
+
 /// A guard that restores the signal mask on drop.
 struct RestoreSigMaskGuard<'a> {
     ctx: &'a Context<'a>,
@@ -310,6 +334,21 @@ pub fn handle_user_signal(
     let mask_to_restore = mask_to_restore.unwrap_or(old_mask);
     ctx.set_sig_mask(old_mask + mask);
 
+    // 32-bit programs use a different signal frame.
+    #[cfg(target_arch = "x86_64")]
+    if user_ctx.is_compat_mode() {
+        return ia32::setup_frame(
+            ctx,
+            sig_num,
+            handler_addr,
+            flags,
+            restorer_addr,
+            mask_to_restore,
+            user_ctx,
+            &sig_info,
+        );
+    }
+
     // Set up the signal stack.
     let mut stack_pointer = if let Some(sp) =
         use_alternate_signal_stack(flags, ctx.thread_local, user_ctx.stack_pointer())
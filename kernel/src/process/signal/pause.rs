@@ -6,10 +6,10 @@ use super::sig_mask::SigMask;
 use crate::{
     prelude::*,
     process::{
-        posix_thread::{AsPosixThread, ContextPthreadAdminApi},
+        posix_thread::{AsPosixThread, ContextPthreadAdminApi, PosixThread},
         signal::HandlePendingSignal,
     },
-    thread::AsThread,
+    thread::{AsThread, kernel_thread::AsKernelThread},
     time::{
         timer::TimerGuard,
         wait::{ManagedTimeout, TimeoutExt},
@@ -27,6 +27,10 @@ use crate::{
 /// which are similar to the `wait`-family methods except that the methods also return
 /// when the waiting thread is interrupted by a POSIX signal.
 /// When this happens, the `pause`-family methods return `Err(EINTR)`.
+/// A kernel thread has no signals, but it can be interrupted in the same way by
+/// [`KernelThread::interrupt`].
+///
+/// [`KernelThread::interrupt`]: crate::thread::kernel_thread::KernelThread::interrupt
 pub trait Pause: WaitTimeout {
     /// Pauses until the condition is met or a signal interrupts.
     ///
@@ -138,21 +142,35 @@ impl Pause for Waiter {
         // No fast paths for `Waiter`. If the caller wants a fast path, it should do so _before_
         // the waiter is created.
 
-        let Some(posix_thread) = self
-            .task()
-            .as_thread()
-            .and_then(|thread| thread.as_posix_thread())
-        else {
+        let task = self.task();
+        let Some(thread) = task.as_thread() else {
             return self.wait_until_or_timeout_cancelled(cond, || Ok(()), timeout);
         };
 
-        let cancel_cond = || {
-            let has_pending = match reason {
-                PauseReason::StopByPtrace => posix_thread.has_pending_sigkill(),
-                _ => posix_thread.has_pending(),
+        if let Some(kernel_thread) = thread.as_kernel_thread() {
+            let cancel_cond = || {
+                if kernel_thread.is_interrupted() {
+                    return Err(Error::with_message(
+                        Errno::EINTR,
+                        "the current thread is interrupted by its owner",
+                    ));
+                }
+                Ok(())
             };
 
-            if has_pending {
+            kernel_thread.set_paused_waker(self.waker());
+            let res = self.wait_until_or_timeout_cancelled(cond, cancel_cond, timeout);
+            kernel_thread.clear_paused_waker();
+
+            return res;
+        }
+
+        let Some(posix_thread) = thread.as_posix_thread() else {
+            return self.wait_until_or_timeout_cancelled(cond, || Ok(()), timeout);
+        };
+
+        let cancel_cond = || {
+            if is_interrupted(posix_thread, reason) {
                 return Err(Error::with_message(
                     Errno::EINTR,
                     "the current thread is interrupted by a signal",
@@ -176,15 +194,15 @@ impl Pause for Waiter {
             })
         });
 
-        let posix_thread_opt = self
-            .task()
-            .as_thread()
-            .and_then(|thread| thread.as_posix_thread());
+        let task = self.task();
+        let thread_opt = task.as_thread();
+        let posix_thread_opt = thread_opt.and_then(|thread| thread.as_posix_thread());
+        let kernel_thread_opt = thread_opt.and_then(|thread| thread.as_kernel_thread());
 
         if let Some(posix_thread) = posix_thread_opt {
             posix_thread.set_signalled_waker(self.waker(), PauseReason::Sleep);
-            // Check `has_pending` after `set_signalled_waker` to avoid race conditions.
-            if posix_thread.has_pending() {
+            // Check for interruptions after `set_signalled_waker` to avoid race conditions.
+            if is_interrupted(posix_thread, PauseReason::Sleep) {
                 posix_thread.clear_signalled_waker();
                 return_errno_with_message!(
                     Errno::EINTR,
@@ -195,6 +213,20 @@ impl Pause for Waiter {
             self.wait();
 
             posix_thread.clear_signalled_waker();
+        } else if let Some(kernel_thread) = kernel_thread_opt {
+            kernel_thread.set_paused_waker(self.waker());
+            // Check for interruptions after `set_paused_waker` to avoid race conditions.
+            if kernel_thread.is_interrupted() {
+                kernel_thread.clear_paused_waker();
+                return_errno_with_message!(
+                    Errno::EINTR,
+                    "the current thread is interrupted by its owner"
+                );
+            }
+
+            self.wait();
+
+            kernel_thread.clear_paused_waker();
         } else {
             self.wait();
         }
@@ -210,7 +242,8 @@ impl Pause for Waiter {
 
         if posix_thread_opt
             .as_ref()
-            .is_some_and(|posix_thread| posix_thread.has_pending())
+            .is_some_and(|posix_thread| is_interrupted(posix_thread, PauseReason::Sleep))
+            || kernel_thread_opt.is_some_and(|kernel_thread| kernel_thread.is_interrupted())
         {
             return_errno_with_message!(
                 Errno::EINTR,
@@ -256,6 +289,19 @@ pub enum PauseReason {
     Sleep,
     StopBySignal,
     StopByPtrace,
+    /// The thread is parked by the cgroup freezer.
+    Freeze,
+}
+
+/// Returns whether a thread paused for `reason` should be interrupted.
+fn is_interrupted(posix_thread: &PosixThread, reason: PauseReason) -> bool {
+    match reason {
+        PauseReason::StopByPtrace | PauseReason::Freeze => posix_thread.has_pending_sigkill(),
+        PauseReason::StopBySignal => posix_thread.has_pending(),
+        // A sleeping thread is also interrupted if its cgroup is being frozen, so that it can
+        // be parked before returning to user space.
+        PauseReason::Sleep => posix_thread.has_pending() || posix_thread.process().is_freezing(),
+    }
 }
 
 /// Executes a closure after temporarily adjusting the signal mask of the current POSIX thread.
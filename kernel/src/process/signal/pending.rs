@@ -129,6 +129,11 @@ impl HandlePendingSignal for PosixThread {
 }
 
 fn has_pending_signal(posix_thread: &PosixThread, process: &Process) -> bool {
+    // A `PTRACE_INTERRUPT` stop is handled in the same way as a pending signal.
+    if posix_thread.has_pending_ptrace_interrupt() {
+        return true;
+    }
+
     // Fast path: No signals are pending.
     if posix_thread.sig_queues().is_empty() && process.sig_queues().is_empty() {
         return false;
@@ -6,7 +6,7 @@ use super::Signal;
 use crate::{
     context::Context,
     process::{
-        Pid, Uid,
+        Pid, Process, Uid,
         signal::{
             c_types::siginfo_t,
             constants::{SI_QUEUE, SI_TKILL, SI_USER},
@@ -70,8 +70,16 @@ impl Signal for UserSignal {
             UserSignalKind::Sigqueue => SI_QUEUE,
         };
 
+        // The sender's PID is reported in the PID namespace of the current process, which is the
+        // receiver when the signal is delivered. A sender in an ancestor namespace is reported as
+        // zero.
+        let pid = match Process::current() {
+            Some(process) => process.pid_ns().local_id_of(self.pid).unwrap_or(0),
+            None => self.pid,
+        };
+
         let mut info = siginfo_t::new(self.num, code);
-        info.set_pid_uid(self.pid, self.uid);
+        info.set_pid_uid(pid, self.uid);
 
         info
     }
@@ -2,7 +2,7 @@
 
 //! The process status.
 
-use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicU64, Ordering};
 
 use ostd::sync::SpinLock;
 
@@ -89,6 +89,14 @@ pub(super) struct StopStatus {
     /// Indicates whether the process is stopped.
     is_stopped: AtomicBool,
 
+    /// The signal that stopped the process most recently.
+    stop_signal: AtomicU8,
+
+    /// The number of times that the process has been stopped.
+    ///
+    /// Ptrace uses this to report each group-stop only once to the tracer.
+    stop_id: AtomicU64,
+
     /// Indicates whether the process's status has changed and has not yet been waited on.
     ///
     /// User programs may use the wait* syscalls to check for changes in
@@ -102,6 +110,8 @@ impl StopStatus {
     pub(self) const fn new() -> Self {
         Self {
             is_stopped: AtomicBool::new(false),
+            stop_signal: AtomicU8::new(0),
+            stop_id: AtomicU64::new(0),
             wait_status: SpinLock::new(None),
         }
     }
@@ -116,6 +126,8 @@ impl StopStatus {
         if self.is_stopped.load(Ordering::Relaxed) {
             false
         } else {
+            self.stop_signal.store(signum.as_u8(), Ordering::Relaxed);
+            self.stop_id.fetch_add(1, Ordering::Relaxed);
             self.is_stopped.store(true, Ordering::Relaxed);
             *wait_status = Some(StopWaitStatus::Stopped(signum));
             true
@@ -143,6 +155,20 @@ impl StopStatus {
         self.is_stopped.load(Ordering::Relaxed)
     }
 
+    /// Returns the signal that stopped the process and the ID of the stop.
+    ///
+    /// This method returns `None` if the process is not stopped.
+    pub(super) fn stop_signal_and_id(&self) -> Option<(SigNum, u64)> {
+        let _wait_status = self.wait_status.lock();
+
+        if !self.is_stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let signal = SigNum::from_u8(self.stop_signal.load(Ordering::Relaxed));
+        Some((signal, self.stop_id.load(Ordering::Relaxed)))
+    }
+
     /// Returns the stop status changes for the `wait` syscall.
     pub(super) fn wait(&self, options: WaitOptions) -> Option<StopWaitStatus> {
         let mut wait_status = self.wait_status.lock();
@@ -7,7 +7,7 @@ use ostd::{
     task::{CurrentTask, Task},
 };
 
-use crate::prelude::*;
+use crate::{prelude::*, process::coredump::CoreState};
 
 /// A task set that maintains all tasks in a POSIX process.
 pub struct TaskSet {
@@ -16,6 +16,7 @@ pub struct TaskSet {
     has_exited_group: bool,
     in_execve: bool,
     execve_waker: Option<Arc<Waker>>,
+    core_state: Option<Arc<CoreState>>,
 }
 
 impl TaskSet {
@@ -27,6 +28,7 @@ impl TaskSet {
             has_exited_group: false,
             in_execve: false,
             execve_waker: None,
+            core_state: None,
         }
     }
 
@@ -126,6 +128,19 @@ impl TaskSet {
         self.execve_waker = Some(waker);
     }
 
+    /// Sets the state of the core dump in progress.
+    ///
+    /// Every thread that exits afterward reports to the core dump.
+    pub(super) fn set_core_state(&mut self, core_state: Arc<CoreState>) {
+        debug_assert!(self.core_state.is_none());
+        self.core_state = Some(core_state);
+    }
+
+    /// Returns the state of the core dump in progress, if any.
+    pub(super) fn core_state(&self) -> Option<&Arc<CoreState>> {
+        self.core_state.as_ref()
+    }
+
     /// Clears the waker previously set by [`Self::set_execve_waker`].
     pub(super) fn clear_execve_waker(&mut self) {
         self.execve_waker = None;
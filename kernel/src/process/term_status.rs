@@ -6,14 +6,20 @@ use super::signal::sig_num::SigNum;
 pub enum TermStatus {
     Exited(u8),
     Killed(SigNum),
+    /// Killed by a signal after dumping core.
+    Dumped(SigNum),
 }
 
 impl TermStatus {
+    /// The bit that is set in the wait status if the process dumped core.
+    pub const CORE_DUMP_FLAG: u32 = 0x80;
+
     /// Return as a 32-bit integer encoded as specified in wait(2) man page.
     pub fn as_u32(&self) -> u32 {
         match self {
             TermStatus::Exited(status) => (*status as u32) << 8,
             TermStatus::Killed(signum) => signum.as_u8() as u32,
+            TermStatus::Dumped(signum) => signum.as_u8() as u32 | Self::CORE_DUMP_FLAG,
         }
     }
 }
@@ -48,11 +48,15 @@ impl WaitOptions {
     }
 }
 
+/// Waits for a state change of a child or tracee.
+///
+/// On success, returns the wait status and the ID of the waited process or thread in the PID
+/// namespace of the current process.
 pub fn do_wait(
     child_filter: ProcessFilter,
     wait_options: WaitOptions,
     ctx: &Context,
-) -> Result<Option<WaitStatus>> {
+) -> Result<Option<(WaitStatus, Pid)>> {
     wait_options.check()?;
 
     let is_nonblocking = if let ProcessFilter::WithPidfd(pid_file) = &child_filter {
@@ -67,13 +71,13 @@ pub fn do_wait(
         || {
             ctx.process.children_wait_queue().pause_until(|| {
                 let has_unready_tracee = match try_wait_tracees(&child_filter, wait_options, ctx) {
-                    WaitResult::Found(status) => return Some(Ok(Some(status))),
+                    WaitResult::Found(status, pid) => return Some(Ok(Some((status, pid)))),
                     WaitResult::MatchedButUnready => true,
                     WaitResult::NoMatch => false,
                 };
 
                 let has_unready_child = match try_wait_children(&child_filter, wait_options, ctx) {
-                    WaitResult::Found(status) => return Some(Ok(Some(status))),
+                    WaitResult::Found(status, pid) => return Some(Ok(Some((status, pid)))),
                     WaitResult::MatchedButUnready => true,
                     WaitResult::NoMatch => false,
                 };
@@ -114,9 +118,19 @@ fn wait_filter(child_pid: Pid, child: &Arc<Process>, child_filter: &ProcessFilte
             Some(process) => Arc::ptr_eq(&process, child),
             None => false,
         },
+        ProcessFilter::Invisible => false,
     }
 }
 
+/// Translates the global ID of a waited process or thread to the ID in the PID namespace of the
+/// current process.
+///
+/// This must be done before the waited process or thread is reaped, since its IDs in the PID
+/// namespaces are freed at that time.
+fn local_id_of(id: Tid, ctx: &Context) -> Pid {
+    ctx.process.pid_ns().local_id_of(id).unwrap_or(0)
+}
+
 pub enum WaitStatus {
     Zombie(Arc<Process>),
     Stop(Arc<Process>, SigNum),
@@ -126,6 +140,7 @@ pub enum WaitStatus {
 }
 
 impl WaitStatus {
+    /// Returns the global ID of the process or thread.
     pub fn pid(&self) -> Pid {
         match self.source() {
             WaitStatusSource::Process(process) => process.pid(),
@@ -171,8 +186,9 @@ enum WaitStatusSource<'a> {
 
 /// The result of trying to wait for a child/tracee state change.
 enum WaitResult {
-    /// A waitable status is found.
-    Found(WaitStatus),
+    /// A waitable status is found, with the ID of its source in the PID namespace of the current
+    /// process.
+    Found(WaitStatus, Pid),
     /// At least one target matches, but none is waitable yet.
     MatchedButUnready,
     /// No target matches the wait filter.
@@ -233,15 +249,17 @@ fn try_wait_tracees(
             }
 
             let thread = thread.clone();
+            let tid = local_id_of(tracee.tid(), ctx);
             if !wait_options.contains(WaitOptions::WNOWAIT) {
                 cleanup_exited_tracee(&process, &thread, tracees, ctx);
             }
-            return WaitResult::Found(WaitStatus::TraceeExit(thread));
+            return WaitResult::Found(WaitStatus::TraceeExit(thread), tid);
         }
 
         // Waiting for ptrace-stops does not require `WaitOptions::WSTOPPED`.
         if let Some(sig_num) = tracee.wait_ptrace_stopped(wait_options) {
-            return WaitResult::Found(WaitStatus::TraceeStop(thread.clone(), sig_num));
+            let tid = local_id_of(tracee.tid(), ctx);
+            return WaitResult::Found(WaitStatus::TraceeStop(thread.clone(), sig_num), tid);
         }
     }
 
@@ -309,6 +327,7 @@ fn try_wait_children(
 
         if child.status().is_zombie() {
             let child = child.clone();
+            let pid = local_id_of(child.pid(), ctx);
             if !wait_options.contains(WaitOptions::WNOWAIT) {
                 reap_zombie_child(
                     child.pid(),
@@ -316,7 +335,7 @@ fn try_wait_children(
                     ctx.process.reaped_children_stats(),
                 );
             }
-            return WaitResult::Found(WaitStatus::Zombie(child));
+            return WaitResult::Found(WaitStatus::Zombie(child), pid);
         }
 
         if !wait_options.intersects(WaitOptions::WSTOPPED | WaitOptions::WCONTINUED) {
@@ -329,14 +348,14 @@ fn try_wait_children(
             StopWaitStatus::Stopped(sig_num) => WaitStatus::Stop(child.clone(), sig_num),
             StopWaitStatus::Continue => WaitStatus::Continue(child.clone()),
         };
-        return WaitResult::Found(wait_status);
+        return WaitResult::Found(wait_status, local_id_of(child.pid(), ctx));
     }
 
     fallback_result
 }
 
 /// Free zombie child with `child_pid`, returns the exit code of child process.
-fn reap_zombie_child(
+pub(super) fn reap_zombie_child(
     child_pid: Pid,
     children_lock: &mut BTreeMap<Pid, Arc<Process>>,
     reaped_children_stats: &Mutex<ReapedChildrenStats>,
@@ -4,10 +4,14 @@
 
 mod alien_access;
 mod capability;
+mod path;
+mod socket;
 
 pub use self::{
     alien_access::{AlienAccessContext, on_alien_access},
     capability::{CapableContext, on_capable},
+    path::{PathContext, PathOperation, on_current_path_operation, on_path_operation},
+    socket::{SocketAddrContext, on_socket_bind, on_socket_connect},
 };
 use crate::prelude::*;
 
@@ -24,3 +28,22 @@ pub(super) trait LsmCapabilityHook: Sync {
         Ok(())
     }
 }
+
+pub(super) trait LsmPathHook: Sync {
+    /// Checks whether a thread may perform an operation on paths.
+    fn on_path_operation(&self, _context: &PathContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) trait LsmSocketHook: Sync {
+    /// Checks whether a thread may bind a socket to an address.
+    fn on_socket_bind(&self, _context: &SocketAddrContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may connect a socket to an address.
+    fn on_socket_connect(&self, _context: &SocketAddrContext) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Hooks for operations on paths in the VFS tree.
+
+use super::super::modules;
+use crate::{
+    fs::{
+        file::{AccessMode, InodeType},
+        vfs::path::Path,
+    },
+    prelude::*,
+    process::posix_thread::{AsPosixThread, PosixThread},
+};
+
+/// Runs path hooks in module order.
+pub fn on_path_operation(context: PathContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_path_operation(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs path hooks in module order for the current thread.
+///
+/// Operations performed by kernel threads are not checked.
+pub fn on_current_path_operation(operation: PathOperation) -> Result<()> {
+    let current_thread = current_thread!();
+    let Some(posix_thread) = current_thread.as_posix_thread() else {
+        return Ok(());
+    };
+
+    on_path_operation(PathContext::new(posix_thread, operation))
+}
+
+/// The inputs for checking an operation on paths.
+pub struct PathContext<'a> {
+    posix_thread: &'a PosixThread,
+    operation: PathOperation<'a>,
+}
+
+impl<'a> PathContext<'a> {
+    /// Creates a path operation context.
+    pub const fn new(posix_thread: &'a PosixThread, operation: PathOperation<'a>) -> Self {
+        Self {
+            posix_thread,
+            operation,
+        }
+    }
+
+    /// Returns the thread performing the operation.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the operation.
+    pub const fn operation(&self) -> &PathOperation<'a> {
+        &self.operation
+    }
+}
+
+/// An operation on paths that is checked by LSM modules.
+#[derive(Clone, Copy, Debug)]
+pub enum PathOperation<'a> {
+    /// Opens the file at `path`.
+    ///
+    /// `is_exec` is set if the file is opened for execution.
+    Open {
+        path: &'a Path,
+        access_mode: AccessMode,
+        is_exec: bool,
+    },
+    /// Truncates the file at `path`.
+    Truncate { path: &'a Path },
+    /// Creates a new file of `type_` in the directory `dir`.
+    Create { dir: &'a Path, type_: InodeType },
+    /// Removes a file (or a directory if `is_dir` is set) from the directory `dir`.
+    Remove { dir: &'a Path, is_dir: bool },
+    /// Links the file at `old` into the directory `new_dir`.
+    Link { old: &'a Path, new_dir: &'a Path },
+    /// Moves a file of `old_type` from the directory `old_dir` to the directory `new_dir`.
+    ///
+    /// `replaced_type` is the type of the file being replaced, if any.
+    Rename {
+        old_dir: &'a Path,
+        old_type: InodeType,
+        new_dir: &'a Path,
+        replaced_type: Option<InodeType>,
+    },
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Hooks for socket operations.
+
+use super::super::modules;
+use crate::{
+    net::socket::util::SocketAddr, prelude::*, process::posix_thread::PosixThread,
+    util::net::SockType,
+};
+
+/// Runs socket bind hooks in module order.
+pub fn on_socket_bind(context: SocketAddrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_socket_bind(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs socket connect hooks in module order.
+pub fn on_socket_connect(context: SocketAddrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_socket_connect(&context)?;
+    }
+
+    Ok(())
+}
+
+/// The inputs for checking a socket operation on an address.
+pub struct SocketAddrContext<'a> {
+    posix_thread: &'a PosixThread,
+    sock_type: SockType,
+    addr: &'a SocketAddr,
+}
+
+impl<'a> SocketAddrContext<'a> {
+    /// Creates a socket address context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        sock_type: SockType,
+        addr: &'a SocketAddr,
+    ) -> Self {
+        Self {
+            posix_thread,
+            sock_type,
+            addr,
+        }
+    }
+
+    /// Returns the thread performing the operation.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the type of the socket.
+    pub const fn sock_type(&self) -> SockType {
+        self.sock_type
+    }
+
+    /// Returns the address to bind or connect to.
+    pub const fn addr(&self) -> &SocketAddr {
+        self.addr
+    }
+}
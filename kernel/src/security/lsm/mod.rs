@@ -7,17 +7,24 @@
 //! inspect common hook contexts before allowing or rejecting an operation.
 //!
 //! This module defines the common LSM traits and hook contexts shared by
-//! built-in modules such as `capability` and `yama`. Module selection follows
+//! built-in modules such as `capability`, `landlock` and `yama`. Module selection follows
 //! the `lsm=` and legacy `security=` kernel command-line parameters.
 
 pub mod hooks;
 mod modules;
 
+pub mod landlock {
+    pub use super::modules::landlock::{
+        AccessFs, AccessNet, LANDLOCK_ABI_VERSION, LandlockDomain, Ruleset, RulesetFile,
+        restrict_self,
+    };
+}
+
 pub mod yama {
     pub use super::modules::yama::{YamaScope, get_scope, set_scope};
 }
 
-use self::hooks::{LsmAlienAccessHook, LsmCapabilityHook};
+use self::hooks::{LsmAlienAccessHook, LsmCapabilityHook, LsmPathHook, LsmSocketHook};
 use crate::prelude::*;
 
 bitflags! {
@@ -31,7 +38,7 @@ bitflags! {
 }
 
 /// The common interface for built-in LSM modules.
-trait LsmModule: LsmAlienAccessHook + LsmCapabilityHook + Sync {
+trait LsmModule: LsmAlienAccessHook + LsmCapabilityHook + LsmPathHook + LsmSocketHook + Sync {
     /// Returns the module name.
     fn name(&self) -> &'static str;
 
@@ -39,6 +46,13 @@ trait LsmModule: LsmAlienAccessHook + LsmCapabilityHook + Sync {
     fn flags(&self) -> LsmFlags;
 }
 
+/// Returns whether the Landlock LSM is enabled.
+pub fn is_landlock_enabled() -> bool {
+    modules::active_modules()
+        .iter()
+        .any(|module| module.name() == "landlock")
+}
+
 /// Returns whether the Yama LSM is enabled.
 pub fn is_yama_enabled() -> bool {
     modules::active_modules()
@@ -2,7 +2,10 @@
 
 use super::super::{
     LsmFlags, LsmModule,
-    hooks::{AlienAccessContext, CapableContext, LsmAlienAccessHook, LsmCapabilityHook},
+    hooks::{
+        AlienAccessContext, CapableContext, LsmAlienAccessHook, LsmCapabilityHook, LsmPathHook,
+        LsmSocketHook,
+    },
 };
 use crate::{
     prelude::*,
@@ -81,3 +84,7 @@ impl LsmAlienAccessHook for CapabilityLsm {
         );
     }
 }
+
+impl LsmPathHook for CapabilityLsm {}
+
+impl LsmSocketHook for CapabilityLsm {}
@@ -0,0 +1,296 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The Landlock LSM.
+//!
+//! Landlock lets unprivileged threads sandbox themselves. A thread builds a [`Ruleset`] that
+//! declares the access rights it wants to restrict, adds rules that allow some of these rights
+//! for file hierarchies or TCP ports, and then enforces the ruleset on itself.
+//!
+//! The enforced rulesets form a [`LandlockDomain`], which is attached to the credentials of the
+//! thread. Each enforced ruleset is a layer of the domain. An access is allowed only if every
+//! layer that handles the access right allows it. Since child threads inherit the credentials,
+//! they inherit the domain as well, and a domain can only be further restricted.
+//!
+//! For filesystem accesses, a rule attached to a directory allows access to everything beneath
+//! it. So the check walks from the accessed path up to the root of the VFS tree, collecting the
+//! access rights allowed by the rules on the way.
+
+mod ruleset;
+
+use self::ruleset::ObjectKey;
+pub use self::ruleset::{AccessFs, AccessNet, Ruleset, RulesetFile};
+use super::super::{
+    LsmFlags, LsmModule,
+    hooks::{
+        LsmAlienAccessHook, LsmCapabilityHook, LsmPathHook, LsmSocketHook, PathContext,
+        PathOperation, SocketAddrContext,
+    },
+};
+use crate::{
+    fs::{
+        file::{AccessMode, InodeType},
+        vfs::path::Path,
+    },
+    net::socket::util::SocketAddr,
+    prelude::*,
+    util::net::SockType,
+};
+
+/// The Landlock ABI version.
+///
+/// Version 4 supports all the filesystem access rights up to `LANDLOCK_ACCESS_FS_TRUNCATE` and
+/// the TCP network access rights.
+pub const LANDLOCK_ABI_VERSION: u32 = 4;
+
+/// The maximum number of layers in a domain.
+const MAX_LAYERS: usize = 16;
+
+pub(super) static LANDLOCK_LSM: LandlockLsm = LandlockLsm;
+
+/// The Landlock minor LSM.
+pub(super) struct LandlockLsm;
+
+impl LsmModule for LandlockLsm {
+    fn name(&self) -> &'static str {
+        "landlock"
+    }
+
+    fn flags(&self) -> LsmFlags {
+        LsmFlags::empty()
+    }
+}
+
+impl LsmAlienAccessHook for LandlockLsm {}
+
+impl LsmCapabilityHook for LandlockLsm {}
+
+impl LsmPathHook for LandlockLsm {
+    fn on_path_operation(&self, context: &PathContext) -> Result<()> {
+        let Some(domain) = context.posix_thread().credentials().landlock_domain() else {
+            return Ok(());
+        };
+
+        match *context.operation() {
+            PathOperation::Open {
+                path,
+                access_mode,
+                is_exec,
+            } => domain.check_path(path, open_access(path, access_mode, is_exec)),
+            PathOperation::Truncate { path } => domain.check_path(path, AccessFs::TRUNCATE),
+            PathOperation::Create { dir, type_ } => domain.check_path(dir, AccessFs::make(type_)),
+            PathOperation::Remove { dir, is_dir } => {
+                domain.check_path(dir, AccessFs::remove(is_dir))
+            }
+            PathOperation::Link { old, new_dir } => {
+                let old_dir = old.effective_parent();
+                domain.check_reparent(old_dir.as_ref(), old.type_(), new_dir, None, false)
+            }
+            PathOperation::Rename {
+                old_dir,
+                old_type,
+                new_dir,
+                replaced_type,
+            } => domain.check_reparent(Some(old_dir), old_type, new_dir, replaced_type, true),
+        }
+    }
+}
+
+impl LsmSocketHook for LandlockLsm {
+    fn on_socket_bind(&self, context: &SocketAddrContext) -> Result<()> {
+        check_tcp_port(context, AccessNet::BIND_TCP)
+    }
+
+    fn on_socket_connect(&self, context: &SocketAddrContext) -> Result<()> {
+        check_tcp_port(context, AccessNet::CONNECT_TCP)
+    }
+}
+
+/// Returns the access rights required to open the file at `path`.
+fn open_access(path: &Path, access_mode: AccessMode, is_exec: bool) -> AccessFs {
+    if path.type_() == InodeType::Dir {
+        // A directory can only be opened in read mode.
+        return if access_mode.is_readable() {
+            AccessFs::READ_DIR
+        } else {
+            AccessFs::empty()
+        };
+    }
+
+    let mut access = AccessFs::empty();
+    if access_mode.is_readable() {
+        access |= AccessFs::READ_FILE;
+    }
+    if access_mode.is_writable() {
+        access |= AccessFs::WRITE_FILE;
+    }
+    if is_exec {
+        access |= AccessFs::EXECUTE;
+    }
+    access
+}
+
+fn check_tcp_port(context: &SocketAddrContext, access: AccessNet) -> Result<()> {
+    if !matches!(context.sock_type(), SockType::SOCK_STREAM) {
+        return Ok(());
+    }
+    let (SocketAddr::IPv4(_, port) | SocketAddr::IPv6(_, port)) = *context.addr() else {
+        return Ok(());
+    };
+    let Some(domain) = context.posix_thread().credentials().landlock_domain() else {
+        return Ok(());
+    };
+
+    domain.check_port(port, access)
+}
+
+/// A Landlock domain, which is the stack of rulesets enforced on a thread.
+#[derive(Debug)]
+pub struct LandlockDomain {
+    layers: Vec<Arc<Ruleset>>,
+}
+
+impl LandlockDomain {
+    /// Checks whether `access` to the file at `path` is allowed.
+    fn check_path(&self, path: &Path, access: AccessFs) -> Result<()> {
+        let unmet = self
+            .layers
+            .iter()
+            .map(|layer| access & layer.handled_fs())
+            .collect();
+        if self.walk_path(path, unmet).iter().all(AccessFs::is_empty) {
+            return Ok(());
+        }
+
+        return_errno_with_message!(Errno::EACCES, "the access is denied by Landlock");
+    }
+
+    /// Checks whether a file of `type_` may be moved or linked from `old_dir` to `new_dir`.
+    ///
+    /// If `old_dir` is `None`, the file is not in any directory and only the creation in
+    /// `new_dir` is checked.
+    fn check_reparent(
+        &self,
+        old_dir: Option<&Path>,
+        type_: InodeType,
+        new_dir: &Path,
+        replaced_type: Option<InodeType>,
+        is_removable: bool,
+    ) -> Result<()> {
+        let is_dir = type_ == InodeType::Dir;
+        let remove_access = if is_removable {
+            AccessFs::remove(is_dir)
+        } else {
+            AccessFs::empty()
+        };
+        let new_dir_access = AccessFs::make(type_)
+            | replaced_type.map_or(AccessFs::empty(), |type_| {
+                AccessFs::remove(type_ == InodeType::Dir)
+            });
+
+        let Some(old_dir) = old_dir.filter(|old_dir| *old_dir != new_dir) else {
+            return self.check_path(new_dir, remove_access | new_dir_access);
+        };
+
+        self.check_path(old_dir, remove_access)?;
+        self.check_path(new_dir, new_dir_access)?;
+
+        // Moving a file to another directory must not grant it more access rights, since it
+        // would then escape the restrictions. `REFER` is always handled, so that moving files
+        // across directories is denied unless explicitly allowed.
+        let relevant_access = if is_dir {
+            AccessFs::all()
+        } else {
+            AccessFs::FILE
+        };
+        let old_granted = self.granted(old_dir);
+        let new_granted = self.granted(new_dir);
+        for (old_granted, new_granted) in old_granted.into_iter().zip(new_granted) {
+            if !old_granted.contains(AccessFs::REFER)
+                || !new_granted.contains(AccessFs::REFER)
+                || !old_granted.contains(new_granted & relevant_access)
+            {
+                return_errno_with_message!(
+                    Errno::EXDEV,
+                    "the file cannot be moved to another directory due to Landlock"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the access rights granted to `path` in each layer.
+    fn granted(&self, path: &Path) -> Vec<AccessFs> {
+        let handled: Vec<_> = self
+            .layers
+            .iter()
+            .map(|layer| layer.handled_fs() | AccessFs::REFER)
+            .collect();
+        let unmet = self.walk_path(path, handled.clone());
+
+        handled
+            .into_iter()
+            .zip(unmet)
+            .map(|(handled, unmet)| handled - unmet)
+            .collect()
+    }
+
+    /// Walks from `path` up to the root and removes the access rights allowed on the way from
+    /// the `unmet` rights of each layer.
+    ///
+    /// Returns the remaining rights of each layer.
+    fn walk_path(&self, path: &Path, mut unmet: Vec<AccessFs>) -> Vec<AccessFs> {
+        // Pseudo files (e.g., pipes and sockets) are not part of the VFS tree. They can only be
+        // accessed via file descriptors, so they are not restricted.
+        if path.is_pseudo() {
+            unmet.fill(AccessFs::empty());
+            return unmet;
+        }
+
+        let mut current = Some(path.clone());
+        while let Some(path) = current
+            && !unmet.iter().all(AccessFs::is_empty)
+        {
+            let key = ObjectKey::of(&path);
+            for (layer, unmet) in self.layers.iter().zip(unmet.iter_mut()) {
+                *unmet -= layer.fs_rule(&key);
+            }
+
+            current = path.effective_parent();
+        }
+
+        unmet
+    }
+
+    /// Checks whether `access` to the TCP `port` is allowed.
+    fn check_port(&self, port: u16, access: AccessNet) -> Result<()> {
+        let is_denied = self.layers.iter().any(|layer| {
+            layer.handled_net().contains(access) && !layer.net_rule(port).contains(access)
+        });
+        if is_denied {
+            return_errno_with_message!(Errno::EACCES, "the access is denied by Landlock");
+        }
+
+        Ok(())
+    }
+}
+
+/// Enforces `ruleset` on the current thread.
+///
+/// The ruleset is added as a new layer of the Landlock domain of the thread.
+pub fn restrict_self(ruleset: Ruleset, ctx: &Context) -> Result<()> {
+    let mut layers = ctx
+        .posix_thread
+        .credentials()
+        .landlock_domain()
+        .map(|domain| domain.layers.clone())
+        .unwrap_or_default();
+    if layers.len() >= MAX_LAYERS {
+        return_errno_with_message!(Errno::E2BIG, "the Landlock domain has too many layers");
+    }
+    layers.push(Arc::new(ruleset));
+
+    ctx.credentials_mut()
+        .set_landlock_domain(Arc::new(LandlockDomain { layers }));
+    Ok(())
+}
@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Display;
+
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{AccessMode, CreationFlags, FileLike, InodeType, file_table::FdFlags},
+        pseudofs::AnonInodeFs,
+        vfs::path::Path,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+};
+
+bitflags! {
+    /// The filesystem access rights (`LANDLOCK_ACCESS_FS_*`).
+    pub struct AccessFs: u64 {
+        /// Executes a file.
+        const EXECUTE = 1 << 0;
+        /// Opens a file with write access.
+        const WRITE_FILE = 1 << 1;
+        /// Opens a file with read access.
+        const READ_FILE = 1 << 2;
+        /// Opens a directory or lists its contents.
+        const READ_DIR = 1 << 3;
+        /// Removes an empty directory or renames one.
+        const REMOVE_DIR = 1 << 4;
+        /// Unlinks or renames a file.
+        const REMOVE_FILE = 1 << 5;
+        /// Creates, renames or links a character device.
+        const MAKE_CHAR = 1 << 6;
+        /// Creates or renames a directory.
+        const MAKE_DIR = 1 << 7;
+        /// Creates, renames or links a regular file.
+        const MAKE_REG = 1 << 8;
+        /// Creates, renames or links a UNIX domain socket.
+        const MAKE_SOCK = 1 << 9;
+        /// Creates, renames or links a named pipe.
+        const MAKE_FIFO = 1 << 10;
+        /// Creates, renames or links a block device.
+        const MAKE_BLOCK = 1 << 11;
+        /// Creates, renames or links a symbolic link.
+        const MAKE_SYM = 1 << 12;
+        /// Links or renames a file from or to a different directory.
+        const REFER = 1 << 13;
+        /// Truncates a file.
+        const TRUNCATE = 1 << 14;
+    }
+}
+
+impl AccessFs {
+    /// The access rights that make sense for non-directory files.
+    pub const FILE: Self = Self::EXECUTE
+        .union(Self::WRITE_FILE)
+        .union(Self::READ_FILE)
+        .union(Self::TRUNCATE);
+
+    /// Returns the access right required to create a file of the type.
+    pub(super) fn make(type_: InodeType) -> Self {
+        match type_ {
+            InodeType::Dir => Self::MAKE_DIR,
+            InodeType::CharDevice => Self::MAKE_CHAR,
+            InodeType::BlockDevice => Self::MAKE_BLOCK,
+            InodeType::NamedPipe => Self::MAKE_FIFO,
+            InodeType::Socket => Self::MAKE_SOCK,
+            InodeType::SymLink => Self::MAKE_SYM,
+            InodeType::File | InodeType::Unknown => Self::MAKE_REG,
+        }
+    }
+
+    /// Returns the access right required to remove a file or a directory.
+    pub(super) fn remove(is_dir: bool) -> Self {
+        if is_dir {
+            Self::REMOVE_DIR
+        } else {
+            Self::REMOVE_FILE
+        }
+    }
+}
+
+bitflags! {
+    /// The network access rights (`LANDLOCK_ACCESS_NET_*`).
+    pub struct AccessNet: u64 {
+        /// Binds a TCP socket to a local port.
+        const BIND_TCP = 1 << 0;
+        /// Connects a TCP socket to a remote port.
+        const CONNECT_TCP = 1 << 1;
+    }
+}
+
+/// A Landlock ruleset.
+///
+/// A ruleset declares the access rights that it handles. Once a ruleset is enforced, the
+/// handled rights are denied unless a rule in the ruleset allows them.
+#[derive(Clone, Debug)]
+pub struct Ruleset {
+    handled_fs: AccessFs,
+    handled_net: AccessNet,
+    /// The rules that allow access to the file hierarchies beneath some inodes.
+    fs_rules: BTreeMap<ObjectKey, AccessFs>,
+    /// The rules that allow access to some TCP ports.
+    net_rules: BTreeMap<u16, AccessNet>,
+}
+
+impl Ruleset {
+    /// Creates an empty ruleset that handles the given access rights.
+    pub fn new(handled_fs: AccessFs, handled_net: AccessNet) -> Result<Self> {
+        if handled_fs.is_empty() && handled_net.is_empty() {
+            return_errno_with_message!(Errno::ENOMSG, "the ruleset handles no access rights");
+        }
+
+        Ok(Self {
+            handled_fs,
+            handled_net,
+            fs_rules: BTreeMap::new(),
+            net_rules: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the handled filesystem access rights.
+    pub fn handled_fs(&self) -> AccessFs {
+        self.handled_fs
+    }
+
+    /// Returns the handled network access rights.
+    pub fn handled_net(&self) -> AccessNet {
+        self.handled_net
+    }
+
+    /// Adds a rule that allows `access` to the file hierarchy beneath `path`.
+    pub fn add_path_rule(&mut self, path: &Path, access: AccessFs) -> Result<()> {
+        self.check_fs_access(access)?;
+        if path.type_() != InodeType::Dir && !AccessFs::FILE.contains(access) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the access rights only make sense for directories"
+            );
+        }
+
+        *self
+            .fs_rules
+            .entry(ObjectKey::of(path))
+            .or_insert(AccessFs::empty()) |= access;
+        Ok(())
+    }
+
+    /// Adds a rule that allows `access` to the TCP `port`.
+    pub fn add_net_rule(&mut self, port: u16, access: AccessNet) -> Result<()> {
+        self.check_net_access(access)?;
+
+        *self.net_rules.entry(port).or_insert(AccessNet::empty()) |= access;
+        Ok(())
+    }
+
+    /// Checks that `access` can be allowed by a filesystem rule.
+    pub fn check_fs_access(&self, access: AccessFs) -> Result<()> {
+        if access.is_empty() {
+            return_errno_with_message!(Errno::ENOMSG, "the rule allows no access rights");
+        }
+        if !self.handled_fs.contains(access) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the rule allows access rights that are not handled"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `access` can be allowed by a network rule.
+    pub fn check_net_access(&self, access: AccessNet) -> Result<()> {
+        if access.is_empty() {
+            return_errno_with_message!(Errno::ENOMSG, "the rule allows no access rights");
+        }
+        if !self.handled_net.contains(access) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the rule allows access rights that are not handled"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the access rights allowed by the rule for `key`.
+    pub(super) fn fs_rule(&self, key: &ObjectKey) -> AccessFs {
+        self.fs_rules.get(key).copied().unwrap_or(AccessFs::empty())
+    }
+
+    /// Returns the access rights allowed by the rule for `port`.
+    pub(super) fn net_rule(&self, port: u16) -> AccessNet {
+        self.net_rules
+            .get(&port)
+            .copied()
+            .unwrap_or(AccessNet::empty())
+    }
+}
+
+/// The identity of a filesystem object that rules are attached to.
+///
+/// Rules are tied to inodes rather than paths, so that a rule applies regardless of the
+/// path through which the inode is reached (e.g., via bind mounts).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct ObjectKey {
+    dev: u64,
+    ino: u64,
+}
+
+impl ObjectKey {
+    pub(super) fn of(path: &Path) -> Self {
+        let metadata = path.metadata();
+        Self {
+            dev: metadata.container_dev_id.as_encoded_u64(),
+            ino: metadata.ino,
+        }
+    }
+}
+
+/// A file that holds a Landlock ruleset being built.
+pub struct RulesetFile {
+    ruleset: Mutex<Ruleset>,
+    /// The pseudo path associated with this ruleset file.
+    pseudo_path: Path,
+}
+
+impl RulesetFile {
+    /// Creates a new ruleset file.
+    pub fn new(ruleset: Ruleset) -> Self {
+        let pseudo_path = AnonInodeFs::new_path(|_| "anon_inode:landlock-ruleset".to_string());
+        Self {
+            ruleset: Mutex::new(ruleset),
+            pseudo_path,
+        }
+    }
+
+    /// Returns the ruleset.
+    pub fn ruleset(&self) -> &Mutex<Ruleset> {
+        &self.ruleset
+    }
+}
+
+impl Pollable for RulesetFile {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        mask & (IoEvents::IN | IoEvents::OUT)
+    }
+}
+
+impl FileLike for RulesetFile {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "ruleset files cannot be read");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "ruleset files cannot be written");
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::O_RDWR
+    }
+
+    fn path(&self) -> &Path {
+        &self.pseudo_path
+    }
+
+    fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
+        struct FdInfo {
+            flags: u32,
+        }
+
+        impl Display for FdInfo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                writeln!(f, "pos:\t{}", 0)?;
+                writeln!(f, "flags:\t0{:o}", self.flags)?;
+                writeln!(f, "mnt_id:\t{}", AnonInodeFs::mount_node().id())?;
+                writeln!(f, "ino:\t{}", AnonInodeFs::shared_inode().ino())
+            }
+        }
+
+        let mut flags = self.status_flags().bits() | self.access_mode() as u32;
+        if fd_flags.contains(FdFlags::CLOEXEC) {
+            flags |= CreationFlags::O_CLOEXEC.bits();
+        }
+
+        Box::new(FdInfo { flags })
+    }
+}
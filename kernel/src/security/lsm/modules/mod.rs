@@ -29,6 +29,7 @@
 //! mandatory modules plus the default optional stack are used.
 
 mod capability;
+pub mod landlock;
 pub mod yama;
 
 use spin::Once;
@@ -46,10 +47,15 @@ aster_cmdline::define_kv_param!("security", LEGACY_SECURITY_PARAM);
 static MANDATORY_MODULES: [&'static dyn LsmModule; 1] = [&capability::CAPABILITY_LSM];
 
 /// All LSM modules compiled into the kernel.
-static ALL_MODULES: [&'static dyn LsmModule; 2] = [&capability::CAPABILITY_LSM, &yama::YAMA_LSM];
+static ALL_MODULES: [&'static dyn LsmModule; 3] = [
+    &capability::CAPABILITY_LSM,
+    &landlock::LANDLOCK_LSM,
+    &yama::YAMA_LSM,
+];
 
 /// The fallback optional LSM stack used when no boot-time selector is specified.
-pub(super) static DEFAULT_OPTIONAL_MODULES: [&'static dyn LsmModule; 1] = [&yama::YAMA_LSM];
+pub(super) static DEFAULT_OPTIONAL_MODULES: [&'static dyn LsmModule; 2] =
+    [&landlock::LANDLOCK_LSM, &yama::YAMA_LSM];
 
 static ALL_MODULES_BY_NAME: Once<BTreeMap<&'static str, &'static dyn LsmModule>> = Once::new();
 static ACTIVE_MODULES: Once<Box<[&'static dyn LsmModule]>> = Once::new();
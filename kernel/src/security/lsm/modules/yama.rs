@@ -6,7 +6,9 @@ use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 
 use super::super::{
     LsmFlags, LsmModule,
-    hooks::{AlienAccessContext, LsmAlienAccessHook, LsmCapabilityHook},
+    hooks::{
+        AlienAccessContext, LsmAlienAccessHook, LsmCapabilityHook, LsmPathHook, LsmSocketHook,
+    },
 };
 use crate::{
     prelude::*,
@@ -72,6 +74,10 @@ impl LsmModule for YamaLsm {
 
 impl LsmCapabilityHook for YamaLsm {}
 
+impl LsmPathHook for YamaLsm {}
+
+impl LsmSocketHook for YamaLsm {}
+
 /// Returns the current Yama scope for alien access.
 pub fn get_scope() -> YamaScope {
     YAMA_SCOPE.load(Ordering::Relaxed)
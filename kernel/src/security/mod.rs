@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod lsm;
+pub mod seccomp;
 
 use cfg_if::cfg_if;
 
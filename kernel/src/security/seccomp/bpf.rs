@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The classic BPF (cBPF) programs used by seccomp filters.
+//!
+//! Only the subset of instructions accepted by Linux's seccomp is supported.
+//! Programs are verified and decoded once when they are loaded, so that
+//! running them on each syscall entry is cheap and cannot fail.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.15.7/source/kernel/seccomp.c#L278>
+
+use crate::prelude::*;
+
+/// A classic BPF instruction (`struct sock_filter`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct SockFilter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// The maximum number of instructions in a single program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+/// The number of scratch memory slots.
+const BPF_MEMWORDS: u32 = 16;
+
+// Instruction classes.
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ST: u16 = 0x02;
+const BPF_STX: u16 = 0x03;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_MISC: u16 = 0x07;
+
+// Load sizes and modes.
+const BPF_W: u16 = 0x00;
+const BPF_IMM: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_MEM: u16 = 0x60;
+const BPF_LEN: u16 = 0x80;
+
+// ALU operations.
+const BPF_ADD: u16 = 0x00;
+const BPF_SUB: u16 = 0x10;
+const BPF_MUL: u16 = 0x20;
+const BPF_DIV: u16 = 0x30;
+const BPF_OR: u16 = 0x40;
+const BPF_AND: u16 = 0x50;
+const BPF_LSH: u16 = 0x60;
+const BPF_RSH: u16 = 0x70;
+const BPF_NEG: u16 = 0x80;
+const BPF_XOR: u16 = 0xa0;
+
+// Jump operations.
+const BPF_JA: u16 = 0x00;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGT: u16 = 0x20;
+const BPF_JGE: u16 = 0x30;
+const BPF_JSET: u16 = 0x40;
+
+// Operand sources.
+const BPF_K: u16 = 0x00;
+const BPF_X: u16 = 0x08;
+const BPF_A: u16 = 0x10;
+
+// Miscellaneous operations.
+const BPF_TAX: u16 = 0x00;
+const BPF_TXA: u16 = 0x80;
+
+/// A verified classic BPF program.
+#[derive(Debug)]
+pub struct BpfProgram {
+    insns: Box<[Insn]>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Insn {
+    /// `A = data[k]` (a 32-bit word).
+    LdAbs(u32),
+    /// `A = k`.
+    LdImm(u32),
+    /// `X = k`.
+    LdxImm(u32),
+    /// `A = M[k]`.
+    LdMem(u32),
+    /// `X = M[k]`.
+    LdxMem(u32),
+    /// `M[k] = A`.
+    St(u32),
+    /// `M[k] = X`.
+    Stx(u32),
+    /// `A = A <op> src`.
+    Alu(AluOp, Operand),
+    /// `A = -A`.
+    Neg,
+    /// `pc += k`.
+    Ja(u32),
+    /// `pc += (A <op> src) ? jt : jf`.
+    Jmp(JmpOp, Operand, u8, u8),
+    /// Returns `k`.
+    RetK(u32),
+    /// Returns `A`.
+    RetA,
+    /// `X = A`.
+    Tax,
+    /// `A = X`.
+    Txa,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AluOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Or,
+    And,
+    Lsh,
+    Rsh,
+    Xor,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum JmpOp {
+    Eq,
+    Gt,
+    Ge,
+    Set,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Operand {
+    K(u32),
+    X,
+}
+
+impl BpfProgram {
+    /// Verifies and decodes a program that examines `data_len` bytes of data.
+    ///
+    /// The checks follow Linux's `bpf_check_classic` and `seccomp_check_filter`.
+    pub fn new(filter: &[SockFilter], data_len: u32) -> Result<Self> {
+        if filter.is_empty() || filter.len() > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program length is invalid");
+        }
+
+        let len = filter.len();
+        let mut insns = Vec::with_capacity(len);
+        for (pc, sock_filter) in filter.iter().enumerate() {
+            let insn = decode(sock_filter, data_len)?;
+
+            // Jumps may only go forward and must stay in the program.
+            let remaining = len - pc - 1;
+            match insn {
+                Insn::Ja(k) if k as usize >= remaining => {
+                    return_errno_with_message!(Errno::EINVAL, "the jump target is out of range");
+                }
+                Insn::Jmp(_, _, jt, jf) if jt as usize >= remaining || jf as usize >= remaining => {
+                    return_errno_with_message!(Errno::EINVAL, "the jump target is out of range");
+                }
+                _ => {}
+            }
+
+            insns.push(insn);
+        }
+
+        if !matches!(insns.last(), Some(Insn::RetK(_) | Insn::RetA)) {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program does not end with a return");
+        }
+
+        check_load_and_stores(&insns)?;
+
+        Ok(Self {
+            insns: insns.into_boxed_slice(),
+        })
+    }
+
+    /// Returns the number of instructions in the program.
+    pub fn nr_insns(&self) -> usize {
+        self.insns.len()
+    }
+
+    /// Runs the program on `data` and returns its result.
+    ///
+    /// `data` must be at least as long as the `data_len` that the program is
+    /// verified with.
+    pub fn run(&self, data: &[u8]) -> u32 {
+        let mut a: u32 = 0;
+        let mut x: u32 = 0;
+        let mut mem = [0u32; BPF_MEMWORDS as usize];
+        let mut pc = 0;
+
+        loop {
+            let insn = self.insns[pc];
+            pc += 1;
+
+            match insn {
+                Insn::LdAbs(k) => {
+                    let k = k as usize;
+                    a = u32::from_ne_bytes(data[k..k + 4].try_into().unwrap());
+                }
+                Insn::LdImm(k) => a = k,
+                Insn::LdxImm(k) => x = k,
+                Insn::LdMem(k) => a = mem[k as usize],
+                Insn::LdxMem(k) => x = mem[k as usize],
+                Insn::St(k) => mem[k as usize] = a,
+                Insn::Stx(k) => mem[k as usize] = x,
+                Insn::Alu(op, src) => {
+                    let src = match src {
+                        Operand::K(k) => k,
+                        Operand::X => x,
+                    };
+                    a = match op {
+                        AluOp::Add => a.wrapping_add(src),
+                        AluOp::Sub => a.wrapping_sub(src),
+                        AluOp::Mul => a.wrapping_mul(src),
+                        AluOp::Div => {
+                            // A division by zero (only possible with `X`) aborts the program.
+                            let Some(res) = a.checked_div(src) else {
+                                return 0;
+                            };
+                            res
+                        }
+                        AluOp::Or => a | src,
+                        AluOp::And => a & src,
+                        AluOp::Lsh => a.wrapping_shl(src),
+                        AluOp::Rsh => a.wrapping_shr(src),
+                        AluOp::Xor => a ^ src,
+                    };
+                }
+                Insn::Neg => a = a.wrapping_neg(),
+                Insn::Ja(k) => pc += k as usize,
+                Insn::Jmp(op, src, jt, jf) => {
+                    let src = match src {
+                        Operand::K(k) => k,
+                        Operand::X => x,
+                    };
+                    let cond = match op {
+                        JmpOp::Eq => a == src,
+                        JmpOp::Gt => a > src,
+                        JmpOp::Ge => a >= src,
+                        JmpOp::Set => a & src != 0,
+                    };
+                    pc += if cond { jt as usize } else { jf as usize };
+                }
+                Insn::RetK(k) => return k,
+                Insn::RetA => return a,
+                Insn::Tax => x = a,
+                Insn::Txa => a = x,
+            }
+        }
+    }
+}
+
+fn decode(sock_filter: &SockFilter, data_len: u32) -> Result<Insn> {
+    let SockFilter { code, jt, jf, k } = *sock_filter;
+
+    let operand = |code: u16| {
+        if code & BPF_X != 0 {
+            Operand::X
+        } else {
+            Operand::K(k)
+        }
+    };
+    let check_mem = |k: u32| {
+        if k >= BPF_MEMWORDS {
+            return_errno_with_message!(Errno::EINVAL, "the memory slot is out of range");
+        }
+        Ok(k)
+    };
+
+    let insn = match code {
+        _ if code == BPF_LD | BPF_W | BPF_ABS => {
+            if k >= data_len || k % 4 != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the load offset is invalid");
+            }
+            Insn::LdAbs(k)
+        }
+        // The "length" of the data is a constant.
+        _ if code == BPF_LD | BPF_W | BPF_LEN => Insn::LdImm(data_len),
+        _ if code == BPF_LDX | BPF_W | BPF_LEN => Insn::LdxImm(data_len),
+        _ if code == BPF_LD | BPF_IMM => Insn::LdImm(k),
+        _ if code == BPF_LDX | BPF_IMM => Insn::LdxImm(k),
+        _ if code == BPF_LD | BPF_MEM => Insn::LdMem(check_mem(k)?),
+        _ if code == BPF_LDX | BPF_MEM => Insn::LdxMem(check_mem(k)?),
+        _ if code == BPF_ST => Insn::St(check_mem(k)?),
+        _ if code == BPF_STX => Insn::Stx(check_mem(k)?),
+        _ if code == BPF_ALU | BPF_NEG => Insn::Neg,
+        _ if code & 0x07 == BPF_ALU && code & !(0xf0 | BPF_X | 0x07) == 0 => {
+            let op = match code & 0xf0 {
+                BPF_ADD => AluOp::Add,
+                BPF_SUB => AluOp::Sub,
+                BPF_MUL => AluOp::Mul,
+                BPF_DIV => AluOp::Div,
+                BPF_OR => AluOp::Or,
+                BPF_AND => AluOp::And,
+                BPF_LSH => AluOp::Lsh,
+                BPF_RSH => AluOp::Rsh,
+                BPF_XOR => AluOp::Xor,
+                _ => return_errno_with_message!(Errno::EINVAL, "the BPF instruction is invalid"),
+            };
+            let operand = operand(code);
+            match (op, operand) {
+                (AluOp::Div, Operand::K(0)) => {
+                    return_errno_with_message!(Errno::EINVAL, "the BPF program divides by zero");
+                }
+                (AluOp::Lsh | AluOp::Rsh, Operand::K(32..)) => {
+                    return_errno_with_message!(Errno::EINVAL, "the shift amount is too large");
+                }
+                _ => {}
+            }
+            Insn::Alu(op, operand)
+        }
+        _ if code == BPF_JMP | BPF_JA => Insn::Ja(k),
+        _ if code & 0x07 == BPF_JMP && code & !(0xf0 | BPF_X | 0x07) == 0 => {
+            let op = match code & 0xf0 {
+                BPF_JEQ => JmpOp::Eq,
+                BPF_JGT => JmpOp::Gt,
+                BPF_JGE => JmpOp::Ge,
+                BPF_JSET => JmpOp::Set,
+                _ => return_errno_with_message!(Errno::EINVAL, "the BPF instruction is invalid"),
+            };
+            Insn::Jmp(op, operand(code), jt, jf)
+        }
+        _ if code == BPF_RET | BPF_K => Insn::RetK(k),
+        _ if code == BPF_RET | BPF_A => Insn::RetA,
+        _ if code == BPF_MISC | BPF_TAX => Insn::Tax,
+        _ if code == BPF_MISC | BPF_TXA => Insn::Txa,
+        _ => return_errno_with_message!(Errno::EINVAL, "the BPF instruction is not allowed"),
+    };
+
+    Ok(insn)
+}
+
+/// Checks that every memory slot is stored before it is loaded on all paths.
+fn check_load_and_stores(insns: &[Insn]) -> Result<()> {
+    const ALL_VALID: u16 = u16::MAX;
+
+    let mut masks = vec![ALL_VALID; insns.len()];
+    let mut mem_valid: u16 = 0;
+
+    for (pc, insn) in insns.iter().enumerate() {
+        mem_valid &= masks[pc];
+
+        match *insn {
+            Insn::St(k) | Insn::Stx(k) => mem_valid |= 1 << k,
+            Insn::LdMem(k) | Insn::LdxMem(k) => {
+                if mem_valid & (1 << k) == 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the memory slot is loaded before being stored"
+                    );
+                }
+            }
+            Insn::Ja(k) => {
+                masks[pc + 1 + k as usize] &= mem_valid;
+                mem_valid = ALL_VALID;
+            }
+            Insn::Jmp(_, _, jt, jf) => {
+                masks[pc + 1 + jt as usize] &= mem_valid;
+                masks[pc + 1 + jf as usize] &= mem_valid;
+                mem_valid = ALL_VALID;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
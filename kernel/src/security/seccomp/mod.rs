@@ -0,0 +1,471 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Secure computing mode (seccomp).
+//!
+//! Seccomp restricts the syscalls that a thread can make. In the strict mode,
+//! only `read`, `write`, `exit`, and `rt_sigreturn` are permitted. In the
+//! filter mode, every syscall is examined by a stack of classic BPF programs
+//! installed by the thread, which decide how the syscall is handled.
+//!
+//! Unlike LSM modules, seccomp cannot be disabled on the kernel command line,
+//! since user programs rely on it to sandbox themselves.
+//!
+//! Reference: <https://docs.kernel.org/userspace-api/seccomp_filter.html>
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use ostd::{arch::cpu::context::UserContext, user::UserContextApi};
+
+pub use self::bpf::{BPF_MAXINSNS, BpfProgram, SockFilter};
+use crate::{
+    cpu::LinuxAbi,
+    prelude::*,
+    process::{
+        TermStatus,
+        posix_thread::{
+            AsPosixThread, PosixThread, do_exit, do_exit_group, ptrace::PtraceStopResult,
+        },
+        signal::{
+            c_types::siginfo_t,
+            constants::{SIGKILL, SIGSYS, SYS_SECCOMP},
+            signals::raw::RawSignal,
+        },
+    },
+    thread::Tid,
+};
+
+mod bpf;
+
+/// The seccomp mode of a thread.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum SeccompMode {
+    Disabled = 0,
+    Strict = 1,
+    Filter = 2,
+}
+
+bitflags! {
+    /// The flags of `SECCOMP_SET_MODE_FILTER`.
+    pub struct SeccompFilterFlags: u32 {
+        /// Synchronizes the filters of all threads in the process.
+        const TSYNC = 1 << 0;
+        /// Logs all actions except `SECCOMP_RET_ALLOW`.
+        const LOG = 1 << 1;
+        /// Disables the speculative store bypass mitigation.
+        const SPEC_ALLOW = 1 << 2;
+        /// Returns a user notification file descriptor.
+        const NEW_LISTENER = 1 << 3;
+        /// Returns `ESRCH` instead of a thread ID if `TSYNC` fails.
+        const TSYNC_ESRCH = 1 << 4;
+        /// Waits killably for user notification replies.
+        const WAIT_KILLABLE_RECV = 1 << 5;
+    }
+}
+
+// The return values of seccomp filters.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.15.7/source/include/uapi/linux/seccomp.h#L38>
+pub const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+pub const SECCOMP_RET_KILL_THREAD: u32 = 0x0000_0000;
+pub const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+pub const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+pub const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+pub const SECCOMP_RET_TRACE: u32 = 0x7ff0_0000;
+pub const SECCOMP_RET_LOG: u32 = 0x7ffc_0000;
+pub const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+const SECCOMP_RET_ACTION_FULL: u32 = 0xffff_0000;
+const SECCOMP_RET_DATA: u32 = 0x0000_ffff;
+
+/// The maximum number of instructions of all filters that a syscall can go through.
+///
+/// Each filter is penalized by four instructions to discourage tiny filters.
+const MAX_INSNS_PER_PATH: usize = 32768;
+const FILTER_INSNS_PENALTY: usize = 4;
+
+/// The largest error number that `SECCOMP_RET_ERRNO` can return.
+const MAX_ERRNO: u16 = 4095;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3;
+#[cfg(target_arch = "loongarch64")]
+const AUDIT_ARCH: u32 = 0xc000_0102;
+/// The architecture of the 32-bit system calls issued by `int 0x80`.
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH_I386: u32 = 0x4000_0003;
+
+/// The syscalls permitted in the strict mode (`read`, `write`, `exit`, and `rt_sigreturn`).
+#[cfg(target_arch = "x86_64")]
+const STRICT_MODE_SYSCALLS: [usize; 4] = [0, 1, 60, 15];
+#[cfg(not(target_arch = "x86_64"))]
+const STRICT_MODE_SYSCALLS: [usize; 4] = [63, 64, 93, 139];
+/// The 32-bit syscalls permitted in the strict mode (`read`, `write`, `exit`, and `sigreturn`).
+#[cfg(target_arch = "x86_64")]
+const STRICT_MODE_IA32_SYSCALLS: [usize; 4] = [3, 4, 1, 119];
+
+/// The data examined by seccomp filters (`struct seccomp_data`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct SeccompData {
+    pub nr: i32,
+    pub arch: u32,
+    pub instruction_pointer: u64,
+    pub args: [u64; 6],
+}
+
+impl SeccompData {
+    fn from_user_ctx(user_ctx: &UserContext) -> Self {
+        Self {
+            nr: user_ctx.syscall_num() as i32,
+            arch: audit_arch(user_ctx),
+            instruction_pointer: user_ctx.instruction_pointer() as u64,
+            args: user_ctx.syscall_args().map(|arg| arg as u64),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "x86_64"), expect(unused_variables))]
+fn audit_arch(user_ctx: &UserContext) -> u32 {
+    #[cfg(target_arch = "x86_64")]
+    if user_ctx.is_ia32_syscall() {
+        return AUDIT_ARCH_I386;
+    }
+
+    AUDIT_ARCH
+}
+
+#[cfg_attr(not(target_arch = "x86_64"), expect(unused_variables))]
+fn strict_mode_syscalls(user_ctx: &UserContext) -> &'static [usize] {
+    #[cfg(target_arch = "x86_64")]
+    if user_ctx.is_ia32_syscall() {
+        return &STRICT_MODE_IA32_SYSCALLS;
+    }
+
+    &STRICT_MODE_SYSCALLS
+}
+
+/// A seccomp filter.
+///
+/// Filters form a stack: each filter refers to the filter installed before
+/// it, which may be shared by multiple threads.
+#[derive(Debug)]
+pub struct SeccompFilter {
+    prog: BpfProgram,
+    log: bool,
+    prev: Option<Arc<SeccompFilter>>,
+}
+
+impl SeccompFilter {
+    /// Runs all filters in the stack and returns the return value with the highest
+    /// precedence, along with whether the filter returning it requests logging.
+    fn run(&self, data: &SeccompData) -> (u32, bool) {
+        let data = data.as_bytes();
+
+        let mut result = (SECCOMP_RET_ALLOW, false);
+        let mut filter = Some(self);
+        while let Some(current) = filter {
+            let ret = current.prog.run(data);
+            // Lower (signed) action values take precedence.
+            if ((ret & SECCOMP_RET_ACTION_FULL) as i32)
+                < ((result.0 & SECCOMP_RET_ACTION_FULL) as i32)
+            {
+                result = (ret, current.log);
+            }
+            filter = current.prev.as_deref();
+        }
+
+        result
+    }
+
+    /// Returns whether `self` is `filter` or one of its ancestors.
+    fn is_ancestor_of(self: &Arc<Self>, filter: Option<&Arc<SeccompFilter>>) -> bool {
+        let mut filter = filter;
+        while let Some(current) = filter {
+            if Arc::ptr_eq(self, current) {
+                return true;
+            }
+            filter = current.prev.as_ref();
+        }
+        false
+    }
+
+    /// Iterates over the filters in the stack, starting from `self`.
+    fn iter(&self) -> impl Iterator<Item = &SeccompFilter> {
+        core::iter::successors(Some(self), |filter| filter.prev.as_deref())
+    }
+}
+
+/// The per-thread seccomp state.
+pub struct Seccomp {
+    mode: AtomicU8,
+    filter: SpinLock<Option<Arc<SeccompFilter>>>,
+}
+
+impl Seccomp {
+    /// Creates a disabled seccomp state.
+    pub fn new() -> Self {
+        Self {
+            mode: AtomicU8::new(SeccompMode::Disabled as u8),
+            filter: SpinLock::new(None),
+        }
+    }
+
+    /// Creates a seccomp state inherited from `self` for a new thread.
+    pub fn new_inherited(&self) -> Self {
+        let filter = self.filter.lock();
+        Self {
+            mode: AtomicU8::new(self.mode.load(Ordering::Relaxed)),
+            filter: SpinLock::new(filter.clone()),
+        }
+    }
+
+    /// Returns the seccomp mode.
+    pub fn mode(&self) -> SeccompMode {
+        SeccompMode::try_from(self.mode.load(Ordering::Acquire)).unwrap()
+    }
+
+    /// Returns the number of filters in the stack.
+    pub fn nr_filters(&self) -> usize {
+        self.filter
+            .lock()
+            .as_ref()
+            .map_or(0, |filter| filter.iter().count())
+    }
+
+    fn filter(&self) -> Option<Arc<SeccompFilter>> {
+        self.filter.lock().clone()
+    }
+
+    fn set_filter(&self, filter: Arc<SeccompFilter>) {
+        *self.filter.lock() = Some(filter);
+        self.mode
+            .store(SeccompMode::Filter as u8, Ordering::Release);
+    }
+}
+
+impl Default for Seccomp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Enables the strict mode for the current thread.
+pub fn set_mode_strict(ctx: &Context) -> Result<()> {
+    let seccomp = ctx.posix_thread.seccomp();
+    if seccomp.mode() != SeccompMode::Disabled {
+        return_errno_with_message!(Errno::EINVAL, "the seccomp mode cannot be changed");
+    }
+
+    seccomp
+        .mode
+        .store(SeccompMode::Strict as u8, Ordering::Release);
+    Ok(())
+}
+
+/// Installs a new filter for the current thread.
+///
+/// With [`SeccompFilterFlags::TSYNC`], the filter is also installed for all
+/// other threads in the process. If some thread cannot be synchronized, no
+/// filter is installed and the ID of that thread is returned.
+pub fn set_mode_filter(
+    prog: BpfProgram,
+    flags: SeccompFilterFlags,
+    ctx: &Context,
+) -> Result<Option<Tid>> {
+    let posix_thread = ctx.posix_thread;
+    let seccomp = posix_thread.seccomp();
+
+    // Hold the lock to prevent other threads from changing their filters concurrently
+    // if the filter is going to be installed for all threads.
+    let tasks = flags
+        .contains(SeccompFilterFlags::TSYNC)
+        .then(|| ctx.process.tasks().lock());
+
+    if !matches!(seccomp.mode(), SeccompMode::Disabled | SeccompMode::Filter) {
+        return_errno_with_message!(Errno::EINVAL, "the seccomp mode cannot be changed");
+    }
+
+    let prev = seccomp.filter();
+
+    let total_insns = prev
+        .iter()
+        .flat_map(|filter| filter.iter())
+        .map(|filter| filter.prog.nr_insns() + FILTER_INSNS_PENALTY)
+        .sum::<usize>()
+        + prog.nr_insns()
+        + FILTER_INSNS_PENALTY;
+    if total_insns > MAX_INSNS_PER_PATH {
+        return_errno_with_message!(Errno::ENOMEM, "the seccomp filters are too large");
+    }
+
+    let filter = Arc::new(SeccompFilter {
+        prog,
+        log: flags.contains(SeccompFilterFlags::LOG),
+        prev: prev.clone(),
+    });
+
+    let Some(tasks) = tasks else {
+        seccomp.set_filter(filter);
+        return Ok(None);
+    };
+
+    let other_threads = || {
+        tasks
+            .as_slice()
+            .iter()
+            .filter(|task| !core::ptr::eq(task.as_ref(), ctx.task))
+            .map(|task| task.as_posix_thread().unwrap())
+    };
+
+    // All other threads must have no filters, or have filters that are the
+    // ancestors of the filter of the current thread.
+    for thread in other_threads() {
+        let can_sync = match thread.seccomp().mode() {
+            SeccompMode::Disabled => true,
+            SeccompMode::Strict => false,
+            SeccompMode::Filter => thread
+                .seccomp()
+                .filter()
+                .is_some_and(|thread_filter| thread_filter.is_ancestor_of(prev.as_ref())),
+        };
+        if !can_sync {
+            return Ok(Some(thread.tid()));
+        }
+    }
+
+    let no_new_privs = posix_thread.no_new_privs();
+    for thread in other_threads() {
+        if no_new_privs {
+            thread.set_no_new_privs();
+        }
+        thread.seccomp().set_filter(filter.clone());
+    }
+    seccomp.set_filter(filter);
+
+    Ok(None)
+}
+
+/// Returns whether the seccomp action is supported.
+pub fn is_action_available(action: u32) -> bool {
+    matches!(
+        action,
+        SECCOMP_RET_KILL_PROCESS
+            | SECCOMP_RET_KILL_THREAD
+            | SECCOMP_RET_TRAP
+            | SECCOMP_RET_ERRNO
+            | SECCOMP_RET_TRACE
+            | SECCOMP_RET_LOG
+            | SECCOMP_RET_ALLOW
+    )
+}
+
+/// Checks whether the current syscall may be executed according to the
+/// seccomp state of the current thread.
+///
+/// If this method returns `false`, the syscall should be skipped. The return
+/// value of the syscall (if any) has been set in `user_ctx`, or the current
+/// thread has been killed.
+pub fn may_execute_syscall(ctx: &Context, user_ctx: &mut UserContext) -> bool {
+    match ctx.posix_thread.seccomp().mode() {
+        SeccompMode::Disabled => true,
+        SeccompMode::Strict => {
+            if strict_mode_syscalls(user_ctx).contains(&user_ctx.syscall_num()) {
+                return true;
+            }
+            do_exit(TermStatus::Killed(SIGKILL), ctx, user_ctx);
+            false
+        }
+        SeccompMode::Filter => run_filters(ctx, user_ctx, false),
+    }
+}
+
+fn run_filters(ctx: &Context, user_ctx: &mut UserContext, is_recheck_after_trace: bool) -> bool {
+    let Some(filter) = ctx.posix_thread.seccomp().filter() else {
+        return true;
+    };
+
+    let data = SeccompData::from_user_ctx(user_ctx);
+    let (ret, log) = filter.run(&data);
+    let action = ret & SECCOMP_RET_ACTION_FULL;
+    let ret_data = (ret & SECCOMP_RET_DATA) as u16;
+
+    if action == SECCOMP_RET_LOG || (log && action != SECCOMP_RET_ALLOW) {
+        info!(
+            "[seccomp] tid = {}, syscall = {}, action = {:#x}",
+            ctx.posix_thread.tid(),
+            data.nr,
+            action
+        );
+    }
+
+    match action {
+        SECCOMP_RET_ALLOW | SECCOMP_RET_LOG => true,
+        SECCOMP_RET_ERRNO => {
+            let errno = ret_data.min(MAX_ERRNO);
+            user_ctx.set_syscall_ret((-(errno as isize)) as usize);
+            false
+        }
+        SECCOMP_RET_TRAP => {
+            // The registers are left untouched, so the return value of the
+            // syscall is "rolled back" to what it was at the syscall entry.
+            let mut siginfo = siginfo_t::new(SIGSYS, SYS_SECCOMP);
+            siginfo.si_errno = ret_data as i32;
+            siginfo.set_sigsys(data.instruction_pointer as Vaddr, data.nr, data.arch);
+            ctx.posix_thread
+                .enqueue_signal(Box::new(RawSignal::new(siginfo)));
+            false
+        }
+        SECCOMP_RET_TRACE => {
+            // A tracer has already examined the syscall.
+            if is_recheck_after_trace {
+                return true;
+            }
+            trace_syscall(ret_data, ctx, user_ctx)
+        }
+        SECCOMP_RET_USER_NOTIF => {
+            // User notifications are not supported, so there are no listeners.
+            user_ctx.set_syscall_ret((-(Errno::ENOSYS as isize)) as usize);
+            false
+        }
+        SECCOMP_RET_KILL_THREAD if !is_single_threaded(ctx.posix_thread) => {
+            do_exit(TermStatus::Killed(SIGSYS), ctx, user_ctx);
+            false
+        }
+        // Unknown actions are treated as `SECCOMP_RET_KILL_PROCESS`.
+        _ => {
+            do_exit_group(TermStatus::Killed(SIGSYS), ctx, user_ctx);
+            false
+        }
+    }
+}
+
+/// Notifies the tracer of the syscall with a seccomp ptrace-event-stop.
+fn trace_syscall(ret_data: u16, ctx: &Context, user_ctx: &mut UserContext) -> bool {
+    match ctx
+        .posix_thread
+        .ptrace_may_stop_on_seccomp(ret_data, ctx, user_ctx)
+    {
+        PtraceStopResult::Continued(_) => {}
+        PtraceStopResult::Interrupted => return false,
+        PtraceStopResult::NotTraced(_) => {
+            // Without a tracer, the syscall fails.
+            user_ctx.set_syscall_ret((-(Errno::ENOSYS as isize)) as usize);
+            return false;
+        }
+    }
+
+    // The tracer may skip the syscall by setting the syscall number to -1.
+    if (user_ctx.syscall_num() as isize) < 0 {
+        return false;
+    }
+
+    // The tracer may have changed the syscall, so the filters must be run again.
+    run_filters(ctx, user_ctx, true)
+}
+
+fn is_single_threaded(posix_thread: &PosixThread) -> bool {
+    posix_thread.process().tasks().lock().as_slice().len() == 1
+}
@@ -0,0 +1,437 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System call dispatch for 32-bit x86 programs (the ia32 ABI).
+//!
+//! The system calls whose arguments or user-space structures have different layouts for 32-bit
+//! programs are handled by the compatible versions (e.g., `compat_sys_*`). The system calls that
+//! are not listed here return `ENOSYS`.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/entry/syscalls/syscall_32.tbl>
+
+use super::{
+    accept::sys_accept4,
+    access::{sys_access, sys_faccessat, sys_faccessat2},
+    alarm::sys_alarm,
+    bind::sys_bind,
+    brk::sys_brk,
+    capget::sys_capget,
+    capset::sys_capset,
+    chdir::{sys_chdir, sys_fchdir},
+    chmod::{sys_chmod, sys_fchmod, sys_fchmodat, sys_fchmodat2},
+    chown::{sys_chown, sys_fchown, sys_fchownat, sys_lchown},
+    chroot::sys_chroot,
+    clock_gettime::{compat_sys_clock_gettime, sys_clock_gettime},
+    clone::{compat_sys_clone, sys_clone3},
+    close::{sys_close, sys_close_range},
+    connect::sys_connect,
+    copy_file_range::sys_copy_file_range,
+    dup::{sys_dup, sys_dup2, sys_dup3},
+    epoll::{
+        sys_epoll_create, sys_epoll_create1, sys_epoll_ctl, sys_epoll_pwait, sys_epoll_pwait2,
+        sys_epoll_wait,
+    },
+    eventfd::{sys_eventfd, sys_eventfd2},
+    execve::{sys_execve, sys_execveat},
+    exit::sys_exit,
+    exit_group::sys_exit_group,
+    fadvise64::{compat_sys_fadvise64, compat_sys_fadvise64_64},
+    fallocate::compat_sys_fallocate,
+    fanotify::sys_fanotify_init,
+    fcntl::compat_sys_fcntl64,
+    flock::sys_flock,
+    fork::{sys_fork, sys_vfork},
+    fsync::{sys_fdatasync, sys_fsync},
+    futex::{compat_sys_futex, sys_futex},
+    get_ioprio::sys_ioprio_get,
+    get_priority::sys_get_priority,
+    getcpu::sys_getcpu,
+    getcwd::sys_getcwd,
+    getdents64::sys_getdents64,
+    getegid::sys_getegid,
+    geteuid::sys_geteuid,
+    getgid::sys_getgid,
+    getgroups::sys_getgroups,
+    getpeername::sys_getpeername,
+    getpgid::sys_getpgid,
+    getpgrp::sys_getpgrp,
+    getpid::sys_getpid,
+    getppid::sys_getppid,
+    getrandom::sys_getrandom,
+    getresgid::sys_getresgid,
+    getresuid::sys_getresuid,
+    getrusage::sys_getrusage,
+    getsid::sys_getsid,
+    getsockname::sys_getsockname,
+    getsockopt::sys_getsockopt,
+    gettid::sys_gettid,
+    gettimeofday::compat_sys_gettimeofday,
+    getuid::sys_getuid,
+    getxattr::{sys_fgetxattr, sys_getxattr, sys_lgetxattr},
+    impl_syscall_nums_and_dispatch_fn,
+    inotify::{sys_inotify_add_watch, sys_inotify_init, sys_inotify_init1, sys_inotify_rm_watch},
+    io_uring_enter::sys_io_uring_enter,
+    io_uring_register::sys_io_uring_register,
+    io_uring_setup::sys_io_uring_setup,
+    ioctl::sys_ioctl,
+    kill::sys_kill,
+    landlock::{sys_landlock_add_rule, sys_landlock_create_ruleset, sys_landlock_restrict_self},
+    link::{sys_link, sys_linkat},
+    listen::sys_listen,
+    listxattr::{sys_flistxattr, sys_listxattr, sys_llistxattr},
+    lseek::{compat_sys_lseek, sys_llseek},
+    madvise::sys_madvise,
+    memfd_create::sys_memfd_create,
+    mkdir::{sys_mkdir, sys_mkdirat},
+    mknod::{sys_mknod, sys_mknodat},
+    mmap::sys_mmap2,
+    mount::sys_mount,
+    mprotect::sys_mprotect,
+    mq_timedreceive::sys_mq_timedreceive,
+    mq_timedsend::sys_mq_timedsend,
+    mq_unlink::sys_mq_unlink,
+    mremap::sys_mremap,
+    msgget::sys_msgget,
+    msync::sys_msync,
+    munmap::sys_munmap,
+    nanosleep::{compat_sys_clock_nanosleep, compat_sys_nanosleep, sys_clock_nanosleep},
+    open::{sys_creat, sys_open, sys_openat},
+    pause::sys_pause,
+    personality::sys_personality,
+    pidfd_getfd::sys_pidfd_getfd,
+    pidfd_open::sys_pidfd_open,
+    pipe::{sys_pipe, sys_pipe2},
+    pivot_root::sys_pivot_root,
+    poll::sys_poll,
+    ppoll::sys_ppoll,
+    prctl::sys_prctl,
+    pread64::compat_sys_pread64,
+    preadv::{compat_sys_preadv, sys_readv},
+    prlimit64::sys_prlimit64,
+    pwrite64::compat_sys_pwrite64,
+    pwritev::{compat_sys_pwritev, sys_writev},
+    read::sys_read,
+    readlink::{sys_readlink, sys_readlinkat},
+    reboot::sys_reboot,
+    recvfrom::sys_recvfrom,
+    recvmsg::compat_sys_recvmsg,
+    removexattr::{sys_fremovexattr, sys_lremovexattr, sys_removexattr},
+    rename::{sys_rename, sys_renameat, sys_renameat2},
+    rmdir::sys_rmdir,
+    rt_sigaction::compat_sys_rt_sigaction,
+    rt_sigpending::sys_rt_sigpending,
+    rt_sigprocmask::sys_rt_sigprocmask,
+    rt_sigreturn::{compat_sys_rt_sigreturn, sys_sigreturn},
+    rt_sigsuspend::sys_rt_sigsuspend,
+    sched_affinity::{sys_sched_getaffinity, sys_sched_setaffinity},
+    sched_get_priority_max::sys_sched_get_priority_max,
+    sched_get_priority_min::sys_sched_get_priority_min,
+    sched_getattr::sys_sched_getattr,
+    sched_getparam::sys_sched_getparam,
+    sched_getscheduler::sys_sched_getscheduler,
+    sched_setattr::sys_sched_setattr,
+    sched_setparam::sys_sched_setparam,
+    sched_setscheduler::sys_sched_setscheduler,
+    sched_yield::sys_sched_yield,
+    semget::sys_semget,
+    semop::sys_semtimedop,
+    sendfile::sys_sendfile,
+    sendmsg::compat_sys_sendmsg,
+    sendto::sys_sendto,
+    set_ioprio::sys_ioprio_set,
+    set_priority::sys_set_priority,
+    set_tid_address::sys_set_tid_address,
+    setdomainname::sys_setdomainname,
+    setfsgid::sys_setfsgid,
+    setfsuid::sys_setfsuid,
+    setgid::sys_setgid,
+    setgroups::sys_setgroups,
+    sethostname::sys_sethostname,
+    setns::sys_setns,
+    setpgid::sys_setpgid,
+    setregid::sys_setregid,
+    setresgid::sys_setresgid,
+    setresuid::sys_setresuid,
+    setreuid::sys_setreuid,
+    setsid::sys_setsid,
+    setsockopt::sys_setsockopt,
+    setuid::sys_setuid,
+    setxattr::{sys_fsetxattr, sys_lsetxattr, sys_setxattr},
+    shmat::sys_shmat,
+    shmdt::sys_shmdt,
+    shmget::sys_shmget,
+    shutdown::sys_shutdown,
+    sigaltstack::compat_sys_sigaltstack,
+    signalfd::{sys_signalfd, sys_signalfd4},
+    socket::sys_socket,
+    socketpair::sys_socketpair,
+    splice::{sys_splice, sys_tee, sys_vmsplice},
+    stat::{sys_fstat64, sys_fstatat64, sys_lstat64, sys_stat64},
+    statx::sys_statx,
+    swapoff::sys_swapoff,
+    swapon::sys_swapon,
+    symlink::{sys_symlink, sys_symlinkat},
+    sync::{sys_sync, sys_syncfs},
+    tgkill::{sys_tgkill, sys_tkill},
+    thread_area::{sys_get_thread_area, sys_set_thread_area},
+    time::compat_sys_time,
+    timerfd_create::sys_timerfd_create,
+    timerfd_gettime::sys_timerfd_gettime,
+    timerfd_settime::sys_timerfd_settime,
+    truncate::{compat_sys_ftruncate, compat_sys_truncate, sys_ftruncate64, sys_truncate64},
+    umask::sys_umask,
+    umount::sys_umount,
+    uname::sys_uname,
+    unlink::{sys_unlink, sys_unlinkat},
+    unshare::sys_unshare,
+    userfaultfd::sys_userfaultfd,
+    utimens::sys_utimensat,
+    wait4::sys_wait4,
+    write::sys_write,
+};
+
+impl_syscall_nums_and_dispatch_fn! {
+    SYS_EXIT = 1               => sys_exit(args[..1], &mut user_ctx);
+    SYS_FORK = 2               => sys_fork(args[..0], &mut user_ctx);
+    SYS_READ = 3               => sys_read(args[..3]);
+    SYS_WRITE = 4              => sys_write(args[..3]);
+    SYS_OPEN = 5               => sys_open(args[..3]);
+    SYS_CLOSE = 6              => sys_close(args[..1]);
+    SYS_CREAT = 8              => sys_creat(args[..2]);
+    SYS_LINK = 9               => sys_link(args[..2]);
+    SYS_UNLINK = 10            => sys_unlink(args[..1]);
+    SYS_EXECVE = 11            => sys_execve(args[..3], &mut user_ctx);
+    SYS_CHDIR = 12             => sys_chdir(args[..1]);
+    SYS_TIME = 13              => compat_sys_time(args[..1]);
+    SYS_MKNOD = 14             => sys_mknod(args[..3]);
+    SYS_CHMOD = 15             => sys_chmod(args[..2]);
+    SYS_LSEEK = 19             => compat_sys_lseek(args[..3]);
+    SYS_GETPID = 20            => sys_getpid(args[..0]);
+    SYS_MOUNT = 21             => sys_mount(args[..5]);
+    SYS_ALARM = 27             => sys_alarm(args[..1]);
+    SYS_PAUSE = 29             => sys_pause(args[..0]);
+    SYS_ACCESS = 33            => sys_access(args[..2]);
+    SYS_SYNC = 36              => sys_sync(args[..0]);
+    SYS_KILL = 37              => sys_kill(args[..2]);
+    SYS_RENAME = 38            => sys_rename(args[..2]);
+    SYS_MKDIR = 39             => sys_mkdir(args[..2]);
+    SYS_RMDIR = 40             => sys_rmdir(args[..1]);
+    SYS_DUP = 41               => sys_dup(args[..1]);
+    SYS_PIPE = 42              => sys_pipe(args[..1]);
+    SYS_BRK = 45               => sys_brk(args[..1]);
+    SYS_UMOUNT2 = 52           => sys_umount(args[..2]);
+    SYS_IOCTL = 54             => sys_ioctl(args[..3]);
+    SYS_FCNTL = 55             => compat_sys_fcntl64(args[..3]);
+    SYS_SETPGID = 57           => sys_setpgid(args[..2]);
+    SYS_UMASK = 60             => sys_umask(args[..1]);
+    SYS_CHROOT = 61            => sys_chroot(args[..1]);
+    SYS_DUP2 = 63              => sys_dup2(args[..2]);
+    SYS_GETPPID = 64           => sys_getppid(args[..0]);
+    SYS_GETPGRP = 65           => sys_getpgrp(args[..0]);
+    SYS_SETSID = 66            => sys_setsid(args[..0]);
+    SYS_SETHOSTNAME = 74       => sys_sethostname(args[..2]);
+    SYS_GETRUSAGE = 77         => sys_getrusage(args[..2]);
+    SYS_GETTIMEOFDAY = 78      => compat_sys_gettimeofday(args[..2]);
+    SYS_SYMLINK = 83           => sys_symlink(args[..2]);
+    SYS_READLINK = 85          => sys_readlink(args[..3]);
+    SYS_SWAPON = 87            => sys_swapon(args[..2]);
+    SYS_REBOOT = 88            => sys_reboot(args[..4]);
+    SYS_MUNMAP = 91            => sys_munmap(args[..2]);
+    SYS_TRUNCATE = 92          => compat_sys_truncate(args[..2]);
+    SYS_FTRUNCATE = 93         => compat_sys_ftruncate(args[..2]);
+    SYS_FCHMOD = 94            => sys_fchmod(args[..2]);
+    SYS_GET_PRIORITY = 96      => sys_get_priority(args[..2]);
+    SYS_SET_PRIORITY = 97      => sys_set_priority(args[..3]);
+    SYS_WAIT4 = 114            => sys_wait4(args[..4]);
+    SYS_SWAPOFF = 115          => sys_swapoff(args[..1]);
+    SYS_FSYNC = 118            => sys_fsync(args[..1]);
+    SYS_SIGRETURN = 119        => sys_sigreturn(args[..0], &mut user_ctx);
+    SYS_CLONE = 120            => compat_sys_clone(args[..5], &mut user_ctx);
+    SYS_SETDOMAINNAME = 121    => sys_setdomainname(args[..2]);
+    SYS_UNAME = 122            => sys_uname(args[..1]);
+    SYS_MPROTECT = 125         => sys_mprotect(args[..3]);
+    SYS_GETPGID = 132          => sys_getpgid(args[..1]);
+    SYS_FCHDIR = 133           => sys_fchdir(args[..1]);
+    SYS_PERSONALITY = 136      => sys_personality(args[..1]);
+    SYS_LLSEEK = 140           => sys_llseek(args[..5]);
+    SYS_FLOCK = 143            => sys_flock(args[..2]);
+    SYS_MSYNC = 144            => sys_msync(args[..3]);
+    SYS_READV = 145            => sys_readv(args[..3]);
+    SYS_WRITEV = 146           => sys_writev(args[..3]);
+    SYS_GETSID = 147           => sys_getsid(args[..1]);
+    SYS_FDATASYNC = 148        => sys_fdatasync(args[..1]);
+    SYS_SCHED_SETPARAM = 154   => sys_sched_setparam(args[..2]);
+    SYS_SCHED_GETPARAM = 155   => sys_sched_getparam(args[..2]);
+    SYS_SCHED_SETSCHEDULER = 156 => sys_sched_setscheduler(args[..3]);
+    SYS_SCHED_GETSCHEDULER = 157 => sys_sched_getscheduler(args[..1]);
+    SYS_SCHED_YIELD = 158      => sys_sched_yield(args[..0]);
+    SYS_SCHED_GET_PRIORITY_MAX = 159 => sys_sched_get_priority_max(args[..1]);
+    SYS_SCHED_GET_PRIORITY_MIN = 160 => sys_sched_get_priority_min(args[..1]);
+    SYS_NANOSLEEP = 162        => compat_sys_nanosleep(args[..2]);
+    SYS_MREMAP = 163           => sys_mremap(args[..5]);
+    SYS_POLL = 168             => sys_poll(args[..3]);
+    SYS_PRCTL = 172            => sys_prctl(args[..5]);
+    SYS_RT_SIGRETURN = 173     => compat_sys_rt_sigreturn(args[..0], &mut user_ctx);
+    SYS_RT_SIGACTION = 174     => compat_sys_rt_sigaction(args[..4]);
+    SYS_RT_SIGPROCMASK = 175   => sys_rt_sigprocmask(args[..4]);
+    SYS_RT_SIGPENDING = 176    => sys_rt_sigpending(args[..2]);
+    SYS_RT_SIGSUSPEND = 179    => sys_rt_sigsuspend(args[..2]);
+    SYS_PREAD64 = 180          => compat_sys_pread64(args[..5]);
+    SYS_PWRITE64 = 181         => compat_sys_pwrite64(args[..5]);
+    SYS_GETCWD = 183           => sys_getcwd(args[..2]);
+    SYS_CAPGET = 184           => sys_capget(args[..2]);
+    SYS_CAPSET = 185           => sys_capset(args[..2]);
+    SYS_SIGALTSTACK = 186      => compat_sys_sigaltstack(args[..2], &user_ctx);
+    SYS_VFORK = 190            => sys_vfork(args[..0], &mut user_ctx);
+    SYS_MMAP2 = 192            => sys_mmap2(args[..6]);
+    SYS_TRUNCATE64 = 193       => sys_truncate64(args[..3]);
+    SYS_FTRUNCATE64 = 194      => sys_ftruncate64(args[..3]);
+    SYS_STAT64 = 195           => sys_stat64(args[..2]);
+    SYS_LSTAT64 = 196          => sys_lstat64(args[..2]);
+    SYS_FSTAT64 = 197          => sys_fstat64(args[..2]);
+    SYS_LCHOWN32 = 198         => sys_lchown(args[..3]);
+    SYS_GETUID32 = 199         => sys_getuid(args[..0]);
+    SYS_GETGID32 = 200         => sys_getgid(args[..0]);
+    SYS_GETEUID32 = 201        => sys_geteuid(args[..0]);
+    SYS_GETEGID32 = 202        => sys_getegid(args[..0]);
+    SYS_SETREUID32 = 203       => sys_setreuid(args[..2]);
+    SYS_SETREGID32 = 204       => sys_setregid(args[..2]);
+    SYS_GETGROUPS32 = 205      => sys_getgroups(args[..2]);
+    SYS_SETGROUPS32 = 206      => sys_setgroups(args[..2]);
+    SYS_FCHOWN32 = 207         => sys_fchown(args[..3]);
+    SYS_SETRESUID32 = 208      => sys_setresuid(args[..3]);
+    SYS_GETRESUID32 = 209      => sys_getresuid(args[..3]);
+    SYS_SETRESGID32 = 210      => sys_setresgid(args[..3]);
+    SYS_GETRESGID32 = 211      => sys_getresgid(args[..3]);
+    SYS_CHOWN32 = 212          => sys_chown(args[..3]);
+    SYS_SETUID32 = 213         => sys_setuid(args[..1]);
+    SYS_SETGID32 = 214         => sys_setgid(args[..1]);
+    SYS_SETFSUID32 = 215       => sys_setfsuid(args[..1]);
+    SYS_SETFSGID32 = 216       => sys_setfsgid(args[..1]);
+    SYS_PIVOT_ROOT = 217       => sys_pivot_root(args[..2]);
+    SYS_MADVISE = 219          => sys_madvise(args[..3]);
+    SYS_GETDENTS64 = 220       => sys_getdents64(args[..3]);
+    SYS_FCNTL64 = 221          => compat_sys_fcntl64(args[..3]);
+    SYS_GETTID = 224           => sys_gettid(args[..0]);
+    SYS_SETXATTR = 226         => sys_setxattr(args[..5]);
+    SYS_LSETXATTR = 227        => sys_lsetxattr(args[..5]);
+    SYS_FSETXATTR = 228        => sys_fsetxattr(args[..5]);
+    SYS_GETXATTR = 229         => sys_getxattr(args[..4]);
+    SYS_LGETXATTR = 230        => sys_lgetxattr(args[..4]);
+    SYS_FGETXATTR = 231        => sys_fgetxattr(args[..4]);
+    SYS_LISTXATTR = 232        => sys_listxattr(args[..3]);
+    SYS_LLISTXATTR = 233       => sys_llistxattr(args[..3]);
+    SYS_FLISTXATTR = 234       => sys_flistxattr(args[..3]);
+    SYS_REMOVEXATTR = 235      => sys_removexattr(args[..2]);
+    SYS_LREMOVEXATTR = 236     => sys_lremovexattr(args[..2]);
+    SYS_FREMOVEXATTR = 237     => sys_fremovexattr(args[..2]);
+    SYS_TKILL = 238            => sys_tkill(args[..2]);
+    SYS_SENDFILE64 = 239       => sys_sendfile(args[..4]);
+    SYS_FUTEX = 240            => compat_sys_futex(args[..6]);
+    SYS_SCHED_SETAFFINITY = 241 => sys_sched_setaffinity(args[..3]);
+    SYS_SCHED_GETAFFINITY = 242 => sys_sched_getaffinity(args[..3]);
+    SYS_SET_THREAD_AREA = 243  => sys_set_thread_area(args[..1]);
+    SYS_GET_THREAD_AREA = 244  => sys_get_thread_area(args[..1]);
+    SYS_FADVISE64 = 250        => compat_sys_fadvise64(args[..5]);
+    SYS_EXIT_GROUP = 252       => sys_exit_group(args[..1], &mut user_ctx);
+    SYS_EPOLL_CREATE = 254     => sys_epoll_create(args[..1]);
+    SYS_EPOLL_CTL = 255        => sys_epoll_ctl(args[..4]);
+    SYS_EPOLL_WAIT = 256       => sys_epoll_wait(args[..4]);
+    SYS_SET_TID_ADDRESS = 258  => sys_set_tid_address(args[..1]);
+    SYS_CLOCK_GETTIME = 265    => compat_sys_clock_gettime(args[..2]);
+    SYS_CLOCK_NANOSLEEP = 267  => compat_sys_clock_nanosleep(args[..4]);
+    SYS_TGKILL = 270           => sys_tgkill(args[..3]);
+    SYS_FADVISE64_64 = 272     => compat_sys_fadvise64_64(args[..6]);
+    SYS_MQ_UNLINK = 278        => sys_mq_unlink(args[..1]);
+    SYS_IOPRIO_SET = 289       => sys_ioprio_set(args[..3]);
+    SYS_IOPRIO_GET = 290       => sys_ioprio_get(args[..2]);
+    SYS_INOTIFY_INIT = 291     => sys_inotify_init(args[..0]);
+    SYS_INOTIFY_ADD_WATCH = 292 => sys_inotify_add_watch(args[..3]);
+    SYS_INOTIFY_RM_WATCH = 293 => sys_inotify_rm_watch(args[..2]);
+    SYS_OPENAT = 295           => sys_openat(args[..4]);
+    SYS_MKDIRAT = 296          => sys_mkdirat(args[..3]);
+    SYS_MKNODAT = 297          => sys_mknodat(args[..4]);
+    SYS_FCHOWNAT = 298         => sys_fchownat(args[..5]);
+    SYS_FSTATAT64 = 300        => sys_fstatat64(args[..4]);
+    SYS_UNLINKAT = 301         => sys_unlinkat(args[..3]);
+    SYS_RENAMEAT = 302         => sys_renameat(args[..4]);
+    SYS_LINKAT = 303           => sys_linkat(args[..5]);
+    SYS_SYMLINKAT = 304        => sys_symlinkat(args[..3]);
+    SYS_READLINKAT = 305       => sys_readlinkat(args[..4]);
+    SYS_FCHMODAT = 306         => sys_fchmodat(args[..3]);
+    SYS_FACCESSAT = 307        => sys_faccessat(args[..3]);
+    SYS_UNSHARE = 310          => sys_unshare(args[..1]);
+    SYS_SPLICE = 313           => sys_splice(args[..6]);
+    SYS_TEE = 315              => sys_tee(args[..4]);
+    SYS_VMSPLICE = 316         => sys_vmsplice(args[..4]);
+    SYS_GETCPU = 318           => sys_getcpu(args[..3]);
+    SYS_EPOLL_PWAIT = 319      => sys_epoll_pwait(args[..6]);
+    SYS_SIGNALFD = 321         => sys_signalfd(args[..3]);
+    SYS_TIMERFD_CREATE = 322   => sys_timerfd_create(args[..2]);
+    SYS_EVENTFD = 323          => sys_eventfd(args[..1]);
+    SYS_FALLOCATE = 324        => compat_sys_fallocate(args[..6]);
+    SYS_SIGNALFD4 = 327        => sys_signalfd4(args[..4]);
+    SYS_EVENTFD2 = 328         => sys_eventfd2(args[..2]);
+    SYS_EPOLL_CREATE1 = 329    => sys_epoll_create1(args[..1]);
+    SYS_DUP3 = 330             => sys_dup3(args[..3]);
+    SYS_PIPE2 = 331            => sys_pipe2(args[..2]);
+    SYS_INOTIFY_INIT1 = 332    => sys_inotify_init1(args[..1]);
+    SYS_PREADV = 333           => compat_sys_preadv(args[..5]);
+    SYS_PWRITEV = 334          => compat_sys_pwritev(args[..5]);
+    SYS_FANOTIFY_INIT = 338    => sys_fanotify_init(args[..2]);
+    SYS_PRLIMIT64 = 340        => sys_prlimit64(args[..4]);
+    SYS_SYNCFS = 344           => sys_syncfs(args[..1]);
+    SYS_SETNS = 346            => sys_setns(args[..2]);
+    SYS_SCHED_SETATTR = 351    => sys_sched_setattr(args[..3]);
+    SYS_SCHED_GETATTR = 352    => sys_sched_getattr(args[..4]);
+    SYS_RENAMEAT2 = 353        => sys_renameat2(args[..5]);
+    SYS_GETRANDOM = 355        => sys_getrandom(args[..3]);
+    SYS_MEMFD_CREATE = 356     => sys_memfd_create(args[..2]);
+    SYS_EXECVEAT = 358         => sys_execveat(args[..5], &mut user_ctx);
+    SYS_SOCKET = 359           => sys_socket(args[..3]);
+    SYS_SOCKETPAIR = 360       => sys_socketpair(args[..4]);
+    SYS_BIND = 361             => sys_bind(args[..3]);
+    SYS_CONNECT = 362          => sys_connect(args[..3]);
+    SYS_LISTEN = 363           => sys_listen(args[..2]);
+    SYS_ACCEPT4 = 364          => sys_accept4(args[..4]);
+    SYS_GETSOCKOPT = 365       => sys_getsockopt(args[..5]);
+    SYS_SETSOCKOPT = 366       => sys_setsockopt(args[..5]);
+    SYS_GETSOCKNAME = 367      => sys_getsockname(args[..3]);
+    SYS_GETPEERNAME = 368      => sys_getpeername(args[..3]);
+    SYS_SENDTO = 369           => sys_sendto(args[..6]);
+    SYS_SENDMSG = 370          => compat_sys_sendmsg(args[..3]);
+    SYS_RECVFROM = 371         => sys_recvfrom(args[..6]);
+    SYS_RECVMSG = 372          => compat_sys_recvmsg(args[..3]);
+    SYS_SHUTDOWN = 373         => sys_shutdown(args[..2]);
+    SYS_USERFAULTFD = 374      => sys_userfaultfd(args[..1]);
+    SYS_COPY_FILE_RANGE = 377  => sys_copy_file_range(args[..6]);
+    SYS_STATX = 383            => sys_statx(args[..5]);
+    SYS_SEMGET = 393           => sys_semget(args[..3]);
+    SYS_SHMGET = 395           => sys_shmget(args[..3]);
+    SYS_SHMAT = 397            => sys_shmat(args[..3]);
+    SYS_SHMDT = 398            => sys_shmdt(args[..1]);
+    SYS_MSGGET = 399           => sys_msgget(args[..2]);
+    // The `*_time64` system calls use the same `timespec` layout as 64-bit programs.
+    SYS_CLOCK_GETTIME64 = 403  => sys_clock_gettime(args[..2]);
+    SYS_CLOCK_NANOSLEEP_TIME64 = 407 => sys_clock_nanosleep(args[..4]);
+    SYS_TIMERFD_GETTIME64 = 410 => sys_timerfd_gettime(args[..2]);
+    SYS_TIMERFD_SETTIME64 = 411 => sys_timerfd_settime(args[..4]);
+    SYS_UTIMENSAT_TIME64 = 412 => sys_utimensat(args[..4]);
+    SYS_PPOLL_TIME64 = 414     => sys_ppoll(args[..5]);
+    SYS_MQ_TIMEDSEND_TIME64 = 418 => sys_mq_timedsend(args[..5]);
+    SYS_MQ_TIMEDRECEIVE_TIME64 = 419 => sys_mq_timedreceive(args[..5]);
+    SYS_SEMTIMEDOP_TIME64 = 420 => sys_semtimedop(args[..4]);
+    SYS_FUTEX_TIME64 = 422     => sys_futex(args[..6]);
+    SYS_IO_URING_SETUP = 425   => sys_io_uring_setup(args[..2]);
+    SYS_IO_URING_ENTER = 426   => sys_io_uring_enter(args[..6]);
+    SYS_IO_URING_REGISTER = 427 => sys_io_uring_register(args[..4]);
+    SYS_PIDFD_OPEN = 434       => sys_pidfd_open(args[..2]);
+    SYS_CLONE3 = 435           => sys_clone3(args[..2], &mut user_ctx);
+    SYS_CLOSE_RANGE = 436      => sys_close_range(args[..3]);
+    SYS_PIDFD_GETFD = 438      => sys_pidfd_getfd(args[..3]);
+    SYS_FACCESSAT2 = 439       => sys_faccessat2(args[..4]);
+    SYS_EPOLL_PWAIT2 = 441     => sys_epoll_pwait2(args[..6]);
+    SYS_LANDLOCK_CREATE_RULESET = 444 => sys_landlock_create_ruleset(args[..3]);
+    SYS_LANDLOCK_ADD_RULE = 445 => sys_landlock_add_rule(args[..4]);
+    SYS_LANDLOCK_RESTRICT_SELF = 446 => sys_landlock_restrict_self(args[..2]);
+    SYS_FCHMODAT2 = 452        => sys_fchmodat2(args[..4]);
+}
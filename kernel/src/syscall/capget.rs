@@ -42,8 +42,10 @@ pub fn sys_capget(
     }
 
     let credentials = if cap_user_header.pid != 0 {
-        pid_table::pid_table_mut()
-            .get_thread(cap_user_header.pid)
+        ctx.process
+            .pid_ns()
+            .global_id_of(cap_user_header.pid)
+            .and_then(|tid| pid_table::pid_table_mut().get_thread(tid))
             .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target thread does not exist"))?
             .as_posix_thread()
             .unwrap()
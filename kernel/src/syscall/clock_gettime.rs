@@ -6,6 +6,8 @@ use int_to_c_enum::TryFromInt;
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::time::compat_timespec_t;
 use crate::{
     prelude::*,
     process::{pid_table, posix_thread::AsPosixThread},
@@ -34,6 +36,23 @@ pub fn sys_clock_gettime(
     Ok(SyscallReturn::Return(0))
 }
 
+/// The `clock_gettime` system call of 32-bit programs, which uses 32-bit `timespec`s.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_clock_gettime(
+    clockid: clockid_t,
+    timespec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("clockid = {:?}", clockid);
+
+    let time_duration = read_clock(clockid, ctx)?;
+
+    let timespec = compat_timespec_t::from(time_duration);
+    ctx.user_space().write_val(timespec_addr, &timespec)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
 // The hard-coded clock IDs.
 #[expect(non_camel_case_types)]
 #[repr(i32)]
@@ -108,26 +127,33 @@ pub enum DynamicClockType {
 
 /// Reads the time of a clock specified by the input clock ID.
 ///
+/// The time is adjusted by the offsets of the time namespace of the current thread.
+///
 /// If the clock ID does not support, this function will return `Err`.
 pub fn read_clock(clockid: clockid_t, ctx: &Context) -> Result<Duration> {
     if clockid >= 0 {
         let clock_id = ClockId::try_from(clockid)?;
-        match clock_id {
-            ClockId::CLOCK_REALTIME => Ok(RealTimeClock::get().read_time()),
-            ClockId::CLOCK_MONOTONIC => Ok(MonotonicClock::get().read_time()),
-            ClockId::CLOCK_MONOTONIC_RAW => Ok(MonotonicRawClock::get().read_time()),
-            ClockId::CLOCK_REALTIME_COARSE => Ok(RealTimeCoarseClock::get().read_time()),
-            ClockId::CLOCK_MONOTONIC_COARSE => Ok(MonotonicCoarseClock::get().read_time()),
-            ClockId::CLOCK_BOOTTIME => Ok(BootTimeClock::get().read_time()),
-            ClockId::CLOCK_PROCESS_CPUTIME_ID => Ok(ctx.process.prof_clock().read_time()),
-            ClockId::CLOCK_THREAD_CPUTIME_ID => Ok(ctx.posix_thread.prof_clock().read_time()),
-        }
+        let host_time = match clock_id {
+            ClockId::CLOCK_REALTIME => RealTimeClock::get().read_time(),
+            ClockId::CLOCK_MONOTONIC => MonotonicClock::get().read_time(),
+            ClockId::CLOCK_MONOTONIC_RAW => MonotonicRawClock::get().read_time(),
+            ClockId::CLOCK_REALTIME_COARSE => RealTimeCoarseClock::get().read_time(),
+            ClockId::CLOCK_MONOTONIC_COARSE => MonotonicCoarseClock::get().read_time(),
+            ClockId::CLOCK_BOOTTIME => BootTimeClock::get().read_time(),
+            ClockId::CLOCK_PROCESS_CPUTIME_ID => ctx.process.prof_clock().read_time(),
+            ClockId::CLOCK_THREAD_CPUTIME_ID => ctx.posix_thread.prof_clock().read_time(),
+        };
+        let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+        Ok(ns_proxy.unwrap().time_ns().to_ns_time(clock_id, host_time))
     } else {
         let dynamic_clockid_info = DynamicClockIdInfo::try_from(clockid)?;
         match dynamic_clockid_info {
             DynamicClockIdInfo::Pid(pid, clock_type) => {
-                let process = pid_table::pid_table_mut()
-                    .get_process(pid)
+                let process = ctx
+                    .process
+                    .pid_ns()
+                    .global_id_of(pid)
+                    .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
                     .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid clock ID"))?;
                 match clock_type {
                     DynamicClockType::Profiling => Ok(process.prof_clock().read_time()),
@@ -137,8 +163,11 @@ pub fn read_clock(clockid: clockid_t, ctx: &Context) -> Result<Duration> {
                 }
             }
             DynamicClockIdInfo::Tid(tid, clock_type) => {
-                let thread = pid_table::pid_table_mut()
-                    .get_thread(tid)
+                let thread = ctx
+                    .process
+                    .pid_ns()
+                    .global_id_of(tid)
+                    .and_then(|tid| pid_table::pid_table_mut().get_thread(tid))
                     .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid clock ID"))?;
                 let posix_thread = thread.as_posix_thread().unwrap();
                 match clock_type {
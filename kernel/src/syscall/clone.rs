@@ -20,7 +20,7 @@ pub fn sys_clone(
     child_tidptr: Vaddr,
     tls: u64,
     ctx: &Context,
-    parent_context: &UserContext,
+    parent_context: &mut UserContext,
 ) -> Result<SyscallReturn> {
     let args = CloneArgs::for_clone(clone_flags, parent_tidptr, child_tidptr, tls, new_sp)?;
     debug!("clone args = {:x?}", args);
@@ -33,7 +33,7 @@ pub fn sys_clone3(
     clong_args_addr: Vaddr,
     size: usize,
     ctx: &Context,
-    parent_context: &UserContext,
+    parent_context: &mut UserContext,
 ) -> Result<SyscallReturn> {
     debug!(
         "clone args addr = 0x{:x}, size = 0x{:x}",
@@ -154,3 +154,27 @@ impl TryFrom<Clone3Args> for CloneArgs {
         })
     }
 }
+
+/// The `clone` system call of 32-bit programs.
+///
+/// The TLS argument comes before the child TID pointer on i386.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_clone(
+    clone_flags: u64,
+    new_sp: u64,
+    parent_tidptr: Vaddr,
+    tls: u64,
+    child_tidptr: Vaddr,
+    ctx: &Context,
+    parent_context: &mut UserContext,
+) -> Result<SyscallReturn> {
+    sys_clone(
+        clone_flags,
+        new_sp,
+        parent_tidptr,
+        child_tidptr,
+        tls,
+        ctx,
+        parent_context,
+    )
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    SyscallReturn,
+    splice::{MAX_RW_COUNT, read_offset_from_user, write_offset_to_user},
+};
+use crate::{
+    fs::{
+        self,
+        file::{
+            FileLike, InodeHandle, InodeType, SeekFrom, StatusFlags,
+            file_table::{RawFileDesc, WithFileTable},
+        },
+    },
+    prelude::*,
+};
+
+pub fn sys_copy_file_range(
+    fd_in: RawFileDesc,
+    off_in_ptr: Vaddr,
+    fd_out: RawFileDesc,
+    off_out_ptr: Vaddr,
+    len: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd_in = {}, off_in_ptr = {:#x}, fd_out = {}, off_out_ptr = {:#x}, len = {:#x}, flags = {:#x}",
+        fd_in, off_in_ptr, fd_out, off_out_ptr, len, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    let (in_file, out_file) = ctx
+        .thread_local
+        .borrow_file_table_mut()
+        .read_with(|inner| {
+            let in_file = inner.get_file(fd_in.try_into()?)?.clone();
+            let out_file = inner.get_file(fd_out.try_into()?)?.clone();
+            Ok::<_, Error>((in_file, out_file))
+        })?;
+
+    let in_offset = read_offset_from_user(off_in_ptr, ctx)?;
+    let out_offset = read_offset_from_user(off_out_ptr, ctx)?;
+
+    if !in_file.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "fd_in is not readable");
+    }
+    if !out_file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "fd_out is not writable");
+    }
+    if out_file.status_flags().contains(StatusFlags::O_APPEND) {
+        return_errno_with_message!(Errno::EBADF, "fd_out is opened with O_APPEND");
+    }
+
+    let (Some(in_handle), Some(out_handle)) = (
+        in_file.downcast_ref::<InodeHandle>(),
+        out_file.downcast_ref::<InodeHandle>(),
+    ) else {
+        return_errno_with_message!(Errno::EINVAL, "the files are not regular files");
+    };
+    let in_inode = in_handle.path().inode();
+    let out_inode = out_handle.path().inode();
+    for inode in [in_inode, out_inode] {
+        match inode.type_() {
+            InodeType::File => (),
+            InodeType::Dir => return_errno_with_message!(Errno::EISDIR, "the file is a directory"),
+            _ => return_errno_with_message!(Errno::EINVAL, "the file is not a regular file"),
+        }
+    }
+
+    let in_pos = in_offset.unwrap_or_else(|| in_handle.offset());
+    let out_pos = out_offset.unwrap_or_else(|| out_handle.offset());
+    if in_pos.checked_add(len).is_none() || out_pos.checked_add(len).is_none() {
+        return_errno_with_message!(Errno::EOVERFLOW, "the range is too large");
+    }
+
+    // Shorten the copy to the end of the source file.
+    let in_size = in_inode.size();
+    let len = if in_pos >= in_size {
+        0
+    } else {
+        len.min(in_size - in_pos).min(MAX_RW_COUNT)
+    };
+
+    if Arc::ptr_eq(in_inode, out_inode) && out_pos + len > in_pos && out_pos < in_pos + len {
+        return_errno_with_message!(Errno::EINVAL, "the ranges overlap in the same file");
+    }
+
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    fs::vfs::notify::check_access_perm(&in_file).map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
+
+    // Let the file system copy the data without reading it out if it can.
+    let fast_res = if Arc::ptr_eq(&in_inode.fs(), &out_inode.fs()) {
+        in_inode.copy_range_to(in_pos, out_inode.as_ref(), out_pos, len)
+    } else {
+        Err(Error::new(Errno::EXDEV))
+    };
+    let copied_len = match fast_res {
+        Ok(copied_len) => copied_len,
+        Err(err) if matches!(err.error(), Errno::EOPNOTSUPP | Errno::EXDEV) => {
+            copy_through_kernel(in_file.as_ref(), in_pos, out_file.as_ref(), out_pos, len)?
+        }
+        Err(err) => return Err(err),
+    };
+
+    if in_offset.is_some() {
+        write_offset_to_user(off_in_ptr, Some(in_pos + copied_len), ctx)?;
+    } else {
+        in_file.seek(SeekFrom::Start(in_pos + copied_len))?;
+    }
+    if out_offset.is_some() {
+        write_offset_to_user(off_out_ptr, Some(out_pos + copied_len), ctx)?;
+    } else {
+        out_file.seek(SeekFrom::Start(out_pos + copied_len))?;
+    }
+
+    if copied_len > 0 {
+        fs::vfs::notify::on_access(&in_file);
+        fs::vfs::notify::on_modify(&out_file);
+    }
+
+    Ok(SyscallReturn::Return(copied_len as _))
+}
+
+/// Copies the data through a kernel buffer, which works for all regular files.
+fn copy_through_kernel(
+    in_file: &dyn FileLike,
+    in_pos: usize,
+    out_file: &dyn FileLike,
+    out_pos: usize,
+    len: usize,
+) -> Result<usize> {
+    let mut buffer = vec![0u8; PAGE_SIZE.min(len)].into_boxed_slice();
+    let mut copied_len = 0;
+
+    while copied_len < len {
+        let max_read_len = buffer.len().min(len - copied_len);
+        let read_res = in_file.read_bytes_at(in_pos + copied_len, &mut buffer[..max_read_len]);
+        let read_len = match read_res {
+            Ok(0) => break,
+            Ok(read_len) => read_len,
+            Err(_) if copied_len > 0 => break,
+            Err(err) => return Err(err),
+        };
+
+        let write_res = out_file.write_bytes_at(out_pos + copied_len, &buffer[..read_len]);
+        let written_len = match write_res {
+            Ok(written_len) => written_len,
+            Err(_) if copied_len > 0 => break,
+            Err(err) => return Err(err),
+        };
+
+        copied_len += written_len;
+        if written_len < read_len {
+            break;
+        }
+    }
+
+    Ok(copied_len)
+}
@@ -67,7 +67,7 @@ bitflags! {
     }
 }
 
-struct EventFile {
+pub struct EventFile {
     counter: Mutex<u64>,
     pollee: Pollee,
     flags: Mutex<Flags>,
@@ -157,6 +157,14 @@ impl EventFile {
 
         return_errno_with_message!(Errno::EAGAIN, "the new value exceeds MAX_COUNTER_VALUE");
     }
+
+    /// Signals the eventfd by adding one to the counter.
+    ///
+    /// This is used by kernel-side notifiers (e.g., io_uring). If the counter cannot be
+    /// increased without exceeding `MAX_COUNTER_VALUE`, the signal is discarded.
+    pub fn signal(&self) {
+        let _ = self.add_counter_val(1);
+    }
 }
 
 impl Pollable for EventFile {
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs::file::file_table::{RawFileDesc, get_file_fast},
     prelude::*,
@@ -58,3 +60,34 @@ pub fn sys_fadvise64(
 
     Ok(SyscallReturn::Return(0))
 }
+
+/// The `fadvise64` system call of 32-bit programs, which splits the 64-bit offset into two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_fadvise64(
+    raw_fd: RawFileDesc,
+    offset_low: u32,
+    offset_high: u32,
+    len: u32,
+    advice: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high);
+    sys_fadvise64(raw_fd, offset as usize, len as usize, advice, ctx)
+}
+
+/// The `fadvise64_64` system call of 32-bit programs, which splits the 64-bit offset and length
+/// into two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_fadvise64_64(
+    raw_fd: RawFileDesc,
+    offset_low: u32,
+    offset_high: u32,
+    len_low: u32,
+    len_high: u32,
+    advice: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high);
+    let len = compat_arg_u64(len_low, len_high);
+    sys_fadvise64(raw_fd, offset as usize, len as usize, advice, ctx)
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs,
     fs::{
@@ -38,6 +40,23 @@ pub fn sys_fallocate(
     Ok(SyscallReturn::Return(0))
 }
 
+/// The `fallocate` system call of 32-bit programs, which splits the 64-bit offset and length into
+/// two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_fallocate(
+    raw_fd: RawFileDesc,
+    mode: u64,
+    offset_low: u32,
+    offset_high: u32,
+    len_low: u32,
+    len_high: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high) as i64;
+    let len = compat_arg_u64(len_low, len_high) as i64;
+    sys_fallocate(raw_fd, mode, offset, len, ctx)
+}
+
 fn check_offset_and_len(offset: i64, len: i64, ctx: &Context) -> Result<()> {
     if offset < 0 || len <= 0 {
         return_errno_with_message!(
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::{
+            InodeType, Permission,
+            file_table::{FdFlags, RawFileDesc, get_file_fast},
+        },
+        vfs::{
+            notify::{
+                FsEvents,
+                fanotify::{FanotifyFile, FanotifyInitFlags, FanotifyMarkType},
+            },
+            path::{EmptyPathStr, FsPath},
+        },
+    },
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet},
+    security::lsm::hooks as lsm_hooks,
+    syscall::constants::MAX_FILENAME_LEN,
+};
+
+pub fn sys_fanotify_init(flags: u32, event_f_flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = FanotifyInitFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("flags = {:?}, event_f_flags = {:#x}", flags, event_f_flags);
+
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        UserNamespace::get_init_singleton().as_ref(),
+        ctx.posix_thread,
+        CapSet::SYS_ADMIN,
+    ))?;
+
+    let fd_flags = if flags.contains(FanotifyInitFlags::CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let file = FanotifyFile::new(flags, event_f_flags)?;
+
+    let file_table = ctx.thread_local.borrow_file_table();
+    let fd = file_table.unwrap().write().insert(Arc::new(file), fd_flags);
+    Ok(SyscallReturn::Return(fd.into()))
+}
+
+pub fn sys_fanotify_mark(
+    fanotify_fd: RawFileDesc,
+    flags: u32,
+    mask: u64,
+    dirfd: RawFileDesc,
+    path_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = FanotifyMarkFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!(
+        "fanotify_fd = {}, flags = {:?}, mask = {:#x}, dirfd = {}, path_addr = {:#x}",
+        fanotify_fd, flags, mask, dirfd, path_addr
+    );
+
+    let mark_type = match (
+        flags.contains(FanotifyMarkFlags::MOUNT),
+        flags.contains(FanotifyMarkFlags::FILESYSTEM),
+    ) {
+        (false, false) => FanotifyMarkType::Inode,
+        (true, false) => FanotifyMarkType::Mount,
+        (false, true) => FanotifyMarkType::Filesystem,
+        (true, true) => {
+            return_errno_with_message!(Errno::EINVAL, "multiple mark types are given")
+        }
+    };
+
+    let action =
+        flags & (FanotifyMarkFlags::ADD | FanotifyMarkFlags::REMOVE | FanotifyMarkFlags::FLUSH);
+    if action.bits().count_ones() != 1 {
+        return_errno_with_message!(Errno::EINVAL, "exactly one action must be given");
+    }
+
+    let mask = u32::try_from(mask)
+        .ok()
+        .and_then(FsEvents::from_bits)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid mask"))?;
+
+    if action == FanotifyMarkFlags::FLUSH {
+        if !(FanotifyMarkFlags::FLUSH | FanotifyMarkFlags::MOUNT | FanotifyMarkFlags::FILESYSTEM)
+            .contains(flags)
+        {
+            return_errno_with_message!(Errno::EINVAL, "invalid flags for flushing marks");
+        }
+        with_fanotify_file(fanotify_fd, ctx, |fanotify_file| {
+            fanotify_file.flush_marks(mark_type);
+            Ok(())
+        })?;
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    if action == FanotifyMarkFlags::ADD && mask.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "no events are given");
+    }
+
+    // Unlike other system calls, `fanotify_mark` marks the object referred to by `dirfd` if
+    // the pathname is NULL.
+    // Reference: <https://man7.org/linux/man-pages/man2/fanotify_mark.2.html>
+    let path = {
+        let path_name = if path_addr == 0 {
+            None
+        } else {
+            let path_name = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+            Some(path_name.to_string_lossy().into_owned())
+        };
+        let fs_path = match path_name.as_ref() {
+            Some(path_name) => FsPath::from_fd_at(dirfd, path_name, EmptyPathStr::Reject)?,
+            None => FsPath::from_fd(dirfd)?,
+        };
+
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let path_resolver = fs_ref.resolver().read();
+        if flags.contains(FanotifyMarkFlags::DONT_FOLLOW) {
+            path_resolver.lookup_no_follow(&fs_path)?
+        } else {
+            path_resolver.lookup(&fs_path)?
+        }
+    };
+
+    // Verify that the caller has read permissions on the inode.
+    let inode = path.inode();
+    inode.check_permission(Permission::MAY_READ)?;
+
+    if flags.contains(FanotifyMarkFlags::ONLYDIR) && inode.type_() != InodeType::Dir {
+        return_errno_with_message!(Errno::ENOTDIR, "the path is not a directory");
+    }
+
+    with_fanotify_file(fanotify_fd, ctx, |fanotify_file| {
+        if action == FanotifyMarkFlags::ADD {
+            fanotify_file.add_mark(&path, mark_type, mask)
+        } else {
+            fanotify_file.remove_mark(&path, mark_type, mask)
+        }
+    })?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Calls `f` with the fanotify file referred to by `fanotify_fd`.
+///
+/// The file table is borrowed only during the call, because resolving a path relative to
+/// `dirfd` borrows the file table as well.
+fn with_fanotify_file<F>(fanotify_fd: RawFileDesc, ctx: &Context, f: F) -> Result<()>
+where
+    F: FnOnce(&FanotifyFile) -> Result<()>,
+{
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fanotify_fd.try_into()?);
+    let Some(fanotify_file) = file.downcast_ref::<FanotifyFile>() else {
+        return_errno_with_message!(Errno::EINVAL, "the file is not a fanotify file");
+    };
+
+    f(fanotify_file)
+}
+
+bitflags! {
+    struct FanotifyMarkFlags: u32 {
+        const ADD         = 0x0000_0001;
+        const REMOVE      = 0x0000_0002;
+        const DONT_FOLLOW = 0x0000_0004;
+        const ONLYDIR     = 0x0000_0008;
+        const MOUNT       = 0x0000_0010;
+        const FLUSH       = 0x0000_0080;
+        const FILESYSTEM  = 0x0000_0100;
+    }
+}
@@ -27,12 +27,9 @@ pub fn sys_fcntl(raw_fd: RawFileDesc, cmd: i32, arg: u64, ctx: &Context) -> Resu
         FcntlCmd::F_SETFD => handle_setfd(fd, arg, ctx),
         FcntlCmd::F_GETFL => handle_getfl(fd, ctx),
         FcntlCmd::F_SETFL => handle_setfl(fd, arg, ctx),
-        FcntlCmd::F_GETLK => handle_getlk(fd, arg, ctx),
-        FcntlCmd::F_SETLK => handle_setlk(fd, arg, true, ctx),
-        FcntlCmd::F_SETLKW => handle_setlk(fd, arg, false, ctx).map_err(|err| match err.error() {
-            Errno::EINTR => Error::new(Errno::ERESTARTSYS),
-            _ => err,
-        }),
+        FcntlCmd::F_GETLK => handle_getlk::<c_flock>(fd, arg, ctx),
+        FcntlCmd::F_SETLK => handle_setlk::<c_flock>(fd, arg, true, ctx),
+        FcntlCmd::F_SETLKW => handle_setlkw::<c_flock>(fd, arg, ctx),
         FcntlCmd::F_GETOWN => handle_getown(fd, ctx),
         FcntlCmd::F_SETOWN => handle_setown(fd, arg, ctx),
         FcntlCmd::F_ADD_SEALS => handle_addseal(fd, arg, ctx),
@@ -40,6 +37,35 @@ pub fn sys_fcntl(raw_fd: RawFileDesc, cmd: i32, arg: u64, ctx: &Context) -> Resu
     }
 }
 
+/// The `fcntl` and `fcntl64` system calls of 32-bit programs, which use the 32-bit `flock` layouts.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_fcntl64(
+    raw_fd: RawFileDesc,
+    cmd: i32,
+    arg: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    // The lock commands, which use `struct compat_flock` or `struct compat_flock64`.
+    const F_GETLK: i32 = FcntlCmd::F_GETLK as i32;
+    const F_SETLK: i32 = FcntlCmd::F_SETLK as i32;
+    const F_SETLKW: i32 = FcntlCmd::F_SETLKW as i32;
+    const F_GETLK64: i32 = 12;
+    const F_SETLK64: i32 = 13;
+    const F_SETLKW64: i32 = 14;
+
+    let fd = FileDesc::try_from(raw_fd)?;
+    let arg = arg as u64;
+    match cmd {
+        F_GETLK => handle_getlk::<compat_flock>(fd, arg, ctx),
+        F_SETLK => handle_setlk::<compat_flock>(fd, arg, true, ctx),
+        F_SETLKW => handle_setlkw::<compat_flock>(fd, arg, ctx),
+        F_GETLK64 => handle_getlk::<compat_flock64>(fd, arg, ctx),
+        F_SETLK64 => handle_setlk::<compat_flock64>(fd, arg, true, ctx),
+        F_SETLKW64 => handle_setlkw::<compat_flock64>(fd, arg, ctx),
+        _ => sys_fcntl(raw_fd, cmd, arg, ctx),
+    }
+}
+
 fn handle_dupfd(fd: FileDesc, arg: u64, flags: FdFlags, ctx: &Context) -> Result<SyscallReturn> {
     let file_table = ctx.thread_local.borrow_file_table();
     let ceil_fd = (arg as RawFileDesc)
@@ -95,11 +121,18 @@ fn handle_setfl(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     Ok(SyscallReturn::Return(0))
 }
 
-fn handle_getlk(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
+fn handle_getlk<F: Pod + Into<c_flock> + TryFrom<c_flock>>(
+    fd: FileDesc,
+    arg: u64,
+    ctx: &Context,
+) -> Result<SyscallReturn>
+where
+    Error: From<F::Error>,
+{
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, fd);
     let lock_mut_ptr = arg as Vaddr;
-    let mut lock_mut_c = ctx.user_space().read_val::<c_flock>(lock_mut_ptr)?;
+    let mut lock_mut_c: c_flock = ctx.user_space().read_val::<F>(lock_mut_ptr)?.into();
     let lock_type = RangeLockType::try_from(lock_mut_c.l_type)?;
     if lock_type == RangeLockType::Unlock {
         return_errno_with_message!(Errno::EINVAL, "invalid flock type for getlk");
@@ -108,11 +141,12 @@ fn handle_getlk(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     let inode_file = file.as_inode_handle_or_err()?;
     lock = inode_file.test_range_lock(lock)?;
     lock_mut_c.copy_from_range_lock(&lock);
-    ctx.user_space().write_val(lock_mut_ptr, &lock_mut_c)?;
+    ctx.user_space()
+        .write_val(lock_mut_ptr, &F::try_from(lock_mut_c)?)?;
     Ok(SyscallReturn::Return(0))
 }
 
-fn handle_setlk(
+fn handle_setlk<F: Pod + Into<c_flock>>(
     fd: FileDesc,
     arg: u64,
     is_nonblocking: bool,
@@ -121,7 +155,7 @@ fn handle_setlk(
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, fd);
     let lock_mut_ptr = arg as Vaddr;
-    let lock_mut_c = ctx.user_space().read_val::<c_flock>(lock_mut_ptr)?;
+    let lock_mut_c: c_flock = ctx.user_space().read_val::<F>(lock_mut_ptr)?.into();
     let lock_type = RangeLockType::try_from(lock_mut_c.l_type)?;
     let lock = RangeLockItem::new(lock_type, from_c_flock_and_file(&lock_mut_c, &**file)?);
     let inode_file = file.as_inode_handle_or_err()?;
@@ -129,6 +163,17 @@ fn handle_setlk(
     Ok(SyscallReturn::Return(0))
 }
 
+fn handle_setlkw<F: Pod + Into<c_flock>>(
+    fd: FileDesc,
+    arg: u64,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    handle_setlk::<F>(fd, arg, false, ctx).map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })
+}
+
 fn handle_getown(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     file_table.read_with(|inner| {
@@ -150,8 +195,10 @@ fn handle_setown(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
         None
     } else {
         Some(
-            pid_table::pid_table_mut()
-                .get_process(pid)
+            ctx.process
+                .pid_ns()
+                .global_id_of(pid)
+                .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
                 .ok_or(Error::with_message(
                     Errno::ESRCH,
                     "cannot set_owner with an invalid pid",
@@ -251,6 +298,94 @@ impl c_flock {
     }
 }
 
+/// The 32-bit version of [`c_flock`] with 32-bit offsets (`struct compat_flock`).
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct compat_flock {
+    pub l_type: u16,
+    pub l_whence: u16,
+    pub l_start: i32,
+    pub l_len: i32,
+    pub l_pid: Pid,
+}
+
+/// The 32-bit version of [`c_flock`] with 64-bit offsets (`struct compat_flock64`).
+#[cfg(target_arch = "x86_64")]
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct compat_flock64 {
+    pub l_type: u16,
+    pub l_whence: u16,
+    pub l_start: off_t,
+    pub l_len: off_t,
+    pub l_pid: Pid,
+}
+
+/// Converts a 32-bit version of [`c_flock`] to [`c_flock`].
+#[cfg(target_arch = "x86_64")]
+macro_rules! impl_from_compat_flock {
+    ($compat_flock:ty) => {
+        impl From<$compat_flock> for c_flock {
+            fn from(lock: $compat_flock) -> Self {
+                let mut c_lock = c_flock::new_zeroed();
+                c_lock.l_type = lock.l_type;
+                c_lock.l_whence = lock.l_whence;
+                c_lock.l_start = lock.l_start as off_t;
+                c_lock.l_len = lock.l_len as off_t;
+                c_lock.l_pid = lock.l_pid;
+                c_lock
+            }
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+impl_from_compat_flock!(compat_flock);
+#[cfg(target_arch = "x86_64")]
+impl_from_compat_flock!(compat_flock64);
+
+/// Converts the result of `F_GETLK` to the 32-bit offsets.
+///
+/// Like Linux, the conversion fails with `EOVERFLOW` if the start offset does not fit, and the
+/// length is limited to the maximum offset otherwise.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/fcntl.c>
+#[cfg(target_arch = "x86_64")]
+impl TryFrom<c_flock> for compat_flock {
+    type Error = Error;
+
+    fn try_from(lock: c_flock) -> Result<Self> {
+        let Ok(l_start) = i32::try_from(lock.l_start) else {
+            return_errno_with_message!(
+                Errno::EOVERFLOW,
+                "the start offset does not fit in the 32-bit flock"
+            );
+        };
+
+        Ok(Self {
+            l_type: lock.l_type,
+            l_whence: lock.l_whence,
+            l_start,
+            l_len: lock.l_len.min(i32::MAX as off_t) as i32,
+            l_pid: lock.l_pid,
+        })
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<c_flock> for compat_flock64 {
+    fn from(lock: c_flock) -> Self {
+        Self {
+            l_type: lock.l_type,
+            l_whence: lock.l_whence,
+            l_start: lock.l_start,
+            l_len: lock.l_len,
+            l_pid: lock.l_pid,
+        }
+    }
+}
+
 /// Create the file range through C flock and opened file reference
 fn from_c_flock_and_file(lock: &c_flock, file: &dyn FileLike) -> Result<FileRange> {
     let start = {
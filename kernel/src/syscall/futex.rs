@@ -4,6 +4,8 @@ use core::time::Duration;
 
 use ostd::mm::VmIo;
 
+#[cfg(target_arch = "x86_64")]
+use crate::time::compat_timespec_t;
 use crate::{
     context::current_userspace,
     prelude::*,
@@ -28,6 +30,48 @@ pub fn sys_futex(
     futex_new_addr: Vaddr,
     bitset: u32,
     ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_futex::<timespec_t>(
+        futex_addr,
+        futex_op,
+        futex_val,
+        utime_addr,
+        futex_new_addr,
+        bitset,
+        ctx,
+    )
+}
+
+/// The `futex` system call of 32-bit programs, which uses 32-bit `timespec`s.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_futex(
+    futex_addr: Vaddr,
+    futex_op: i32,
+    futex_val: u32,
+    utime_addr: Vaddr,
+    futex_new_addr: Vaddr,
+    bitset: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_futex::<compat_timespec_t>(
+        futex_addr,
+        futex_op,
+        futex_val,
+        utime_addr,
+        futex_new_addr,
+        bitset,
+        ctx,
+    )
+}
+
+fn do_futex<T: Pod + Into<timespec_t>>(
+    futex_addr: Vaddr,
+    futex_op: i32,
+    futex_val: u32,
+    utime_addr: Vaddr,
+    futex_new_addr: Vaddr,
+    bitset: u32,
+    ctx: &Context,
 ) -> Result<SyscallReturn> {
     let (futex_op, futex_flags) = futex_op_and_flags_from_u32(futex_op as _)?;
     debug!(
@@ -41,7 +85,7 @@ pub fn sys_futex(
         }
 
         let timeout = {
-            let time_spec: timespec_t = current_userspace!().read_val(timeout_addr)?;
+            let time_spec: timespec_t = current_userspace!().read_val::<T>(timeout_addr)?.into();
             Duration::try_from(time_spec)?
         };
 
@@ -29,7 +29,10 @@ impl IoPrioWho {
                 let target_tid = if who == 0 {
                     ctx.posix_thread.tid()
                 } else {
-                    who
+                    ctx.process
+                        .pid_ns()
+                        .global_id_of(who)
+                        .ok_or_else(|| Error::new(Errno::ESRCH))?
                 };
 
                 let thread = crate::process::pid_table::pid_table_mut()
@@ -85,7 +85,10 @@ impl PriorityTarget {
                 let pid = if who == 0 {
                     ctx.process.pid()
                 } else {
-                    who as Pid
+                    ctx.process
+                        .pid_ns()
+                        .global_id_of(who as Pid)
+                        .ok_or(Error::new(Errno::ESRCH))?
                 };
                 Self::Process(pid)
             }
@@ -93,7 +96,10 @@ impl PriorityTarget {
                 let pgid = if who == 0 {
                     ctx.process.pgid()
                 } else {
-                    who as Pgid
+                    ctx.process
+                        .pid_ns()
+                        .global_id_of(who as Pgid)
+                        .ok_or(Error::new(Errno::ESRCH))?
                 };
                 Self::ProcessGroup(pgid)
             }
@@ -14,12 +14,17 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
 
     // "If `pid` is equal to 0, getpgid() shall return the process group ID of the calling
     // process."
+    let pid_ns = ctx.process.pid_ns();
+
+    // IDs that are invisible in the PID namespace of the calling process are reported as zero.
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.pgid() as _));
+        let pgid = pid_ns.local_id_of(ctx.process.pgid()).unwrap_or(0);
+        return Ok(SyscallReturn::Return(pgid as _));
     }
 
-    let process = pid_table::pid_table_mut()
-        .get_process(pid)
+    let process = pid_ns
+        .global_id_of(pid)
+        .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
         .ok_or(Error::with_message(
             Errno::ESRCH,
             "the process to get the PGID does not exist",
@@ -29,5 +34,6 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // session than the current process. Linux does not perform this check by default, but some
     // strict security policies (e.g. SELinux) may do so.
 
-    Ok(SyscallReturn::Return(process.pgid() as _))
+    let pgid = pid_ns.local_id_of(process.pgid()).unwrap_or(0);
+    Ok(SyscallReturn::Return(pgid as _))
 }
@@ -4,5 +4,10 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getpgrp(ctx: &Context) -> Result<SyscallReturn> {
-    Ok(SyscallReturn::Return(ctx.process.pgid() as _))
+    let pgid = ctx
+        .process
+        .pid_ns()
+        .local_id_of(ctx.process.pgid())
+        .unwrap_or(0);
+    Ok(SyscallReturn::Return(pgid as _))
 }
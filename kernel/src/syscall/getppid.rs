@@ -4,5 +4,12 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getppid(ctx: &Context) -> Result<SyscallReturn> {
-    Ok(SyscallReturn::Return(ctx.process.parent().pid() as _))
+    // The parent process is invisible if the current process is the init process of a PID
+    // namespace, in which case the parent process ID is zero.
+    let ppid = ctx
+        .process
+        .pid_ns()
+        .local_id_of(ctx.process.parent().pid())
+        .unwrap_or(0);
+    Ok(SyscallReturn::Return(ppid as _))
 }
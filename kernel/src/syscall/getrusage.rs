@@ -4,6 +4,8 @@ use int_to_c_enum::TryFromInt;
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::time::compat_timeval_t;
 use crate::{prelude::*, time::timeval_t};
 
 #[repr(i32)]
@@ -50,12 +52,29 @@ pub fn sys_getrusage(target: i32, rusage_addr: Vaddr, ctx: &Context) -> Result<S
             }
         };
 
-        ctx.user_space().write_val(rusage_addr, &rusage)?;
+        write_rusage_to_user(&rusage, rusage_addr, ctx)?;
     }
 
     Ok(SyscallReturn::Return(0))
 }
 
+/// Writes the resource usage to the user space in the layout expected by the current system call.
+pub(super) fn write_rusage_to_user(
+    rusage: &rusage_t,
+    rusage_addr: Vaddr,
+    ctx: &Context,
+) -> Result<()> {
+    #[cfg(target_arch = "x86_64")]
+    if ctx.thread_local.in_compat_syscall() {
+        ctx.user_space()
+            .write_val(rusage_addr, &compat_rusage_t::from(rusage))?;
+        return Ok(());
+    }
+
+    ctx.user_space().write_val(rusage_addr, rusage)?;
+    Ok(())
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, Pod)]
 pub struct rusage_t {
@@ -92,3 +111,43 @@ pub struct rusage_t {
     /// involuntary
     pub ru_nivcsw: u64,
 }
+
+/// The 32-bit version of [`rusage_t`].
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct compat_rusage_t {
+    ru_utime: compat_timeval_t,
+    ru_stime: compat_timeval_t,
+    /// The remaining fields, whose meanings are the same as those in [`rusage_t`].
+    others: [i32; 14],
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<&rusage_t> for compat_rusage_t {
+    fn from(rusage: &rusage_t) -> Self {
+        let others = [
+            rusage.ru_maxrss,
+            rusage.ru_ixrss,
+            rusage.ru_idrss,
+            rusage.ru_isrss,
+            rusage.ru_minflt,
+            rusage.ru_majflt,
+            rusage.ru_nswap,
+            rusage.ru_inblock,
+            rusage.ru_oublock,
+            rusage.ru_msgsnd,
+            rusage.ru_msgrcv,
+            rusage.ru_nsignals,
+            rusage.ru_nvcsw,
+            rusage.ru_nivcsw,
+        ]
+        .map(|val| val as i32);
+
+        Self {
+            ru_utime: rusage.ru_utime.into(),
+            ru_stime: rusage.ru_stime.into(),
+            others,
+        }
+    }
+}
@@ -13,12 +13,17 @@ pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // <https://www.man7.org/linux/man-pages/man2/getsid.2.html>.
 
     // "If `pid` is 0, getsid() returns the session ID of the calling process."
+    let pid_ns = ctx.process.pid_ns();
+
+    // IDs that are invisible in the PID namespace of the calling process are reported as zero.
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.sid() as _));
+        let sid = pid_ns.local_id_of(ctx.process.sid()).unwrap_or(0);
+        return Ok(SyscallReturn::Return(sid as _));
     }
 
-    let process = pid_table::pid_table_mut()
-        .get_process(pid)
+    let process = pid_ns
+        .global_id_of(pid)
+        .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
         .ok_or(Error::with_message(
             Errno::ESRCH,
             "the process to get the SID does not exist",
@@ -28,5 +33,6 @@ pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // session than the current process. Linux does not perform this check by default, but some
     // strict security policies (e.g. SELinux) may do so.
 
-    Ok(SyscallReturn::Return(process.sid() as _))
+    let sid = pid_ns.local_id_of(process.sid()).unwrap_or(0);
+    Ok(SyscallReturn::Return(sid as _))
 }
@@ -3,6 +3,8 @@
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::time::compat_timeval_t;
 use crate::{
     prelude::*,
     time::{SystemTime, timeval_t, timezone_t},
@@ -14,19 +16,43 @@ pub fn sys_gettimeofday(
     ctx: &Context,
 ) -> Result<SyscallReturn> {
     if timeval_addr != 0 {
-        let time_val = {
-            let now = SystemTime::now();
-            let time_duration = now.duration_since(&SystemTime::UNIX_EPOCH)?;
-            timeval_t::from(time_duration)
-        };
+        let time_val = now_as_timeval()?;
         ctx.user_space().write_val(timeval_addr, &time_val)?;
     }
 
+    write_timezone(timezone_addr, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_gettimeofday(
+    timeval_addr: Vaddr,
+    timezone_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    if timeval_addr != 0 {
+        let time_val = compat_timeval_t::from(now_as_timeval()?);
+        ctx.user_space().write_val(timeval_addr, &time_val)?;
+    }
+
+    write_timezone(timezone_addr, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn now_as_timeval() -> Result<timeval_t> {
+    let now = SystemTime::now();
+    let time_duration = now.duration_since(&SystemTime::UNIX_EPOCH)?;
+    Ok(timeval_t::from(time_duration))
+}
+
+fn write_timezone(timezone_addr: Vaddr, ctx: &Context) -> Result<()> {
     if timezone_addr != 0 {
         // TODO: Return the actual system timezone when available.
         let tz = timezone_t::default();
         ctx.user_space().write_val(timezone_addr, &tz)?;
     }
 
-    Ok(SyscallReturn::Return(0))
+    Ok(())
 }
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file::file_table::{FileDesc, get_file_fast},
+    io_uring::{IoUringEnterFlags, IoUringFile},
+    prelude::*,
+    process::{posix_thread::ContextPthreadAdminApi, signal::sig_mask::SigMask},
+};
+
+pub fn sys_io_uring_enter(
+    fd: FileDesc,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    sigmask_addr: Vaddr,
+    sigmask_size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = IoUringEnterFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid enter flags"))?;
+    debug!(
+        "fd = {}, to_submit = {}, min_complete = {}, flags = {:?}",
+        fd, to_submit, min_complete, flags
+    );
+
+    if flags.contains(IoUringEnterFlags::IORING_ENTER_EXT_ARG) {
+        return_errno_with_message!(Errno::EINVAL, "extended arguments are not supported");
+    }
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd).into_owned();
+    drop(file_table);
+    let Some(io_uring_file) = file.downcast_ref::<IoUringFile>() else {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the file is not an io_uring file");
+    };
+
+    // `IORING_ENTER_SQ_WAKEUP` and `IORING_ENTER_SQ_WAIT` only make sense with an SQ polling
+    // thread, which is not supported. So they are simply ignored.
+
+    let nr_submitted = if to_submit > 0 {
+        io_uring_file.submit(to_submit, ctx)?
+    } else {
+        0
+    };
+
+    if flags.contains(IoUringEnterFlags::IORING_ENTER_GETEVENTS) && min_complete > 0 {
+        if sigmask_addr != 0 {
+            if sigmask_size != size_of::<SigMask>() {
+                return_errno_with_message!(Errno::EINVAL, "invalid sigmask size");
+            }
+
+            let sigmask = ctx.user_space().read_val::<SigMask>(sigmask_addr)?;
+            ctx.save_and_set_sig_mask(sigmask);
+        }
+
+        // Like Linux, errors that occur while waiting are not reported if some SQEs have
+        // been submitted.
+        if let Err(err) = io_uring_file.wait_cqes(min_complete)
+            && nr_submitted == 0
+        {
+            return Err(err);
+        }
+    }
+
+    Ok(SyscallReturn::Return(nr_submitted as _))
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file::file_table::{FileDesc, get_file_fast},
+    io_uring::{IORING_MAX_FIXED_FILES, IoUringFile, IoUringRegisterOp},
+    prelude::*,
+};
+
+pub fn sys_io_uring_register(
+    fd: FileDesc,
+    opcode: u32,
+    arg: Vaddr,
+    nr_args: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let opcode = IoUringRegisterOp::try_from(opcode)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "unsupported register opcode"))?;
+    debug!(
+        "fd = {}, opcode = {:?}, arg = 0x{:x}, nr_args = {}",
+        fd, opcode, arg, nr_args
+    );
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd).into_owned();
+    let Some(io_uring_file) = file.downcast_ref::<IoUringFile>() else {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the file is not an io_uring file");
+    };
+
+    match opcode {
+        IoUringRegisterOp::RegisterFiles => {
+            if nr_args == 0 {
+                return_errno_with_message!(Errno::EINVAL, "no files are specified");
+            }
+            if nr_args > IORING_MAX_FIXED_FILES {
+                return_errno_with_message!(Errno::EMFILE, "too many files are specified");
+            }
+
+            let user_space = ctx.user_space();
+            let mut files = Vec::with_capacity(nr_args as usize);
+            for i in 0..nr_args as usize {
+                let fd = user_space.read_val::<i32>(arg + i * size_of::<i32>())?;
+                // A file descriptor of -1 leaves the slot empty.
+                let file = if fd == -1 {
+                    None
+                } else {
+                    Some(get_file_fast!(&mut file_table, fd.try_into()?).into_owned())
+                };
+                files.push(file);
+            }
+
+            io_uring_file.register_files(files)?;
+        }
+        IoUringRegisterOp::UnregisterFiles => {
+            if arg != 0 || nr_args != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the arguments are not empty");
+            }
+            io_uring_file.unregister_files()?;
+        }
+        IoUringRegisterOp::RegisterEventfd => {
+            if nr_args != 1 {
+                return_errno_with_message!(Errno::EINVAL, "exactly one eventfd must be specified");
+            }
+
+            let eventfd = ctx.user_space().read_val::<i32>(arg)?;
+            let file = get_file_fast!(&mut file_table, eventfd.try_into()?).into_owned();
+            io_uring_file.register_eventfd(file)?;
+        }
+        IoUringRegisterOp::UnregisterEventfd => {
+            if arg != 0 || nr_args != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the arguments are not empty");
+            }
+            io_uring_file.unregister_eventfd()?;
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
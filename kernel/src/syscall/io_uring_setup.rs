@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file::file_table::FdFlags,
+    io_uring::{IoUringFile, IoUringParams},
+    prelude::*,
+};
+
+pub fn sys_io_uring_setup(
+    entries: u32,
+    params_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+
+    let mut params = user_space.read_val::<IoUringParams>(params_addr)?;
+    params.sq_entries = entries;
+    debug!("entries = {}, params = {:?}", entries, params);
+
+    let io_uring_file = IoUringFile::new(&mut params)?;
+    user_space.write_val(params_addr, &params)?;
+
+    // Linux always creates io_uring files with `O_CLOEXEC`.
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/io_uring.c>.
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        file_table_locked.insert(Arc::new(io_uring_file), FdFlags::CLOEXEC)
+    };
+
+    Ok(SyscallReturn::Return(fd.into()))
+}
@@ -13,7 +13,7 @@ use crate::{
 };
 
 pub fn sys_kill(process_filter: u64, sig_num: u64, ctx: &Context) -> Result<SyscallReturn> {
-    let process_filter = ProcessFilter::from_id(process_filter as _)?;
+    let process_filter = ProcessFilter::from_id(process_filter as _, ctx)?;
     let sig_num = if sig_num == 0 {
         None
     } else {
@@ -37,6 +37,9 @@ fn do_sys_kill(filter: ProcessFilter, sig_num: Option<SigNum>, ctx: &Context) ->
         }
         ProcessFilter::WithPgid(pgid) => kill_group(pgid, signal, ctx)?,
         ProcessFilter::WithPidfd(_) => unreachable!(),
+        ProcessFilter::Invisible => {
+            return_errno_with_message!(Errno::ESRCH, "the target process does not exist")
+        }
     }
     Ok(())
 }
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file::{
+        FileLike,
+        file_table::{FdFlags, RawFileDesc, get_file_fast},
+    },
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    security::lsm::{
+        self, hooks as lsm_hooks,
+        landlock::{self, AccessFs, AccessNet, LANDLOCK_ABI_VERSION, Ruleset, RulesetFile},
+    },
+    util::CopyCompat,
+};
+
+pub fn sys_landlock_create_ruleset(
+    attr_addr: Vaddr,
+    size: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "attr_addr = {:#x}, size = {}, flags = {:#x}",
+        attr_addr, size, flags
+    );
+
+    check_landlock_enabled()?;
+
+    if flags != 0 {
+        if attr_addr != 0 || size != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the attributes must be empty with flags");
+        }
+        if flags != LANDLOCK_CREATE_RULESET_VERSION {
+            return_errno_with_message!(Errno::EINVAL, "invalid flags");
+        }
+        return Ok(SyscallReturn::Return(LANDLOCK_ABI_VERSION as _));
+    }
+
+    if size < size_of::<u64>() {
+        return_errno_with_message!(Errno::EINVAL, "the attribute size is too small");
+    }
+    if size > PAGE_SIZE {
+        return_errno_with_message!(Errno::E2BIG, "the attribute size is too large");
+    }
+    let attr = ctx
+        .user_space()
+        .read_val_compat::<LandlockRulesetAttr>(attr_addr, size)?;
+
+    let handled_fs = AccessFs::from_bits(attr.handled_access_fs)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid filesystem access rights"))?;
+    let handled_net = AccessNet::from_bits(attr.handled_access_net)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid network access rights"))?;
+    let ruleset = Ruleset::new(handled_fs, handled_net)?;
+
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        file_table_locked.insert(Arc::new(RulesetFile::new(ruleset)), FdFlags::CLOEXEC)
+    };
+    Ok(SyscallReturn::Return(fd.into()))
+}
+
+pub fn sys_landlock_add_rule(
+    ruleset_fd: RawFileDesc,
+    rule_type: u32,
+    rule_attr_addr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "ruleset_fd = {}, rule_type = {}, rule_attr_addr = {:#x}, flags = {:#x}",
+        ruleset_fd, rule_type, rule_attr_addr, flags
+    );
+
+    check_landlock_enabled()?;
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    let file = get_ruleset_file(ruleset_fd, ctx)?;
+    let ruleset_file = file.downcast_ref::<RulesetFile>().unwrap();
+
+    let user_space = ctx.user_space();
+    match rule_type {
+        LANDLOCK_RULE_PATH_BENEATH => {
+            // The `landlock_path_beneath_attr` structure is packed, so its fields are read
+            // separately.
+            let allowed_access = user_space.read_val::<u64>(rule_attr_addr)?;
+            let parent_fd =
+                user_space.read_val::<RawFileDesc>(rule_attr_addr + size_of::<u64>())?;
+
+            let access = AccessFs::from_bits(allowed_access)
+                .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid access rights"))?;
+            ruleset_file.ruleset().lock().check_fs_access(access)?;
+
+            let parent_path = {
+                let mut file_table = ctx.thread_local.borrow_file_table_mut();
+                let parent_file = get_file_fast!(&mut file_table, parent_fd.try_into()?);
+                parent_file.path().clone()
+            };
+            if parent_path.is_pseudo() {
+                return_errno_with_message!(
+                    Errno::EBADFD,
+                    "the parent file is not in the filesystem tree"
+                );
+            }
+
+            ruleset_file
+                .ruleset()
+                .lock()
+                .add_path_rule(&parent_path, access)?;
+        }
+        LANDLOCK_RULE_NET_PORT => {
+            let attr = user_space.read_val::<LandlockNetPortAttr>(rule_attr_addr)?;
+
+            let access = AccessNet::from_bits(attr.allowed_access)
+                .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid access rights"))?;
+            ruleset_file.ruleset().lock().check_net_access(access)?;
+            let port = u16::try_from(attr.port)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the port is out of range"))?;
+
+            ruleset_file.ruleset().lock().add_net_rule(port, access)?;
+        }
+        _ => return_errno_with_message!(Errno::EINVAL, "invalid rule type"),
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_landlock_restrict_self(
+    ruleset_fd: RawFileDesc,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("ruleset_fd = {}, flags = {:#x}", ruleset_fd, flags);
+
+    check_landlock_enabled()?;
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    // Enforcing a ruleset requires either `no_new_privs` or `CAP_SYS_ADMIN`. Otherwise, the
+    // restrictions could confuse a privileged `set_uid` program that the thread executes.
+    if !ctx.posix_thread.no_new_privs()
+        && lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            ctx.thread_local.borrow_user_ns().as_ref(),
+            ctx.posix_thread,
+            CapSet::SYS_ADMIN,
+        ))
+        .is_err()
+    {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "enforcing rulesets requires `no_new_privs` or `CAP_SYS_ADMIN`"
+        );
+    }
+
+    let file = get_ruleset_file(ruleset_fd, ctx)?;
+    let ruleset = file
+        .downcast_ref::<RulesetFile>()
+        .unwrap()
+        .ruleset()
+        .lock()
+        .clone();
+    landlock::restrict_self(ruleset, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn check_landlock_enabled() -> Result<()> {
+    if !lsm::is_landlock_enabled() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "Landlock is not enabled");
+    }
+
+    Ok(())
+}
+
+/// Gets the file of `ruleset_fd`, which must be a Landlock ruleset file.
+fn get_ruleset_file(ruleset_fd: RawFileDesc, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, ruleset_fd.try_into()?).into_owned();
+    if file.downcast_ref::<RulesetFile>().is_none() {
+        return_errno_with_message!(Errno::EBADFD, "the file is not a Landlock ruleset");
+    }
+
+    Ok(file)
+}
+
+const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+
+const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+const LANDLOCK_RULE_NET_PORT: u32 = 2;
+
+/// The ruleset attributes (`struct landlock_ruleset_attr`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct LandlockRulesetAttr {
+    handled_access_fs: u64,
+    handled_access_net: u64,
+}
+
+/// The network port rule attributes (`struct landlock_net_port_attr`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct LandlockNetPortAttr {
+    allowed_access: u64,
+    port: u64,
+}
@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
+#[cfg(target_arch = "x86_64")]
+use ostd::mm::VmIo;
+
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs::file::{
         SeekFrom,
@@ -20,6 +25,50 @@ pub fn sys_lseek(
         raw_fd, offset, whence
     );
 
+    let offset = do_lseek(raw_fd, offset, whence, ctx)?;
+    Ok(SyscallReturn::Return(offset as _))
+}
+
+/// The `lseek` system call of 32-bit programs, which uses a 32-bit signed offset.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_lseek(
+    raw_fd: RawFileDesc,
+    offset: i32,
+    whence: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "raw_fd = {}, offset = {}, whence = {}",
+        raw_fd, offset, whence
+    );
+
+    let offset = do_lseek(raw_fd, offset as isize, whence, ctx)?;
+    Ok(SyscallReturn::Return(offset as _))
+}
+
+/// The `_llseek` system call of 32-bit programs, which splits the 64-bit offset into two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_llseek(
+    raw_fd: RawFileDesc,
+    offset_high: u32,
+    offset_low: u32,
+    result_addr: Vaddr,
+    whence: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high) as i64;
+    debug!(
+        "raw_fd = {}, offset = {}, result_addr = 0x{:x}, whence = {}",
+        raw_fd, offset, result_addr, whence
+    );
+
+    let offset = do_lseek(raw_fd, offset as isize, whence, ctx)? as i64;
+    ctx.user_space().write_val(result_addr, &offset)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn do_lseek(raw_fd: RawFileDesc, offset: isize, whence: u32, ctx: &Context) -> Result<usize> {
     let seek_from = match SeekType::try_from(whence)? {
         SeekType::SEEK_SET => SeekFrom::Start(offset.cast_unsigned()),
         SeekType::SEEK_CUR => SeekFrom::Current(offset),
@@ -29,8 +78,7 @@ pub fn sys_lseek(
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
 
-    let offset = file.seek(seek_from)?;
-    Ok(SyscallReturn::Return(offset as _))
+    file.seek(seek_from)
 }
 
 // Reference: <https://elixir.bootlin.com/linux/v6.17.7/source/include/uapi/linux/fs.h#L52>
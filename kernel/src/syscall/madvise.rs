@@ -3,7 +3,10 @@
 use align_ext::AlignExt;
 
 use super::SyscallReturn;
-use crate::{prelude::*, vm::vmar::VMAR_CAP_ADDR};
+use crate::{
+    prelude::*,
+    vm::{thp::ThpAdvice, vmar::VMAR_CAP_ADDR},
+};
 
 pub fn sys_madvise(addr: Vaddr, len: usize, behavior: i32, ctx: &Context) -> Result<SyscallReturn> {
     let behavior = MadviseBehavior::try_from(behavior)?;
@@ -32,6 +35,18 @@ pub fn sys_madvise(addr: Vaddr, len: usize, behavior: i32, ctx: &Context) -> Res
         MadviseBehavior::MADV_DONTNEED => {
             vmar.discard_pages(addr_range)?;
         }
+        MadviseBehavior::MADV_HUGEPAGE => {
+            vmar.advise_huge_pages(ThpAdvice::Huge, addr_range)?;
+        }
+        MadviseBehavior::MADV_NOHUGEPAGE => {
+            vmar.advise_huge_pages(ThpAdvice::NoHuge, addr_range)?;
+        }
+        MadviseBehavior::MADV_DONTDUMP => {
+            vmar.advise_dump(true, addr_range)?;
+        }
+        MadviseBehavior::MADV_DODUMP => {
+            vmar.advise_dump(false, addr_range)?;
+        }
         _ if DUMMY_MADVISE.contains(&behavior) => {
             let query_guard = vmar.query(addr_range);
             if !query_guard.is_fully_mapped() {
@@ -105,6 +120,4 @@ const DUMMY_MADVISE: &[MadviseBehavior] = &[
     MadviseBehavior::MADV_FREE,
     MadviseBehavior::MADV_MERGEABLE,
     MadviseBehavior::MADV_UNMERGEABLE,
-    MadviseBehavior::MADV_HUGEPAGE,
-    MadviseBehavior::MADV_NOHUGEPAGE,
 ];
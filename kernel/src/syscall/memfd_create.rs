@@ -7,8 +7,14 @@ use crate::{
         ramfs::memfd::{MAX_MEMFD_NAME_LEN, MemfdFlags, MemfdInodeHandle},
     },
     prelude::*,
+    vm::hugetlb,
 };
 
+/// The shift of the base-2 logarithm of the huge page size in the flags.
+const MFD_HUGE_SHIFT: u32 = 26;
+/// The mask of the huge page size in the flags.
+const MFD_HUGE_SIZE_MASK: u32 = 0x3f << MFD_HUGE_SHIFT;
+
 pub fn sys_memfd_create(name_addr: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
     let name = ctx
         .user_space()
@@ -23,7 +29,17 @@ pub fn sys_memfd_create(name_addr: Vaddr, flags: u32, ctx: &Context) -> Result<S
     debug!("sys_memfd_create: name = {:?}, flags = {}", name, flags);
 
     let fd = {
-        let memfd_flags = MemfdFlags::from_bits(flags).ok_or(Errno::EINVAL)?;
+        let memfd_flags =
+            MemfdFlags::from_bits(flags & !MFD_HUGE_SIZE_MASK).ok_or(Errno::EINVAL)?;
+        if memfd_flags.contains(MemfdFlags::MFD_HUGETLB) {
+            hugetlb::check_huge_page_size(flags >> MFD_HUGE_SHIFT)?;
+        } else if flags & MFD_HUGE_SIZE_MASK != 0 {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the huge page size is specified without `MFD_HUGETLB`"
+            );
+        }
+
         let fd_flags = if memfd_flags.contains(MemfdFlags::MFD_CLOEXEC) {
             FdFlags::CLOEXEC
         } else {
@@ -32,11 +48,6 @@ pub fn sys_memfd_create(name_addr: Vaddr, flags: u32, ctx: &Context) -> Result<S
         let file_table = ctx.thread_local.borrow_file_table();
         let mut file_table_locked = file_table.unwrap().write();
 
-        // FIXME: Support `MFD_HUGETLB`.
-        if memfd_flags.contains(MemfdFlags::MFD_HUGETLB) {
-            warn!("`MFD_HUGETLB` not supported");
-        }
-
         if memfd_flags.contains(MemfdFlags::MFD_NOEXEC_SEAL | MemfdFlags::MFD_EXEC) {
             return_errno_with_message!(
                 Errno::EINVAL,
@@ -7,9 +7,10 @@ use crate::{
     fs::file::file_table::{RawFileDesc, get_file_fast},
     prelude::*,
     vm::{
+        hugetlb::{self, HugetlbFile},
         page_cache::VmoOptions,
         perms::VmPerms,
-        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, VmarMapOffset},
+        vmar::{VMAR_LOWEST_ADDR, VmarMapOffset},
     },
 };
 
@@ -36,6 +37,30 @@ pub fn sys_mmap(
     Ok(SyscallReturn::Return(res as _))
 }
 
+/// The `mmap2` system call of 32-bit programs, whose offset is in units of 4096 bytes.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_mmap2(
+    addr: u64,
+    len: u64,
+    perms: u64,
+    flags: u64,
+    fd: u64,
+    page_offset: u64,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    const MMAP2_OFFSET_UNIT: u64 = 4096;
+
+    sys_mmap(
+        addr,
+        len,
+        perms,
+        flags,
+        fd,
+        page_offset * MMAP2_OFFSET_UNIT,
+        ctx,
+    )
+}
+
 fn do_sys_mmap(
     addr: Vaddr,
     len: usize,
@@ -50,14 +75,42 @@ fn do_sys_mmap(
         addr, len, vm_perms, option, raw_fd, offset
     );
 
-    let len = check_len(len)?;
+    let user_space = ctx.user_space();
+    let vmar = user_space.vmar();
+    // The user space of 32-bit programs is smaller.
+    let cap_addr = vmar.process_vm().vmar_cap_addr();
+
+    let huge_page_size = check_huge_page_size(&option, raw_fd, ctx)?;
+
+    let mut len = check_len(len, cap_addr)?;
+    if let Some(huge_page_size) = huge_page_size {
+        len = len.align_up(huge_page_size);
+    }
     let addr = if option.flags().is_fixed() {
-        check_addr(addr, len)?;
+        check_addr(addr, len, cap_addr)?;
+        if huge_page_size.is_some_and(|size| !addr.is_multiple_of(size)) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the mapping address is not aligned to the huge page size"
+            );
+        }
         addr
     } else {
-        adjust_addr_hint(addr, len)
+        let addr = adjust_addr_hint(addr, len, cap_addr);
+        match huge_page_size {
+            Some(huge_page_size) => adjust_addr_hint(addr.align_up(huge_page_size), len, cap_addr),
+            None => addr,
+        }
     };
     check_offset(offset, len, option.flags())?;
+    if !option.flags().contains(MMapFlags::MAP_ANONYMOUS)
+        && huge_page_size.is_some_and(|size| !offset.is_multiple_of(size))
+    {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the mapping offset is not aligned to the huge page size"
+        );
+    }
 
     // On x86_64 and riscv64, `PROT_WRITE` implies `PROT_READ`.
     // Reference:
@@ -73,8 +126,6 @@ fn do_sys_mmap(
 
     let mut vm_may_perms = VmPerms::ALL_MAY_PERMS;
 
-    let user_space = ctx.user_space();
-    let vmar = user_space.vmar();
     let vm_map_options = {
         let mut options = vmar.new_map(len, vm_perms)?;
 
@@ -98,6 +149,10 @@ fn do_sys_mmap(
             options = options.is_shared(true);
         }
 
+        if let Some(huge_page_size) = huge_page_size {
+            options = options.align(huge_page_size);
+        }
+
         if option.flags().contains(MMapFlags::MAP_ANONYMOUS) {
             // Linux rejects MAP_SHARED_VALIDATE for anonymous mappings.
             if option.typ() == MMapType::SharedValidate {
@@ -106,8 +161,15 @@ fn do_sys_mmap(
                     "MAP_SHARED_VALIDATE and MAP_ANONYMOUS cannot be used together"
                 );
             }
-            // Anonymous shared mappings should share the same memory pages.
-            if option.typ().is_shared() {
+            if huge_page_size.is_some() {
+                // Anonymous shared hugetlb mappings should share the same huge pages.
+                if option.typ().is_shared() {
+                    options = options.hugetlb_file(HugetlbFile::new(len));
+                } else {
+                    options = options.hugetlb();
+                }
+            } else if option.typ().is_shared() {
+                // Anonymous shared mappings should share the same memory pages.
                 let shared_vmo = {
                     let vmo_options = VmoOptions::new(len);
                     vmo_options.alloc()?
@@ -144,19 +206,53 @@ fn do_sys_mmap(
     Ok(map_addr)
 }
 
-fn check_len(len: usize) -> Result<usize> {
+/// Checks whether the mapping is backed by huge pages, and returns the size of
+/// the huge pages if so.
+///
+/// A mapping is backed by huge pages if it is an anonymous mapping with
+/// `MAP_HUGETLB` or if it maps a hugetlbfs file.
+fn check_huge_page_size(
+    option: &MMapOptions,
+    raw_fd: RawFileDesc,
+    ctx: &Context,
+) -> Result<Option<usize>> {
+    let flags = option.flags();
+
+    if flags.contains(MMapFlags::MAP_ANONYMOUS) {
+        if !flags.contains(MMapFlags::MAP_HUGETLB) {
+            return Ok(None);
+        }
+        return hugetlb::check_huge_page_size(option.huge_size_log2()).map(Some);
+    }
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+    let is_hugetlb_file = file
+        .as_inode_handle_or_err()
+        .is_ok_and(|handle| handle.path().inode().hugetlb_file().is_some());
+
+    if is_hugetlb_file {
+        Ok(hugetlb::huge_page_size())
+    } else if flags.contains(MMapFlags::MAP_HUGETLB) {
+        return_errno_with_message!(Errno::EINVAL, "the file is not a hugetlbfs file");
+    } else {
+        Ok(None)
+    }
+}
+
+fn check_len(len: usize, cap_addr: Vaddr) -> Result<usize> {
     if len == 0 {
         return_errno_with_message!(Errno::EINVAL, "the mapping length is zero");
     }
 
-    if len > VMAR_CAP_ADDR {
+    if len > cap_addr {
         return_errno_with_message!(Errno::ENOMEM, "the mapping length is too large");
     }
 
     Ok(len.align_up(PAGE_SIZE))
 }
 
-fn check_addr(addr: Vaddr, len: usize) -> Result<()> {
+fn check_addr(addr: Vaddr, len: usize, cap_addr: Vaddr) -> Result<()> {
     if !addr.is_multiple_of(PAGE_SIZE) {
         return_errno_with_message!(Errno::EINVAL, "the mapping address is not aligned");
     }
@@ -165,14 +261,14 @@ fn check_addr(addr: Vaddr, len: usize) -> Result<()> {
         return_errno_with_message!(Errno::EPERM, "the mapping address is too low");
     }
 
-    if addr > VMAR_CAP_ADDR - len {
+    if addr > cap_addr - len {
         return_errno_with_message!(Errno::ENOMEM, "the mapping address is too high");
     }
 
     Ok(())
 }
 
-fn adjust_addr_hint(mut addr: Vaddr, len: usize) -> Vaddr {
+fn adjust_addr_hint(mut addr: Vaddr, len: usize, cap_addr: Vaddr) -> Vaddr {
     addr = addr.align_down(PAGE_SIZE);
     if addr == 0 {
         // No hint.
@@ -184,7 +280,7 @@ fn adjust_addr_hint(mut addr: Vaddr, len: usize) -> Vaddr {
         // Reference: <https://elixir.bootlin.com/linux/v6.19.3/source/mm/mmap.c#L219>.
         addr = VMAR_LOWEST_ADDR;
     }
-    if addr > VMAR_CAP_ADDR - len {
+    if addr > cap_addr - len {
         // Illegal hint. Treat it as if there were no hint.
         addr = 0;
     }
@@ -220,6 +316,13 @@ fn check_offset(offset: usize, len: usize, flags: MMapFlags) -> Result<()> {
 /// The mask for the mapping type.
 const MAP_TYPE_MASK: u32 = 0xf;
 
+/// The shift of the base-2 logarithm of the huge page size in the flag value.
+const MAP_HUGE_SHIFT: u32 = 26;
+/// The mask of the huge page size in the flag value.
+const MAP_HUGE_MASK: u32 = 0x3f << MAP_HUGE_SHIFT;
+const MAP_HUGE_2MB: u32 = 21 << MAP_HUGE_SHIFT;
+const MAP_HUGE_1GB: u32 = 30 << MAP_HUGE_SHIFT;
+
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, TryFromInt)]
 enum MMapType {
@@ -277,6 +380,9 @@ impl MMapFlags {
 struct MMapOptions {
     typ: MMapType,
     flags: MMapFlags,
+    /// The base-2 logarithm of the huge page size for `MAP_HUGETLB`, or zero
+    /// for the default huge page size.
+    huge_size_log2: u32,
 }
 
 impl TryFrom<u32> for MMapOptions {
@@ -289,12 +395,19 @@ impl TryFrom<u32> for MMapOptions {
         // According to the Linux behavior, unknown flags are silently ignored unless
         // `MAP_SHARED_VALIDATE` is specified.
         let flags_raw = value & !MAP_TYPE_MASK;
-        if typ == MMapType::SharedValidate && (flags_raw & !LEGACY_MMAP_FLAGS.bits()) != 0 {
+        if typ == MMapType::SharedValidate
+            && (flags_raw & !(LEGACY_MMAP_FLAGS.bits() | MAP_HUGE_2MB | MAP_HUGE_1GB)) != 0
+        {
             return_errno_with_message!(Errno::EOPNOTSUPP, "the mapping flags are not supported");
         }
         let flags = MMapFlags::from_bits_truncate(flags_raw);
+        let huge_size_log2 = (flags_raw & MAP_HUGE_MASK) >> MAP_HUGE_SHIFT;
 
-        Ok(MMapOptions { typ, flags })
+        Ok(MMapOptions {
+            typ,
+            flags,
+            huge_size_log2,
+        })
     }
 }
 
@@ -306,4 +419,8 @@ impl MMapOptions {
     pub(self) fn flags(&self) -> MMapFlags {
         self.flags
     }
+
+    pub(self) fn huge_size_log2(&self) -> u32 {
+        self.huge_size_log2
+    }
 }
@@ -8,6 +8,7 @@
 )]
 
 pub use clock_gettime::ClockId;
+pub use eventfd::EventFile;
 use ostd::arch::cpu::context::UserContext;
 pub use timer_create::create_timer;
 
@@ -17,6 +18,9 @@ use crate::{cpu::LinuxAbi, prelude::*};
 #[cfg_attr(target_arch = "riscv64", path = "arch/riscv.rs")]
 #[cfg_attr(target_arch = "loongarch64", path = "arch/loongarch.rs")]
 mod arch;
+#[cfg(target_arch = "x86_64")]
+#[path = "arch/ia32.rs"]
+mod ia32;
 
 mod accept;
 mod access;
@@ -36,6 +40,7 @@ mod clone;
 mod close;
 mod connect;
 mod constants;
+mod copy_file_range;
 mod dup;
 mod epoll;
 mod eventfd;
@@ -44,6 +49,7 @@ mod exit;
 mod exit_group;
 mod fadvise64;
 mod fallocate;
+mod fanotify;
 mod fcntl;
 mod flock;
 mod fork;
@@ -75,8 +81,12 @@ mod gettimeofday;
 mod getuid;
 mod getxattr;
 mod inotify;
+mod io_uring_enter;
+mod io_uring_register;
+mod io_uring_setup;
 mod ioctl;
 mod kill;
+mod landlock;
 mod link;
 mod listen;
 mod listxattr;
@@ -88,7 +98,17 @@ mod mknod;
 mod mmap;
 mod mount;
 mod mprotect;
+mod mq_getsetattr;
+mod mq_notify;
+mod mq_open;
+mod mq_timedreceive;
+mod mq_timedsend;
+mod mq_unlink;
 mod mremap;
+mod msgctl;
+mod msgget;
+mod msgrcv;
+mod msgsnd;
 mod msync;
 mod munmap;
 mod nanosleep;
@@ -134,6 +154,7 @@ mod sched_setattr;
 mod sched_setparam;
 mod sched_setscheduler;
 mod sched_yield;
+mod seccomp;
 mod select;
 mod semctl;
 mod semget;
@@ -163,18 +184,27 @@ mod setsid;
 mod setsockopt;
 mod setuid;
 mod setxattr;
+mod shmat;
+mod shmctl;
+mod shmdt;
+mod shmget;
 mod shutdown;
 mod sigaltstack;
 mod signalfd;
 mod socket;
 mod socketpair;
+mod splice;
 mod stat;
 mod statfs;
 mod statx;
+mod swapoff;
+mod swapon;
 mod symlink;
 mod sync;
 mod sysinfo;
 mod tgkill;
+#[cfg(target_arch = "x86_64")]
+mod thread_area;
 mod time;
 mod timer_create;
 mod timer_settime;
@@ -187,6 +217,7 @@ mod umount;
 mod uname;
 mod unlink;
 mod unshare;
+mod userfaultfd;
 mod utimens;
 mod wait4;
 mod waitid;
@@ -372,8 +403,24 @@ impl SyscallArgument {
 }
 
 pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
+    #[cfg(target_arch = "x86_64")]
+    ctx.thread_local
+        .set_in_compat_syscall(user_ctx.is_ia32_syscall());
+
+    if !crate::security::seccomp::may_execute_syscall(ctx, user_ctx) {
+        return;
+    }
+
     let syscall_frame = SyscallArgument::new_from_context(user_ctx);
-    let syscall_return = arch::syscall_dispatch(
+    #[cfg(target_arch = "x86_64")]
+    let syscall_dispatch = if ctx.thread_local.in_compat_syscall() {
+        ia32::syscall_dispatch
+    } else {
+        arch::syscall_dispatch
+    };
+    #[cfg(not(target_arch = "x86_64"))]
+    let syscall_dispatch = arch::syscall_dispatch;
+    let syscall_return = syscall_dispatch(
         syscall_frame.syscall_number,
         syscall_frame.args,
         ctx,
@@ -394,6 +441,12 @@ pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
     }
 }
 
+/// Combines the two halves of a 64-bit argument of a 32-bit system call.
+#[cfg(target_arch = "x86_64")]
+fn compat_arg_u64(low: u32, high: u32) -> u64 {
+    ((high as u64) << 32) | (low as u64)
+}
+
 macro_rules! log_syscall_entry {
     ($syscall_name: tt) => {
         if ostd::log_enabled!(ostd::log::Level::Info) {
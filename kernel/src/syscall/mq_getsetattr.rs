@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::{
+            StatusFlags,
+            file_table::{RawFileDesc, get_file_fast},
+        },
+        mqueuefs,
+    },
+    ipc::mqueue::MqAttr,
+    prelude::*,
+};
+
+pub fn sys_mq_getsetattr(
+    mqdes: RawFileDesc,
+    newattr_addr: Vaddr,
+    oldattr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mq_getsetattr: mqdes = {}, newattr_addr = {:#x}, oldattr_addr = {:#x}",
+        mqdes, newattr_addr, oldattr_addr
+    );
+
+    // Only the `O_NONBLOCK` flag of the queue description can be changed.
+    let new_is_nonblocking = if newattr_addr != 0 {
+        let new_attr = ctx.user_space().read_val::<MqAttr>(newattr_addr)?;
+        if new_attr.mq_flags & !(StatusFlags::O_NONBLOCK.bits() as i64) != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the message queue flags are invalid");
+        }
+        Some(new_attr.mq_flags != 0)
+    } else {
+        None
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let queue = mqueuefs::queue_of(&**file)?;
+
+    let mut status_flags = file.status_flags();
+    let old_attr = MqAttr {
+        mq_flags: (status_flags & StatusFlags::O_NONBLOCK).bits() as i64,
+        ..queue.attr()
+    };
+
+    if let Some(is_nonblocking) = new_is_nonblocking {
+        status_flags.set(StatusFlags::O_NONBLOCK, is_nonblocking);
+        file.set_status_flags(status_flags)?;
+    }
+
+    if oldattr_addr != 0 {
+        ctx.user_space().write_val(oldattr_addr, &old_attr)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::file_table::{RawFileDesc, get_file_fast},
+        mqueuefs,
+    },
+    ipc::mqueue::MqNotifyKind,
+    prelude::*,
+    process::signal::{
+        c_types::{SigNotify, sigevent_t},
+        sig_num::SigNum,
+    },
+};
+
+pub fn sys_mq_notify(mqdes: RawFileDesc, sevp_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("mq_notify: mqdes = {}, sevp_addr = {:#x}", mqdes, sevp_addr);
+
+    let kind = if sevp_addr == 0 {
+        None
+    } else {
+        let sig_event = ctx.user_space().read_val::<sigevent_t>(sevp_addr)?;
+        let sigev_notify = SigNotify::try_from(sig_event.sigev_notify).map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the notification method is invalid")
+        })?;
+        match sigev_notify {
+            SigNotify::SIGEV_NONE => Some(MqNotifyKind::None),
+            SigNotify::SIGEV_SIGNAL => {
+                let signum = u8::try_from(sig_event.sigev_signo)
+                    .ok()
+                    .and_then(|signo| SigNum::try_from(signo).ok())
+                    .ok_or_else(|| {
+                        Error::with_message(Errno::EINVAL, "the signal number is invalid")
+                    })?;
+                Some(MqNotifyKind::Signal {
+                    signum,
+                    value: sig_event.sigev_value,
+                })
+            }
+            // TODO: Support `SIGEV_THREAD`, which is implemented with netlink sockets in Linux.
+            SigNotify::SIGEV_THREAD => {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the thread notification is not supported"
+                );
+            }
+            SigNotify::SIGEV_THREAD_ID => {
+                return_errno_with_message!(Errno::EINVAL, "the notification method is invalid");
+            }
+        }
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let queue = mqueuefs::queue_of(&**file)?;
+
+    queue.set_notification(kind, &ctx.process)?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::{CreationFlags, InodeMode, OpenArgs, file_table::FdFlags},
+        mqueuefs,
+    },
+    ipc::mqueue::MqAttr,
+    prelude::*,
+    syscall::constants::MAX_FILENAME_LEN,
+};
+
+pub fn sys_mq_open(
+    name_addr: Vaddr,
+    oflag: u32,
+    mode: u16,
+    attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!(
+        "mq_open: name = {:?}, oflag = {:#o}, mode = {:#o}, attr_addr = {:#x}",
+        name, oflag, mode, attr_addr
+    );
+
+    let creation_flags = CreationFlags::from_bits_truncate(oflag);
+    let attr = if creation_flags.contains(CreationFlags::O_CREAT) && attr_addr != 0 {
+        Some(ctx.user_space().read_val::<MqAttr>(attr_addr)?)
+    } else {
+        None
+    };
+
+    let file_handle = {
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let mask_mode = mode & !fs_ref.umask().get();
+        let open_args =
+            OpenArgs::from_flags_and_mode(oflag, InodeMode::from_bits_truncate(mask_mode))?;
+
+        let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+        let mount = ns_proxy.unwrap().ipc_ns().mqueue_mount()?;
+
+        let path_resolver = fs_ref.resolver().read();
+        mqueuefs::open_queue(
+            &path_resolver,
+            mount,
+            &name.to_string_lossy(),
+            &open_args,
+            attr.as_ref(),
+        )?
+    };
+
+    // Like Linux, message queue descriptors are always closed on `execve`.
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/mqueue.c>
+    let fd = ctx
+        .thread_local
+        .borrow_file_table()
+        .unwrap()
+        .write()
+        .insert(Arc::new(file_handle), FdFlags::CLOEXEC);
+
+    Ok(SyscallReturn::Return(fd.into()))
+}
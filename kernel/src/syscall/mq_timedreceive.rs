@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::{SyscallReturn, mq_timedsend::read_abs_timeout};
+use crate::{
+    fs::{
+        file::{
+            StatusFlags,
+            file_table::{RawFileDesc, get_file_fast},
+        },
+        mqueuefs,
+    },
+    prelude::*,
+};
+
+pub fn sys_mq_timedreceive(
+    mqdes: RawFileDesc,
+    msg_ptr: Vaddr,
+    msg_len: usize,
+    msg_prio_addr: Vaddr,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mq_timedreceive: mqdes = {}, msg_ptr = {:#x}, msg_len = {}, msg_prio_addr = {:#x}, abs_timeout_addr = {:#x}",
+        mqdes, msg_ptr, msg_len, msg_prio_addr, abs_timeout_addr
+    );
+
+    let timeout = read_abs_timeout(abs_timeout_addr, ctx)?;
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let queue = mqueuefs::queue_of(&**file)?;
+    if !file.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the message queue is not opened for reading");
+    }
+    let is_nonblocking = file.status_flags().contains(StatusFlags::O_NONBLOCK);
+
+    let (message, priority) = queue
+        .receive(msg_len, is_nonblocking, timeout.as_ref())
+        .map_err(|err| match err.error() {
+            Errno::ETIME => Error::new(Errno::ETIMEDOUT),
+            Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+            _ => err,
+        })?;
+
+    // TODO: Put the message back if it cannot be copied to the user space.
+    let user_space = ctx.user_space();
+    user_space.write_bytes(msg_ptr, &*message)?;
+    if msg_prio_addr != 0 {
+        user_space.write_val(msg_prio_addr, &priority)?;
+    }
+
+    Ok(SyscallReturn::Return(message.len() as _))
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::{
+            StatusFlags,
+            file_table::{RawFileDesc, get_file_fast},
+        },
+        mqueuefs,
+    },
+    ipc::mqueue::MQ_PRIO_MAX,
+    prelude::*,
+    time::{clocks::RealTimeClock, timespec_t},
+};
+
+pub fn sys_mq_timedsend(
+    mqdes: RawFileDesc,
+    msg_ptr: Vaddr,
+    msg_len: usize,
+    msg_prio: u32,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mq_timedsend: mqdes = {}, msg_ptr = {:#x}, msg_len = {}, msg_prio = {}, abs_timeout_addr = {:#x}",
+        mqdes, msg_ptr, msg_len, msg_prio, abs_timeout_addr
+    );
+
+    let timeout = read_abs_timeout(abs_timeout_addr, ctx)?;
+    if msg_prio >= MQ_PRIO_MAX {
+        return_errno_with_message!(Errno::EINVAL, "the message priority is too large");
+    }
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let queue = mqueuefs::queue_of(&**file)?;
+    if !file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the message queue is not opened for writing");
+    }
+    let is_nonblocking = file.status_flags().contains(StatusFlags::O_NONBLOCK);
+
+    if msg_len > queue.max_msg_size() {
+        return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+    }
+    let mut message = vec![0u8; msg_len].into_boxed_slice();
+    ctx.user_space().read_bytes(msg_ptr, &mut *message)?;
+
+    queue
+        .send(
+            message,
+            msg_prio,
+            is_nonblocking,
+            timeout.as_ref(),
+            ctx.process.pid(),
+            ctx.posix_thread.credentials().ruid(),
+        )
+        .map_err(|err| match err.error() {
+            Errno::ETIME => Error::new(Errno::ETIMEDOUT),
+            Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+            _ => err,
+        })?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Reads the absolute timeout for `mq_timedsend` and `mq_timedreceive`.
+///
+/// The timeout is measured against `CLOCK_REALTIME` and is returned as the remaining duration.
+pub(super) fn read_abs_timeout(abs_timeout_addr: Vaddr, ctx: &Context) -> Result<Option<Duration>> {
+    if abs_timeout_addr == 0 {
+        return Ok(None);
+    }
+
+    let abs_timeout = {
+        let timespec = ctx.user_space().read_val::<timespec_t>(abs_timeout_addr)?;
+        Duration::try_from(timespec)?
+    };
+    let now = RealTimeClock::get().read_time();
+
+    Ok(Some(abs_timeout.saturating_sub(now)))
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{fs::mqueuefs, prelude::*, syscall::constants::MAX_FILENAME_LEN};
+
+pub fn sys_mq_unlink(name_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!("mq_unlink: name = {:?}", name);
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let mount = ns_proxy.unwrap().ipc_ns().mqueue_mount()?;
+
+    mqueuefs::unlink_queue(mount, &name.to_string_lossy())?;
+
+    Ok(SyscallReturn::Return(0))
+}
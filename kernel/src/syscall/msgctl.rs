@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{IpcControlCmd, IpcId, PermissionMode, msg::msg_queue::MsqidDs},
+    prelude::*,
+};
+
+pub fn sys_msgctl(msqid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let Ok(msqid) = IpcId::try_from(msqid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive message queue IDs are invalid");
+    };
+    let cmd = IpcControlCmd::try_from(cmd)?;
+
+    debug!(
+        "msgctl: msqid = {:?}, cmd = {:?}, buf = {:#x}",
+        msqid, cmd, buf
+    );
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    match cmd {
+        IpcControlCmd::IPC_RMID => {
+            ipc_ns.remove_msg_queue(msqid, ctx.posix_thread)?;
+        }
+        IpcControlCmd::IPC_SET => {
+            let msqid_ds = ctx.user_space().read_val::<MsqidDs>(buf)?;
+            ipc_ns.set_msg_queue(msqid, &msqid_ds, ctx.posix_thread)?;
+        }
+        IpcControlCmd::IPC_STAT => {
+            let msg_queue = ipc_ns.get_msg_queue(msqid, PermissionMode::READ, ctx.posix_thread)?;
+            let msqid_ds = msg_queue.msqid_ds(ctx.process.pid_ns());
+            ctx.user_space().write_val(buf, &msqid_ds)?;
+        }
+        _ => {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the command is not valid for message queues"
+            );
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
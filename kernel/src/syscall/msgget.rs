@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{ipc::IpcFlags, prelude::*};
+
+pub fn sys_msgget(key: i32, msgflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = IpcFlags::from_bits_truncate(msgflg.cast_unsigned());
+    let mode: u16 = (msgflg.cast_unsigned() & 0x1FF) as u16;
+
+    debug!(
+        "msgget: key = {}, flags = {:?}, mode = {:03o}",
+        key, flags, mode
+    );
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    let msqid = ipc_ns.get_or_create_msg_queue(key, flags, mode, ctx.posix_thread)?;
+
+    Ok(SyscallReturn::Return(msqid.get() as isize))
+}
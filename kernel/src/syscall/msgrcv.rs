@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{IpcId, PermissionMode, msg::MsgRcvFlags},
+    prelude::*,
+};
+
+pub fn sys_msgrcv(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: isize,
+    msgtyp: i64,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let Ok(msqid) = IpcId::try_from(msqid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive message queue IDs are invalid");
+    };
+    let mut flags = MsgRcvFlags::from_bits_truncate(msgflg.cast_unsigned());
+
+    debug!(
+        "msgrcv: msqid = {:?}, msgp = {:#x}, msgsz = {}, msgtyp = {}, flags = {:?}",
+        msqid, msgp, msgsz, msgtyp, flags
+    );
+
+    if msgsz < 0 {
+        return_errno_with_message!(Errno::EINVAL, "the buffer size is negative");
+    }
+    if flags.contains(MsgRcvFlags::MSG_COPY) {
+        // TODO: Support `MSG_COPY`, which is only used for checkpoint/restore.
+        return_errno_with_message!(Errno::ENOSYS, "MSG_COPY is not supported");
+    }
+    // Like Linux, `MSG_EXCEPT` is ignored if `msgtyp` is zero.
+    if msgtyp == 0 {
+        flags.remove(MsgRcvFlags::MSG_EXCEPT);
+    }
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    let msg_queue = ipc_ns.get_msg_queue(msqid, PermissionMode::READ, ctx.posix_thread)?;
+    let message = msg_queue.receive(msgtyp, msgsz as usize, flags, ctx.process.pid())?;
+
+    let user_space = ctx.user_space();
+    user_space.write_val(msgp, &message.mtype())?;
+    user_space.write_bytes(msgp + size_of::<i64>(), message.data())?;
+
+    Ok(SyscallReturn::Return(message.data().len() as isize))
+}
@@ -5,6 +5,8 @@ use core::time::Duration;
 use ostd::{mm::VmIo, sync::Waiter};
 
 use super::{ClockId, SyscallReturn, clock_gettime::read_clock};
+#[cfg(target_arch = "x86_64")]
+use crate::time::compat_timespec_t;
 use crate::{
     prelude::*,
     time::{
@@ -23,7 +25,7 @@ pub fn sys_nanosleep(
 ) -> Result<SyscallReturn> {
     let clockid = ClockId::CLOCK_MONOTONIC;
 
-    do_clock_nanosleep(
+    do_clock_nanosleep::<timespec_t>(
         clockid as clockid_t,
         false,
         request_timespec_addr,
@@ -41,7 +43,7 @@ pub fn sys_clock_nanosleep(
 ) -> Result<SyscallReturn> {
     let is_abs_time = (flags & TIMER_ABSTIME) != 0;
 
-    do_clock_nanosleep(
+    do_clock_nanosleep::<timespec_t>(
         clockid,
         is_abs_time,
         request_timespec_addr,
@@ -50,7 +52,45 @@ pub fn sys_clock_nanosleep(
     )
 }
 
-fn do_clock_nanosleep(
+/// The `nanosleep` system call of 32-bit programs, which uses 32-bit `timespec`s.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_nanosleep(
+    request_timespec_addr: Vaddr,
+    remain_timespec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let clockid = ClockId::CLOCK_MONOTONIC;
+
+    do_clock_nanosleep::<compat_timespec_t>(
+        clockid as clockid_t,
+        false,
+        request_timespec_addr,
+        remain_timespec_addr,
+        ctx,
+    )
+}
+
+/// The `clock_nanosleep` system call of 32-bit programs, which uses 32-bit `timespec`s.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_clock_nanosleep(
+    clockid: clockid_t,
+    flags: i32,
+    request_timespec_addr: Vaddr,
+    remain_timespec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let is_abs_time = (flags & TIMER_ABSTIME) != 0;
+
+    do_clock_nanosleep::<compat_timespec_t>(
+        clockid,
+        is_abs_time,
+        request_timespec_addr,
+        remain_timespec_addr,
+        ctx,
+    )
+}
+
+fn do_clock_nanosleep<T: Pod + Into<timespec_t> + From<Duration>>(
     clockid: clockid_t,
     is_abs_time: bool,
     request_timespec_addr: Vaddr,
@@ -58,9 +98,10 @@ fn do_clock_nanosleep(
     ctx: &Context,
 ) -> Result<SyscallReturn> {
     let request_time = {
-        let timespec = ctx
+        let timespec: timespec_t = ctx
             .user_space()
-            .read_val::<timespec_t>(request_timespec_addr)?;
+            .read_val::<T>(request_timespec_addr)?
+            .into();
         Duration::try_from(timespec)?
     };
 
@@ -124,7 +165,7 @@ fn do_clock_nanosleep(
 
             if remain_timespec_addr != 0 && !is_abs_time {
                 let remaining_duration = (start_time + duration) - end_time;
-                let remaining_timespec = timespec_t::from(remaining_duration);
+                let remaining_timespec = T::from(remaining_duration);
                 ctx.user_space()
                     .write_val(remain_timespec_addr, &remaining_timespec)?;
             }
@@ -45,11 +45,16 @@ pub fn sys_openat(
             flags,
             InodeMode::from_bits_truncate(mask_mode),
         )
-        .map_err(|err| match err.error() {
-            Errno::EINTR => Error::new(Errno::ERESTARTSYS),
-            _ => err,
-        })?
-    };
+    }
+    .and_then(|file_handle| {
+        // This may block, so it is done after the path resolver is released.
+        fs::vfs::notify::check_open_perm(&file_handle)?;
+        Ok(file_handle)
+    })
+    .map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
 
     let fd = {
         let file_table = ctx.thread_local.borrow_file_table();
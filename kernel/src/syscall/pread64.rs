@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs,
     fs::file::file_table::{RawFileDesc, get_file_fast},
@@ -30,6 +32,8 @@ pub fn sys_pread64(
         return_errno_with_message!(Errno::EINVAL, "offset + user_buf_len overflow");
     }
 
+    fs::vfs::notify::check_access_perm(&file)?;
+
     let read_len = {
         let user_space = ctx.user_space();
         let mut writer = user_space.writer(user_buf_ptr, user_buf_len)?;
@@ -42,3 +46,17 @@ pub fn sys_pread64(
 
     Ok(SyscallReturn::Return(read_len as _))
 }
+
+/// The `pread64` system call of 32-bit programs, which splits the 64-bit offset into two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_pread64(
+    raw_fd: RawFileDesc,
+    user_buf_ptr: Vaddr,
+    user_buf_len: u32,
+    offset_low: u32,
+    offset_high: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high) as i64;
+    sys_pread64(raw_fd, user_buf_ptr, user_buf_len as usize, offset, ctx)
+}
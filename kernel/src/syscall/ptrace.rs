@@ -3,17 +3,22 @@
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
-#[cfg(target_arch = "x86_64")]
-use crate::arch::ptrace as arch_ptrace;
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+use crate::{arch::ptrace as arch_ptrace, process::posix_thread::PosixThread};
 use crate::{
     prelude::*,
     process::{
+        pid_table,
         posix_thread::{
             AsPosixThread,
             alien_access::AlienAccessMode,
             ptrace::{PtraceContRequest, PtraceOptions},
         },
-        signal::{constants::SIGKILL, sig_num::SigNum, signals::user::UserSignal},
+        signal::{
+            constants::{SIGKILL, SIGSTOP},
+            sig_num::SigNum,
+            signals::{kernel::KernelSignal, user::UserSignal},
+        },
     },
     thread::{Thread, Tid},
 };
@@ -32,13 +37,68 @@ pub fn sys_ptrace(
         request, tid, addr, data
     );
 
+    // Translate the TID in the PID namespace of the current process to the global TID. An
+    // invisible TID does not match any tracee.
+    let tid = ctx.process.pid_ns().global_id_of(tid).unwrap_or(0);
+
     match request {
         PtraceRequest::PTRACE_TRACEME => {
             let current_thread = current_thread!();
             let parent_guard = ctx.process.parent().lock();
             let parent_main_thread = parent_guard.process().upgrade().unwrap().main_thread();
 
-            do_ptrace_attach(&parent_main_thread, current_thread)?;
+            do_ptrace_attach(
+                &parent_main_thread,
+                current_thread,
+                PtraceOptions::empty(),
+                false,
+            )?;
+        }
+        PtraceRequest::PTRACE_ATTACH => {
+            let tracee_thread = get_thread_to_attach(tid)?;
+
+            do_ptrace_attach(
+                &current_thread!(),
+                tracee_thread.clone(),
+                PtraceOptions::empty(),
+                false,
+            )?;
+
+            let tracee = tracee_thread.as_posix_thread().unwrap();
+            tracee.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+        }
+        PtraceRequest::PTRACE_SEIZE => {
+            if addr != 0 {
+                return_errno_with_message!(Errno::EIO, "the address must be zero");
+            }
+            let options = PtraceOptions::from_bits(data)
+                .ok_or_else(|| Error::with_message(Errno::EIO, "invalid ptrace options"))?;
+            let tracee_thread = get_thread_to_attach(tid)?;
+
+            do_ptrace_attach(&current_thread!(), tracee_thread.clone(), options, true)?;
+
+            // A seized tracee reports the group-stop that is already in effect.
+            let tracee = tracee_thread.as_posix_thread().unwrap();
+            if tracee.process().is_stopped() {
+                let _ = tracee.ptrace_interrupt();
+            }
+        }
+        PtraceRequest::PTRACE_INTERRUPT => {
+            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            tracee.ptrace_interrupt()?;
+        }
+        PtraceRequest::PTRACE_LISTEN => {
+            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            tracee.ptrace_continue(PtraceContRequest::Listen, ctx)?;
+        }
+        PtraceRequest::PTRACE_DETACH => {
+            let sig_num = parse_ptrace_injected_signal(data)?;
+
+            ctx.posix_thread.detach_from(tid, sig_num, ctx)?;
         }
         PtraceRequest::PTRACE_PEEKTEXT | PtraceRequest::PTRACE_PEEKDATA => {
             let tracee = ctx.posix_thread.get_tracee(tid)?;
@@ -109,6 +169,42 @@ pub fn sys_ptrace(
                 .read_val::<arch_ptrace::CUserRegsStruct>(data)?;
             tracee.ptrace_set_regs(regs)?;
         }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_GETFPREGS => {
+            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            let fpregs = tracee.ptrace_get_fpregs()?;
+            ctx.user_space().write_val(data, &fpregs)?;
+        }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_SETFPREGS => {
+            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            let fpregs = ctx
+                .user_space()
+                .read_val::<arch_ptrace::CUserFpregsStruct>(data)?;
+            tracee.ptrace_set_fpregs(fpregs)?;
+        }
+        #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+        PtraceRequest::PTRACE_GETREGSET => {
+            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            match addr {
+                NT_PRSTATUS => write_regset(&tracee.ptrace_get_regs()?, data, ctx)?,
+                NT_PRFPREG => write_regset(&tracee.ptrace_get_fpregs()?, data, ctx)?,
+                _ => return_errno_with_message!(Errno::EINVAL, "unsupported register set"),
+            }
+        }
+        #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+        PtraceRequest::PTRACE_SETREGSET => {
+            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            do_ptrace_setregset(tracee, addr, data, ctx)?;
+        }
         PtraceRequest::PTRACE_SYSCALL => {
             let sig_num = parse_ptrace_injected_signal(data)?;
 
@@ -145,7 +241,24 @@ pub fn sys_ptrace(
     Ok(SyscallReturn::Return(0))
 }
 
-fn do_ptrace_attach(tracer_thread: &Arc<Thread>, tracee_thread: Arc<Thread>) -> Result<()> {
+/// Returns the thread with the given global TID for `PTRACE_ATTACH` and `PTRACE_SEIZE`.
+fn get_thread_to_attach(tid: Tid) -> Result<Arc<Thread>> {
+    let thread = pid_table::pid_table_mut()
+        .get_thread(tid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the thread does not exist"))?;
+    if thread.as_posix_thread().is_none() {
+        return_errno_with_message!(Errno::EPERM, "kernel threads cannot be traced");
+    }
+
+    Ok(thread)
+}
+
+fn do_ptrace_attach(
+    tracer_thread: &Arc<Thread>,
+    tracee_thread: Arc<Thread>,
+    options: PtraceOptions,
+    is_seized: bool,
+) -> Result<()> {
     let tracer = tracer_thread.as_posix_thread().unwrap();
     let tracee = tracee_thread.as_posix_thread().unwrap();
     if !Arc::ptr_eq(&tracer.process().main_thread(), tracer_thread) {
@@ -164,7 +277,80 @@ fn do_ptrace_attach(tracer_thread: &Arc<Thread>, tracee_thread: Arc<Thread>) ->
 
     tracee.check_alien_access_from(tracer, AlienAccessMode::ATTACH_WITH_REAL_CREDS)?;
 
-    tracer.attach_to(tracer_thread, tracee_thread)
+    tracer.attach_to(tracer_thread, tracee_thread, options, is_seized)
+}
+
+/// The register set of the general-purpose registers (`struct user_regs_struct`).
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+const NT_PRSTATUS: usize = 1;
+/// The register set of the floating-point registers (`struct user_fpregs_struct`).
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+const NT_PRFPREG: usize = 2;
+
+/// The user buffer of `PTRACE_GETREGSET` and `PTRACE_SETREGSET`.
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CRegSetIoVec {
+    base: Vaddr,
+    len: usize,
+}
+
+/// Writes the register set to the user buffer described by the `iovec` at `iov_addr`.
+///
+/// The register set is truncated if the buffer is too small,
+/// and the buffer length is updated to the number of bytes written.
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+fn write_regset<T: Pod>(regset: &T, iov_addr: Vaddr, ctx: &Context) -> Result<()> {
+    let user_space = ctx.user_space();
+    let mut iov: CRegSetIoVec = user_space.read_val(iov_addr)?;
+
+    let len = iov.len.min(size_of::<T>());
+    user_space.write_bytes(iov.base, &regset.as_bytes()[..len])?;
+
+    iov.len = len;
+    user_space.write_val(iov_addr, &iov)?;
+
+    Ok(())
+}
+
+/// Reads the register set from the user buffer described by the `iovec` at `iov_addr`.
+///
+/// If the buffer is too small, the remaining registers keep their values in `regset`.
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+fn read_regset<T: Pod>(regset: &mut T, iov_addr: Vaddr, ctx: &Context) -> Result<()> {
+    let user_space = ctx.user_space();
+    let mut iov: CRegSetIoVec = user_space.read_val(iov_addr)?;
+
+    let len = iov.len.min(size_of::<T>());
+    user_space.read_bytes(iov.base, &mut regset.as_mut_bytes()[..len])?;
+
+    iov.len = len;
+    user_space.write_val(iov_addr, &iov)?;
+
+    Ok(())
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+fn do_ptrace_setregset(
+    tracee: &PosixThread,
+    note_type: usize,
+    iov_addr: Vaddr,
+    ctx: &Context,
+) -> Result<()> {
+    match note_type {
+        NT_PRSTATUS => {
+            let mut regs = tracee.ptrace_get_regs()?;
+            read_regset(&mut regs, iov_addr, ctx)?;
+            tracee.ptrace_set_regs(regs)
+        }
+        NT_PRFPREG => {
+            let mut fpregs = tracee.ptrace_get_fpregs()?;
+            read_regset(&mut fpregs, iov_addr, ctx)?;
+            tracee.ptrace_set_fpregs(fpregs)
+        }
+        _ => return_errno_with_message!(Errno::EINVAL, "unsupported register set"),
+    }
 }
 
 fn parse_ptrace_injected_signal(data: usize) -> Result<Option<SigNum>> {
@@ -212,6 +398,16 @@ enum PtraceRequest {
     /// Sets all general-purpose registers used by the thread.
     #[cfg(target_arch = "x86_64")]
     PTRACE_SETREGS = 13,
+    /// Gets all floating-point registers used by the thread.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_GETFPREGS = 14,
+    /// Sets all floating-point registers used by the thread.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_SETFPREGS = 15,
+    /// Attaches to a thread that is already running.
+    PTRACE_ATTACH = 16,
+    /// Detaches from an attached thread.
+    PTRACE_DETACH = 17,
     /// Continues and stops at the next entry to or return from syscall.
     PTRACE_SYSCALL = 24,
     /// Sets ptrace options.
@@ -220,15 +416,19 @@ enum PtraceRequest {
     PTRACE_GETEVENTMSG = 0x4201,
     /// Gets the `siginfo` of the last ptrace-stop.
     PTRACE_GETSIGINFO = 0x4202,
+    /// Gets register contents.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    PTRACE_GETREGSET = 0x4204,
+    /// Sets register contents.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    PTRACE_SETREGSET = 0x4205,
+    /// Attaches to a thread without stopping it.
+    PTRACE_SEIZE = 0x4206,
+    /// Stops a thread that is attached with `PTRACE_SEIZE`.
+    PTRACE_INTERRUPT = 0x4207,
+    /// Restarts a thread in a group-stop, but keeps it stopped.
+    PTRACE_LISTEN = 0x4208,
     // TODO: Support other operations.
-    // /// Gets all floating-point registers used by the thread.
-    // PTRACE_GETFPREGS = 14,
-    // /// Sets all floating-point registers used by the thread.
-    // PTRACE_SETFPREGS = 15,
-    // /// Attaches to a thread that is already running.
-    // PTRACE_ATTACH = 16,
-    // /// Detaches from an attached thread.
-    // PTRACE_DETACH = 17,
     // /// Gets all extended floating-point registers used by the thread.
     // PTRACE_GETFPXREGS = 18,
     // /// Sets all extended floating-point registers used by the thread.
@@ -237,8 +437,4 @@ enum PtraceRequest {
     // PTRACE_SYSEMU = 31,
     // /// Single-steps the thread, and the next syscall will not be executed.
     // PTRACE_SYSEMU_SINGLESTEP = 32,
-    // /// Gets register contents.
-    // PTRACE_GETREGSET = 0x4204,
-    // /// Sets register contents.
-    // PTRACE_SETREGSET = 0x4205,
 }
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs,
     fs::file::file_table::{RawFileDesc, get_file_fast},
@@ -40,3 +42,17 @@ pub fn sys_pwrite64(
 
     Ok(SyscallReturn::Return(write_len as _))
 }
+
+/// The `pwrite64` system call of 32-bit programs, which splits the 64-bit offset into two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_pwrite64(
+    raw_fd: RawFileDesc,
+    user_buf_ptr: Vaddr,
+    user_buf_len: u32,
+    offset_low: u32,
+    offset_high: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high) as i64;
+    sys_pwrite64(raw_fd, user_buf_ptr, user_buf_len as usize, offset, ctx)
+}
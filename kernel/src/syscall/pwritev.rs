@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs,
     fs::file::file_table::{RawFileDesc, get_file_fast},
@@ -66,6 +68,28 @@ pub fn sys_pwritev2(
     Ok(SyscallReturn::Return(res as _))
 }
 
+/// The `pwritev` system call of 32-bit programs, which splits the 64-bit offset into two halves.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_pwritev(
+    raw_fd: RawFileDesc,
+    io_vec_ptr: Vaddr,
+    io_vec_count: u32,
+    offset_low: u32,
+    offset_high: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let offset = compat_arg_u64(offset_low, offset_high).cast_signed();
+    let res = do_sys_pwritev(
+        raw_fd,
+        io_vec_ptr,
+        io_vec_count as usize,
+        offset,
+        RWFFlag::empty(),
+        ctx,
+    )?;
+    Ok(SyscallReturn::Return(res as _))
+}
+
 fn do_sys_pwritev(
     raw_fd: RawFileDesc,
     io_vec_ptr: Vaddr,
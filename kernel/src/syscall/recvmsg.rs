@@ -3,6 +3,8 @@
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::util::net::CompatUserMsgHdr;
 use crate::{
     fs::file::file_table::{RawFileDesc, get_file_fast},
     net::socket::util::SendRecvFlags,
@@ -18,6 +20,42 @@ pub fn sys_recvmsg(
 ) -> Result<SyscallReturn> {
     let user_space = ctx.user_space();
     let mut c_user_msghdr: CUserMsgHdr = user_space.read_val(user_msghdr_ptr)?;
+
+    let total_bytes = do_recvmsg(sockfd, &mut c_user_msghdr, flags, ctx)?;
+
+    user_space.write_val(user_msghdr_ptr, &c_user_msghdr)?;
+
+    Ok(SyscallReturn::Return(total_bytes as _))
+}
+
+/// The `recvmsg` system call of 32-bit programs, which uses the 32-bit `msghdr` layout.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_recvmsg(
+    sockfd: RawFileDesc,
+    user_msghdr_ptr: Vaddr,
+    flags: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+    let compat_user_msghdr: CompatUserMsgHdr = user_space.read_val(user_msghdr_ptr)?;
+    let mut c_user_msghdr = CUserMsgHdr::from(compat_user_msghdr);
+
+    let total_bytes = do_recvmsg(sockfd, &mut c_user_msghdr, flags, ctx)?;
+
+    let compat_user_msghdr = CompatUserMsgHdr::from(c_user_msghdr);
+    user_space.write_val(user_msghdr_ptr, &compat_user_msghdr)?;
+
+    Ok(SyscallReturn::Return(total_bytes as _))
+}
+
+/// Receives a message and updates the lengths in `c_user_msghdr`.
+fn do_recvmsg(
+    sockfd: RawFileDesc,
+    c_user_msghdr: &mut CUserMsgHdr,
+    flags: i32,
+    ctx: &Context,
+) -> Result<usize> {
+    let user_space = ctx.user_space();
     let flags = SendRecvFlags::from_bits_truncate(flags);
 
     debug!(
@@ -51,7 +89,5 @@ pub fn sys_recvmsg(
     c_user_msghdr.msg_controllen =
         c_user_msghdr.write_control_messages_to_user(control_messages, &user_space)? as _;
 
-    user_space.write_val(user_msghdr_ptr, &c_user_msghdr)?;
-
-    Ok(SyscallReturn::Return(total_bytes as _))
+    Ok(total_bytes)
 }
@@ -3,6 +3,8 @@
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::process::signal::c_types::compat_sigaction_t;
 use crate::{
     prelude::*,
     process::{
@@ -38,31 +40,59 @@ pub fn sys_rt_sigaction(
         return_errno_with_message!(Errno::EINVAL, "sigset size is not equal to 8");
     }
 
-    let sig_dispositions = ctx.process.sig_dispositions().lock();
-    let mut sig_dispositions = sig_dispositions.lock();
+    let new_action = if sig_action_addr != 0 {
+        let sig_action_c = ctx.user_space().read_val::<sigaction_t>(sig_action_addr)?;
+        Some(SigAction::from(sig_action_c))
+    } else {
+        None
+    };
 
-    let old_action = if sig_action_addr != 0 {
-        if sig_num == SIGKILL || sig_num == SIGSTOP {
-            return_errno_with_message!(
-                Errno::EINVAL,
-                "cannot set a new signal action for SIGKILL and SIGSTOP"
-            );
-        }
+    let old_action = do_rt_sigaction(sig_num, new_action, ctx)?;
 
-        let sig_action_c = ctx.user_space().read_val::<sigaction_t>(sig_action_addr)?;
-        let sig_action = SigAction::from(sig_action_c);
-        debug!("sig action = {:?}", sig_action);
-        if sig_action.will_ignore(sig_num) {
-            discard_signals_if_ignored(ctx, sig_num);
-        }
+    if old_sig_action_addr != 0 {
+        let old_action_c = old_action.as_c_type();
+        ctx.user_space()
+            .write_val(old_sig_action_addr, &old_action_c)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The `rt_sigaction` system call of 32-bit programs, which uses the 32-bit `sigaction` layout.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_rt_sigaction(
+    sig_num: u8,
+    sig_action_addr: Vaddr,
+    old_sig_action_addr: Vaddr,
+    sigset_size: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let sig_num = SigNum::try_from(sig_num)?;
+    debug!(
+        "signal = {}, sig_action_addr = 0x{:x}, old_sig_action_addr = 0x{:x}, sigset_size = {}",
+        sig_num.sig_name(),
+        sig_action_addr,
+        old_sig_action_addr,
+        sigset_size
+    );
+
+    if sigset_size != 8 {
+        return_errno_with_message!(Errno::EINVAL, "sigset size is not equal to 8");
+    }
 
-        sig_dispositions.set(sig_num, sig_action)?
+    let new_action = if sig_action_addr != 0 {
+        let sig_action_c = ctx
+            .user_space()
+            .read_val::<compat_sigaction_t>(sig_action_addr)?;
+        Some(SigAction::from(sigaction_t::from(sig_action_c)))
     } else {
-        sig_dispositions.get(sig_num)
+        None
     };
 
+    let old_action = do_rt_sigaction(sig_num, new_action, ctx)?;
+
     if old_sig_action_addr != 0 {
-        let old_action_c = old_action.as_c_type();
+        let old_action_c = compat_sigaction_t::from(old_action.as_c_type());
         ctx.user_space()
             .write_val(old_sig_action_addr, &old_action_c)?;
     }
@@ -70,6 +100,34 @@ pub fn sys_rt_sigaction(
     Ok(SyscallReturn::Return(0))
 }
 
+/// Sets the new signal action, if any, and returns the old one.
+fn do_rt_sigaction(
+    sig_num: SigNum,
+    new_action: Option<SigAction>,
+    ctx: &Context,
+) -> Result<SigAction> {
+    let sig_dispositions = ctx.process.sig_dispositions().lock();
+    let mut sig_dispositions = sig_dispositions.lock();
+
+    let Some(sig_action) = new_action else {
+        return Ok(sig_dispositions.get(sig_num));
+    };
+
+    if sig_num == SIGKILL || sig_num == SIGSTOP {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "cannot set a new signal action for SIGKILL and SIGSTOP"
+        );
+    }
+
+    debug!("sig action = {:?}", sig_action);
+    if sig_action.will_ignore(sig_num) {
+        discard_signals_if_ignored(ctx, sig_num);
+    }
+
+    sig_dispositions.set(sig_num, sig_action)
+}
+
 /// Discard signals if the new action is to ignore the signal.
 ///
 /// Ref: <https://elixir.bootlin.com/linux/v6.13/source/kernel/signal.c#L4323>
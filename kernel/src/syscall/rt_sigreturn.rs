@@ -7,6 +7,8 @@ use ostd::{
 };
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::process::signal::ia32;
 use crate::{
     prelude::*,
     process::{posix_thread::ContextPthreadAdminApi, signal::c_types::ucontext_t},
@@ -55,3 +57,32 @@ pub fn sys_rt_sigreturn(ctx: &Context, user_ctx: &mut UserContext) -> Result<Sys
 
     Ok(SyscallReturn::NoReturn)
 }
+
+/// The `sigreturn` system call of 32-bit programs.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_sigreturn(ctx: &Context, user_ctx: &mut UserContext) -> Result<SyscallReturn> {
+    debug!(
+        "sys_sigreturn: stack_pointer = {:#x}",
+        user_ctx.stack_pointer()
+    );
+
+    ia32::restore_frame(ctx, user_ctx)?;
+
+    Ok(SyscallReturn::NoReturn)
+}
+
+/// The `rt_sigreturn` system call of 32-bit programs.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_rt_sigreturn(ctx: &Context, user_ctx: &mut UserContext) -> Result<SyscallReturn> {
+    debug!(
+        "compat_sys_rt_sigreturn: stack_pointer = {:#x}",
+        user_ctx.stack_pointer()
+    );
+
+    let stack = ia32::restore_rt_frame(ctx, user_ctx)?;
+
+    // If the stack setting is invalid, we silently ignore the error, as in `sys_rt_sigreturn`.
+    let _ = set_new_stack(stack, ctx, user_ctx.stack_pointer());
+
+    Ok(SyscallReturn::NoReturn)
+}
@@ -9,7 +9,12 @@ use ostd::{
 };
 
 use super::SyscallReturn;
-use crate::{prelude::*, process::pid_table, thread::Tid};
+use crate::{
+    fs::cgroupfs::{CgroupMembership, cpuset_cpus_allowed},
+    prelude::*,
+    process::{pid_table, posix_thread::AsPosixThread},
+    thread::Tid,
+};
 
 pub fn sys_sched_getaffinity(
     tid: Tid,
@@ -19,7 +24,12 @@ pub fn sys_sched_getaffinity(
 ) -> Result<SyscallReturn> {
     let cpu_set = match tid {
         0 => ctx.thread.atomic_cpu_affinity().load(Ordering::Relaxed),
-        _ => match pid_table::pid_table_mut().get_thread(tid) {
+        _ => match ctx
+            .process
+            .pid_ns()
+            .global_id_of(tid)
+            .and_then(|tid| pid_table::pid_table_mut().get_thread(tid))
+        {
             Some(thread) => thread.atomic_cpu_affinity().load(Ordering::Relaxed),
             None => return Err(Error::with_message(Errno::ESRCH, "thread does not exist")),
         },
@@ -30,10 +40,6 @@ pub fn sys_sched_getaffinity(
     Ok(SyscallReturn::Return(bytes_written as isize))
 }
 
-// TODO: The manual page of `sched_setaffinity` says that if the thread is not
-// running on the CPU specified in the affinity mask, it would be migrated to
-// one of the CPUs specified in the mask. We currently do not support this
-// feature as the scheduler is not ready for migration yet.
 pub fn sys_sched_setaffinity(
     tid: Tid,
     cpuset_size: usize,
@@ -42,20 +48,24 @@ pub fn sys_sched_setaffinity(
 ) -> Result<SyscallReturn> {
     let user_cpu_set = read_cpu_set_from(ctx.user_space(), cpuset_size, cpu_set_ptr)?;
 
-    match tid {
-        0 => ctx
-            .thread
-            .atomic_cpu_affinity()
-            .store(&user_cpu_set, Ordering::Relaxed),
-        _ => match pid_table::pid_table_mut().get_thread(tid) {
-            Some(thread) => {
-                thread
-                    .atomic_cpu_affinity()
-                    .store(&user_cpu_set, Ordering::Relaxed);
-            }
-            None => return Err(Error::with_message(Errno::ESRCH, "thread does not exist")),
-        },
-    }
+    let thread = match tid {
+        0 => current_thread!(),
+        _ => ctx
+            .process
+            .pid_ns()
+            .global_id_of(tid)
+            .and_then(|tid| pid_table::pid_table_mut().get_thread(tid))
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "thread does not exist"))?,
+    };
+    let process = thread.as_posix_thread().unwrap().process();
+
+    // Hold the read lock to ensure the cpuset of the process won't change
+    // before the CPU affinity is set.
+    let _cgroup_read_guard = CgroupMembership::read_lock();
+    thread.set_user_cpu_affinity(&user_cpu_set, &cpuset_cpus_allowed(&process))?;
+
+    // If the thread is running on a CPU that is no longer allowed, it will
+    // migrate before returning to the user space.
 
     Ok(SyscallReturn::Return(0))
 }
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    security::{
+        lsm::hooks as lsm_hooks,
+        seccomp::{self, BPF_MAXINSNS, BpfProgram, SeccompData, SeccompFilterFlags, SockFilter},
+    },
+};
+
+pub fn sys_seccomp(op: u32, flags: u32, args: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("op = {}, flags = {:#x}, args = {:#x}", op, flags, args);
+
+    do_seccomp(op, flags, args, ctx)
+}
+
+pub(super) fn do_seccomp(op: u32, flags: u32, args: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let op = SeccompOp::try_from(op)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "unsupported seccomp operation"))?;
+
+    match op {
+        SeccompOp::SetModeStrict => {
+            if flags != 0 || args != 0 {
+                return_errno_with_message!(Errno::EINVAL, "invalid arguments for the strict mode");
+            }
+            seccomp::set_mode_strict(ctx)?;
+        }
+        SeccompOp::SetModeFilter => {
+            return set_mode_filter(flags, args, ctx);
+        }
+        SeccompOp::GetActionAvail => {
+            if flags != 0 {
+                return_errno_with_message!(Errno::EINVAL, "invalid flags");
+            }
+            let action = ctx.user_space().read_val::<u32>(args)?;
+            if !seccomp::is_action_available(action) {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "the action is not supported");
+            }
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn set_mode_filter(flags: u32, args: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = SeccompFilterFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid filter flags"))?;
+    if flags.intersects(SeccompFilterFlags::NEW_LISTENER | SeccompFilterFlags::WAIT_KILLABLE_RECV) {
+        return_errno_with_message!(Errno::EINVAL, "user notifications are not supported");
+    }
+
+    let user_space = ctx.user_space();
+    let fprog = user_space.read_val::<CSockFprog>(args)?;
+    if fprog.len == 0 || fprog.len as usize > BPF_MAXINSNS {
+        return_errno_with_message!(Errno::EINVAL, "the BPF program length is invalid");
+    }
+
+    // Installing filters requires either `no_new_privs` or `CAP_SYS_ADMIN`. Otherwise, an
+    // unprivileged program could confuse a privileged `set_uid` program that it executes.
+    if !ctx.posix_thread.no_new_privs()
+        && lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            ctx.thread_local.borrow_user_ns().as_ref(),
+            ctx.posix_thread,
+            CapSet::SYS_ADMIN,
+        ))
+        .is_err()
+    {
+        return_errno_with_message!(
+            Errno::EACCES,
+            "installing filters requires `no_new_privs` or `CAP_SYS_ADMIN`"
+        );
+    }
+
+    if fprog.filter == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the BPF program is null");
+    }
+    let mut filter = Vec::with_capacity(fprog.len as usize);
+    for i in 0..fprog.len as usize {
+        let insn = user_space.read_val::<SockFilter>(fprog.filter + i * size_of::<SockFilter>())?;
+        filter.push(insn);
+    }
+    let prog = BpfProgram::new(&filter, size_of::<SeccompData>() as u32)?;
+
+    let Some(failed_tid) = seccomp::set_mode_filter(prog, flags, ctx)? else {
+        return Ok(SyscallReturn::Return(0));
+    };
+
+    if flags.contains(SeccompFilterFlags::TSYNC_ESRCH) {
+        return_errno_with_message!(Errno::ESRCH, "the threads cannot be synchronized");
+    }
+    let failed_tid = ctx
+        .process
+        .pid_ns()
+        .local_id_of(failed_tid)
+        .unwrap_or_default();
+    Ok(SyscallReturn::Return(failed_tid as _))
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum SeccompOp {
+    SetModeStrict = 0,
+    SetModeFilter = 1,
+    GetActionAvail = 2,
+}
+
+/// The classic BPF program (`struct sock_fprog`).
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct CSockFprog {
+    len: u16,
+    filter: Vaddr,
+}
@@ -4,10 +4,7 @@ use ostd::mm::VmIo;
 
 use super::SyscallReturn;
 use crate::{
-    ipc::{
-        IpcControlCmd, IpcId,
-        semaphore::system_v::{PermissionMode, sem::Semaphore},
-    },
+    ipc::{IpcControlCmd, IpcId, PermissionMode, semaphore::system_v::sem::Semaphore},
     prelude::*,
     process::Pid,
 };
@@ -49,6 +46,13 @@ pub fn sys_semctl(
                 Ok(())
             })?;
         }
+        IpcControlCmd::IPC_SET => {
+            // TODO: Support `IPC_SET` for semaphore sets.
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IPC_SET is not supported for semaphore sets yet"
+            );
+        }
         IpcControlCmd::IPC_STAT => {
             ipc_ns.with_sem_set(semid, PermissionMode::READ, |sem_set| {
                 let semid_ds = sem_set.semid_ds();
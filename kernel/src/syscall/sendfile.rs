@@ -112,6 +112,8 @@ pub fn sys_sendfile(
         return_errno_with_message!(Errno::EINVAL, "in_file is a directory");
     }
 
+    fs::vfs::notify::check_access_perm(&in_file)?;
+
     // Sending to an append-only file is not allowed.
     if !outfile_is_pipe && out_file.status_flags().contains(StatusFlags::O_APPEND) {
         return_errno_with_message!(Errno::EINVAL, "out_file is opened with O_APPEND");
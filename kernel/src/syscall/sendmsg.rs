@@ -3,6 +3,8 @@
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::util::net::CompatUserMsgHdr;
 use crate::{
     fs::file::file_table::RawFileDesc,
     net::socket::{
@@ -18,9 +20,30 @@ pub fn sys_sendmsg(
     user_msghdr_ptr: Vaddr,
     flags: i32,
     ctx: &Context,
+) -> Result<SyscallReturn> {
+    let c_user_msghdr: CUserMsgHdr = ctx.user_space().read_val(user_msghdr_ptr)?;
+    do_sendmsg(sockfd, &c_user_msghdr, flags, ctx)
+}
+
+/// The `sendmsg` system call of 32-bit programs, which uses the 32-bit `msghdr` layout.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_sendmsg(
+    sockfd: RawFileDesc,
+    user_msghdr_ptr: Vaddr,
+    flags: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let c_user_msghdr: CompatUserMsgHdr = ctx.user_space().read_val(user_msghdr_ptr)?;
+    do_sendmsg(sockfd, &c_user_msghdr.into(), flags, ctx)
+}
+
+fn do_sendmsg(
+    sockfd: RawFileDesc,
+    c_user_msghdr: &CUserMsgHdr,
+    flags: i32,
+    ctx: &Context,
 ) -> Result<SyscallReturn> {
     let user_space = ctx.user_space();
-    let c_user_msghdr: CUserMsgHdr = user_space.read_val(user_msghdr_ptr)?;
     let flags = SendRecvFlags::from_bits_truncate(flags);
 
     debug!(
@@ -37,7 +60,7 @@ pub fn sys_sendmsg(
     };
     let socket = file.as_socket_or_err()?;
 
-    let total_bytes = send_one_message(socket, &c_user_msghdr, &user_space, flags)?;
+    let total_bytes = send_one_message(socket, c_user_msghdr, &user_space, flags)?;
 
     Ok(SyscallReturn::Return(total_bytes as _))
 }
@@ -18,11 +18,12 @@ use crate::{
         vfs::path::MountNamespace,
     },
     ipc::IpcNamespace,
-    net::uts_ns::UtsNamespace,
+    net::{net_ns::NetNamespace, uts_ns::UtsNamespace},
     prelude::*,
     process::{
-        CloneFlags, ContextSetNsAdminApi, NsProxy, NsProxyBuilder, PidFile,
-        check_unsupported_ns_flags, credentials::capabilities::CapSet, posix_thread::AsPosixThread,
+        CloneFlags, ContextSetNsAdminApi, NsProxy, NsProxyBuilder, PidFile, PidNamespace,
+        TimeNamespace, check_unsupported_ns_flags, credentials::capabilities::CapSet,
+        posix_thread::AsPosixThread,
     },
     security::lsm::hooks as lsm_hooks,
     syscall::SyscallReturn,
@@ -45,6 +46,15 @@ pub fn sys_setns(fd: RawFileDesc, flags: u32, ctx: &Context) -> Result<SyscallRe
         build_proxy_from_ns_file(file.as_ref(), ns_type_flags, ctx)?
     };
 
+    // Enter the new time namespace, if any.
+    let time_ns = new_ns_proxy.time_ns();
+    if !Arc::ptr_eq(
+        time_ns,
+        ctx.thread_local.borrow_ns_proxy().unwrap().time_ns(),
+    ) {
+        time_ns.enter(ctx.user_space().vmar())?;
+    }
+
     // Install the newly created `NsProxy`.
     ctx.set_ns_proxy(Arc::new(new_ns_proxy));
 
@@ -71,10 +81,10 @@ fn build_proxy_from_pid_file(
 
     check_unsupported_ns_flags(flags)?;
 
-    let target_thread = pid_file
+    let target_process = pid_file
         .process_opt()
-        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process has been reaped"))?
-        .main_thread();
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process has been reaped"))?;
+    let target_thread = target_process.main_thread();
     let target_proxy = target_thread.as_posix_thread().unwrap().ns_proxy().lock();
     let Some(target_proxy) = target_proxy.as_ref() else {
         return_errno_with_message!(Errno::ESRCH, "the target process has exited");
@@ -100,13 +110,26 @@ fn build_proxy_from_pid_file(
         set_mnt_ns(&mut builder, target_ns, ctx)?;
     }
 
+    if flags.contains(CloneFlags::CLONE_NEWNET) {
+        let target_ns = target_proxy.net_ns();
+        set_net_ns(&mut builder, target_ns, ctx)?;
+    }
+
+    if flags.contains(CloneFlags::CLONE_NEWPID) {
+        let target_ns = target_process.pid_ns();
+        set_pid_ns(&mut builder, target_ns, ctx)?;
+    }
+
+    if flags.contains(CloneFlags::CLONE_NEWTIME) {
+        let target_ns = target_proxy.time_ns();
+        set_time_ns(&mut builder, target_ns, ctx)?;
+    }
+
     if flags.contains(CloneFlags::CLONE_NEWUTS) {
         let target_ns = target_proxy.uts_ns();
         set_uts_ns(&mut builder, target_ns, ctx)?;
     }
 
-    // TODO: Support setting other namespaces from the target process.
-
     Ok(builder.build())
 }
 
@@ -141,10 +164,18 @@ fn build_proxy_from_ns_file(
         || try_apply_ns_from_inode::<MountNamespace>(inode_handle, flags, |ns| {
             set_mnt_ns(&mut builder, &ns, ctx)
         })?
+        || try_apply_ns_from_inode::<NetNamespace>(inode_handle, flags, |ns| {
+            set_net_ns(&mut builder, &ns, ctx)
+        })?
+        || try_apply_ns_from_inode::<PidNamespace>(inode_handle, flags, |ns| {
+            set_pid_ns(&mut builder, &ns, ctx)
+        })?
+        || try_apply_ns_from_inode::<TimeNamespace>(inode_handle, flags, |ns| {
+            set_time_ns(&mut builder, &ns, ctx)
+        })?
         || try_apply_ns_from_inode::<UtsNamespace>(inode_handle, flags, |ns| {
             set_uts_ns(&mut builder, &ns, ctx)
         })?;
-    // TODO: Support setting other namespaces from the ns file.
 
     if !applied {
         return_errno_with_message!(Errno::EINVAL, "invalid flags are specified with a ns file");
@@ -218,6 +249,64 @@ fn set_mnt_ns(
     Ok(())
 }
 
+fn set_net_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<NetNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    check_set_ns_perms(target_ns, ctx)?;
+
+    builder.net_ns(target_ns.clone());
+
+    Ok(())
+}
+
+fn set_pid_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<PidNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    check_set_ns_perms(target_ns, ctx)?;
+
+    // Only the PID namespace of the current process or its descendants can be entered.
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid_namespace.c#L389>
+    if !ctx.process.pid_ns().is_same_or_ancestor_of(target_ns) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the PID namespace is not a descendant of the current PID namespace"
+        );
+    }
+
+    // The PID namespace of the current process never changes. Only the namespace in which the
+    // children will be created is set.
+    builder.pid_ns_for_children(target_ns.clone());
+
+    Ok(())
+}
+
+fn set_time_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<TimeNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    // The vDSO mappings of the process are changed to reflect the new time namespace, which is
+    // not possible if other threads may be using them.
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/time/namespace.c>
+    if ctx.process.tasks().lock().as_slice().len() != 1 {
+        return_errno_with_message!(
+            Errno::EUSERS,
+            "setting a time namespace is not allowed for multi-threaded processes"
+        );
+    }
+
+    check_set_ns_perms(target_ns, ctx)?;
+
+    builder.time_ns(target_ns.clone());
+    builder.time_ns_for_children(target_ns.clone());
+
+    Ok(())
+}
+
 fn set_uts_ns(
     builder: &mut NsProxyBuilder,
     target_ns: &Arc<UtsNamespace>,
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{IpcId, PermissionMode, shm::ShmFlags},
+    prelude::*,
+};
+
+/// The alignment of the attach addresses.
+const SHMLBA: usize = PAGE_SIZE;
+
+pub fn sys_shmat(shmid: i32, shmaddr: Vaddr, shmflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    let Ok(shmid) = IpcId::try_from(shmid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive segment IDs are invalid");
+    };
+    let flags = ShmFlags::from_bits_truncate(shmflg.cast_unsigned());
+
+    debug!(
+        "shmat: shmid = {:?}, shmaddr = {:#x}, flags = {:?}",
+        shmid, shmaddr, flags
+    );
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/shm.c#L1538>
+    let addr = if shmaddr == 0 {
+        if flags.contains(ShmFlags::SHM_REMAP) {
+            return_errno_with_message!(Errno::EINVAL, "SHM_REMAP requires an address");
+        }
+        None
+    } else if shmaddr.is_multiple_of(SHMLBA) {
+        Some(shmaddr)
+    } else if flags.contains(ShmFlags::SHM_RND) {
+        let addr = shmaddr.align_down(SHMLBA);
+        if addr == 0 && flags.contains(ShmFlags::SHM_REMAP) {
+            return_errno_with_message!(Errno::EINVAL, "SHM_REMAP requires a non-zero address");
+        }
+        (addr != 0).then_some(addr)
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "the address is not aligned");
+    };
+
+    let mut required_perm = PermissionMode::READ;
+    if !flags.contains(ShmFlags::SHM_RDONLY) {
+        required_perm |= PermissionMode::WRITE;
+    }
+    if flags.contains(ShmFlags::SHM_EXEC) {
+        required_perm |= PermissionMode::EXEC;
+    }
+
+    let segment = {
+        let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+        let ipc_ns = ns_proxy.unwrap().ipc_ns();
+        ipc_ns.get_shm_segment(shmid, required_perm, ctx.posix_thread)?
+    };
+
+    let user_space = ctx.user_space();
+    let attach_addr = segment.attach(user_space.vmar(), addr, flags)?;
+
+    Ok(SyscallReturn::Return(attach_addr as isize))
+}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{IpcControlCmd, IpcId, PermissionMode, shm::shm_segment::ShmidDs},
+    prelude::*,
+};
+
+pub fn sys_shmctl(shmid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let Ok(shmid) = IpcId::try_from(shmid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive segment IDs are invalid");
+    };
+    let cmd = IpcControlCmd::try_from(cmd)?;
+
+    debug!(
+        "shmctl: shmid = {:?}, cmd = {:?}, buf = {:#x}",
+        shmid, cmd, buf
+    );
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    match cmd {
+        IpcControlCmd::IPC_RMID => {
+            ipc_ns.remove_shm_segment(shmid, ctx.posix_thread)?;
+        }
+        IpcControlCmd::IPC_SET => {
+            let shmid_ds = ctx.user_space().read_val::<ShmidDs>(buf)?;
+            ipc_ns.set_shm_segment(shmid, &shmid_ds, ctx.posix_thread)?;
+        }
+        IpcControlCmd::IPC_STAT => {
+            let segment = ipc_ns.get_shm_segment(shmid, PermissionMode::READ, ctx.posix_thread)?;
+            let shmid_ds = segment.shmid_ds(ctx.process.pid_ns());
+            ctx.user_space().write_val(buf, &shmid_ds)?;
+        }
+        _ => {
+            // TODO: Support `IPC_INFO`, `SHM_INFO`, `SHM_STAT`, `SHM_LOCK`, and `SHM_UNLOCK`.
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the command is not valid for shared memory segments"
+            );
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
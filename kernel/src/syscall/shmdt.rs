@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{ipc::shm, prelude::*};
+
+pub fn sys_shmdt(shmaddr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("shmdt: shmaddr = {:#x}", shmaddr);
+
+    let user_space = ctx.user_space();
+    shm::detach(user_space.vmar(), shmaddr)?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -3,6 +3,8 @@
 use ostd::{arch::cpu::context::UserContext, mm::VmIo, user::UserContextApi};
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use crate::process::signal::c_types::compat_stack_t;
 use crate::{
     prelude::*,
     process::signal::{SigStack, SigStackFlags, SigStackStatus, c_types::stack_t},
@@ -35,6 +37,37 @@ pub fn sys_sigaltstack(
     Ok(SyscallReturn::Return(0))
 }
 
+/// The `sigaltstack` system call of 32-bit programs, which uses the 32-bit `stack_t` layout.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_sigaltstack(
+    sig_stack_addr: Vaddr,
+    old_sig_stack_addr: Vaddr,
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> Result<SyscallReturn> {
+    debug!(
+        "sig_stack_addr = 0x{:x}, old_sig_stack_addr: 0x{:x}",
+        sig_stack_addr, old_sig_stack_addr
+    );
+
+    let sp = user_ctx.stack_pointer();
+
+    if old_sig_stack_addr != 0 {
+        let stack = compat_stack_t::from(get_old_stack(ctx, sp)?);
+        ctx.user_space()
+            .write_val::<compat_stack_t>(old_sig_stack_addr, &stack)?;
+    }
+
+    if sig_stack_addr != 0 {
+        let stack = ctx
+            .user_space()
+            .read_val::<compat_stack_t>(sig_stack_addr)?;
+        set_new_stack(stack.into(), ctx, sp)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
 fn get_old_stack(ctx: &Context, sp: usize) -> Result<stack_t> {
     let old_stack = ctx.thread_local.sig_stack().borrow();
 
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        self,
+        file::file_table::{RawFileDesc, WithFileTable, get_file_fast},
+        pipe::SpliceFlags,
+    },
+    prelude::*,
+    util::{VmReaderArray, VmWriterArray},
+};
+
+/// The maximum number of bytes that can be transferred by a single call.
+pub(super) const MAX_RW_COUNT: usize = 0x7fff_f000;
+
+pub fn sys_splice(
+    fd_in: RawFileDesc,
+    off_in_ptr: Vaddr,
+    fd_out: RawFileDesc,
+    off_out_ptr: Vaddr,
+    len: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!(
+        "fd_in = {}, off_in_ptr = {:#x}, fd_out = {}, off_out_ptr = {:#x}, len = {:#x}, flags = {:?}",
+        fd_in, off_in_ptr, fd_out, off_out_ptr, len, flags
+    );
+
+    let (in_file, out_file) = ctx
+        .thread_local
+        .borrow_file_table_mut()
+        .read_with(|inner| {
+            let in_file = inner.get_file(fd_in.try_into()?)?.clone();
+            let out_file = inner.get_file(fd_out.try_into()?)?.clone();
+            Ok::<_, Error>((in_file, out_file))
+        })?;
+
+    let mut in_offset = read_offset_from_user(off_in_ptr, ctx)?;
+    let mut out_offset = read_offset_from_user(off_out_ptr, ctx)?;
+
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    fs::vfs::notify::check_access_perm(&in_file).map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
+
+    let spliced_len = fs::pipe::splice(
+        in_file.as_ref(),
+        in_offset.as_mut(),
+        out_file.as_ref(),
+        out_offset.as_mut(),
+        len.min(MAX_RW_COUNT),
+        flags,
+    )
+    .map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
+
+    write_offset_to_user(off_in_ptr, in_offset, ctx)?;
+    write_offset_to_user(off_out_ptr, out_offset, ctx)?;
+
+    if spliced_len > 0 {
+        fs::vfs::notify::on_access(&in_file);
+        fs::vfs::notify::on_modify(&out_file);
+    }
+
+    Ok(SyscallReturn::Return(spliced_len as _))
+}
+
+pub fn sys_tee(
+    fd_in: RawFileDesc,
+    fd_out: RawFileDesc,
+    len: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!(
+        "fd_in = {}, fd_out = {}, len = {:#x}, flags = {:?}",
+        fd_in, fd_out, len, flags
+    );
+
+    let (in_file, out_file) = ctx
+        .thread_local
+        .borrow_file_table_mut()
+        .read_with(|inner| {
+            let in_file = inner.get_file(fd_in.try_into()?)?.clone();
+            let out_file = inner.get_file(fd_out.try_into()?)?.clone();
+            Ok::<_, Error>((in_file, out_file))
+        })?;
+
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    let copied_len = fs::pipe::tee(
+        in_file.as_ref(),
+        out_file.as_ref(),
+        len.min(MAX_RW_COUNT),
+        flags,
+    )
+    .map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
+
+    if copied_len > 0 {
+        fs::vfs::notify::on_access(&in_file);
+        fs::vfs::notify::on_modify(&out_file);
+    }
+
+    Ok(SyscallReturn::Return(copied_len as _))
+}
+
+pub fn sys_vmsplice(
+    fd: RawFileDesc,
+    io_vec_ptr: Vaddr,
+    io_vec_count: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!(
+        "fd = {}, io_vec_ptr = {:#x}, io_vec_count = {}, flags = {:?}",
+        fd, io_vec_ptr, io_vec_count, flags
+    );
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd.try_into()?);
+
+    let user_space = ctx.user_space();
+    // The direction is decided by the access mode: a writable pipe is filled from the user
+    // memory, and a readable pipe is drained into the user memory.
+    let res = if file.access_mode().is_writable() {
+        let mut reader_array =
+            VmReaderArray::from_user_io_vecs(&user_space, io_vec_ptr, io_vec_count)?;
+        fs::pipe::vmsplice_to_pipe(&**file, reader_array.readers_mut(), flags)
+    } else if file.access_mode().is_readable() {
+        let mut writer_array =
+            VmWriterArray::from_user_io_vecs(&user_space, io_vec_ptr, io_vec_count)?;
+        fs::pipe::vmsplice_from_pipe(&**file, writer_array.writers_mut(), flags)
+    } else {
+        return_errno_with_message!(Errno::EBADF, "the file is neither readable nor writable");
+    };
+    let len = res.map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
+
+    Ok(SyscallReturn::Return(len as _))
+}
+
+pub(super) fn read_offset_from_user(offset_ptr: Vaddr, ctx: &Context) -> Result<Option<usize>> {
+    if offset_ptr == 0 {
+        return Ok(None);
+    }
+
+    let offset: i64 = ctx.user_space().read_val(offset_ptr)?;
+    if offset < 0 {
+        return_errno_with_message!(Errno::EINVAL, "offset cannot be negative");
+    }
+
+    Ok(Some(offset as usize))
+}
+
+pub(super) fn write_offset_to_user(
+    offset_ptr: Vaddr,
+    offset: Option<usize>,
+    ctx: &Context,
+) -> Result<()> {
+    if let Some(offset) = offset {
+        ctx.user_space().write_val(offset_ptr, &(offset as i64))?;
+    }
+
+    Ok(())
+}
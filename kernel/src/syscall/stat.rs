@@ -17,15 +17,7 @@ use crate::{
 };
 
 pub fn sys_fstat(raw_fd: RawFileDesc, stat_buf_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
-    debug!("fd = {}, stat_buf_addr = 0x{:x}", raw_fd, stat_buf_ptr);
-
-    let mut file_table = ctx.thread_local.borrow_file_table_mut();
-    let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
-
-    let stat = Stat::from(file.path().metadata());
-    ctx.user_space().write_val(stat_buf_ptr, &stat)?;
-
-    Ok(SyscallReturn::Return(0))
+    do_fstat::<Stat>(raw_fd, stat_buf_ptr, ctx)
 }
 
 pub fn sys_stat(filename_ptr: Vaddr, stat_buf_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
@@ -48,6 +40,76 @@ pub fn sys_fstatat(
     stat_buf_ptr: Vaddr,
     flags: u32,
     ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_fstatat::<Stat>(dirfd, filename_ptr, stat_buf_ptr, flags, ctx)
+}
+
+#[cfg(target_arch = "x86_64")]
+pub fn sys_fstat64(
+    raw_fd: RawFileDesc,
+    stat_buf_ptr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_fstat::<Stat64>(raw_fd, stat_buf_ptr, ctx)
+}
+
+#[cfg(target_arch = "x86_64")]
+pub fn sys_stat64(
+    filename_ptr: Vaddr,
+    stat_buf_ptr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_fstatat::<Stat64>(AT_FDCWD, filename_ptr, stat_buf_ptr, 0, ctx)
+}
+
+#[cfg(target_arch = "x86_64")]
+pub fn sys_lstat64(
+    filename_ptr: Vaddr,
+    stat_buf_ptr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_fstatat::<Stat64>(
+        AT_FDCWD,
+        filename_ptr,
+        stat_buf_ptr,
+        StatFlags::AT_SYMLINK_NOFOLLOW.bits(),
+        ctx,
+    )
+}
+
+#[cfg(target_arch = "x86_64")]
+pub fn sys_fstatat64(
+    dirfd: RawFileDesc,
+    filename_ptr: Vaddr,
+    stat_buf_ptr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    do_fstatat::<Stat64>(dirfd, filename_ptr, stat_buf_ptr, flags, ctx)
+}
+
+fn do_fstat<T: Pod + From<Metadata>>(
+    raw_fd: RawFileDesc,
+    stat_buf_ptr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("fd = {}, stat_buf_addr = 0x{:x}", raw_fd, stat_buf_ptr);
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+
+    let stat = T::from(file.path().metadata());
+    ctx.user_space().write_val(stat_buf_ptr, &stat)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn do_fstatat<T: Pod + From<Metadata>>(
+    dirfd: RawFileDesc,
+    filename_ptr: Vaddr,
+    stat_buf_ptr: Vaddr,
+    flags: u32,
+    ctx: &Context,
 ) -> Result<SyscallReturn> {
     let user_space = ctx.user_space();
     let filename = user_space.read_cstring(filename_ptr, MAX_FILENAME_LEN)?;
@@ -59,7 +121,7 @@ pub fn sys_fstatat(
     );
 
     if flags.contains(StatFlags::AT_EMPTY_PATH) && filename.is_empty() {
-        return sys_fstat(dirfd, stat_buf_ptr, ctx);
+        return do_fstat::<T>(dirfd, stat_buf_ptr, ctx);
     }
 
     let path = {
@@ -76,7 +138,7 @@ pub fn sys_fstatat(
         }
     };
 
-    let stat = Stat::from(path.metadata());
+    let stat = T::from(path.metadata());
     user_space.write_val(stat_buf_ptr, &stat)?;
     Ok(SyscallReturn::Return(0))
 }
@@ -199,3 +261,78 @@ impl From<Metadata> for Stat {
         }
     }
 }
+
+/// File status of 32-bit programs; `struct stat64` in Linux.
+///
+/// This is the i386-specific version.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.9/source/arch/x86/include/uapi/asm/stat.h>.
+#[cfg(target_arch = "x86_64")]
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct Stat64 {
+    /// Device.
+    st_dev: u64,
+    /// Padding bytes.
+    __pad0: [u8; 4],
+    /// The lower 32 bits of the file serial number.
+    __st_ino: u32,
+    /// File mode.
+    st_mode: u32,
+    /// Link count.
+    st_nlink: u32,
+    /// User ID of the file's owner.
+    st_uid: u32,
+    /// Group ID of the file's group.
+    st_gid: u32,
+    /// Device number, if device.
+    st_rdev: u64,
+    /// Padding bytes.
+    __pad3: [u8; 4],
+    /// Total size, in bytes
+    st_size: i64,
+    /// Optimal block size for I/O.
+    st_blksize: u32,
+    /// Number 512-byte blocks allocated.
+    st_blocks: u64,
+    /// Time of last access.
+    st_atime: u32,
+    /// Nanoseconds of the time of last access.
+    st_atime_nsec: u32,
+    /// Time of last modification.
+    st_mtime: u32,
+    /// Nanoseconds of the time of last modification.
+    st_mtime_nsec: u32,
+    /// Time of last status change.
+    st_ctime: u32,
+    /// Nanoseconds of the time of last status change.
+    st_ctime_nsec: u32,
+    /// File serial number.
+    st_ino: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<Metadata> for Stat64 {
+    fn from(info: Metadata) -> Self {
+        Self {
+            st_dev: info.container_dev_id.as_encoded_u64(),
+            __st_ino: info.ino as u32,
+            st_mode: info.type_ as u32 | info.mode.bits() as u32,
+            st_nlink: info.nr_hard_links as _,
+            st_uid: info.uid.into(),
+            st_gid: info.gid.into(),
+            st_rdev: info.self_dev_id.map_or(0, |id| id.as_encoded_u64()),
+            st_size: info.size as i64,
+            st_blksize: info.optimal_block_size as _,
+            st_blocks: info.nr_sectors_allocated as _,
+            st_atime: info.last_access_at.as_secs() as u32,
+            st_atime_nsec: info.last_access_at.subsec_nanos(),
+            st_mtime: info.last_modify_at.as_secs() as u32,
+            st_mtime_nsec: info.last_modify_at.subsec_nanos(),
+            st_ctime: info.last_meta_change_at.as_secs() as u32,
+            st_ctime_nsec: info.last_meta_change_at.subsec_nanos(),
+            st_ino: info.ino,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::vfs::path::{AT_FDCWD, EmptyPathStr, FsPath},
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet},
+    security::lsm::hooks as lsm_hooks,
+    syscall::constants::MAX_FILENAME_LEN,
+    vm::swap::{self, SwapBacking},
+};
+
+pub fn sys_swapoff(path_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let path_name = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    debug!("path = {:?}", path_name);
+
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        UserNamespace::get_init_singleton().as_ref(),
+        ctx.posix_thread,
+        CapSet::SYS_ADMIN,
+    ))?;
+
+    let path_name = path_name.to_string_lossy();
+    let fs_path = FsPath::from_fd_at(AT_FDCWD, &path_name, EmptyPathStr::Reject)?;
+    let path = ctx
+        .thread_local
+        .borrow_fs()
+        .resolver()
+        .read()
+        .lookup(&fs_path)?;
+
+    let backing = SwapBacking::from_path(&path)?;
+    swap::swapoff(&backing)?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::vfs::path::{AT_FDCWD, EmptyPathStr, FsPath},
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet},
+    security::lsm::hooks as lsm_hooks,
+    syscall::constants::MAX_FILENAME_LEN,
+    vm::swap::{self, SwapBacking},
+};
+
+pub fn sys_swapon(path_addr: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let path_name = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    let swap_flags = SwapFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid swap flags"))?;
+    debug!("path = {:?}, flags = {:?}", path_name, swap_flags);
+
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        UserNamespace::get_init_singleton().as_ref(),
+        ctx.posix_thread,
+        CapSet::SYS_ADMIN,
+    ))?;
+
+    // Discarding is an optimization for SSDs, so it is ignored.
+    let priority = if swap_flags.contains(SwapFlags::SWAP_FLAG_PREFER) {
+        Some((flags & SWAP_FLAG_PRIO_MASK) as i16)
+    } else {
+        None
+    };
+
+    let path_name = path_name.to_string_lossy();
+    let fs_path = FsPath::from_fd_at(AT_FDCWD, &path_name, EmptyPathStr::Reject)?;
+    let (path, name) = {
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let path_resolver = fs_ref.resolver().read();
+        let path = path_resolver.lookup(&fs_path)?;
+        let name = path_resolver.make_abs_path(&path).into_string();
+        (path, name)
+    };
+
+    let backing = SwapBacking::from_path(&path)?;
+    swap::swapon(backing, name, priority)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The mask of the priority in the flags.
+const SWAP_FLAG_PRIO_MASK: u32 = 0x7fff;
+
+bitflags! {
+    struct SwapFlags: u32 {
+        const SWAP_FLAG_PRIO_MASK     = SWAP_FLAG_PRIO_MASK;
+        const SWAP_FLAG_PREFER        = 0x8000;  // Set if the priority is specified.
+        const SWAP_FLAG_DISCARD       = 0x10000; // Enable discarding of freed slots.
+        const SWAP_FLAG_DISCARD_ONCE  = 0x20000; // Discard the whole area at swapon.
+        const SWAP_FLAG_DISCARD_PAGES = 0x40000; // Discard freed pages.
+    }
+}
@@ -4,7 +4,7 @@ use aster_time::read_monotonic_time;
 use ostd::mm::VmIo;
 
 use super::SyscallReturn;
-use crate::{prelude::*, process::pid_table};
+use crate::{prelude::*, process::pid_table, vm::swap};
 
 #[padding_struct]
 #[repr(C)]
@@ -29,6 +29,8 @@ pub fn sys_sysinfo(sysinfo_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn>
         uptime: read_monotonic_time().as_secs() as i64,
         totalram: crate::vm::mem_total() as u64,
         freeram: osdk_frame_allocator::load_total_free_size() as u64,
+        totalswap: (swap::nr_total_pages() * PAGE_SIZE) as u64,
+        freeswap: (swap::nr_free_pages() * PAGE_SIZE) as u64,
         procs: pid_table::pid_table_mut().process_count() as u16,
         // `mem_unit` will always be 1 byte since Asterinas only supports
         // 64-bit CPU architectures.
@@ -39,6 +39,22 @@ fn do_sys_tgkill(tgid: Option<Pid>, tid: Tid, sig_num: u8, ctx: &Context) -> Res
         return_errno_with_message!(Errno::EINVAL, "non-positive TGIDs or TIDs are not valid");
     }
 
+    // Translate the IDs in the PID namespace of the calling process to global IDs.
+    let pid_ns = ctx.process.pid_ns();
+    let tid = pid_ns
+        .global_id_of(tid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target thread does not exist"))?;
+    let tgid = tgid
+        .map(|tgid| {
+            pid_ns.global_id_of(tgid).ok_or_else(|| {
+                Error::with_message(
+                    Errno::ESRCH,
+                    "the combination of the TGID and the TID is not valid",
+                )
+            })
+        })
+        .transpose()?;
+
     let signal = sig_num.map(|sig_num| {
         let pid = ctx.process.pid();
         let uid = ctx.posix_thread.credentials().ruid();
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{arch::tls::UserDesc, prelude::*};
+
+pub fn sys_set_thread_area(user_desc_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+    let mut user_desc = user_space.read_val::<UserDesc>(user_desc_addr)?;
+    debug!("user_desc = {:x?}", user_desc);
+
+    let tls_entries = ctx.thread_local.supp_user_context().tls_entries();
+    let mut new_tls_entries = tls_entries.get();
+    user_desc.install(&mut new_tls_entries, true)?;
+    tls_entries.set(new_tls_entries);
+
+    // The entry number may be allocated by the kernel, so it should be reported to the user.
+    user_space.write_val(user_desc_addr, &user_desc.entry_number)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_get_thread_area(user_desc_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+    let entry_number = user_space.read_val::<u32>(user_desc_addr)?;
+    debug!("entry_number = {}", entry_number);
+
+    let tls_entries = ctx.thread_local.supp_user_context().tls_entries().get();
+    let user_desc = UserDesc::read_from(&tls_entries, entry_number)?;
+    user_space.write_val(user_desc_addr, &user_desc)?;
+
+    Ok(SyscallReturn::Return(0))
+}
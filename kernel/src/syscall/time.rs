@@ -19,3 +19,20 @@ pub fn sys_time(tloc: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
 
     Ok(SyscallReturn::Return(now_as_secs as _))
 }
+
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_time(tloc: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("tloc = 0x{tloc:x}");
+
+    // Like Linux, the seconds are truncated after the year 2038.
+    let now_as_secs = {
+        let now = SystemTime::now();
+        now.duration_since(&SystemTime::UNIX_EPOCH)?.as_secs() as i32
+    };
+
+    if tloc != 0 {
+        ctx.user_space().write_val(tloc, &now_as_secs)?;
+    }
+
+    Ok(SyscallReturn::Return(now_as_secs as _))
+}
@@ -42,7 +42,9 @@ pub fn sys_timer_settime(
         let timeout = if (flags & TIMER_ABSTIME) == 0 {
             Timeout::After(expire_time)
         } else {
-            Timeout::When(expire_time)
+            let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+            let time_ns = ns_proxy.unwrap().time_ns();
+            Timeout::When(time_ns.to_host_timer_time(&timer, expire_time))
         };
         timer_guard.set_timeout(timeout);
     }
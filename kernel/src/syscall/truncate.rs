@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
+#[cfg(target_arch = "x86_64")]
+use super::compat_arg_u64;
 use crate::{
     fs,
     fs::{
@@ -44,6 +46,42 @@ pub fn sys_truncate(path_ptr: Vaddr, len: isize, ctx: &Context) -> Result<Syscal
     Ok(SyscallReturn::Return(0))
 }
 
+/// The `ftruncate` system call of 32-bit programs, which uses a 32-bit signed length.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_ftruncate(raw_fd: RawFileDesc, len: i32, ctx: &Context) -> Result<SyscallReturn> {
+    sys_ftruncate(raw_fd, len as isize, ctx)
+}
+
+/// The `truncate` system call of 32-bit programs, which uses a 32-bit signed length.
+#[cfg(target_arch = "x86_64")]
+pub fn compat_sys_truncate(path_ptr: Vaddr, len: i32, ctx: &Context) -> Result<SyscallReturn> {
+    sys_truncate(path_ptr, len as isize, ctx)
+}
+
+/// The `ftruncate64` system call of 32-bit programs, which splits the 64-bit length into two
+/// halves.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_ftruncate64(
+    raw_fd: RawFileDesc,
+    len_low: u32,
+    len_high: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    sys_ftruncate(raw_fd, compat_arg_u64(len_low, len_high) as isize, ctx)
+}
+
+/// The `truncate64` system call of 32-bit programs, which splits the 64-bit length into two
+/// halves.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_truncate64(
+    path_ptr: Vaddr,
+    len_low: u32,
+    len_high: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    sys_truncate(path_ptr, compat_arg_u64(len_low, len_high) as isize, ctx)
+}
+
 #[inline]
 fn check_length(len: isize, ctx: &Context) -> Result<()> {
     if len < 0 {
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file::{CreationFlags, StatusFlags, file_table::FdFlags},
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet},
+    security::lsm::hooks as lsm_hooks,
+    vm::userfaultfd::UserfaultfdFile,
+};
+
+pub fn sys_userfaultfd(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = UserfaultfdFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("flags = {:?}", flags);
+
+    // Handling page faults in kernel mode requires `CAP_SYS_PTRACE`, since the
+    // user space can stall the kernel at any user memory access.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/userfaultfd.c>
+    let is_user_mode_only = flags.contains(UserfaultfdFlags::UFFD_USER_MODE_ONLY);
+    if !is_user_mode_only {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            UserNamespace::get_init_singleton().as_ref(),
+            ctx.posix_thread,
+            CapSet::SYS_PTRACE,
+        ))?;
+    }
+
+    let file = UserfaultfdFile::new(
+        ctx.user_space().vmar(),
+        flags.contains(UserfaultfdFlags::O_NONBLOCK),
+        is_user_mode_only,
+    );
+    let fd_flags = if flags.contains(UserfaultfdFlags::O_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+
+    let file_table = ctx.thread_local.borrow_file_table();
+    let fd = file_table.unwrap().write().insert(Arc::new(file), fd_flags);
+    Ok(SyscallReturn::Return(fd.into()))
+}
+
+bitflags! {
+    struct UserfaultfdFlags: u32 {
+        const UFFD_USER_MODE_ONLY = 1;
+        const O_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+        const O_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+    }
+}
@@ -2,7 +2,10 @@
 
 use ostd::mm::VmIo;
 
-use super::{SyscallReturn, getrusage::rusage_t};
+use super::{
+    SyscallReturn,
+    getrusage::{rusage_t, write_rusage_to_user},
+};
 use crate::{
     prelude::*,
     process::{ProcessFilter, WaitOptions, WaitStatus, do_wait, posix_thread::AsPosixThread},
@@ -22,7 +25,7 @@ pub fn sys_wait4(
         wait_pid as i32, status_ptr, wait_options
     );
     debug!("wait4 current pid = {}", ctx.process.pid());
-    let process_filter = ProcessFilter::from_id(wait_pid as _)?;
+    let process_filter = ProcessFilter::from_id(wait_pid as _, ctx)?;
 
     if wait_options.intersects(WaitOptions::WSTOPPED | WaitOptions::WCONTINUED)
         && wait_options.contains(WaitOptions::WNOWAIT)
@@ -38,11 +41,11 @@ pub fn sys_wait4(
             Errno::EINTR => Error::new(Errno::ERESTARTSYS),
             _ => err,
         })?;
-    let Some(wait_status) = wait_status else {
+    let Some((wait_status, return_pid)) = wait_status else {
         return Ok(SyscallReturn::Return(0 as _));
     };
 
-    let (return_pid, status_code) = (wait_status.pid(), calculate_status_code(&wait_status));
+    let status_code = calculate_status_code(&wait_status);
     if status_ptr != 0 {
         ctx.user_space().write_val(status_ptr as _, &status_code)?;
     }
@@ -54,7 +57,7 @@ pub fn sys_wait4(
             ..Default::default()
         };
 
-        ctx.user_space().write_val(rusage_addr, &rusage)?;
+        write_rusage_to_user(&rusage, rusage_addr, ctx)?;
     }
 
     Ok(SyscallReturn::Return(return_pid as _))
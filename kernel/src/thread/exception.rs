@@ -8,8 +8,14 @@ use ostd::{arch::cpu::context::UserContext, task::Task};
 
 use crate::{
     prelude::*,
-    process::signal::signals::fault::FaultSignal,
-    vm::vmar::{PageFaultInfo, Vmar},
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{HandlePendingSignal, signals::fault::FaultSignal},
+    },
+    vm::{
+        oom::{OomConstraint, out_of_memory},
+        vmar::{PageFaultInfo, Vmar},
+    },
 };
 
 pub(super) fn handle_exception(ctx: &Context, user_ctx: &UserContext, exception: CpuException) {
@@ -18,7 +24,18 @@ pub(super) fn handle_exception(ctx: &Context, user_ctx: &UserContext, exception:
     if let Ok(page_fault_info) = PageFaultInfo::try_from(&exception) {
         let user_space = ctx.user_space();
         let vmar = user_space.vmar();
-        if handle_page_fault_from_vmar(vmar, &page_fault_info).is_ok() {
+        match handle_page_fault_from_vmar(vmar, &page_fault_info.user_mode()) {
+            Ok(()) => return,
+            // The page fault is interrupted by a signal (e.g., when waiting for
+            // a userfaultfd to resolve it). The faulting instruction will be
+            // retried after the signal is handled.
+            Err(err) if err.error() == Errno::EINTR => return,
+            Err(_) => (),
+        }
+
+        // The current process has been killed by the OOM killer. There is no
+        // need to send a fault signal since it will exit soon.
+        if ctx.has_pending_sigkill() {
             return;
         }
     }
@@ -29,15 +46,33 @@ pub(super) fn handle_exception(ctx: &Context, user_ctx: &UserContext, exception:
 }
 
 /// Handles the page fault occurs in the VMAR.
-fn handle_page_fault_from_vmar(vmar: &Vmar, page_fault_info: &PageFaultInfo) -> Result<(), ()> {
-    if let Err(e) = vmar.handle_page_fault(page_fault_info) {
-        warn!(
-            "page fault handler failed: info: {:#x?}, err: {:?}",
-            page_fault_info, e
-        );
-        return Err(());
+///
+/// If the page fault fails due to insufficient memory, the OOM killer is
+/// invoked to free memory, and the page fault is retried unless the current
+/// process itself is killed. If the memory cgroup of the current process has
+/// reached its limit, the OOM killer only kills processes in the cgroup.
+fn handle_page_fault_from_vmar(vmar: &Vmar, page_fault_info: &PageFaultInfo) -> Result<()> {
+    loop {
+        let Err(e) = vmar.handle_page_fault(page_fault_info) else {
+            return Ok(());
+        };
+
+        if e.error() == Errno::ENOMEM && out_of_memory(&OomConstraint::for_process(&current!())) {
+            let current = current_thread!();
+            if !current.as_posix_thread().unwrap().has_pending_sigkill() {
+                continue;
+            }
+            return Err(e);
+        }
+
+        if e.error() != Errno::EINTR {
+            warn!(
+                "page fault handler failed: info: {:#x?}, err: {:?}",
+                page_fault_info, e
+            );
+        }
+        return Err(e);
     }
-    Ok(())
 }
 
 /// A trait that converts CPU exceptions into fault signals.
@@ -78,5 +113,5 @@ pub(super) fn page_fault_handler(info: &CpuException) -> Result<(), ()> {
     }
 
     let user_space = CurrentUserSpace::new(thread_local);
-    handle_page_fault_from_vmar(user_space.vmar(), &info.try_into().unwrap())
+    handle_page_fault_from_vmar(user_space.vmar(), &info.try_into().unwrap()).map_err(|_| ())
 }
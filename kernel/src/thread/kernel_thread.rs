@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::sync::atomic::{AtomicBool, Ordering};
+
 use ostd::{
     cpu::CpuSet,
+    sync::Waker,
     task::{Task, TaskOptions},
 };
 
@@ -12,7 +15,70 @@ use crate::{
 };
 
 /// The inner data of a kernel thread.
-struct KernelThread;
+pub struct KernelThread {
+    /// Whether the thread has been interrupted.
+    is_interrupted: AtomicBool,
+    /// The waker of the thread if it is paused.
+    paused_waker: SpinLock<Option<Arc<Waker>>>,
+}
+
+impl KernelThread {
+    fn new() -> Self {
+        Self {
+            is_interrupted: AtomicBool::new(false),
+            paused_waker: SpinLock::new(None),
+        }
+    }
+
+    /// Interrupts the thread.
+    ///
+    /// If the thread is paused by a `pause`-family method, the method will fail with `EINTR`.
+    /// So will all the later `pause`-family methods until [`Self::clear_interrupt`] is called.
+    ///
+    /// Unlike POSIX threads, kernel threads can only be interrupted by their owners, which
+    /// know what the threads are waiting for.
+    pub fn interrupt(&self) {
+        self.is_interrupted.store(true, Ordering::Release);
+
+        if let Some(waker) = self.paused_waker.lock().as_ref() {
+            waker.wake_up();
+        }
+    }
+
+    /// Clears the interruption set by [`Self::interrupt`].
+    pub fn clear_interrupt(&self) {
+        self.is_interrupted.store(false, Ordering::Release);
+    }
+
+    /// Returns whether the thread has been interrupted.
+    pub fn is_interrupted(&self) -> bool {
+        self.is_interrupted.load(Ordering::Acquire)
+    }
+
+    /// Sets the waker to be woken up when the thread is interrupted.
+    pub fn set_paused_waker(&self, waker: Arc<Waker>) {
+        let mut paused_waker = self.paused_waker.lock();
+        assert!(paused_waker.is_none());
+        *paused_waker = Some(waker);
+    }
+
+    /// Clears the waker set by [`Self::set_paused_waker`].
+    pub fn clear_paused_waker(&self) {
+        *self.paused_waker.lock() = None;
+    }
+}
+
+/// A trait to provide the `as_kernel_thread` method for threads.
+pub trait AsKernelThread {
+    /// Returns the associated [`KernelThread`].
+    fn as_kernel_thread(&self) -> Option<&KernelThread>;
+}
+
+impl AsKernelThread for Thread {
+    fn as_kernel_thread(&self) -> Option<&KernelThread> {
+        self.data().downcast_ref::<KernelThread>()
+    }
+}
 
 /// Options to create or spawn a new kernel thread.
 pub struct ThreadOptions {
@@ -61,7 +127,7 @@ impl ThreadOptions {
 
         Arc::new_cyclic(|weak_task| {
             let thread = {
-                let kernel_thread = KernelThread;
+                let kernel_thread = KernelThread::new();
                 let cpu_affinity = self.cpu_affinity;
                 let sched_policy = self.sched_policy;
                 Arc::new(Thread::new(
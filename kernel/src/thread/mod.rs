@@ -83,7 +83,12 @@ pub struct Thread {
     /// Thread status
     is_exited: AtomicBool,
     /// Thread CPU affinity
+    ///
+    /// This is the CPU affinity requested by the user, restricted to the CPUs
+    /// that are allowed by the cpuset of the thread.
     cpu_affinity: AtomicCpuSet,
+    /// The CPU affinity requested by the user, e.g., via `sched_setaffinity`.
+    user_cpu_affinity: AtomicCpuSet,
     sched_attr: SchedAttr,
 }
 
@@ -99,6 +104,7 @@ impl Thread {
             task,
             data: Box::new(data),
             is_exited: AtomicBool::new(false),
+            user_cpu_affinity: AtomicCpuSet::new(cpu_affinity.clone()),
             cpu_affinity: AtomicCpuSet::new(cpu_affinity),
             sched_attr: SchedAttr::new(sched_policy),
         }
@@ -138,6 +144,75 @@ impl Thread {
         &self.cpu_affinity
     }
 
+    /// Returns the CPU affinity requested by the user.
+    pub fn user_cpu_affinity(&self) -> CpuSet {
+        self.user_cpu_affinity.load(Ordering::Relaxed)
+    }
+
+    /// Sets the CPU affinity requested by the user.
+    ///
+    /// The CPU affinity of the thread is set to the CPUs that are both
+    /// requested and in `allowed_cpus`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EINVAL`] if none of the requested CPUs is allowed.
+    ///
+    /// [`EINVAL`]: crate::error::Errno::EINVAL
+    pub fn set_user_cpu_affinity(
+        &self,
+        cpu_affinity: &CpuSet,
+        allowed_cpus: &CpuSet,
+    ) -> Result<()> {
+        let effective = intersect_cpus(cpu_affinity, allowed_cpus);
+        if effective.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "none of the requested CPUs is allowed");
+        }
+
+        self.user_cpu_affinity
+            .store(cpu_affinity, Ordering::Relaxed);
+        self.cpu_affinity.store(&effective, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Restricts the CPU affinity of the thread to `allowed_cpus`.
+    ///
+    /// The CPU affinity is set to the CPUs that are both requested by the user
+    /// and in `allowed_cpus`. If there are no such CPUs, the requested CPU
+    /// affinity is ignored and all CPUs in `allowed_cpus` are used.
+    pub fn restrict_cpu_affinity(&self, allowed_cpus: &CpuSet) {
+        let user_cpu_affinity = self.user_cpu_affinity.load(Ordering::Relaxed);
+        let effective = intersect_cpus(&user_cpu_affinity, allowed_cpus);
+        if effective.is_empty() {
+            self.cpu_affinity.store(allowed_cpus, Ordering::Relaxed);
+        } else {
+            self.cpu_affinity.store(&effective, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns whether the thread is running on a CPU outside of its CPU affinity.
+    ///
+    /// This method should only be called by the thread itself.
+    pub fn is_on_disallowed_cpu(&self) -> bool {
+        let cpu = CpuId::current_racy();
+        !self.cpu_affinity.contains(cpu, Ordering::Relaxed)
+    }
+
+    /// Migrates the current thread to a CPU in its CPU affinity, if it is
+    /// running on a CPU outside of its CPU affinity.
+    ///
+    /// This method should only be called by the thread itself, without holding
+    /// any locks.
+    pub fn migrate_if_disallowed(&self) {
+        if !self.is_on_disallowed_cpu() {
+            return;
+        }
+
+        // The scheduler selects a CPU in the CPU affinity of the thread when
+        // it is enqueued again.
+        Task::migrate_current();
+    }
+
     pub fn sched_attr(&self) -> &SchedAttr {
         &self.sched_attr
     }
@@ -178,3 +253,14 @@ impl AsThread for Task {
         self.data().downcast_ref::<Arc<Thread>>()
     }
 }
+
+/// Returns the CPUs that are in both `cpus` and `other`.
+fn intersect_cpus(cpus: &CpuSet, other: &CpuSet) -> CpuSet {
+    let mut intersection = cpus.clone();
+    for cpu in cpus.iter() {
+        if !other.contains(cpu) {
+            intersection.remove(cpu);
+        }
+    }
+    intersection
+}
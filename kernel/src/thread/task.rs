@@ -12,6 +12,7 @@ use super::{Thread, oops};
 use crate::{
     context::current_userspace,
     cpu::LinuxAbi,
+    fs::cgroupfs::throttle_memory_over_high,
     prelude::*,
     process::{
         posix_thread::{AsPosixThread, FIRST_POSIX_TID, ThreadLocal, ptrace::PtraceStopResult},
@@ -51,7 +52,11 @@ pub fn create_new_user_task(
         if is_userspace_vaddr(child_tid_ptr) {
             // At this point, we can do almost nothing if the address is not valid and the store
             // operation fails. So we ignore the error here.
-            let _ = current_userspace!().write_val(child_tid_ptr, &current_posix_thread.tid());
+            let child_tid = current_process
+                .pid_ns()
+                .local_id_of(current_posix_thread.tid())
+                .unwrap();
+            let _ = current_userspace!().write_val(child_tid_ptr, &child_tid);
         }
 
         let ctx = Context {
@@ -62,13 +67,26 @@ pub fn create_new_user_task(
             task: &current_task,
         };
 
-        let has_kernel_event_fn = || ctx.has_pending();
+        let has_kernel_event_fn = || {
+            ctx.has_pending() || current_thread.is_on_disallowed_cpu() || ctx.process.is_freezing()
+        };
 
         // The startup method is only executed when the first user thread starts up.
         if ctx.posix_thread.tid() == FIRST_POSIX_TID {
             crate::init::on_first_process_startup(&ctx);
         }
 
+        // A new thread may be created in a frozen cgroup.
+        if park_while_freezing(&ctx, &stop_waiter).is_err() {
+            handle_pending_signal(user_mode.context_mut(), &ctx);
+        }
+
+        // A new thread may have signals pending before it runs any user code (e.g., a new thread
+        // that is automatically traced starts with a `SIGSTOP`).
+        if ctx.has_pending() {
+            handle_pending_signal(user_mode.context_mut(), &ctx);
+        }
+
         while !current_thread.is_exited() {
             // Execute the user code
             let return_reason = user_mode.execute(has_kernel_event_fn);
@@ -85,11 +103,18 @@ pub fn create_new_user_task(
                     ctx.thread_local
                         .set_orig_syscall_ret(Some(user_ctx.syscall_ret()));
 
-                    let res = ctx.posix_thread.ptrace_may_stop_on_syscall(&ctx, user_ctx);
-                    if !matches!(res, PtraceStopResult::Interrupted) {
-                        handle_syscall(&ctx, user_ctx);
+                    #[cfg(target_arch = "x86_64")]
+                    let is_prepared = crate::arch::vdso32::prepare_ia32_syscall(&ctx, user_ctx);
+                    #[cfg(not(target_arch = "x86_64"))]
+                    let is_prepared = true;
+
+                    if is_prepared {
+                        let res = ctx.posix_thread.ptrace_may_stop_on_syscall(&ctx, user_ctx);
+                        if !matches!(res, PtraceStopResult::Interrupted) {
+                            handle_syscall(&ctx, user_ctx);
 
-                        ctx.posix_thread.ptrace_may_stop_on_syscall(&ctx, user_ctx);
+                            ctx.posix_thread.ptrace_may_stop_on_syscall(&ctx, user_ctx);
+                        }
                     }
                 }
                 ReturnReason::KernelEvent => {
@@ -102,6 +127,14 @@ pub fn create_new_user_task(
                 break;
             }
 
+            // Throttle the thread if its memory cgroup uses too much memory
+            if ctx.thread_local.take_memcg_over_high() {
+                throttle_memory_over_high(&current_process);
+            }
+
+            // Migrate the thread if its CPU affinity has changed
+            current_thread.migrate_if_disallowed();
+
             // Handle signals
             handle_pending_signal(user_ctx, &ctx);
 
@@ -113,13 +146,22 @@ pub fn create_new_user_task(
             // We need to further investigate Linux behavior regarding which signals should be handled
             // when the thread is stopped.
             while !current_thread.is_exited() && ctx.process.is_stopped() {
+                // A traced thread reports the group-stop to its tracer, which may restart it.
+                if ctx.posix_thread.ptrace_group_stop(&ctx, user_ctx) {
+                    break;
+                }
+
                 let _ = stop_waiter.pause_until_by(
                     || (!ctx.process.is_stopped()).then_some(()),
-                    // We currently do not support ptrace.
                     PauseReason::StopBySignal,
                 );
                 handle_pending_signal(user_ctx, &ctx);
             }
+
+            // Park the thread if its cgroup is being frozen
+            if park_while_freezing(&ctx, &stop_waiter).is_err() {
+                handle_pending_signal(user_ctx, &ctx);
+            }
         }
     };
 
@@ -135,3 +177,18 @@ pub fn create_new_user_task(
     .build()
     .expect("spawn task failed")
 }
+
+/// Parks the current thread until its cgroup is thawed.
+///
+/// Unlike a job-control stop, freezing is invisible to signals. The thread
+/// can only be woken up early by `SIGKILL`, in which case an error is returned.
+fn park_while_freezing(ctx: &Context, waiter: &Waiter) -> Result<()> {
+    if ctx.thread.is_exited() || !ctx.process.is_freezing() {
+        return Ok(());
+    }
+
+    waiter.pause_until_by(
+        || (!ctx.process.is_freezing()).then_some(()),
+        PauseReason::Freeze,
+    )
+}
@@ -131,6 +131,54 @@ impl TryFrom<timeval_t> for Duration {
     }
 }
 
+/// The `timespec` of 32-bit programs with 32-bit seconds (`struct old_timespec32`).
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct compat_timespec_t {
+    pub sec: i32,
+    pub nsec: i32,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<compat_timespec_t> for timespec_t {
+    fn from(timespec: compat_timespec_t) -> timespec_t {
+        timespec_t {
+            sec: timespec.sec as time_t,
+            nsec: timespec.nsec as i64,
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<Duration> for compat_timespec_t {
+    fn from(duration: Duration) -> compat_timespec_t {
+        // Like Linux, the seconds are truncated after the year 2038.
+        let sec = duration.as_secs() as i32;
+        let nsec = duration.subsec_nanos() as i32;
+        compat_timespec_t { sec, nsec }
+    }
+}
+
+/// The `timeval` of 32-bit programs with 32-bit seconds (`struct old_timeval32`).
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct compat_timeval_t {
+    pub sec: i32,
+    pub usec: i32,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<timeval_t> for compat_timeval_t {
+    fn from(timeval: timeval_t) -> compat_timeval_t {
+        compat_timeval_t {
+            sec: timeval.sec as i32,
+            usec: timeval.usec as i32,
+        }
+    }
+}
+
 /// The various flags for setting POSIX.1b interval timers:
 pub const TIMER_ABSTIME: i32 = 0x01;
 
@@ -352,6 +352,14 @@ impl<const MAGIC: u8, const NR: u8, const IS_MODERN: bool, T: Pod>
         self.with_data_ptr_unchecked_access(|ptr| ptr.write(val))?;
         Ok(())
     }
+
+    /// Reads the ioctl argument from userspace.
+    ///
+    /// This should only be used for the ioctls that are defined with `_IOR` in Linux but take
+    /// input arguments, such as `UFFDIO_WAKE`.
+    pub fn read(&self) -> Result<T> {
+        Ok(self.with_data_ptr_unchecked_access(|ptr| ptr.read())?)
+    }
 }
 
 impl DataSpec for OutData<[u8]> {
@@ -389,7 +397,6 @@ impl<const MAGIC: u8, const NR: u8, const IS_MODERN: bool, T: Pod>
     Ioctl<MAGIC, NR, IS_MODERN, InOutData<T>>
 {
     /// Reads the ioctl argument from userspace.
-    #[expect(dead_code)]
     pub fn read(&self) -> Result<T> {
         self.with_data_ptr(|ptr| Ok(ptr.read()?))
     }
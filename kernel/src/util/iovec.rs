@@ -3,9 +3,13 @@
 use ostd::{
     Error as OstdError,
     mm::{Infallible, VmSpace},
+    task::Task,
 };
 
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    process::posix_thread::{AsThreadLocal, ThreadLocal},
+};
 
 /// A kernel space I/O vector.
 #[derive(Clone, Copy, Debug)]
@@ -26,6 +30,23 @@ struct UserIoVec {
     len: isize,
 }
 
+/// A user space I/O vector of 32-bit programs (`struct compat_iovec`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CompatUserIoVec {
+    base: u32,
+    len: i32,
+}
+
+impl From<CompatUserIoVec> for UserIoVec {
+    fn from(value: CompatUserIoVec) -> Self {
+        Self {
+            base: value.base as Vaddr,
+            len: value.len as isize,
+        }
+    }
+}
+
 impl TryFrom<UserIoVec> for IoVec {
     type Error = Error;
 
@@ -88,13 +109,26 @@ fn copy_iovs_and_convert<'a, T: 'a>(
 
     let vm_space = user_space.vmar().vm_space();
 
+    // The I/O vectors of 32-bit programs are in a different layout.
+    let is_compat = Task::current()
+        .and_then(|task| task.as_thread_local().map(ThreadLocal::in_compat_syscall))
+        .unwrap_or(false);
+
     let mut v = Vec::with_capacity(count);
     let mut max_len = MAX_TOTAL_IOV_BYTES;
 
     for idx in 0..count {
         let mut iov = {
-            let addr = start_addr + idx * size_of::<UserIoVec>();
-            let uiov: UserIoVec = vm_space.reader(addr, size_of::<UserIoVec>())?.read_val()?;
+            let uiov: UserIoVec = if is_compat {
+                let addr = start_addr + idx * size_of::<CompatUserIoVec>();
+                let compat_uiov: CompatUserIoVec = vm_space
+                    .reader(addr, size_of::<CompatUserIoVec>())?
+                    .read_val()?;
+                compat_uiov.into()
+            } else {
+                let addr = start_addr + idx * size_of::<UserIoVec>();
+                vm_space.reader(addr, size_of::<UserIoVec>())?.read_val()?
+            };
             IoVec::try_from(uiov)?
         };
 
@@ -172,6 +206,23 @@ impl<'a> VmWriterArray<'a> {
     }
 }
 
+/// Reads user-provided I/O vector buffers as `(base, len)` pairs.
+///
+/// Unlike [`VmReaderArray`] and [`VmWriterArray`], the returned buffers are not bound to the
+/// current [`VmSpace`], so they can be accessed later from another thread (e.g., via
+/// [`Vmar::read_alien`]). Empty buffers are filtered out.
+///
+/// [`Vmar::read_alien`]: crate::vm::vmar::Vmar::read_alien
+pub fn read_io_vecs_from_user<'a>(
+    user_space: &'a CurrentUserSpace<'a>,
+    start_addr: Vaddr,
+    count: usize,
+) -> Result<Box<[(Vaddr, usize)]>> {
+    copy_iovs_and_convert(user_space, start_addr, count, |iov, _| {
+        Ok((iov.base, iov.len))
+    })
+}
+
 /// Trait defining the read behavior for a collection of [`VmReader`]s.
 pub trait MultiRead: ReadCString {
     /// Reads the exact number of bytes required to exhaust `self` or fill `writer`,
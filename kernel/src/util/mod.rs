@@ -10,6 +10,6 @@ mod read_cstring;
 pub mod ring_buffer;
 
 pub use copy_compact::CopyCompat;
-pub use iovec::{MultiRead, MultiWrite, VmReaderArray, VmWriterArray};
+pub use iovec::{MultiRead, MultiWrite, VmReaderArray, VmWriterArray, read_io_vecs_from_user};
 pub use padded::padded;
 pub use read_cstring::ReadCString;
@@ -281,6 +281,25 @@ pub fn write_socket_addr_with_max_len(
     Ok(actual_len as i32)
 }
 
+/// Converts a socket address to the bytes of the corresponding Linux C structure.
+///
+/// Unlike [`write_socket_addr_with_max_len`], this method does not access the current user space,
+/// so it can be used when the socket address is written to the user space of another process.
+///
+/// # Panics
+///
+/// This method will panic if the socket address cannot be validly mapped to the corresponding
+/// Linux C structures. See [`write_socket_addr_with_max_len`] for details.
+pub fn socket_addr_to_c_bytes(socket_addr: &SocketAddr) -> Vec<u8> {
+    match socket_addr {
+        SocketAddr::IPv4(addr, port) => CSocketAddrInet::from((*addr, *port)).as_bytes().to_vec(),
+        SocketAddr::IPv6(addr, port) => CSocketAddrInet6::from((*addr, *port)).as_bytes().to_vec(),
+        SocketAddr::Unix(addr) => unix::into_c_bytes_and(addr, |bytes| bytes.to_vec()),
+        SocketAddr::Netlink(addr) => CSocketAddrNetlink::from(*addr).as_bytes().to_vec(),
+        SocketAddr::Vsock(addr) => CSocketAddrVm::from(*addr).as_bytes().to_vec(),
+    }
+}
+
 // Utility function to write a C socket address to user space.
 fn write_c_socket_address_util<TCSockAddr, TSockAddr>(
     addr: TSockAddr,
@@ -1,8 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub use family::{
-    CSocketAddrFamily, read_socket_addr_from_user, write_socket_addr_to_user,
-    write_socket_addr_with_max_len,
+    CSocketAddrFamily, read_socket_addr_from_user, socket_addr_to_c_bytes,
+    write_socket_addr_to_user, write_socket_addr_with_max_len,
 };
 
 mod family;
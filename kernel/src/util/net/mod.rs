@@ -5,8 +5,10 @@ mod options;
 mod socket;
 
 pub use addr::{
-    CSocketAddrFamily, read_socket_addr_from_user, write_socket_addr_to_user,
-    write_socket_addr_with_max_len,
+    CSocketAddrFamily, read_socket_addr_from_user, socket_addr_to_c_bytes,
+    write_socket_addr_to_user, write_socket_addr_with_max_len,
 };
 pub use options::{CSocketOptionLevel, new_raw_socket_option};
+#[cfg(target_arch = "x86_64")]
+pub use socket::CompatUserMsgHdr;
 pub use socket::{CUserMsgHdr, Protocol, SOCK_TYPE_MASK, SockFlags, SockType};
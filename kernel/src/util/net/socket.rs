@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
+#[cfg(target_arch = "x86_64")]
+use ostd::task::Task;
+
 use super::read_socket_addr_from_user;
+#[cfg(target_arch = "x86_64")]
+use crate::process::posix_thread::{AsThreadLocal, ThreadLocal};
 use crate::{
     net::socket::util::{ControlMessage, SocketAddr},
     prelude::*,
@@ -131,6 +136,10 @@ impl CUserMsgHdr {
         }
 
         let mut reader = user_space.reader(self.msg_control, self.msg_controllen)?;
+        #[cfg(target_arch = "x86_64")]
+        if in_compat_syscall() {
+            return ControlMessage::read_all_from_compat(&mut reader);
+        }
         let control_messages = ControlMessage::read_all_from(&mut reader)?;
         Ok(control_messages)
     }
@@ -150,6 +159,11 @@ impl CUserMsgHdr {
         }
 
         let mut writer = user_space.writer(self.msg_control, self.msg_controllen)?;
+        #[cfg(target_arch = "x86_64")]
+        if in_compat_syscall() {
+            let write_len = ControlMessage::write_all_to_compat(control_messages, &mut writer);
+            return Ok(write_len as u32);
+        }
         let write_len = ControlMessage::write_all_to(control_messages, &mut writer) as u32;
         Ok(write_len)
     }
@@ -176,3 +190,63 @@ impl CUserMsgHdr {
         VmWriterArray::from_user_io_vecs(user_space, self.msg_iov, self.msg_iovlen)
     }
 }
+
+/// The 32-bit version of [`CUserMsgHdr`].
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CompatUserMsgHdr {
+    /// Pointer to socket address structure
+    pub msg_name: u32,
+    /// Size of socket address
+    pub msg_namelen: i32,
+    /// Scatter/Gather iov array
+    pub msg_iov: u32,
+    /// The # of elements in msg_iov
+    pub msg_iovlen: u32,
+    /// Ancillary data
+    pub msg_control: u32,
+    /// Ancillary data buffer length
+    pub msg_controllen: u32,
+    /// Flags on received message
+    pub msg_flags: u32,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<CompatUserMsgHdr> for CUserMsgHdr {
+    fn from(value: CompatUserMsgHdr) -> Self {
+        let mut msghdr = Self::new_zeroed();
+        msghdr.msg_name = value.msg_name as Vaddr;
+        msghdr.msg_namelen = value.msg_namelen;
+        msghdr.msg_iov = value.msg_iov as Vaddr;
+        msghdr.msg_iovlen = value.msg_iovlen as usize;
+        msghdr.msg_control = value.msg_control as Vaddr;
+        msghdr.msg_controllen = value.msg_controllen as usize;
+        msghdr.msg_flags = value.msg_flags;
+        msghdr
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<CUserMsgHdr> for CompatUserMsgHdr {
+    fn from(value: CUserMsgHdr) -> Self {
+        Self {
+            msg_name: value.msg_name as u32,
+            msg_namelen: value.msg_namelen,
+            msg_iov: value.msg_iov as u32,
+            msg_iovlen: value.msg_iovlen as u32,
+            msg_control: value.msg_control as u32,
+            msg_controllen: value.msg_controllen as u32,
+            msg_flags: value.msg_flags,
+        }
+    }
+}
+
+/// Returns whether the current system call is a 32-bit system call, which uses the 32-bit layout
+/// of the control messages.
+#[cfg(target_arch = "x86_64")]
+fn in_compat_syscall() -> bool {
+    Task::current()
+        .and_then(|task| task.as_thread_local().map(ThreadLocal::in_compat_syscall))
+        .unwrap_or(false)
+}
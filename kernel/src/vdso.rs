@@ -11,30 +11,38 @@
 //! [`VdsoData`] instance with necessary time-related information, and a Virtual Memory Object
 //! ([`Vmo`]) that encapsulates both the data and the vDSO routines. The VMO is intended to be
 //! mapped into the address space of every user space process for efficient access.
+//!
+//! Processes in a non-initial time namespace see a different vvar page, which contains the clock
+//! offsets of the namespace, while the actual vDSO data is moved to the time namespace page. This
+//! is the layout expected by the vDSO library (see [`map_vvar_to_vmar`]).
 
-use alloc::sync::Arc;
 use core::{mem::ManuallyDrop, time::Duration};
 
 use aster_time::{Instant, read_monotonic_time};
 use aster_util::coeff::Coeff;
 use ostd::{
     const_assert,
-    mm::{PAGE_SIZE, UFrame, VmIo, VmIoOnce, VmReader},
-    sync::SpinLock,
+    mm::{UFrame, VmIo, VmIoOnce},
 };
-use ostd_pod::IntoBytes;
 use spin::Once;
 
 use crate::{
+    prelude::*,
+    process::TimeNsOffsets,
     syscall::ClockId,
     time::{
         START_TIME, SystemTime,
         clocks::MonotonicClock,
         timer::{Timeout, TimerGuard},
     },
-    vm::page_cache::{Vmo, VmoOptions},
+    vm::{
+        page_cache::{Vmo, VmoOptions},
+        perms::VmPerms,
+        vmar::{Vmar, VmarMapOffset},
+    },
 };
 
+const CLOCK_BOOTTIME_ALARM: usize = 9;
 const CLOCK_TAI: usize = 11;
 const VDSO_BASES: usize = CLOCK_TAI + 1;
 const DEFAULT_CLOCK_MODE: VdsoClockMode = VdsoClockMode::Tsc;
@@ -46,6 +54,10 @@ static VDSO: Once<Arc<Vdso>> = Once::new();
 enum VdsoClockMode {
     None = 0,
     Tsc = 1,
+    /// The vDSO data belongs to a time namespace and contains clock offsets.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.2.10/source/include/vdso/clocksource.h>
+    Timens = i32::MAX as isize,
 }
 
 /// An instant used in [`VdsoData`]
@@ -134,6 +146,40 @@ impl VdsoData {
         }
     }
 
+    /// Creates vDSO data of a time namespace with the given clock offsets.
+    ///
+    /// The offsets are stored in place of the instants, as `struct timens_offset` in Linux.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.2.10/source/kernel/time/namespace.c>.
+    fn new_timens(offsets: &TimeNsOffsets) -> Self {
+        let mut data = Self::empty();
+        // The sequence is kept odd so that the vDSO library never treats the data as real one.
+        data.seq = 1;
+        data.set_clock_mode(VdsoClockMode::Timens);
+
+        let monotonic_clock_ids = [
+            ClockId::CLOCK_MONOTONIC as usize,
+            ClockId::CLOCK_MONOTONIC_RAW as usize,
+            ClockId::CLOCK_MONOTONIC_COARSE as usize,
+        ];
+        for clockid in monotonic_clock_ids {
+            data.update_clock_instant(
+                clockid,
+                offsets.monotonic.secs() as u64,
+                offsets.monotonic.nanos() as u64,
+            );
+        }
+        for clockid in [ClockId::CLOCK_BOOTTIME as usize, CLOCK_BOOTTIME_ALARM] {
+            data.update_clock_instant(
+                clockid,
+                offsets.boottime.secs() as u64,
+                offsets.boottime.nanos() as u64,
+            );
+        }
+
+        data
+    }
+
     /// Initializes vDSO data based on the default clock source.
     fn init(&mut self) {
         let clocksource = aster_time::default_clocksource();
@@ -208,6 +254,8 @@ struct Vdso {
     ///
     /// Note: This frame should only be updated while holding the spin lock on [`Self::data`].
     data_frame: UFrame,
+    /// The VMOs that contain the vvar pages of time namespaces.
+    timens_vvar_vmos: SpinLock<Vec<Weak<Vmo>>>,
 }
 
 /// The binary of a prebuilt Linux vDSO library.
@@ -265,6 +313,7 @@ impl Vdso {
             data: SpinLock::new(vdso_data),
             vmo: vdso_vmo,
             data_frame: data_frame.into(),
+            timens_vvar_vmos: SpinLock::new(Vec::new()),
         }
     }
 
@@ -385,6 +434,100 @@ pub fn vdso_vmo() -> Option<Arc<Vmo>> {
     VDSO.get().map(|vdso| vdso.vmo.clone())
 }
 
+/// Creates a VMO that contains the vvar page of a time namespace with the given clock offsets.
+///
+/// This function will return `None` if vDSO does not exist (e.g., if it has not been initialized).
+pub fn new_timens_vvar_vmo(offsets: &TimeNsOffsets) -> Option<Arc<Vmo>> {
+    let vdso = VDSO.get()?;
+
+    let timens_data = VdsoData::new_timens(offsets);
+    let vmo = VmoOptions::new(VDSO_VMO_LAYOUT.data_segment_size)
+        .alloc()
+        .unwrap();
+    // The vDSO library uses separate data for `CLOCK_MONOTONIC_RAW` (`CS_RAW`), which follows the
+    // data for other clocks (`CS_HRES_COARSE`). Both of them should contain the offsets.
+    for index in 0..VDSO_NR_BASES {
+        let mut reader = VmReader::from(timens_data.as_bytes()).to_fallible();
+        vmo.write(
+            VDSO_VMO_LAYOUT.data_offset + index * size_of::<VdsoData>(),
+            &mut reader,
+        )
+        .unwrap();
+    }
+
+    let mut timens_vvar_vmos = vdso.timens_vvar_vmos.lock();
+    timens_vvar_vmos.retain(|vmo| vmo.strong_count() > 0);
+    timens_vvar_vmos.push(Arc::downgrade(&vmo));
+
+    Some(vmo)
+}
+
+/// Returns whether `vmo` contains the vvar page of a time namespace.
+pub fn is_timens_vvar_vmo(vmo: &Arc<Vmo>) -> bool {
+    let Some(vdso) = VDSO.get() else {
+        return false;
+    };
+
+    vdso.timens_vvar_vmos
+        .lock()
+        .iter()
+        .any(|timens_vmo| core::ptr::eq(timens_vmo.as_ptr(), Arc::as_ptr(vmo)))
+}
+
+/// Maps the vvar pages of the vDSO in `vmar` for the time namespace whose vvar page is contained in
+/// `timens_vvar_vmo`.
+///
+/// If `timens_vvar_vmo` is `None`, the vvar pages are mapped as in the initial time namespace.
+/// Otherwise, the vvar page of the time namespace is mapped at the data segment, and the vDSO data
+/// is mapped at the time namespace page, where the vDSO library looks for the real data after
+/// seeing [`VdsoClockMode::Timens`].
+///
+/// This function does nothing if the vDSO is not mapped in `vmar`.
+pub fn map_vvar_to_vmar(vmar: &Vmar, timens_vvar_vmo: Option<&Arc<Vmo>>) -> Result<()> {
+    let Some(vdso) = VDSO.get() else {
+        return Ok(());
+    };
+    let vdso_text_base = vmar.process_vm().vdso_base();
+    if vdso_text_base == 0 {
+        return Ok(());
+    }
+    let vdso_vmo_base = vdso_text_base - VDSO_VMO_LAYOUT.text_segment_offset;
+
+    // Map the vvar pages of the vDSO VMO, replacing the ones of other time namespaces.
+    vmar.new_map(VDSO_VMO_LAYOUT.text_segment_offset, VmPerms::empty())?
+        .vmo(vdso.vmo.clone())
+        .offset(VmarMapOffset::FixedReplace(vdso_vmo_base))
+        .build()?;
+    let vdso_data_base = vdso_vmo_base + VDSO_VMO_LAYOUT.data_segment_offset;
+    vmar.protect(
+        VmPerms::READ,
+        vdso_data_base..(vdso_data_base + VDSO_VMO_LAYOUT.data_segment_size),
+    )?;
+
+    let Some(timens_vvar_vmo) = timens_vvar_vmo else {
+        return Ok(());
+    };
+
+    vmar.new_map(VDSO_VMO_LAYOUT.data_segment_size, VmPerms::READ)?
+        .vmo(timens_vvar_vmo.clone())
+        .offset(VmarMapOffset::FixedReplace(vdso_data_base))
+        .build()?;
+    vmar.new_map(VDSO_VMO_LAYOUT.data_segment_size, VmPerms::READ)?
+        .vmo(vdso.vmo.clone())
+        .vmo_offset(VDSO_VMO_LAYOUT.data_segment_offset)
+        .offset(VmarMapOffset::FixedReplace(
+            vdso_vmo_base + VDSO_VMO_LAYOUT.timens_page_offset,
+        ))
+        .build()?;
+
+    Ok(())
+}
+
+/// The number of `VdsoData` instances in the data segment in Linux (`CS_BASES`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.2.10/source/include/vdso/datapage.h>.
+const VDSO_NR_BASES: usize = 2;
+
 #[cfg(target_arch = "x86_64")]
 pub const VDSO_VMO_LAYOUT: VdsoVmoLayout = VdsoVmoLayout {
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/entry/vdso/vdso-layout.lds.S#L20
@@ -393,6 +536,8 @@ pub const VDSO_VMO_LAYOUT: VdsoVmoLayout = VdsoVmoLayout {
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/entry/vdso/vdso-layout.lds.S#L19
     text_segment_offset: 4 * PAGE_SIZE,
     text_segment_size: PAGE_SIZE,
+    // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/entry/vdso/vdso-layout.lds.S
+    timens_page_offset: 3 * PAGE_SIZE,
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/include/asm/vvar.h#L51
     data_offset: 0x80,
 
@@ -407,6 +552,8 @@ pub const VDSO_VMO_LAYOUT: VdsoVmoLayout = VdsoVmoLayout {
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso.c#L256
     text_segment_offset: 2 * PAGE_SIZE,
     text_segment_size: PAGE_SIZE,
+    // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso/vdso.lds.S
+    timens_page_offset: PAGE_SIZE,
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso.c#L47
     data_offset: 0,
 
@@ -418,6 +565,7 @@ pub struct VdsoVmoLayout {
     pub data_segment_size: usize,
     pub text_segment_offset: usize,
     pub text_segment_size: usize,
+    pub timens_page_offset: usize,
     pub data_offset: usize,
     pub size: usize,
 }
@@ -434,6 +582,7 @@ const_assert!(
         .is_multiple_of(PAGE_SIZE)
 );
 const_assert!(VDSO_VMO_LAYOUT.text_segment_size.is_multiple_of(PAGE_SIZE));
+const_assert!(VDSO_VMO_LAYOUT.timens_page_offset.is_multiple_of(PAGE_SIZE));
 const_assert!(VDSO_VMO_LAYOUT.size.is_multiple_of(PAGE_SIZE));
 
 // Ensure that the vDSO data at `VDSO_VMO_LAYOUT.data_offset` is in the data segment.
@@ -444,3 +593,17 @@ const_assert!(
     VDSO_VMO_LAYOUT.data_offset + size_of::<VdsoData>()
         <= VDSO_VMO_LAYOUT.data_segment_offset + VDSO_VMO_LAYOUT.data_segment_size
 );
+
+// Ensure that the vDSO data of a time namespace fits in the data segment, and that the time
+// namespace page is between the data segment and the text segment.
+const_assert!(
+    VDSO_VMO_LAYOUT.data_offset + VDSO_NR_BASES * size_of::<VdsoData>()
+        <= VDSO_VMO_LAYOUT.data_segment_offset + VDSO_VMO_LAYOUT.data_segment_size
+);
+const_assert!(
+    VDSO_VMO_LAYOUT.data_segment_offset + VDSO_VMO_LAYOUT.data_segment_size
+        <= VDSO_VMO_LAYOUT.timens_page_offset
+);
+const_assert!(
+    VDSO_VMO_LAYOUT.timens_page_offset + PAGE_SIZE <= VDSO_VMO_LAYOUT.text_segment_offset
+);
@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Huge pages from the hugetlb pool.
+//!
+//! The hugetlb pool holds huge pages that are allocated up front, so that they
+//! are still available after physical memory has become fragmented. The size
+//! of the pool is set via `/proc/sys/vm/nr_hugepages`.
+//!
+//! The pool pages back the mappings created with `MAP_HUGETLB`, the files in
+//! hugetlbfs, and the memfds created with `MFD_HUGETLB`. Unlike transparent
+//! huge pages (see [`super::thp`]), a hugetlb page is never split, so such
+//! mappings can only be unmapped, protected, or discarded as a whole huge
+//! page.
+//!
+//! A pool page stays in the pool for its whole life. It is free if nothing
+//! other than the pool refers to it, i.e., it is neither mapped nor held by a
+//! file.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/mm/hugetlbpage.html>
+//
+// TODO: Reserve huge pages when mappings are created, like Linux does. For
+// now, `mmap` only checks that there are enough free huge pages, and a page
+// fault fails if the pool runs out of free huge pages. So `HugePages_Rsvd` is
+// always zero.
+//
+// TODO: Support surplus huge pages. For now, shrinking the pool only frees the
+// free huge pages, so `HugePages_Surp` is always zero.
+
+use alloc::collections::btree_map::BTreeMap;
+use core::ops::Range;
+
+use align_ext::AlignExt;
+use ostd::{
+    impl_untyped_frame_meta_for,
+    mm::{
+        Frame, FrameAllocOptions, HasPaddr, HasSize, Paddr, Segment, USegment,
+        frame::meta::AnyFrameMeta, io::util::HasVmReaderWriter, vm_space::HUGE_PAGE_SIZE,
+    },
+};
+
+use crate::prelude::*;
+
+/// Returns the size of the huge pages in the pool, or `None` if huge pages are
+/// not supported.
+pub fn huge_page_size() -> Option<usize> {
+    HUGE_PAGE_SIZE
+}
+
+/// Checks the huge page size requested with `MAP_HUGE_*` or `MFD_HUGE_*`, and
+/// returns the size of the huge pages to use.
+///
+/// The requested size is encoded as its base-2 logarithm. Zero selects the
+/// default huge page size.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/hugetlbfs/inode.c>
+pub fn check_huge_page_size(size_log2: u32) -> Result<usize> {
+    let Some(huge_page_size) = HUGE_PAGE_SIZE else {
+        return_errno_with_message!(Errno::ENODEV, "huge pages are not supported");
+    };
+
+    if size_log2 != 0 && 1usize.checked_shl(size_log2) != Some(huge_page_size) {
+        return_errno_with_message!(Errno::ENODEV, "the huge page size is not supported");
+    }
+
+    Ok(huge_page_size)
+}
+
+/// The huge pages in the pool.
+static POOL: SpinLock<Vec<Segment<HugetlbPageMeta>>> = SpinLock::new(Vec::new());
+
+/// A lock that serializes the resizing of the pool.
+static POOL_RESIZE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Metadata for a base page in a hugetlb page.
+#[derive(Debug)]
+struct HugetlbPageMeta;
+
+impl_untyped_frame_meta_for!(HugetlbPageMeta);
+
+/// Returns the number of huge pages in the pool.
+pub fn nr_huge_pages() -> usize {
+    POOL.lock().len()
+}
+
+/// Returns the number of free huge pages in the pool.
+pub fn nr_free_huge_pages() -> usize {
+    POOL.lock()
+        .iter()
+        .filter(|segment| is_free(segment))
+        .count()
+}
+
+/// Resizes the pool to hold `nr_pages` huge pages.
+///
+/// Growing the pool stops early if no more huge pages can be allocated.
+/// Shrinking the pool only frees the huge pages that are free.
+pub fn set_nr_huge_pages(nr_pages: usize) {
+    let Some(huge_page_size) = HUGE_PAGE_SIZE else {
+        return;
+    };
+
+    let _guard = POOL_RESIZE_LOCK.lock();
+
+    while POOL.lock().len() < nr_pages {
+        let Ok(segment) = FrameAllocOptions::new()
+            .zeroed(false)
+            .align(huge_page_size)
+            .alloc_segment_with(huge_page_size / PAGE_SIZE, |_| HugetlbPageMeta)
+        else {
+            break;
+        };
+
+        POOL.lock().push(segment);
+    }
+
+    let removed = {
+        let mut pool = POOL.lock();
+        let mut nr_to_remove = pool.len().saturating_sub(nr_pages);
+        let mut removed = Vec::with_capacity(nr_to_remove);
+        let mut i = 0;
+        while i < pool.len() && nr_to_remove > 0 {
+            if is_free(&pool[i]) {
+                removed.push(pool.swap_remove(i));
+                nr_to_remove -= 1;
+            } else {
+                i += 1;
+            }
+        }
+        removed
+    };
+    // Free the huge pages after releasing the lock.
+    drop(removed);
+}
+
+/// Allocates a free huge page from the pool.
+///
+/// Returns `None` if there are no free huge pages in the pool.
+///
+/// The allocation does not block, so it can be done in atomic mode.
+pub(in crate::vm) fn alloc_huge_page(zeroed: bool) -> Option<USegment> {
+    let segment: USegment = {
+        let pool = POOL.lock();
+        // Cloning the huge page makes it no longer free.
+        pool.iter().find(|segment| is_free(segment))?.clone().into()
+    };
+
+    if zeroed {
+        segment.writer().fill_zeros(segment.size());
+    }
+
+    Some(segment)
+}
+
+/// Returns the number of users of the huge page, which is allocated from the
+/// pool.
+///
+/// Each mapping of the huge page and each file holding it is a user.
+pub(in crate::vm) fn nr_users(segment: &USegment) -> u64 {
+    nr_users_of_base_page(segment.paddr())
+}
+
+/// Returns whether a huge page in the pool is free.
+fn is_free(segment: &Segment<HugetlbPageMeta>) -> bool {
+    // Base pages of a mapped huge page can be referenced separately (e.g., via
+    // `access_alien`), so all of them are checked.
+    (segment.paddr()..segment.paddr() + segment.size())
+        .step_by(PAGE_SIZE)
+        .all(|paddr| nr_users_of_base_page(paddr) == 0)
+}
+
+fn nr_users_of_base_page(paddr: Paddr) -> u64 {
+    let frame = Frame::<dyn AnyFrameMeta>::from_in_use(paddr).unwrap();
+    // Exclude the references held by the pool and by `frame` itself.
+    frame.reference_count() - 2
+}
+
+/// The huge pages of a hugetlbfs file.
+///
+/// A shared anonymous hugetlb mapping is also backed by a `HugetlbFile` that
+/// does not belong to any file system, so that its pages are shared after
+/// `fork`.
+#[derive(Debug)]
+pub struct HugetlbFile {
+    inner: SpinLock<HugetlbFileInner>,
+}
+
+#[derive(Debug)]
+struct HugetlbFileInner {
+    size: usize,
+    /// The allocated huge pages, indexed by their huge page index in the file.
+    pages: BTreeMap<usize, USegment>,
+}
+
+impl HugetlbFile {
+    /// Creates a `HugetlbFile` of the size.
+    pub fn new(size: usize) -> Arc<Self> {
+        Arc::new(Self {
+            inner: SpinLock::new(HugetlbFileInner {
+                size,
+                pages: BTreeMap::new(),
+            }),
+        })
+    }
+
+    /// Resizes the file.
+    ///
+    /// The new size must be a multiple of the huge page size. Shrinking the
+    /// file frees the huge pages beyond the new size unless they are mapped.
+    //
+    // TODO: Linux unmaps the truncated huge pages from all mappings. We keep
+    // them mapped until they are unmapped by the processes.
+    pub fn resize(&self, new_size: usize) -> Result<()> {
+        let huge_page_size = HUGE_PAGE_SIZE.unwrap();
+        if !new_size.is_multiple_of(huge_page_size) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the size of a hugetlbfs file must be a multiple of the huge page size"
+            );
+        }
+
+        let truncated = {
+            let mut inner = self.inner.lock();
+            inner.size = new_size;
+            inner.pages.split_off(&(new_size / huge_page_size))
+        };
+        drop(truncated);
+
+        Ok(())
+    }
+
+    /// Reads the file from the offset into the writer.
+    ///
+    /// The holes in the file are read as zeros. Returns the number of bytes
+    /// read.
+    pub fn read(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let huge_page_size = HUGE_PAGE_SIZE.unwrap();
+        let size = self.inner.lock().size;
+        let end = size.min(offset.saturating_add(writer.avail()));
+
+        let mut pos = offset;
+        while pos < end {
+            let index = pos / huge_page_size;
+            let page_offset = pos - index * huge_page_size;
+            let len = (huge_page_size - page_offset).min(end - pos);
+
+            // Clone the huge page so that the lock is not held while writing
+            // to the writer, which may cause page faults.
+            let page = self.inner.lock().pages.get(&index).cloned();
+            let res = if let Some(page) = page {
+                let mut reader = page.reader();
+                reader.skip(page_offset).limit(len);
+                reader.to_fallible().read_fallible(writer)
+            } else {
+                writer.fill_zeros(len)
+            };
+            match res {
+                Ok(read_len) => pos += read_len,
+                Err((err, read_len)) => {
+                    pos += read_len;
+                    if pos == offset {
+                        return Err(err.into());
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(pos - offset)
+    }
+
+    /// Returns the huge page at the huge page index, or `None` if it has not
+    /// been allocated yet.
+    ///
+    /// Returns an error if the index is beyond the end of the file.
+    pub(in crate::vm) fn get(&self, index: usize) -> Result<Option<USegment>> {
+        let inner = self.inner.lock();
+        inner.check_index(index)?;
+        Ok(inner.pages.get(&index).cloned())
+    }
+
+    /// Returns the huge page at the huge page index, allocating a zeroed one
+    /// from the pool if it has not been allocated yet.
+    ///
+    /// Returns an error if the index is beyond the end of the file or if there
+    /// are no free huge pages in the pool.
+    pub(in crate::vm) fn get_or_alloc(&self, index: usize) -> Result<USegment> {
+        let mut inner = self.inner.lock();
+        inner.check_index(index)?;
+
+        if let Some(page) = inner.pages.get(&index) {
+            return Ok(page.clone());
+        }
+
+        let page = alloc_huge_page(true).ok_or_else(|| {
+            Error::with_message(Errno::EFAULT, "there are no free huge pages in the pool")
+        })?;
+        inner.pages.insert(index, page.clone());
+
+        Ok(page)
+    }
+
+    /// Returns the number of huge pages that are needed to back the range of
+    /// the file but have not been allocated yet.
+    pub(in crate::vm) fn nr_missing_pages(&self, range: Range<usize>) -> usize {
+        let huge_page_size = HUGE_PAGE_SIZE.unwrap();
+        let start = range.start / huge_page_size;
+        let end = range.end.align_up(huge_page_size) / huge_page_size;
+
+        let inner = self.inner.lock();
+        (end - start) - inner.pages.range(start..end).count()
+    }
+}
+
+impl HugetlbFileInner {
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index >= self.size / HUGE_PAGE_SIZE.unwrap() {
+            return_errno_with_message!(
+                Errno::EFAULT,
+                "the huge page is beyond the end of the file"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Charging pages to memory cgroups.
+//!
+//! Anonymous pages and pages in the page cache are charged to the memory
+//! cgroup of the process that allocates them. A charge is kept in the metadata
+//! of the charged frame, so the page is uncharged when the frame is freed.
+//!
+//! A charge fails if it would exceed the `memory.max` limit of the cgroup or
+//! any of its ancestors. The allocation then fails with `ENOMEM`, and it is
+//! retried after reclaiming pages from the cgroup. If no pages can be
+//! reclaimed, the page fault handler kills a process in the cgroup.
+
+use ostd::{
+    impl_untyped_frame_meta_for,
+    mm::{Frame, FrameAllocOptions, HasPaddr, Paddr, frame::meta::AnyFrameMeta},
+};
+
+use crate::{
+    fs::cgroupfs::{MemCharge, MemChargeKind},
+    prelude::*,
+    vm::{page_cache::CachePageMeta, reclaim::LruHandle},
+};
+
+/// Charges one page to the memory cgroup of the current process.
+///
+/// Returns `None` if the page is not charged to any memory cgroup.
+pub(in crate::vm) fn try_charge_page(kind: MemChargeKind) -> Result<Option<MemCharge>> {
+    MemCharge::try_charge_current(kind).map_err(|_| {
+        Error::with_message(
+            Errno::ENOMEM,
+            "the memory limit of the cgroup has been reached",
+        )
+    })
+}
+
+/// Metadata for an anonymous page.
+#[derive(Debug)]
+pub(in crate::vm) struct AnonPageMeta {
+    charge: Option<MemCharge>,
+    lru_handle: LruHandle,
+}
+
+impl_untyped_frame_meta_for!(AnonPageMeta);
+
+/// Allocates an anonymous page and charges it to the memory cgroup of the
+/// current process.
+///
+/// The allocation does not block, so it can be done in atomic mode.
+pub(in crate::vm) fn alloc_anon_frame(zeroed: bool) -> Result<Frame<AnonPageMeta>> {
+    let meta = AnonPageMeta {
+        charge: try_charge_page(MemChargeKind::Anon)?,
+        lru_handle: LruHandle::new(),
+    };
+    let frame = FrameAllocOptions::new()
+        .zeroed(zeroed)
+        .alloc_frame_with(meta)?;
+    frame.meta().lru_handle.set_paddr(frame.paddr());
+    Ok(frame)
+}
+
+/// Calls `f` with the charge of the page at `paddr` to a memory cgroup.
+///
+/// The charge is `None` if the page is not charged, or if the page is neither
+/// an anonymous page nor a page in the page cache (e.g., it has been freed).
+pub(in crate::vm) fn with_page_charge<R>(
+    paddr: Paddr,
+    f: impl FnOnce(Option<&MemCharge>) -> R,
+) -> R {
+    let Ok(frame) = Frame::<dyn AnyFrameMeta>::from_in_use(paddr) else {
+        return f(None);
+    };
+    let frame = match Frame::<AnonPageMeta>::try_from(frame) {
+        Ok(frame) => return f(frame.meta().charge.as_ref()),
+        Err(frame) => frame,
+    };
+    match Frame::<CachePageMeta>::try_from(frame) {
+        Ok(page) => f(page.meta().charge()),
+        Err(_) => f(None),
+    }
+}
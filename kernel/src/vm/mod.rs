@@ -12,8 +12,16 @@
 use osdk_frame_allocator::FrameAllocator;
 use osdk_heap_allocator::{HeapAllocator, type_from_layout};
 
+pub mod hugetlb;
+mod memcg;
+pub mod oom;
 pub mod page_cache;
 pub mod perms;
+pub mod reclaim;
+pub mod swap;
+mod sysfs;
+pub mod thp;
+pub mod userfaultfd;
 pub mod vmar;
 
 #[ostd::global_frame_allocator]
@@ -27,6 +35,12 @@ const fn slot_type_from_layout(layout: core::alloc::Layout) -> Option<ostd::mm::
     type_from_layout(layout)
 }
 
+pub(super) fn init_in_first_kthread() {
+    reclaim::init_in_first_kthread();
+    sysfs::init();
+    thp::init();
+}
+
 /// Total physical memory in the entire system in bytes.
 pub fn mem_total() -> usize {
     use ostd::boot::{boot_info, memory_region::MemoryRegionType};
@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The out-of-memory (OOM) killer.
+//!
+//! When memory cannot be allocated even after page reclamation, the OOM killer
+//! selects a victim process, kills it with `SIGKILL`, and reaps its address
+//! space early, so that the memory can be reused before the victim exits.
+//!
+//! The victim is the process with the highest badness score. The score is
+//! based on the resident set size (RSS) of the process, and is adjusted by its
+//! `oom_score_adj`, which can be set via `/proc/<pid>/oom_score_adj`.
+//!
+//! While a previous victim is still exiting and its address space has not
+//! been reaped, no new victim is selected. This prevents killing more
+//! processes than necessary.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/oom_kill.c>
+
+use core::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use ostd::sync::WaitQueue;
+
+use crate::{
+    fs::cgroupfs::{CgroupNode, MemoryEvent},
+    prelude::*,
+    process::{
+        Process, pid_table,
+        posix_thread::AsPosixThread,
+        signal::{HandlePendingSignal, constants::SIGKILL, signals::kernel::KernelSignal},
+    },
+    time::wait::WaitTimeout,
+    vm::{mem_total, vmar::RssType},
+};
+
+/// The minimum value of `oom_score_adj`.
+///
+/// A process with this value is never killed by the OOM killer.
+pub const OOM_SCORE_ADJ_MIN: i16 = -1000;
+
+/// The maximum value of `oom_score_adj`.
+pub const OOM_SCORE_ADJ_MAX: i16 = 1000;
+
+/// The scope in which memory runs out.
+pub enum OomConstraint {
+    /// The whole system runs out of memory.
+    System,
+    /// A memory cgroup reaches its limit.
+    ///
+    /// Only the processes in the cgroup or its descendants are considered.
+    Cgroup {
+        cgroup: Arc<CgroupNode>,
+        /// The memory limit of the cgroup, in pages.
+        limit: usize,
+    },
+}
+
+impl OomConstraint {
+    /// Returns the scope in which `process` fails to allocate memory.
+    ///
+    /// If the memory cgroup of `process`, or one of its ancestors, has
+    /// reached its `memory.max` limit, only the processes in that cgroup are
+    /// considered. Otherwise, the whole system runs out of memory.
+    pub fn for_process(process: &Process) -> Self {
+        match CgroupNode::find_memory_limited(process) {
+            Some((cgroup, limit)) => Self::Cgroup { cgroup, limit },
+            None => Self::System,
+        }
+    }
+
+    /// Returns the number of pages that can be used in the scope.
+    fn total_pages(&self) -> usize {
+        match self {
+            Self::System => mem_total() / PAGE_SIZE,
+            Self::Cgroup { limit, .. } => *limit,
+        }
+    }
+
+    /// Returns whether `process` is in the scope.
+    fn contains(&self, process: &Process) -> bool {
+        match self {
+            Self::System => true,
+            Self::Cgroup { cgroup, .. } => process
+                .cgroup()
+                .get()
+                .is_some_and(|process_cgroup| process_cgroup.is_same_or_descendant_of(cgroup)),
+        }
+    }
+}
+
+/// Serializes the invocations of the OOM killer.
+///
+/// Otherwise, concurrent allocation failures could kill multiple processes
+/// when killing one of them is enough.
+static OOM_LOCK: Mutex<()> = Mutex::new(());
+
+/// The wait queue for the address spaces of OOM victims to be freed.
+static OOM_VICTIMS_WAIT_QUEUE: WaitQueue = WaitQueue::new();
+
+/// The number of OOM victims whose address spaces have been freed.
+static NR_EXITED_VICTIMS: AtomicUsize = AtomicUsize::new(0);
+
+/// The maximum time to wait for an exiting victim before retrying.
+const VICTIM_WAIT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The maximum number of attempts to reap a victim.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/oom_kill.c>
+const MAX_OOM_REAP_RETRIES: usize = 10;
+
+/// The interval between two attempts to reap a victim.
+const OOM_REAP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Kills a process to free memory.
+///
+/// Returns whether the failed allocation should be retried. This is the case
+/// if a victim has been killed, if a previous victim is still exiting, or if
+/// the OOM killer has been invoked concurrently by others. If the caller
+/// itself has been killed, it should stop retrying once it finds a pending
+/// `SIGKILL`.
+pub fn out_of_memory(constraint: &OomConstraint) -> bool {
+    let Some(guard) = OOM_LOCK.try_lock() else {
+        // Someone else is killing a process. Wait for it and retry.
+        drop(OOM_LOCK.lock());
+        return true;
+    };
+
+    // The current process is exiting and will free its memory soon.
+    if current_thread!()
+        .as_posix_thread()
+        .unwrap()
+        .has_pending_sigkill()
+    {
+        return true;
+    }
+
+    if let OomConstraint::Cgroup { cgroup, .. } = constraint {
+        cgroup.record_memory_event(MemoryEvent::Oom);
+    }
+
+    let total_pages = constraint.total_pages();
+    let processes: Vec<Arc<Process>> = pid_table::pid_table_mut()
+        .iter_processes()
+        .filter(|process| constraint.contains(process))
+        .collect();
+
+    let mut victim: Option<(Arc<Process>, isize)> = None;
+    for process in processes {
+        match evaluate(&process, total_pages) {
+            Evaluation::Skip => (),
+            Evaluation::Abort => {
+                // A process that is being killed will free its memory soon.
+                // Killing another process now may be unnecessary, so wait for
+                // it to exit instead. The process is reaped if it has not been
+                // killed by the OOM killer, so that it cannot hold its memory
+                // forever.
+                reap(&process);
+                let nr_exited_victims = NR_EXITED_VICTIMS.load(Ordering::Acquire);
+                drop(guard);
+                wait_for_victims(nr_exited_victims);
+                return true;
+            }
+            Evaluation::Candidate(points) => {
+                if victim
+                    .as_ref()
+                    .is_none_or(|(_, max_points)| points > *max_points)
+                {
+                    victim = Some((process, points));
+                }
+            }
+        }
+    }
+
+    let Some((victim, _)) = victim else {
+        error!("Out of memory and no killable processes");
+        return false;
+    };
+
+    kill(&victim);
+    reap(&victim);
+    true
+}
+
+/// Notifies the OOM killer that the address space of a victim has been freed.
+pub(super) fn exit_oom_victim() {
+    NR_EXITED_VICTIMS.fetch_add(1, Ordering::Release);
+    OOM_VICTIMS_WAIT_QUEUE.wake_all();
+}
+
+/// Waits until the address space of a victim is freed, or until the timeout
+/// expires.
+fn wait_for_victims(nr_exited_victims: usize) {
+    let _ = OOM_VICTIMS_WAIT_QUEUE.wait_until_or_timeout(
+        || (NR_EXITED_VICTIMS.load(Ordering::Acquire) != nr_exited_victims).then_some(()),
+        &VICTIM_WAIT_TIMEOUT,
+    );
+}
+
+/// The result of evaluating a process as an OOM victim.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/oom_kill.c>
+enum Evaluation {
+    /// The process should not be killed.
+    Skip,
+    /// The process is being killed, so the selection should be aborted.
+    Abort,
+    /// The process can be killed with the badness score.
+    Candidate(isize),
+}
+
+/// Evaluates whether `process` should be killed and its badness score.
+fn evaluate(process: &Process, total_pages: usize) -> Evaluation {
+    if process.is_init_process() || process.status().is_zombie() {
+        return Evaluation::Skip;
+    }
+
+    let vmar_guard = process.lock_vmar();
+    let Some(vmar) = vmar_guard.as_ref() else {
+        // The process has exited and freed its memory.
+        return Evaluation::Skip;
+    };
+
+    // A process that has been reaped is not waited for, since the memory that
+    // can be freed early has been freed.
+    if vmar.is_oom_skipped() {
+        return Evaluation::Skip;
+    }
+    let main_thread = process.main_thread();
+    let has_pending_sigkill = main_thread
+        .as_posix_thread()
+        .is_some_and(|posix_thread| posix_thread.has_pending_sigkill());
+    if vmar.is_oom_victim() || has_pending_sigkill {
+        return Evaluation::Abort;
+    }
+
+    let oom_score_adj = process.oom_score_adj().load(Ordering::Relaxed);
+    if oom_score_adj == OOM_SCORE_ADJ_MIN {
+        return Evaluation::Skip;
+    }
+
+    let rss = vmar.get_rss_counter(RssType::File)
+        + vmar.get_rss_counter(RssType::Anon)
+        + vmar.get_rss_counter(RssType::Swap);
+
+    // Normalize the adjustment to a proportion of the total pages, so that
+    // `OOM_SCORE_ADJ_MAX` always makes the process the preferred victim.
+    let adj = oom_score_adj as isize * (total_pages / 1000) as isize;
+
+    Evaluation::Candidate(rss as isize + adj)
+}
+
+fn kill(victim: &Process) {
+    let oom_score_adj = victim.oom_score_adj().load(Ordering::Relaxed);
+
+    victim.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+    if let Some(cgroup) = victim.cgroup().get() {
+        cgroup.record_memory_event(MemoryEvent::OomKill);
+    }
+
+    let vmar_guard = victim.lock_vmar();
+    let Some(vmar) = vmar_guard.as_ref() else {
+        return;
+    };
+
+    let total_vm = vmar.get_mappings_total_size() / 1024;
+    let anon_rss = vmar.get_rss_counter(RssType::Anon) * (PAGE_SIZE / 1024);
+    let file_rss = vmar.get_rss_counter(RssType::File) * (PAGE_SIZE / 1024);
+    error!(
+        "Out of memory: Killed process {} ({}) total-vm:{}kB, anon-rss:{}kB, file-rss:{}kB, oom_score_adj:{}",
+        victim.pid(),
+        comm_of(victim),
+        total_vm,
+        anon_rss,
+        file_rss,
+        oom_score_adj
+    );
+}
+
+/// Marks the address space of `victim` as an OOM victim and reaps it.
+///
+/// Reaping is retried if the address space is busy. The address space is
+/// skipped by later invocations of the OOM killer once it is reaped or the
+/// retries run out.
+fn reap(victim: &Process) {
+    let Some(vmar) = victim
+        .lock_vmar()
+        .as_ref()
+        .and_then(|vmar| vmar.weak_self().upgrade())
+    else {
+        return;
+    };
+    if vmar.is_oom_victim() {
+        return;
+    }
+    vmar.mark_oom_victim();
+
+    // The address space may be shared with other processes that are not
+    // killed. Reaping it would break them, so the OOM killer waits until the
+    // victim exits.
+    if vmar.has_multiple_handles() {
+        return;
+    }
+
+    for _ in 0..MAX_OOM_REAP_RETRIES {
+        if let Some(nr_reaped) = vmar.reap_private_pages() {
+            info!(
+                "oom_reaper: reaped {} pages of process {} ({})",
+                nr_reaped,
+                victim.pid(),
+                comm_of(victim)
+            );
+            vmar.set_oom_skipped();
+            return;
+        }
+
+        // The VMAR is locked by a writer. Retry later unless the victim exits
+        // in the meantime.
+        let nr_exited_victims = NR_EXITED_VICTIMS.load(Ordering::Acquire);
+        let _ = OOM_VICTIMS_WAIT_QUEUE.wait_until_or_timeout(
+            || (NR_EXITED_VICTIMS.load(Ordering::Acquire) != nr_exited_victims).then_some(()),
+            &OOM_REAP_INTERVAL,
+        );
+        if victim.lock_vmar().as_ref().is_none() {
+            return;
+        }
+    }
+
+    warn!(
+        "oom_reaper: unable to reap process {} ({}) since its VMAR is busy",
+        victim.pid(),
+        comm_of(victim)
+    );
+    vmar.set_oom_skipped();
+}
+
+fn comm_of(process: &Process) -> String {
+    process
+        .main_thread()
+        .as_posix_thread()
+        .unwrap()
+        .thread_name()
+        .lock()
+        .name()
+        .to_string_lossy()
+        .into_owned()
+}
@@ -13,7 +13,11 @@ use ostd::{
     sync::WaitQueue,
 };
 
-use crate::prelude::*;
+use crate::{
+    fs::cgroupfs::{MemCharge, MemChargeKind},
+    prelude::*,
+    vm::{memcg::try_charge_page, reclaim::LruHandle},
+};
 
 /// The state of a page in the page cache.
 #[repr(u8)]
@@ -76,7 +80,18 @@ pub struct CachePageMeta {
     /// cleared without the page lock only from the BIO completion callback after
     /// the VMO writeback path has handed off the writeback state.
     is_writing_back: AtomicBool,
-    // TODO: Add a reverse mapping from the page to VMO for eviction.
+    /// This bit indicates that the page has been accessed since it was last
+    /// scanned by page reclamation.
+    ///
+    /// It works like `PG_referenced` in Linux. Accesses via page table
+    /// mappings are tracked by the accessed bits in the page tables instead.
+    is_referenced: AtomicBool,
+    /// The charge of the page to a memory cgroup, which is uncharged when
+    /// the page is freed.
+    charge: Option<MemCharge>,
+    /// The handle that removes the page from the LRU lists when the page is
+    /// freed.
+    lru_handle: LruHandle,
 }
 
 impl Default for CachePageMeta {
@@ -85,12 +100,22 @@ impl Default for CachePageMeta {
             state: AtomicPageState::new(PageState::Uninit),
             lock: AtomicBool::new(false),
             is_writing_back: AtomicBool::new(false),
+            is_referenced: AtomicBool::new(false),
+            charge: None,
+            lru_handle: LruHandle::new(),
         }
     }
 }
 
 impl_untyped_frame_meta_for!(CachePageMeta);
 
+impl CachePageMeta {
+    /// Returns the charge of the page to a memory cgroup.
+    pub(in crate::vm) fn charge(&self) -> Option<&MemCharge> {
+        self.charge.as_ref()
+    }
+}
+
 /// Convenience operations on a [`CachePage`] handle.
 ///
 /// Implemented for every [`CachePage`], this gives access to the page lock,
@@ -139,24 +164,49 @@ pub trait CachePageExt: Sized {
         self.wait_queue().wake_all();
     }
 
+    /// Marks the page as referenced.
+    fn mark_referenced(&self) {
+        let is_referenced = &self.metadata().is_referenced;
+        // Avoid bouncing the cache line if the bit has already been set.
+        if !is_referenced.load(Ordering::Relaxed) {
+            is_referenced.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Clears the referenced bit and returns whether it was set.
+    fn test_and_clear_referenced(&self) -> bool {
+        let is_referenced = &self.metadata().is_referenced;
+        is_referenced.load(Ordering::Relaxed) && is_referenced.swap(false, Ordering::Relaxed)
+    }
+
     /// Allocates a new cache page which content and state are uninitialized.
+    ///
+    /// The page is charged to the memory cgroup of the current process.
     fn alloc_uninit() -> Result<CachePage> {
-        let meta = CachePageMeta::default();
+        let meta = CachePageMeta {
+            charge: try_charge_page(MemChargeKind::File)?,
+            ..Default::default()
+        };
         let page = FrameAllocOptions::new()
             .zeroed(false)
             .alloc_frame_with(meta)?;
+        page.metadata().lru_handle.set_paddr(page.paddr());
         Ok(page)
     }
 
     /// Allocates a new zeroed cache page with the up-to-date state.
+    ///
+    /// The page is charged to the memory cgroup of the current process.
     fn alloc_zero() -> Result<CachePage> {
         let meta = CachePageMeta {
             state: AtomicPageState::new(PageState::UpToDate),
+            charge: try_charge_page(MemChargeKind::File)?,
             ..Default::default()
         };
         let page = FrameAllocOptions::new()
             .zeroed(true)
             .alloc_frame_with(meta)?;
+        page.metadata().lru_handle.set_paddr(page.paddr());
         Ok(page)
     }
 
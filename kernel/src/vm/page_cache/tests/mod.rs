@@ -5,11 +5,15 @@
 
 use alloc::vec;
 
-use ostd::{mm::VmIo, prelude::ktest};
+use io_util::batch::IoBatch;
+use ostd::{
+    mm::{HasPaddr, VmIo},
+    prelude::ktest,
+};
 
 use self::utils::{IoCompletion, IoKind, MockPageCacheBackend, wait_until};
 use super::{PageCache, PageCacheBackend, VmoCommitError};
-use crate::{prelude::*, thread::kernel_thread::ThreadOptions};
+use crate::{prelude::*, thread::kernel_thread::ThreadOptions, vm::reclaim::ReclaimResult};
 
 mod utils;
 
@@ -495,3 +499,41 @@ fn delayed_io_completion() {
     assert!(second_flush_result.lock().take().unwrap().is_ok());
     assert_eq!(backend.persisted_page_bytes(0), latest_dirty_pattern);
 }
+
+/// Reclaims a dirty page, which must be written back before it can be dropped
+/// from the page cache, and checks that a later read fetches it again.
+#[ktest]
+fn reclaim_dirty_page() {
+    let backend = MockPageCacheBackend::new(1);
+    let page_cache = new_backend_page_cache(&backend, 1);
+
+    let pattern = vec![0x5a; PAGE_SIZE];
+    page_cache.write_bytes(0, &pattern).unwrap();
+
+    let vmo = page_cache.as_vmo();
+    let paddr = vmo.commit_on(0).unwrap().paddr();
+    let try_reclaim = || {
+        let mut io_batch = IoBatch::new();
+        let result = vmo.try_reclaim_page(0, paddr, true, &mut io_batch);
+        io_batch.wait_all().unwrap();
+        result
+    };
+
+    // The page has been referenced by the commit above, so it is kept.
+    assert_eq!(try_reclaim(), ReclaimResult::Referenced);
+
+    // The dirty page is written back instead of being dropped.
+    assert_eq!(try_reclaim(), ReclaimResult::Busy);
+    assert_eq!(backend.write_count(0), 1);
+    assert_eq!(backend.persisted_page_bytes(0), pattern);
+
+    // The clean page can be dropped now.
+    assert_eq!(try_reclaim(), ReclaimResult::Reclaimed);
+    assert!(!vmo.contains_page(0, paddr));
+
+    let read_count = backend.read_count(0);
+    let mut read_buffer = vec![0; PAGE_SIZE];
+    page_cache.read_bytes(0, &mut read_buffer).unwrap();
+    assert_eq!(read_buffer, pattern);
+    assert_eq!(backend.read_count(0), read_count + 1);
+}
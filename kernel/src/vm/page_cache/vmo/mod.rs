@@ -24,14 +24,17 @@ use core::{
 use align_ext::AlignExt;
 use io_util::batch::IoBatch;
 use ostd::{
-    mm::{HasPaddr, io::util::HasVmReaderWriter},
+    mm::{HasPaddr, Paddr, io::util::HasVmReaderWriter},
     task::disable_preempt,
 };
 use xarray::{Cursor, LockedXArray, XArray};
 
 use crate::{
     prelude::*,
-    vm::page_cache::{CachePage, CachePageExt, PageCacheBackend},
+    vm::{
+        page_cache::{CachePage, CachePageExt, PageCacheBackend},
+        reclaim::{self, ReclaimResult, Rmap, RmapOp},
+    },
 };
 
 mod options;
@@ -131,6 +134,10 @@ pub struct Vmo {
     // not have the knowledge to determine if they belong to memfd. We may want to enhance
     // `VmoOptions` to make VMOs aware of whether its writable mappings should be tracked.
     pub(super) writable_mapping_status: WritableMappingStatus,
+    /// The reverse mapping of the VMO.
+    pub(super) rmap: Rmap,
+    /// A weak reference to itself, which is recorded in the LRU lists.
+    pub(super) weak_self: Weak<Vmo>,
 }
 
 impl Debug for Vmo {
@@ -219,11 +226,19 @@ impl Vmo {
     }
 
     fn commit_on_internal(&self, page_idx: usize, commit_mode: CommitMode) -> Result<CachePage> {
-        if let Some(backed_vmo) = self.as_backed_vmo() {
-            return backed_vmo.commit_on_internal(page_idx, commit_mode);
+        let mut nr_retries = 0;
+        loop {
+            let res = if let Some(backed_vmo) = self.as_backed_vmo() {
+                backed_vmo.commit_on_internal(page_idx, commit_mode)
+            } else {
+                self.commit_on_anonymous(page_idx)
+            };
+
+            match res {
+                Err(err) if reclaim::should_retry_after_reclaim(&err, &mut nr_retries) => continue,
+                res => return res,
+            }
         }
-
-        self.commit_on_anonymous(page_idx)
     }
 
     fn commit_on_anonymous(&self, page_idx: usize) -> Result<CachePage> {
@@ -234,11 +249,15 @@ impl Vmo {
 
         let mut cursor = locked_pages.cursor_mut(page_idx as u64);
         if let Some(page) = cursor.load() {
+            page.mark_referenced();
             return Ok(page.clone());
         }
 
         let new_page = CachePage::alloc_zero()?;
         cursor.store(new_page.clone());
+        drop(locked_pages);
+
+        reclaim::lru_add_vmo_page(self, page_idx, &new_page);
         Ok(new_page)
     }
 
@@ -269,6 +288,7 @@ impl Vmo {
         if let Some(backed_vmo) = self.as_backed_vmo() {
             return backed_vmo.try_commit_with_cursor(cursor, commit_mode);
         } else if let Some(page) = cursor.load() {
+            page.mark_referenced();
             let index = cursor.index() as usize;
             return Ok((index, page.clone()));
         }
@@ -378,11 +398,123 @@ impl Vmo {
     }
 
     /// Returns whether this VMO has a backend.
-    fn has_backend(&self) -> bool {
+    pub(in crate::vm) fn has_backend(&self) -> bool {
         self.backend.is_some()
     }
 }
 
+// Implement the methods used by page reclamation for `Vmo`.
+impl Vmo {
+    /// Returns the reverse mapping of the VMO.
+    pub(in crate::vm) fn rmap(&self) -> &Rmap {
+        &self.rmap
+    }
+
+    /// Returns a weak reference to the VMO.
+    pub(in crate::vm) fn weak_self(&self) -> &Weak<Vmo> {
+        &self.weak_self
+    }
+
+    /// Returns whether the page at `page_idx` is the frame at `paddr`.
+    pub(in crate::vm) fn contains_page(&self, page_idx: usize, paddr: Paddr) -> bool {
+        let guard = disable_preempt();
+        let mut cursor = self.pages.cursor(&guard, page_idx as u64);
+        cursor.load().is_some_and(|page| page.paddr() == paddr)
+    }
+
+    /// Tries to reclaim the page at `page_idx`, which should be the frame at
+    /// `paddr`.
+    ///
+    /// The page is unmapped from all the VMARs that map it. Then, if the page
+    /// is clean, it is removed from the VMO. Otherwise, if `may_writepage` is
+    /// true, the page is written back to the backend with `io_batch`, so it
+    /// can be reclaimed later once the writeback completes.
+    ///
+    /// Only pages in VMOs with a backend can be reclaimed.
+    //
+    // TODO: A page fault or a buffered I/O operation may obtain the page
+    // after we check the reference count, but before we remove the page. We
+    // should freeze the reference count, or use a lock that excludes them.
+    pub(in crate::vm) fn try_reclaim_page(
+        &self,
+        page_idx: usize,
+        paddr: Paddr,
+        may_writepage: bool,
+        io_batch: &mut IoBatch,
+    ) -> ReclaimResult {
+        let Some(backed_vmo) = self.as_backed_vmo() else {
+            return ReclaimResult::Gone;
+        };
+
+        let page = {
+            let guard = disable_preempt();
+            let mut cursor = self.pages.cursor(&guard, page_idx as u64);
+            match cursor.load() {
+                Some(page) if page.paddr() == paddr => page.clone(),
+                _ => return ReclaimResult::Gone,
+            }
+        };
+
+        let mut rmap_result = self
+            .rmap
+            .apply(self, page_idx, paddr, RmapOp::ClearAccessed);
+        if page.test_and_clear_referenced() || rmap_result.is_referenced {
+            return ReclaimResult::Referenced;
+        }
+        if rmap_result.is_busy || page.is_uninit() {
+            return ReclaimResult::Busy;
+        }
+
+        if rmap_result.nr_mapped > 0 {
+            rmap_result = self.rmap.apply(self, page_idx, paddr, RmapOp::Unmap);
+            if rmap_result.is_busy {
+                return ReclaimResult::Busy;
+            }
+        }
+
+        let Some(locked_page) = page.try_lock_guard() else {
+            return ReclaimResult::Busy;
+        };
+        if rmap_result.is_dirty && locked_page.is_up_to_date() {
+            locked_page.set_dirty();
+        }
+        if locked_page.is_writing_back() {
+            return ReclaimResult::Busy;
+        }
+
+        if locked_page.is_dirty() {
+            if may_writepage {
+                // Errors are ignored. The page stays dirty and will be tried
+                // again later.
+                let _ = backed_vmo.backend.write_page_async(
+                    page_idx,
+                    locked_page.into_owned(),
+                    io_batch,
+                );
+            }
+            return ReclaimResult::Busy;
+        }
+
+        let mut locked_pages = self.pages.lock();
+        let mut cursor = locked_pages.cursor_mut(page_idx as u64);
+        if !cursor
+            .load()
+            .is_some_and(|current_page| current_page.paddr() == paddr)
+        {
+            return ReclaimResult::Gone;
+        }
+        // One reference is held by the `XArray` and the other is held by us.
+        // Other references indicate that the page is still in use (e.g., it
+        // is mapped and the page table has not released it yet).
+        if page.reference_count() > 2 {
+            return ReclaimResult::Busy;
+        }
+        cursor.remove();
+
+        ReclaimResult::Reclaimed
+    }
+}
+
 // Implement the read/write methods for `Vmo`.
 impl Vmo {
     /// Maximum number of pages collected per batch from the `XArray`.
@@ -766,6 +898,8 @@ impl<'a> BackedVmo<'a> {
             let page = page.clone();
             drop(locked_pages);
 
+            page.mark_referenced();
+
             if !commit_mode.skips_backend_read() {
                 page.ensure_init(|locked_page| self.backend.read_page(page_idx, locked_page))?;
                 return Ok(page);
@@ -779,6 +913,8 @@ impl<'a> BackedVmo<'a> {
         cursor.store(uninit_page.clone());
         drop(locked_pages);
 
+        reclaim::lru_add_vmo_page(self, page_idx, &uninit_page);
+
         if commit_mode.skips_backend_read() {
             // The page will be completely overwritten, no need to read.
             Ok(uninit_page)
@@ -809,6 +945,7 @@ impl<'a> BackedVmo<'a> {
             });
         }
 
+        page.mark_referenced();
         Ok((page_idx, page.clone()))
     }
 
@@ -871,14 +1008,15 @@ impl<'a> BackedVmo<'a> {
             let mut cursor = locked_pages.cursor_mut(page_idx as u64);
 
             // The caller will hold the higher-level lock that excludes concurrent
-            // removal, read/write and page fault handling, so the collected index
-            // still identifies the page that need to be deleted.
-            debug_assert!(
-                cursor
-                    .load()
-                    .is_some_and(|current_page| current_page.paddr() == page.paddr())
-            );
-            cursor.remove();
+            // removal, read/write and page fault handling. However, page
+            // reclamation may have removed the page, in which case the index
+            // may be vacant.
+            if cursor
+                .load()
+                .is_some_and(|current_page| current_page.paddr() == page.paddr())
+            {
+                cursor.remove();
+            }
         }
     }
 }
@@ -11,7 +11,10 @@ use xarray::XArray;
 use super::{Vmo, VmoFlags, WritableMappingStatus};
 use crate::{
     prelude::*,
-    vm::page_cache::{CachePage, CachePageMeta, PageCacheBackend},
+    vm::{
+        page_cache::{CachePage, CachePageMeta, PageCacheBackend},
+        reclaim::Rmap,
+    },
 };
 
 /// Options for allocating a root VMO.
@@ -96,8 +99,7 @@ impl VmoOptions {
             backend,
             ..
         } = self;
-        let vmo = alloc_vmo(size, flags, backend)?;
-        Ok(Arc::new(vmo))
+        alloc_vmo(size, flags, backend)
     }
 }
 
@@ -105,17 +107,19 @@ fn alloc_vmo(
     size: usize,
     flags: VmoFlags,
     backend: Option<Weak<dyn PageCacheBackend>>,
-) -> Result<Vmo> {
+) -> Result<Arc<Vmo>> {
     let size = size.align_up(PAGE_SIZE);
     let pages = committed_pages_if_continuous(flags, size)?;
     let writable_mapping_status = WritableMappingStatus::default();
-    Ok(Vmo {
+    Ok(Arc::new_cyclic(|weak_self| Vmo {
         backend,
         flags,
         pages,
         size: AtomicUsize::new(size),
         writable_mapping_status,
-    })
+        rmap: Rmap::new(),
+        weak_self: weak_self.clone(),
+    }))
 }
 
 fn committed_pages_if_continuous(flags: VmoFlags, size: usize) -> Result<XArray<CachePage>> {
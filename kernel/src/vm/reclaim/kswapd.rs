@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use ostd::sync::WaitQueue;
+use spin::Once;
+
+use super::shrink::{ScanControl, shrink_lists};
+use crate::{
+    prelude::*,
+    sched::{Nice, SchedPolicy},
+    thread::kernel_thread::ThreadOptions,
+    time::wait::WaitTimeout,
+    vm::mem_total,
+};
+
+/// The watermarks of free memory, in pages.
+struct Watermarks {
+    /// Below this watermark, kswapd starts to reclaim pages.
+    low: usize,
+    /// Above this watermark, kswapd stops reclaiming pages.
+    high: usize,
+}
+
+/// Returns the watermarks of free memory.
+fn watermarks() -> &'static Watermarks {
+    static WATERMARKS: Once<Watermarks> = Once::new();
+
+    WATERMARKS.call_once(|| {
+        // The minimum amount of free memory is calculated in the same way as
+        // `calculate_min_free_kbytes` in Linux.
+        //
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/page_alloc.c>
+        let total_kbytes = mem_total() / 1024;
+        let min_kbytes = (total_kbytes * 16).isqrt().clamp(128, 256 * 1024);
+
+        let min = min_kbytes * 1024 / PAGE_SIZE;
+        Watermarks {
+            low: min + min / 4,
+            high: min + min / 2,
+        }
+    })
+}
+
+/// Returns the number of free pages.
+fn nr_free_pages() -> usize {
+    osdk_frame_allocator::load_total_free_size() / PAGE_SIZE
+}
+
+static KSWAPD_WAIT_QUEUE: WaitQueue = WaitQueue::new();
+static IS_KSWAPD_WOKEN: AtomicBool = AtomicBool::new(false);
+
+/// The interval at which kswapd checks free memory even if it is not woken.
+const KSWAPD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The maximum number of consecutive passes that reclaim no pages before
+/// kswapd gives up.
+///
+/// A pass that reclaims no pages may still write back dirty pages, which can
+/// then be reclaimed in the following passes.
+const MAX_PASSES_WITHOUT_PROGRESS: usize = 3;
+
+pub(super) fn spawn_kswapd() {
+    ThreadOptions::new(kswapd_loop)
+        .sched_policy(SchedPolicy::Fair(Nice::default()))
+        .spawn();
+
+    // Free memory is checked on the slow paths of the frame allocator rather
+    // than on every page allocation.
+    osdk_frame_allocator::set_low_memory_handler(watermarks().low * PAGE_SIZE, wake_kswapd);
+}
+
+/// Wakes up kswapd.
+///
+/// This may be called in atomic mode, including IRQ context.
+pub(super) fn wake_kswapd() {
+    if !IS_KSWAPD_WOKEN.swap(true, Ordering::Relaxed) {
+        KSWAPD_WAIT_QUEUE.wake_one();
+    }
+}
+
+fn kswapd_loop() {
+    loop {
+        let _ = KSWAPD_WAIT_QUEUE.wait_until_or_timeout(
+            || IS_KSWAPD_WOKEN.swap(false, Ordering::Relaxed).then_some(()),
+            &KSWAPD_INTERVAL,
+        );
+
+        let watermarks = watermarks();
+        if nr_free_pages() >= watermarks.low {
+            continue;
+        }
+
+        let mut nr_passes_without_progress = 0;
+        while nr_passes_without_progress < MAX_PASSES_WITHOUT_PROGRESS {
+            let nr_free_pages = nr_free_pages();
+            if nr_free_pages >= watermarks.high {
+                break;
+            }
+
+            let sc = ScanControl {
+                nr_to_reclaim: watermarks.high - nr_free_pages,
+                may_writepage: true,
+                memcg: None,
+            };
+            if shrink_lists(&sc) == 0 {
+                nr_passes_without_progress += 1;
+            } else {
+                nr_passes_without_progress = 0;
+            }
+        }
+    }
+}
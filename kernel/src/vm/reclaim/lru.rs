@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::{
+    cpu::{CpuId, all_cpus},
+    cpu_local,
+    mm::Paddr,
+    sync::LocalIrqDisabled,
+    task::disable_preempt,
+};
+
+use crate::{
+    prelude::*,
+    vm::{page_cache::Vmo, vmar::Vmar},
+};
+
+/// The global LRU lists.
+///
+/// The lock disables local IRQs because pages may be freed in IRQ context
+/// (e.g., when a writeback completes), which updates the LRU lists.
+pub(super) static LRU_LISTS: SpinLock<LruLists, LocalIrqDisabled> = SpinLock::new(LruLists::new());
+
+cpu_local! {
+    /// The CPU-local batches of pending LRU updates.
+    static LRU_BATCHES: SpinLock<LruBatch, LocalIrqDisabled> = SpinLock::new(LruBatch::new());
+}
+
+/// The number of pending updates that trigger draining a CPU-local batch.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/pagevec.h>
+const LRU_BATCH_SIZE: usize = 15;
+
+/// The LRU lists of file pages and anonymous pages.
+pub(super) struct LruLists {
+    file: LruList,
+    anon: LruList,
+    /// The positions of the entries, indexed by the physical addresses of
+    /// their pages.
+    ///
+    /// A page has at most one entry in the LRU lists.
+    positions: BTreeMap<Paddr, LruPosition>,
+    /// The sequence number of the next entry to be added.
+    next_seq: u64,
+}
+
+/// The position of an entry in the LRU lists.
+#[derive(Clone, Copy, Debug)]
+struct LruPosition {
+    lru_type: LruType,
+    is_active: bool,
+    seq: u64,
+}
+
+impl LruLists {
+    const fn new() -> Self {
+        Self {
+            file: LruList::new(),
+            anon: LruList::new(),
+            positions: BTreeMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub(super) fn get(&self, lru_type: LruType) -> &LruList {
+        match lru_type {
+            LruType::File => &self.file,
+            LruType::Anon => &self.anon,
+        }
+    }
+
+    fn get_mut(&mut self, lru_type: LruType) -> &mut LruList {
+        match lru_type {
+            LruType::File => &mut self.file,
+            LruType::Anon => &mut self.anon,
+        }
+    }
+
+    /// Adds an entry as the most recently used one.
+    ///
+    /// An existing entry of the same page is replaced, since it refers to a
+    /// page that has been freed.
+    fn push_back(&mut self, lru_type: LruType, entry: LruEntry, is_active: bool) {
+        let paddr = entry.paddr();
+        self.remove(paddr);
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.get_mut(lru_type)
+            .list_mut(is_active)
+            .insert(seq, entry);
+        let position = LruPosition {
+            lru_type,
+            is_active,
+            seq,
+        };
+        self.positions.insert(paddr, position);
+    }
+
+    /// Puts back an entry that has been isolated by [`Self::isolate_inactive`].
+    ///
+    /// If the page has been freed and reused while the entry is isolated, the
+    /// entry is dropped because the page already has a newer entry.
+    pub(super) fn putback(&mut self, lru_type: LruType, entry: LruEntry, is_active: bool) {
+        if self.positions.contains_key(&entry.paddr()) {
+            return;
+        }
+        self.push_back(lru_type, entry, is_active);
+    }
+
+    /// Removes the entry of the page at `paddr`, if any.
+    fn remove(&mut self, paddr: Paddr) {
+        let Some(position) = self.positions.remove(&paddr) else {
+            return;
+        };
+        self.get_mut(position.lru_type)
+            .list_mut(position.is_active)
+            .remove(&position.seq);
+    }
+
+    /// Isolates at most `max_nr` least recently used entries from the
+    /// inactive list of `lru_type`.
+    pub(super) fn isolate_inactive(
+        &mut self,
+        lru_type: LruType,
+        max_nr: usize,
+        isolated: &mut Vec<LruEntry>,
+    ) {
+        for _ in 0..max_nr {
+            let Some((_, entry)) = self.get_mut(lru_type).inactive.pop_first() else {
+                break;
+            };
+            self.positions.remove(&entry.paddr());
+            isolated.push(entry);
+        }
+    }
+
+    /// Deactivates the least recently used active entries of `lru_type` until
+    /// the inactive list is no smaller than the active list.
+    ///
+    /// Deactivated pages are not lost. If they are referenced again before
+    /// being scanned in the inactive list, they will be activated again.
+    pub(super) fn balance(&mut self, lru_type: LruType) {
+        loop {
+            let list = self.get_mut(lru_type);
+            if list.inactive.len() >= list.active.len() {
+                break;
+            }
+            let (_, entry) = list.active.pop_first().unwrap();
+            self.push_back(lru_type, entry, false);
+        }
+    }
+}
+
+/// A pair of the active list and the inactive list.
+///
+/// In both lists, entries are ordered from the least recently used (the
+/// first) to the most recently used (the last) by their sequence numbers.
+pub(super) struct LruList {
+    active: BTreeMap<u64, LruEntry>,
+    inactive: BTreeMap<u64, LruEntry>,
+}
+
+impl LruList {
+    const fn new() -> Self {
+        Self {
+            active: BTreeMap::new(),
+            inactive: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the total number of entries in both lists.
+    pub(super) fn len(&self) -> usize {
+        self.active.len() + self.inactive.len()
+    }
+
+    /// Returns the number of entries in the inactive list.
+    pub(super) fn nr_inactive(&self) -> usize {
+        self.inactive.len()
+    }
+
+    fn list_mut(&mut self, is_active: bool) -> &mut BTreeMap<u64, LruEntry> {
+        if is_active {
+            &mut self.active
+        } else {
+            &mut self.inactive
+        }
+    }
+}
+
+/// The type of pages in an LRU list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum LruType {
+    /// Pages that can be written back to their files.
+    File,
+    /// Pages that are not backed by files.
+    Anon,
+}
+
+/// An entry in the LRU lists.
+///
+/// Entries are removed when their pages are freed. An entry may still become
+/// stale if its page is freed while the entry is pending in the batch of
+/// another CPU or is isolated for reclamation. Such an entry is dropped when
+/// it is scanned or when its page is reused.
+#[derive(Debug)]
+pub(super) enum LruEntry {
+    /// A page in a [`Vmo`].
+    Vmo {
+        vmo: Weak<Vmo>,
+        page_idx: usize,
+        paddr: Paddr,
+    },
+    /// A private anonymous page mapped in a [`Vmar`].
+    Anon {
+        vmar: Weak<Vmar>,
+        vaddr: Vaddr,
+        paddr: Paddr,
+    },
+}
+
+impl LruEntry {
+    pub(super) fn paddr(&self) -> Paddr {
+        match self {
+            Self::Vmo { paddr, .. } | Self::Anon { paddr, .. } => *paddr,
+        }
+    }
+}
+
+/// A CPU-local batch of pending LRU updates, like a pagevec in Linux.
+///
+/// Batching amortizes the cost of locking the global LRU lists on the hot
+/// paths of committing pages, handling page faults, and freeing pages.
+struct LruBatch {
+    added: Vec<(LruType, LruEntry)>,
+    freed: Vec<Paddr>,
+}
+
+impl LruBatch {
+    const fn new() -> Self {
+        Self {
+            added: Vec::new(),
+            freed: Vec::new(),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.added.len() + self.freed.len() >= LRU_BATCH_SIZE
+    }
+
+    /// Applies the pending updates to the global LRU lists.
+    fn drain_into(&mut self, lists: &mut LruLists) {
+        // A page may be added and then freed before the batch is drained, so
+        // the additions must be applied first.
+        for (lru_type, entry) in self.added.drain(..) {
+            lists.push_back(lru_type, entry, false);
+        }
+        for paddr in self.freed.drain(..) {
+            lists.remove(paddr);
+        }
+    }
+}
+
+/// Updates the CPU-local batch with `update` and drains it if it is full.
+fn update_local_batch(update: impl FnOnce(&mut LruBatch)) {
+    let preempt_guard = disable_preempt();
+    let mut batch = LRU_BATCHES.get_on_cpu(preempt_guard.current_cpu()).lock();
+
+    update(&mut batch);
+    if batch.is_full() {
+        // Lock order: CPU-local batch -> LRU lists
+        batch.drain_into(&mut LRU_LISTS.lock());
+    }
+}
+
+/// Adds a newly allocated page to the inactive list of `lru_type`.
+pub(super) fn add(lru_type: LruType, entry: LruEntry) {
+    update_local_batch(|batch| batch.added.push((lru_type, entry)));
+}
+
+/// Removes the entry of the freed page at `paddr`.
+pub(super) fn remove_freed(paddr: Paddr) {
+    update_local_batch(|batch| batch.freed.push(paddr));
+}
+
+/// Drains the pending updates of all CPUs into the global LRU lists.
+///
+/// This should be called before scanning the LRU lists.
+pub(super) fn drain_all() {
+    for cpu in all_cpus() {
+        drain_cpu(cpu);
+    }
+}
+
+fn drain_cpu(cpu: CpuId) {
+    let mut batch = LRU_BATCHES.get_on_cpu(cpu).lock();
+    if batch.added.is_empty() && batch.freed.is_empty() {
+        return;
+    }
+
+    batch.drain_into(&mut LRU_LISTS.lock());
+}
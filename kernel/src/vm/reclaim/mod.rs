@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Page reclamation.
+//!
+//! When free memory runs low, pages in the page cache and private anonymous
+//! pages are reclaimed to make room for new allocations. Pages are tracked in
+//! the global LRU lists, which are split into file pages and anonymous pages.
+//! Each kind has an active list and an inactive list. A new page is added to
+//! the inactive list. It is activated if it is referenced again before being
+//! scanned, and it is reclaimed otherwise. A page is removed from the LRU lists
+//! when it is freed. Additions and removals are batched per CPU before they
+//! reach the global LRU lists.
+//!
+//! To reclaim a page, the page is first unmapped from every [`Vmar`] found
+//! via the reverse mapping of its [`Vmo`] (see [`Rmap`]). A dirty page is then
+//! written back through its [`PageCacheBackend`], and a clean page is dropped
+//! from its [`Vmo`]. A private anonymous page is swapped out if swap space is
+//! available (see [`swap`]).
+//!
+//! Reclamation is performed in two ways:
+//!  - kswapd, a kernel thread, is woken up by the frame allocator once free
+//!    memory drops below the low watermark on an allocation slow path, and
+//!    reclaims pages until free memory reaches the high watermark;
+//!  - direct reclaim is performed synchronously when an allocation fails.
+//!
+//! Pages can also be reclaimed from a memory cgroup, when the cgroup reaches
+//! its limits. Only the pages charged to the cgroup are reclaimed then. Pages
+//! protected by `memory.min` and `memory.low` are skipped (see [`MemCgroup`]).
+//!
+//! [`Vmar`]: crate::vm::vmar::Vmar
+//! [`PageCacheBackend`]: crate::vm::page_cache::PageCacheBackend
+//! [`swap`]: crate::vm::swap
+//
+// TODO: Reclaim the pages in shared anonymous mappings, which are tracked in
+// the LRU lists but are never reclaimed.
+
+mod kswapd;
+mod lru;
+mod rmap;
+mod shrink;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use ostd::mm::{HasPaddr, Paddr};
+
+pub use self::rmap::Rmap;
+use self::{
+    lru::{LRU_LISTS, LruEntry, LruType},
+    shrink::{ScanControl, shrink_lists},
+};
+pub(in crate::vm) use self::{
+    rmap::{RmapOp, RmapResult},
+    shrink::ReclaimResult,
+};
+use crate::{
+    fs::cgroupfs::{CgroupNode, MemCgroup},
+    prelude::*,
+    process::Process,
+    vm::{
+        page_cache::{CachePage, Vmo},
+        vmar::Vmar,
+    },
+};
+
+pub(super) fn init_in_first_kthread() {
+    kswapd::spawn_kswapd();
+}
+
+/// The number of pages that direct reclaim tries to reclaim at a time.
+const DIRECT_RECLAIM_BATCH: usize = 32;
+
+/// The maximum number of times that an allocation is retried after direct
+/// reclaim.
+const MAX_RECLAIM_RETRIES: usize = 16;
+
+/// Adds a newly committed page at `page_idx` in `vmo` to the LRU lists.
+pub(in crate::vm) fn lru_add_vmo_page(vmo: &Vmo, page_idx: usize, page: &CachePage) {
+    let lru_type = if vmo.has_backend() {
+        LruType::File
+    } else {
+        LruType::Anon
+    };
+    let entry = LruEntry::Vmo {
+        vmo: vmo.weak_self().clone(),
+        page_idx,
+        paddr: page.paddr(),
+    };
+    lru::add(lru_type, entry);
+}
+
+/// Adds a newly mapped private anonymous page at `vaddr` in `vmar` to the
+/// LRU lists.
+pub(in crate::vm) fn lru_add_anon_page(vmar: &Vmar, vaddr: Vaddr, paddr: Paddr) {
+    let entry = LruEntry::Anon {
+        vmar: vmar.weak_self().clone(),
+        vaddr,
+        paddr,
+    };
+    lru::add(LruType::Anon, entry);
+}
+
+/// A handle in the metadata of a page that removes the page from the LRU
+/// lists when the page is freed.
+#[derive(Debug)]
+pub(in crate::vm) struct LruHandle {
+    paddr: AtomicUsize,
+}
+
+impl LruHandle {
+    /// The value of `paddr` before the page is allocated.
+    const NO_PADDR: Paddr = Paddr::MAX;
+
+    pub(in crate::vm) const fn new() -> Self {
+        Self {
+            paddr: AtomicUsize::new(Self::NO_PADDR),
+        }
+    }
+
+    /// Records the physical address of the page.
+    ///
+    /// This should be called right after the page is allocated.
+    pub(in crate::vm) fn set_paddr(&self, paddr: Paddr) {
+        self.paddr.store(paddr, Ordering::Relaxed);
+    }
+}
+
+impl Drop for LruHandle {
+    fn drop(&mut self) {
+        let paddr = *self.paddr.get_mut();
+        if paddr != Self::NO_PADDR {
+            lru::remove_freed(paddr);
+        }
+    }
+}
+
+/// Reclaims pages synchronously.
+///
+/// Dirty pages are not written back during direct reclaim. Instead, kswapd is
+/// woken up to write them back.
+///
+/// Returns whether some pages have been reclaimed.
+pub fn direct_reclaim() -> bool {
+    kswapd::wake_kswapd();
+
+    let sc = ScanControl {
+        nr_to_reclaim: DIRECT_RECLAIM_BATCH,
+        may_writepage: false,
+        memcg: None,
+    };
+    shrink_lists(&sc) > 0
+}
+
+/// Reclaims at most `nr_to_reclaim` pages charged to `memcg` or its
+/// descendants.
+///
+/// Dirty pages are written back only if `may_writepage` is true, which is
+/// allowed only if no filesystem locks are held.
+///
+/// Returns the number of reclaimed pages.
+pub fn reclaim_memcg(memcg: &MemCgroup, nr_to_reclaim: usize, may_writepage: bool) -> usize {
+    let sc = ScanControl {
+        nr_to_reclaim: nr_to_reclaim.min(DIRECT_RECLAIM_BATCH),
+        may_writepage,
+        memcg: Some(memcg),
+    };
+    shrink_lists(&sc)
+}
+
+/// Returns whether an operation that fails with `err` should be retried.
+///
+/// If the operation fails due to insufficient memory, direct reclaim is
+/// performed. If the memory cgroup of the current process has reached its
+/// `memory.max` limit, pages are reclaimed from that cgroup instead. The
+/// operation should be retried if some pages have been reclaimed, unless it
+/// has been retried too many times. `nr_retries` tracks the number of retries
+/// of the operation.
+pub(in crate::vm) fn should_retry_after_reclaim(err: &Error, nr_retries: &mut usize) -> bool {
+    if err.error() != Errno::ENOMEM || *nr_retries >= MAX_RECLAIM_RETRIES {
+        return false;
+    }
+
+    *nr_retries += 1;
+    let limited_cgroup =
+        Process::current().and_then(|process| CgroupNode::find_memory_limited(&process));
+    match limited_cgroup {
+        Some((cgroup, _)) => {
+            reclaim_memcg(&MemCgroup::of(&cgroup), DIRECT_RECLAIM_BATCH, false) > 0
+        }
+        None => direct_reclaim(),
+    }
+}
+
+/// Returns the number of file pages in the LRU lists.
+///
+/// The number is an estimation because the pending updates in the CPU-local
+/// batches are not counted.
+pub fn nr_file_pages() -> usize {
+    LRU_LISTS.lock().get(LruType::File).len()
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::Paddr;
+
+use crate::{
+    prelude::*,
+    vm::{page_cache::Vmo, vmar::Vmar},
+};
+
+/// The reverse mapping of a [`Vmo`].
+///
+/// It records the [`Vmar`]s that have mapped the VMO, so that a page in the
+/// VMO can be found in and unmapped from every address space.
+///
+/// A VMAR is never removed from the reverse mapping when its mappings of the
+/// VMO are removed, since walking a VMAR that no longer maps the VMO is
+/// harmless. Dropped VMARs are pruned when new VMARs are added.
+#[derive(Debug)]
+pub struct Rmap {
+    vmars: SpinLock<Vec<Weak<Vmar>>>,
+}
+
+impl Rmap {
+    /// Creates an empty reverse mapping.
+    pub(in crate::vm) const fn new() -> Self {
+        Self {
+            vmars: SpinLock::new(Vec::new()),
+        }
+    }
+
+    /// Adds `vmar` to the reverse mapping.
+    pub(in crate::vm) fn add(&self, vmar: &Weak<Vmar>) {
+        let mut vmars = self.vmars.lock();
+        if vmars.iter().any(|existing| existing.ptr_eq(vmar)) {
+            return;
+        }
+
+        vmars.retain(|existing| existing.strong_count() > 0);
+        vmars.push(vmar.clone());
+    }
+
+    /// Applies `op` to all the page table entries that map the page at
+    /// `page_idx` in `vmo` to the frame at `paddr`.
+    pub(in crate::vm) fn apply(
+        &self,
+        vmo: &Vmo,
+        page_idx: usize,
+        paddr: Paddr,
+        op: RmapOp,
+    ) -> RmapResult {
+        let vmars: Vec<Arc<Vmar>> = self.vmars.lock().iter().filter_map(Weak::upgrade).collect();
+
+        let mut result = RmapResult::default();
+        for vmar in vmars {
+            vmar.rmap_vmo_page(vmo, page_idx, paddr, op, &mut result);
+        }
+        result
+    }
+}
+
+/// An operation on the page table entries found by reverse mappings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate::vm) enum RmapOp {
+    /// Collects the states of the entries only.
+    Query,
+    /// Clears the accessed bits of the entries.
+    ClearAccessed,
+    /// Removes the entries.
+    Unmap,
+}
+
+/// The result of an [`RmapOp`].
+#[derive(Debug, Default)]
+pub(in crate::vm) struct RmapResult {
+    /// The number of entries that map the page.
+    pub(in crate::vm) nr_mapped: usize,
+    /// Whether the accessed bit is set in any entry.
+    pub(in crate::vm) is_referenced: bool,
+    /// Whether the dirty bit is set in any entry.
+    pub(in crate::vm) is_dirty: bool,
+    /// Whether some entries cannot be checked because the VMARs are busy.
+    pub(in crate::vm) is_busy: bool,
+}
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use io_util::batch::IoBatch;
+
+use super::lru::{self, LRU_LISTS, LruEntry, LruType};
+use crate::{
+    fs::cgroupfs::{MemCgroup, MemProtection},
+    prelude::*,
+    vm::{memcg::with_page_charge, swap},
+};
+
+/// The maximum number of entries isolated from the LRU lists at a time.
+const SCAN_BATCH: usize = 32;
+
+/// The control parameters of a reclamation pass.
+#[derive(Clone, Copy)]
+pub(super) struct ScanControl<'a> {
+    /// The number of pages to reclaim.
+    pub(super) nr_to_reclaim: usize,
+    /// Whether dirty pages can be written back and anonymous pages can be
+    /// written to swap files.
+    ///
+    /// Direct reclaim may happen with filesystem locks held, while writing
+    /// back pages may require such locks. So only kswapd and the reclamation
+    /// of memory cgroups outside such contexts write back pages.
+    pub(super) may_writepage: bool,
+    /// The memory cgroup from which pages are reclaimed, or `None` if pages
+    /// are reclaimed from the whole system.
+    pub(super) memcg: Option<&'a MemCgroup>,
+}
+
+/// The result of trying to reclaim a page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate::vm) enum ReclaimResult {
+    /// The page has been reclaimed.
+    Reclaimed,
+    /// The page has been referenced recently, so it should be activated.
+    Referenced,
+    /// The page cannot be reclaimed for now.
+    ///
+    /// For example, the page may be locked, be mapped, or be under writeback.
+    Busy,
+    /// The page is no longer present, or it can never be reclaimed.
+    Gone,
+}
+
+/// Reclaims pages from the LRU lists.
+///
+/// File pages are reclaimed first, since they are cheaper to reclaim.
+/// Anonymous pages are swapped out only if file pages are not enough and there
+/// is free swap space.
+///
+/// Pages protected by `memory.min` are never reclaimed. Like Linux, pages
+/// protected by `memory.low` are reclaimed only if no other pages can be
+/// reclaimed.
+///
+/// Returns the number of reclaimed pages.
+pub(super) fn shrink_lists(sc: &ScanControl) -> usize {
+    lru::drain_all();
+
+    let nr_reclaimed = shrink_lists_with(sc, MemProtection::Low);
+    if nr_reclaimed > 0 {
+        return nr_reclaimed;
+    }
+    shrink_lists_with(sc, MemProtection::Min)
+}
+
+/// Reclaims pages that are protected less than `protected`.
+fn shrink_lists_with(sc: &ScanControl, protected: MemProtection) -> usize {
+    let nr_reclaimed = shrink_list(LruType::File, sc, protected);
+    if nr_reclaimed >= sc.nr_to_reclaim || swap::nr_free_pages() == 0 {
+        return nr_reclaimed;
+    }
+
+    let anon_sc = ScanControl {
+        nr_to_reclaim: sc.nr_to_reclaim - nr_reclaimed,
+        ..*sc
+    };
+    nr_reclaimed + shrink_list(LruType::Anon, &anon_sc, protected)
+}
+
+/// Scans an inactive list and reclaims the pages in it.
+///
+/// Returns the number of reclaimed pages.
+fn shrink_list(lru_type: LruType, sc: &ScanControl, protected: MemProtection) -> usize {
+    let nr_to_scan = {
+        let mut lists = LRU_LISTS.lock();
+        lists.balance(lru_type);
+        lists.get(lru_type).nr_inactive()
+    };
+
+    let mut io_batch = IoBatch::new();
+    let mut isolated = Vec::with_capacity(SCAN_BATCH);
+    let mut kept = Vec::with_capacity(SCAN_BATCH);
+    let mut nr_scanned = 0;
+    let mut nr_reclaimed = 0;
+
+    while nr_scanned < nr_to_scan && nr_reclaimed < sc.nr_to_reclaim {
+        let nr_to_isolate = SCAN_BATCH.min(nr_to_scan - nr_scanned);
+        LRU_LISTS
+            .lock()
+            .isolate_inactive(lru_type, nr_to_isolate, &mut isolated);
+        if isolated.is_empty() {
+            break;
+        }
+        nr_scanned += isolated.len();
+
+        // Reclaim the pages without holding the lock, since it involves
+        // walking page tables and submitting I/O.
+        for entry in isolated.drain(..) {
+            if !may_reclaim(&entry, sc, protected) {
+                kept.push((entry, false));
+                continue;
+            }
+            match shrink_page(&entry, sc, &mut io_batch) {
+                ReclaimResult::Reclaimed => nr_reclaimed += 1,
+                ReclaimResult::Referenced => kept.push((entry, true)),
+                ReclaimResult::Busy => kept.push((entry, false)),
+                ReclaimResult::Gone => (),
+            }
+        }
+
+        let mut lists = LRU_LISTS.lock();
+        for (entry, is_referenced) in kept.drain(..) {
+            lists.putback(lru_type, entry, is_referenced);
+        }
+    }
+
+    // Wait for the writeback, so the written pages can be reclaimed in the
+    // next pass.
+    if let Err(err) = io_batch.wait_all() {
+        warn!("failed to write back pages for reclamation: {:?}", err);
+    }
+
+    nr_reclaimed
+}
+
+/// Returns whether the page of `entry` may be reclaimed, considering the
+/// memory cgroup that it is charged to.
+fn may_reclaim(entry: &LruEntry, sc: &ScanControl, protected: MemProtection) -> bool {
+    with_page_charge(entry.paddr(), |charge| {
+        let Some(charge) = charge else {
+            // Pages that are not charged are reclaimed only globally.
+            return sc.memcg.is_none();
+        };
+        if sc.memcg.is_some_and(|memcg| !charge.is_charged_to(memcg)) {
+            return false;
+        }
+
+        let protection = charge.protection(sc.memcg);
+        if protection >= protected {
+            return false;
+        }
+        if protection == MemProtection::Low {
+            charge.record_low_reclaim();
+        }
+        true
+    })
+}
+
+fn shrink_page(entry: &LruEntry, sc: &ScanControl, io_batch: &mut IoBatch) -> ReclaimResult {
+    match entry {
+        LruEntry::Vmo {
+            vmo,
+            page_idx,
+            paddr,
+        } => {
+            let Some(vmo) = vmo.upgrade() else {
+                return ReclaimResult::Gone;
+            };
+            vmo.try_reclaim_page(*page_idx, *paddr, sc.may_writepage, io_batch)
+        }
+        LruEntry::Anon { vmar, vaddr, paddr } => {
+            let Some(vmar) = vmar.upgrade() else {
+                return ReclaimResult::Gone;
+            };
+            vmar.try_swap_out_page(*vaddr, *paddr, sc.may_writepage)
+        }
+    }
+}
@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_block::{
+    BlockDevice, SECTOR_SIZE,
+    bio::{BioDirection, BioSegment, BioStatus},
+    id::Bid,
+};
+use ostd::mm::{FrameAllocOptions, UFrame, USegment, VmIo, io::util::HasVmReaderWriter};
+
+use super::MAX_SWAP_PAGES;
+use crate::{
+    fs::{
+        file::{InodeType, StatusFlags},
+        vfs::{
+            inode::Inode,
+            path::Path,
+            registry::{self, FsProperties},
+        },
+    },
+    prelude::*,
+};
+
+/// The storage that backs a swap area.
+pub enum SwapBacking {
+    /// A block device, which is typically a disk partition.
+    Partition(Arc<dyn BlockDevice>),
+    /// A regular file.
+    File(Arc<dyn Inode>),
+}
+
+impl SwapBacking {
+    /// Returns the storage at `path`.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.type_() {
+            InodeType::BlockDevice => {
+                let device = path
+                    .metadata()
+                    .self_dev_id
+                    .and_then(aster_block::lookup)
+                    .ok_or_else(|| Error::with_message(Errno::ENODEV, "the device is not found"))?;
+                Ok(Self::Partition(device))
+            }
+            InodeType::File => {
+                // The pages written to a file in a memory-backed filesystem
+                // stay in memory, so swapping them out frees nothing.
+                let is_on_disk = registry::look_up(path.fs().name())
+                    .is_some_and(|fs_type| fs_type.properties().contains(FsProperties::NEED_DISK));
+                if !is_on_disk {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the swap file is not in a disk-backed filesystem"
+                    );
+                }
+                Ok(Self::File(path.inode().clone()))
+            }
+            _ => return_errno_with_message!(
+                Errno::EINVAL,
+                "the swap area is neither a block device nor a regular file"
+            ),
+        }
+    }
+
+    /// Returns whether `self` and `other` refer to the same storage.
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Partition(this), Self::Partition(other)) => this.id() == other.id(),
+            (Self::File(this), Self::File(other)) => Arc::ptr_eq(this, other),
+            _ => false,
+        }
+    }
+
+    /// Returns the size of the storage in pages.
+    fn nr_pages(&self) -> usize {
+        match self {
+            Self::Partition(device) => device.metadata().nr_sectors * SECTOR_SIZE / PAGE_SIZE,
+            Self::File(inode) => inode.size() / PAGE_SIZE,
+        }
+    }
+
+    fn read_page(&self, idx: usize, frame: &UFrame) -> Result<()> {
+        match self {
+            Self::Partition(device) => {
+                let bio_segment = BioSegment::new_from_segment(
+                    USegment::from(frame.clone()),
+                    BioDirection::FromDevice,
+                );
+                match device.read_blocks(Bid::new(idx as u64), bio_segment)? {
+                    BioStatus::Complete => Ok(()),
+                    err_status => Err(Error::from(err_status)),
+                }
+            }
+            Self::File(inode) => {
+                // Bypass the page cache, which would otherwise allocate memory
+                // for the pages that are being swapped out.
+                let mut writer = frame.writer().to_fallible();
+                let read_len =
+                    inode.read_at(idx * PAGE_SIZE, &mut writer, StatusFlags::O_DIRECT)?;
+                if read_len != PAGE_SIZE {
+                    return_errno_with_message!(Errno::EIO, "the swap file is truncated");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn write_page(&self, idx: usize, frame: &UFrame) -> Result<()> {
+        match self {
+            Self::Partition(device) => {
+                let bio_segment = BioSegment::new_from_segment(
+                    USegment::from(frame.clone()),
+                    BioDirection::ToDevice,
+                );
+                match device.write_blocks(Bid::new(idx as u64), bio_segment)? {
+                    BioStatus::Complete => Ok(()),
+                    err_status => Err(Error::from(err_status)),
+                }
+            }
+            Self::File(inode) => {
+                let mut reader = frame.reader().to_fallible();
+                let written_len =
+                    inode.write_at(idx * PAGE_SIZE, &mut reader, StatusFlags::O_DIRECT)?;
+                if written_len != PAGE_SIZE {
+                    return_errno_with_message!(Errno::EIO, "the swap file is truncated");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The information at the beginning of a swap area, written by `mkswap`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/swap.h#L134-L155>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct SwapHeaderInfo {
+    version: u32,
+    last_page: u32,
+    nr_badpages: u32,
+    uuid: [u8; 16],
+    volume_name: [u8; 16],
+}
+
+/// The offset of [`SwapHeaderInfo`] in the first page.
+///
+/// The bytes before it are reserved for boot loaders and disk labels.
+const SWAP_HEADER_INFO_OFFSET: usize = 1024;
+/// The offset of the list of bad pages in the first page.
+const SWAP_BADPAGES_OFFSET: usize = SWAP_HEADER_INFO_OFFSET + 512;
+/// The maximum number of bad pages.
+const MAX_SWAP_BADPAGES: usize =
+    (PAGE_SIZE - SWAP_MAGIC.len() - SWAP_BADPAGES_OFFSET) / size_of::<u32>();
+/// The signature at the end of the first page.
+const SWAP_MAGIC: &[u8; 10] = b"SWAPSPACE2";
+
+/// The usage of a slot that must not be used, e.g., the header and bad pages.
+const SLOT_BAD: u32 = u32::MAX;
+
+/// An enabled swap area.
+pub(super) struct SwapArea {
+    backing: SwapBacking,
+    /// The absolute path, which is shown in `/proc/swaps`.
+    name: String,
+    priority: i16,
+    /// The number of usable slots, excluding the header and bad pages.
+    nr_pages: usize,
+    slots: SpinLock<SwapSlots>,
+    /// Whether new slots can be allocated.
+    ///
+    /// This is cleared while the area is being turned off.
+    is_writable: AtomicBool,
+}
+
+/// The usage of the slots in a swap area.
+struct SwapSlots {
+    /// The number of swap entries that refer to each slot.
+    ///
+    /// A slot is free if its count is zero.
+    counts: Vec<u32>,
+    nr_free: usize,
+    /// The slot from which the next allocation starts searching.
+    next: usize,
+}
+
+impl SwapArea {
+    /// Creates a swap area by reading the swap header from `backing`.
+    pub(super) fn new(backing: SwapBacking, name: String, priority: i16) -> Result<Self> {
+        let header: UFrame = FrameAllocOptions::new().alloc_frame()?.into();
+        backing.read_page(0, &header)?;
+
+        let mut magic = [0u8; SWAP_MAGIC.len()];
+        header.read_bytes(PAGE_SIZE - SWAP_MAGIC.len(), &mut magic[..])?;
+        if &magic != SWAP_MAGIC {
+            return_errno_with_message!(Errno::EINVAL, "the swap signature is not found");
+        }
+
+        let info = header.read_val::<SwapHeaderInfo>(SWAP_HEADER_INFO_OFFSET)?;
+        if info.version != 1 {
+            return_errno_with_message!(Errno::EINVAL, "the swap header version is unsupported");
+        }
+        if info.last_page == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the swap area is empty");
+        }
+        let nr_badpages = info.nr_badpages as usize;
+        if nr_badpages > MAX_SWAP_BADPAGES {
+            return_errno_with_message!(Errno::EINVAL, "the swap area has too many bad pages");
+        }
+
+        let max_pages = (info.last_page as usize + 1).min(MAX_SWAP_PAGES);
+        if max_pages > backing.nr_pages() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the swap area is smaller than the swap header indicates"
+            );
+        }
+
+        let mut counts = vec![0; max_pages];
+        // The first page holds the swap header.
+        counts[0] = SLOT_BAD;
+        for i in 0..nr_badpages {
+            let bad_page =
+                header.read_val::<u32>(SWAP_BADPAGES_OFFSET + i * size_of::<u32>())? as usize;
+            if bad_page == 0 || bad_page > info.last_page as usize {
+                return_errno_with_message!(Errno::EINVAL, "the bad page is out of the swap area");
+            }
+            if let Some(count) = counts.get_mut(bad_page) {
+                *count = SLOT_BAD;
+            }
+        }
+
+        let nr_pages = counts.iter().filter(|count| **count == 0).count();
+        if nr_pages == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the swap area has no usable pages");
+        }
+
+        Ok(Self {
+            backing,
+            name,
+            priority,
+            nr_pages,
+            slots: SpinLock::new(SwapSlots {
+                counts,
+                nr_free: nr_pages,
+                next: 1,
+            }),
+            is_writable: AtomicBool::new(true),
+        })
+    }
+
+    pub(super) fn backing(&self) -> &SwapBacking {
+        &self.backing
+    }
+
+    /// Returns whether the area is backed by `backing`.
+    pub(super) fn is_backed_by(&self, backing: &SwapBacking) -> bool {
+        self.backing.is_same(backing)
+    }
+
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(super) fn priority(&self) -> i16 {
+        self.priority
+    }
+
+    /// Returns the number of usable slots.
+    pub(super) fn nr_pages(&self) -> usize {
+        self.nr_pages
+    }
+
+    /// Returns the number of free slots.
+    pub(super) fn nr_free(&self) -> usize {
+        self.slots.lock().nr_free
+    }
+
+    pub(super) fn set_writable(&self, is_writable: bool) {
+        self.is_writable.store(is_writable, Ordering::Relaxed);
+    }
+
+    /// Allocates a free slot, whose count is set to one.
+    pub(super) fn alloc_slot(&self) -> Option<usize> {
+        if !self.is_writable.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut slots = self.slots.lock();
+        if slots.nr_free == 0 {
+            return None;
+        }
+
+        let nr_slots = slots.counts.len();
+        let start = slots.next;
+        let offset = (start..nr_slots)
+            .chain(1..start)
+            .find(|offset| slots.counts[*offset] == 0)?;
+
+        slots.counts[offset] = 1;
+        slots.nr_free -= 1;
+        slots.next = if offset + 1 < nr_slots { offset + 1 } else { 1 };
+        Some(offset)
+    }
+
+    /// Increases the count of an allocated slot.
+    pub(super) fn dup_slot(&self, offset: usize) {
+        let mut slots = self.slots.lock();
+        let count = &mut slots.counts[offset];
+        debug_assert!(*count != 0 && *count != SLOT_BAD);
+        *count += 1;
+    }
+
+    /// Decreases the count of an allocated slot, and frees the slot if the
+    /// count drops to zero.
+    pub(super) fn free_slot(&self, offset: usize) {
+        let mut slots = self.slots.lock();
+        let count = &mut slots.counts[offset];
+        debug_assert!(*count != 0 && *count != SLOT_BAD);
+        *count -= 1;
+        if *count == 0 {
+            slots.nr_free += 1;
+        }
+    }
+
+    pub(super) fn read_page(&self, offset: usize, frame: &UFrame) -> Result<()> {
+        self.backing.read_page(offset, frame)
+    }
+
+    pub(super) fn write_page(&self, offset: usize, frame: &UFrame) -> Result<()> {
+        self.backing.write_page(offset, frame)
+    }
+}
@@ -0,0 +1,310 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Swapping.
+//!
+//! Private anonymous pages have no files to be written back to. To reclaim
+//! them, page reclamation writes them to swap areas, which are block devices
+//! or regular files formatted by `mkswap` and enabled by `swapon`.
+//!
+//! A swapped-out page is located by a [`SwapEntry`], which consists of the
+//! index of a swap area and the offset of a slot in the area. The swap entry
+//! is stored as a token in the non-present page table entry that used to map
+//! the page, where the page fault handler finds it to swap the page in. All
+//! tokens in user page tables are swap entries.
+//!
+//! A slot is counted by the swap entries that refer to it. Swap entries are
+//! duplicated when page tables are copied on fork, and they are freed when
+//! they are unmapped or swapped in.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/swapfile.c>
+//
+// TODO: Pages in shared anonymous mappings are kept in their `Vmo`s and are
+// not swapped out yet.
+
+mod area;
+
+use ostd::mm::{UFrame, vm_space::TOKEN_BITS};
+
+use self::area::SwapArea;
+pub use self::area::SwapBacking;
+use crate::{
+    prelude::*,
+    process::{Process, pid_table},
+    thread::Thread,
+    vm::memcg::alloc_anon_frame,
+};
+
+/// The maximum number of swap areas.
+const MAX_SWAP_AREAS: usize = 32;
+
+/// The number of bits of the area index in a swap entry.
+const AREA_IDX_BITS: usize = MAX_SWAP_AREAS.ilog2() as usize;
+
+/// The maximum number of pages in a swap area.
+const MAX_SWAP_PAGES: usize = 1 << (TOKEN_BITS - AREA_IDX_BITS);
+
+/// The location of a swapped-out page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate::vm) struct SwapEntry {
+    area_idx: usize,
+    offset: usize,
+}
+
+impl SwapEntry {
+    /// Decodes a swap entry from a token in a page table.
+    pub(in crate::vm) fn from_token(token: usize) -> Self {
+        Self {
+            area_idx: token & (MAX_SWAP_AREAS - 1),
+            offset: token >> AREA_IDX_BITS,
+        }
+    }
+
+    /// Encodes the swap entry as a token in a page table.
+    pub(in crate::vm) fn to_token(self) -> usize {
+        (self.offset << AREA_IDX_BITS) | self.area_idx
+    }
+
+    /// Returns the index of the swap area.
+    pub(in crate::vm) fn area_idx(&self) -> usize {
+        self.area_idx
+    }
+}
+
+/// The enabled swap areas.
+struct SwapAreas {
+    /// The swap areas, indexed by the area indexes in swap entries.
+    areas: [Option<Arc<SwapArea>>; MAX_SWAP_AREAS],
+    /// The indexes of the swap areas in the descending order of priority.
+    by_priority: Vec<usize>,
+    /// The priority of the last swap area that is enabled without a priority.
+    least_priority: i16,
+}
+
+static SWAP_AREAS: RwLock<SwapAreas> = RwLock::new(SwapAreas {
+    areas: [const { None }; MAX_SWAP_AREAS],
+    by_priority: Vec::new(),
+    least_priority: 0,
+});
+
+/// Serializes `swapon` and `swapoff`, which may take a long time.
+static SWAPON_MUTEX: Mutex<()> = Mutex::new(());
+
+fn area_of(entry: SwapEntry) -> Arc<SwapArea> {
+    SWAP_AREAS.read().areas[entry.area_idx].clone().unwrap()
+}
+
+/// Enables a swap area.
+///
+/// If `priority` is `None`, the area has a lower priority than all the areas
+/// that are enabled before.
+pub fn swapon(backing: SwapBacking, name: String, priority: Option<i16>) -> Result<()> {
+    let _guard = SWAPON_MUTEX.lock();
+
+    {
+        let swap_areas = SWAP_AREAS.read();
+        if swap_areas
+            .areas
+            .iter()
+            .flatten()
+            .any(|area| area.is_backed_by(&backing))
+        {
+            return_errno_with_message!(Errno::EBUSY, "the swap area is already enabled");
+        }
+    }
+
+    let priority = priority.unwrap_or(SWAP_AREAS.read().least_priority - 1);
+    // Reading the swap header involves I/O, so no locks other than
+    // `SWAPON_MUTEX` are held.
+    let area = Arc::new(SwapArea::new(backing, name, priority)?);
+
+    let mut swap_areas = SWAP_AREAS.write();
+    let Some(area_idx) = swap_areas.areas.iter().position(Option::is_none) else {
+        return_errno_with_message!(Errno::EPERM, "too many swap areas are enabled");
+    };
+    swap_areas.areas[area_idx] = Some(area);
+    let pos = swap_areas
+        .by_priority
+        .iter()
+        .position(|idx| swap_areas.areas[*idx].as_ref().unwrap().priority() < priority)
+        .unwrap_or(swap_areas.by_priority.len());
+    swap_areas.by_priority.insert(pos, area_idx);
+    swap_areas.least_priority = swap_areas.least_priority.min(priority);
+
+    Ok(())
+}
+
+/// Disables the swap area that is backed by `backing`.
+///
+/// All the pages in the swap area are swapped in before the area is disabled.
+pub fn swapoff(backing: &SwapBacking) -> Result<()> {
+    let _guard = SWAPON_MUTEX.lock();
+
+    let (area_idx, area) = {
+        let swap_areas = SWAP_AREAS.read();
+        let Some((area_idx, area)) = swap_areas
+            .areas
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, area)| Some((idx, area.as_ref()?)))
+            .find(|(_, area)| area.is_backed_by(backing))
+        else {
+            return_errno_with_message!(Errno::EINVAL, "the swap area is not enabled");
+        };
+        (area_idx, area.clone())
+    };
+
+    area.set_writable(false);
+    if let Err(err) = unuse_area(area_idx, &area) {
+        area.set_writable(true);
+        return Err(err);
+    }
+
+    let mut swap_areas = SWAP_AREAS.write();
+    swap_areas.areas[area_idx] = None;
+    swap_areas.by_priority.retain(|idx| *idx != area_idx);
+
+    Ok(())
+}
+
+/// Swaps in all the pages in the swap area.
+fn unuse_area(area_idx: usize, area: &SwapArea) -> Result<()> {
+    /// The maximum number of consecutive passes that swap in no pages.
+    ///
+    /// A pass may swap in no pages if the remaining pages are being swapped
+    /// in by others or are being copied to processes that are being forked.
+    const MAX_PASSES_WITHOUT_PROGRESS: usize = 3;
+
+    let mut nr_passes_without_progress = 0;
+    loop {
+        let nr_used = area.nr_pages() - area.nr_free();
+        if nr_used == 0 {
+            return Ok(());
+        }
+        if nr_passes_without_progress >= MAX_PASSES_WITHOUT_PROGRESS {
+            return_errno_with_message!(Errno::EBUSY, "the swap area is still in use");
+        }
+
+        let processes: Vec<Arc<Process>> = pid_table::pid_table_mut().iter_processes().collect();
+        for process in processes {
+            let vmar_guard = process.lock_vmar();
+            if let Some(vmar) = vmar_guard.as_ref() {
+                vmar.unuse_swap_area(area_idx)?;
+            }
+        }
+
+        if area.nr_pages() - area.nr_free() < nr_used {
+            nr_passes_without_progress = 0;
+        } else {
+            nr_passes_without_progress += 1;
+            Thread::yield_now();
+        }
+    }
+}
+
+/// Allocates a swap entry, whose slot is counted once.
+///
+/// If `may_use_file` is false, only the swap areas on block devices are used,
+/// since writing to files may require filesystem locks.
+///
+/// Returns `None` if no slots are available.
+pub(in crate::vm) fn alloc_entry(may_use_file: bool) -> Option<SwapEntry> {
+    let swap_areas = SWAP_AREAS.read();
+    swap_areas.by_priority.iter().find_map(|area_idx| {
+        let area = swap_areas.areas[*area_idx].as_ref().unwrap();
+        if !may_use_file && matches!(area.backing(), SwapBacking::File(_)) {
+            return None;
+        }
+        let offset = area.alloc_slot()?;
+        Some(SwapEntry {
+            area_idx: *area_idx,
+            offset,
+        })
+    })
+}
+
+/// Increases the count of the slot of a swap entry.
+///
+/// This is done when the swap entry is copied, or when the slot needs to be
+/// pinned while its page is being read.
+pub(in crate::vm) fn dup_entry(entry: SwapEntry) {
+    area_of(entry).dup_slot(entry.offset);
+}
+
+/// Decreases the count of the slot of a swap entry, and frees the slot if the
+/// count drops to zero.
+pub(in crate::vm) fn free_entry(entry: SwapEntry) {
+    area_of(entry).free_slot(entry.offset);
+}
+
+/// Writes `frame` to the slot of a swap entry.
+pub(in crate::vm) fn write_page(entry: SwapEntry, frame: &UFrame) -> Result<()> {
+    area_of(entry).write_page(entry.offset, frame)
+}
+
+/// Reads the page in the slot of a swap entry into a new frame.
+///
+/// The new frame is charged to the memory cgroup of the current process.
+pub(in crate::vm) fn read_page(entry: SwapEntry) -> Result<UFrame> {
+    let frame = alloc_anon_frame(false)?.into();
+    area_of(entry).read_page(entry.offset, &frame)?;
+    Ok(frame)
+}
+
+/// Returns whether any swap area is enabled.
+///
+/// If not, there are no swap entries in page tables.
+pub(in crate::vm) fn is_enabled() -> bool {
+    !SWAP_AREAS.read().by_priority.is_empty()
+}
+
+/// Returns the total number of usable pages in all swap areas.
+pub fn nr_total_pages() -> usize {
+    SWAP_AREAS
+        .read()
+        .areas
+        .iter()
+        .flatten()
+        .map(|area| area.nr_pages())
+        .sum()
+}
+
+/// Returns the number of free pages in all swap areas.
+pub fn nr_free_pages() -> usize {
+    SWAP_AREAS
+        .read()
+        .areas
+        .iter()
+        .flatten()
+        .map(|area| area.nr_free())
+        .sum()
+}
+
+/// The information of a swap area, which is shown in `/proc/swaps`.
+pub struct SwapAreaInfo {
+    /// The absolute path of the swap area.
+    pub name: String,
+    /// Whether the swap area is a block device rather than a regular file.
+    pub is_partition: bool,
+    /// The number of usable pages.
+    pub nr_pages: usize,
+    /// The number of pages in use.
+    pub nr_used: usize,
+    pub priority: i16,
+}
+
+/// Returns the information of all swap areas.
+pub fn swap_area_infos() -> Vec<SwapAreaInfo> {
+    SWAP_AREAS
+        .read()
+        .areas
+        .iter()
+        .flatten()
+        .map(|area| SwapAreaInfo {
+            name: area.name().to_string(),
+            is_partition: matches!(area.backing(), SwapBacking::Partition(_)),
+            nr_pages: area.nr_pages(),
+            nr_used: area.nr_pages() - area.nr_free(),
+            priority: area.priority(),
+        })
+        .collect()
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The `/sys/kernel/mm` sysfs directory.
+//!
+//! The directory holds the knobs of memory management subsystems, e.g., the
+//! `transparent_hugepage` directory (see [`thp`]).
+//!
+//! [`thp`]: super::thp
+
+use aster_systree::{
+    BranchNodeFields, Result, SysAttrSetBuilder, SysNode, SysPerms, SysStr, inherit_sys_branch_node,
+};
+use inherit_methods_macro::inherit_methods;
+use spin::Once;
+
+use crate::prelude::*;
+
+pub(super) fn init() {
+    let node = MM_SYS_NODE.call_once(MmSysNode::new);
+    crate::fs::sysfs::register_kernel_sysnode(node.clone()).unwrap();
+}
+
+/// Registers a `SysNode` under the `/sys/kernel/mm` directory.
+pub(super) fn register(node: Arc<dyn SysNode>) -> Result<()> {
+    MM_SYS_NODE.get().unwrap().add_child(node)
+}
+
+static MM_SYS_NODE: Once<Arc<MmSysNode>> = Once::new();
+
+/// A systree node representing the `/sys/kernel/mm` directory.
+#[derive(Debug)]
+struct MmSysNode {
+    fields: BranchNodeFields<dyn SysNode, Self>,
+}
+
+#[inherit_methods(from = "self.fields")]
+impl MmSysNode {
+    fn new() -> Arc<Self> {
+        let name = SysStr::from("mm");
+        let attrs = SysAttrSetBuilder::new().build().unwrap();
+        Arc::new_cyclic(|weak_self| {
+            let fields = BranchNodeFields::new(name, attrs, weak_self.clone());
+
+            MmSysNode { fields }
+        })
+    }
+
+    fn add_child(&self, new_child: Arc<dyn SysNode>) -> Result<()>;
+}
+
+inherit_sys_branch_node!(MmSysNode, fields, {
+    fn perms(&self) -> SysPerms {
+        SysPerms::DEFAULT_RW_PERMS
+    }
+});
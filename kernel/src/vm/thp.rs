@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Transparent huge pages (THP) for anonymous memory.
+//!
+//! A page fault in a private anonymous mapping is handled by mapping a huge
+//! page if the huge-page-aligned range around the faulting address lies within
+//! the mapping and nothing in the range has been mapped yet. If no huge page
+//! can be allocated or charged, the fault falls back to mapping a base page.
+//!
+//! A huge page is split into base pages when only part of it is unmapped,
+//! protected, or remapped, or when it is written after being shared
+//! copy-on-write by `fork`.
+//!
+//! Whether huge pages are used is controlled by the
+//! `/sys/kernel/mm/transparent_hugepage/enabled` knob:
+//!  - `always`: huge pages are used for all eligible mappings, except those
+//!    advised with `MADV_NOHUGEPAGE`;
+//!  - `madvise`: huge pages are used only for the mappings advised with
+//!    `MADV_HUGEPAGE`;
+//!  - `never`: huge pages are never used.
+//!
+//! Changing the knob or the advice does not affect the huge pages that have
+//! already been mapped.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/mm/transhuge.html>
+//
+// TODO: Reclaim huge pages. They are not tracked in the LRU lists for now, so
+// they stay in memory until they are unmapped.
+
+use alloc::format;
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use aster_systree::{
+    Error, MAX_ATTR_SIZE, NormalNodeFields, Result, SysAttrSetBuilder, SysPerms, SysStr,
+    inherit_sys_leaf_node,
+};
+use aster_util::printer::VmPrinter;
+use ostd::{
+    impl_untyped_frame_meta_for,
+    mm::{FrameAllocOptions, Segment, vm_space::HUGE_PAGE_SIZE},
+};
+
+use crate::{
+    fs::cgroupfs::{MemCharge, MemChargeKind},
+    prelude::*,
+    vm::memcg::try_charge_page,
+};
+
+pub(super) fn init() {
+    super::sysfs::register(TransparentHugepageNode::new()).unwrap();
+}
+
+/// The advice of a mapping on the use of transparent huge pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThpAdvice {
+    /// No advice is given (the default).
+    None,
+    /// The mapping is worth backing with huge pages (`MADV_HUGEPAGE`).
+    Huge,
+    /// The mapping should not be backed with huge pages (`MADV_NOHUGEPAGE`).
+    NoHuge,
+}
+
+impl ThpAdvice {
+    /// Returns whether a mapping with the advice may be backed with huge pages
+    /// under the current mode.
+    pub(in crate::vm) fn allows_huge_pages(self) -> bool {
+        if HUGE_PAGE_SIZE.is_none() {
+            return false;
+        }
+
+        match ThpMode::current() {
+            ThpMode::Always => self != ThpAdvice::NoHuge,
+            ThpMode::Madvise => self == ThpAdvice::Huge,
+            ThpMode::Never => false,
+        }
+    }
+}
+
+/// The mode of transparent huge pages.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+enum ThpMode {
+    Always = 0,
+    Madvise = 1,
+    Never = 2,
+}
+
+static THP_MODE: AtomicU8 = AtomicU8::new(ThpMode::Always as u8);
+
+impl ThpMode {
+    const ALL: [ThpMode; 3] = [ThpMode::Always, ThpMode::Madvise, ThpMode::Never];
+
+    fn current() -> Self {
+        Self::try_from(THP_MODE.load(Ordering::Relaxed)).unwrap()
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ThpMode::Always => "always",
+            ThpMode::Madvise => "madvise",
+            ThpMode::Never => "never",
+        }
+    }
+}
+
+/// The number of base pages in the allocated anonymous huge pages.
+static NR_ANON_HUGE_BASE_PAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of base pages in the allocated anonymous huge pages.
+///
+/// The pages of a huge page are still counted after it is split, until they
+/// are freed.
+pub fn nr_anon_huge_pages() -> usize {
+    NR_ANON_HUGE_BASE_PAGES.load(Ordering::Relaxed)
+}
+
+/// Metadata for a base page in an anonymous huge page.
+///
+/// Each base page is charged to the memory cgroup separately, so the charges
+/// remain correct after the huge page is split and partially freed.
+#[derive(Debug)]
+pub(in crate::vm) struct AnonHugePageMeta {
+    _charge: Option<MemCharge>,
+}
+
+impl_untyped_frame_meta_for!(AnonHugePageMeta);
+
+impl AnonHugePageMeta {
+    fn new(charge: Option<MemCharge>) -> Self {
+        NR_ANON_HUGE_BASE_PAGES.fetch_add(1, Ordering::Relaxed);
+        Self { _charge: charge }
+    }
+}
+
+impl Drop for AnonHugePageMeta {
+    fn drop(&mut self) {
+        NR_ANON_HUGE_BASE_PAGES.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Allocates a zeroed anonymous huge page and charges it to the memory cgroup
+/// of the current process.
+///
+/// Returns `None` if huge pages are not supported or if the huge page cannot
+/// be allocated or charged. In that case, the caller should fall back to base
+/// pages instead of reclaiming memory.
+///
+/// The allocation does not block, so it can be done in atomic mode.
+pub(in crate::vm) fn alloc_anon_huge_page() -> Option<Segment<AnonHugePageMeta>> {
+    let huge_page_size = HUGE_PAGE_SIZE?;
+    let nr_pages = huge_page_size / PAGE_SIZE;
+
+    // The base pages are charged once the huge page is allocated, so a failed
+    // allocation never charges the memory cgroup.
+    let mut is_charged = true;
+    let segment = FrameAllocOptions::new()
+        .zeroed(true)
+        .align(huge_page_size)
+        .alloc_segment_with(nr_pages, |_| {
+            let charge = if is_charged {
+                try_charge_page(MemChargeKind::Anon).unwrap_or_else(|_| {
+                    is_charged = false;
+                    None
+                })
+            } else {
+                None
+            };
+            AnonHugePageMeta::new(charge)
+        })
+        .ok()?;
+    // Dropping the segment uncharges the base pages that have been charged.
+    if !is_charged {
+        return None;
+    }
+
+    Some(segment)
+}
+
+/// A systree node representing the `/sys/kernel/mm/transparent_hugepage`
+/// directory.
+#[derive(Debug)]
+struct TransparentHugepageNode {
+    fields: NormalNodeFields<Self>,
+}
+
+impl TransparentHugepageNode {
+    fn new() -> Arc<Self> {
+        let name = SysStr::from("transparent_hugepage");
+
+        let mut builder = SysAttrSetBuilder::new();
+        builder.add(SysStr::from("enabled"), SysPerms::DEFAULT_RW_ATTR_PERMS);
+        let attrs = builder.build().unwrap();
+
+        Arc::new_cyclic(|weak_self| {
+            let fields = NormalNodeFields::new(name, attrs, weak_self.clone());
+
+            TransparentHugepageNode { fields }
+        })
+    }
+}
+
+inherit_sys_leaf_node!(TransparentHugepageNode, fields, {
+    fn perms(&self) -> SysPerms {
+        SysPerms::DEFAULT_RW_PERMS
+    }
+
+    fn read_attr_at(&self, name: &str, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        if name != "enabled" {
+            return Err(Error::AttributeError);
+        }
+
+        // The current mode is enclosed in brackets, e.g., `always [madvise] never`.
+        let current = ThpMode::current();
+        let modes = ThpMode::ALL.map(|mode| {
+            if mode == current {
+                format!("[{}]", mode.name())
+            } else {
+                String::from(mode.name())
+            }
+        });
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        writeln!(printer, "{}", modes.join(" "))?;
+        Ok(printer.bytes_written())
+    }
+
+    fn write_attr(&self, name: &str, reader: &mut VmReader) -> Result<usize> {
+        if name != "enabled" {
+            return Err(Error::AttributeError);
+        }
+
+        let (content, len) = reader
+            .read_cstring_until_end(MAX_ATTR_SIZE)
+            .map_err(|_| Error::PageFault)?;
+        let value = content
+            .to_str()
+            .map_err(|_| Error::InvalidOperation)?
+            .trim();
+        let mode = ThpMode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == value)
+            .ok_or(Error::InvalidOperation)?;
+
+        THP_MODE.store(mode as u8, Ordering::Relaxed);
+
+        Ok(len)
+    }
+});
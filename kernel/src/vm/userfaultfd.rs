@@ -0,0 +1,875 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Userfaultfd, which allows the user space to handle page faults.
+//!
+//! A userfaultfd is created by the `userfaultfd` system call and is bound to
+//! the address space of the creating process. After the API handshake
+//! (`UFFDIO_API`), the user space can register ranges of private anonymous
+//! mappings to it (`UFFDIO_REGISTER`) in the following modes:
+//!  - `UFFDIO_REGISTER_MODE_MISSING`: a fault on a page that is not mapped is
+//!    reported, instead of mapping a zeroed page;
+//!  - `UFFDIO_REGISTER_MODE_WP`: a write fault on a page that has been
+//!    write-protected by `UFFDIO_WRITEPROTECT` is reported.
+//!
+//! A reported fault blocks the faulting thread, and a `UFFD_EVENT_PAGEFAULT`
+//! message can be read from the userfaultfd. The faulting thread is woken up
+//! after the user space resolves the fault with `UFFDIO_COPY`,
+//! `UFFDIO_ZEROPAGE`, or `UFFDIO_WRITEPROTECT`, or wakes it up explicitly with
+//! `UFFDIO_WAKE`. The faulting access is then retried.
+//!
+//! Closing the userfaultfd unregisters all the ranges and wakes up all the
+//! faulting threads.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/mm/userfaultfd.html>
+//
+// TODO: Support shmem and hugetlb mappings, minor faults, non-cooperative
+// events (`UFFD_FEATURE_EVENT_*`), and `UFFD_FEATURE_SIGBUS`. Also, the write
+// protection of a page is lost if the page is swapped out.
+
+use alloc::collections::VecDeque;
+use core::{
+    fmt::Display,
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use align_ext::AlignExt;
+use ostd::{
+    mm::{Frame, PageFlags, io::util::HasVmReaderWriter},
+    sync::WaitQueue,
+};
+
+use crate::{
+    context::current_userspace,
+    events::IoEvents,
+    fs::{
+        file::{AccessMode, CreationFlags, FileLike, StatusFlags, file_table::FdFlags},
+        pseudofs::AnonInodeFs,
+        vfs::path::Path,
+    },
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable, Pollee},
+    },
+    util::ioctl::{RawIoctl, dispatch_ioctl},
+    vm::{
+        memcg::{AnonPageMeta, alloc_anon_frame},
+        vmar::{VMAR_CAP_ADDR, Vmar, is_userspace_vaddr},
+    },
+};
+
+/// The page flag that marks a page as write-protected by a userfaultfd.
+///
+/// A write-protected page is always mapped without [`PageFlags::W`], so that
+/// writing to it triggers a page fault.
+pub(in crate::vm) const UFFD_WP_PAGE_FLAG: PageFlags = PageFlags::AVAIL2;
+
+/// A userfaultfd.
+pub struct UserfaultfdFile {
+    ctx: Arc<Userfaultfd>,
+    is_nonblocking: AtomicBool,
+    /// The pseudo path associated with this userfaultfd file.
+    pseudo_path: Path,
+}
+
+impl UserfaultfdFile {
+    /// Creates a userfaultfd that handles the page faults in the VMAR.
+    pub fn new(vmar: &Vmar, is_nonblocking: bool, is_user_mode_only: bool) -> Self {
+        let ctx = Arc::new(Userfaultfd {
+            vmar: vmar.weak_self().clone(),
+            is_user_mode_only,
+            state: Mutex::new(State {
+                features: None,
+                faults: VecDeque::new(),
+                next_fault_id: 0,
+                is_released: false,
+            }),
+            pollee: Pollee::new(),
+            fault_wait_queue: WaitQueue::new(),
+        });
+
+        Self {
+            ctx,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pseudo_path: AnonInodeFs::new_path(|_| "anon_inode:[userfaultfd]".to_string()),
+        }
+    }
+}
+
+impl Drop for UserfaultfdFile {
+    fn drop(&mut self) {
+        self.ctx.release();
+    }
+}
+
+impl Pollable for UserfaultfdFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.ctx
+            .pollee
+            .poll_with(mask, poller, || self.ctx.check_io_events())
+    }
+}
+
+impl FileLike for UserfaultfdFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let msg_len = size_of::<UffdMsg>();
+        if writer.avail() < msg_len {
+            return_errno_with_message!(Errno::EINVAL, "the message buffer is too small");
+        }
+        if !self.ctx.is_api_done() {
+            return_errno_with_message!(Errno::EINVAL, "the API handshake has not been done");
+        }
+
+        // Block until at least one message is available.
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.ctx.try_read(writer)?;
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.ctx.try_read(writer))?;
+        }
+
+        // Read more messages if there are any.
+        let mut read_len = msg_len;
+        while writer.avail() >= msg_len && self.ctx.try_read(writer).is_ok() {
+            read_len += msg_len;
+        }
+
+        Ok(read_len)
+    }
+
+    fn ioctl(&self, raw_ioctl: RawIoctl) -> Result<i32> {
+        use ioctl_defs::*;
+
+        dispatch_ioctl!(match raw_ioctl {
+            cmd @ Api => {
+                let mut api = cmd.read()?;
+                let res = self.ctx.handle_api(&mut api);
+                cmd.write(&api)?;
+                res?;
+            }
+            cmd @ Register => {
+                let mut register = cmd.read()?;
+                self.ctx.register(&mut register)?;
+                cmd.write(&register)?;
+            }
+            cmd @ Unregister => {
+                let range = cmd.read()?;
+                self.ctx.unregister(&range)?;
+            }
+            cmd @ Wake => {
+                let range = cmd.read()?;
+                self.ctx.check_api_done()?;
+                let range = check_range(&range)?;
+                self.ctx.wake(&range);
+            }
+            cmd @ Copy => {
+                let mut copy = cmd.read()?;
+                let res = self.ctx.copy(&mut copy);
+                cmd.write(&copy)?;
+                res?;
+            }
+            cmd @ ZeroPage => {
+                let mut zeropage = cmd.read()?;
+                let res = self.ctx.zeropage(&mut zeropage);
+                cmd.write(&zeropage)?;
+                res?;
+            }
+            cmd @ WriteProtect => {
+                let writeprotect = cmd.read()?;
+                self.ctx.write_protect(&writeprotect)?;
+            }
+            _ => return_errno_with_message!(Errno::ENOTTY, "the ioctl command is unknown"),
+        });
+
+        Ok(0)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/userfaultfd.c>
+        AccessMode::O_RDWR
+    }
+
+    fn path(&self) -> &Path {
+        &self.pseudo_path
+    }
+
+    fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
+        struct FdInfo {
+            inner: Arc<UserfaultfdFile>,
+            fd_flags: FdFlags,
+        }
+
+        impl Display for FdInfo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                let mut flags = self.inner.status_flags().bits() | self.inner.access_mode() as u32;
+                if self.fd_flags.contains(FdFlags::CLOEXEC) {
+                    flags |= CreationFlags::O_CLOEXEC.bits();
+                }
+
+                let (nr_pending, nr_total, features) = {
+                    let state = self.inner.ctx.state.lock();
+                    let nr_pending = state.faults.iter().filter(|fault| !fault.is_read).count();
+                    let features = state.features.unwrap_or(UffdFeatures::empty());
+                    (nr_pending, state.faults.len(), features)
+                };
+
+                writeln!(f, "pos:\t{}", 0)?;
+                writeln!(f, "flags:\t0{:o}", flags)?;
+                writeln!(f, "mnt_id:\t{}", AnonInodeFs::mount_node().id())?;
+                writeln!(f, "ino:\t{}", AnonInodeFs::shared_inode().ino())?;
+                writeln!(
+                    f,
+                    "pending:\t{}\ntotal:\t{}\nAPI:\t{:x}:{:x}:{:x}",
+                    nr_pending,
+                    nr_total,
+                    UFFD_API,
+                    features.bits(),
+                    SUPPORTED_IOCTLS
+                )
+            }
+        }
+
+        Box::new(FdInfo {
+            inner: self,
+            fd_flags,
+        })
+    }
+}
+
+/// The context of a userfaultfd.
+///
+/// The memory mappings registered to a userfaultfd keep a reference to the
+/// context, since it outlives the userfaultfd file until all the faulting
+/// threads leave.
+pub(in crate::vm) struct Userfaultfd {
+    /// The VMAR whose page faults are handled.
+    vmar: Weak<Vmar>,
+    /// Whether only the page faults in user mode are handled.
+    ///
+    /// Page faults in kernel mode (e.g., when the kernel copies data from or
+    /// to the user space) fail with `EFAULT` instead.
+    is_user_mode_only: bool,
+    state: Mutex<State>,
+    pollee: Pollee,
+    /// The wait queue for the faulting threads.
+    fault_wait_queue: WaitQueue,
+}
+
+struct State {
+    /// The features enabled by `UFFDIO_API`.
+    ///
+    /// This is `None` if the API handshake has not been done.
+    features: Option<UffdFeatures>,
+    /// The faults that wait to be resolved.
+    faults: VecDeque<PendingFault>,
+    next_fault_id: u64,
+    /// Whether the userfaultfd file has been closed.
+    is_released: bool,
+}
+
+struct PendingFault {
+    id: u64,
+    /// The page-aligned address of the fault.
+    page_addr: Vaddr,
+    msg: UffdMsg,
+    /// Whether the message has been read by the user space.
+    is_read: bool,
+}
+
+impl Userfaultfd {
+    fn check_io_events(&self) -> IoEvents {
+        let state = self.state.lock();
+
+        if state.features.is_none() {
+            return IoEvents::ERR;
+        }
+        if state.faults.iter().any(|fault| !fault.is_read) {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<()> {
+        let mut state = self.state.lock();
+
+        let Some(fault) = state.faults.iter_mut().find(|fault| !fault.is_read) else {
+            return_errno_with_message!(Errno::EAGAIN, "no page faults are pending");
+        };
+        writer.write_val(&fault.msg)?;
+        fault.is_read = true;
+
+        self.pollee.invalidate();
+
+        Ok(())
+    }
+
+    fn is_api_done(&self) -> bool {
+        self.state.lock().features.is_some()
+    }
+
+    fn check_api_done(&self) -> Result<()> {
+        if !self.is_api_done() {
+            return_errno_with_message!(Errno::EINVAL, "the API handshake has not been done");
+        }
+        Ok(())
+    }
+
+    fn vmar(&self) -> Result<Arc<Vmar>> {
+        self.vmar.upgrade().ok_or_else(|| {
+            Error::with_message(Errno::ESRCH, "the address space has been destroyed")
+        })
+    }
+
+    fn handle_api(&self, api: &mut UffdioApi) -> Result<()> {
+        let mut state = self.state.lock();
+
+        if state.features.is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the API handshake has been done");
+        }
+        if api.api != UFFD_API {
+            api.features = 0;
+            api.ioctls = 0;
+            return_errno_with_message!(Errno::EINVAL, "the API version is not supported");
+        }
+        let Some(features) = UffdFeatures::from_bits(api.features) else {
+            api.features = 0;
+            api.ioctls = 0;
+            return_errno_with_message!(Errno::EINVAL, "the requested features are not supported");
+        };
+
+        state.features = Some(features);
+        self.pollee.invalidate();
+
+        // Report all the supported features, as Linux does.
+        api.features = UffdFeatures::all().bits();
+        api.ioctls = SUPPORTED_IOCTLS;
+
+        Ok(())
+    }
+
+    fn register(self: &Arc<Self>, register: &mut UffdioRegister) -> Result<()> {
+        self.check_api_done()?;
+
+        let mode = UffdRegisterMode::from_bits(register.mode)
+            .filter(|mode| !mode.is_empty())
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the register mode is invalid"))?;
+        let range = check_range(&register.range)?;
+
+        self.vmar()?
+            .register_userfaultfd(range, UffdRegistration::new(self.clone(), mode))?;
+
+        register.ioctls = if mode.contains(UffdRegisterMode::WP) {
+            SUPPORTED_RANGE_IOCTLS
+        } else {
+            SUPPORTED_RANGE_IOCTLS & !(1 << UFFDIO_WRITEPROTECT_NR)
+        };
+
+        Ok(())
+    }
+
+    fn unregister(self: &Arc<Self>, range: &UffdioRange) -> Result<()> {
+        self.check_api_done()?;
+        let range = check_range(range)?;
+
+        self.vmar()?.unregister_userfaultfd(self, range.clone())?;
+
+        // Faults in the range are no longer reported, so the faulting threads
+        // can handle them normally.
+        self.wake(&range);
+
+        Ok(())
+    }
+
+    fn copy(self: &Arc<Self>, copy: &mut UffdioCopy) -> Result<()> {
+        self.check_api_done()?;
+
+        let mode = UffdCopyMode::from_bits(copy.mode)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the copy mode is invalid"))?;
+        let range = check_range(&UffdioRange {
+            start: copy.dst,
+            len: copy.len,
+        })?;
+        let src = copy.src as Vaddr;
+        if !src.is_multiple_of(PAGE_SIZE) {
+            return_errno_with_message!(Errno::EINVAL, "the source address is not page-aligned");
+        }
+        if src
+            .checked_add(range.len())
+            .is_none_or(|src_end| src_end > VMAR_CAP_ADDR)
+        {
+            return_errno_with_message!(Errno::EINVAL, "the source range is invalid");
+        }
+
+        let (nr_bytes, res) = self.fill(&range, mode.contains(UffdCopyMode::WP), |page_addr| {
+            let frame = alloc_anon_frame(false)?;
+            let src_addr = src + (page_addr - range.start);
+            frame
+                .writer()
+                .to_fallible()
+                .write_fallible(&mut current_userspace!().reader(src_addr, PAGE_SIZE)?)?;
+            Ok(frame)
+        });
+        copy.copy = filled_result(nr_bytes, &res);
+
+        if !mode.contains(UffdCopyMode::DONTWAKE) && nr_bytes > 0 {
+            self.wake(&(range.start..range.start + nr_bytes));
+        }
+        check_filled(nr_bytes, res)
+    }
+
+    fn zeropage(self: &Arc<Self>, zeropage: &mut UffdioZeropage) -> Result<()> {
+        self.check_api_done()?;
+
+        let mode = UffdZeropageMode::from_bits(zeropage.mode)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the zeropage mode is invalid"))?;
+        let range = check_range(&zeropage.range)?;
+
+        let (nr_bytes, res) = self.fill(&range, false, |_| alloc_anon_frame(true));
+        zeropage.zeropage = filled_result(nr_bytes, &res);
+
+        if !mode.contains(UffdZeropageMode::DONTWAKE) && nr_bytes > 0 {
+            self.wake(&(range.start..range.start + nr_bytes));
+        }
+        check_filled(nr_bytes, res)
+    }
+
+    /// Fills the pages in the range with the frames returned by `alloc_frame`.
+    ///
+    /// Returns the number of bytes that have been filled and the result of
+    /// the operation.
+    fn fill<F>(
+        self: &Arc<Self>,
+        range: &Range<Vaddr>,
+        is_wp: bool,
+        mut alloc_frame: F,
+    ) -> (usize, Result<()>)
+    where
+        F: FnMut(Vaddr) -> Result<Frame<AnonPageMeta>>,
+    {
+        let vmar = match self.vmar() {
+            Ok(vmar) => vmar,
+            Err(err) => return (0, Err(err)),
+        };
+
+        for page_addr in range.clone().step_by(PAGE_SIZE) {
+            let res = alloc_frame(page_addr)
+                .and_then(|frame| vmar.fill_userfault_page(self, page_addr, frame, is_wp));
+            if let Err(err) = res {
+                return (page_addr - range.start, Err(err));
+            }
+        }
+
+        (range.len(), Ok(()))
+    }
+
+    fn write_protect(self: &Arc<Self>, writeprotect: &UffdioWriteprotect) -> Result<()> {
+        self.check_api_done()?;
+
+        let mode = UffdWriteprotectMode::from_bits(writeprotect.mode).ok_or_else(|| {
+            Error::with_message(Errno::EINVAL, "the write protection mode is invalid")
+        })?;
+        if mode.contains(UffdWriteprotectMode::WP | UffdWriteprotectMode::DONTWAKE) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "`UFFDIO_WRITEPROTECT_MODE_DONTWAKE` is only valid when clearing write protection"
+            );
+        }
+        let range = check_range(&writeprotect.range)?;
+
+        let is_wp = mode.contains(UffdWriteprotectMode::WP);
+        self.vmar()?
+            .write_protect_userfault(self, range.clone(), is_wp)?;
+
+        if !is_wp && !mode.contains(UffdWriteprotectMode::DONTWAKE) {
+            self.wake(&range);
+        }
+
+        Ok(())
+    }
+
+    /// Wakes up the threads waiting for the faults in the range.
+    fn wake(&self, range: &Range<Vaddr>) {
+        let mut state = self.state.lock();
+
+        let nr_faults = state.faults.len();
+        state
+            .faults
+            .retain(|fault| !range.contains(&fault.page_addr));
+        if state.faults.len() == nr_faults {
+            return;
+        }
+        drop(state);
+
+        self.pollee.invalidate();
+        self.fault_wait_queue.wake_all();
+    }
+
+    /// Releases the userfaultfd after its file is closed.
+    fn release(self: &Arc<Self>) {
+        {
+            let mut state = self.state.lock();
+            state.is_released = true;
+            state.faults.clear();
+        }
+
+        if let Some(vmar) = self.vmar.upgrade() {
+            vmar.release_userfaultfd(self);
+        }
+
+        self.fault_wait_queue.wake_all();
+    }
+
+    /// Reports a page fault to the user space and waits for it to be resolved.
+    ///
+    /// If this method returns `Ok`, the faulting access should be retried.
+    fn handle_fault(&self, address: Vaddr, flags: u64) -> Result<()> {
+        let id = {
+            let mut state = self.state.lock();
+
+            if state.is_released {
+                // The mapping will be unregistered soon, so the fault can be
+                // handled normally after retrying.
+                return Ok(());
+            }
+            let features = state.features.unwrap_or(UffdFeatures::empty());
+
+            let page_addr = address.align_down(PAGE_SIZE);
+            let msg = UffdMsg {
+                event: UFFD_EVENT_PAGEFAULT,
+                flags,
+                address: if features.contains(UffdFeatures::EXACT_ADDRESS) {
+                    address as u64
+                } else {
+                    page_addr as u64
+                },
+                ptid: if features.contains(UffdFeatures::THREAD_ID) {
+                    current_thread!().as_posix_thread().unwrap().tid()
+                } else {
+                    0
+                },
+                ..UffdMsg::new_zeroed()
+            };
+
+            let id = state.next_fault_id;
+            state.next_fault_id += 1;
+            state.faults.push_back(PendingFault {
+                id,
+                page_addr,
+                msg,
+                is_read: false,
+            });
+            id
+        };
+        self.pollee.notify(IoEvents::IN);
+
+        let res = self.fault_wait_queue.pause_until(|| {
+            let state = self.state.lock();
+            (state.is_released || state.faults.iter().all(|fault| fault.id != id)).then_some(())
+        });
+
+        if res.is_err() {
+            // The fault is no longer waiting to be resolved.
+            self.state.lock().faults.retain(|fault| fault.id != id);
+            self.pollee.invalidate();
+        }
+
+        res
+    }
+}
+
+/// The registration of a memory mapping to a userfaultfd.
+#[derive(Clone)]
+pub(in crate::vm) struct UffdRegistration {
+    ctx: Arc<Userfaultfd>,
+    mode: UffdRegisterMode,
+}
+
+impl UffdRegistration {
+    fn new(ctx: Arc<Userfaultfd>, mode: UffdRegisterMode) -> Self {
+        Self { ctx, mode }
+    }
+
+    /// Returns the userfaultfd of the registration.
+    pub(in crate::vm) fn ctx(&self) -> &Arc<Userfaultfd> {
+        &self.ctx
+    }
+
+    /// Returns whether the registration is to the userfaultfd.
+    pub(in crate::vm) fn is_to(&self, ctx: &Arc<Userfaultfd>) -> bool {
+        Arc::ptr_eq(&self.ctx, ctx)
+    }
+
+    /// Returns whether faults on pages that are not mapped are reported.
+    pub(in crate::vm) fn reports_missing(&self) -> bool {
+        self.mode.contains(UffdRegisterMode::MISSING)
+    }
+
+    /// Returns whether write faults on write-protected pages are reported.
+    pub(in crate::vm) fn reports_wp(&self) -> bool {
+        self.mode.contains(UffdRegisterMode::WP)
+    }
+
+    /// Creates a fault to be reported to the user space.
+    ///
+    /// This method fails if the fault occurs in kernel mode while the
+    /// userfaultfd only handles faults in user mode.
+    pub(in crate::vm) fn new_fault(
+        &self,
+        address: Vaddr,
+        is_write: bool,
+        is_wp: bool,
+        is_user_mode: bool,
+    ) -> Result<Userfault> {
+        if self.ctx.is_user_mode_only && !is_user_mode {
+            return_errno_with_message!(
+                Errno::EFAULT,
+                "the userfaultfd does not handle page faults in kernel mode"
+            );
+        }
+
+        let mut flags = 0;
+        if is_write {
+            flags |= UFFD_PAGEFAULT_FLAG_WRITE;
+        }
+        if is_wp {
+            flags |= UFFD_PAGEFAULT_FLAG_WP;
+        }
+
+        Ok(Userfault {
+            ctx: self.ctx.clone(),
+            address,
+            flags,
+        })
+    }
+}
+
+impl PartialEq for UffdRegistration {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_to(&other.ctx) && self.mode == other.mode
+    }
+}
+
+impl Debug for UffdRegistration {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UffdRegistration")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A page fault to be handled by a userfaultfd.
+pub(in crate::vm) struct Userfault {
+    ctx: Arc<Userfaultfd>,
+    address: Vaddr,
+    flags: u64,
+}
+
+impl Userfault {
+    /// Reports the fault to the user space and waits for it to be resolved.
+    ///
+    /// If this method returns `Ok`, the faulting access should be retried.
+    /// The waiting can be interrupted by signals, in which case `EINTR` is
+    /// returned.
+    pub(in crate::vm) fn wait(self) -> Result<()> {
+        self.ctx.handle_fault(self.address, self.flags)
+    }
+}
+
+/// Checks and converts a range in the arguments of the ioctls.
+fn check_range(range: &UffdioRange) -> Result<Range<Vaddr>> {
+    let start = range.start as Vaddr;
+    let len = range.len as usize;
+
+    if !start.is_multiple_of(PAGE_SIZE) || !len.is_multiple_of(PAGE_SIZE) {
+        return_errno_with_message!(Errno::EINVAL, "the range is not page-aligned");
+    }
+    if len == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the range is empty");
+    }
+    let end = start
+        .checked_add(len)
+        .filter(|end| is_userspace_vaddr(start) && *end <= VMAR_CAP_ADDR)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the range is not in user space"))?;
+
+    Ok(start..end)
+}
+
+/// Returns the value reported in the `copy` or `zeropage` field.
+fn filled_result(nr_bytes: usize, res: &Result<()>) -> i64 {
+    match res {
+        Err(err) if nr_bytes == 0 => -(err.error() as i64),
+        _ => nr_bytes as i64,
+    }
+}
+
+/// Returns the result of `UFFDIO_COPY` or `UFFDIO_ZEROPAGE`.
+fn check_filled(nr_bytes: usize, res: Result<()>) -> Result<()> {
+    match res {
+        Ok(()) => Ok(()),
+        Err(_) if nr_bytes > 0 => {
+            return_errno_with_message!(Errno::EAGAIN, "the range is only partially filled")
+        }
+        Err(err) => Err(err),
+    }
+}
+
+const UFFD_API: u64 = 0xAA;
+
+const UFFD_EVENT_PAGEFAULT: u8 = 0x12;
+
+const UFFD_PAGEFAULT_FLAG_WRITE: u64 = 1 << 0;
+const UFFD_PAGEFAULT_FLAG_WP: u64 = 1 << 1;
+
+const UFFDIO_REGISTER_NR: u8 = 0x00;
+const UFFDIO_UNREGISTER_NR: u8 = 0x01;
+const UFFDIO_WAKE_NR: u8 = 0x02;
+const UFFDIO_COPY_NR: u8 = 0x03;
+const UFFDIO_ZEROPAGE_NR: u8 = 0x04;
+const UFFDIO_WRITEPROTECT_NR: u8 = 0x06;
+const UFFDIO_API_NR: u8 = 0x3F;
+
+/// The ioctls supported on userfaultfds.
+const SUPPORTED_IOCTLS: u64 =
+    (1 << UFFDIO_REGISTER_NR) | (1 << UFFDIO_UNREGISTER_NR) | (1 << UFFDIO_API_NR);
+
+/// The ioctls supported on registered ranges.
+const SUPPORTED_RANGE_IOCTLS: u64 = (1 << UFFDIO_WAKE_NR)
+    | (1 << UFFDIO_COPY_NR)
+    | (1 << UFFDIO_ZEROPAGE_NR)
+    | (1 << UFFDIO_WRITEPROTECT_NR);
+
+bitflags! {
+    /// The features of userfaultfds (`UFFD_FEATURE_*`).
+    struct UffdFeatures: u64 {
+        const PAGEFAULT_FLAG_WP = 1 << 0;
+        const THREAD_ID         = 1 << 8;
+        const EXACT_ADDRESS     = 1 << 11;
+    }
+}
+
+bitflags! {
+    #[derive(PartialEq, Eq, Debug)]
+    struct UffdRegisterMode: u64 {
+        const MISSING = 1 << 0;
+        const WP      = 1 << 1;
+    }
+}
+
+bitflags! {
+    struct UffdCopyMode: u64 {
+        const DONTWAKE = 1 << 0;
+        const WP       = 1 << 1;
+    }
+}
+
+bitflags! {
+    struct UffdZeropageMode: u64 {
+        const DONTWAKE = 1 << 0;
+    }
+}
+
+bitflags! {
+    struct UffdWriteprotectMode: u64 {
+        const WP       = 1 << 0;
+        const DONTWAKE = 1 << 1;
+    }
+}
+
+/// The message read from a userfaultfd (`struct uffd_msg`).
+///
+/// Only page fault events are supported, so the union in the Linux definition
+/// is flattened to the fields of `pagefault`.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdMsg {
+    event: u8,
+    flags: u64,
+    address: u64,
+    ptid: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdioApi {
+    api: u64,
+    features: u64,
+    ioctls: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdioRange {
+    start: u64,
+    len: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdioRegister {
+    range: UffdioRange,
+    mode: u64,
+    ioctls: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdioCopy {
+    dst: u64,
+    src: u64,
+    len: u64,
+    mode: u64,
+    copy: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdioZeropage {
+    range: UffdioRange,
+    mode: u64,
+    zeropage: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct UffdioWriteprotect {
+    range: UffdioRange,
+    mode: u64,
+}
+
+mod ioctl_defs {
+    use super::{
+        UffdioApi, UffdioCopy, UffdioRange, UffdioRegister, UffdioWriteprotect, UffdioZeropage,
+    };
+    use crate::util::ioctl::{InOutData, OutData, ioc};
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/userfaultfd.h>
+
+    // Note that `UFFDIO_UNREGISTER` and `UFFDIO_WAKE` are defined with `_IOR`, although their
+    // arguments are input-only.
+
+    pub(super) type Api          = ioc!(UFFDIO_API,          0xAA, 0x3F, InOutData<UffdioApi>);
+    pub(super) type Register     = ioc!(UFFDIO_REGISTER,     0xAA, 0x00, InOutData<UffdioRegister>);
+    pub(super) type Unregister   = ioc!(UFFDIO_UNREGISTER,   0xAA, 0x01, OutData<UffdioRange>);
+    pub(super) type Wake         = ioc!(UFFDIO_WAKE,         0xAA, 0x02, OutData<UffdioRange>);
+    pub(super) type Copy         = ioc!(UFFDIO_COPY,         0xAA, 0x03, InOutData<UffdioCopy>);
+    pub(super) type ZeroPage     = ioc!(UFFDIO_ZEROPAGE,     0xAA, 0x04, InOutData<UffdioZeropage>);
+    pub(super) type WriteProtect = ioc!(UFFDIO_WRITEPROTECT, 0xAA, 0x06, InOutData<UffdioWriteprotect>);
+}
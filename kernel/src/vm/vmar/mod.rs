@@ -13,11 +13,17 @@ use ostd::mm::Vaddr;
 
 pub use self::{
     handle::VmarHandle,
+    vm_mapping::{VmMapping, VmoMappingObserver},
     vmar_impls::{RssType, Vmar, map::VmarMapOffset, page_fault::PageFaultInfo},
 };
 
 pub const VMAR_LOWEST_ADDR: Vaddr = 0x001_0000; // 64 KiB is the Linux configurable default
 pub const VMAR_CAP_ADDR: Vaddr = ostd::mm::MAX_USERSPACE_VADDR;
+/// The cap address of the user space of 32-bit programs.
+///
+/// Linux calls this `IA32_PAGE_OFFSET`.
+#[cfg(target_arch = "x86_64")]
+pub const COMPAT_VMAR_CAP_ADDR: Vaddr = 0xFFFF_E000;
 
 /// Returns whether the input `vaddr` is a legal user space virtual address.
 pub fn is_userspace_vaddr(vaddr: Vaddr) -> bool {
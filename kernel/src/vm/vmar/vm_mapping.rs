@@ -12,13 +12,20 @@ use aster_util::printer::VmPrinter;
 use ostd::{
     io::IoMem,
     mm::{
-        CachePolicy, Frame, FrameAllocOptions, PageFlags, PageProperty, UFrame, VmSpace,
-        io::util::HasVmReaderWriter, tlb::TlbFlushOp, vm_space::VmQueriedItem,
+        CachePolicy, Frame, HasPaddr, HasSize, PageFlags, PageProperty, UFrame, USegment, VmSpace,
+        io::util::HasVmReaderWriter,
+        tlb::TlbFlushOp,
+        vm_space::{HUGE_PAGE_SIZE, VmQueriedItem},
     },
     task::disable_preempt,
 };
 
-use super::{RssType, Vmar, interval_set::Interval, util::is_intersected, vmar_impls::RssDelta};
+use super::{
+    RssType, Vmar,
+    interval_set::Interval,
+    util::is_intersected,
+    vmar_impls::{RssDelta, page_fault::swap_in_page, swap::free_swap_entries},
+};
 use crate::{
     fs::vfs::{
         inode::Inode,
@@ -27,8 +34,14 @@ use crate::{
     prelude::*,
     process::LockedHeap,
     vm::{
+        hugetlb::{self, HugetlbFile},
+        memcg::{AnonPageMeta, alloc_anon_frame},
         page_cache::{CachePage, Vmo, VmoCommitError},
         perms::VmPerms,
+        reclaim,
+        swap::{self, SwapEntry},
+        thp::{ThpAdvice, alloc_anon_huge_page},
+        userfaultfd::{UFFD_WP_PAGE_FLAG, UffdRegistration, Userfault},
         vmar::PageFaultInfo,
     },
 };
@@ -85,6 +98,16 @@ pub struct VmMapping {
     ///
     /// All pages within the same `VmMapping` have the same permissions.
     perms: VmPerms,
+    /// The advice on whether the mapping should be backed with transparent
+    /// huge pages.
+    thp_advice: ThpAdvice,
+    /// Whether the mapping is excluded from core dumps.
+    ///
+    /// This is set by `madvise(MADV_DONTDUMP)` and cleared by
+    /// `madvise(MADV_DODUMP)`.
+    is_dont_dump: bool,
+    /// The registration of the mapping to a userfaultfd, if any.
+    userfaultfd: Option<UffdRegistration>,
 }
 
 impl Interval<Vaddr> for VmMapping {
@@ -113,6 +136,9 @@ impl VmMapping {
             is_shared,
             handle_page_faults_around,
             perms,
+            thp_advice: ThpAdvice::None,
+            is_dont_dump: false,
+            userfaultfd: None,
         }
     }
 
@@ -120,6 +146,9 @@ impl VmMapping {
         VmMapping {
             mapped_mem: self.mapped_mem.dup(),
             path: self.path.clone(),
+            // The mappings of the child process are not registered to the
+            // userfaultfds of the parent process.
+            userfaultfd: None,
             ..*self
         }
     }
@@ -127,6 +156,7 @@ impl VmMapping {
     pub(super) fn clone_for_remap_at(&self, va: Vaddr) -> VmMapping {
         let mut vm_mapping = self.new_fork();
         vm_mapping.map_to_addr = va;
+        vm_mapping.userfaultfd = self.userfaultfd.clone();
         vm_mapping
     }
 
@@ -150,11 +180,63 @@ impl VmMapping {
         self.perms
     }
 
+    /// Returns the advice on transparent huge pages of the mapping.
+    pub fn thp_advice(&self) -> ThpAdvice {
+        self.thp_advice
+    }
+
+    /// Sets the advice on transparent huge pages of the mapping.
+    pub(super) fn set_thp_advice(&mut self, advice: ThpAdvice) {
+        self.thp_advice = advice;
+    }
+
+    /// Returns whether the mapping is excluded from core dumps.
+    pub fn is_dont_dump(&self) -> bool {
+        self.is_dont_dump
+    }
+
+    /// Sets whether the mapping is excluded from core dumps.
+    pub(super) fn set_dont_dump(&mut self, is_dont_dump: bool) {
+        self.is_dont_dump = is_dont_dump;
+    }
+
+    /// Returns the registration of the mapping to a userfaultfd.
+    pub(super) fn userfaultfd(&self) -> Option<&UffdRegistration> {
+        self.userfaultfd.as_ref()
+    }
+
+    /// Sets the registration of the mapping to a userfaultfd.
+    pub(super) fn set_userfaultfd(&mut self, registration: Option<UffdRegistration>) {
+        self.userfaultfd = registration;
+    }
+
+    /// Returns whether the mapping can be registered to a userfaultfd.
+    ///
+    /// Only private anonymous mappings are supported for now.
+    pub(super) fn can_userfault(&self) -> bool {
+        !self.is_shared && matches!(self.mapped_mem, MappedMemory::Anonymous)
+    }
+
+    /// Returns the path of the file that backs the mapping.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+
     /// Returns the inode of the file that backs the mapping.
     pub fn inode(&self) -> Option<&Arc<dyn Inode>> {
         self.path.as_ref().map(|path| path.inode())
     }
 
+    /// Returns the offset in the mapped file or memory object where the
+    /// mapping starts.
+    pub fn file_offset(&self) -> usize {
+        match &self.mapped_mem {
+            MappedMemory::Vmo(mapped_vmo) => mapped_vmo.offset,
+            MappedMemory::Hugetlb(mapped_hugetlb) => mapped_hugetlb.offset,
+            MappedMemory::Anonymous | MappedMemory::Device => 0,
+        }
+    }
+
     /// Returns a reference to the VMO if this mapping is VMO-backed.
     pub(super) fn vmo(&self) -> Option<&MappedVmo> {
         match &self.mapped_mem {
@@ -163,11 +245,25 @@ impl VmMapping {
         }
     }
 
+    /// Returns the VMO and the mapped offset in it if this mapping is VMO-backed.
+    pub fn vmo_and_offset(&self) -> Option<(&Arc<Vmo>, usize)> {
+        self.vmo()
+            .map(|mapped_vmo| (mapped_vmo.vmo(), mapped_vmo.offset()))
+    }
+
+    /// Returns the observer of the mapping if this mapping is VMO-backed and
+    /// has an observer.
+    pub fn observer(&self) -> Option<&Arc<dyn VmoMappingObserver>> {
+        self.vmo()
+            .and_then(|mapped_vmo| mapped_vmo.observer.as_ref())
+    }
+
     /// Returns the mapping's RSS type.
     pub fn rss_type(&self) -> RssType {
         match &self.mapped_mem {
             MappedMemory::Anonymous => RssType::Anon,
             MappedMemory::Vmo(_) | MappedMemory::Device => RssType::File,
+            MappedMemory::Hugetlb(_) => RssType::Hugetlb,
         }
     }
 
@@ -180,6 +276,10 @@ impl VmMapping {
         let mapped_vmo = match &self.mapped_mem {
             MappedMemory::Vmo(mapped_vmo) => mapped_vmo,
             MappedMemory::Anonymous => return Ok(None),
+            // FIXME: Hugetlb pages are not backed by VMOs, so shared futexes on
+            // them are treated as private ones and cannot be woken up from
+            // other processes.
+            MappedMemory::Hugetlb(_) => return Ok(None),
             MappedMemory::Device => {
                 return_errno_with_message!(
                     Errno::EFAULT,
@@ -193,6 +293,11 @@ impl VmMapping {
         Ok(Some((Arc::downgrade(mapped_vmo.vmo()), offset)))
     }
 
+    /// Returns whether this mapping is shared.
+    pub fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
     /// Returns whether this mapping can be expanded.
     ///
     /// Device mappings cannot be expanded as they represent fixed-size MMIO
@@ -201,6 +306,40 @@ impl VmMapping {
         !matches!(self.mapped_mem, MappedMemory::Device)
     }
 
+    /// Returns whether this mapping is backed by huge pages from the hugetlb
+    /// pool.
+    pub fn is_hugetlb(&self) -> bool {
+        matches!(self.mapped_mem, MappedMemory::Hugetlb(_))
+    }
+
+    /// Returns whether this mapping maps device memory.
+    pub fn is_device(&self) -> bool {
+        matches!(self.mapped_mem, MappedMemory::Device)
+    }
+
+    /// Checks whether the part of the mapping in the range can be split from
+    /// the rest of the mapping.
+    ///
+    /// A hugetlb mapping can only be split at huge page boundaries, since its
+    /// huge pages cannot be split.
+    pub(super) fn check_split_range(&self, range: &Range<Vaddr>) -> Result<()> {
+        if !self.is_hugetlb() {
+            return Ok(());
+        }
+
+        let huge_page_size = HUGE_PAGE_SIZE.unwrap();
+        let start = max(range.start, self.map_to_addr);
+        let end = min(range.end, self.map_end());
+        if !start.is_multiple_of(huge_page_size) || !end.is_multiple_of(huge_page_size) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the range is not aligned to the huge pages of the hugetlb mapping"
+            );
+        }
+
+        Ok(())
+    }
+
     /// Populates device memory for this mapping.
     ///
     /// This method should only be called for device memory mappings. It maps
@@ -249,7 +388,7 @@ impl VmMapping {
             '-'
         };
         let shared_char = if self.is_shared { 's' } else { 'p' };
-        let offset = self.vmo().map(|vmo| vmo.offset).unwrap_or(0);
+        let offset = self.file_offset();
         let (dev_major, dev_minor) = self
             .inode()
             .map(|inode| {
@@ -286,7 +425,11 @@ impl VmMapping {
 
             #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
             if let Some(vmo) = self.vmo() {
-                use crate::vdso::{VDSO_VMO_LAYOUT, vdso_vmo};
+                use crate::vdso::{VDSO_VMO_LAYOUT, is_timens_vvar_vmo, vdso_vmo};
+
+                if is_timens_vvar_vmo(vmo.vmo()) {
+                    return Some(Cow::Borrowed("[vvar]"));
+                }
 
                 if let Some(vdso_vmo) = vdso_vmo()
                     && Arc::ptr_eq(vmo.vmo(), &vdso_vmo)
@@ -308,6 +451,9 @@ impl VmMapping {
             if matches!(&self.mapped_mem, MappedMemory::Vmo(_)) && self.is_shared {
                 return Some(Cow::Borrowed("/dev/zero (deleted)"));
             }
+            if matches!(&self.mapped_mem, MappedMemory::Hugetlb(_)) && self.is_shared {
+                return Some(Cow::Borrowed("/anon_hugepage (deleted)"));
+            }
 
             // Common anonymous mappings do not have names.
             None
@@ -382,6 +528,53 @@ impl VmMapping {
         )
     }
 
+    /// Checks whether a page fault should be reported to the userfaultfd that
+    /// the mapping is registered to.
+    ///
+    /// Returns the fault to be reported, or `None` if the page fault should be
+    /// handled normally.
+    pub(super) fn check_userfault(
+        &self,
+        vm_space: &VmSpace,
+        page_fault_info: &PageFaultInfo,
+    ) -> Result<Option<Userfault>> {
+        let Some(registration) = self.userfaultfd.as_ref() else {
+            return Ok(None);
+        };
+        self.check_perms_for_page_fault(page_fault_info)?;
+
+        let page_aligned_addr = page_fault_info.address.align_down(PAGE_SIZE);
+        let is_write = page_fault_info.required_perms.contains(VmPerms::WRITE);
+
+        let preempt_guard = disable_preempt();
+        let mut cursor = vm_space.cursor(
+            &preempt_guard,
+            &(page_aligned_addr..page_aligned_addr + PAGE_SIZE),
+        )?;
+        let (_, item) = cursor.query()?;
+        let is_wp = match item {
+            None if registration.reports_missing() => false,
+            Some(
+                VmQueriedItem::MappedRam { prop, .. } | VmQueriedItem::MappedHugeRam { prop, .. },
+            ) if is_write
+                && prop.flags.contains(UFFD_WP_PAGE_FLAG)
+                && registration.reports_wp() =>
+            {
+                true
+            }
+            _ => return Ok(None),
+        };
+
+        registration
+            .new_fault(
+                page_fault_info.address,
+                is_write,
+                is_wp,
+                page_fault_info.is_user_mode(),
+            )
+            .map(Some)
+    }
+
     fn check_perms_for_page_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()> {
         debug!(
             "self.perms {:?}, page_fault_info.required_perms {:?}, self.range {:?}",
@@ -412,19 +605,36 @@ impl VmMapping {
         Ok(())
     }
 
-    fn handle_single_page_fault(
+    pub(super) fn handle_single_page_fault(
         &self,
         vm_space: &VmSpace,
         page_aligned_addr: Vaddr,
         required_perms: VmPerms,
         rss_delta: &mut RssDelta,
     ) -> Result<()> {
+        if let MappedMemory::Hugetlb(mapped_hugetlb) = &self.mapped_mem {
+            return self.handle_hugetlb_page_fault(
+                mapped_hugetlb,
+                vm_space,
+                page_aligned_addr,
+                required_perms,
+                rss_delta,
+            );
+        }
+
+        let mut nr_reclaim_retries = 0;
+
         'retry: loop {
+            let thp_range = self.thp_range_around(page_aligned_addr);
+
             let preempt_guard = disable_preempt();
             let mut cursor = vm_space.cursor_mut(
                 &preempt_guard,
-                &(page_aligned_addr..page_aligned_addr + PAGE_SIZE),
+                thp_range
+                    .as_ref()
+                    .unwrap_or(&(page_aligned_addr..page_aligned_addr + PAGE_SIZE)),
             )?;
+            cursor.jump(page_aligned_addr).unwrap();
 
             let (va, item) = cursor.query().unwrap();
             let is_write = required_perms.contains(VmPerms::WRITE);
@@ -454,15 +664,33 @@ impl VmMapping {
                     if self.is_shared || only_reference {
                         cursor.protect_next(PAGE_SIZE, |flags, _cache| {
                             *flags |= new_flags;
+                            // The page is no longer write-protected by userfaultfds.
+                            *flags -= UFFD_WP_PAGE_FLAG;
                         });
                         cursor.flusher().issue_tlb_flush(TlbFlushOp::for_range(va));
                         cursor.flusher().dispatch_tlb_flush();
                     } else {
-                        let new_frame = duplicate_frame(&frame)?;
+                        let new_frame = match duplicate_frame(&frame) {
+                            Ok(new_frame) => new_frame,
+                            Err(err) => {
+                                drop(cursor);
+                                drop(preempt_guard);
+                                if reclaim::should_retry_after_reclaim(
+                                    &err,
+                                    &mut nr_reclaim_retries,
+                                ) {
+                                    continue 'retry;
+                                }
+                                return Err(err);
+                            }
+                        };
+                        let new_paddr = new_frame.paddr();
                         prop.flags |= new_flags;
+                        prop.flags -= UFFD_WP_PAGE_FLAG;
                         cursor.unmap(PAGE_SIZE);
                         cursor.jump(va.start).unwrap();
                         cursor.map(new_frame.into(), prop);
+                        reclaim::lru_add_anon_page(rss_delta.operated_vmar(), va.start, new_paddr);
                         // FIXME: Linux re-classifies the page from `File` to `Anon` in RSS,
                         // when a COW on a file-backed mapping happens.
                         // We currently do not support this re-classification,
@@ -470,6 +698,19 @@ impl VmMapping {
                     }
                     cursor.flusher().sync_tlb_flush();
                 }
+                Some(VmQueriedItem::MappedHugeRam { prop, .. }) => {
+                    if VmPerms::from(prop.flags).contains(required_perms) {
+                        // The page fault is already handled maybe by other threads.
+                        // Just flush the TLB and return.
+                        TlbFlushOp::for_range(va).perform_on_current();
+                        return Ok(());
+                    }
+                    assert!(is_write);
+                    // The huge page is shared copy-on-write after fork. Split it so
+                    // that only the faulting page is copied.
+                    cursor.split_huge();
+                    continue 'retry;
+                }
                 Some(VmQueriedItem::MappedIoMem { .. }) => {
                     // The page of I/O memory is populated when the memory
                     // mapping is created.
@@ -478,15 +719,71 @@ impl VmMapping {
                         "device memory page faults cannot be resolved"
                     );
                 }
+                Some(VmQueriedItem::Token(token)) => {
+                    // The page has been swapped out. Pin the swap entry before
+                    // dropping the cursor to read the page.
+                    let entry = SwapEntry::from_token(token);
+                    swap::dup_entry(entry);
+                    drop(cursor);
+                    drop(preempt_guard);
+
+                    let page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED;
+                    let map_prop = PageProperty::new_user(page_flags, CachePolicy::Writeback);
+                    if let Err(err) = swap_in_page(
+                        vm_space,
+                        page_aligned_addr,
+                        entry,
+                        map_prop,
+                        self.rss_type(),
+                        rss_delta,
+                    ) {
+                        if reclaim::should_retry_after_reclaim(&err, &mut nr_reclaim_retries) {
+                            continue 'retry;
+                        }
+                        return Err(err);
+                    }
+
+                    // Handle the page fault again, since the swapped-in page
+                    // may still need COW.
+                    continue 'retry;
+                }
                 None => {
+                    // Map a huge page if nothing in the huge page range is mapped.
+                    if thp_range.as_ref() == Some(&va)
+                        && let Some(segment) = alloc_anon_huge_page()
+                    {
+                        let mut page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED;
+                        if is_write {
+                            page_flags |= PageFlags::DIRTY;
+                        }
+                        let map_prop = PageProperty::new_user(page_flags, CachePolicy::Writeback);
+                        let nr_pages = segment.size() / PAGE_SIZE;
+
+                        cursor.jump(va.start).unwrap();
+                        cursor.map_huge(segment.into(), map_prop);
+                        rss_delta.add(self.rss_type(), nr_pages as isize);
+                        break 'retry;
+                    }
+
                     // Map a new frame to the page fault address.
                     let (frame, is_readonly) = match self.prepare_page(page_aligned_addr, is_write)
                     {
                         Ok((frame, is_readonly)) => (frame, is_readonly),
                         Err(err) => {
-                            let index = err.pending_index()?;
                             drop(cursor);
                             drop(preempt_guard);
+                            let index = match err.pending_index() {
+                                Ok(index) => index,
+                                Err(err)
+                                    if reclaim::should_retry_after_reclaim(
+                                        &err,
+                                        &mut nr_reclaim_retries,
+                                    ) =>
+                                {
+                                    continue 'retry;
+                                }
+                                Err(err) => return Err(err),
+                            };
                             self.vmo().unwrap().commit_on(index)?;
                             continue 'retry;
                         }
@@ -508,8 +805,21 @@ impl VmMapping {
                     }
                     let map_prop = PageProperty::new_user(page_flags, CachePolicy::Writeback);
 
+                    // Private writable pages are newly allocated anonymous pages.
+                    // Other pages belong to the VMO and are tracked by it.
+                    let is_private_anon = !self.is_shared && !is_readonly;
+                    let paddr = frame.paddr();
+
                     cursor.map(frame, map_prop);
                     rss_delta.add(self.rss_type(), 1);
+
+                    if is_private_anon {
+                        reclaim::lru_add_anon_page(
+                            rss_delta.operated_vmar(),
+                            page_aligned_addr,
+                            paddr,
+                        );
+                    }
                 }
             }
             break 'retry;
@@ -518,6 +828,124 @@ impl VmMapping {
         Ok(())
     }
 
+    /// Returns the huge page range around the address if a page fault at the
+    /// address may be handled by mapping a transparent huge page.
+    ///
+    /// See [`crate::vm::thp`] for details.
+    fn thp_range_around(&self, page_aligned_addr: Vaddr) -> Option<Range<Vaddr>> {
+        let huge_page_size = HUGE_PAGE_SIZE?;
+        // Missing pages in the mappings registered to userfaultfds are filled
+        // by the user space page by page.
+        if self.is_shared
+            || !matches!(self.mapped_mem, MappedMemory::Anonymous)
+            || !self.thp_advice.allows_huge_pages()
+            || self.userfaultfd.is_some()
+        {
+            return None;
+        }
+
+        let start = page_aligned_addr.align_down(huge_page_size);
+        let end = start + huge_page_size;
+        (self.map_to_addr <= start && end <= self.map_end()).then_some(start..end)
+    }
+
+    /// Handles a page fault in a hugetlb mapping by mapping the whole huge
+    /// page around the address.
+    ///
+    /// See [`crate::vm::hugetlb`] for details.
+    fn handle_hugetlb_page_fault(
+        &self,
+        mapped_hugetlb: &MappedHugetlb,
+        vm_space: &VmSpace,
+        page_aligned_addr: Vaddr,
+        required_perms: VmPerms,
+        rss_delta: &mut RssDelta,
+    ) -> Result<()> {
+        let huge_page_size = HUGE_PAGE_SIZE.unwrap();
+        let start = page_aligned_addr.align_down(huge_page_size);
+        let is_write = required_perms.contains(VmPerms::WRITE);
+
+        let preempt_guard = disable_preempt();
+        let mut cursor = vm_space.cursor_mut(&preempt_guard, &(start..start + huge_page_size))?;
+
+        let (va, item) = cursor.query().unwrap();
+        match item {
+            Some(VmQueriedItem::MappedHugeRam { segment, mut prop }) => {
+                if VmPerms::from(prop.flags).contains(required_perms) {
+                    // The page fault is already handled maybe by other threads.
+                    // Just flush the TLB and return.
+                    TlbFlushOp::for_range(va).perform_on_current();
+                    return Ok(());
+                }
+                assert!(is_write);
+                // Perform COW if it is a write access to a read-only huge page.
+
+                let new_flags = PageFlags::W | PageFlags::ACCESSED | PageFlags::DIRTY;
+
+                // If no other mappings or files use the huge page, it can be
+                // directly mapped as writable without copying.
+                if self.is_shared || hugetlb::nr_users(&segment) == 1 {
+                    cursor.protect_next(huge_page_size, |flags, _cache| {
+                        *flags |= new_flags;
+                    });
+                    cursor.flusher().issue_tlb_flush(TlbFlushOp::for_range(va));
+                } else {
+                    let new_segment = duplicate_huge_page(&segment)?;
+                    prop.flags |= new_flags;
+                    cursor.unmap(huge_page_size);
+                    cursor.jump(va.start).unwrap();
+                    cursor.map_huge(new_segment, prop);
+                }
+                cursor.flusher().sync_tlb_flush();
+            }
+            None => {
+                let (segment, is_readonly) = if let Some(file) = &mapped_hugetlb.file {
+                    let index =
+                        (mapped_hugetlb.offset + (start - self.map_to_addr)) / huge_page_size;
+                    if self.is_shared {
+                        (file.get_or_alloc(index)?, false)
+                    } else if let Some(segment) = file.get(index)? {
+                        // Read access to a private mapping maps the page of the
+                        // file as read-only. Write access performs COW directly.
+                        if is_write {
+                            (duplicate_huge_page(&segment)?, false)
+                        } else {
+                            (segment, true)
+                        }
+                    } else {
+                        (alloc_huge_page()?, false)
+                    }
+                } else {
+                    (alloc_huge_page()?, false)
+                };
+
+                let mut vm_perms = self.perms;
+                if is_readonly {
+                    // COW pages are forced to be read-only.
+                    vm_perms -= VmPerms::WRITE;
+                }
+                let mut page_flags = PageFlags::from(vm_perms) | PageFlags::ACCESSED;
+                if is_write {
+                    page_flags |= PageFlags::DIRTY;
+                }
+                let map_prop = PageProperty::new_user(page_flags, CachePolicy::Writeback);
+
+                cursor.map_huge(segment, map_prop);
+                rss_delta.add(self.rss_type(), (huge_page_size / PAGE_SIZE) as isize);
+            }
+            Some(_) => {
+                // Hugetlb mappings only map huge pages, which are never split
+                // or swapped out.
+                return_errno_with_message!(
+                    Errno::EFAULT,
+                    "the hugetlb mapping maps a page that is not a huge page"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     fn prepare_page(
         &self,
         page_aligned_addr: Vaddr,
@@ -529,7 +957,7 @@ impl VmMapping {
             MappedMemory::Vmo(vmo) => vmo,
             MappedMemory::Anonymous => {
                 // Anonymous mapping. Allocate a new frame.
-                return Ok((FrameAllocOptions::new().alloc_frame()?.into(), is_readonly));
+                return Ok((alloc_anon_frame(true)?.into(), is_readonly));
             }
             MappedMemory::Device => {
                 // Device memory is populated when the memory mapping is created.
@@ -538,12 +966,16 @@ impl VmMapping {
                     "device memory page faults cannot be resolved",
                 )));
             }
+            MappedMemory::Hugetlb(_) => {
+                // Hugetlb page faults are handled by `handle_hugetlb_page_fault`.
+                unreachable!("hugetlb mappings do not map base pages");
+            }
         };
 
         let page_offset = page_aligned_addr - self.map_to_addr;
         if !self.is_shared && page_offset >= vmo.valid_size() {
             // The page index is outside the VMO. This is only allowed in private mapping.
-            return Ok((FrameAllocOptions::new().alloc_frame()?.into(), is_readonly));
+            return Ok((alloc_anon_frame(true)?.into(), is_readonly));
         }
 
         let page = vmo.get_committed_frame(page_offset)?;
@@ -598,9 +1030,12 @@ impl VmMapping {
             let mut cursor = vm_space.cursor_mut(&preempt_guard, &(start_addr..end_addr))?;
 
             let rss_delta_ref = &mut rss_delta;
+            let mut is_swapped_out = false;
+            let is_swapped_out_ref = &mut is_swapped_out;
             let operate =
                 move |commit_fn: &mut dyn FnMut() -> Result<(usize, CachePage), VmoCommitError>| {
-                    if let (_, None) = cursor.query().unwrap() {
+                    let (va, item) = cursor.query().unwrap();
+                    if item.is_none() {
                         // We regard all the surrounding pages as accessed, no matter
                         // if it is really so. Then the hardware won't bother to update
                         // the accessed bit of the page table on following accesses.
@@ -610,6 +1045,14 @@ impl VmMapping {
                         cursor.map(frame.into(), page_prop);
                         rss_delta_ref.add(self.rss_type(), 1);
                     } else {
+                        // A swapped-out page (i.e., a COW copy of a VMO page)
+                        // is not handled here. If it is the faulting page,
+                        // it will be swapped in as a single page fault.
+                        if va.start == page_aligned_addr
+                            && matches!(item, Some(VmQueriedItem::Token(_)))
+                        {
+                            *is_swapped_out_ref = true;
+                        }
                         let next_addr = cursor.virt_addr() + PAGE_SIZE;
                         if next_addr < end_addr {
                             let _ = cursor.jump(next_addr);
@@ -621,6 +1064,15 @@ impl VmMapping {
             let start_offset = start_addr - self.map_to_addr;
             let end_offset = end_addr - self.map_to_addr;
             match vmo.try_operate_on_range(&(start_offset..end_offset), operate) {
+                Ok(_) if is_swapped_out => {
+                    drop(preempt_guard);
+                    return self.handle_single_page_fault(
+                        vm_space,
+                        page_aligned_addr,
+                        required_perms,
+                        rss_delta,
+                    );
+                }
                 Ok(_) => return Ok(()),
                 Err(err) => {
                     let index = err.pending_index()?;
@@ -667,6 +1119,15 @@ impl VmMapping {
                 // For device memory mappings, we create new device memory mappings for the split parts
                 (MappedMemory::Device, MappedMemory::Device)
             }
+            MappedMemory::Hugetlb(mapped_hugetlb) => {
+                debug_assert!(at.is_multiple_of(HUGE_PAGE_SIZE.unwrap()));
+                let at_offset = mapped_hugetlb.offset + (at - self.map_to_addr);
+                let r_mapped_hugetlb = mapped_hugetlb.dup_at_offset(at_offset);
+                (
+                    MappedMemory::Hugetlb(mapped_hugetlb),
+                    MappedMemory::Hugetlb(r_mapped_hugetlb),
+                )
+            }
         };
 
         let left_size = at - self.map_to_addr;
@@ -676,6 +1137,7 @@ impl VmMapping {
             map_to_addr: self.map_to_addr,
             mapped_mem: l_mapped_mem,
             path: self.path.clone(),
+            userfaultfd: self.userfaultfd.clone(),
             ..self
         };
         let right = Self {
@@ -765,17 +1227,19 @@ impl VmMapping {
 
 impl VmMapping {
     /// Unmaps the mapping from the VM space,
-    /// and returns the number of unmapped pages.
-    pub(super) fn unmap(self, vm_space: &VmSpace) -> usize {
+    /// and updates the RSS counters with the unmapped pages.
+    pub(super) fn unmap(self, vm_space: &VmSpace, rss_delta: &mut RssDelta) {
         let preempt_guard = disable_preempt();
         let range = self.range();
         let mut cursor = vm_space.cursor_mut(&preempt_guard, &range).unwrap();
 
+        let num_swapped = free_swap_entries(&mut cursor, range.len());
         let num_unmapped = cursor.unmap(range.len());
         cursor.flusher().dispatch_tlb_flush();
         cursor.flusher().sync_tlb_flush();
 
-        num_unmapped
+        rss_delta.add(RssType::Swap, -(num_swapped as isize));
+        rss_delta.add(self.rss_type(), -(num_unmapped as isize));
     }
 
     /// Change the perms of the mapping.
@@ -789,7 +1253,15 @@ impl VmMapping {
         let range = self.range();
         let mut cursor = vm_space.cursor_mut(&preempt_guard, &range).unwrap();
 
-        let op = |flags: &mut PageFlags, _cache: &mut CachePolicy| *flags = new_flags;
+        let op = |flags: &mut PageFlags, _cache: &mut CachePolicy| {
+            let is_uffd_wp = flags.contains(UFFD_WP_PAGE_FLAG);
+            *flags = new_flags;
+            // Pages write-protected by userfaultfds stay read-only.
+            if is_uffd_wp {
+                *flags -= PageFlags::W;
+                *flags |= UFFD_WP_PAGE_FLAG;
+            }
+        };
         while cursor.virt_addr() < range.end {
             if let Some(va) = cursor.protect_next(range.end - cursor.virt_addr(), op) {
                 cursor.flusher().issue_tlb_flush(TlbFlushOp::for_range(va));
@@ -802,6 +1274,73 @@ impl VmMapping {
 
         Self { perms, ..self }
     }
+
+    /// Maps a frame to a page that is not mapped to resolve a fault reported
+    /// to the userfaultfd.
+    ///
+    /// If `is_wp` is true, the page is write-protected by the userfaultfd.
+    pub(super) fn map_userfault_page(
+        &self,
+        vm_space: &VmSpace,
+        page_aligned_addr: Vaddr,
+        frame: Frame<AnonPageMeta>,
+        is_wp: bool,
+        rss_delta: &mut RssDelta,
+    ) -> Result<()> {
+        debug_assert!(self.can_userfault());
+
+        let preempt_guard = disable_preempt();
+        let mut cursor = vm_space.cursor_mut(
+            &preempt_guard,
+            &(page_aligned_addr..page_aligned_addr + PAGE_SIZE),
+        )?;
+        if let (_, Some(_)) = cursor.query()? {
+            return_errno_with_message!(Errno::EEXIST, "the page is already mapped");
+        }
+
+        let mut page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED | PageFlags::DIRTY;
+        if is_wp {
+            page_flags -= PageFlags::W;
+            page_flags |= UFFD_WP_PAGE_FLAG;
+        }
+        let map_prop = PageProperty::new_user(page_flags, CachePolicy::Writeback);
+
+        let paddr = frame.paddr();
+        cursor.map(frame.into(), map_prop);
+        rss_delta.add(self.rss_type(), 1);
+        reclaim::lru_add_anon_page(rss_delta.operated_vmar(), page_aligned_addr, paddr);
+
+        Ok(())
+    }
+
+    /// Sets or clears the write protection of userfaultfds on the mapped pages
+    /// in the range.
+    ///
+    /// The write permission is not restored when clearing the write
+    /// protection. The next write to the page triggers a normal page fault,
+    /// which makes the page writable or copies it as needed.
+    pub(super) fn set_userfault_wp(&self, vm_space: &VmSpace, range: &Range<Vaddr>, is_wp: bool) {
+        let preempt_guard = disable_preempt();
+        let mut cursor = vm_space.cursor_mut(&preempt_guard, range).unwrap();
+
+        let op = |flags: &mut PageFlags, _cache: &mut CachePolicy| {
+            if is_wp {
+                *flags -= PageFlags::W;
+                *flags |= UFFD_WP_PAGE_FLAG;
+            } else {
+                *flags -= UFFD_WP_PAGE_FLAG;
+            }
+        };
+        while cursor.virt_addr() < range.end {
+            if let Some(va) = cursor.protect_next(range.end - cursor.virt_addr(), op) {
+                cursor.flusher().issue_tlb_flush(TlbFlushOp::for_range(va));
+            } else {
+                break;
+            }
+        }
+        cursor.flusher().dispatch_tlb_flush();
+        cursor.flusher().sync_tlb_flush();
+    }
 }
 
 /// Memory mapped by a [`VmMapping`].
@@ -823,6 +1362,11 @@ pub(super) enum MappedMemory {
     /// These pages are associated with special files (typically device memory). They are populated
     /// when the memory mapping is created via mmap, instead of occurring at page faults.
     Device,
+    /// Huge pages from the hugetlb pool.
+    ///
+    /// These pages are either private anonymous pages or associated with hugetlbfs files. On-demand
+    /// population is possible by enabling page fault handlers to map whole huge pages.
+    Hugetlb(MappedHugetlb),
 }
 
 impl MappedMemory {
@@ -832,6 +1376,37 @@ impl MappedMemory {
             MappedMemory::Anonymous => MappedMemory::Anonymous,
             MappedMemory::Vmo(v) => MappedMemory::Vmo(v.dup()),
             MappedMemory::Device => MappedMemory::Device,
+            MappedMemory::Hugetlb(h) => MappedMemory::Hugetlb(h.dup()),
+        }
+    }
+}
+
+/// The huge pages mapped by a hugetlb mapping.
+#[derive(Debug)]
+pub(super) struct MappedHugetlb {
+    /// The hugetlbfs file that holds the huge pages.
+    ///
+    /// Private anonymous hugetlb mappings do not have a file.
+    file: Option<Arc<HugetlbFile>>,
+    /// Represents the mapped offset in the file for the mapping.
+    offset: usize,
+}
+
+impl MappedHugetlb {
+    /// Creates a `MappedHugetlb` used for the mapping.
+    pub(super) fn new(file: Option<Arc<HugetlbFile>>, offset: usize) -> Self {
+        Self { file, offset }
+    }
+
+    /// Duplicates the capability.
+    pub fn dup(&self) -> Self {
+        self.dup_at_offset(self.offset)
+    }
+
+    fn dup_at_offset(&self, offset: usize) -> Self {
+        Self {
+            file: self.file.clone(),
+            offset,
         }
     }
 }
@@ -846,19 +1421,47 @@ pub(super) struct MappedVmo {
     /// Whether the VMO's writable mappings need to be tracked, and the
     /// mapping is writable to the VMO.
     is_writable_tracked: bool,
+    /// The observer to be notified when the mapping is created or destroyed.
+    observer: Option<Arc<dyn VmoMappingObserver>>,
+}
+
+/// An observer of the mappings of a [`Vmo`].
+///
+/// The observer is notified whenever a mapping of the VMO is created or
+/// destroyed, including the mappings created implicitly by forking a process
+/// or by splitting, merging, or remapping existing mappings. Therefore, the
+/// number of notifications reflects the number of mappings at any time.
+pub trait VmoMappingObserver: Any + Send + Sync + Debug {
+    /// Called when a mapping of the VMO is created.
+    fn on_map(&self);
+
+    /// Called when a mapping of the VMO is destroyed.
+    ///
+    /// This method may be called with the lock of the [`Vmar`] held, so it
+    /// must not access the [`Vmar`].
+    fn on_unmap(&self);
 }
 
 impl MappedVmo {
     /// Creates a `MappedVmo` used for the mapping.
-    pub(super) fn new(vmo: Arc<Vmo>, offset: usize, is_writable_tracked: bool) -> Result<Self> {
+    pub(super) fn new(
+        vmo: Arc<Vmo>,
+        offset: usize,
+        is_writable_tracked: bool,
+        observer: Option<Arc<dyn VmoMappingObserver>>,
+    ) -> Result<Self> {
         if is_writable_tracked {
             vmo.writable_mapping_status().map()?;
         }
+        if let Some(observer) = observer.as_ref() {
+            observer.on_map();
+        }
 
         Ok(Self {
             vmo,
             offset,
             is_writable_tracked,
+            observer,
         })
     }
 
@@ -932,11 +1535,15 @@ impl MappedVmo {
         if self.is_writable_tracked {
             self.vmo.writable_mapping_status().increment();
         }
+        if let Some(observer) = self.observer.as_ref() {
+            observer.on_map();
+        }
 
         Self {
             vmo: self.vmo.clone(),
             offset,
             is_writable_tracked: self.is_writable_tracked,
+            observer: self.observer.clone(),
         }
     }
 }
@@ -946,6 +1553,9 @@ impl Drop for MappedVmo {
         if self.is_writable_tracked {
             self.vmo.writable_mapping_status().decrement();
         }
+        if let Some(observer) = self.observer.as_ref() {
+            observer.on_unmap();
+        }
     }
 }
 
@@ -962,7 +1572,10 @@ fn try_merge(left: &VmMapping, right: &VmMapping) -> Option<VmMapping> {
     let is_adjacent = left.map_end() == right.map_to_addr();
     let is_type_equal = left.is_shared == right.is_shared
         && left.handle_page_faults_around == right.handle_page_faults_around
-        && left.perms == right.perms;
+        && left.perms == right.perms
+        && left.thp_advice == right.thp_advice
+        && left.is_dont_dump == right.is_dont_dump
+        && left.userfaultfd == right.userfaultfd;
 
     if !is_adjacent || !is_type_equal {
         return None;
@@ -998,12 +1611,28 @@ fn try_merge(left: &VmMapping, right: &VmMapping) -> Option<VmMapping> {
         map_size,
         mapped_mem,
         path: left.path.clone(),
+        userfaultfd: left.userfaultfd.clone(),
         ..*left
     })
 }
 
-fn duplicate_frame(src: &UFrame) -> Result<Frame<()>> {
-    let new_frame = FrameAllocOptions::new().zeroed(false).alloc_frame()?;
+/// Allocates a zeroed huge page from the hugetlb pool.
+fn alloc_huge_page() -> Result<USegment> {
+    hugetlb::alloc_huge_page(true).ok_or_else(|| {
+        Error::with_message(Errno::EFAULT, "there are no free huge pages in the pool")
+    })
+}
+
+fn duplicate_huge_page(src: &USegment) -> Result<USegment> {
+    let new_segment = hugetlb::alloc_huge_page(false).ok_or_else(|| {
+        Error::with_message(Errno::EFAULT, "there are no free huge pages in the pool")
+    })?;
+    new_segment.writer().write(&mut src.reader());
+    Ok(new_segment)
+}
+
+fn duplicate_frame(src: &UFrame) -> Result<Frame<AnonPageMeta>> {
+    let new_frame = alloc_anon_frame(false)?;
     new_frame.writer().write(&mut src.reader());
     Ok(new_frame)
 }
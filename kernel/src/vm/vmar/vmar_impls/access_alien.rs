@@ -83,6 +83,22 @@ impl Vmar {
         self.access_alien(vaddr, len, PageFlags::W, write)
     }
 
+    /// Returns whether the page at `vaddr` has been populated.
+    ///
+    /// A page that has been swapped out is also considered populated. Unlike
+    /// [`Self::read_alien`], this method never handles page faults.
+    pub fn is_page_populated(&self, vaddr: Vaddr) -> Result<bool> {
+        debug_assert!(is_userspace_vaddr(vaddr) && vaddr.is_multiple_of(PAGE_SIZE));
+
+        let preempt_guard = disable_preempt();
+        let mut cursor = self
+            .vm_space()
+            .cursor(&preempt_guard, &(vaddr..vaddr + PAGE_SIZE))?;
+        let (_, vm_item) = cursor.query()?;
+
+        Ok(vm_item.is_some())
+    }
+
     /// Accesses memory at `vaddr..vaddr+len` in the context of an alien thread using `op`.
     ///
     /// The `VmSpace` of the process is not required to be activated on the current CPU.
@@ -147,19 +163,27 @@ impl Vmar {
             let preempt_guard = disable_preempt();
             let mut cursor = vmspace.cursor(&preempt_guard, &(vaddr..vaddr + PAGE_SIZE))?;
 
-            match cursor.query()?.1 {
-                Some(vm_item) if vm_item.prop().flags.contains(required_page_flags) => {
+            match cursor.query()? {
+                (range, Some(vm_item)) if vm_item.prop().flags.contains(required_page_flags) => {
                     match vm_item {
                         VmQueriedItem::MappedRam { frame, .. } => return Ok((*frame).clone()),
+                        VmQueriedItem::MappedHugeRam { segment, .. } => {
+                            let offset = vaddr - range.start;
+                            let mut frames = segment.slice(&(offset..offset + PAGE_SIZE));
+                            return Ok(frames.next().unwrap());
+                        }
                         VmQueriedItem::MappedIoMem { .. } => {
                             return_errno_with_message!(
                                 Errno::EOPNOTSUPP,
                                 "accessing alien MMIO memory is not supported currently"
                             );
                         }
+                        // The page has been swapped out. It will be swapped in
+                        // by the page fault handler.
+                        VmQueriedItem::Token(_) => (),
                     }
                 }
-                Some(_) | None => (),
+                (_, Some(_) | None) => (),
             }
 
             drop(cursor);
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use super::{Interval, VmMapping, Vmar, util::get_intersected_range};
+use crate::{prelude::*, vm::thp::ThpAdvice};
+
+impl Vmar {
+    /// Sets the advice on transparent huge pages of the memory mappings in the
+    /// specified range.
+    ///
+    /// The range's start and end addresses must be page-aligned. The advice
+    /// only affects the pages that are mapped afterward.
+    ///
+    /// If the range contains unmapped pages, an [`ENOMEM`] error will be returned.
+    /// Note that the mappings before the unmapped hole are still advised.
+    ///
+    /// [`ENOMEM`]: Errno::ENOMEM
+    pub fn advise_huge_pages(&self, advice: ThpAdvice, range: Range<usize>) -> Result<()> {
+        self.advise_mappings(
+            range,
+            |vm_mapping| vm_mapping.thp_advice() == advice,
+            |vm_mapping| vm_mapping.set_thp_advice(advice),
+        )
+    }
+
+    /// Sets whether the memory mappings in the specified range are excluded
+    /// from core dumps.
+    ///
+    /// The range's start and end addresses must be page-aligned.
+    ///
+    /// If the range contains unmapped pages, an [`ENOMEM`] error will be returned.
+    /// Note that the mappings before the unmapped hole are still advised.
+    ///
+    /// [`ENOMEM`]: Errno::ENOMEM
+    pub fn advise_dump(&self, is_dont_dump: bool, range: Range<usize>) -> Result<()> {
+        if !is_dont_dump && self.query(range.clone()).iter().any(VmMapping::is_device) {
+            // Device mappings are never dumped.
+            // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/mm/madvise.c>
+            return_errno_with_message!(Errno::EINVAL, "device mappings cannot be dumped");
+        }
+
+        self.advise_mappings(
+            range,
+            |vm_mapping| vm_mapping.is_dont_dump() == is_dont_dump,
+            |vm_mapping| vm_mapping.set_dont_dump(is_dont_dump),
+        )
+    }
+
+    /// Applies `advise` to the memory mappings in the specified range.
+    ///
+    /// Mappings for which `is_advised` returns `true` are left untouched.
+    /// Other mappings are split at the range boundaries if needed.
+    fn advise_mappings(
+        &self,
+        range: Range<usize>,
+        is_advised: impl Fn(&VmMapping) -> bool,
+        advise: impl Fn(&mut VmMapping),
+    ) -> Result<()> {
+        debug_assert!(range.start.is_multiple_of(PAGE_SIZE));
+        debug_assert!(range.end.is_multiple_of(PAGE_SIZE));
+
+        let mut inner = self.inner.write();
+
+        let mut advise_mappings = Vec::new();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            vm_mapping.check_split_range(&range)?;
+            advise_mappings.push((vm_mapping.range(), is_advised(vm_mapping)))
+        }
+
+        let mut last_mapping_end = range.start;
+        for (vm_mapping_range, vm_mapping_is_advised) in advise_mappings {
+            if last_mapping_end < vm_mapping_range.start {
+                return_errno_with_message!(
+                    Errno::ENOMEM,
+                    "the range contains pages that are not mapped"
+                );
+            }
+            last_mapping_end = vm_mapping_range.end;
+
+            if vm_mapping_is_advised {
+                continue;
+            }
+
+            let Some(vm_mapping) = inner.remove(&vm_mapping_range.start) else {
+                // This can happen only if the mapping is merged to the previous one (just
+                // advised before). We can skip this mapping because its advice is already
+                // correct.
+                continue;
+            };
+            let vm_mapping_range = vm_mapping.range();
+            let intersected_range = get_intersected_range(&range, &vm_mapping_range);
+
+            // Advises part of the taken `VmMapping`.
+            let (left, mut taken, right) = vm_mapping.split_range(&intersected_range);
+
+            // Puts the rest back.
+            if let Some(left) = left {
+                inner.insert_without_try_merge(left);
+            }
+            if let Some(right) = right {
+                inner.insert_without_try_merge(right);
+            }
+
+            advise(&mut taken);
+            inner.insert_try_merge(taken);
+        }
+
+        if last_mapping_end < range.end {
+            return_errno_with_message!(
+                Errno::ENOMEM,
+                "the range contains pages that are not mapped"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -2,15 +2,23 @@
 
 use ostd::{
     mm::{
-        CachePolicy, PageFlags,
+        CachePolicy, HasSize, PageFlags,
         tlb::TlbFlushOp,
         vm_space::{CursorMut, VmQueriedItem},
     },
     task::disable_preempt,
 };
 
-use super::{RssDelta, VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, Vmar};
-use crate::{prelude::*, process::ProcessVm, vm::vmar::VmarHandle};
+use super::{RssDelta, RssType, VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, Vmar};
+use crate::{
+    prelude::*,
+    process::ProcessVm,
+    vm::{
+        swap::{self, SwapEntry},
+        userfaultfd::UFFD_WP_PAGE_FLAG,
+        vmar::VmarHandle,
+    },
+};
 
 impl Vmar {
     /// Creates a new VMAR whose content is inherited from another
@@ -38,6 +46,9 @@ impl Vmar {
                 let base = vm_mapping.map_to_addr();
 
                 // Clone the `VmMapping` to the new VMAR.
+                if let Some((vmo, _)) = vm_mapping.vmo_and_offset() {
+                    vmo.rmap().add(new_vmar.weak_self());
+                }
                 let new_mapping = vm_mapping.new_fork();
                 new_inner.insert_without_try_merge(new_mapping);
 
@@ -45,10 +56,11 @@ impl Vmar {
                 cur_cursor.jump(base).unwrap();
                 new_cursor.jump(base).unwrap();
 
-                let num_copied =
+                let (num_copied, num_swapped) =
                     cow_copy_pt(&mut cur_cursor, &mut new_cursor, vm_mapping.map_size());
 
                 rss_delta.add(vm_mapping.rss_type(), num_copied as isize);
+                rss_delta.add(RssType::Swap, num_swapped as isize);
             }
 
             cur_cursor.flusher().issue_tlb_flush(TlbFlushOp::for_all());
@@ -66,13 +78,17 @@ impl Vmar {
 /// The copied range starts from `src`'s current position with the given
 /// `size`. The destination range starts from `dst`'s current position.
 ///
-/// The number of physical frames copied is returned.
-fn cow_copy_pt(src: &mut CursorMut<'_>, dst: &mut CursorMut<'_>, size: usize) -> usize {
+/// The swap entries of swapped-out pages are shared by both page tables.
+///
+/// The number of physical frames copied and the number of swap entries copied
+/// are returned.
+fn cow_copy_pt(src: &mut CursorMut<'_>, dst: &mut CursorMut<'_>, size: usize) -> (usize, usize) {
     let start_va = src.virt_addr();
     let end_va = start_va + size;
     let mut remain_size = size;
 
     let mut num_copied = 0;
+    let mut num_swapped = 0;
 
     let op = |flags: &mut PageFlags, _cache: &mut CachePolicy| {
         *flags -= PageFlags::W;
@@ -82,7 +98,7 @@ fn cow_copy_pt(src: &mut CursorMut<'_>, dst: &mut CursorMut<'_>, size: usize) ->
         let (va, Some(item)) = src.query().unwrap() else {
             panic!("Found mapped page but query failed");
         };
-        debug_assert_eq!(mapped_va, va.start);
+        debug_assert!(va.contains(&mapped_va));
 
         match item {
             VmQueriedItem::MappedRam { frame, mut prop } => {
@@ -92,10 +108,32 @@ fn cow_copy_pt(src: &mut CursorMut<'_>, dst: &mut CursorMut<'_>, size: usize) ->
 
                 dst.jump(mapped_va).unwrap();
                 op(&mut prop.flags, &mut prop.cache);
+                // The child is not registered to the userfaultfds of the parent.
+                prop.flags -= UFFD_WP_PAGE_FLAG;
                 dst.map(frame, prop);
 
                 num_copied += 1;
             }
+            VmQueriedItem::MappedHugeRam { segment, mut prop } => {
+                if va.start != mapped_va || va.end > end_va {
+                    // Only part of the huge page is in the range. Split it so
+                    // that the base pages in the range can be copied.
+                    src.split_huge();
+                    continue;
+                }
+
+                let segment = (*segment).clone();
+                let nr_pages = segment.size() / PAGE_SIZE;
+
+                src.protect_next(end_va - mapped_va, op).unwrap();
+
+                dst.jump(mapped_va).unwrap();
+                op(&mut prop.flags, &mut prop.cache);
+                prop.flags -= UFFD_WP_PAGE_FLAG;
+                dst.map_huge(segment, prop);
+
+                num_copied += nr_pages;
+            }
             VmQueriedItem::MappedIoMem { paddr, prop } => {
                 // For MMIO pages, find the corresponding `IoMem` and map it
                 let (iomem, offset) = src.find_iomem_by_paddr(paddr).unwrap();
@@ -107,12 +145,22 @@ fn cow_copy_pt(src: &mut CursorMut<'_>, dst: &mut CursorMut<'_>, size: usize) ->
                 // However, this does not apply to the `MappedIoMem` case.
                 src.jump(mapped_va + PAGE_SIZE).unwrap();
             }
+            VmQueriedItem::Token(token) => {
+                swap::dup_entry(SwapEntry::from_token(token));
+                dst.jump(mapped_va).unwrap();
+                dst.map_token(token);
+
+                // Manually advance the source cursor, as in the `MappedIoMem` case.
+                src.jump(mapped_va + PAGE_SIZE).unwrap();
+
+                num_swapped += 1;
+            }
         }
 
         remain_size = end_va - src.virt_addr();
     }
 
-    num_copied
+    (num_copied, num_swapped)
 }
 
 #[cfg(ktest)]
@@ -154,7 +202,8 @@ mod test {
         {
             let mut child_cursor = child_space.cursor_mut(&preempt_guard, &cow_range).unwrap();
             let mut parent_cursor = vm_space.cursor_mut(&preempt_guard, &cow_range).unwrap();
-            let num_copied = cow_copy_pt(&mut parent_cursor, &mut child_cursor, cow_range.len());
+            let (num_copied, _) =
+                cow_copy_pt(&mut parent_cursor, &mut child_cursor, cow_range.len());
             assert_eq!(num_copied, 1); // Only one page should be copied
         };
 
@@ -199,7 +248,8 @@ mod test {
                 .cursor_mut(&preempt_guard, &cow_range)
                 .unwrap();
             let mut parent_cursor = vm_space.cursor_mut(&preempt_guard, &cow_range).unwrap();
-            let num_copied = cow_copy_pt(&mut parent_cursor, &mut sibling_cursor, cow_range.len());
+            let (num_copied, _) =
+                cow_copy_pt(&mut parent_cursor, &mut sibling_cursor, cow_range.len());
             assert_eq!(num_copied, 0); // No pages should be copied
         }
 
@@ -283,7 +333,8 @@ mod test {
         {
             let mut child_cursor = child_space.cursor_mut(&preempt_guard, &cow_range).unwrap();
             let mut parent_cursor = vm_space.cursor_mut(&preempt_guard, &cow_range).unwrap();
-            let num_copied = cow_copy_pt(&mut parent_cursor, &mut child_cursor, cow_range.len());
+            let (num_copied, _) =
+                cow_copy_pt(&mut parent_cursor, &mut child_cursor, cow_range.len());
             assert_eq!(num_copied, 0); // `IoMem` pages are not "copied" in the same sense as RAM pages.
         };
 
@@ -334,7 +385,8 @@ mod test {
                 .cursor_mut(&preempt_guard, &cow_range)
                 .unwrap();
             let mut parent_cursor = vm_space.cursor_mut(&preempt_guard, &cow_range).unwrap();
-            let num_copied = cow_copy_pt(&mut parent_cursor, &mut sibling_cursor, cow_range.len());
+            let (num_copied, _) =
+                cow_copy_pt(&mut parent_cursor, &mut sibling_cursor, cow_range.len());
             assert_eq!(num_copied, 0); // No pages should be copied
         }
 
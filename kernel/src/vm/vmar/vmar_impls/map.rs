@@ -2,7 +2,9 @@
 
 use core::num::NonZeroUsize;
 
-use super::{MappedMemory, MappedVmo, RssDelta, VmMapping, Vmar};
+use super::{
+    MappedHugetlb, MappedMemory, MappedVmo, RssDelta, VmMapping, Vmar, VmoMappingObserver,
+};
 use crate::{
     fs::{
         file::{FileLike, Mappable},
@@ -10,7 +12,11 @@ use crate::{
         vfs::path::Path,
     },
     prelude::*,
-    vm::{page_cache::Vmo, perms::VmPerms},
+    vm::{
+        hugetlb::{self, HugetlbFile},
+        page_cache::Vmo,
+        perms::VmPerms,
+    },
 };
 
 impl Vmar {
@@ -59,6 +65,7 @@ pub struct VmarMapOptions<'a> {
     parent: &'a Vmar,
     mappable: Option<Mappable>,
     path: Option<Path>,
+    observer: Option<Arc<dyn VmoMappingObserver>>,
     perms: VmPerms,
     may_perms: VmPerms,
     vmo_offset: usize,
@@ -70,6 +77,8 @@ pub struct VmarMapOptions<'a> {
     // Whether the mapping needs to handle surrounding pages when handling
     // page fault.
     handle_page_faults_around: bool,
+    // Whether the mapping is backed by huge pages from the hugetlb pool.
+    is_hugetlb: bool,
 }
 
 /// An offset within a VMAR where a new mapping will reside.
@@ -103,6 +112,7 @@ impl<'a> VmarMapOptions<'a> {
             parent,
             mappable: None,
             path: None,
+            observer: None,
             perms,
             may_perms: VmPerms::ALL_MAY_PERMS,
             vmo_offset: 0,
@@ -111,6 +121,7 @@ impl<'a> VmarMapOptions<'a> {
             align: PAGE_SIZE,
             is_shared: false,
             handle_page_faults_around: false,
+            is_hugetlb: false,
         }
     }
 
@@ -155,10 +166,37 @@ impl<'a> VmarMapOptions<'a> {
         self
     }
 
+    /// Backs the mapping with huge pages from the hugetlb pool.
+    ///
+    /// Without a [`HugetlbFile`], the mapping is a private anonymous mapping.
+    /// The alignment of the mapping must be a multiple of the huge page size.
+    ///
+    /// This will be implicitly set if [`Self::hugetlb_file`] is called or if
+    /// [`Self::mappable`] is set with a [`Mappable::Hugetlb`].
+    pub fn hugetlb(mut self) -> Self {
+        self.is_hugetlb = true;
+        self
+    }
+
+    /// Binds a [`HugetlbFile`] to the hugetlb mapping.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a [`Vmo`] or [`Mappable`] is already provided.
+    pub fn hugetlb_file(mut self, file: Arc<HugetlbFile>) -> Self {
+        if self.mappable.is_some() {
+            panic!("Cannot set `hugetlb_file` when `mappable` is already set");
+        }
+        self.mappable = Some(Mappable::Hugetlb(file));
+        self.is_hugetlb = true;
+
+        self
+    }
+
     /// Sets the [`Path`] of the mapping.
     ///
-    /// If a [`Vmo`] is specified, the inode behind the [`Path`] must have
-    /// the [`Vmo`] as the page cache.
+    /// If a [`Vmo`] is specified and the inode behind the [`Path`] has a
+    /// page cache, the [`Vmo`] must be the page cache.
     ///
     /// The [`Path`] of a mapping will be implicitly set if [`Self::mappable`]
     /// is set.
@@ -175,6 +213,17 @@ impl<'a> VmarMapOptions<'a> {
         self
     }
 
+    /// Sets the observer of the mapping.
+    ///
+    /// The observer is notified when the mapping, or any mapping derived from
+    /// it, is created or destroyed. See [`VmoMappingObserver`] for details.
+    ///
+    /// The observer takes effect only if a [`Vmo`] is bound to the mapping.
+    pub fn observer(mut self, observer: Arc<dyn VmoMappingObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
     /// Sets the offset of the first memory page in the VMO that is to be
     /// mapped into the VMAR.
     ///
@@ -251,6 +300,9 @@ impl<'a> VmarMapOptions<'a> {
         }
 
         let mappable = file.mappable()?;
+        if matches!(mappable, Mappable::Hugetlb(_)) {
+            self.is_hugetlb = true;
+        }
         self.mappable = Some(mappable);
         self.path = Some(file.path().clone());
 
@@ -268,6 +320,7 @@ impl<'a> VmarMapOptions<'a> {
             parent,
             mappable,
             path,
+            observer,
             perms,
             mut may_perms,
             vmo_offset,
@@ -276,8 +329,26 @@ impl<'a> VmarMapOptions<'a> {
             align,
             is_shared,
             handle_page_faults_around,
+            is_hugetlb,
         } = self;
 
+        if is_hugetlb {
+            // Huge pages are not reserved for the mapping, but there should be
+            // enough free huge pages to populate it at this time.
+            let nr_needed = match &mappable {
+                Some(Mappable::Hugetlb(file)) if is_shared => {
+                    file.nr_missing_pages(vmo_offset..vmo_offset + map_size)
+                }
+                _ => map_size / hugetlb::huge_page_size().unwrap(),
+            };
+            if nr_needed > hugetlb::nr_free_huge_pages() {
+                return_errno_with_message!(
+                    Errno::ENOMEM,
+                    "there are not enough free huge pages in the pool"
+                );
+            }
+        }
+
         let mut inner = parent.inner.write();
 
         inner
@@ -317,20 +388,26 @@ impl<'a> VmarMapOptions<'a> {
                 if inner.alloc_free_region_exact(map_to_addr, map_size).is_ok() {
                     map_to_addr
                 } else {
-                    inner.alloc_free_region(map_size, align)?.start
+                    inner
+                        .alloc_free_region(map_size, align, parent.process_vm().vmar_cap_addr())?
+                        .start
                 }
             }
-            VmarMapOffset::Any => inner.alloc_free_region(map_size, align)?.start,
+            VmarMapOffset::Any => {
+                inner
+                    .alloc_free_region(map_size, align, parent.process_vm().vmar_cap_addr())?
+                    .start
+            }
         };
 
         // Parse the `Mappable` and prepare the `MappedMemory`.
         let (mapped_mem, io_mem) = match mappable {
             Some(Mappable::Vmo(vmo)) => {
-                if let Some(ref path) = path {
-                    debug_assert!(Arc::ptr_eq(
-                        &vmo,
-                        &path.inode().page_cache().unwrap().as_vmo().clone()
-                    ));
+                // Pseudo files (e.g., io_uring files) may provide VMOs that are not page caches.
+                if let Some(ref path) = path
+                    && let Some(page_cache) = path.inode().page_cache()
+                {
+                    debug_assert!(Arc::ptr_eq(&vmo, page_cache.as_vmo()));
                 }
 
                 let is_writable_tracked = if let Some(ref path) = path
@@ -344,11 +421,30 @@ impl<'a> VmarMapOptions<'a> {
                     false
                 };
 
-                let mapped_mem =
-                    MappedMemory::Vmo(MappedVmo::new(vmo, vmo_offset, is_writable_tracked)?);
+                vmo.rmap().add(parent.weak_self());
+
+                let mapped_mem = MappedMemory::Vmo(MappedVmo::new(
+                    vmo,
+                    vmo_offset,
+                    is_writable_tracked,
+                    observer,
+                )?);
                 (mapped_mem, None)
             }
             Some(Mappable::IoMem(io_mem)) => (MappedMemory::Device, Some(io_mem)),
+            Some(Mappable::Hugetlb(file)) => {
+                if let Some(ref path) = path
+                    && let Some(memfd_inode) = path.inode().downcast_ref::<MemfdInode>()
+                    && is_shared
+                    && may_perms.contains(VmPerms::MAY_WRITE)
+                {
+                    memfd_inode.check_writable(perms, &mut may_perms)?;
+                }
+
+                let mapped_hugetlb = MappedHugetlb::new(Some(file), vmo_offset);
+                (MappedMemory::Hugetlb(mapped_hugetlb), None)
+            }
+            None if is_hugetlb => (MappedMemory::Hugetlb(MappedHugetlb::new(None, 0)), None),
             None => (MappedMemory::Anonymous, None),
         };
 
@@ -395,6 +491,14 @@ impl<'a> VmarMapOptions<'a> {
         if !self.vmo_offset.is_multiple_of(self.align) {
             return_errno_with_message!(Errno::EINVAL, "invalid vmo offset");
         }
+        if self.is_hugetlb
+            && hugetlb::huge_page_size().is_none_or(|size| !self.align.is_multiple_of(size))
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "hugetlb mappings must be aligned to the huge page size"
+            );
+        }
         match self.offset {
             VmarMapOffset::FixedReplace(offset)
             | VmarMapOffset::FixedNoReplace(offset)
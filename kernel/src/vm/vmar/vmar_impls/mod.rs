@@ -1,18 +1,22 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod access_alien;
+mod advise;
 mod fork;
 pub(super) mod map;
 pub(super) mod page_fault;
 mod protect;
 mod query;
 mod remap;
+mod rmap;
+pub(super) mod swap;
 mod unmap;
+mod userfaultfd;
 
 use core::{
     array,
     ops::Range,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
 use align_ext::AlignExt;
@@ -24,7 +28,7 @@ use super::{
     interval_set::{Interval, IntervalSet},
     is_userspace_vaddr,
     util::{self, get_intersected_range},
-    vm_mapping::{MappedMemory, MappedVmo, VmMapping},
+    vm_mapping::{MappedHugetlb, MappedMemory, MappedVmo, VmMapping, VmoMappingObserver},
 };
 use crate::{
     prelude::*,
@@ -47,6 +51,12 @@ pub struct Vmar {
     process_vm: ProcessVm,
     /// The number of handles that this `Vmar` has (see [`super::VmarHandle`])
     num_handles: AtomicUsize,
+    /// Whether a process using this VMAR has been killed by the OOM killer
+    is_oom_victim: AtomicBool,
+    /// Whether the OOM killer has reaped this VMAR and should skip it from now on
+    is_oom_skipped: AtomicBool,
+    /// A weak reference to itself, which is recorded in reverse mappings
+    weak_self: Weak<Vmar>,
 }
 
 impl Vmar {
@@ -57,15 +67,23 @@ impl Vmar {
         let inner = VmarInner::new();
         let vm_space = VmSpace::new();
         let rss_counters = array::from_fn(|_| PerCpuCounter::new());
-        Arc::new(Vmar {
+        Arc::new_cyclic(|weak_self| Vmar {
             inner: RwMutex::new(inner),
             vm_space: Arc::new(vm_space),
             rss_counters,
             process_vm,
             num_handles: AtomicUsize::new(1),
+            is_oom_victim: AtomicBool::new(false),
+            is_oom_skipped: AtomicBool::new(false),
+            weak_self: weak_self.clone(),
         })
     }
 
+    /// Returns a weak reference to this VMAR.
+    pub(in crate::vm) fn weak_self(&self) -> &Weak<Vmar> {
+        &self.weak_self
+    }
+
     /// Returns the current RSS count for the given RSS type.
     pub fn get_rss_counter(&self, rss_type: RssType) -> usize {
         self.rss_counters[rss_type as usize].sum_all_cpus()
@@ -109,9 +127,35 @@ impl Vmar {
             // Clear all the mappings. The last process using this VMAR exited
             // or executed a new program, so this VMAR no longer has a handle.
             self.clear();
+            if self.is_oom_victim() {
+                crate::vm::oom::exit_oom_victim();
+            }
         }
     }
 
+    /// Marks that a process using this VMAR has been killed by the OOM killer.
+    pub(in crate::vm) fn mark_oom_victim(&self) {
+        self.is_oom_victim.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether a process using this VMAR has been killed by the OOM
+    /// killer.
+    pub(in crate::vm) fn is_oom_victim(&self) -> bool {
+        self.is_oom_victim.load(Ordering::Relaxed)
+    }
+
+    /// Marks that the OOM killer should no longer wait for this VMAR to be
+    /// freed.
+    pub(in crate::vm) fn set_oom_skipped(&self) {
+        self.is_oom_skipped.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the OOM killer should no longer wait for this VMAR to
+    /// be freed.
+    pub(in crate::vm) fn is_oom_skipped(&self) -> bool {
+        self.is_oom_skipped.load(Ordering::Relaxed)
+    }
+
     fn add_rss_counter(&self, rss_type: RssType, val: isize) {
         // There are races but updating a remote counter won't cause any problems.
         let cpu_id = CpuId::current_racy();
@@ -127,9 +171,13 @@ impl Vmar {
 pub enum RssType {
     File = 0,
     Anon = 1,
+    /// Swapped-out pages, which are not resident but are counted like RSS.
+    Swap = 2,
+    /// Base pages in the mapped hugetlb pages, which are not counted in RSS.
+    Hugetlb = 3,
 }
 
-const NUM_RSS_COUNTERS: usize = 2;
+const NUM_RSS_COUNTERS: usize = 4;
 
 pub(super) struct RssDelta<'a> {
     delta: [isize; NUM_RSS_COUNTERS],
@@ -148,6 +196,11 @@ impl<'a> RssDelta<'a> {
         self.delta[rss_type as usize] += increment;
     }
 
+    /// Returns the VMAR whose RSS counters are updated.
+    pub(super) fn operated_vmar(&self) -> &'a Vmar {
+        self.operated_vmar
+    }
+
     fn get(&self, rss_type: RssType) -> isize {
         self.delta[rss_type as usize]
     }
@@ -316,6 +369,7 @@ impl VmarInner {
         let range = offset..offset + size;
         let mut mappings_to_remove = Vec::new();
         for vm_mapping in self.vm_mappings.find(&range) {
+            vm_mapping.check_split_range(&range)?;
             mappings_to_remove.push(vm_mapping.map_to_addr());
         }
 
@@ -332,16 +386,22 @@ impl VmarInner {
                 self.insert_without_try_merge(right);
             }
 
-            rss_delta.add(taken.rss_type(), -(taken.unmap(vm_space) as isize));
+            taken.unmap(vm_space, rss_delta);
         }
 
         Ok(offset..(offset + size))
     }
 
-    /// Allocates a free region for mapping, searching from high address to low address.
+    /// Allocates a free region for mapping below `cap_addr`, searching from high address to low
+    /// address.
     ///
     /// If no such region is found, return an error.
-    fn alloc_free_region(&mut self, size: usize, align: usize) -> Result<Range<Vaddr>> {
+    fn alloc_free_region(
+        &mut self,
+        size: usize,
+        align: usize,
+        cap_addr: Vaddr,
+    ) -> Result<Range<Vaddr>> {
         // This value represents the highest possible address for a new mapping.
         // For simplicity, we use a fixed value `2048` here. The value contains the following considerations:
         // - The stack fixed padding size.
@@ -349,7 +409,7 @@ impl VmarInner {
         // - The future growth of the stack.
         // FIXME: This value should consider the process's actual stack configuration, which may
         // exist in `ResourceLimits`.
-        let high_limit = VMAR_CAP_ADDR - INIT_STACK_SIZE - PAGE_SIZE * 2048;
+        let high_limit = cap_addr - INIT_STACK_SIZE - PAGE_SIZE * 2048;
         let low_limit = VMAR_LOWEST_ADDR;
 
         fn try_alloc_in_hole(
@@ -450,6 +510,9 @@ impl VmarInner {
         if !last_mapping.can_expand() {
             return_errno_with_message!(Errno::EFAULT, "device mappings cannot be expanded");
         }
+        if last_mapping.is_hugetlb() {
+            return_errno_with_message!(Errno::EINVAL, "hugetlb mappings cannot be expanded");
+        }
 
         self.check_extra_size_fits_rlimit(new_size - old_size)?;
         let last_mapping = self.remove(&last_mapping_addr).unwrap();
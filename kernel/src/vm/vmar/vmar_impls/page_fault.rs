@@ -1,7 +1,19 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::{Interval, RssDelta, Vmar};
-use crate::{prelude::*, vm::perms::VmPerms};
+use ostd::{
+    mm::{HasPaddr, PageProperty, VmSpace, vm_space::VmQueriedItem},
+    task::disable_preempt,
+};
+
+use super::{Interval, RssDelta, RssType, Vmar};
+use crate::{
+    prelude::*,
+    vm::{
+        perms::VmPerms,
+        reclaim,
+        swap::{self, SwapEntry},
+    },
+};
 
 impl Vmar {
     pub fn handle_page_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()> {
@@ -11,6 +23,13 @@ impl Vmar {
         if let Some(vm_mapping) = inner.vm_mappings.find_one(&address) {
             debug_assert!(vm_mapping.range().contains(&address));
 
+            if let Some(userfault) = vm_mapping.check_userfault(&self.vm_space, page_fault_info)? {
+                // Resolving the fault requires operating on the mappings, so
+                // the lock must be released before waiting.
+                drop(inner);
+                return userfault.wait();
+            }
+
             let mut rss_delta = RssDelta::new(self);
             return vm_mapping.handle_page_fault(&self.vm_space, page_fault_info, &mut rss_delta);
         }
@@ -22,6 +41,59 @@ impl Vmar {
     }
 }
 
+/// Swaps in the page at `va`, which has been swapped out to `entry`.
+///
+/// The caller should pin `entry` with [`swap::dup_entry`] before dropping the
+/// cursor that finds it, so that its slot cannot be reused while the page is
+/// being read. This function releases the pin.
+///
+/// The page is mapped with `prop` only if the page table entry at `va` still
+/// contains `entry`. Otherwise, the page has been swapped in or unmapped
+/// concurrently, and nothing is done.
+pub(in crate::vm::vmar) fn swap_in_page(
+    vm_space: &VmSpace,
+    va: Vaddr,
+    entry: SwapEntry,
+    prop: PageProperty,
+    rss_type: RssType,
+    rss_delta: &mut RssDelta,
+) -> Result<()> {
+    let res = read_and_map_swap_page(vm_space, va, entry, prop, rss_type, rss_delta);
+    swap::free_entry(entry);
+    res
+}
+
+fn read_and_map_swap_page(
+    vm_space: &VmSpace,
+    va: Vaddr,
+    entry: SwapEntry,
+    prop: PageProperty,
+    rss_type: RssType,
+    rss_delta: &mut RssDelta,
+) -> Result<()> {
+    let frame = swap::read_page(entry)?;
+    let paddr = frame.paddr();
+
+    let preempt_guard = disable_preempt();
+    let mut cursor = vm_space.cursor_mut(&preempt_guard, &(va..va + PAGE_SIZE))?;
+    match cursor.query()? {
+        (_, Some(VmQueriedItem::Token(token))) if SwapEntry::from_token(token) == entry => (),
+        _ => return Ok(()),
+    }
+
+    cursor.unmap(PAGE_SIZE);
+    cursor.jump(va).unwrap();
+    cursor.map(frame, prop);
+    // The page table entry no longer refers to the swap entry.
+    swap::free_entry(entry);
+
+    rss_delta.add(RssType::Swap, -1);
+    rss_delta.add(rss_type, 1);
+    reclaim::lru_add_anon_page(rss_delta.operated_vmar(), va, paddr);
+
+    Ok(())
+}
+
 /// Page fault information converted from [`CpuException`].
 ///
 /// `TryFrom<CpuException>` should be implemented for this struct.
@@ -41,6 +113,9 @@ pub struct PageFaultInfo {
     /// Whether this page fault is forced (e.g., manually triggered by `ptrace`).
     /// A forced page fault may bypass some permission checks.
     is_forced: bool,
+
+    /// Whether this page fault occurs in user mode.
+    is_user_mode: bool,
 }
 
 impl PageFaultInfo {
@@ -50,6 +125,7 @@ impl PageFaultInfo {
             address,
             required_perms,
             is_forced: false,
+            is_user_mode: false,
         }
     }
 
@@ -63,4 +139,15 @@ impl PageFaultInfo {
         self.is_forced = true;
         self
     }
+
+    /// Returns whether this page fault occurs in user mode.
+    pub(in crate::vm::vmar) fn is_user_mode(&self) -> bool {
+        self.is_user_mode
+    }
+
+    /// Marks this page fault as occurring in user mode.
+    pub fn user_mode(mut self) -> Self {
+        self.is_user_mode = true;
+        self
+    }
 }
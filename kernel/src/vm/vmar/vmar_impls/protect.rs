@@ -24,6 +24,7 @@ impl Vmar {
         let mut protect_mappings = Vec::new();
 
         for vm_mapping in inner.vm_mappings.find(&range) {
+            vm_mapping.check_split_range(&range)?;
             protect_mappings.push((vm_mapping.range(), vm_mapping.perms()))
         }
 
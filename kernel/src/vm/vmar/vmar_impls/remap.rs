@@ -83,6 +83,9 @@ impl Vmar {
                 "remap: there is no mapping at the old address"
             )
         };
+        if old_mapping.is_hugetlb() {
+            return_errno_with_message!(Errno::EINVAL, "remap: hugetlb mappings cannot be remapped");
+        }
         if new_size > old_size {
             if !old_mapping.can_expand() {
                 return_errno_with_message!(
@@ -146,7 +149,7 @@ impl Vmar {
                 return Ok(old_range.start);
             }
 
-            inner.alloc_free_region(new_size, PAGE_SIZE)?
+            inner.alloc_free_region(new_size, PAGE_SIZE, self.process_vm.vmar_cap_addr())?
         };
 
         // Create a new `VmMapping`.
@@ -181,7 +184,7 @@ impl Vmar {
             let (va, Some(item)) = cursor.query().unwrap() else {
                 panic!("Found mapped page but query failed");
             };
-            debug_assert_eq!(mapped_va, va.start);
+            debug_assert!(va.contains(&mapped_va));
 
             let offset = mapped_va - old_range.start;
             let new_map_va = new_range.start + offset;
@@ -195,6 +198,12 @@ impl Vmar {
 
                     cursor.map(frame, prop);
                 }
+                VmQueriedItem::MappedHugeRam { .. } => {
+                    // Split the huge page so that its base pages are moved one
+                    // by one. The new address may not be aligned to huge pages.
+                    cursor.split_huge();
+                    continue;
+                }
                 VmQueriedItem::MappedIoMem { paddr, prop } => {
                     cursor.unmap(PAGE_SIZE);
                     cursor.jump(new_map_va).unwrap();
@@ -204,6 +213,13 @@ impl Vmar {
                     let (iomem, offset) = cursor.find_iomem_by_paddr(paddr).unwrap();
                     cursor.map_iomem(iomem, prop, PAGE_SIZE, offset);
                 }
+                VmQueriedItem::Token(token) => {
+                    // Move the swap entry of the swapped-out page.
+                    cursor.unmap(PAGE_SIZE);
+                    cursor.jump(new_map_va).unwrap();
+
+                    cursor.map_token(token);
+                }
             }
 
             current_offset = offset + PAGE_SIZE;
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::{
+    mm::{HasPaddr, Paddr, PageFlags, tlb::TlbFlushOp, vm_space::VmQueriedItem},
+    task::disable_preempt,
+};
+
+use super::{RssType, Vmar};
+use crate::{
+    prelude::*,
+    vm::{
+        page_cache::Vmo,
+        reclaim::{RmapOp, RmapResult},
+    },
+};
+
+impl Vmar {
+    /// Applies `op` to the page table entries that map the page at `page_idx` in `vmo`.
+    ///
+    /// Only the entries that map the frame at `paddr` are affected. Private
+    /// mappings of the VMO may have replaced the page with their own copies,
+    /// and such copies are left untouched.
+    ///
+    /// This method does not block. If the VMAR is locked by a writer, the
+    /// method returns with [`RmapResult::is_busy`] set.
+    pub(in crate::vm) fn rmap_vmo_page(
+        &self,
+        vmo: &Vmo,
+        page_idx: usize,
+        paddr: Paddr,
+        op: RmapOp,
+        result: &mut RmapResult,
+    ) {
+        let Some(inner) = self.inner.try_read() else {
+            result.is_busy = true;
+            return;
+        };
+
+        let page_offset = page_idx * PAGE_SIZE;
+        for vm_mapping in inner.vm_mappings.iter() {
+            let Some((mapped_vmo, vmo_offset)) = vm_mapping.vmo_and_offset() else {
+                continue;
+            };
+            if !core::ptr::eq(Arc::as_ptr(mapped_vmo), vmo) || page_offset < vmo_offset {
+                continue;
+            }
+
+            let vaddr = vm_mapping.map_to_addr() + (page_offset - vmo_offset);
+            if vaddr >= vm_mapping.map_end() {
+                continue;
+            }
+
+            self.rmap_one(vm_mapping.rss_type(), vaddr, paddr, op, result);
+        }
+    }
+
+    /// Applies `op` to the page table entry that maps the private anonymous
+    /// page at `vaddr`.
+    ///
+    /// The entry is affected only if it still maps the frame at `paddr`.
+    ///
+    /// This method does not block. If the VMAR is locked by a writer, the
+    /// method returns with [`RmapResult::is_busy`] set.
+    pub(in crate::vm) fn rmap_anon_page(
+        &self,
+        vaddr: Vaddr,
+        paddr: Paddr,
+        op: RmapOp,
+        result: &mut RmapResult,
+    ) {
+        let Some(inner) = self.inner.try_read() else {
+            result.is_busy = true;
+            return;
+        };
+
+        if let Some(vm_mapping) = inner.vm_mappings.find_one(&vaddr) {
+            self.rmap_one(vm_mapping.rss_type(), vaddr, paddr, op, result);
+        }
+    }
+
+    fn rmap_one(
+        &self,
+        rss_type: RssType,
+        vaddr: Vaddr,
+        paddr: Paddr,
+        op: RmapOp,
+        result: &mut RmapResult,
+    ) {
+        let preempt_guard = disable_preempt();
+        let Ok(mut cursor) = self
+            .vm_space
+            .cursor_mut(&preempt_guard, &(vaddr..vaddr + PAGE_SIZE))
+        else {
+            return;
+        };
+
+        let flags = match cursor.query() {
+            Ok((_, Some(VmQueriedItem::MappedRam { frame, prop }))) if frame.paddr() == paddr => {
+                prop.flags
+            }
+            _ => return,
+        };
+        result.nr_mapped += 1;
+        result.is_referenced |= flags.contains(PageFlags::ACCESSED);
+        result.is_dirty |= flags.contains(PageFlags::DIRTY);
+
+        match op {
+            RmapOp::Query => {}
+            RmapOp::ClearAccessed => {
+                if flags.contains(PageFlags::ACCESSED) {
+                    cursor.protect_next(PAGE_SIZE, |flags, _cache| {
+                        flags.remove(PageFlags::ACCESSED);
+                    });
+                    cursor
+                        .flusher()
+                        .issue_tlb_flush(TlbFlushOp::for_single(vaddr));
+                    cursor.flusher().dispatch_tlb_flush();
+                }
+            }
+            RmapOp::Unmap => {
+                if flags.contains(PageFlags::W) && !flags.contains(PageFlags::DIRTY) {
+                    // Write-protect the page first. Otherwise, the page may be
+                    // dirtied after we collect the dirty bit but before we
+                    // unmap it, and the modification would be lost.
+                    cursor.protect_next(PAGE_SIZE, |flags, _cache| {
+                        flags.remove(PageFlags::W);
+                    });
+                    cursor
+                        .flusher()
+                        .issue_tlb_flush(TlbFlushOp::for_single(vaddr));
+                    cursor.flusher().dispatch_tlb_flush();
+                    cursor.flusher().sync_tlb_flush();
+
+                    cursor.jump(vaddr).unwrap();
+                    if let Ok((_, Some(item))) = cursor.query() {
+                        result.is_dirty |= item.prop().flags.contains(PageFlags::DIRTY);
+                    }
+                }
+
+                cursor.unmap(PAGE_SIZE);
+                cursor.flusher().sync_tlb_flush();
+                self.add_rss_counter(rss_type, -1);
+            }
+        }
+    }
+}
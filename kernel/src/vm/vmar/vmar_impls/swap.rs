@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::{
+    mm::{
+        HasPaddr, Paddr, PageFlags,
+        tlb::TlbFlushOp,
+        vm_space::{CursorMut, VmQueriedItem},
+    },
+    task::disable_preempt,
+};
+
+use super::{RssDelta, RssType, Vmar};
+use crate::{
+    prelude::*,
+    vm::{
+        perms::VmPerms,
+        reclaim::ReclaimResult,
+        swap::{self, SwapEntry},
+    },
+};
+
+impl Vmar {
+    /// Tries to swap out the private anonymous page at `vaddr`.
+    ///
+    /// The page is swapped out only if it still maps the frame at `paddr`, and
+    /// the frame is not shared with other page tables. The page is written to
+    /// a swap area before its page table entry is replaced by the swap entry.
+    ///
+    /// If `may_use_file` is false, the page is not written to swap files.
+    ///
+    /// This method does not block on the VMAR lock. If the VMAR is locked by
+    /// a writer, the method returns [`ReclaimResult::Busy`].
+    pub(in crate::vm) fn try_swap_out_page(
+        &self,
+        vaddr: Vaddr,
+        paddr: Paddr,
+        may_use_file: bool,
+    ) -> ReclaimResult {
+        let page_range = vaddr..vaddr + PAGE_SIZE;
+
+        let (entry, frame) = {
+            let Some(inner) = self.inner.try_read() else {
+                return ReclaimResult::Busy;
+            };
+            if inner.vm_mappings.find_one(&vaddr).is_none() {
+                return ReclaimResult::Gone;
+            }
+
+            let preempt_guard = disable_preempt();
+            let Ok(mut cursor) = self.vm_space.cursor_mut(&preempt_guard, &page_range) else {
+                return ReclaimResult::Gone;
+            };
+            let (frame, flags) = match cursor.query() {
+                Ok((_, Some(VmQueriedItem::MappedRam { frame, prop })))
+                    if frame.paddr() == paddr =>
+                {
+                    ((*frame).clone(), prop.flags)
+                }
+                _ => return ReclaimResult::Gone,
+            };
+
+            if flags.contains(PageFlags::ACCESSED) {
+                cursor.protect_next(PAGE_SIZE, |flags, _cache| {
+                    flags.remove(PageFlags::ACCESSED);
+                });
+                cursor
+                    .flusher()
+                    .issue_tlb_flush(TlbFlushOp::for_single(vaddr));
+                cursor.flusher().dispatch_tlb_flush();
+                return ReclaimResult::Referenced;
+            }
+
+            // One reference is held by the page table and the other is held
+            // by us. Other references indicate that the frame is shared, e.g.,
+            // with a child process after fork.
+            if frame.reference_count() > 2 {
+                return ReclaimResult::Busy;
+            }
+
+            let Some(entry) = swap::alloc_entry(may_use_file) else {
+                return ReclaimResult::Busy;
+            };
+
+            // Write-protect the page and clear its dirty bit, so that any
+            // modification during the writeback can be detected. A write to
+            // the page will replace it with a copy via COW, since we hold a
+            // reference to the frame.
+            cursor.protect_next(PAGE_SIZE, |flags, _cache| {
+                flags.remove(PageFlags::W | PageFlags::DIRTY);
+            });
+            cursor
+                .flusher()
+                .issue_tlb_flush(TlbFlushOp::for_single(vaddr));
+            cursor.flusher().dispatch_tlb_flush();
+            cursor.flusher().sync_tlb_flush();
+
+            (entry, frame)
+        };
+
+        // Write the page without holding any locks, since it involves I/O.
+        if let Err(err) = swap::write_page(entry, &frame) {
+            warn!("failed to write a page to the swap area: {:?}", err);
+            swap::free_entry(entry);
+            return ReclaimResult::Busy;
+        }
+
+        let Some(inner) = self.inner.try_read() else {
+            swap::free_entry(entry);
+            return ReclaimResult::Busy;
+        };
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&vaddr) else {
+            swap::free_entry(entry);
+            return ReclaimResult::Gone;
+        };
+
+        let preempt_guard = disable_preempt();
+        let Ok(mut cursor) = self.vm_space.cursor_mut(&preempt_guard, &page_range) else {
+            swap::free_entry(entry);
+            return ReclaimResult::Gone;
+        };
+        let is_unchanged = match cursor.query() {
+            Ok((_, Some(VmQueriedItem::MappedRam { frame, prop }))) => {
+                frame.paddr() == paddr && !prop.flags.intersects(PageFlags::W | PageFlags::DIRTY)
+            }
+            _ => false,
+        };
+        if !is_unchanged {
+            swap::free_entry(entry);
+            return ReclaimResult::Gone;
+        }
+
+        cursor.unmap(PAGE_SIZE);
+        cursor.jump(vaddr).unwrap();
+        cursor.map_token(entry.to_token());
+        cursor.flusher().dispatch_tlb_flush();
+        cursor.flusher().sync_tlb_flush();
+
+        self.add_rss_counter(vm_mapping.rss_type(), -1);
+        self.add_rss_counter(RssType::Swap, 1);
+
+        ReclaimResult::Reclaimed
+    }
+
+    /// Swaps in all the pages that are swapped out to the swap area at
+    /// `area_idx`.
+    pub(in crate::vm) fn unuse_swap_area(&self, area_idx: usize) -> Result<()> {
+        let inner = self.inner.read();
+        let mut rss_delta = RssDelta::new(self);
+
+        for vm_mapping in inner.vm_mappings.iter() {
+            let range = vm_mapping.range();
+            let mut vaddrs = Vec::new();
+            {
+                let preempt_guard = disable_preempt();
+                let mut cursor = self.vm_space.cursor(&preempt_guard, &range)?;
+                while let Some(vaddr) = cursor.find_next(range.end - cursor.virt_addr()) {
+                    if let (_, Some(VmQueriedItem::Token(token))) = cursor.query()?
+                        && SwapEntry::from_token(token).area_idx() == area_idx
+                    {
+                        vaddrs.push(vaddr);
+                    }
+                    if vaddr + PAGE_SIZE >= range.end {
+                        break;
+                    }
+                    cursor.jump(vaddr + PAGE_SIZE)?;
+                }
+            }
+
+            // Swap in the pages via the page fault handler. No permissions are
+            // required, so no COW is performed.
+            for vaddr in vaddrs {
+                vm_mapping.handle_single_page_fault(
+                    &self.vm_space,
+                    vaddr,
+                    VmPerms::empty(),
+                    &mut rss_delta,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Frees the swap entries in the next `len` bytes from the current position
+/// of `cursor`.
+///
+/// [`CursorMut::unmap`] clears the swap entries without reporting them, so
+/// this function should be called before unmapping pages. The swap entries are
+/// unmapped from the page table and the cursor is moved back to where it was.
+///
+/// Returns the number of freed swap entries.
+pub(in crate::vm::vmar) fn free_swap_entries(cursor: &mut CursorMut<'_>, len: usize) -> usize {
+    if !swap::is_enabled() {
+        return 0;
+    }
+
+    let start = cursor.virt_addr();
+    let end = start + len;
+    let mut nr_freed = 0;
+
+    while cursor.find_next(end - cursor.virt_addr()).is_some() {
+        let (va, Some(item)) = cursor.query().unwrap() else {
+            panic!("Found mapped page but query failed");
+        };
+        if let VmQueriedItem::Token(token) = item {
+            cursor.unmap(PAGE_SIZE);
+            swap::free_entry(SwapEntry::from_token(token));
+            nr_freed += 1;
+        }
+        // Skip the whole item, which may be a huge page.
+        if va.end >= end {
+            break;
+        }
+        cursor.jump(va.end).unwrap();
+    }
+
+    cursor.jump(start).unwrap();
+    nr_freed
+}
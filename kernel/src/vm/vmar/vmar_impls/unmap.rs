@@ -4,7 +4,7 @@ use core::ops::Range;
 
 use ostd::task::disable_preempt;
 
-use super::{RssDelta, Vmar};
+use super::{RssDelta, RssType, Vmar, swap::free_swap_entries};
 use crate::{
     prelude::*,
     vm::vmar::{VMAR_CAP_ADDR, interval_set::Interval, util::get_intersected_range},
@@ -25,10 +25,48 @@ impl Vmar {
             .vm_space
             .cursor_mut(&preempt_guard, &full_range)
             .unwrap();
+        free_swap_entries(&mut cursor, full_range.len());
         cursor.unmap(full_range.len());
         cursor.flusher().sync_tlb_flush();
     }
 
+    /// Unmaps the pages in all private mappings, so that the memory of a
+    /// process killed by the OOM killer can be freed before it exits.
+    ///
+    /// The mappings themselves are kept. If the pages are accessed again, they
+    /// will be reloaded by the page fault handler. The swapped-out pages in the
+    /// mappings are discarded as well, but they are not counted as reaped.
+    ///
+    /// This method does not block. If the VMAR is locked by a writer, it
+    /// returns `None`. Otherwise, it returns the number of unmapped pages.
+    pub(in crate::vm) fn reap_private_pages(&self) -> Option<usize> {
+        let inner = self.inner.try_read()?;
+        let mut rss_delta = RssDelta::new(self);
+        let mut nr_reaped = 0;
+
+        for vm_mapping in inner.vm_mappings.iter() {
+            // Pages in shared mappings are still reachable from their VMOs, so
+            // unmapping them frees no memory.
+            if vm_mapping.is_shared() || !vm_mapping.can_expand() {
+                continue;
+            }
+
+            let range = vm_mapping.range();
+            let preempt_guard = disable_preempt();
+            let mut cursor = self.vm_space.cursor_mut(&preempt_guard, &range).unwrap();
+            let nr_swapped = free_swap_entries(&mut cursor, range.len());
+            let nr_unmapped = cursor.unmap(range.len());
+            cursor.flusher().dispatch_tlb_flush();
+            cursor.flusher().sync_tlb_flush();
+
+            rss_delta.add(RssType::Swap, -(nr_swapped as isize));
+            rss_delta.add(vm_mapping.rss_type(), -(nr_unmapped as isize));
+            nr_reaped += nr_unmapped;
+        }
+
+        Some(nr_reaped)
+    }
+
     /// Destroys all mappings that fall within the specified
     /// range in bytes.
     ///
@@ -78,6 +116,7 @@ impl Vmar {
             if !vm_mapping.can_expand() {
                 return_errno_with_message!(Errno::EINVAL, "device mappings cannot be discarded");
             }
+            vm_mapping.check_split_range(&range)?;
 
             // The range may contain pages that are not mapped. According to Linux behavior, this
             // is not a fault. However, an `ENOMEM` should be reported at the end.
@@ -94,6 +133,10 @@ impl Vmar {
                 .cursor_mut(&preempt_guard, &intersected_range)
                 .unwrap();
 
+            rss_delta.add(
+                RssType::Swap,
+                -(free_swap_entries(&mut cursor, intersected_range.len()) as isize),
+            );
             rss_delta.add(
                 vm_mapping.rss_type(),
                 -(cursor.unmap(intersected_range.len()) as isize),
@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use ostd::mm::Frame;
+
+use super::{Interval, RssDelta, Vmar, VmarInner, util::get_intersected_range};
+use crate::{
+    prelude::*,
+    vm::{
+        memcg::AnonPageMeta,
+        userfaultfd::{UffdRegistration, Userfaultfd},
+    },
+};
+
+impl Vmar {
+    /// Registers the memory mappings in the specified range to a userfaultfd.
+    ///
+    /// The range's start and end addresses must be page-aligned. Unmapped
+    /// pages in the range are skipped, but the range must contain at least
+    /// one mapping.
+    ///
+    /// If any mapping in the range cannot be registered, an [`EINVAL`] error
+    /// will be returned. If any mapping in the range has been registered to
+    /// another userfaultfd, an [`EBUSY`] error will be returned.
+    ///
+    /// [`EINVAL`]: Errno::EINVAL
+    /// [`EBUSY`]: Errno::EBUSY
+    pub(in crate::vm) fn register_userfaultfd(
+        &self,
+        range: Range<Vaddr>,
+        registration: UffdRegistration,
+    ) -> Result<()> {
+        debug_assert!(range.start.is_multiple_of(PAGE_SIZE));
+        debug_assert!(range.end.is_multiple_of(PAGE_SIZE));
+
+        let mut inner = self.inner.write();
+
+        let mut register_mappings = Vec::new();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            if !vm_mapping.can_userfault() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the range contains mappings that cannot be registered"
+                );
+            }
+            if let Some(old_registration) = vm_mapping.userfaultfd()
+                && !old_registration.is_to(registration.ctx())
+            {
+                return_errno_with_message!(
+                    Errno::EBUSY,
+                    "the range contains mappings registered to another userfaultfd"
+                );
+            }
+            register_mappings.push(vm_mapping.range());
+        }
+
+        if register_mappings.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the range does not contain any mappings");
+        }
+
+        for vm_mapping_range in register_mappings {
+            set_userfaultfd_in_range(
+                &mut inner,
+                vm_mapping_range.start,
+                &range,
+                Some(registration.clone()),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Unregisters the memory mappings in the specified range from a
+    /// userfaultfd.
+    ///
+    /// The range's start and end addresses must be page-aligned. The mappings
+    /// that are not registered to the userfaultfd are skipped.
+    ///
+    /// If any mapping in the range cannot be registered, an [`EINVAL`] error
+    /// will be returned.
+    ///
+    /// [`EINVAL`]: Errno::EINVAL
+    pub(in crate::vm) fn unregister_userfaultfd(
+        &self,
+        ctx: &Arc<Userfaultfd>,
+        range: Range<Vaddr>,
+    ) -> Result<()> {
+        debug_assert!(range.start.is_multiple_of(PAGE_SIZE));
+        debug_assert!(range.end.is_multiple_of(PAGE_SIZE));
+
+        let mut inner = self.inner.write();
+
+        let mut unregister_mappings = Vec::new();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            if !vm_mapping.can_userfault() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the range contains mappings that cannot be registered"
+                );
+            }
+            if vm_mapping
+                .userfaultfd()
+                .is_some_and(|registration| registration.is_to(ctx))
+            {
+                unregister_mappings.push(vm_mapping.range());
+            }
+        }
+
+        for vm_mapping_range in unregister_mappings {
+            let intersected_range = get_intersected_range(&range, &vm_mapping_range);
+            let vm_mapping = inner.vm_mappings.find_one(&vm_mapping_range.start).unwrap();
+            vm_mapping.set_userfault_wp(self.vm_space(), &intersected_range, false);
+
+            set_userfaultfd_in_range(&mut inner, vm_mapping_range.start, &range, None);
+        }
+
+        Ok(())
+    }
+
+    /// Unregisters all the memory mappings from a userfaultfd after the
+    /// userfaultfd is closed.
+    pub(in crate::vm) fn release_userfaultfd(&self, ctx: &Arc<Userfaultfd>) {
+        let mut inner = self.inner.write();
+
+        let unregister_mappings = inner
+            .vm_mappings
+            .iter()
+            .filter(|vm_mapping| {
+                vm_mapping
+                    .userfaultfd()
+                    .is_some_and(|registration| registration.is_to(ctx))
+            })
+            .map(|vm_mapping| vm_mapping.range())
+            .collect::<Vec<_>>();
+
+        for vm_mapping_range in unregister_mappings {
+            let Some(mut vm_mapping) = inner.remove(&vm_mapping_range.start) else {
+                // This can happen only if the mapping is merged to the previous one (just
+                // unregistered before).
+                continue;
+            };
+            vm_mapping.set_userfault_wp(self.vm_space(), &vm_mapping.range(), false);
+            vm_mapping.set_userfaultfd(None);
+            inner.insert_try_merge(vm_mapping);
+        }
+    }
+
+    /// Maps a frame to a page registered to a userfaultfd (`UFFDIO_COPY` and
+    /// `UFFDIO_ZEROPAGE`).
+    ///
+    /// If the page is not in a mapping registered to the userfaultfd, an
+    /// [`ENOENT`] error will be returned. If the page is already mapped, an
+    /// [`EEXIST`] error will be returned.
+    ///
+    /// [`ENOENT`]: Errno::ENOENT
+    /// [`EEXIST`]: Errno::EEXIST
+    pub(in crate::vm) fn fill_userfault_page(
+        &self,
+        ctx: &Arc<Userfaultfd>,
+        page_addr: Vaddr,
+        frame: Frame<AnonPageMeta>,
+        is_wp: bool,
+    ) -> Result<()> {
+        debug_assert!(page_addr.is_multiple_of(PAGE_SIZE));
+
+        let inner = self.inner.read();
+
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&page_addr) else {
+            return_errno_with_message!(Errno::ENOENT, "the page is not mapped");
+        };
+        let Some(registration) = vm_mapping
+            .userfaultfd()
+            .filter(|registration| registration.is_to(ctx))
+        else {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the page is not registered to the userfaultfd"
+            );
+        };
+        if is_wp && !registration.reports_wp() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the page is not registered in the write protection mode"
+            );
+        }
+
+        let mut rss_delta = RssDelta::new(self);
+        vm_mapping.map_userfault_page(self.vm_space(), page_addr, frame, is_wp, &mut rss_delta)
+    }
+
+    /// Sets or clears the write protection of a userfaultfd on the pages in
+    /// the specified range (`UFFDIO_WRITEPROTECT`).
+    ///
+    /// The range's start and end addresses must be page-aligned. If the range
+    /// contains pages that are not in mappings registered to the userfaultfd
+    /// in the write protection mode, an [`ENOENT`] error will be returned.
+    ///
+    /// [`ENOENT`]: Errno::ENOENT
+    pub(in crate::vm) fn write_protect_userfault(
+        &self,
+        ctx: &Arc<Userfaultfd>,
+        range: Range<Vaddr>,
+        is_wp: bool,
+    ) -> Result<()> {
+        debug_assert!(range.start.is_multiple_of(PAGE_SIZE));
+        debug_assert!(range.end.is_multiple_of(PAGE_SIZE));
+
+        let inner = self.inner.read();
+
+        let mut last_mapping_end = range.start;
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let is_registered = vm_mapping
+                .userfaultfd()
+                .is_some_and(|registration| registration.is_to(ctx) && registration.reports_wp());
+            if last_mapping_end < vm_mapping.map_to_addr() || !is_registered {
+                return_errno_with_message!(
+                    Errno::ENOENT,
+                    "the range is not registered to the userfaultfd in the write protection mode"
+                );
+            }
+            last_mapping_end = vm_mapping.map_end();
+        }
+        if last_mapping_end < range.end {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the range is not registered to the userfaultfd in the write protection mode"
+            );
+        }
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.set_userfault_wp(self.vm_space(), &intersected_range, is_wp);
+        }
+
+        Ok(())
+    }
+}
+
+/// Sets the registration to a userfaultfd of the part of the mapping in the
+/// range.
+fn set_userfaultfd_in_range(
+    inner: &mut VmarInner,
+    vm_mapping_addr: Vaddr,
+    range: &Range<Vaddr>,
+    registration: Option<UffdRegistration>,
+) {
+    let Some(vm_mapping) = inner.remove(&vm_mapping_addr) else {
+        // This can happen only if the mapping is merged to the previous one (just updated
+        // before). We can skip this mapping because its registration is already correct.
+        return;
+    };
+    let vm_mapping_range = vm_mapping.range();
+    let intersected_range = get_intersected_range(range, &vm_mapping_range);
+
+    // Updates part of the taken `VmMapping`.
+    let (left, mut taken, right) = vm_mapping.split_range(&intersected_range);
+
+    // Puts the rest back.
+    if let Some(left) = left {
+        inner.insert_without_try_merge(left);
+    }
+    if let Some(right) = right {
+        inner.insert_without_try_merge(right);
+    }
+
+    taken.set_userfaultfd(registration);
+    inner.insert_try_merge(taken);
+}
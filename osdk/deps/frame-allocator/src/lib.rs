@@ -38,6 +38,7 @@ use ostd::{
 
 mod cache;
 mod chunk;
+mod low_memory;
 mod pools;
 mod set;
 mod smp_counter;
@@ -55,6 +56,8 @@ pub fn load_total_free_size() -> usize {
     TOTAL_FREE_SIZE.get()
 }
 
+pub use low_memory::set_low_memory_handler;
+
 /// The global frame allocator provided by OSDK.
 ///
 /// It is a singleton that provides frame allocation for the kernel. If
@@ -69,6 +72,12 @@ impl GlobalFrameAllocator for FrameAllocator {
         if res.is_some() {
             TOTAL_FREE_SIZE.sub(guard.current_cpu(), layout.size());
         }
+        let is_slow_path = pools::take_slow_path_flag(&guard) || res.is_none();
+        drop(guard);
+
+        if is_slow_path {
+            low_memory::notify_if_low();
+        }
         res
     }
 
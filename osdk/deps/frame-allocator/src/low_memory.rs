@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Notifications of low free memory.
+//!
+//! Checking the total size of free memory sums up CPU-local counters, so it
+//! is only done on the slow paths of allocation, i.e., when an allocation
+//! locks the global pool or fails.
+
+use ostd::sync::{LocalIrqDisabled, SpinLock};
+
+use crate::load_total_free_size;
+
+/// The registered handler and the low watermark (in bytes) below which the
+/// handler is called.
+static LOW_MEMORY_HANDLER: SpinLock<Option<(usize, fn())>, LocalIrqDisabled> = SpinLock::new(None);
+
+/// Sets a handler that is called on the slow paths of allocation when the
+/// total size of free memory is below `low_watermark` bytes.
+///
+/// The handler may be called in atomic mode, including IRQ context, so it
+/// should not sleep or allocate frames.
+pub fn set_low_memory_handler(low_watermark: usize, handler: fn()) {
+    *LOW_MEMORY_HANDLER.lock() = Some((low_watermark, handler));
+}
+
+/// Calls the handler if the total size of free memory is below the low
+/// watermark.
+pub(super) fn notify_if_low() {
+    let Some((low_watermark, handler)) = *LOW_MEMORY_HANDLER.lock() else {
+        return;
+    };
+
+    if load_total_free_size() < low_watermark {
+        handler();
+    }
+}
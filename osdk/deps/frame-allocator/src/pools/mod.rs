@@ -4,7 +4,7 @@ mod balancing;
 
 use core::{
     alloc::Layout,
-    cell::RefCell,
+    cell::{Cell, RefCell},
     ops::DerefMut,
     sync::atomic::{AtomicUsize, Ordering},
 };
@@ -31,6 +31,12 @@ cpu_local! {
     static LOCAL_POOL: RefCell<BuddySet<MAX_LOCAL_BUDDY_ORDER>> = RefCell::new(BuddySet::new_empty());
 }
 
+// Whether an allocation on the CPU has locked the global pool since the flag
+// was last taken.
+cpu_local! {
+    static TOOK_SLOW_PATH: Cell<bool> = Cell::new(false);
+}
+
 /// Maximum supported order of the buddy system.
 ///
 /// i.e., it is the number of classes of free blocks. It determines the
@@ -89,9 +95,19 @@ pub(super) fn alloc(guard: &DisabledLocalIrqGuard, layout: Layout) -> Option<Pad
 
     balancing::balance(local_pool.deref_mut(), &mut global_pool);
 
+    if global_pool.guard.is_some() {
+        TOOK_SLOW_PATH.get_with(guard).set(true);
+    }
+
     chunk_addr
 }
 
+/// Returns whether an allocation on the current CPU has locked the global
+/// pool since the last call, and clears the flag.
+pub(super) fn take_slow_path_flag(guard: &DisabledLocalIrqGuard) -> bool {
+    TOOK_SLOW_PATH.get_with(guard).replace(false)
+}
+
 pub(super) fn dealloc(
     guard: &DisabledLocalIrqGuard,
     segments: impl Iterator<Item = (Paddr, usize)>,
@@ -16,6 +16,9 @@ pub(crate) use remapping::IrqRemapping;
 pub(crate) const IRQ_NUM_MIN: u8 = 0;
 pub(crate) const IRQ_NUM_MAX: u8 = 255;
 
+/// The IRQ numbers that are not available for allocation.
+pub(crate) const RESERVED_IRQ_NUMS: &[u8] = &[];
+
 /// An IRQ line with additional information that helps acknowledge the interrupt
 /// on hardware.
 ///
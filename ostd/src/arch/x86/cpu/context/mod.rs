@@ -19,7 +19,11 @@ use x86_64::registers::{
 use crate::{
     arch::{
         irq::HwIrqLine,
-        trap::{RawUserContext, TrapFrame},
+        trap::{
+            IA32_SYSCALL_TRAPNUM, RawUserContext, SYSCALL_TRAPNUM, SYSENTER_TRAPNUM, TrapFrame,
+            USER_CS_VALUE, USER32_CS_VALUE,
+            gdt::{GDT_ENTRY_TLS_MIN, NR_TLS_ENTRIES, load_tls_entries},
+        },
     },
     cpu::PrivilegeLevel,
     debug,
@@ -137,6 +141,48 @@ impl GsBase {
     }
 }
 
+/// The user-mode thread-local storage (TLS) entries of the GDT.
+///
+/// 32-bit programs set up the entries with the `set_thread_area` system call and access them via
+/// the segment selectors that refer to the entries.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TlsEntries([u64; NR_TLS_ENTRIES]);
+
+impl TlsEntries {
+    /// The GDT index of the first TLS entry.
+    pub const FIRST_INDEX: usize = GDT_ENTRY_TLS_MIN;
+    /// The number of the TLS entries.
+    pub const LEN: usize = NR_TLS_ENTRIES;
+
+    /// Returns the segment descriptor of the `index`-th TLS entry.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `index` is not less than [`Self::LEN`].
+    pub fn get(&self, index: usize) -> u64 {
+        self.0[index]
+    }
+
+    /// Sets the segment descriptor of the `index`-th TLS entry.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `index` is not less than [`Self::LEN`], or if `desc` is neither
+    /// empty nor a user-mode code or data segment descriptor.
+    pub fn set(&mut self, index: usize, desc: u64) {
+        const DESC_S: u64 = 1 << 44;
+        const DESC_DPL3: u64 = 3 << 45;
+
+        assert!(desc == 0 || (desc & DESC_S != 0 && desc & DESC_DPL3 == DESC_DPL3));
+        self.0[index] = desc;
+    }
+
+    /// Loads the TLS entries into the GDT of the current CPU.
+    pub fn load(&self, guard: &DisabledLocalIrqGuard) {
+        load_tls_entries(&self.0, guard);
+    }
+}
+
 /// Architectural CPU exceptions (x86-64 vectors 0-31).
 ///
 /// For the authoritative specification of each vector, see the
@@ -301,6 +347,41 @@ impl UserContext {
     pub fn take_exception(&mut self) -> Option<CpuException> {
         self.exception.take()
     }
+
+    /// Returns whether the user space runs in the 32-bit compatibility mode.
+    pub fn is_compat_mode(&self) -> bool {
+        self.user_context.cs == USER32_CS_VALUE
+    }
+
+    /// Sets whether the user space runs in the 32-bit compatibility mode.
+    pub fn set_compat_mode(&mut self, is_compat: bool) {
+        self.user_context.cs = if is_compat {
+            USER32_CS_VALUE
+        } else {
+            USER_CS_VALUE
+        };
+    }
+
+    /// Returns whether the current system call is a 32-bit system call issued by `int 0x80` or
+    /// `sysenter`.
+    ///
+    /// The result is meaningful only if the user space has returned due to
+    /// [`ReturnReason::UserSyscall`].
+    pub fn is_ia32_syscall(&self) -> bool {
+        self.user_context.trap_num == IA32_SYSCALL_TRAPNUM
+            || self.user_context.trap_num == SYSENTER_TRAPNUM
+    }
+
+    /// Returns whether the current system call is a 32-bit system call issued by `sysenter`.
+    ///
+    /// The `sysenter` instruction does not save the user instruction pointer, which is set to
+    /// zero. The user stack pointer is taken from `rbp`, as `__kernel_vsyscall` passes it there.
+    ///
+    /// The result is meaningful only if the user space has returned due to
+    /// [`ReturnReason::UserSyscall`].
+    pub fn is_ia32_sysenter(&self) -> bool {
+        self.user_context.trap_num == SYSENTER_TRAPNUM
+    }
 }
 
 impl UserContextApiInternal for UserContext {
@@ -312,8 +393,6 @@ impl UserContextApiInternal for UserContext {
         // set ID flag which means cpu support CPUID instruction
         self.user_context.general.rflags |= (RFlags::INTERRUPT_FLAG | RFlags::ID).bits() as usize;
 
-        const SYSCALL_TRAPNUM: usize = 0x100;
-
         // Return when it is syscall or cpu exception type is Fault or Trap.
         loop {
             crate::task::scheduler::might_preempt();
@@ -342,7 +421,7 @@ impl UserContextApiInternal for UserContext {
                         self.as_trap_frame()
                     );
                 }
-                None if self.user_context.trap_num == SYSCALL_TRAPNUM => {
+                None if self.user_context.trap_num == SYSCALL_TRAPNUM || self.is_ia32_syscall() => {
                     crate::arch::irq::enable_local();
                     return ReturnReason::UserSyscall;
                 }
@@ -382,7 +461,7 @@ impl UserContextApiInternal for UserContext {
             trap_num: self.user_context.trap_num,
             error_code: self.user_context.error_code,
             rip: self.user_context.general.rip,
-            cs: 0,
+            cs: self.user_context.cs,
             rflags: self.user_context.general.rflags,
             rsp: self.user_context.general.rsp,
             ss: 0,
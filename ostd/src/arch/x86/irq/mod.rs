@@ -30,6 +30,11 @@ use crate::arch::{cpu, kernel};
 pub(crate) const IRQ_NUM_MIN: u8 = 32;
 pub(crate) const IRQ_NUM_MAX: u8 = 255;
 
+/// The IRQ numbers that are not available for allocation.
+///
+/// The vector `0x80` is used by 32-bit user programs to issue system calls via `int 0x80`.
+pub(crate) const RESERVED_IRQ_NUMS: &[u8] = &[crate::arch::trap::IA32_SYSCALL_TRAPNUM as u8];
+
 /// An IRQ line with additional information that helps acknowledge the interrupt
 /// on hardware.
 ///
@@ -18,7 +18,11 @@ use x86_64::{
     },
 };
 
-use crate::cpu::local::{CpuLocal, StaticCpuLocal};
+use crate::{
+    cpu::local::{CpuLocal, StaticCpuLocal},
+    cpu_local_cell,
+    irq::DisabledLocalIrqGuard,
+};
 
 /// Initializes and loads the GDT and TSS.
 ///
@@ -47,15 +51,25 @@ pub(super) unsafe fn init_on_cpu() {
     // intended for switching to a new kernel CS.
     assert_eq!(CS::get_reg(), KERNEL_CS);
 
-    // Allocate a new GDT with 9 entries.
-    let gdt = Box::new([
-        0, KCODE64, KDATA, /* KCODE32 (not used) */ 0, /* UCODE32 (not used) */ 0, UDATA,
-        UCODE64, tss0, tss1,
+    // Allocate a new GDT with 15 entries.
+    //
+    // The layout follows Linux so that the selectors hard-coded in 32-bit programs (e.g., the
+    // selectors of the TLS entries) keep working.
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/asm/segment.h>
+    let gdt: Box<[u64; GDT_LEN]> = Box::new([
+        0, KCODE64, KDATA, /* KCODE32 (not used) */ 0, UCODE32, UDATA, UCODE64, tss0, tss1,
+        /* Not used */ 0, /* LDT (not used) */ 0, /* LDT (not used) */ 0,
+        /* TLS */ 0, /* TLS */ 0, /* TLS */ 0,
     ]);
-    let gdt = &*Box::leak(gdt);
+    let gdt = Box::into_raw(gdt);
+    LOCAL_GDT.store(gdt);
+    // SAFETY: The GDT is leaked, so it lives for `'static`. It will only be modified by
+    // `load_tls_entries` on the current CPU, which cannot happen before this method returns.
+    let gdt = unsafe { &*gdt };
     assert_eq!(gdt[KERNEL_CS.index() as usize], KCODE64);
     assert_eq!(gdt[KERNEL_SS.index() as usize], KDATA);
     assert_eq!(gdt[USER_CS.index() as usize], UCODE64);
+    assert_eq!(gdt[USER32_CS.index() as usize], UCODE32);
     assert_eq!(gdt[USER_SS.index() as usize], UDATA);
 
     // Load the new GDT.
@@ -66,7 +80,7 @@ pub(super) unsafe fn init_on_cpu() {
     // SAFETY: The GDT is valid to load because:
     //  - It lives for `'static`.
     //  - It contains correct entries at correct indexes: the kernel code/data segments, the user
-    //    code/data segments, and the TSS segment.
+    //    code/data segments, and the TSS segment. The TLS entries are empty.
     //  - Specifically, the TSS segment points to the CPU-local TSS of the current CPU.
     unsafe { lgdt(&gdtr) };
 
@@ -79,6 +93,7 @@ pub(super) unsafe fn init_on_cpu() {
 
     // Set up the selectors for the `syscall` and `sysret` instructions.
     let sysret = SegmentSelector::new(4, PrivilegeLevel::Ring3);
+    assert_eq!(gdt[sysret.index() as usize], UCODE32);
     assert_eq!(gdt[(sysret.index() + 1) as usize], UDATA);
     assert_eq!(gdt[(sysret.index() + 2) as usize], UCODE64);
     let syscall = SegmentSelector::new(1, PrivilegeLevel::Ring0);
@@ -88,6 +103,36 @@ pub(super) unsafe fn init_on_cpu() {
     unsafe { Star::write_raw(sysret.0, syscall.0) };
 }
 
+/// Loads the TLS entries of the GDT on the current CPU.
+///
+/// The TLS entries are used by 32-bit programs, which refer to them via segment selectors.
+pub(in crate::arch) fn load_tls_entries(
+    entries: &[u64; NR_TLS_ENTRIES],
+    _guard: &DisabledLocalIrqGuard,
+) {
+    let gdt = LOCAL_GDT.load();
+    assert!(!gdt.is_null());
+
+    // SAFETY: The GDT of the current CPU lives for `'static` and is only modified on the current
+    // CPU, with local IRQs disabled. Modifying the TLS entries is always safe because the kernel
+    // never uses them. The CPU caches the descriptors of the loaded segment selectors, so the
+    // changes will not affect them until the user program loads the selectors again.
+    let tls_entries = unsafe { &mut (*gdt)[GDT_ENTRY_TLS_MIN..GDT_ENTRY_TLS_MIN + NR_TLS_ENTRIES] };
+    tls_entries.copy_from_slice(entries);
+}
+
+const GDT_LEN: usize = 15;
+
+/// The index of the first TLS entry in the GDT.
+pub(in crate::arch) const GDT_ENTRY_TLS_MIN: usize = 12;
+/// The number of the TLS entries in the GDT.
+pub(in crate::arch) const NR_TLS_ENTRIES: usize = 3;
+
+cpu_local_cell! {
+    /// The GDT of the current CPU.
+    static LOCAL_GDT: *mut [u64; GDT_LEN] = core::ptr::null_mut();
+}
+
 // The linker script makes sure that the `.cpu_local_tss` section is at the beginning of the area
 // that stores CPU-local variables. This is important because `trap.S` and `syscall.S` will assume
 // this and treat the beginning of the CPU-local area as a TSS for loading and saving the kernel
@@ -121,11 +166,13 @@ pub(in crate::arch) const KCODE32: u64 = 0x00CF_9B00_0000_FFFF;
 // The Intel manual says: "It is the responsibility of OS software to ensure that the descriptors
 // (in GDT or LDT) referenced by those selector values correspond to the fixed values loaded into
 // the descriptor caches; the SYSRET instruction does not ensure this correspondence."
-const UCODE64: u64 = 0x00AF_FB00_0000_FFFF;
+const UCODE32: u64 = 0x00CF_FB00_0000_FFFF;
 const UDATA: u64 = 0x00CF_F300_0000_FFFF;
+const UCODE64: u64 = 0x00AF_FB00_0000_FFFF;
 
-const KERNEL_CS: SegmentSelector = SegmentSelector::new(1, PrivilegeLevel::Ring0);
+pub(super) const KERNEL_CS: SegmentSelector = SegmentSelector::new(1, PrivilegeLevel::Ring0);
 const KERNEL_SS: SegmentSelector = SegmentSelector::new(2, PrivilegeLevel::Ring0);
 
 pub(super) const USER_CS: SegmentSelector = SegmentSelector::new(6, PrivilegeLevel::Ring3);
+pub(super) const USER32_CS: SegmentSelector = SegmentSelector::new(4, PrivilegeLevel::Ring3);
 pub(super) const USER_SS: SegmentSelector = SegmentSelector::new(5, PrivilegeLevel::Ring3);
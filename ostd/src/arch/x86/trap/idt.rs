@@ -45,8 +45,8 @@ pub(super) fn init_on_cpu() {
             // corresponding exception or interrupt.
             let opt = unsafe { entry.set_handler_addr(handler) };
 
-            // Enable `int3` and `into` in the userspace.
-            if intr_no == 3 || intr_no == 4 {
+            // Enable `int3`, `into`, and `int 0x80` in the userspace.
+            if intr_no == 3 || intr_no == 4 || intr_no == super::IA32_SYSCALL_TRAPNUM {
                 opt.set_privilege_level(PrivilegeLevel::Ring3);
             }
         }
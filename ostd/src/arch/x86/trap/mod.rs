@@ -108,12 +108,13 @@ crate::const_assert!(size_of::<TrapFrame>().is_multiple_of(16));
 /// - Switch to a new, CPU-local [GDT].
 /// - Switch to a new, CPU-local [TSS].
 /// - Switch to a new, global [IDT].
-/// - Enable the [`syscall`] instruction.
+/// - Enable the [`syscall`] and [`sysenter`] instructions.
 ///
 /// [GDT]: https://wiki.osdev.org/GDT
 /// [IDT]: https://wiki.osdev.org/IDT
 /// [TSS]: https://wiki.osdev.org/Task_State_Segment
 /// [`syscall`]: https://www.felixcloutier.com/x86/syscall
+/// [`sysenter`]: https://www.felixcloutier.com/x86/sysenter
 ///
 /// # Safety
 ///
@@ -132,13 +133,34 @@ pub(crate) unsafe fn init_on_cpu() {
 
 /// Userspace context.
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) struct RawUserContext {
     pub(super) general: GeneralRegs,
     pub(super) trap_num: usize,
     pub(super) error_code: usize,
+    /// The code segment selector, which determines whether the user space runs in the 64-bit
+    /// mode or the 32-bit compatibility mode.
+    pub(super) cs: usize,
 }
 
+impl Default for RawUserContext {
+    fn default() -> Self {
+        Self {
+            general: GeneralRegs::default(),
+            trap_num: 0,
+            error_code: 0,
+            cs: USER_CS_VALUE,
+        }
+    }
+}
+
+/// The trap number of system calls issued by the `syscall` instruction.
+pub(super) const SYSCALL_TRAPNUM: usize = 0x100;
+/// The trap number of 32-bit system calls issued by the `int 0x80` instruction.
+pub(crate) const IA32_SYSCALL_TRAPNUM: usize = 0x80;
+/// The trap number of 32-bit system calls issued by the `sysenter` instruction.
+pub(super) const SYSENTER_TRAPNUM: usize = 0x101;
+
 /// Handle traps (only from kernel).
 // SAFETY: The name does not collide with other symbols.
 #[unsafe(no_mangle)]
@@ -155,6 +177,14 @@ unsafe extern "sysv64" fn trap_handler(f: &mut TrapFrame) {
         }
     }
 
+    // The user space enters the kernel with the TF flag set. Since the kernel GS base has not been
+    // swapped in, nothing can be done except clearing the flag. This makes the user space miss
+    // the single-step trap of the `sysenter` instruction.
+    if f.trap_num == 1 && syscall::is_sysenter_entry(f.rip) {
+        f.rflags &= !(x86_64::registers::rflags::RFlags::TRAP_FLAG.bits() as usize);
+        return;
+    }
+
     // The IRQ state before trapping. We need to ensure that the IRQ state
     // during exception handling is consistent with the state before the trap.
     let was_irq_enabled =
@@ -236,5 +266,7 @@ fn handle_user_page_fault(f: &mut TrapFrame, exception: &CpuException) {
 
 /// User-space code segment selector value.
 pub const USER_CS_VALUE: usize = gdt::USER_CS.0 as usize;
+/// User-space code segment selector value in the 32-bit compatibility mode.
+pub const USER32_CS_VALUE: usize = gdt::USER32_CS.0 as usize;
 /// User-space stack segment selector value.
 pub const USER_SS_VALUE: usize = gdt::USER_SS.0 as usize;
@@ -9,6 +9,8 @@
 //
 // We make the following new changes:
 // * Revise some comments.
+// * Reject the `syscall` instruction in the 32-bit compatibility mode.
+// * Enable the `sysenter` instruction in the 32-bit compatibility mode.
 //
 // These changes are released under the following license:
 //
@@ -16,12 +18,13 @@
 
 //! Configure fast syscall.
 
+use alloc::boxed::Box;
 use core::arch::global_asm;
 
 use x86_64::{
     VirtAddr,
     registers::{
-        model_specific::{Efer, EferFlags, LStar, SFMask},
+        model_specific::{Efer, EferFlags, LStar, Msr, SFMask},
         rflags::RFlags,
     },
 };
@@ -32,6 +35,8 @@ use crate::mm::PagingConstsTrait;
 global_asm!(
     include_str!("syscall.S"),
     USER_CS = const super::gdt::USER_CS.0,
+    USER32_CS = const super::gdt::USER32_CS.0,
+    SYSENTER_TRAPNUM = const super::SYSENTER_TRAPNUM,
     USER_SS = const super::gdt::USER_SS.0,
     ADDRESS_WIDTH = const crate::arch::mm::PagingConsts::ADDRESS_WIDTH,
 );
@@ -56,8 +61,16 @@ pub(super) unsafe fn init_on_cpu() {
     // `sysret` instructions is safe.
     unsafe {
         LStar::write(VirtAddr::new(syscall_entry as *const () as usize as u64));
+        Msr::new(IA32_CSTAR).write(syscall32_entry as *const () as usize as u64);
         SFMask::write(RFlags::from_bits(RFLAGS_MASK).unwrap());
 
+        // Enable the `sysenter` instruction. The stack is only used if a trap occurs before
+        // `sysenter_entry` switches to the kernel stack (see `is_sysenter_entry`).
+        let sysenter_stack = Box::leak(Box::new(SysenterStack([0; SYSENTER_STACK_SIZE])));
+        Msr::new(IA32_SYSENTER_CS).write(super::gdt::KERNEL_CS.0 as u64);
+        Msr::new(IA32_SYSENTER_ESP).write(sysenter_stack.0.as_ptr_range().end as u64);
+        Msr::new(IA32_SYSENTER_EIP).write(sysenter_entry as *const () as usize as u64);
+
         // Enable the `syscall` and `sysret` instructions.
         Efer::update(|efer| {
             efer.insert(EferFlags::SYSTEM_CALL_EXTENSIONS);
@@ -65,8 +78,33 @@ pub(super) unsafe fn init_on_cpu() {
     }
 }
 
+/// The MSR of the target address of the `syscall` instruction in the 32-bit compatibility mode.
+const IA32_CSTAR: u32 = 0xC000_0083;
+/// The MSR of the kernel code segment selector loaded by the `sysenter` instruction.
+const IA32_SYSENTER_CS: u32 = 0x174;
+/// The MSR of the stack pointer loaded by the `sysenter` instruction.
+const IA32_SYSENTER_ESP: u32 = 0x175;
+/// The MSR of the target address of the `sysenter` instruction.
+const IA32_SYSENTER_EIP: u32 = 0x176;
+
+const SYSENTER_STACK_SIZE: usize = 4096;
+
+/// The stack that is loaded by the `sysenter` instruction.
+#[repr(C, align(16))]
+struct SysenterStack([u8; SYSENTER_STACK_SIZE]);
+
+/// Returns whether `rip` is the target address of the `sysenter` instruction.
+///
+/// The `sysenter` instruction does not clear the TF flag. So if the user space sets the flag, a
+/// debug exception occurs at the target address, before the kernel GS base is swapped in.
+pub(super) fn is_sysenter_entry(rip: usize) -> bool {
+    rip == sysenter_entry as *const () as usize
+}
+
 unsafe extern "C" {
     unsafe fn syscall_entry();
+    unsafe fn syscall32_entry();
+    unsafe fn sysenter_entry();
     unsafe fn syscall_return(regs: &mut RawUserContext);
 }
 
@@ -78,8 +116,8 @@ impl RawUserContext {
     ///
     /// If the trap was triggered by `syscall` instruction, the `trap_num` will be set to `0x100`.
     ///
-    /// If `trap_num` is `0x100`, it will go user by `sysret` (`rcx` and `r11` are dropped),
-    /// otherwise it will use `iret`.
+    /// If `trap_num` is `0x100` and `cs` refers to the 64-bit user code segment, it will go user
+    /// by `sysret` (`rcx` and `r11` are dropped), otherwise it will use `iret`.
     pub(in crate::arch) fn run(&mut self) {
         let guard = crate::irq::disable_local();
 
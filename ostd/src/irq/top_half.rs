@@ -10,7 +10,7 @@ use spin::Once;
 use crate::{
     Error,
     arch::{
-        irq::{HwIrqLine, IRQ_NUM_MAX, IRQ_NUM_MIN, IrqRemapping},
+        irq::{HwIrqLine, IRQ_NUM_MAX, IRQ_NUM_MIN, IrqRemapping, RESERVED_IRQ_NUMS},
         trap::TrapFrame,
     },
     prelude::*,
@@ -138,7 +138,15 @@ static INNERS: [Inner; NUMBER_OF_IRQS] = [const { Inner::new() }; NUMBER_OF_IRQS
 static ALLOCATOR: Once<SpinLock<IdAlloc>> = Once::new();
 
 fn get_or_init_allocator() -> &'static SpinLock<IdAlloc> {
-    ALLOCATOR.call_once(|| SpinLock::new(IdAlloc::with_capacity(NUMBER_OF_IRQS)))
+    ALLOCATOR.call_once(|| {
+        let mut allocator = IdAlloc::with_capacity(NUMBER_OF_IRQS);
+        for &irq_num in RESERVED_IRQ_NUMS {
+            allocator
+                .alloc_specific((irq_num - IRQ_NUM_MIN) as usize)
+                .unwrap();
+        }
+        SpinLock::new(allocator)
+    })
 }
 
 /// A handle for an allocated IRQ line.
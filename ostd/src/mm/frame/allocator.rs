@@ -19,6 +19,7 @@ use crate::{
 /// Options for allocating physical memory frames.
 pub struct FrameAllocOptions {
     zeroed: bool,
+    align: usize,
 }
 
 impl Default for FrameAllocOptions {
@@ -30,7 +31,10 @@ impl Default for FrameAllocOptions {
 impl FrameAllocOptions {
     /// Creates new options for allocating the specified number of frames.
     pub fn new() -> Self {
-        Self { zeroed: true }
+        Self {
+            zeroed: true,
+            align: PAGE_SIZE,
+        }
     }
 
     /// Sets whether the allocated frames should be initialized with zeros.
@@ -45,6 +49,18 @@ impl FrameAllocOptions {
         self
     }
 
+    /// Sets the alignment of the allocated segments in bytes.
+    ///
+    /// The alignment must be a power of two. An alignment smaller than
+    /// [`PAGE_SIZE`] is treated as [`PAGE_SIZE`], which is the default.
+    ///
+    /// For example, a segment of huge page size can be aligned to its size, so
+    /// that it can be mapped as a huge page.
+    pub fn align(&mut self, align: usize) -> &mut Self {
+        self.align = align.max(PAGE_SIZE);
+        self
+    }
+
     /// Allocates a single untyped frame without metadata.
     pub fn alloc_frame(&self) -> Result<Frame<()>> {
         self.alloc_frame_with(())
@@ -75,7 +91,8 @@ impl FrameAllocOptions {
     /// Allocates a contiguous range of frames with additional metadata.
     ///
     /// The returned [`Segment`] contains at least one frame. The method returns
-    /// an error if the number of frames is zero.
+    /// an error if the number of frames is zero, or if the alignment is not a
+    /// power of two.
     pub fn alloc_segment_with<M: AnyFrameMeta, F>(
         &self,
         nframes: usize,
@@ -87,7 +104,8 @@ impl FrameAllocOptions {
         if nframes == 0 {
             return Err(Error::InvalidArgs);
         }
-        let layout = Layout::from_size_align(nframes * PAGE_SIZE, PAGE_SIZE).unwrap();
+        let layout = Layout::from_size_align(nframes * PAGE_SIZE, self.align)
+            .map_err(|_| Error::InvalidArgs)?;
         let segment = get_global_frame_allocator()
             .alloc(layout)
             .map(|start| {
@@ -2,7 +2,11 @@
 
 //! A contiguous range of frames.
 
-use core::{fmt::Debug, mem::ManuallyDrop, ops::Range};
+use core::{
+    fmt::Debug,
+    mem::ManuallyDrop,
+    ops::{Deref, Range},
+};
 
 use super::{
     Frame, inc_frame_ref_count,
@@ -106,22 +110,6 @@ impl<M: AnyFrameMeta> Segment<M> {
         }
         Ok(segment)
     }
-
-    /// Restores the [`Segment`] from the raw physical address range.
-    ///
-    /// # Safety
-    ///
-    /// The range must be a forgotten [`Segment`] that matches the type `M`.
-    /// It could be manually forgotten by [`core::mem::forget`],
-    /// [`ManuallyDrop`], or [`Self::into_raw`].
-    pub(crate) unsafe fn from_raw(range: Range<Paddr>) -> Self {
-        debug_assert_eq!(range.start % PAGE_SIZE, 0);
-        debug_assert_eq!(range.end % PAGE_SIZE, 0);
-        Self {
-            range,
-            _marker: core::marker::PhantomData,
-        }
-    }
 }
 
 impl<M: AnyFrameMeta + ?Sized> Split for Segment<M> {
@@ -180,6 +168,64 @@ impl<M: AnyFrameMeta + ?Sized> Segment<M> {
         let _ = ManuallyDrop::new(self);
         range
     }
+
+    /// Restores the [`Segment`] from the raw physical address range.
+    ///
+    /// # Safety
+    ///
+    /// The range must be a forgotten [`Segment`] that matches the type `M`.
+    /// It could be manually forgotten by [`core::mem::forget`],
+    /// [`ManuallyDrop`], or [`Self::into_raw`].
+    pub(crate) unsafe fn from_raw(range: Range<Paddr>) -> Self {
+        debug_assert_eq!(range.start % PAGE_SIZE, 0);
+        debug_assert_eq!(range.end % PAGE_SIZE, 0);
+        Self {
+            range,
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+/// A struct that can work as `&'a Segment<M>`.
+pub struct SegmentRef<'a, M: AnyFrameMeta + ?Sized> {
+    inner: ManuallyDrop<Segment<M>>,
+    _marker: core::marker::PhantomData<&'a Segment<M>>,
+}
+
+impl<M: AnyFrameMeta + ?Sized> SegmentRef<'_, M> {
+    /// Borrows the frames in the physical address range as a [`SegmentRef`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that:
+    ///  - the frames outlive the created reference, so that the reference can
+    ///    be seen as borrowed from a segment of the frames.
+    ///  - the type of the [`SegmentRef`] (`M`) matches the borrowed frames.
+    pub(in crate::mm) unsafe fn borrow_range(range: Range<Paddr>) -> Self {
+        Self {
+            // SAFETY: The caller ensures the safety.
+            inner: ManuallyDrop::new(unsafe { Segment::from_raw(range) }),
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<M: AnyFrameMeta + ?Sized> Deref for SegmentRef<'_, M> {
+    type Target = Segment<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<M: AnyFrameMeta + ?Sized> Debug for SegmentRef<'_, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "SegmentRef({:#x}..{:#x})",
+            self.inner.range.start, self.inner.range.end
+        )
+    }
 }
 
 impl Segment<dyn AnyFrameMeta> {
@@ -9,7 +9,7 @@ use align_ext::AlignExt;
 use super::Cursor;
 use crate::{
     mm::{
-        HasPaddr, Vaddr, nr_subpage_per_huge, paddr_to_vaddr,
+        HasPaddr, Vaddr, nr_base_per_page, nr_subpage_per_huge, paddr_to_vaddr,
         page_table::{
             PageTable, PageTableConfig, PageTableGuard, PageTableNodeRef, PagingConstsTrait,
             PagingLevel, PteScalar, PteStateRef, PteTrait, load_pte, page_size, pte_index,
@@ -98,7 +98,12 @@ fn try_traverse_and_lock_subtree_root<'rcu, C: PageTableConfig>(
         let start_idx = pte_index::<C>(va.start, cur_level);
         let level_too_high = {
             let end_idx = pte_index::<C>(va.end - 1, cur_level);
-            cur_level > 1 && start_idx == end_idx
+            // A range that covers exactly one entry may be mapped by a huge
+            // page at that entry. So the node holding the entry is locked
+            // instead of a newly allocated child node.
+            let may_map_huge = cur_level <= C::HIGHEST_TRANSLATION_LEVEL
+                && va.end - va.start == page_size::<C>(cur_level);
+            cur_level > 1 && start_idx == end_idx && !may_map_huge
         };
         if !level_too_high {
             break;
@@ -265,6 +270,7 @@ pub(super) unsafe fn dfs_mark_stray_and_unlock<C: PageTableConfig>(
     }
 
     let mut num_frames = 0;
+    let level = sub_tree.level();
 
     for i in (0..nr_subpage_per_huge::<C>()).rev() {
         let child = sub_tree.entry(i);
@@ -276,7 +282,8 @@ pub(super) unsafe fn dfs_mark_stray_and_unlock<C: PageTableConfig>(
                 // guards are forgotten.
                 num_frames += unsafe { dfs_mark_stray_and_unlock(rcu_guard, locked_pt) };
             }
-            PteStateRef::Absent | PteStateRef::Mapped(_) => {}
+            PteStateRef::Mapped(_) => num_frames += nr_base_per_page::<C>(level),
+            PteStateRef::Absent => {}
         }
     }
 
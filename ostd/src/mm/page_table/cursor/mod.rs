@@ -547,6 +547,36 @@ impl<'rcu, C: PageTableConfig> CursorMut<'rcu, C> {
         Some(protected_va)
     }
 
+    /// Splits the huge page mapped at the current virtual address, if any.
+    ///
+    /// The huge page is split into pages of the base page size, which map
+    /// the same physical addresses with the same properties. The cursor then
+    /// points to the base page at the current virtual address.
+    ///
+    /// Returns whether a huge page has been split.
+    pub fn split_huge(&mut self) -> bool {
+        let rcu_guard = self.0.rcu_guard;
+
+        let mut is_split = false;
+        while self.0.level > 1 {
+            let mut cur_entry = self.0.cur_entry();
+            let child_guard = match cur_entry.to_ref() {
+                PteStateRef::PageTable(pt) => {
+                    // SAFETY: The `pt` must be locked and no other guards exist.
+                    unsafe { pt.make_guard_unchecked(rcu_guard) }
+                }
+                PteStateRef::Absent => break,
+                PteStateRef::Mapped(_) => {
+                    is_split = true;
+                    cur_entry.split_if_mapped_huge(rcu_guard).unwrap()
+                }
+            };
+            self.0.push_level(child_guard);
+        }
+
+        is_split
+    }
+
     fn replace_cur_entry(&mut self, new_child: PteState<C>) -> Option<PageTableFrag<C>> {
         let rcu_guard = self.0.rcu_guard;
 
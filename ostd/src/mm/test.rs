@@ -6,11 +6,11 @@ use crate::{
     Error,
     io::IoMem,
     mm::{
-        CachePolicy, FallibleVmRead, FallibleVmWrite, FrameAllocOptions, PageFlags, PageProperty,
-        UFrame, VmSpace,
+        CachePolicy, FallibleVmRead, FallibleVmWrite, FrameAllocOptions, PAGE_SIZE, PageFlags,
+        PageProperty, UFrame, USegment, VmSpace,
         io::{VmIo, VmIoFill, VmReader, VmWriter, util::HasVmReaderWriter},
         tlb::TlbFlushOp,
-        vm_space::{VmQueriedItem, get_activated_vm_space},
+        vm_space::{HUGE_PAGE_SIZE, TOKEN_BITS, VmQueriedItem, get_activated_vm_space},
     },
     prelude::*,
     task::disable_preempt,
@@ -649,6 +649,143 @@ mod vmspace {
         ));
     }
 
+    /// Stores, protects, and clears a token using `CursorMut`.
+    #[ktest]
+    fn vmspace_map_token() {
+        let vmspace = VmSpace::default();
+        let range = 0x1000..0x2000;
+        let token = (1 << TOKEN_BITS) - 1;
+        let preempt_guard = disable_preempt();
+
+        {
+            let mut cursor_mut = vmspace
+                .cursor_mut(&preempt_guard, &range)
+                .expect("failed to create the mutable cursor");
+            cursor_mut.map_token(token);
+        }
+
+        {
+            let mut cursor_mut = vmspace
+                .cursor_mut(&preempt_guard, &range)
+                .expect("failed to create the mutable cursor");
+            assert!(matches!(
+                cursor_mut.query().unwrap(),
+                (r, Some(VmQueriedItem::Token(t))) if r == range && t == token
+            ));
+
+            // Protecting a token should not make it accessible.
+            let protected = cursor_mut.protect_next(range.len(), |flags, _cache| {
+                *flags |= PageFlags::RW;
+            });
+            assert_eq!(protected, Some(range.clone()));
+            cursor_mut.jump(range.start).unwrap();
+            assert!(matches!(
+                cursor_mut.query().unwrap(),
+                (_, Some(VmQueriedItem::Token(t))) if t == token
+            ));
+
+            // Clearing a token should not be counted as unmapping a page.
+            assert_eq!(cursor_mut.unmap(range.len()), 0);
+        }
+
+        let mut cursor = vmspace
+            .cursor(&preempt_guard, &range)
+            .expect("failed to create the cursor");
+        assert!(matches!(
+            cursor.query().unwrap(),
+            (r, None) if r == range
+        ));
+    }
+
+    /// Maps a huge page and unmaps it piece by piece using `CursorMut`.
+    #[ktest]
+    fn vmspace_map_huge_unmap_partial() {
+        let Some(huge_page_size) = HUGE_PAGE_SIZE else {
+            return;
+        };
+        let vmspace = VmSpace::default();
+        let range = huge_page_size..huge_page_size * 2;
+        let segment: USegment = FrameAllocOptions::new()
+            .alloc_segment(huge_page_size / PAGE_SIZE)
+            .unwrap()
+            .into();
+        let prop = PageProperty::new_user(PageFlags::RW, CachePolicy::Writeback);
+        let preempt_guard = disable_preempt();
+
+        let mut cursor_mut = vmspace
+            .cursor_mut(&preempt_guard, &range)
+            .expect("failed to create the mutable cursor");
+        assert!(matches!(
+            cursor_mut.query().unwrap(),
+            (r, None) if r == range
+        ));
+        cursor_mut.map_huge(segment.clone(), prop);
+
+        // Any address in the huge page is mapped by the whole huge page.
+        cursor_mut.jump(range.start + PAGE_SIZE).unwrap();
+        assert!(matches!(
+            cursor_mut.query().unwrap(),
+            (r, Some(VmQueriedItem::MappedHugeRam { segment: s, prop: p }))
+                if r == range && s.paddr() == segment.paddr() && p == prop
+        ));
+
+        // Unmapping a base page splits the huge page.
+        assert_eq!(cursor_mut.unmap(PAGE_SIZE), 1);
+        cursor_mut.jump(range.start).unwrap();
+        assert_matches_mapped!(
+            cursor_mut,
+            range.start..range.start + PAGE_SIZE,
+            segment,
+            prop
+        );
+        cursor_mut.jump(range.start + PAGE_SIZE).unwrap();
+        assert!(matches!(
+            cursor_mut.query().unwrap(),
+            (r, None) if r == (range.start + PAGE_SIZE..range.start + PAGE_SIZE * 2)
+        ));
+
+        cursor_mut.jump(range.start).unwrap();
+        assert_eq!(
+            cursor_mut.unmap(huge_page_size),
+            huge_page_size / PAGE_SIZE - 1
+        );
+    }
+
+    /// Splits a huge page without changing the mappings using `CursorMut`.
+    #[ktest]
+    fn vmspace_split_huge() {
+        let Some(huge_page_size) = HUGE_PAGE_SIZE else {
+            return;
+        };
+        let vmspace = VmSpace::default();
+        let range = huge_page_size..huge_page_size * 2;
+        let segment: USegment = FrameAllocOptions::new()
+            .alloc_segment(huge_page_size / PAGE_SIZE)
+            .unwrap()
+            .into();
+        let prop = PageProperty::new_user(PageFlags::R, CachePolicy::Writeback);
+        let preempt_guard = disable_preempt();
+
+        let mut cursor_mut = vmspace
+            .cursor_mut(&preempt_guard, &range)
+            .expect("failed to create the mutable cursor");
+        cursor_mut.map_huge(segment.clone(), prop);
+
+        let last_page = range.end - PAGE_SIZE..range.end;
+        cursor_mut.jump(last_page.start).unwrap();
+        assert!(cursor_mut.split_huge());
+        assert!(!cursor_mut.split_huge());
+        assert_matches_mapped!(
+            cursor_mut,
+            last_page.clone(),
+            segment.slice(&(huge_page_size - PAGE_SIZE..huge_page_size)),
+            prop
+        );
+
+        cursor_mut.jump(range.start).unwrap();
+        assert_eq!(cursor_mut.unmap(huge_page_size), huge_page_size / PAGE_SIZE);
+    }
+
     /// Activates and deactivates the `VmSpace` in single-CPU scenarios.
     #[ktest]
     fn vmspace_activate() {
@@ -10,7 +10,7 @@
 
 use core::{ops::Range, sync::atomic::Ordering};
 
-use super::{AnyUFrameMeta, PagingLevel, page_table::PageTableConfig};
+use super::{AnyUFrameMeta, PagingLevel, USegment, page_size, page_table::PageTableConfig};
 use crate::{
     Error,
     arch::mm::{PageTableEntry, PagingConsts, current_page_table_paddr},
@@ -18,9 +18,9 @@ use crate::{
     cpu_local_cell,
     io::IoMem,
     mm::{
-        Frame, MAX_USERSPACE_VADDR, PAGE_SIZE, PageProperty, PrivilegedPageFlags, UFrame, VmReader,
-        VmWriter,
-        frame::FrameRef,
+        Frame, MAX_USERSPACE_VADDR, PAGE_SIZE, PageProperty, PagingConstsTrait,
+        PrivilegedPageFlags, UFrame, VmReader, VmWriter,
+        frame::{FrameRef, segment::SegmentRef},
         io::Fallible,
         kspace::KERNEL_PAGE_TABLE,
         page_prop::{CachePolicy, PageFlags},
@@ -376,6 +376,67 @@ impl<'a> CursorMut<'a> {
         unsafe { self.pt_cursor.map(item) };
     }
 
+    /// Maps contiguous frames as a huge page into the current slot.
+    ///
+    /// The size of the huge page is [`HUGE_PAGE_SIZE`]. The huge page can be
+    /// queried as a [`VmQueriedItem::MappedHugeRam`]. Unmapping or protecting
+    /// a part of it splits it into base pages, which can also be done with
+    /// [`Self::split_huge`].
+    ///
+    /// This method will bring the cursor to the next slot after the modification.
+    ///
+    /// # Panics
+    ///
+    /// Panics if
+    ///  - huge pages are not supported, i.e., [`HUGE_PAGE_SIZE`] is `None`;
+    ///  - the size of the segment is not the huge page size;
+    ///  - the physical or virtual address is not aligned to the huge page size;
+    ///  - the current slot is already mapped, or any base page in it has
+    ///    been mapped since the creation of the cursor.
+    pub fn map_huge(&mut self, segment: USegment, prop: PageProperty) {
+        let huge_page_size = HUGE_PAGE_SIZE.expect("huge pages are not supported");
+        assert_eq!(segment.size(), huge_page_size);
+        assert_eq!(segment.paddr() % huge_page_size, 0);
+
+        let item = VmItem::new_tracked_huge(segment, prop);
+
+        // SAFETY: It is safe to map untyped memory into the userspace.
+        unsafe { self.pt_cursor.map(item) };
+    }
+
+    /// Splits the huge page mapped at the current slot into base pages.
+    ///
+    /// The base pages map the same frames with the same properties, so no TLB
+    /// flush is needed. The cursor then points to the base page at the current
+    /// virtual address.
+    ///
+    /// Returns whether a huge page has been split.
+    pub fn split_huge(&mut self) -> bool {
+        self.pt_cursor.split_huge()
+    }
+
+    /// Stores a token into the current slot.
+    ///
+    /// The slot stays inaccessible from the user space, so accessing it
+    /// causes a page fault. The token can be retrieved later with
+    /// [`Self::query`], which returns a [`VmQueriedItem::Token`]. This can be
+    /// used to record where the content of a page has been moved, e.g., a
+    /// location in a swap area.
+    ///
+    /// This method will bring the cursor to the next slot after the modification.
+    ///
+    /// # Panics
+    ///
+    /// Panics if
+    ///  - the token does not fit in [`TOKEN_BITS`] bits;
+    ///  - the current virtual address is already mapped.
+    pub fn map_token(&mut self, token: usize) {
+        assert!(token < (1 << TOKEN_BITS), "the token is too large");
+
+        // SAFETY: A token maps nothing, so it is safe to store it into the userspace.
+        unsafe { self.pt_cursor.map(VmItem::new_token(token)) };
+    }
+
     /// Maps a range of [`IoMem`] into the current slot.
     ///
     /// The memory region to be mapped is the [`IoMem`] range starting at
@@ -453,6 +514,11 @@ impl<'a> CursorMut<'a> {
     /// Already-absent mappings encountered by the cursor will be skipped. It
     /// is valid to unmap a range that is not mapped.
     ///
+    /// Tokens (see [`Self::map_token`]) are cleared as well, but they are not
+    /// counted as unmapped pages. Note that the tokens are cleared without
+    /// being reported, so a caller that needs to release the resources
+    /// referred to by the tokens should find and clear the tokens first.
+    ///
     /// It must issue and dispatch a TLB flush after the operation. Otherwise,
     /// the memory safety will be compromised. Please call this function less
     /// to avoid the overhead of TLB flush. Using a large `len` is wiser than
@@ -494,6 +560,20 @@ impl<'a> CursorMut<'a> {
                             self.flusher
                                 .issue_tlb_flush_with(TlbFlushOp::for_single(va), rcu_frame);
                         }
+                        VmItem {
+                            mapped_item: MappedItem::TrackedSegment(old_segment),
+                            ..
+                        } => {
+                            num_unmapped += old_segment.size() / PAGE_SIZE;
+
+                            let flush_op = TlbFlushOp::for_range(va..va + old_segment.size());
+                            for old_frame in old_segment {
+                                let rcu_frame = Frame::rcu_from_unsized(RcuDrop::new(old_frame));
+                                self.flusher
+                                    .issue_tlb_flush_with(flush_op.clone(), rcu_frame);
+                            }
+                            panic_guard.forget();
+                        }
                         VmItem {
                             mapped_item: MappedItem::UntrackedIoMem { .. },
                             ..
@@ -507,6 +587,13 @@ impl<'a> CursorMut<'a> {
                             // handled here might be one segment of it.
                             self.flusher.issue_tlb_flush(TlbFlushOp::for_single(va));
                         }
+                        VmItem {
+                            mapped_item: MappedItem::Token(_),
+                            ..
+                        } => {
+                            // Tokens are never cached in the TLB.
+                            panic_guard.forget();
+                        }
                     }
                 }
                 PageTableFrag::StrayPageTable {
@@ -547,6 +634,9 @@ impl<'a> CursorMut<'a> {
     /// make the decision yourself on when and how to flush the TLB using
     /// [`Self::flusher`].
     ///
+    /// Tokens (see [`Self::map_token`]) are left untouched, but the ranges of
+    /// them are still yielded.
+    ///
     /// # Panics
     ///
     /// Panics if the length is longer than the remaining range of the cursor.
@@ -556,9 +646,12 @@ impl<'a> CursorMut<'a> {
         mut op: impl FnMut(&mut PageFlags, &mut CachePolicy),
     ) -> Option<Range<Vaddr>> {
         // SAFETY: It is safe to set `PageFlags` and `CachePolicy` of memory
-        // in the userspace.
+        // in the userspace. Tokens are skipped, so they never become present.
         unsafe {
             self.pt_cursor.protect_next(len, &mut |prop| {
+                if is_token_prop(prop) {
+                    return;
+                }
                 op(&mut prop.flags, &mut prop.cache);
             })
         }
@@ -590,6 +683,16 @@ pub enum VmQueriedItem<'a> {
         /// The property of the slot.
         prop: PageProperty,
     },
+    /// The current slot is mapped with a huge page, the frames within are
+    /// allocated from the physical memory.
+    ///
+    /// See [`CursorMut::map_huge`] for details.
+    MappedHugeRam {
+        /// The mapped frames.
+        segment: SegmentRef<'a, dyn AnyUFrameMeta>,
+        /// The property of the slot.
+        prop: PageProperty,
+    },
     /// The current slot is mapped, the frame within is allocated from the
     /// MMIO memory.
     MappedIoMem {
@@ -598,18 +701,63 @@ pub enum VmQueriedItem<'a> {
         /// The property of the slot.
         prop: PageProperty,
     },
+    /// The current slot stores a token, which is inaccessible from the user
+    /// space.
+    ///
+    /// See [`CursorMut::map_token`] for details.
+    Token(usize),
 }
 
 impl VmQueriedItem<'_> {
     /// Returns the page property of the mapped item.
+    ///
+    /// A token has no permissions and no access status.
     pub fn prop(&self) -> &PageProperty {
         match self {
             Self::MappedRam { prop, .. } => prop,
+            Self::MappedHugeRam { prop, .. } => prop,
             Self::MappedIoMem { prop, .. } => prop,
+            Self::Token(_) => &TOKEN_PROP,
         }
     }
 }
 
+/// The size of the huge pages that can be mapped by [`CursorMut::map_huge`].
+///
+/// It is `None` if the architecture does not support huge pages.
+pub const HUGE_PAGE_SIZE: Option<usize> =
+    if PagingConsts::HIGHEST_TRANSLATION_LEVEL >= HUGE_PAGE_LEVEL {
+        Some(page_size::<PagingConsts>(HUGE_PAGE_LEVEL))
+    } else {
+        None
+    };
+
+/// The paging level of the huge pages that can be mapped by
+/// [`CursorMut::map_huge`].
+const HUGE_PAGE_LEVEL: PagingLevel = 2;
+
+/// The number of bits in a token that can be stored in a [`VmSpace`].
+///
+/// Tokens are stored in the physical address fields of non-present page table
+/// entries, so the number is limited by the narrowest physical address field
+/// among the supported architectures.
+pub const TOKEN_BITS: usize = 36;
+
+/// The page property of tokens.
+///
+/// No user mappings have `AVAIL1` set and `USER` cleared, so tokens can be
+/// distinguished from I/O memory and tracked frames.
+const TOKEN_PROP: PageProperty = PageProperty {
+    flags: PageFlags::empty(),
+    cache: CachePolicy::Writeback,
+    priv_flags: PrivilegedPageFlags::AVAIL1,
+};
+
+fn is_token_prop(prop: &PageProperty) -> bool {
+    prop.priv_flags.contains(PrivilegedPageFlags::AVAIL1)
+        && !prop.priv_flags.contains(PrivilegedPageFlags::USER)
+}
+
 /// Internal representation of a VM item.
 ///
 /// This is kept private to ensure memory safety. The public interface
@@ -630,13 +778,17 @@ pub(crate) struct VmItemRef<'a> {
 #[derive(Clone, Debug, PartialEq)]
 enum MappedItem {
     TrackedFrame(UFrame),
+    TrackedSegment(USegment),
     UntrackedIoMem { paddr: Paddr, level: PagingLevel },
+    Token(usize),
 }
 
 #[derive(Debug)]
 enum MappedItemRef<'a> {
     TrackedFrame(FrameRef<'a, dyn AnyUFrameMeta>),
+    TrackedSegment(SegmentRef<'a, dyn AnyUFrameMeta>),
     UntrackedIoMem { paddr: Paddr, level: PagingLevel },
+    Token(usize),
 }
 
 impl VmItem {
@@ -648,6 +800,14 @@ impl VmItem {
         }
     }
 
+    /// Creates a new `VmItem` that maps tracked frames as a huge page.
+    fn new_tracked_huge(segment: USegment, prop: PageProperty) -> Self {
+        Self {
+            prop,
+            mapped_item: MappedItem::TrackedSegment(segment),
+        }
+    }
+
     /// Creates a new `VmItem` that maps an untracked I/O memory.
     fn new_untracked_io(paddr: Paddr, prop: PageProperty) -> Self {
         Self {
@@ -655,6 +815,14 @@ impl VmItem {
             mapped_item: MappedItem::UntrackedIoMem { paddr, level: 1 },
         }
     }
+
+    /// Creates a new `VmItem` that stores a token.
+    fn new_token(token: usize) -> Self {
+        Self {
+            prop: TOKEN_PROP,
+            mapped_item: MappedItem::Token(token),
+        }
+    }
 }
 
 impl<'a> From<VmItemRef<'a>> for VmQueriedItem<'a> {
@@ -664,6 +832,10 @@ impl<'a> From<VmItemRef<'a>> for VmQueriedItem<'a> {
                 frame,
                 prop: item.prop,
             },
+            MappedItemRef::TrackedSegment(segment) => VmQueriedItem::MappedHugeRam {
+                segment,
+                prop: item.prop,
+            },
             MappedItemRef::UntrackedIoMem { paddr, level } => {
                 debug_assert_eq!(level, 1);
                 VmQueriedItem::MappedIoMem {
@@ -671,6 +843,7 @@ impl<'a> From<VmItemRef<'a>> for VmQueriedItem<'a> {
                     prop: item.prop,
                 }
             }
+            MappedItemRef::Token(token) => VmQueriedItem::Token(token),
         }
     }
 }
@@ -699,19 +872,39 @@ unsafe impl PageTableConfig for UserPtConfig {
                 let paddr = frame.paddr();
                 (paddr, level, prop)
             }
+            MappedItem::TrackedSegment(segment) => {
+                let mut prop = item.prop;
+                prop.priv_flags -= PrivilegedPageFlags::AVAIL1; // Clear AVAIL1 for tracked frames
+                debug_assert_eq!(segment.size(), page_size::<PagingConsts>(HUGE_PAGE_LEVEL));
+                (segment.paddr(), HUGE_PAGE_LEVEL, prop)
+            }
             MappedItem::UntrackedIoMem { paddr, level } => {
                 let mut prop = item.prop;
                 prop.priv_flags |= PrivilegedPageFlags::AVAIL1; // Set AVAIL1 for I/O memory
                 (*paddr, *level, prop)
             }
+            MappedItem::Token(token) => {
+                // Encode the token in the physical address field.
+                (*token * PAGE_SIZE, 1, TOKEN_PROP)
+            }
         }
     }
 
     unsafe fn item_from_raw(paddr: Paddr, level: PagingLevel, prop: PageProperty) -> Self::Item {
-        debug_assert_eq!(level, 1);
-        if prop.priv_flags.contains(PrivilegedPageFlags::AVAIL1) {
+        if is_token_prop(&prop) {
+            debug_assert_eq!(level, 1);
+            VmItem::new_token(paddr / PAGE_SIZE)
+        } else if prop.priv_flags.contains(PrivilegedPageFlags::AVAIL1) {
             // `AVAIL1` is set, this is I/O memory.
+            debug_assert_eq!(level, 1);
             VmItem::new_untracked_io(paddr, prop)
+        } else if level > 1 {
+            // `AVAIL1` is clear, this is tracked memory mapped as a huge page.
+            debug_assert_eq!(level, HUGE_PAGE_LEVEL);
+            let range = paddr..paddr + page_size::<PagingConsts>(level);
+            // SAFETY: The caller ensures safety.
+            let segment = unsafe { USegment::from_raw(range) };
+            VmItem::new_tracked_huge(segment, prop)
         } else {
             // `AVAIL1` is clear, this is tracked memory.
             // SAFETY: The caller ensures safety.
@@ -725,13 +918,30 @@ unsafe impl PageTableConfig for UserPtConfig {
         level: PagingLevel,
         prop: PageProperty,
     ) -> Self::ItemRef<'a> {
-        debug_assert_eq!(level, 1);
-        if prop.priv_flags.contains(PrivilegedPageFlags::AVAIL1) {
+        if is_token_prop(&prop) {
+            debug_assert_eq!(level, 1);
+            VmItemRef {
+                prop,
+                mapped_item: MappedItemRef::Token(paddr / PAGE_SIZE),
+            }
+        } else if prop.priv_flags.contains(PrivilegedPageFlags::AVAIL1) {
             // `AVAIL1` is set, this is I/O memory.
+            debug_assert_eq!(level, 1);
             VmItemRef {
                 prop,
                 mapped_item: MappedItemRef::UntrackedIoMem { paddr, level },
             }
+        } else if level > 1 {
+            // `AVAIL1` is clear, this is tracked memory mapped as a huge page.
+            debug_assert_eq!(level, HUGE_PAGE_LEVEL);
+            let range = paddr..paddr + page_size::<PagingConsts>(level);
+            // SAFETY: The caller ensures that the frames outlive `'a` and that
+            // the type matches the frames.
+            let segment_ref = unsafe { SegmentRef::<dyn AnyUFrameMeta>::borrow_range(range) };
+            VmItemRef {
+                prop,
+                mapped_item: MappedItemRef::TrackedSegment(segment_ref),
+            }
         } else {
             // `AVAIL1` is clear, this is tracked memory.
             // SAFETY: The caller ensures that the frame outlives `'a` and that
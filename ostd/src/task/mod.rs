@@ -114,6 +114,16 @@ impl Task {
         scheduler::yield_now()
     }
 
+    /// Migrates the current task to another CPU.
+    ///
+    /// The task is switched out and enqueued again, so the scheduler selects a
+    /// CPU for it as if it were woken up. This method will return once the
+    /// current task is scheduled again.
+    #[track_caller]
+    pub fn migrate_current() {
+        scheduler::migrate_current()
+    }
+
     /// Kicks the task scheduler to run the task.
     ///
     /// BUG: This method highly depends on the current scheduling policy.
@@ -17,6 +17,9 @@ cpu_local_cell! {
     /// It is used for delayed resource release since it would be the current
     /// task's job to recycle the previous resources.
     static PREVIOUS_TASK_PTR: *const Task = core::ptr::null();
+    /// Whether the previous task should be enqueued again after switching to
+    /// the current task.
+    static SHOULD_REQUEUE_PREVIOUS_TASK: bool = false;
 }
 
 /// Returns a pointer to the current task running on the processor.
@@ -26,6 +29,15 @@ pub(super) fn current_task() -> Option<NonNull<Task>> {
     NonNull::new(CURRENT_TASK_PTR.load().cast_mut())
 }
 
+/// Enqueues the current task again once the next call to [`switch_to_task`]
+/// switches it out.
+///
+/// A running task cannot be enqueued, so this is how the current task is
+/// migrated to another CPU. The scheduler then selects the CPU for it.
+pub(super) fn requeue_current_on_switch() {
+    SHOULD_REQUEUE_PREVIOUS_TASK.store(true);
+}
+
 /// Calls this function to switch to other task
 ///
 /// If current task is none, then it will use the default task context and it
@@ -134,6 +146,8 @@ pub(super) unsafe fn after_switching_to() {
     } else {
         None
     };
+    let should_requeue_prev = SHOULD_REQUEUE_PREVIOUS_TASK.load();
+    SHOULD_REQUEUE_PREVIOUS_TASK.store(false);
 
     if let Some(handler) = POST_SCHEDULE_HANDLER.get() {
         handler();
@@ -142,6 +156,10 @@ pub(super) unsafe fn after_switching_to() {
     // See `switch_to_task`, where we forgot an IRQ guard.
     crate::arch::irq::enable_local();
 
+    if should_requeue_prev && let Some(prev) = prev.as_ref() {
+        super::scheduler::requeue_migrated(prev.clone());
+    }
+
     // It was forgotten using `Arc::into_raw` at `switch_to_task`.
     // We drop it after enabling the IRQ in case dropping user-provided
     // resources would violate the atomic mode.
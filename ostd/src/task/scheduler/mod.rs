@@ -407,6 +407,8 @@ pub enum EnqueueFlags {
     Spawn,
     /// Wake a sleeping task.
     Wake,
+    /// Migrate a task that was running on another CPU.
+    Migrate,
 }
 
 /// Possible triggers of an `update_current` action.
@@ -493,6 +495,16 @@ pub(crate) fn unpark_target(runnable: Arc<Task>) {
     }
 }
 
+/// Enqueues a task that has been switched out for a migration.
+///
+/// See [`migrate_current`].
+pub(super) fn requeue_migrated(runnable: Arc<Task>) {
+    let preempt_cpu = scheduler_singleton().enqueue(runnable, EnqueueFlags::Migrate);
+    if let Some(preempt_cpu_id) = preempt_cpu {
+        set_need_preempt(preempt_cpu_id);
+    }
+}
+
 /// Enqueues a newly built task.
 ///
 /// Note that the new task is not guaranteed to run at once.
@@ -559,6 +571,25 @@ pub(super) fn yield_now() {
     })
 }
 
+/// Migrates the current task to a CPU selected by the scheduler.
+///
+/// The current task is dequeued from the local runqueue and switched out.
+/// Then it is enqueued again with [`EnqueueFlags::Migrate`], so the scheduler
+/// can select another CPU for it (e.g., according to its CPU affinity).
+///
+/// If there is no other task to run on the current CPU, the current task keeps
+/// running.
+#[track_caller]
+pub(super) fn migrate_current() {
+    reschedule(|local_rq: &mut dyn LocalRunQueue| {
+        if !local_rq.update_current(UpdateFlags::Wait) {
+            return ReschedAction::DoNothing;
+        }
+        let _current = local_rq.dequeue_current();
+        ReschedAction::SwitchToAndRequeue(local_rq.pick_next().clone())
+    });
+}
+
 /// Do rescheduling by acting on the scheduling decision (`ReschedAction`) made by a
 /// user-given closure.
 ///
@@ -588,6 +619,10 @@ where
             ReschedAction::SwitchTo(next_task) => {
                 break next_task;
             }
+            ReschedAction::SwitchToAndRequeue(next_task) => {
+                processor::requeue_current_on_switch();
+                break next_task;
+            }
         };
     };
 
@@ -608,4 +643,7 @@ enum ReschedAction {
     Retry,
     /// Switch to target task.
     SwitchTo(Arc<Task>),
+    /// Switch to target task, and enqueue the current task again after it is
+    /// switched out.
+    SwitchToAndRequeue(Arc<Task>),
 }
@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <poll.h>
+#include <pthread.h>
+#include <stdint.h>
+#include <sys/fanotify.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define TEST_DIR "/tmp"
+#define TEST_FILE "/tmp/fanotify_test"
+
+static char buf[4096] __attribute__((aligned(8)));
+
+FN_SETUP(create_file)
+{
+	int fd;
+
+	fd = CHECK(open(TEST_FILE, O_CREAT | O_WRONLY | O_TRUNC, 0644));
+	CHECK_WITH(write(fd, "hello", 5), _ret == 5);
+	CHECK(close(fd));
+}
+END_SETUP()
+
+static ino_t file_ino(int fd)
+{
+	struct stat st;
+
+	CHECK(fstat(fd, &st));
+	return st.st_ino;
+}
+
+static ino_t path_ino(const char *path)
+{
+	struct stat st;
+
+	CHECK(stat(path, &st));
+	return st.st_ino;
+}
+
+/*
+ * Finds the event on `ino` in the event records, since marks on mounts and
+ * file systems may also report events caused by other processes.
+ */
+static struct fanotify_event_metadata *find_event(ssize_t len, ino_t ino)
+{
+	struct fanotify_event_metadata *event;
+
+	for (event = (void *)buf; FAN_EVENT_OK(event, len);
+	     event = FAN_EVENT_NEXT(event, len)) {
+		if (event->fd >= 0 && file_ino(event->fd) == ino)
+			return event;
+	}
+
+	return NULL;
+}
+
+static void close_events(ssize_t len)
+{
+	struct fanotify_event_metadata *event;
+
+	for (event = (void *)buf; FAN_EVENT_OK(event, len);
+	     event = FAN_EVENT_NEXT(event, len)) {
+		if (event->fd >= 0)
+			CHECK(close(event->fd));
+	}
+}
+
+static int has_pending_events(int fan_fd)
+{
+	struct pollfd pfd = { .fd = fan_fd, .events = POLLIN };
+
+	return CHECK(poll(&pfd, 1, 0));
+}
+
+FN_TEST(init_errors)
+{
+	int fd;
+
+	TEST_ERRNO(fanotify_init(0x80000000, O_RDONLY), EINVAL);
+	TEST_ERRNO(fanotify_init(FAN_CLASS_CONTENT | FAN_CLASS_PRE_CONTENT,
+				 O_RDONLY),
+		   EINVAL);
+	TEST_ERRNO(fanotify_init(FAN_CLASS_CONTENT | FAN_REPORT_FID, O_RDONLY),
+		   EINVAL);
+	TEST_ERRNO(fanotify_init(FAN_CLASS_NOTIF, O_ACCMODE), EINVAL);
+	TEST_ERRNO(fanotify_init(FAN_CLASS_NOTIF, O_RDONLY | O_CREAT), EINVAL);
+
+	fd = TEST_SUCC(fanotify_init(FAN_CLASS_NOTIF | FAN_CLOEXEC,
+				     O_RDONLY | O_LARGEFILE));
+	TEST_RES(fcntl(fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(mark_errors)
+{
+	int fan_fd, fd;
+
+	fan_fd = TEST_SUCC(fanotify_init(FAN_CLASS_NOTIF, O_RDONLY));
+	fd = TEST_SUCC(open(TEST_FILE, O_RDONLY));
+
+	TEST_ERRNO(fanotify_mark(fd, FAN_MARK_ADD, FAN_OPEN, AT_FDCWD,
+				 TEST_FILE),
+		   EINVAL);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_ADD, 0, AT_FDCWD, TEST_FILE),
+		   EINVAL);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_ADD | FAN_MARK_REMOVE,
+				 FAN_OPEN, AT_FDCWD, TEST_FILE),
+		   EINVAL);
+	TEST_ERRNO(fanotify_mark(fan_fd,
+				 FAN_MARK_ADD | FAN_MARK_MOUNT |
+					 FAN_MARK_FILESYSTEM,
+				 FAN_OPEN, AT_FDCWD, TEST_FILE),
+		   EINVAL);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_ADD, FAN_OPEN_PERM, AT_FDCWD,
+				 TEST_FILE),
+		   EINVAL);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_ADD, FAN_ATTRIB, AT_FDCWD,
+				 TEST_FILE),
+		   EINVAL);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_ADD | FAN_MARK_ONLYDIR,
+				 FAN_OPEN, AT_FDCWD, TEST_FILE),
+		   ENOTDIR);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_REMOVE, FAN_OPEN, AT_FDCWD,
+				 TEST_FILE),
+		   ENOENT);
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_FLUSH | FAN_MARK_ONLYDIR, 0,
+				 AT_FDCWD, TEST_FILE),
+		   EINVAL);
+
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_ADD, FAN_OPEN, fd, NULL));
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_REMOVE, FAN_OPEN, AT_FDCWD,
+				TEST_FILE));
+	TEST_ERRNO(fanotify_mark(fan_fd, FAN_MARK_REMOVE, FAN_OPEN, AT_FDCWD,
+				 TEST_FILE),
+		   ENOENT);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(fan_fd));
+}
+END_TEST()
+
+FN_TEST(inode_mark)
+{
+	int fan_fd, fd;
+	struct fanotify_event_metadata *event;
+	ssize_t len;
+
+	fan_fd = TEST_SUCC(fanotify_init(FAN_CLASS_NOTIF | FAN_NONBLOCK,
+					 O_RDONLY));
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_ADD,
+				FAN_OPEN | FAN_CLOSE_NOWRITE, AT_FDCWD,
+				TEST_FILE));
+
+	TEST_ERRNO(read(fan_fd, buf, sizeof(buf)), EAGAIN);
+
+	fd = TEST_SUCC(open(TEST_FILE, O_RDONLY));
+	TEST_SUCC(close(fd));
+
+	// The events on the same file are merged.
+	len = TEST_RES(read(fan_fd, buf, sizeof(buf)),
+		       _ret == sizeof(struct fanotify_event_metadata));
+	event = (void *)buf;
+	TEST_RES(event->vers, _ret == FANOTIFY_METADATA_VERSION);
+	TEST_RES(event->metadata_len,
+		 _ret == sizeof(struct fanotify_event_metadata));
+	TEST_RES(event->mask, _ret == (FAN_OPEN | FAN_CLOSE_NOWRITE));
+	TEST_RES(event->pid, _ret == getpid());
+	TEST_RES(file_ino(event->fd), _ret == path_ino(TEST_FILE));
+	close_events(len);
+
+	// Accessing the file via the reported file descriptor does not
+	// generate events.
+	TEST_RES(has_pending_events(fan_fd), _ret == 0);
+
+	// The buffer is too small for an event.
+	fd = TEST_SUCC(open(TEST_FILE, O_RDONLY));
+	TEST_SUCC(close(fd));
+	TEST_ERRNO(read(fan_fd, buf, 8), EINVAL);
+	len = TEST_RES(read(fan_fd, buf, sizeof(buf)), _ret > 0);
+	close_events(len);
+
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_FLUSH, 0, AT_FDCWD, NULL));
+	fd = TEST_SUCC(open(TEST_FILE, O_RDONLY));
+	TEST_SUCC(close(fd));
+	TEST_RES(has_pending_events(fan_fd), _ret == 0);
+
+	TEST_SUCC(close(fan_fd));
+}
+END_TEST()
+
+FN_TEST(mount_mark)
+{
+	int fan_fd, fd;
+	struct fanotify_event_metadata *event;
+	ssize_t len;
+
+	fan_fd = TEST_SUCC(fanotify_init(FAN_CLASS_NOTIF | FAN_NONBLOCK,
+					 O_RDONLY));
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_ADD | FAN_MARK_MOUNT,
+				FAN_CLOSE_WRITE, AT_FDCWD, TEST_DIR));
+
+	fd = TEST_SUCC(open(TEST_FILE, O_WRONLY));
+	TEST_SUCC(close(fd));
+
+	len = TEST_RES(read(fan_fd, buf, sizeof(buf)), _ret > 0);
+	event = CHECK_WITH(find_event(len, path_ino(TEST_FILE)), _ret != NULL);
+	TEST_RES(event->mask, _ret == FAN_CLOSE_WRITE);
+	TEST_RES(event->pid, _ret == getpid());
+	close_events(len);
+
+	TEST_SUCC(close(fan_fd));
+}
+END_TEST()
+
+struct fid_record {
+	struct fanotify_event_info_fid info;
+	uint32_t handle_bytes;
+	int32_t handle_type;
+	uint64_t ino;
+	uint32_t gen;
+} __attribute__((packed));
+
+FN_TEST(filesystem_mark_fid)
+{
+	int fan_fd, fd;
+	struct fanotify_event_metadata *event;
+	struct fid_record *fid;
+	ssize_t len;
+	ino_t ino = path_ino(TEST_FILE);
+
+	fan_fd = TEST_SUCC(fanotify_init(
+		FAN_CLASS_NOTIF | FAN_NONBLOCK | FAN_REPORT_FID, O_RDONLY));
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_ADD | FAN_MARK_FILESYSTEM,
+				FAN_MODIFY, AT_FDCWD, TEST_DIR));
+
+	fd = TEST_SUCC(open(TEST_FILE, O_WRONLY));
+	TEST_RES(write(fd, "world", 5), _ret == 5);
+	TEST_SUCC(close(fd));
+
+	len = TEST_RES(read(fan_fd, buf, sizeof(buf)), _ret > 0);
+	for (event = (void *)buf; FAN_EVENT_OK(event, len);
+	     event = FAN_EVENT_NEXT(event, len)) {
+		fid = (void *)(event + 1);
+		if (fid->ino == ino)
+			break;
+	}
+	CHECK_WITH(FAN_EVENT_OK(event, len), _ret);
+	TEST_RES(event->event_len, _ret == sizeof(*event) + sizeof(*fid));
+	TEST_RES(event->mask, _ret == FAN_MODIFY);
+	TEST_RES(event->fd, _ret == FAN_NOFD);
+	TEST_RES(fid->info.hdr.info_type, _ret == FAN_EVENT_INFO_TYPE_FID);
+	TEST_RES(fid->info.hdr.len, _ret == sizeof(*fid));
+	TEST_RES(fid->handle_bytes,
+		 _ret == sizeof(fid->ino) + sizeof(fid->gen));
+
+	TEST_SUCC(close(fan_fd));
+}
+END_TEST()
+
+static void *open_file(void *arg)
+{
+	int fd;
+
+	fd = open(TEST_FILE, O_RDONLY);
+	if (fd < 0)
+		return (void *)(intptr_t)errno;
+
+	close(fd);
+	return (void *)0;
+}
+
+static void *read_file(void *arg)
+{
+	int fd = (int)(intptr_t)arg;
+	char c;
+
+	if (read(fd, &c, 1) < 0)
+		return (void *)(intptr_t)errno;
+
+	return (void *)0;
+}
+
+static void respond(int fan_fd, ssize_t len, uint64_t mask, uint32_t response)
+{
+	struct fanotify_event_metadata *event = (void *)buf;
+	struct fanotify_response resp;
+
+	CHECK_WITH(len, _ret == sizeof(*event));
+	CHECK_WITH(event->mask, _ret == mask);
+	CHECK_WITH(event->pid, _ret == getpid());
+
+	resp.fd = event->fd;
+	resp.response = response;
+	CHECK_WITH(write(fan_fd, &resp, sizeof(resp)), _ret == sizeof(resp));
+	CHECK(close(event->fd));
+}
+
+FN_TEST(open_perm)
+{
+	int fan_fd;
+	struct fanotify_event_metadata *event = (void *)buf;
+	struct fanotify_response resp;
+	pthread_t thread;
+	void *result;
+	ssize_t len;
+
+	fan_fd = TEST_SUCC(fanotify_init(FAN_CLASS_CONTENT, O_RDONLY));
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_ADD, FAN_OPEN_PERM, AT_FDCWD,
+				TEST_FILE));
+
+	// Allow the access.
+	CHECK_WITH(pthread_create(&thread, NULL, open_file, NULL), _ret == 0);
+	len = TEST_SUCC(read(fan_fd, buf, sizeof(buf)));
+	respond(fan_fd, len, FAN_OPEN_PERM, FAN_ALLOW);
+	CHECK_WITH(pthread_join(thread, &result), _ret == 0);
+	TEST_RES((intptr_t)result, _ret == 0);
+
+	// Deny the access.
+	CHECK_WITH(pthread_create(&thread, NULL, open_file, NULL), _ret == 0);
+	len = TEST_SUCC(read(fan_fd, buf, sizeof(buf)));
+
+	resp.fd = event->fd;
+	resp.response = FAN_ALLOW | FAN_DENY;
+	TEST_ERRNO(write(fan_fd, &resp, sizeof(resp)), EINVAL);
+	resp.fd = event->fd + 1;
+	resp.response = FAN_DENY;
+	TEST_ERRNO(write(fan_fd, &resp, sizeof(resp)), ENOENT);
+	TEST_ERRNO(write(fan_fd, &resp, sizeof(resp) - 1), EINVAL);
+
+	respond(fan_fd, len, FAN_OPEN_PERM, FAN_DENY);
+	CHECK_WITH(pthread_join(thread, &result), _ret == 0);
+	TEST_RES((intptr_t)result, _ret == EPERM);
+
+	// Closing the group allows the pending accesses.
+	CHECK_WITH(pthread_create(&thread, NULL, open_file, NULL), _ret == 0);
+	TEST_SUCC(read(fan_fd, buf, sizeof(buf)));
+	TEST_SUCC(close(event->fd));
+	TEST_SUCC(close(fan_fd));
+	CHECK_WITH(pthread_join(thread, &result), _ret == 0);
+	TEST_RES((intptr_t)result, _ret == 0);
+}
+END_TEST()
+
+FN_TEST(access_perm)
+{
+	int fan_fd, fd;
+	pthread_t thread;
+	void *result;
+	ssize_t len;
+
+	fan_fd = TEST_SUCC(fanotify_init(FAN_CLASS_PRE_CONTENT, O_RDONLY));
+	TEST_SUCC(fanotify_mark(fan_fd, FAN_MARK_ADD, FAN_ACCESS_PERM, AT_FDCWD,
+				TEST_FILE));
+
+	// Opening the file does not generate `FAN_ACCESS_PERM` events.
+	fd = TEST_SUCC(open(TEST_FILE, O_RDONLY));
+
+	CHECK_WITH(pthread_create(&thread, NULL, read_file,
+				  (void *)(intptr_t)fd),
+		   _ret == 0);
+	len = TEST_SUCC(read(fan_fd, buf, sizeof(buf)));
+	respond(fan_fd, len, FAN_ACCESS_PERM, FAN_DENY);
+	CHECK_WITH(pthread_join(thread, &result), _ret == 0);
+	TEST_RES((intptr_t)result, _ret == EPERM);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(fan_fd));
+	TEST_SUCC(unlink(TEST_FILE));
+}
+END_TEST()
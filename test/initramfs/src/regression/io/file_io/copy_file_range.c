@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <string.h>
+#include <unistd.h>
+
+#define SRC_NAME "/tmp/copy_file_range_src"
+#define DST_NAME "/tmp/copy_file_range_dst"
+#define EXT2_NAME "/ext2/copy_file_range_dst"
+
+static int src_fd;
+static int dst_fd;
+
+FN_SETUP(open)
+{
+	src_fd = CHECK(open(SRC_NAME, O_RDWR | O_CREAT | O_TRUNC, 0644));
+	CHECK_WITH(write(src_fd, "hello world", 11), _ret == 11);
+	CHECK(lseek(src_fd, 0, SEEK_SET));
+
+	dst_fd = CHECK(open(DST_NAME, O_RDWR | O_CREAT | O_TRUNC, 0644));
+}
+END_SETUP()
+
+FN_TEST(copy_with_file_positions)
+{
+	char buf[16] = { 0 };
+
+	TEST_RES(copy_file_range(src_fd, NULL, dst_fd, NULL, 5, 0), _ret == 5);
+	TEST_RES(lseek(src_fd, 0, SEEK_CUR), _ret == 5);
+	TEST_RES(lseek(dst_fd, 0, SEEK_CUR), _ret == 5);
+
+	// The copy stops at the end of the source file.
+	TEST_RES(copy_file_range(src_fd, NULL, dst_fd, NULL, 100, 0),
+		 _ret == 6);
+	TEST_RES(copy_file_range(src_fd, NULL, dst_fd, NULL, 100, 0),
+		 _ret == 0);
+
+	TEST_RES(pread(dst_fd, buf, sizeof(buf), 0),
+		 _ret == 11 && memcmp(buf, "hello world", 11) == 0);
+}
+END_TEST()
+
+FN_TEST(copy_with_offsets)
+{
+	char buf[16] = { 0 };
+	loff_t off_in = 6;
+	loff_t off_out = 0;
+
+	TEST_SUCC(lseek(src_fd, 0, SEEK_SET));
+	TEST_SUCC(lseek(dst_fd, 0, SEEK_SET));
+
+	// The offsets are advanced, but the file positions are not.
+	TEST_RES(copy_file_range(src_fd, &off_in, dst_fd, &off_out, 5, 0),
+		 _ret == 5 && off_in == 11 && off_out == 5);
+	TEST_RES(lseek(src_fd, 0, SEEK_CUR), _ret == 0);
+	TEST_RES(lseek(dst_fd, 0, SEEK_CUR), _ret == 0);
+
+	TEST_RES(pread(dst_fd, buf, sizeof(buf), 0),
+		 _ret == 11 && memcmp(buf, "world world", 11) == 0);
+
+	// Copying past the end of the source file copies nothing.
+	off_in = 100;
+	TEST_RES(copy_file_range(src_fd, &off_in, dst_fd, &off_out, 5, 0),
+		 _ret == 0 && off_in == 100 && off_out == 5);
+}
+END_TEST()
+
+FN_TEST(copy_within_same_file)
+{
+	char buf[16] = { 0 };
+	loff_t off_in = 0;
+	loff_t off_out = 11;
+
+	TEST_RES(copy_file_range(src_fd, &off_in, src_fd, &off_out, 5, 0),
+		 _ret == 5);
+	TEST_RES(pread(src_fd, buf, sizeof(buf), 0),
+		 _ret == 16 && memcmp(buf, "hello worldhello", 16) == 0);
+
+	// The ranges overlap.
+	off_in = 0;
+	off_out = 3;
+	TEST_ERRNO(copy_file_range(src_fd, &off_in, src_fd, &off_out, 5, 0),
+		   EINVAL);
+}
+END_TEST()
+
+FN_TEST(copy_across_file_systems)
+{
+	char buf[16] = { 0 };
+	loff_t off_in = 0;
+	int ext2_fd;
+
+	ext2_fd = TEST_SUCC(open(EXT2_NAME, O_RDWR | O_CREAT | O_TRUNC, 0644));
+
+	TEST_RES(copy_file_range(src_fd, &off_in, ext2_fd, NULL, 11, 0),
+		 _ret == 11);
+	TEST_RES(pread(ext2_fd, buf, sizeof(buf), 0),
+		 _ret == 11 && memcmp(buf, "hello world", 11) == 0);
+
+	TEST_SUCC(close(ext2_fd));
+	TEST_SUCC(unlink(EXT2_NAME));
+}
+END_TEST()
+
+FN_TEST(errors)
+{
+	loff_t off = -1;
+	int fd;
+
+	// Invalid flags.
+	TEST_ERRNO(copy_file_range(src_fd, NULL, dst_fd, NULL, 1, 1), EINVAL);
+
+	// Negative offsets.
+	TEST_ERRNO(copy_file_range(src_fd, &off, dst_fd, NULL, 1, 0), EINVAL);
+
+	// Directories.
+	fd = TEST_SUCC(open("/tmp", O_RDONLY | O_DIRECTORY));
+	TEST_ERRNO(copy_file_range(fd, NULL, dst_fd, NULL, 1, 0), EISDIR);
+	TEST_SUCC(close(fd));
+
+	// The output file is opened with `O_APPEND`.
+	fd = TEST_SUCC(open(DST_NAME, O_WRONLY | O_APPEND));
+	TEST_ERRNO(copy_file_range(src_fd, NULL, fd, NULL, 1, 0), EBADF);
+	TEST_SUCC(close(fd));
+
+	// The access modes are wrong.
+	fd = TEST_SUCC(open(DST_NAME, O_RDONLY));
+	TEST_ERRNO(copy_file_range(src_fd, NULL, fd, NULL, 1, 0), EBADF);
+	TEST_SUCC(close(fd));
+	fd = TEST_SUCC(open(SRC_NAME, O_WRONLY));
+	TEST_ERRNO(copy_file_range(fd, NULL, dst_fd, NULL, 1, 0), EBADF);
+	TEST_SUCC(close(fd));
+
+	TEST_ERRNO(copy_file_range(-1, NULL, dst_fd, NULL, 1, 0), EBADF);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(src_fd));
+	CHECK(close(dst_fd));
+	CHECK(unlink(SRC_NAME));
+	CHECK(unlink(DST_NAME));
+}
+END_SETUP()
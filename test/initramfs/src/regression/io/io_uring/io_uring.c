@@ -0,0 +1,654 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/io_uring.h>
+#include <netinet/in.h>
+#include <poll.h>
+#include <stdatomic.h>
+#include <sys/eventfd.h>
+#include <sys/mman.h>
+#include <sys/socket.h>
+#include <sys/syscall.h>
+#include <sys/uio.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define RING_ENTRIES 8
+#define FILE_NAME "/tmp/io_uring_test_file"
+
+static int ring_fd;
+static struct io_uring_params params;
+
+static unsigned int *sq_head, *sq_tail, *sq_mask, *sq_array;
+static struct io_uring_sqe *sqes;
+static unsigned int *cq_head, *cq_tail, *cq_mask;
+static struct io_uring_cqe *cqes;
+
+static int io_uring_setup(unsigned int entries, struct io_uring_params *p)
+{
+	return syscall(SYS_io_uring_setup, entries, p);
+}
+
+static int io_uring_enter(int fd, unsigned int to_submit,
+			  unsigned int min_complete, unsigned int flags)
+{
+	return syscall(SYS_io_uring_enter, fd, to_submit, min_complete, flags,
+		       NULL, 0);
+}
+
+static int io_uring_register(int fd, unsigned int opcode, void *arg,
+			     unsigned int nr_args)
+{
+	return syscall(SYS_io_uring_register, fd, opcode, arg, nr_args);
+}
+
+static struct io_uring_sqe *get_sqe(int opcode, int fd, __u64 user_data)
+{
+	unsigned int tail = *sq_tail;
+	unsigned int index = tail & *sq_mask;
+	struct io_uring_sqe *sqe = &sqes[index];
+
+	memset(sqe, 0, sizeof(*sqe));
+	sqe->opcode = opcode;
+	sqe->fd = fd;
+	sqe->user_data = user_data;
+
+	sq_array[index] = index;
+	atomic_store_explicit((_Atomic unsigned int *)sq_tail, tail + 1,
+			      memory_order_release);
+
+	return sqe;
+}
+
+static int submit(unsigned int to_submit, unsigned int min_complete)
+{
+	return io_uring_enter(ring_fd, to_submit, min_complete,
+			      min_complete ? IORING_ENTER_GETEVENTS : 0);
+}
+
+static int nr_ready_cqes(void)
+{
+	return atomic_load_explicit((_Atomic unsigned int *)cq_tail,
+				    memory_order_acquire) -
+	       *cq_head;
+}
+
+// Pops a CQE. Returns -1 if the CQ is empty.
+static int pop_cqe(struct io_uring_cqe *cqe)
+{
+	unsigned int head = *cq_head;
+
+	if (nr_ready_cqes() == 0) {
+		errno = EAGAIN;
+		return -1;
+	}
+
+	*cqe = cqes[head & *cq_mask];
+	atomic_store_explicit((_Atomic unsigned int *)cq_head, head + 1,
+			      memory_order_release);
+
+	return 0;
+}
+
+// Waits for and pops a CQE.
+static int wait_cqe(struct io_uring_cqe *cqe)
+{
+	if (nr_ready_cqes() == 0 &&
+	    io_uring_enter(ring_fd, 0, 1, IORING_ENTER_GETEVENTS) < 0)
+		return -1;
+
+	return pop_cqe(cqe);
+}
+
+FN_SETUP(setup_ring)
+{
+	void *sq_ring, *cq_ring;
+
+	ring_fd = CHECK(io_uring_setup(RING_ENTRIES - 1, &params));
+
+	sq_ring = CHECK_WITH(mmap(NULL,
+				  params.sq_off.array +
+					  params.sq_entries * sizeof(unsigned int),
+				  PROT_READ | PROT_WRITE,
+				  MAP_SHARED | MAP_POPULATE, ring_fd,
+				  IORING_OFF_SQ_RING),
+			     _ret != MAP_FAILED);
+	cq_ring = CHECK_WITH(mmap(NULL,
+				  params.cq_off.cqes + params.cq_entries *
+							       sizeof(struct io_uring_cqe),
+				  PROT_READ | PROT_WRITE,
+				  MAP_SHARED | MAP_POPULATE, ring_fd,
+				  IORING_OFF_CQ_RING),
+			     _ret != MAP_FAILED);
+	sqes = CHECK_WITH(mmap(NULL,
+			       params.sq_entries * sizeof(struct io_uring_sqe),
+			       PROT_READ | PROT_WRITE,
+			       MAP_SHARED | MAP_POPULATE, ring_fd,
+			       IORING_OFF_SQES),
+			  _ret != MAP_FAILED);
+
+	sq_head = sq_ring + params.sq_off.head;
+	sq_tail = sq_ring + params.sq_off.tail;
+	sq_mask = sq_ring + params.sq_off.ring_mask;
+	sq_array = sq_ring + params.sq_off.array;
+	cq_head = cq_ring + params.cq_off.head;
+	cq_tail = cq_ring + params.cq_off.tail;
+	cq_mask = cq_ring + params.cq_off.ring_mask;
+	cqes = cq_ring + params.cq_off.cqes;
+}
+END_SETUP()
+
+FN_TEST(setup)
+{
+	struct io_uring_params p;
+	int fd;
+
+	TEST_RES(params.sq_entries,
+		 _ret == RING_ENTRIES && params.cq_entries == 2 * RING_ENTRIES);
+	TEST_RES(*sq_mask, _ret == RING_ENTRIES - 1);
+	TEST_RES(*cq_mask, _ret == 2 * RING_ENTRIES - 1);
+	TEST_RES(params.features & IORING_FEAT_NODROP, _ret != 0);
+	TEST_RES(fcntl(ring_fd, F_GETFD), _ret == FD_CLOEXEC);
+
+	memset(&p, 0, sizeof(p));
+	TEST_ERRNO(io_uring_setup(0, &p), EINVAL);
+	TEST_ERRNO(io_uring_setup(65536, &p), EINVAL);
+
+	p.flags = IORING_SETUP_CLAMP;
+	fd = TEST_RES(io_uring_setup(65536, &p),
+		      p.sq_entries == 32768 && p.cq_entries == 65536);
+	TEST_SUCC(close(fd));
+
+	memset(&p, 0, sizeof(p));
+	p.flags = IORING_SETUP_CQSIZE;
+	p.cq_entries = 0;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+	p.cq_entries = 2;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+	p.cq_entries = 5;
+	fd = TEST_RES(io_uring_setup(4, &p),
+		      p.sq_entries == 4 && p.cq_entries == 8);
+	TEST_SUCC(close(fd));
+
+	memset(&p, 0, sizeof(p));
+	p.resv[0] = 1;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+
+	memset(&p, 0, sizeof(p));
+	p.flags = 1U << 31;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+
+	TEST_ERRNO(io_uring_enter(STDIN_FILENO, 0, 0, 0), EOPNOTSUPP);
+	TEST_ERRNO(io_uring_enter(ring_fd, 0, 0, 1U << 31), EINVAL);
+}
+END_TEST()
+
+FN_TEST(nop)
+{
+	struct io_uring_cqe cqe;
+
+	get_sqe(IORING_OP_NOP, -1, 0x1234);
+	get_sqe(IORING_OP_NOP, -1, 0x5678);
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(nr_ready_cqes(), _ret == 2);
+
+	TEST_RES(pop_cqe(&cqe), cqe.user_data == 0x1234 && cqe.res == 0);
+	TEST_RES(pop_cqe(&cqe), cqe.user_data == 0x5678 && cqe.res == 0);
+	TEST_ERRNO(pop_cqe(&cqe), EAGAIN);
+
+	get_sqe(0xff, -1, 1);
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(pop_cqe(&cqe), cqe.user_data == 1 && cqe.res == -EINVAL);
+
+	// Nothing is submitted.
+	TEST_RES(submit(1, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(read_write_file)
+{
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+	char buf[16] = { 0 };
+	char buf1[4], buf2[4];
+	struct iovec iov[2] = {
+		{ .iov_base = buf1, .iov_len = sizeof(buf1) },
+		{ .iov_base = buf2, .iov_len = sizeof(buf2) },
+	};
+	int fd;
+
+	fd = TEST_SUCC(open(FILE_NAME, O_RDWR | O_CREAT | O_TRUNC, 0600));
+
+	sqe = get_sqe(IORING_OP_WRITE, fd, 1);
+	sqe->addr = (__u64)"hello, io_uring";
+	sqe->len = 15;
+	sqe->off = 0;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 && cqe.res == 15);
+
+	sqe = get_sqe(IORING_OP_READ, fd, 2);
+	sqe->addr = (__u64)buf;
+	sqe->len = sizeof(buf);
+	sqe->off = 7;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 2 && cqe.res == 8 &&
+					 memcmp(buf, "io_uring", 8) == 0);
+
+	// An offset of -1 means the current file position.
+	TEST_RES(lseek(fd, 3, SEEK_SET), _ret == 3);
+	sqe = get_sqe(IORING_OP_READV, fd, 3);
+	sqe->addr = (__u64)iov;
+	sqe->len = 2;
+	sqe->off = -1;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 3 && cqe.res == 8 &&
+					 memcmp(buf1, "lo, ", 4) == 0 &&
+					 memcmp(buf2, "io_u", 4) == 0);
+	TEST_RES(lseek(fd, 0, SEEK_CUR), _ret == 11);
+
+	memcpy(buf1, "HELL", 4);
+	memcpy(buf2, "O, I", 4);
+	sqe = get_sqe(IORING_OP_WRITEV, fd, 4);
+	sqe->addr = (__u64)iov;
+	sqe->len = 2;
+	sqe->off = 0;
+	sqe = get_sqe(IORING_OP_FSYNC, fd, 5);
+	sqe->fsync_flags = IORING_FSYNC_DATASYNC;
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 4 && cqe.res == 8);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 5 && cqe.res == 0);
+
+	TEST_RES(pread(fd, buf, sizeof(buf), 0),
+		 _ret == 15 && memcmp(buf, "HELLO, Io_uring", 15) == 0);
+
+	sqe = get_sqe(IORING_OP_READ, 1000, 6);
+	sqe->addr = (__u64)buf;
+	sqe->len = sizeof(buf);
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 6 && cqe.res == -EBADF);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(FILE_NAME));
+}
+END_TEST()
+
+FN_TEST(read_pipe_async)
+{
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+	char buf[8] = { 0 };
+	int fds[2];
+
+	TEST_SUCC(pipe(fds));
+
+	// The read cannot complete immediately, so it is executed asynchronously.
+	sqe = get_sqe(IORING_OP_READ, fds[0], 1);
+	sqe->addr = (__u64)buf;
+	sqe->len = sizeof(buf);
+	sqe->off = -1;
+	TEST_RES(submit(1, 0), _ret == 1);
+	usleep(100 * 1000);
+	TEST_RES(nr_ready_cqes(), _ret == 0);
+
+	TEST_RES(write(fds[1], "pipe", 4), _ret == 4);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 && cqe.res == 4 &&
+					 memcmp(buf, "pipe", 4) == 0);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(poll)
+{
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+	int fds[2];
+
+	TEST_SUCC(pipe(fds));
+
+	sqe = get_sqe(IORING_OP_POLL_ADD, fds[1], 1);
+	sqe->poll32_events = POLLOUT;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe),
+		 cqe.user_data == 1 && (cqe.res & POLLOUT) && !(cqe.res & POLLIN));
+
+	sqe = get_sqe(IORING_OP_POLL_ADD, fds[0], 2);
+	sqe->poll32_events = POLLIN;
+	TEST_RES(submit(1, 0), _ret == 1);
+	usleep(100 * 1000);
+	TEST_RES(nr_ready_cqes(), _ret == 0);
+	TEST_RES(write(fds[1], "x", 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 2 && (cqe.res & POLLIN));
+
+	TEST_RES(read(fds[0], &cqe, 1), _ret == 1);
+
+	sqe = get_sqe(IORING_OP_POLL_ADD, fds[0], 3);
+	sqe->poll32_events = POLLIN;
+	TEST_RES(submit(1, 0), _ret == 1);
+	sqe = get_sqe(IORING_OP_POLL_REMOVE, -1, 4);
+	sqe->addr = 3;
+	TEST_RES(submit(1, 2), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), (cqe.user_data == 3 && cqe.res == -ECANCELED) ||
+					 (cqe.user_data == 4 && cqe.res == 0));
+	TEST_RES(wait_cqe(&cqe), (cqe.user_data == 3 && cqe.res == -ECANCELED) ||
+					 (cqe.user_data == 4 && cqe.res == 0));
+
+	sqe = get_sqe(IORING_OP_POLL_REMOVE, -1, 5);
+	sqe->addr = 3;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 5 && cqe.res == -ENOENT);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(timeout)
+{
+	struct __kernel_timespec ts = { .tv_sec = 0, .tv_nsec = 50 * 1000000 };
+	struct __kernel_timespec long_ts = { .tv_sec = 100, .tv_nsec = 0 };
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+
+	sqe = get_sqe(IORING_OP_TIMEOUT, -1, 1);
+	sqe->addr = (__u64)&ts;
+	sqe->len = 1;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 && cqe.res == -ETIME);
+
+	// The timeout completes after one other completion.
+	sqe = get_sqe(IORING_OP_TIMEOUT, -1, 2);
+	sqe->addr = (__u64)&long_ts;
+	sqe->len = 1;
+	sqe->off = 1;
+	get_sqe(IORING_OP_NOP, -1, 3);
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 3 && cqe.res == 0);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 2 && cqe.res == 0);
+
+	sqe = get_sqe(IORING_OP_TIMEOUT, -1, 4);
+	sqe->addr = (__u64)&long_ts;
+	sqe->len = 1;
+	TEST_RES(submit(1, 0), _ret == 1);
+	sqe = get_sqe(IORING_OP_TIMEOUT_REMOVE, -1, 5);
+	sqe->addr = 4;
+	TEST_RES(submit(1, 2), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), (cqe.user_data == 4 && cqe.res == -ECANCELED) ||
+					 (cqe.user_data == 5 && cqe.res == 0));
+	TEST_RES(wait_cqe(&cqe), (cqe.user_data == 4 && cqe.res == -ECANCELED) ||
+					 (cqe.user_data == 5 && cqe.res == 0));
+
+	sqe = get_sqe(IORING_OP_TIMEOUT_REMOVE, -1, 6);
+	sqe->addr = 4;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 6 && cqe.res == -ENOENT);
+
+	sqe = get_sqe(IORING_OP_TIMEOUT, -1, 7);
+	sqe->addr = (__u64)&ts;
+	sqe->len = 2;
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 7 && cqe.res == -EINVAL);
+}
+END_TEST()
+
+FN_TEST(link)
+{
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+	int res[4] = { 1, 1, 1, 1 };
+	char buf[8];
+	int fds[2];
+
+	TEST_SUCC(pipe(fds));
+
+	// The write is issued only after the read completes.
+	sqe = get_sqe(IORING_OP_READ, fds[0], 1);
+	sqe->addr = (__u64)buf;
+	sqe->len = 4;
+	sqe->off = -1;
+	sqe->flags = IOSQE_IO_LINK;
+	sqe = get_sqe(IORING_OP_WRITE, fds[1], 2);
+	sqe->addr = (__u64)"done";
+	sqe->len = 4;
+	sqe->off = -1;
+	TEST_RES(submit(2, 0), _ret == 2);
+	usleep(100 * 1000);
+	TEST_RES(nr_ready_cqes(), _ret == 0);
+
+	TEST_RES(write(fds[1], "link", 4), _ret == 4);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 && cqe.res == 4);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 2 && cqe.res == 4);
+	TEST_RES(read(fds[0], buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "done", 4) == 0);
+
+	// A short read breaks the chain.
+	TEST_RES(write(fds[1], "ab", 2), _ret == 2);
+	sqe = get_sqe(IORING_OP_READ, fds[0], 3);
+	sqe->addr = (__u64)buf;
+	sqe->len = 4;
+	sqe->off = -1;
+	sqe->flags = IOSQE_IO_LINK;
+	get_sqe(IORING_OP_NOP, -1, 4);
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 3 && cqe.res == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 4 && cqe.res == -ECANCELED);
+
+	// A hard link does not.
+	TEST_RES(write(fds[1], "ab", 2), _ret == 2);
+	sqe = get_sqe(IORING_OP_READ, fds[0], 5);
+	sqe->addr = (__u64)buf;
+	sqe->len = 4;
+	sqe->off = -1;
+	sqe->flags = IOSQE_IO_HARDLINK;
+	get_sqe(IORING_OP_NOP, -1, 6);
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 5 && cqe.res == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 6 && cqe.res == 0);
+
+	// An invalid SQE cancels the rest of the chain, but not the next chain.
+	sqe = get_sqe(IORING_OP_READ, 1000, 7);
+	sqe->flags = IOSQE_IO_LINK;
+	sqe = get_sqe(IORING_OP_NOP, -1, 8);
+	sqe->flags = IOSQE_IO_LINK;
+	get_sqe(IORING_OP_NOP, -1, 9);
+	get_sqe(IORING_OP_NOP, -1, 10);
+	TEST_RES(submit(4, 4), _ret == 4);
+	for (int i = 0; i < 4; i++) {
+		CHECK(wait_cqe(&cqe));
+		if (cqe.user_data >= 7 && cqe.user_data <= 10)
+			res[cqe.user_data - 7] = cqe.res;
+	}
+	TEST_RES(res[0], _ret == -EBADF);
+	TEST_RES(res[1], _ret == -ECANCELED);
+	TEST_RES(res[2], _ret == -ECANCELED);
+	TEST_RES(res[3], _ret == 0);
+
+	// Successful operations with `IOSQE_CQE_SKIP_SUCCESS` do not post CQEs.
+	sqe = get_sqe(IORING_OP_NOP, -1, 11);
+	sqe->flags = IOSQE_CQE_SKIP_SUCCESS;
+	get_sqe(IORING_OP_NOP, -1, 12);
+	TEST_RES(submit(2, 1), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 12 && cqe.res == 0);
+	TEST_RES(nr_ready_cqes(), _ret == 0);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(socket)
+{
+	struct sockaddr_in addr = { .sin_family = AF_INET,
+				    .sin_addr.s_addr = htonl(INADDR_LOOPBACK) };
+	socklen_t addr_len = sizeof(addr);
+	struct sockaddr_in peer_addr;
+	socklen_t peer_addr_len = sizeof(peer_addr);
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+	int listen_fd, client_fd, accepted_fd;
+	char buf[8] = { 0 };
+
+	listen_fd = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_SUCC(bind(listen_fd, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_SUCC(getsockname(listen_fd, (struct sockaddr *)&addr, &addr_len));
+	TEST_SUCC(listen(listen_fd, 1));
+	client_fd = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+
+	sqe = get_sqe(IORING_OP_ACCEPT, listen_fd, 1);
+	sqe->addr = (__u64)&peer_addr;
+	sqe->addr2 = (__u64)&peer_addr_len;
+	sqe->accept_flags = SOCK_CLOEXEC;
+	sqe = get_sqe(IORING_OP_CONNECT, client_fd, 2);
+	sqe->addr = (__u64)&addr;
+	sqe->off = sizeof(addr);
+	TEST_RES(submit(2, 2), _ret == 2);
+
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 || cqe.user_data == 2);
+	if (cqe.user_data == 1)
+		accepted_fd = cqe.res;
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 || cqe.user_data == 2);
+	if (cqe.user_data == 1)
+		accepted_fd = cqe.res;
+	TEST_RES(fcntl(accepted_fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(peer_addr_len, _ret == sizeof(peer_addr) &&
+					peer_addr.sin_family == AF_INET);
+
+	sqe = get_sqe(IORING_OP_RECV, accepted_fd, 3);
+	sqe->addr = (__u64)buf;
+	sqe->len = sizeof(buf);
+	sqe = get_sqe(IORING_OP_SEND, client_fd, 4);
+	sqe->addr = (__u64)"socket";
+	sqe->len = 6;
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), (cqe.user_data == 3 && cqe.res == 6) ||
+					 (cqe.user_data == 4 && cqe.res == 6));
+	TEST_RES(wait_cqe(&cqe), (cqe.user_data == 3 && cqe.res == 6) ||
+					 (cqe.user_data == 4 && cqe.res == 6));
+	TEST_RES(memcmp(buf, "socket", 6), _ret == 0);
+
+	TEST_SUCC(close(accepted_fd));
+	TEST_SUCC(close(client_fd));
+	TEST_SUCC(close(listen_fd));
+}
+END_TEST()
+
+FN_TEST(register_files)
+{
+	struct io_uring_cqe cqe;
+	struct io_uring_sqe *sqe;
+	int fds[2];
+	int files[2];
+	char buf[8];
+
+	TEST_SUCC(pipe(fds));
+	files[0] = -1;
+	files[1] = fds[1];
+
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_UNREGISTER_FILES, NULL, 0),
+		   ENXIO);
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_REGISTER_FILES, files, 0),
+		   EINVAL);
+	files[0] = ring_fd;
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_REGISTER_FILES, files, 2),
+		   EBADF);
+	files[0] = -1;
+	TEST_SUCC(io_uring_register(ring_fd, IORING_REGISTER_FILES, files, 2));
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_REGISTER_FILES, files, 2),
+		   EBUSY);
+
+	// The registered file is still usable after the file descriptor is closed.
+	TEST_SUCC(close(fds[1]));
+	sqe = get_sqe(IORING_OP_WRITE, 1, 1);
+	sqe->addr = (__u64)"fixed";
+	sqe->len = 5;
+	sqe->off = -1;
+	sqe->flags = IOSQE_FIXED_FILE;
+	sqe = get_sqe(IORING_OP_WRITE, 0, 2);
+	sqe->addr = (__u64)"fixed";
+	sqe->len = 5;
+	sqe->off = -1;
+	sqe->flags = IOSQE_FIXED_FILE;
+	TEST_RES(submit(2, 2), _ret == 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1 && cqe.res == 5);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 2 && cqe.res == -EBADF);
+	TEST_RES(read(fds[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "fixed", 5) == 0);
+
+	TEST_SUCC(io_uring_register(ring_fd, IORING_UNREGISTER_FILES, NULL, 0));
+	TEST_RES(read(fds[0], buf, sizeof(buf)), _ret == 0);
+
+	TEST_SUCC(close(fds[0]));
+}
+END_TEST()
+
+FN_TEST(register_eventfd)
+{
+	struct io_uring_cqe cqe;
+	uint64_t val;
+	int efd, fds[2];
+
+	efd = TEST_SUCC(eventfd(0, EFD_NONBLOCK));
+	TEST_SUCC(pipe(fds));
+
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_UNREGISTER_EVENTFD, NULL,
+				     0),
+		   ENXIO);
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_REGISTER_EVENTFD, &efd, 2),
+		   EINVAL);
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_REGISTER_EVENTFD, &fds[0],
+				     1),
+		   EINVAL);
+	TEST_SUCC(io_uring_register(ring_fd, IORING_REGISTER_EVENTFD, &efd, 1));
+	TEST_ERRNO(io_uring_register(ring_fd, IORING_REGISTER_EVENTFD, &efd, 1),
+		   EBUSY);
+
+	get_sqe(IORING_OP_NOP, -1, 1);
+	get_sqe(IORING_OP_NOP, -1, 2);
+	TEST_RES(submit(2, 2), _ret == 2);
+	// The eventfd may be signaled once for multiple CQEs.
+	TEST_RES(read(efd, &val, sizeof(val)),
+		 _ret == sizeof(val) && val >= 1 && val <= 2);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 1);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 2);
+
+	TEST_SUCC(io_uring_register(ring_fd, IORING_UNREGISTER_EVENTFD, NULL,
+				    0));
+	get_sqe(IORING_OP_NOP, -1, 3);
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_ERRNO(read(efd, &val, sizeof(val)), EAGAIN);
+	TEST_RES(wait_cqe(&cqe), cqe.user_data == 3);
+
+	TEST_ERRNO(io_uring_register(ring_fd, 1000, NULL, 0), EINVAL);
+
+	TEST_SUCC(close(efd));
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(poll_ring_fd)
+{
+	struct pollfd pfd = { .fd = ring_fd, .events = POLLIN | POLLOUT };
+	struct io_uring_cqe cqe;
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLOUT);
+
+	get_sqe(IORING_OP_NOP, -1, 1);
+	TEST_RES(submit(1, 1), _ret == 1);
+	TEST_RES(poll(&pfd, 1, 0),
+		 _ret == 1 && pfd.revents == (POLLIN | POLLOUT));
+
+	TEST_RES(pop_cqe(&cqe), cqe.user_data == 1);
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLOUT);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(ring_fd));
+}
+END_SETUP()
@@ -13,6 +13,9 @@ set -e
 
 ./file_io/access_err
 ./file_io/block_device
+./file_io/copy_file_range
 ./file_io/fcntl_lock
 ./file_io/file_err
 ./file_io/iovec_err
+
+./io_uring/io_uring
@@ -0,0 +1,342 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <mqueue.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/epoll.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define QUEUE_NAME "/mqueue_test"
+#define MOUNT_DIR "/tmp/mqueue_test_mnt"
+
+#define MAX_MSGS 4
+#define MSG_SIZE 64
+
+static mqd_t open_queue(int flags)
+{
+	struct mq_attr attr = {
+		.mq_maxmsg = MAX_MSGS,
+		.mq_msgsize = MSG_SIZE,
+	};
+
+	return mq_open(QUEUE_NAME, flags | O_CREAT | O_EXCL, 0600, &attr);
+}
+
+static void abs_timeout_after_ms(struct timespec *ts, long milliseconds)
+{
+	CHECK(clock_gettime(CLOCK_REALTIME, ts));
+	ts->tv_nsec += milliseconds * 1000000L;
+	ts->tv_sec += ts->tv_nsec / 1000000000L;
+	ts->tv_nsec %= 1000000000L;
+}
+
+FN_TEST(open_unlink)
+{
+	struct mq_attr attr;
+	mqd_t mqd, mqd2;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_ERRNO(open_queue(O_RDWR), EEXIST);
+	mqd2 = TEST_SUCC(mq_open(QUEUE_NAME, O_RDONLY));
+	TEST_SUCC(mq_close(mqd2));
+
+	TEST_RES(mq_getattr(mqd, &attr),
+		 attr.mq_flags == 0 && attr.mq_maxmsg == MAX_MSGS &&
+			 attr.mq_msgsize == MSG_SIZE && attr.mq_curmsgs == 0);
+	TEST_RES(fcntl(mqd, F_GETFD), _ret == FD_CLOEXEC);
+
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+	TEST_ERRNO(mq_unlink(QUEUE_NAME), ENOENT);
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR), ENOENT);
+
+	// The queue is still usable after it has been unlinked.
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(open_invalid)
+{
+	struct mq_attr attr = { .mq_maxmsg = 0, .mq_msgsize = MSG_SIZE };
+	char long_name[300];
+
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &attr), EINVAL);
+	attr.mq_maxmsg = MAX_MSGS;
+	attr.mq_msgsize = -1;
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &attr), EINVAL);
+	attr.mq_msgsize = 1L << 30;
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &attr), EINVAL);
+
+	// glibc strips the leading slash, so use the raw system call.
+	TEST_ERRNO(syscall(SYS_mq_open, "", O_RDWR | O_CREAT, 0600, NULL),
+		   ENOENT);
+	TEST_ERRNO(syscall(SYS_mq_open, "a/b", O_RDWR | O_CREAT, 0600, NULL),
+		   EACCES);
+	TEST_ERRNO(syscall(SYS_mq_open, "..", O_RDWR | O_CREAT, 0600, NULL),
+		   EACCES);
+
+	memset(long_name, 'a', sizeof(long_name) - 1);
+	long_name[sizeof(long_name) - 1] = '\0';
+	TEST_ERRNO(syscall(SYS_mq_open, long_name, O_RDWR | O_CREAT, 0600,
+			   NULL),
+		   ENAMETOOLONG);
+}
+END_TEST()
+
+FN_TEST(send_receive)
+{
+	char buf[MSG_SIZE];
+	unsigned int prio;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_send(mqd, "low1", 4, 1));
+	TEST_SUCC(mq_send(mqd, "high", 4, 5));
+	TEST_SUCC(mq_send(mqd, "low2", 4, 1));
+	TEST_SUCC(mq_send(mqd, "", 0, 0));
+
+	TEST_ERRNO(mq_receive(mqd, buf, MSG_SIZE - 1, &prio), EMSGSIZE);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 4 && prio == 5 && memcmp(buf, "high", 4) == 0);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 4 && prio == 1 && memcmp(buf, "low1", 4) == 0);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 4 && prio == 1 && memcmp(buf, "low2", 4) == 0);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 0);
+
+	TEST_ERRNO(mq_send(mqd, buf, MSG_SIZE + 1, 0), EMSGSIZE);
+	TEST_ERRNO(mq_send(mqd, buf, 1, 32768), EINVAL);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(access_mode)
+{
+	char buf[MSG_SIZE];
+	mqd_t rd, wr;
+
+	rd = TEST_SUCC(open_queue(O_RDONLY));
+	wr = TEST_SUCC(mq_open(QUEUE_NAME, O_WRONLY));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_ERRNO(mq_send(rd, "x", 1, 0), EBADF);
+	TEST_ERRNO(mq_receive(wr, buf, sizeof(buf), NULL), EBADF);
+	TEST_SUCC(mq_send(wr, "x", 1, 0));
+	TEST_RES(mq_receive(rd, buf, sizeof(buf), NULL), _ret == 1);
+
+	// Other files are not message queues.
+	TEST_ERRNO(mq_send(STDIN_FILENO, "x", 1, 0), EBADF);
+
+	TEST_SUCC(mq_close(rd));
+	TEST_SUCC(mq_close(wr));
+}
+END_TEST()
+
+FN_TEST(nonblock_timeout)
+{
+	struct mq_attr attr, old_attr;
+	struct timespec ts;
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+	int i;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR | O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_RES(mq_getattr(mqd, &attr), attr.mq_flags == O_NONBLOCK);
+	TEST_ERRNO(mq_receive(mqd, buf, sizeof(buf), NULL), EAGAIN);
+	for (i = 0; i < MAX_MSGS; i++)
+		TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_ERRNO(mq_send(mqd, "x", 1, 0), EAGAIN);
+	TEST_RES(mq_getattr(mqd, &attr), attr.mq_curmsgs == MAX_MSGS);
+
+	attr.mq_flags = 0;
+	TEST_RES(mq_setattr(mqd, &attr, &old_attr),
+		 old_attr.mq_flags == O_NONBLOCK);
+	TEST_RES(fcntl(mqd, F_GETFL), (_ret & O_NONBLOCK) == 0);
+
+	abs_timeout_after_ms(&ts, 50);
+	TEST_ERRNO(mq_timedsend(mqd, "x", 1, 0, &ts), ETIMEDOUT);
+	for (i = 0; i < MAX_MSGS; i++)
+		TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+	abs_timeout_after_ms(&ts, 50);
+	TEST_ERRNO(mq_timedreceive(mqd, buf, sizeof(buf), NULL, &ts),
+		   ETIMEDOUT);
+
+	ts.tv_nsec = 1000000000L;
+	TEST_ERRNO(mq_timedreceive(mqd, buf, sizeof(buf), NULL, &ts), EINVAL);
+
+	attr.mq_flags = O_APPEND;
+	TEST_ERRNO(mq_setattr(mqd, &attr, NULL), EINVAL);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(poll_epoll)
+{
+	struct epoll_event ev = { .events = EPOLLIN };
+	struct pollfd pfd;
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+	int epfd, i;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	pfd.fd = mqd;
+	pfd.events = POLLIN | POLLOUT;
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLOUT);
+
+	epfd = TEST_SUCC(epoll_create1(0));
+	TEST_SUCC(epoll_ctl(epfd, EPOLL_CTL_ADD, mqd, &ev));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0),
+		 _ret == 1 && ev.events == EPOLLIN);
+	TEST_RES(poll(&pfd, 1, 0),
+		 _ret == 1 && pfd.revents == (POLLIN | POLLOUT));
+
+	for (i = 1; i < MAX_MSGS; i++)
+		TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLIN);
+
+	for (i = 0; i < MAX_MSGS; i++)
+		TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(close(epfd));
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+static volatile sig_atomic_t received_signo;
+static volatile int received_code;
+static volatile int received_value;
+static volatile pid_t received_pid;
+
+static void notify_handler(int signo, siginfo_t *info, void *ucontext)
+{
+	received_signo = signo;
+	received_code = info->si_code;
+	received_value = info->si_value.sival_int;
+	received_pid = info->si_pid;
+}
+
+FN_TEST(notify)
+{
+	struct sigaction sa = { .sa_sigaction = notify_handler,
+				.sa_flags = SA_SIGINFO };
+	struct sigevent sev = {
+		.sigev_notify = SIGEV_SIGNAL,
+		.sigev_signo = SIGUSR1,
+		.sigev_value.sival_int = 42,
+	};
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+	pid_t pid;
+
+	TEST_SUCC(sigaction(SIGUSR1, &sa, NULL));
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_ERRNO(mq_notify(mqd, &sev), EBUSY);
+
+	// Another process cannot register or unregister.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(mq_notify(mqd, &sev), _ret < 0 && errno == EBUSY);
+		CHECK(mq_notify(mqd, NULL));
+		_exit(0);
+	}
+	TEST_RES(wait(NULL), _ret == pid);
+
+	TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_RES(received_signo,
+		 _ret == SIGUSR1 && received_code == SI_MESGQ &&
+			 received_value == 42 && received_pid == getpid());
+
+	// The registration is removed once the notification is delivered.
+	received_signo = 0;
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+	TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_RES(received_signo, _ret == 0);
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+
+	// The notification is not delivered if the queue is not empty.
+	TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_SUCC(mq_send(mqd, "x", 1, 0));
+	TEST_RES(received_signo, _ret == 0);
+	TEST_SUCC(mq_notify(mqd, NULL));
+
+	sev.sigev_notify = SIGEV_NONE;
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_SUCC(mq_notify(mqd, NULL));
+
+	sev.sigev_notify = SIGEV_THREAD_ID;
+	TEST_ERRNO(mq_notify(mqd, &sev), EINVAL);
+	sev.sigev_notify = SIGEV_SIGNAL;
+	sev.sigev_signo = 1000;
+	TEST_ERRNO(mq_notify(mqd, &sev), EINVAL);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mount)
+{
+	char buf[256];
+	struct stat st;
+	mqd_t mqd;
+	int fd;
+
+	TEST_SUCC(mkdir(MOUNT_DIR, 0755));
+	TEST_SUCC(mount("mqueue", MOUNT_DIR, "mqueue", 0, NULL));
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+
+	TEST_RES(stat(MOUNT_DIR QUEUE_NAME, &st),
+		 S_ISREG(st.st_mode) && (st.st_mode & 0777) == 0600);
+	fd = TEST_SUCC(open(MOUNT_DIR QUEUE_NAME, O_RDONLY));
+	memset(buf, 0, sizeof(buf));
+	TEST_RES(read(fd, buf, sizeof(buf) - 1),
+		 _ret > 0 && strncmp(buf, "QSIZE:5 ", 8) == 0);
+	TEST_SUCC(close(fd));
+
+	// Removing the file unlinks the queue.
+	TEST_SUCC(unlink(MOUNT_DIR QUEUE_NAME));
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR), ENOENT);
+
+	// Creating a file creates a queue.
+	fd = TEST_SUCC(open(MOUNT_DIR QUEUE_NAME, O_RDWR | O_CREAT, 0600));
+	TEST_SUCC(close(fd));
+	TEST_SUCC(mq_close(mqd));
+	mqd = TEST_SUCC(mq_open(QUEUE_NAME, O_RDWR));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_close(mqd));
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(rmdir(MOUNT_DIR));
+}
+END_TEST()
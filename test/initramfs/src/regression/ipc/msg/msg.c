@@ -0,0 +1,317 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/msg.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define MSGMAX 8192
+#define MSGMNB 16384
+
+#define CUSTOM_KEY 0x1badcafe
+
+#define SETTLE_MS 100
+
+struct test_msg {
+	long mtype;
+	char mtext[64];
+};
+
+static void sleep_ms(long milliseconds)
+{
+	struct timespec request = {
+		.tv_sec = milliseconds / 1000,
+		.tv_nsec = (milliseconds % 1000) * 1000000L,
+	};
+
+	CHECK(nanosleep(&request, NULL));
+}
+
+static int create_msg_queue(void)
+{
+	return msgget(IPC_PRIVATE, IPC_CREAT | 0600);
+}
+
+static int send_msg(int msqid, long mtype, const char *text, int flags)
+{
+	struct test_msg msg = { .mtype = mtype };
+
+	strcpy(msg.mtext, text);
+	return msgsnd(msqid, &msg, strlen(text), flags);
+}
+
+FN_TEST(msgget_key)
+{
+	int msqid, msqid2;
+
+	msqid = TEST_SUCC(msgget(CUSTOM_KEY, IPC_CREAT | IPC_EXCL | 0600));
+	TEST_RES(msgget(CUSTOM_KEY, 0), _ret == msqid);
+	TEST_RES(msgget(CUSTOM_KEY, IPC_CREAT | 0600), _ret == msqid);
+	TEST_ERRNO(msgget(CUSTOM_KEY, IPC_CREAT | IPC_EXCL | 0600), EEXIST);
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+
+	TEST_ERRNO(msgget(CUSTOM_KEY, 0), ENOENT);
+	TEST_ERRNO(msgctl(msqid, IPC_RMID, NULL), EINVAL);
+
+	// Private queues are always distinct.
+	msqid = TEST_SUCC(create_msg_queue());
+	msqid2 = TEST_RES(create_msg_queue(), _ret != msqid);
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_SUCC(msgctl(msqid2, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgsnd_invalid)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+	struct test_msg msg = { .mtype = 0 };
+
+	TEST_ERRNO(msgsnd(msqid, &msg, 0, 0), EINVAL);
+	msg.mtype = -1;
+	TEST_ERRNO(msgsnd(msqid, &msg, 0, 0), EINVAL);
+	msg.mtype = 1;
+	TEST_ERRNO(msgsnd(msqid, &msg, MSGMAX + 1, 0), EINVAL);
+	TEST_ERRNO(msgsnd(-1, &msg, 0, 0), EINVAL);
+	TEST_ERRNO(msgrcv(msqid, &msg, -1, 0, 0), EINVAL);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_typed)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+	struct test_msg msg;
+
+	TEST_SUCC(send_msg(msqid, 3, "three", 0));
+	TEST_SUCC(send_msg(msqid, 1, "one", 0));
+	TEST_SUCC(send_msg(msqid, 2, "two", 0));
+	TEST_SUCC(send_msg(msqid, 1, "uno", 0));
+	TEST_SUCC(send_msg(msqid, 5, "five", 0));
+
+	// A positive type selects the first message of the type.
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 2, 0),
+		 _ret == 3 && msg.mtype == 2 && memcmp(msg.mtext, "two", 3) == 0);
+
+	// `MSG_EXCEPT` selects the first message not of the type.
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 3, MSG_EXCEPT),
+		 _ret == 3 && msg.mtype == 1 && memcmp(msg.mtext, "one", 3) == 0);
+
+	// A negative type selects the first message with the lowest type that
+	// is not greater than its absolute value.
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), -4, 0),
+		 _ret == 3 && msg.mtype == 1 && memcmp(msg.mtext, "uno", 3) == 0);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), -2, IPC_NOWAIT),
+		   ENOMSG);
+
+	// A zero type selects the first message in the queue.
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, 0),
+		 _ret == 5 && msg.mtype == 3 &&
+			 memcmp(msg.mtext, "three", 5) == 0);
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, MSG_EXCEPT),
+		 _ret == 4 && msg.mtype == 5 &&
+			 memcmp(msg.mtext, "five", 4) == 0);
+
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, IPC_NOWAIT),
+		   ENOMSG);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_too_long)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+	struct test_msg msg;
+
+	TEST_SUCC(send_msg(msqid, 1, "hello", 0));
+
+	// The message remains in the queue if it is too long.
+	TEST_ERRNO(msgrcv(msqid, &msg, 2, 0, IPC_NOWAIT), E2BIG);
+
+	// The message is truncated with `MSG_NOERROR`.
+	memset(msg.mtext, 0, sizeof(msg.mtext));
+	TEST_RES(msgrcv(msqid, &msg, 2, 0, IPC_NOWAIT | MSG_NOERROR),
+		 _ret == 2 && msg.mtype == 1 && strcmp(msg.mtext, "he") == 0);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, IPC_NOWAIT),
+		   ENOMSG);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgsnd_full)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+	struct msqid_ds ds;
+	struct test_msg msg;
+
+	TEST_SUCC(msgctl(msqid, IPC_STAT, &ds));
+	ds.msg_qbytes = 8;
+	TEST_SUCC(msgctl(msqid, IPC_SET, &ds));
+
+	TEST_SUCC(send_msg(msqid, 1, "1234", 0));
+	TEST_SUCC(send_msg(msqid, 1, "5678", 0));
+	TEST_ERRNO(send_msg(msqid, 1, "9", IPC_NOWAIT), EAGAIN);
+
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 2 && ds.msg_cbytes == 8 && ds.msg_qbytes == 8);
+
+	// A blocked sender proceeds after a message is received.
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(send_msg(msqid, 2, "9", 0));
+		_exit(0);
+	}
+	sleep_ms(SETTLE_MS);
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, 0), _ret == 4);
+	TEST_RES(wait(NULL), _ret == pid);
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 2, IPC_NOWAIT),
+		 _ret == 1);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgctl_stat_set)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+	struct msqid_ds ds;
+	struct test_msg msg;
+
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_perm.uid == getuid() && ds.msg_perm.cuid == getuid() &&
+			 (ds.msg_perm.mode & 0777) == 0600 &&
+			 ds.msg_qnum == 0 && ds.msg_cbytes == 0 &&
+			 ds.msg_qbytes == MSGMNB && ds.msg_lspid == 0 &&
+			 ds.msg_lrpid == 0 && ds.msg_stime == 0);
+
+	TEST_SUCC(send_msg(msqid, 1, "abc", 0));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 1 && ds.msg_cbytes == 3 &&
+			 ds.msg_lspid == getpid() && ds.msg_stime != 0);
+
+	TEST_SUCC(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, 0));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 0 && ds.msg_lrpid == getpid() &&
+			 ds.msg_rtime != 0);
+
+	ds.msg_perm.mode = 0640;
+	ds.msg_qbytes = 1024;
+	TEST_SUCC(msgctl(msqid, IPC_SET, &ds));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 (ds.msg_perm.mode & 0777) == 0640 && ds.msg_qbytes == 1024);
+
+	TEST_ERRNO(msgctl(msqid, IPC_INFO + 100, &ds), EINVAL);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_ERRNO(msgctl(msqid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(msgrcv_removed)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct test_msg msg;
+
+		// The blocked receiver fails when the queue is removed.
+		CHECK_WITH(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, 0),
+			   _ret < 0 && errno == EIDRM);
+		_exit(0);
+	}
+
+	sleep_ms(SETTLE_MS);
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(msgrcv_blocking)
+{
+	int msqid = TEST_SUCC(create_msg_queue());
+	struct test_msg msg;
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		sleep_ms(SETTLE_MS);
+		CHECK(send_msg(msqid, 7, "late", 0));
+		_exit(0);
+	}
+
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 7, 0),
+		 _ret == 4 && msg.mtype == 7);
+	TEST_RES(wait(NULL), _ret == pid);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(proc_sysvipc_msg)
+{
+	int msqid = TEST_SUCC(msgget(CUSTOM_KEY, IPC_CREAT | IPC_EXCL | 0640));
+	char buf[4096];
+	char line[256];
+
+	TEST_SUCC(send_msg(msqid, 1, "hello", 0));
+
+	int fd = TEST_SUCC(open("/proc/sysvipc/msg", O_RDONLY));
+	ssize_t len = TEST_SUCC(read(fd, buf, sizeof(buf) - 1));
+	buf[len] = '\0';
+	TEST_SUCC(close(fd));
+
+	snprintf(line, sizeof(line), "%10d %10d  %4o  %10u %10lu %5u", CUSTOM_KEY,
+		 msqid, 0640, 5, 1UL, getpid());
+	TEST_RES(strncmp(buf, "       key      msqid perms", 27), _ret == 0);
+	TEST_RES(strstr(buf, line) != NULL, _ret);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(ipc_ns_isolation)
+{
+	int msqid = TEST_SUCC(msgget(CUSTOM_KEY, IPC_CREAT | IPC_EXCL | 0600));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWIPC));
+
+		// The queues in the old namespace are not visible.
+		CHECK_WITH(msgget(CUSTOM_KEY, 0), _ret < 0 && errno == ENOENT);
+		CHECK_WITH(msgctl(msqid, IPC_RMID, NULL),
+			   _ret < 0 && errno == EINVAL);
+
+		// The same key can be reused in the new namespace.
+		int new_msqid = CHECK(
+			msgget(CUSTOM_KEY, IPC_CREAT | IPC_EXCL | 0600));
+		CHECK(send_msg(new_msqid, 1, "new", 0));
+		CHECK(msgctl(new_msqid, IPC_RMID, NULL));
+		_exit(0);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	struct msqid_ds ds;
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds), ds.msg_qnum == 0);
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/uio.h>
+#include <unistd.h>
+
+#define FILE_NAME "/tmp/splice_test_file"
+
+static int file_fd;
+static int src_pipe[2];
+static int dst_pipe[2];
+
+FN_SETUP(open)
+{
+	signal(SIGPIPE, SIG_IGN);
+
+	file_fd = CHECK(open(FILE_NAME, O_RDWR | O_CREAT | O_TRUNC, 0644));
+	CHECK_WITH(write(file_fd, "hello world", 11), _ret == 11);
+	CHECK(lseek(file_fd, 0, SEEK_SET));
+
+	CHECK(pipe(src_pipe));
+	CHECK(pipe(dst_pipe));
+}
+END_SETUP()
+
+FN_TEST(file_to_pipe)
+{
+	char buf[16] = { 0 };
+	loff_t off = 6;
+
+	// Without an offset, the file position is used and advanced.
+	TEST_RES(splice(file_fd, NULL, src_pipe[1], NULL, 5, 0), _ret == 5);
+	TEST_RES(lseek(file_fd, 0, SEEK_CUR), _ret == 5);
+	TEST_RES(read(src_pipe[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+
+	// With an offset, the offset is advanced but the file position is not.
+	TEST_RES(splice(file_fd, &off, src_pipe[1], NULL, 100, 0),
+		 _ret == 5 && off == 11);
+	TEST_RES(lseek(file_fd, 0, SEEK_CUR), _ret == 5);
+	TEST_RES(read(src_pipe[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+
+	// At the end of the file, nothing is transferred.
+	TEST_RES(splice(file_fd, &off, src_pipe[1], NULL, 100, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(pipe_to_file)
+{
+	char buf[16] = { 0 };
+	loff_t off = 6;
+
+	TEST_RES(write(src_pipe[1], "WORLD", 5), _ret == 5);
+	TEST_RES(splice(src_pipe[0], NULL, file_fd, &off, 100, 0),
+		 _ret == 5 && off == 11);
+	TEST_RES(pread(file_fd, buf, sizeof(buf), 0),
+		 _ret == 11 && memcmp(buf, "hello WORLD", 11) == 0);
+
+	// The pipe is empty and the writer is still open.
+	TEST_ERRNO(splice(src_pipe[0], NULL, file_fd, &off, 100,
+			  SPLICE_F_NONBLOCK),
+		   EAGAIN);
+}
+END_TEST()
+
+FN_TEST(pipe_to_pipe)
+{
+	char buf[16] = { 0 };
+
+	TEST_RES(write(src_pipe[1], "abcdef", 6), _ret == 6);
+	TEST_RES(splice(src_pipe[0], NULL, dst_pipe[1], NULL, 4, 0), _ret == 4);
+	TEST_RES(read(dst_pipe[0], buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "abcd", 4) == 0);
+	TEST_RES(read(src_pipe[0], buf, sizeof(buf)),
+		 _ret == 2 && memcmp(buf, "ef", 2) == 0);
+}
+END_TEST()
+
+FN_TEST(tee)
+{
+	char buf[16] = { 0 };
+
+	TEST_RES(write(src_pipe[1], "abcdef", 6), _ret == 6);
+	TEST_RES(tee(src_pipe[0], dst_pipe[1], 4, 0), _ret == 4);
+
+	// The data is duplicated, not consumed.
+	TEST_RES(read(dst_pipe[0], buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "abcd", 4) == 0);
+	TEST_RES(read(src_pipe[0], buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "abcdef", 6) == 0);
+
+	TEST_ERRNO(tee(src_pipe[0], dst_pipe[1], 4, SPLICE_F_NONBLOCK), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(vmsplice)
+{
+	char buf1[] = "abc";
+	char buf2[] = "def";
+	char out[8] = { 0 };
+	struct iovec iov[2] = {
+		{ .iov_base = buf1, .iov_len = 3 },
+		{ .iov_base = buf2, .iov_len = 3 },
+	};
+	struct iovec out_iov = { .iov_base = out, .iov_len = sizeof(out) };
+
+	// Writing to a pipe gathers the user memory.
+	TEST_RES(vmsplice(src_pipe[1], iov, 2, 0), _ret == 6);
+
+	// Reading from a pipe scatters the data to the user memory.
+	TEST_RES(vmsplice(src_pipe[0], &out_iov, 1, 0),
+		 _ret == 6 && memcmp(out, "abcdef", 6) == 0);
+
+	TEST_ERRNO(vmsplice(src_pipe[0], &out_iov, 1, SPLICE_F_NONBLOCK),
+		   EAGAIN);
+}
+END_TEST()
+
+FN_TEST(errors)
+{
+	char buf[1];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	loff_t off = 0;
+	int other_fd;
+
+	// Neither file is a pipe.
+	other_fd = TEST_SUCC(open(FILE_NAME, O_RDONLY));
+	TEST_ERRNO(splice(other_fd, NULL, file_fd, NULL, 1, 0), EINVAL);
+	TEST_ERRNO(tee(other_fd, src_pipe[1], 1, 0), EINVAL);
+	TEST_ERRNO(vmsplice(other_fd, &iov, 1, 0), EBADF);
+
+	// Pipes do not have offsets.
+	TEST_ERRNO(splice(file_fd, NULL, src_pipe[1], &off, 1, 0), ESPIPE);
+	TEST_ERRNO(splice(src_pipe[0], &off, file_fd, NULL, 1, 0), ESPIPE);
+
+	// The pipe ends are used in the wrong directions.
+	TEST_ERRNO(splice(file_fd, NULL, src_pipe[0], NULL, 1, 0), EBADF);
+	TEST_ERRNO(splice(src_pipe[1], NULL, file_fd, NULL, 1, 0), EBADF);
+	TEST_ERRNO(splice(src_pipe[0], NULL, other_fd, NULL, 1, 0), EBADF);
+
+	// A pipe cannot be spliced to itself.
+	TEST_ERRNO(tee(src_pipe[0], src_pipe[1], 1, 0), EINVAL);
+
+	// Invalid flags.
+	TEST_ERRNO(splice(file_fd, NULL, src_pipe[1], NULL, 1, 0x10), EINVAL);
+
+	// Zero-length transfers succeed without checking the pipe.
+	TEST_RES(splice(src_pipe[0], NULL, dst_pipe[1], NULL, 0, 0), _ret == 0);
+
+	TEST_SUCC(close(other_fd));
+}
+END_TEST()
+
+FN_TEST(no_reader)
+{
+	int fildes[2];
+
+	TEST_SUCC(pipe(fildes));
+	TEST_SUCC(close(fildes[0]));
+
+	TEST_ERRNO(splice(file_fd, NULL, fildes[1], NULL, 1, 0), EPIPE);
+
+	TEST_SUCC(close(fildes[1]));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(src_pipe[0]));
+	CHECK(close(src_pipe[1]));
+	CHECK(close(dst_pipe[0]));
+	CHECK(close(dst_pipe[1]));
+	CHECK(close(file_fd));
+	CHECK(unlink(FILE_NAME));
+}
+END_SETUP()
@@ -4,10 +4,16 @@
 
 set -e
 
+./mqueue/mqueue
+
+./msg/msg
+
 ./pipe/pipe_err
 ./pipe/process_pipe_available
 ./pipe/short_rw
+./pipe/splice
 
 ./sem/sem
 
 ./shm/posix_shm
+./shm/sysv_shm
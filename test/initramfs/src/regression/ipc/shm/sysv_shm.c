@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/mman.h>
+#include <sys/shm.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define CUSTOM_KEY 0x2badcafe
+
+#define PAGE_SIZE 4096
+#define SEG_SIZE (3 * PAGE_SIZE)
+
+static int create_segment(void)
+{
+	return shmget(IPC_PRIVATE, SEG_SIZE, IPC_CREAT | 0600);
+}
+
+static unsigned long num_attaches(int shmid)
+{
+	struct shmid_ds ds;
+
+	CHECK(shmctl(shmid, IPC_STAT, &ds));
+	return ds.shm_nattch;
+}
+
+FN_TEST(shmget_key)
+{
+	int shmid, shmid2;
+
+	shmid = TEST_SUCC(
+		shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600));
+	TEST_RES(shmget(CUSTOM_KEY, 0, 0), _ret == shmid);
+	TEST_RES(shmget(CUSTOM_KEY, PAGE_SIZE, IPC_CREAT | 0600),
+		 _ret == shmid);
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600),
+		   EEXIST);
+	// An existing segment cannot be looked up with a larger size.
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE + 1, 0), EINVAL);
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE, 0), ENOENT);
+	TEST_ERRNO(shmctl(shmid, IPC_RMID, NULL), EINVAL);
+
+	// Segments must not be empty.
+	TEST_ERRNO(shmget(IPC_PRIVATE, 0, IPC_CREAT | 0600), EINVAL);
+
+	// Private segments are always distinct.
+	shmid = TEST_SUCC(create_segment());
+	shmid2 = TEST_RES(create_segment(), _ret != shmid);
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_SUCC(shmctl(shmid2, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmat_shared)
+{
+	int shmid = TEST_SUCC(create_segment());
+	char *addr, *addr2;
+
+	addr = TEST_SUCC(shmat(shmid, NULL, 0));
+	addr2 = TEST_RES(shmat(shmid, NULL, 0), _ret != addr);
+	TEST_RES(num_attaches(shmid), _ret == 2);
+
+	// The segment is initially zeroed.
+	TEST_RES(addr[SEG_SIZE - 1], _ret == 0);
+
+	// The attaches share the same memory.
+	strcpy(addr, "hello");
+	TEST_RES(strcmp(addr2, "hello"), _ret == 0);
+
+	// The child inherits the attaches, and its writes are visible.
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(num_attaches(shmid), _ret == 4);
+		strcpy(addr2 + PAGE_SIZE, "world");
+		_exit(0);
+	}
+	TEST_RES(wait(NULL), _ret == pid);
+	TEST_RES(strcmp(addr + PAGE_SIZE, "world"), _ret == 0);
+	TEST_RES(num_attaches(shmid), _ret == 2);
+
+	TEST_SUCC(shmdt(addr2));
+	TEST_RES(num_attaches(shmid), _ret == 1);
+	TEST_ERRNO(shmdt(addr2), EINVAL);
+
+	TEST_SUCC(shmdt(addr));
+	TEST_RES(num_attaches(shmid), _ret == 0);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmat_address)
+{
+	int shmid = TEST_SUCC(create_segment());
+	char *addr, *addr2;
+
+	// Find a free address range that is large enough for two attaches.
+	addr = TEST_SUCC(mmap(NULL, 2 * SEG_SIZE, PROT_NONE,
+			      MAP_PRIVATE | MAP_ANONYMOUS, -1, 0));
+	TEST_SUCC(munmap(addr, 2 * SEG_SIZE));
+
+	// Unaligned addresses are rounded down only with `SHM_RND`.
+	TEST_ERRNO(shmat(shmid, addr + 1, 0), EINVAL);
+	TEST_RES(shmat(shmid, addr + 1, SHM_RND), _ret == addr);
+
+	// Overlapping attaches fail unless `SHM_REMAP` is specified.
+	TEST_ERRNO(shmat(shmid, addr + PAGE_SIZE, 0), EINVAL);
+	TEST_ERRNO(shmat(shmid, NULL, SHM_REMAP), EINVAL);
+	addr2 = TEST_RES(shmat(shmid, addr + PAGE_SIZE, SHM_REMAP),
+			 _ret == addr + PAGE_SIZE);
+	TEST_RES(num_attaches(shmid), _ret == 2);
+
+	// Only the first page of the first attach remains.
+	TEST_SUCC(shmdt(addr2));
+	TEST_RES(num_attaches(shmid), _ret == 1);
+	TEST_SUCC(shmdt(addr));
+	TEST_RES(num_attaches(shmid), _ret == 0);
+
+	// Detaching requires the attach address, not an arbitrary address.
+	addr = TEST_SUCC(shmat(shmid, NULL, 0));
+	TEST_ERRNO(shmdt(addr + PAGE_SIZE), EINVAL);
+	TEST_ERRNO(shmdt(addr + 1), EINVAL);
+
+	// Detaching removes all the parts of a split attach.
+	TEST_SUCC(mprotect(addr + PAGE_SIZE, PAGE_SIZE, PROT_READ));
+	TEST_RES(num_attaches(shmid), _ret == 3);
+	TEST_SUCC(shmdt(addr));
+	TEST_RES(num_attaches(shmid), _ret == 0);
+	TEST_ERRNO(mprotect(addr + 2 * PAGE_SIZE, PAGE_SIZE, PROT_READ),
+		   ENOMEM);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmat_rdonly)
+{
+	int shmid = TEST_SUCC(create_segment());
+	char *addr, *addr2;
+	int status;
+
+	addr = TEST_SUCC(shmat(shmid, NULL, 0));
+	addr2 = TEST_SUCC(shmat(shmid, NULL, SHM_RDONLY));
+
+	strcpy(addr, "shared");
+	TEST_RES(strcmp(addr2, "shared"), _ret == 0);
+
+	// Read-only attaches cannot be made writable.
+	TEST_ERRNO(mprotect(addr2, PAGE_SIZE, PROT_READ | PROT_WRITE), EACCES);
+
+	// Writing to read-only attaches causes segmentation faults.
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		addr2[0] = 'S';
+		_exit(0);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGSEGV);
+	TEST_RES(strcmp(addr, "shared"), _ret == 0);
+
+	TEST_SUCC(shmdt(addr));
+	TEST_SUCC(shmdt(addr2));
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmctl_rmid_deferred)
+{
+	int shmid, shmid2;
+	struct shmid_ds ds;
+	char *addr;
+
+	shmid = TEST_SUCC(
+		shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600));
+	addr = TEST_SUCC(shmat(shmid, NULL, 0));
+	strcpy(addr, "alive");
+
+	// The removed segment remains until its last detach.
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 (ds.shm_perm.mode & SHM_DEST) && ds.shm_nattch == 1);
+	TEST_RES(strcmp(addr, "alive"), _ret == 0);
+
+	// The removed segment can no longer be looked up by its key.
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE, 0), ENOENT);
+	shmid2 = TEST_RES(
+		shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600),
+		_ret != shmid);
+
+	// The removed segment is destroyed when the process exits.
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(shmdt(addr));
+		CHECK_WITH(shmctl(shmid, IPC_STAT, &ds),
+			   _ret == 0 && ds.shm_nattch == 1);
+		_exit(0);
+	}
+	TEST_RES(wait(NULL), _ret == pid);
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds), ds.shm_nattch == 1);
+
+	// The removed segment is destroyed on its last detach.
+	TEST_SUCC(shmdt(addr));
+	TEST_ERRNO(shmctl(shmid, IPC_STAT, &ds), EINVAL);
+
+	TEST_SUCC(shmctl(shmid2, IPC_RMID, NULL));
+	TEST_ERRNO(shmctl(shmid2, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(shmctl_stat_set)
+{
+	int shmid = TEST_SUCC(create_segment());
+	struct shmid_ds ds;
+	char *addr;
+
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_perm.uid == getuid() && ds.shm_perm.cuid == getuid() &&
+			 (ds.shm_perm.mode & 0777) == 0600 &&
+			 ds.shm_segsz == SEG_SIZE && ds.shm_nattch == 0 &&
+			 ds.shm_cpid == getpid() && ds.shm_lpid == 0 &&
+			 ds.shm_atime == 0 && ds.shm_dtime == 0 &&
+			 ds.shm_ctime != 0);
+
+	addr = TEST_SUCC(shmat(shmid, NULL, 0));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 1 && ds.shm_lpid == getpid() &&
+			 ds.shm_atime != 0 && ds.shm_dtime == 0);
+	TEST_SUCC(shmdt(addr));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 0 && ds.shm_dtime != 0);
+
+	ds.shm_perm.mode = 0400;
+	TEST_SUCC(shmctl(shmid, IPC_SET, &ds));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 (ds.shm_perm.mode & 0777) == 0400);
+
+	TEST_ERRNO(shmctl(shmid, IPC_INFO + 100, &ds), EINVAL);
+	TEST_ERRNO(shmctl(-1, IPC_STAT, &ds), EINVAL);
+	TEST_ERRNO(shmat(-1, NULL, 0), EINVAL);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_ERRNO(shmctl(shmid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(proc_sysvipc_shm)
+{
+	int shmid = TEST_SUCC(
+		shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0640));
+	char buf[4096];
+	char line[256];
+	char *addr;
+
+	addr = TEST_SUCC(shmat(shmid, NULL, 0));
+
+	int fd = TEST_SUCC(open("/proc/sysvipc/shm", O_RDONLY));
+	ssize_t len = TEST_SUCC(read(fd, buf, sizeof(buf) - 1));
+	buf[len] = '\0';
+	TEST_SUCC(close(fd));
+
+	snprintf(line, sizeof(line), "%10d %10d  %4o %21u %5u %5u  %5u",
+		 CUSTOM_KEY, shmid, 0640, SEG_SIZE, getpid(), getpid(), 1);
+	TEST_RES(strncmp(buf, "       key      shmid perms", 27), _ret == 0);
+	TEST_RES(strstr(buf, line) != NULL, _ret);
+
+	TEST_SUCC(shmdt(addr));
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(ipc_ns_isolation)
+{
+	int shmid = TEST_SUCC(
+		shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWIPC));
+
+		// The segments in the old namespace are not visible.
+		CHECK_WITH(shmget(CUSTOM_KEY, SEG_SIZE, 0),
+			   _ret < 0 && errno == ENOENT);
+		CHECK_WITH(shmat(shmid, NULL, 0),
+			   _ret == (void *)-1 && errno == EINVAL);
+
+		// The same key can be reused in the new namespace.
+		int new_shmid = CHECK(shmget(CUSTOM_KEY, SEG_SIZE,
+					     IPC_CREAT | IPC_EXCL | 0600));
+		char *addr = shmat(new_shmid, NULL, 0);
+		CHECK_WITH(addr, _ret != (void *)-1);
+		strcpy(addr, "new");
+		CHECK(shmdt(addr));
+		CHECK(shmctl(new_shmid, IPC_RMID, NULL));
+		_exit(0);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	char *addr = TEST_SUCC(shmat(shmid, NULL, 0));
+	TEST_RES(addr[0], _ret == 0);
+	TEST_SUCC(shmdt(addr));
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/vfs.h>
+#include <sys/wait.h>
+
+#define PAGE_SIZE 4096
+#define HUGE_PAGE_SIZE (2 * 1024 * 1024)
+#define HUGE_PAGE_KB (HUGE_PAGE_SIZE / 1024)
+#define HUGETLBFS_MAGIC 0x958458f6
+#define NR_POOL_PAGES 4
+
+#ifndef MFD_HUGE_2MB
+#define MFD_HUGE_2MB (21U << 26)
+#endif
+#ifndef MFD_HUGE_1GB
+#define MFD_HUGE_1GB (30U << 26)
+#endif
+
+static const char *NR_HUGEPAGES = "/proc/sys/vm/nr_hugepages";
+static const char *MOUNT_DIR = "/tmp/hugetlbfs_test";
+
+static long saved_nr_hugepages;
+
+static long read_nr_hugepages(void)
+{
+	char buf[32];
+	int fd = CHECK(open(NR_HUGEPAGES, O_RDONLY));
+	ssize_t len = CHECK(read(fd, buf, sizeof(buf) - 1));
+	CHECK(close(fd));
+	buf[len] = '\0';
+	return atol(buf);
+}
+
+static int write_nr_hugepages(const char *value)
+{
+	int fd = CHECK(open(NR_HUGEPAGES, O_WRONLY));
+	int ret = write(fd, value, strlen(value));
+	CHECK(close(fd));
+	return ret;
+}
+
+static long get_meminfo(const char *name)
+{
+	FILE *f = CHECK_WITH(fopen("/proc/meminfo", "r"), _ret != NULL);
+	char line[256];
+	size_t name_len = strlen(name);
+	long value = -1;
+
+	while (fgets(line, sizeof(line), f)) {
+		if (strncmp(line, name, name_len) == 0 &&
+		    line[name_len] == ':') {
+			value = atol(line + name_len + 1);
+			break;
+		}
+	}
+
+	CHECK(fclose(f));
+	return value;
+}
+
+static void fill_pages(char *addr, size_t len, char value)
+{
+	for (size_t offset = 0; offset < len; offset += PAGE_SIZE)
+		addr[offset] = value;
+}
+
+static int check_pages(char *addr, size_t len, char value)
+{
+	for (size_t offset = 0; offset < len; offset += PAGE_SIZE) {
+		if (addr[offset] != value)
+			return -1;
+	}
+	return 0;
+}
+
+FN_SETUP(reserve_pool)
+{
+	char buf[16];
+
+	saved_nr_hugepages = read_nr_hugepages();
+
+	snprintf(buf, sizeof(buf), "%d\n", NR_POOL_PAGES);
+	CHECK(write_nr_hugepages(buf));
+	CHECK_WITH(read_nr_hugepages(), _ret == NR_POOL_PAGES);
+}
+END_SETUP()
+
+FN_TEST(pool_counters)
+{
+	TEST_RES(get_meminfo("HugePages_Total"), _ret == NR_POOL_PAGES);
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES);
+	TEST_RES(get_meminfo("HugePages_Rsvd"), _ret == 0);
+	TEST_RES(get_meminfo("HugePages_Surp"), _ret == 0);
+	TEST_RES(get_meminfo("Hugepagesize"), _ret == HUGE_PAGE_KB);
+
+	TEST_ERRNO(write_nr_hugepages("-1"), EINVAL);
+	TEST_ERRNO(write_nr_hugepages("many"), EINVAL);
+	TEST_RES(read_nr_hugepages(), _ret == NR_POOL_PAGES);
+}
+END_TEST()
+
+FN_TEST(private_anonymous)
+{
+	char *addr = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE + PAGE_SIZE,
+				    PROT_READ | PROT_WRITE,
+				    MAP_PRIVATE | MAP_ANONYMOUS | MAP_HUGETLB,
+				    -1, 0));
+	TEST_RES((unsigned long)addr % HUGE_PAGE_SIZE, _ret == 0);
+
+	// The length is rounded up to the huge page size.
+	TEST_RES(check_pages(addr, 2 * HUGE_PAGE_SIZE, 0), _ret == 0);
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES - 2);
+
+	fill_pages(addr, 2 * HUGE_PAGE_SIZE, 'a');
+	TEST_RES(check_pages(addr, 2 * HUGE_PAGE_SIZE, 'a'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, 2 * HUGE_PAGE_SIZE));
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES);
+}
+END_TEST()
+
+FN_TEST(invalid_arguments)
+{
+	char *addr;
+
+	// Only the default huge page size is supported.
+	TEST_ERRNO(mmap(NULL, HUGE_PAGE_SIZE, PROT_READ | PROT_WRITE,
+			MAP_PRIVATE | MAP_ANONYMOUS | MAP_HUGETLB |
+				(30 << MAP_HUGE_SHIFT),
+			-1, 0),
+		   ENODEV);
+	addr = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE, PROT_READ | PROT_WRITE,
+			      MAP_PRIVATE | MAP_ANONYMOUS | MAP_HUGETLB |
+				      (21 << MAP_HUGE_SHIFT),
+			      -1, 0));
+
+	// Huge pages cannot be split.
+	TEST_ERRNO(munmap(addr, PAGE_SIZE), EINVAL);
+	TEST_ERRNO(mprotect(addr + PAGE_SIZE, PAGE_SIZE, PROT_READ), EINVAL);
+	TEST_ERRNO(mremap(addr, HUGE_PAGE_SIZE, 2 * HUGE_PAGE_SIZE,
+			  MREMAP_MAYMOVE),
+		   EINVAL);
+	TEST_SUCC(mprotect(addr, HUGE_PAGE_SIZE, PROT_READ));
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+
+	// The pool does not have enough free huge pages.
+	TEST_ERRNO(mmap(NULL, (NR_POOL_PAGES + 1) * HUGE_PAGE_SIZE,
+			PROT_READ | PROT_WRITE,
+			MAP_PRIVATE | MAP_ANONYMOUS | MAP_HUGETLB, -1, 0),
+		   ENOMEM);
+
+	// Regular files cannot be mapped with `MAP_HUGETLB`.
+	int fd = TEST_SUCC(memfd_create("not_hugetlb", 0));
+	TEST_SUCC(ftruncate(fd, HUGE_PAGE_SIZE));
+	TEST_ERRNO(mmap(NULL, HUGE_PAGE_SIZE, PROT_READ, MAP_SHARED | MAP_HUGETLB,
+			fd, 0),
+		   EINVAL);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(fork_private_and_shared)
+{
+	char *private = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE,
+				       PROT_READ | PROT_WRITE,
+				       MAP_PRIVATE | MAP_ANONYMOUS |
+					       MAP_HUGETLB,
+				       -1, 0));
+	char *shared = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE,
+				      PROT_READ | PROT_WRITE,
+				      MAP_SHARED | MAP_ANONYMOUS | MAP_HUGETLB,
+				      -1, 0));
+	int status;
+
+	fill_pages(private, HUGE_PAGE_SIZE, 'a');
+	fill_pages(shared, HUGE_PAGE_SIZE, 'a');
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		fill_pages(private, HUGE_PAGE_SIZE, 'c');
+		fill_pages(shared, HUGE_PAGE_SIZE, 'c');
+		_exit(check_pages(private, HUGE_PAGE_SIZE, 'c') < 0 ?
+			      EXIT_FAILURE :
+			      EXIT_SUCCESS);
+	}
+
+	TEST_RES(wait(&status), _ret == pid && WIFEXITED(status) &&
+					WEXITSTATUS(status) == EXIT_SUCCESS);
+	TEST_RES(check_pages(private, HUGE_PAGE_SIZE, 'a'), _ret == 0);
+	TEST_RES(check_pages(shared, HUGE_PAGE_SIZE, 'c'), _ret == 0);
+
+	TEST_SUCC(munmap(private, HUGE_PAGE_SIZE));
+	TEST_SUCC(munmap(shared, HUGE_PAGE_SIZE));
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES);
+}
+END_TEST()
+
+FN_TEST(hugetlbfs_file)
+{
+	struct statfs stat;
+	char path[64];
+	char buf[16];
+
+	CHECK(mkdir(MOUNT_DIR, 0755));
+	TEST_SUCC(mount("none", MOUNT_DIR, "hugetlbfs", 0, NULL));
+	TEST_RES(statfs(MOUNT_DIR, &stat), stat.f_type == HUGETLBFS_MAGIC &&
+						   stat.f_bsize == HUGE_PAGE_SIZE);
+
+	snprintf(path, sizeof(path), "%s/file", MOUNT_DIR);
+	int fd = TEST_SUCC(open(path, O_RDWR | O_CREAT, 0644));
+	TEST_ERRNO(ftruncate(fd, PAGE_SIZE), EINVAL);
+	TEST_SUCC(ftruncate(fd, HUGE_PAGE_SIZE));
+	TEST_ERRNO(write(fd, "a", 1), EINVAL);
+
+	char *addr = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE,
+				    PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0));
+	TEST_ERRNO(mmap(NULL, HUGE_PAGE_SIZE, PROT_READ, MAP_SHARED, fd,
+			PAGE_SIZE),
+		   EINVAL);
+	memcpy(addr + PAGE_SIZE, "hugetlbfs", 10);
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+
+	// The huge page stays in the file after it is unmapped.
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES - 1);
+	TEST_RES(pread(fd, buf, sizeof(buf), PAGE_SIZE),
+		 _ret == sizeof(buf) && strcmp(buf, "hugetlbfs") == 0);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(path));
+	TEST_SUCC(umount(MOUNT_DIR));
+	CHECK(rmdir(MOUNT_DIR));
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES);
+}
+END_TEST()
+
+FN_TEST(memfd_hugetlb)
+{
+	TEST_ERRNO(memfd_create("hugetlb", MFD_HUGE_2MB), EINVAL);
+	TEST_ERRNO(memfd_create("hugetlb", MFD_HUGETLB | MFD_HUGE_1GB), ENODEV);
+
+	int fd = TEST_SUCC(
+		memfd_create("hugetlb", MFD_HUGETLB | MFD_HUGE_2MB));
+	TEST_SUCC(ftruncate(fd, HUGE_PAGE_SIZE));
+
+	char *addr = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE,
+				    PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0));
+	char *view = TEST_SUCC(
+		mmap(NULL, HUGE_PAGE_SIZE, PROT_READ, MAP_SHARED, fd, 0));
+	fill_pages(addr, HUGE_PAGE_SIZE, 'm');
+	TEST_RES(check_pages(view, HUGE_PAGE_SIZE, 'm'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+	TEST_SUCC(munmap(view, HUGE_PAGE_SIZE));
+	TEST_SUCC(close(fd));
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == NR_POOL_PAGES);
+}
+END_TEST()
+
+FN_TEST(shrink_pool)
+{
+	char *addr = TEST_SUCC(mmap(NULL, HUGE_PAGE_SIZE,
+				    PROT_READ | PROT_WRITE,
+				    MAP_PRIVATE | MAP_ANONYMOUS | MAP_HUGETLB,
+				    -1, 0));
+	fill_pages(addr, HUGE_PAGE_SIZE, 'a');
+
+	// Huge pages in use are not freed.
+	TEST_RES(write_nr_hugepages("0"), _ret == 1);
+	TEST_RES(read_nr_hugepages(), _ret == 1);
+	TEST_RES(get_meminfo("HugePages_Free"), _ret == 0);
+	TEST_RES(check_pages(addr, HUGE_PAGE_SIZE, 'a'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+	TEST_RES(write_nr_hugepages("0"), _ret == 1);
+	TEST_RES(read_nr_hugepages(), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(restore_pool)
+{
+	char buf[32];
+
+	snprintf(buf, sizeof(buf), "%ld\n", saved_nr_hugepages);
+	CHECK(write_nr_hugepages(buf));
+}
+END_SETUP()
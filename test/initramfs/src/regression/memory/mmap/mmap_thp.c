@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+
+#include <fcntl.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+
+#define PAGE_SIZE 4096
+#define HUGE_PAGE_SIZE (2 * 1024 * 1024)
+#define HUGE_PAGE_KB (HUGE_PAGE_SIZE / 1024)
+
+static const char *THP_ENABLED = "/sys/kernel/mm/transparent_hugepage/enabled";
+
+static char saved_mode[16];
+
+static void read_thp_enabled(char *buf, size_t size)
+{
+	int fd = CHECK(open(THP_ENABLED, O_RDONLY));
+	ssize_t len = CHECK(read(fd, buf, size - 1));
+	CHECK(close(fd));
+	buf[len] = '\0';
+}
+
+static int write_thp_enabled(const char *mode)
+{
+	int fd = CHECK(open(THP_ENABLED, O_WRONLY));
+	int ret = write(fd, mode, strlen(mode));
+	CHECK(close(fd));
+	return ret;
+}
+
+static long get_anon_huge_pages_kb(void)
+{
+	FILE *f = CHECK_WITH(fopen("/proc/meminfo", "r"), _ret != NULL);
+	char line[256];
+	long kb = -1;
+
+	while (fgets(line, sizeof(line), f)) {
+		if (sscanf(line, "AnonHugePages: %ld kB", &kb) == 1)
+			break;
+	}
+
+	CHECK(fclose(f));
+	return kb;
+}
+
+// Maps a private anonymous region that contains `nr_huge` huge-page-aligned
+// huge pages, and returns the start of the aligned part.
+static char *mmap_aligned(size_t nr_huge)
+{
+	size_t len = (nr_huge + 1) * HUGE_PAGE_SIZE;
+	char *addr = CHECK_WITH(mmap(NULL, len, PROT_READ | PROT_WRITE,
+				     MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				_ret != MAP_FAILED);
+	uintptr_t aligned = ((uintptr_t)addr + HUGE_PAGE_SIZE - 1) &
+			    ~((uintptr_t)HUGE_PAGE_SIZE - 1);
+
+	// Trim the unaligned parts, so that only the aligned part is mapped.
+	if (aligned != (uintptr_t)addr)
+		CHECK(munmap(addr, aligned - (uintptr_t)addr));
+	CHECK(munmap((char *)aligned + nr_huge * HUGE_PAGE_SIZE,
+		     (uintptr_t)addr + len - aligned -
+			     nr_huge * HUGE_PAGE_SIZE));
+
+	return (char *)aligned;
+}
+
+static void fill_pages(char *addr, size_t len, char value)
+{
+	for (size_t offset = 0; offset < len; offset += PAGE_SIZE)
+		addr[offset] = value;
+}
+
+static int check_pages(char *addr, size_t len, char value)
+{
+	for (size_t offset = 0; offset < len; offset += PAGE_SIZE) {
+		if (addr[offset] != value)
+			return -1;
+	}
+	return 0;
+}
+
+FN_SETUP(save_mode)
+{
+	char buf[64];
+	read_thp_enabled(buf, sizeof(buf));
+
+	char *start = CHECK_WITH(strchr(buf, '['), _ret != NULL) + 1;
+	char *end = CHECK_WITH(strchr(start, ']'), _ret != NULL);
+	CHECK_WITH(end - start, _ret < (long)sizeof(saved_mode));
+	memcpy(saved_mode, start, end - start);
+
+	CHECK(write_thp_enabled("always"));
+}
+END_SETUP()
+
+FN_TEST(enabled_knob)
+{
+	char buf[64];
+
+	TEST_RES(write_thp_enabled("never"), _ret == 5);
+	read_thp_enabled(buf, sizeof(buf));
+	TEST_RES(strcmp(buf, "always madvise [never]\n"), _ret == 0);
+
+	TEST_RES(write_thp_enabled("madvise\n"), _ret == 8);
+	read_thp_enabled(buf, sizeof(buf));
+	TEST_RES(strcmp(buf, "always [madvise] never\n"), _ret == 0);
+
+	TEST_ERRNO(write_thp_enabled("sometimes"), EINVAL);
+	read_thp_enabled(buf, sizeof(buf));
+	TEST_RES(strcmp(buf, "always [madvise] never\n"), _ret == 0);
+
+	TEST_RES(write_thp_enabled("always"), _ret == 6);
+	read_thp_enabled(buf, sizeof(buf));
+	TEST_RES(strcmp(buf, "[always] madvise never\n"), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(fault_maps_huge_page)
+{
+	char *addr = mmap_aligned(2);
+	long before = get_anon_huge_pages_kb();
+
+	fill_pages(addr, 2 * HUGE_PAGE_SIZE, 'a');
+	TEST_RES(get_anon_huge_pages_kb(), _ret >= before + 2 * HUGE_PAGE_KB);
+	TEST_RES(check_pages(addr, 2 * HUGE_PAGE_SIZE, 'a'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, 2 * HUGE_PAGE_SIZE));
+	TEST_RES(get_anon_huge_pages_kb(), _ret <= before);
+}
+END_TEST()
+
+FN_TEST(unaligned_mapping)
+{
+	// The mapping cannot hold a whole aligned huge page.
+	char *addr = mmap_aligned(1) + PAGE_SIZE;
+	CHECK(munmap(addr - PAGE_SIZE, PAGE_SIZE));
+	long before = get_anon_huge_pages_kb();
+
+	fill_pages(addr, HUGE_PAGE_SIZE - PAGE_SIZE, 'a');
+	TEST_RES(get_anon_huge_pages_kb(), _ret == before);
+	TEST_RES(check_pages(addr, HUGE_PAGE_SIZE - PAGE_SIZE, 'a'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE - PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(partial_munmap)
+{
+	char *addr = mmap_aligned(1);
+	char *hole = addr + HUGE_PAGE_SIZE / 2;
+
+	fill_pages(addr, HUGE_PAGE_SIZE, 'a');
+	TEST_SUCC(munmap(hole, PAGE_SIZE));
+
+	TEST_RES(check_pages(addr, hole - addr, 'a'), _ret == 0);
+	TEST_RES(check_pages(hole + PAGE_SIZE,
+			     addr + HUGE_PAGE_SIZE - hole - PAGE_SIZE, 'a'),
+		 _ret == 0);
+	TEST_ERRNO(madvise(hole, PAGE_SIZE, MADV_NORMAL), ENOMEM);
+
+	// The hole can be mapped again.
+	TEST_RES(mmap(hole, PAGE_SIZE, PROT_READ | PROT_WRITE,
+		      MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED, -1, 0),
+		 _ret == hole);
+	TEST_RES(hole[0], _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(partial_mprotect)
+{
+	char *addr = mmap_aligned(1);
+	char *page = addr + HUGE_PAGE_SIZE / 2;
+
+	fill_pages(addr, HUGE_PAGE_SIZE, 'a');
+	TEST_SUCC(mprotect(page, PAGE_SIZE, PROT_READ));
+	TEST_RES(check_pages(addr, HUGE_PAGE_SIZE, 'a'), _ret == 0);
+
+	TEST_SUCC(mprotect(page, PAGE_SIZE, PROT_READ | PROT_WRITE));
+	fill_pages(page, PAGE_SIZE, 'b');
+	TEST_RES(check_pages(addr, page - addr, 'a'), _ret == 0);
+	TEST_RES(check_pages(page, PAGE_SIZE, 'b'), _ret == 0);
+	TEST_RES(check_pages(page + PAGE_SIZE,
+			     addr + HUGE_PAGE_SIZE - page - PAGE_SIZE, 'a'),
+		 _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(fork_cow)
+{
+	char *addr = mmap_aligned(1);
+	char *page = addr + HUGE_PAGE_SIZE / 2;
+	int status;
+
+	fill_pages(addr, HUGE_PAGE_SIZE, 'a');
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		fill_pages(page, PAGE_SIZE, 'c');
+		if (check_pages(addr, page - addr, 'a') < 0 ||
+		    check_pages(page, PAGE_SIZE, 'c') < 0 ||
+		    check_pages(page + PAGE_SIZE,
+				addr + HUGE_PAGE_SIZE - page - PAGE_SIZE,
+				'a') < 0)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(wait(&status), _ret == pid && WIFEXITED(status) &&
+					WEXITSTATUS(status) == EXIT_SUCCESS);
+	TEST_RES(check_pages(addr, HUGE_PAGE_SIZE, 'a'), _ret == 0);
+
+	fill_pages(page, PAGE_SIZE, 'p');
+	TEST_RES(check_pages(addr, page - addr, 'a'), _ret == 0);
+	TEST_RES(check_pages(page, PAGE_SIZE, 'p'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(madvise_nohugepage)
+{
+	char *addr = mmap_aligned(1);
+	long before = get_anon_huge_pages_kb();
+
+	TEST_SUCC(madvise(addr, HUGE_PAGE_SIZE, MADV_NOHUGEPAGE));
+	fill_pages(addr, HUGE_PAGE_SIZE, 'a');
+	TEST_RES(get_anon_huge_pages_kb(), _ret == before);
+	TEST_RES(check_pages(addr, HUGE_PAGE_SIZE, 'a'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, HUGE_PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(madvise_hugepage)
+{
+	TEST_RES(write_thp_enabled("madvise"), _ret == 7);
+
+	char *addr = mmap_aligned(2);
+	char *advised = addr + HUGE_PAGE_SIZE;
+	long before = get_anon_huge_pages_kb();
+
+	// Only the advised huge page is backed with a huge page.
+	TEST_SUCC(madvise(advised, HUGE_PAGE_SIZE, MADV_HUGEPAGE));
+	fill_pages(addr, HUGE_PAGE_SIZE, 'a');
+	TEST_RES(get_anon_huge_pages_kb(), _ret == before);
+	fill_pages(advised, HUGE_PAGE_SIZE, 'b');
+	TEST_RES(get_anon_huge_pages_kb(), _ret >= before + HUGE_PAGE_KB);
+
+	TEST_RES(check_pages(addr, HUGE_PAGE_SIZE, 'a'), _ret == 0);
+	TEST_RES(check_pages(advised, HUGE_PAGE_SIZE, 'b'), _ret == 0);
+
+	TEST_SUCC(munmap(addr, 2 * HUGE_PAGE_SIZE));
+	TEST_ERRNO(madvise(addr, HUGE_PAGE_SIZE, MADV_HUGEPAGE), ENOMEM);
+
+	TEST_RES(write_thp_enabled("always"), _ret == 6);
+}
+END_TEST()
+
+FN_SETUP(restore_mode)
+{
+	CHECK(write_thp_enabled(saved_mode));
+}
+END_SETUP()
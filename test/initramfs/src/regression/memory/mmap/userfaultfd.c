@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+
+#include <fcntl.h>
+#include <linux/userfaultfd.h>
+#include <poll.h>
+#include <pthread.h>
+#include <stdint.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/ioctl.h>
+#include <sys/mman.h>
+#include <sys/syscall.h>
+
+#define PAGE_SIZE 4096
+
+static int uffd_create(int flags)
+{
+	return syscall(SYS_userfaultfd, flags);
+}
+
+static int uffd_api(int uffd, uint64_t features)
+{
+	struct uffdio_api api = { .api = UFFD_API, .features = features };
+	return ioctl(uffd, UFFDIO_API, &api);
+}
+
+static int uffd_register(int uffd, void *addr, size_t len, uint64_t mode)
+{
+	struct uffdio_register reg = {
+		.range = { .start = (uintptr_t)addr, .len = len },
+		.mode = mode,
+	};
+	return ioctl(uffd, UFFDIO_REGISTER, &reg);
+}
+
+static int uffd_unregister(int uffd, void *addr, size_t len)
+{
+	struct uffdio_range range = { .start = (uintptr_t)addr, .len = len };
+	return ioctl(uffd, UFFDIO_UNREGISTER, &range);
+}
+
+static int uffd_writeprotect(int uffd, void *addr, size_t len, uint64_t mode)
+{
+	struct uffdio_writeprotect wp = {
+		.range = { .start = (uintptr_t)addr, .len = len },
+		.mode = mode,
+	};
+	return ioctl(uffd, UFFDIO_WRITEPROTECT, &wp);
+}
+
+static char *mmap_anon(size_t len)
+{
+	return CHECK_WITH(mmap(NULL, len, PROT_READ | PROT_WRITE,
+			       MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+			  _ret != MAP_FAILED);
+}
+
+static void *read_byte(void *addr)
+{
+	return (void *)(uintptr_t)*(volatile char *)addr;
+}
+
+static void *write_byte(void *addr)
+{
+	*(volatile char *)addr = 'w';
+	return NULL;
+}
+
+static int read_msg(int uffd, struct uffd_msg *msg)
+{
+	struct pollfd pfd = { .fd = uffd, .events = POLLIN };
+
+	if (poll(&pfd, 1, -1) != 1 || pfd.revents != POLLIN)
+		return -1;
+	return read(uffd, msg, sizeof(*msg));
+}
+
+FN_TEST(api)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC | O_NONBLOCK));
+	struct uffdio_api api = { .api = UFFD_API };
+	struct uffd_msg msg;
+	char *addr = mmap_anon(PAGE_SIZE);
+
+	// The handshake must be done first.
+	TEST_ERRNO(read(uffd, &msg, sizeof(msg)), EINVAL);
+	TEST_ERRNO(uffd_register(uffd, addr, PAGE_SIZE,
+				 UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+
+	api.api = 0xAB;
+	TEST_ERRNO(ioctl(uffd, UFFDIO_API, &api), EINVAL);
+	api.api = UFFD_API;
+	api.features = UFFD_FEATURE_SIGBUS;
+	TEST_ERRNO(ioctl(uffd, UFFDIO_API, &api), EINVAL);
+
+	api.features = 0;
+	TEST_RES(ioctl(uffd, UFFDIO_API, &api),
+		 (api.features & UFFD_FEATURE_PAGEFAULT_FLAG_WP) &&
+			 (api.ioctls & (1ULL << _UFFDIO_REGISTER)) &&
+			 (api.ioctls & (1ULL << _UFFDIO_API)));
+	TEST_ERRNO(uffd_api(uffd, 0), EINVAL);
+
+	// No faults are pending.
+	TEST_ERRNO(read(uffd, &msg, sizeof(msg)), EAGAIN);
+	TEST_ERRNO(read(uffd, &msg, sizeof(msg) - 1), EINVAL);
+
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+	TEST_SUCC(close(uffd));
+}
+END_TEST()
+
+FN_TEST(register_errors)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC));
+	int uffd2 = TEST_SUCC(uffd_create(O_CLOEXEC));
+	char *addr = mmap_anon(2 * PAGE_SIZE);
+	char *shared = CHECK_WITH(mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+				       MAP_SHARED | MAP_ANONYMOUS, -1, 0),
+				  _ret != MAP_FAILED);
+
+	TEST_SUCC(uffd_api(uffd, 0));
+	TEST_SUCC(uffd_api(uffd2, 0));
+
+	TEST_ERRNO(uffd_register(uffd, addr + 1, PAGE_SIZE,
+				 UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+	TEST_ERRNO(uffd_register(uffd, addr, 0, UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+	TEST_ERRNO(uffd_register(uffd, addr, PAGE_SIZE, 0), EINVAL);
+	TEST_ERRNO(uffd_register(uffd, shared, PAGE_SIZE,
+				 UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+
+	TEST_SUCC(uffd_register(uffd, addr, PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_MISSING));
+	TEST_ERRNO(uffd_register(uffd2, addr, 2 * PAGE_SIZE,
+				 UFFDIO_REGISTER_MODE_MISSING),
+		   EBUSY);
+	TEST_SUCC(uffd_register(uffd2, addr + PAGE_SIZE, PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_MISSING));
+
+	// The ranges can be reused after the userfaultfds are closed.
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(close(uffd2));
+	addr[0] = 'a';
+	addr[PAGE_SIZE] = 'b';
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_RES(addr[PAGE_SIZE], _ret == 'b');
+
+	TEST_SUCC(munmap(shared, PAGE_SIZE));
+	TEST_SUCC(munmap(addr, 2 * PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(missing_copy)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC | UFFD_USER_MODE_ONLY));
+	char *addr = mmap_anon(2 * PAGE_SIZE);
+	char *src = mmap_anon(2 * PAGE_SIZE);
+	struct uffdio_register reg = {
+		.range = { .start = (uintptr_t)addr, .len = 2 * PAGE_SIZE },
+		.mode = UFFDIO_REGISTER_MODE_MISSING,
+	};
+	struct uffdio_copy copy;
+	struct uffd_msg msg;
+	pthread_t thread;
+	void *value;
+
+	TEST_SUCC(uffd_api(uffd, UFFD_FEATURE_THREAD_ID));
+	TEST_RES(ioctl(uffd, UFFDIO_REGISTER, &reg),
+		 (reg.ioctls & (1ULL << _UFFDIO_COPY)) &&
+			 !(reg.ioctls & (1ULL << _UFFDIO_WRITEPROTECT)));
+
+	memset(src, 'c', 2 * PAGE_SIZE);
+
+	CHECK_WITH(pthread_create(&thread, NULL, read_byte, addr + PAGE_SIZE + 1),
+		   _ret == 0);
+	TEST_RES(read_msg(uffd, &msg),
+		 _ret == sizeof(msg) && msg.event == UFFD_EVENT_PAGEFAULT &&
+			 msg.arg.pagefault.address ==
+				 (uintptr_t)addr + PAGE_SIZE &&
+			 msg.arg.pagefault.flags == 0 &&
+			 msg.arg.pagefault.feat.ptid != 0);
+
+	copy = (struct uffdio_copy){ .dst = (uintptr_t)addr,
+				     .src = (uintptr_t)src,
+				     .len = 2 * PAGE_SIZE };
+	TEST_RES(ioctl(uffd, UFFDIO_COPY, &copy), copy.copy == 2 * PAGE_SIZE);
+	CHECK_WITH(pthread_join(thread, &value), _ret == 0);
+	TEST_RES((char)(uintptr_t)value, _ret == 'c');
+
+	// The pages are already mapped.
+	TEST_ERRNO(ioctl(uffd, UFFDIO_COPY, &copy), EEXIST);
+	TEST_RES(copy.copy, _ret == -EEXIST);
+
+	TEST_RES(addr[0], _ret == 'c');
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(src, 2 * PAGE_SIZE));
+	TEST_SUCC(munmap(addr, 2 * PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(missing_zeropage)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC | UFFD_USER_MODE_ONLY));
+	char *addr = mmap_anon(PAGE_SIZE);
+	struct uffdio_zeropage zeropage = {
+		.range = { .start = (uintptr_t)addr, .len = PAGE_SIZE },
+	};
+	struct uffd_msg msg;
+	pthread_t thread;
+
+	TEST_SUCC(uffd_api(uffd, UFFD_FEATURE_EXACT_ADDRESS));
+	TEST_SUCC(uffd_register(uffd, addr, PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_MISSING));
+
+	CHECK_WITH(pthread_create(&thread, NULL, write_byte, addr + 8),
+		   _ret == 0);
+	TEST_RES(read_msg(uffd, &msg),
+		 _ret == sizeof(msg) &&
+			 msg.arg.pagefault.address == (uintptr_t)addr + 8 &&
+			 msg.arg.pagefault.flags ==
+				 UFFD_PAGEFAULT_FLAG_WRITE &&
+			 msg.arg.pagefault.feat.ptid == 0);
+
+	TEST_RES(ioctl(uffd, UFFDIO_ZEROPAGE, &zeropage),
+		 zeropage.zeropage == PAGE_SIZE);
+	CHECK_WITH(pthread_join(thread, NULL), _ret == 0);
+	TEST_RES(addr[0], _ret == 0);
+	TEST_RES(addr[8], _ret == 'w');
+
+	TEST_ERRNO(ioctl(uffd, UFFDIO_ZEROPAGE, &zeropage), EEXIST);
+
+	TEST_SUCC(uffd_unregister(uffd, addr, PAGE_SIZE));
+	TEST_ERRNO(ioctl(uffd, UFFDIO_ZEROPAGE, &zeropage), ENOENT);
+
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(missing_wake)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC | UFFD_USER_MODE_ONLY));
+	char *addr = mmap_anon(PAGE_SIZE);
+	struct uffdio_zeropage zeropage = {
+		.range = { .start = (uintptr_t)addr, .len = PAGE_SIZE },
+		.mode = UFFDIO_ZEROPAGE_MODE_DONTWAKE,
+	};
+	struct uffdio_range range = { .start = (uintptr_t)addr,
+				      .len = PAGE_SIZE };
+	struct uffd_msg msg;
+	pthread_t thread;
+
+	TEST_SUCC(uffd_api(uffd, 0));
+	TEST_SUCC(uffd_register(uffd, addr, PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_MISSING));
+
+	CHECK_WITH(pthread_create(&thread, NULL, write_byte, addr),
+		   _ret == 0);
+	TEST_RES(read_msg(uffd, &msg), _ret == sizeof(msg));
+
+	TEST_RES(ioctl(uffd, UFFDIO_ZEROPAGE, &zeropage),
+		 zeropage.zeropage == PAGE_SIZE);
+	TEST_SUCC(ioctl(uffd, UFFDIO_WAKE, &range));
+	CHECK_WITH(pthread_join(thread, NULL), _ret == 0);
+	TEST_RES(addr[0], _ret == 'w');
+
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(write_protect)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC | UFFD_USER_MODE_ONLY));
+	char *addr = mmap_anon(2 * PAGE_SIZE);
+	struct uffd_msg msg;
+	pthread_t thread;
+
+	TEST_SUCC(uffd_api(uffd, UFFD_FEATURE_PAGEFAULT_FLAG_WP));
+	memset(addr, 'a', 2 * PAGE_SIZE);
+
+	// The range must be registered in the write protection mode.
+	TEST_SUCC(uffd_register(uffd, addr, 2 * PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_MISSING));
+	TEST_ERRNO(uffd_writeprotect(uffd, addr, PAGE_SIZE,
+				     UFFDIO_WRITEPROTECT_MODE_WP),
+		   ENOENT);
+	TEST_SUCC(uffd_register(uffd, addr, 2 * PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_WP));
+
+	TEST_ERRNO(uffd_writeprotect(uffd, addr, PAGE_SIZE,
+				     UFFDIO_WRITEPROTECT_MODE_WP |
+					     UFFDIO_WRITEPROTECT_MODE_DONTWAKE),
+		   EINVAL);
+	TEST_SUCC(uffd_writeprotect(uffd, addr, PAGE_SIZE,
+				    UFFDIO_WRITEPROTECT_MODE_WP));
+
+	// Reading and writing to other pages are not affected.
+	addr[PAGE_SIZE] = 'b';
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_RES(addr[PAGE_SIZE], _ret == 'b');
+
+	CHECK_WITH(pthread_create(&thread, NULL, write_byte, addr + 1),
+		   _ret == 0);
+	TEST_RES(read_msg(uffd, &msg),
+		 _ret == sizeof(msg) &&
+			 msg.arg.pagefault.address == (uintptr_t)addr &&
+			 msg.arg.pagefault.flags ==
+				 (UFFD_PAGEFAULT_FLAG_WRITE |
+				  UFFD_PAGEFAULT_FLAG_WP));
+	TEST_RES(addr[1], _ret == 'a');
+
+	TEST_SUCC(uffd_writeprotect(uffd, addr, PAGE_SIZE, 0));
+	CHECK_WITH(pthread_join(thread, NULL), _ret == 0);
+	TEST_RES(addr[1], _ret == 'w');
+
+	// Unregistering clears the write protection.
+	TEST_SUCC(uffd_writeprotect(uffd, addr, 2 * PAGE_SIZE,
+				    UFFDIO_WRITEPROTECT_MODE_WP));
+	TEST_SUCC(uffd_unregister(uffd, addr, 2 * PAGE_SIZE));
+	addr[0] = 'c';
+	TEST_RES(addr[0], _ret == 'c');
+
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(addr, 2 * PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(user_mode_only)
+{
+	int uffd = TEST_SUCC(uffd_create(O_CLOEXEC | UFFD_USER_MODE_ONLY));
+	char *addr = mmap_anon(PAGE_SIZE);
+	int fd = TEST_SUCC(open("/dev/zero", O_RDONLY));
+
+	TEST_SUCC(uffd_api(uffd, 0));
+	TEST_SUCC(uffd_register(uffd, addr, PAGE_SIZE,
+				UFFDIO_REGISTER_MODE_MISSING));
+
+	// Page faults in kernel mode are not handled.
+	TEST_ERRNO(read(fd, addr, PAGE_SIZE), EFAULT);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+}
+END_TEST()
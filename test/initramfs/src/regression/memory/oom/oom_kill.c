@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define PAGE_SIZE 4096
+#define CHUNK_SIZE (16 * 1024 * 1024)
+#define BYSTANDER_SIZE (4 * 1024 * 1024)
+
+static int write_oom_score_adj(const char *value)
+{
+	int fd = open("/proc/self/oom_score_adj", O_WRONLY);
+	ssize_t len;
+
+	if (fd < 0)
+		return -1;
+	len = write(fd, value, strlen(value));
+	close(fd);
+
+	return len == (ssize_t)strlen(value) ? 0 : -1;
+}
+
+static void touch_pages(char *buf, size_t size)
+{
+	for (size_t offset = 0; offset < size; offset += PAGE_SIZE)
+		buf[offset] = 1;
+}
+
+// Allocates memory until the process is killed by the OOM killer.
+static void exhaust_memory(void)
+{
+	for (;;) {
+		char *buf = mmap(NULL, CHUNK_SIZE, PROT_READ | PROT_WRITE,
+				 MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+		if (buf == MAP_FAILED)
+			_exit(EXIT_FAILURE);
+		touch_pages(buf, CHUNK_SIZE);
+	}
+}
+
+// Holds some memory until it is killed by `SIGTERM`.
+static void hold_memory(int ready_fd)
+{
+	char *buf = mmap(NULL, BYSTANDER_SIZE, PROT_READ | PROT_WRITE,
+			 MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+
+	if (buf == MAP_FAILED)
+		_exit(EXIT_FAILURE);
+	touch_pages(buf, BYSTANDER_SIZE);
+
+	if (write(ready_fd, "", 1) != 1)
+		_exit(EXIT_FAILURE);
+	for (;;)
+		pause();
+}
+
+FN_TEST(only_preferred_victim_killed)
+{
+	int pipe_fds[2];
+	pid_t bystander, hog;
+	int status;
+	char byte;
+
+	TEST_SUCC(pipe(pipe_fds));
+
+	bystander = TEST_SUCC(fork());
+	if (bystander == 0) {
+		close(pipe_fds[0]);
+		hold_memory(pipe_fds[1]);
+	}
+	TEST_SUCC(close(pipe_fds[1]));
+	TEST_RES(read(pipe_fds[0], &byte, 1), _ret == 1);
+	TEST_SUCC(close(pipe_fds[0]));
+
+	hog = TEST_SUCC(fork());
+	if (hog == 0) {
+		if (write_oom_score_adj("1000") < 0)
+			_exit(EXIT_FAILURE);
+		exhaust_memory();
+	}
+
+	// The process with the maximum `oom_score_adj` must be the only victim.
+	TEST_RES(waitpid(hog, &status, 0),
+		 _ret == hog && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+	TEST_RES(waitpid(bystander, &status, WNOHANG), _ret == 0);
+
+	TEST_SUCC(kill(bystander, SIGTERM));
+	TEST_RES(waitpid(bystander, &status, 0),
+		 _ret == bystander && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGTERM);
+}
+END_TEST()
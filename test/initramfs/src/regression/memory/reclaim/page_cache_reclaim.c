@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define PAGE_SIZE 4096
+#define FILE_PAGES 16384
+#define FILE_SIZE_KB (FILE_PAGES * (PAGE_SIZE / 1024))
+
+#define TEST_FILE "/ext2/test_page_cache_reclaim"
+
+static long read_meminfo_kb(const char *key)
+{
+	char line[256];
+	long value = -1;
+	size_t key_len = strlen(key);
+	FILE *f = fopen("/proc/meminfo", "r");
+
+	if (f == NULL)
+		return -1;
+	while (fgets(line, sizeof(line), f) != NULL) {
+		if (strncmp(line, key, key_len) == 0 && line[key_len] == ':') {
+			value = strtol(line + key_len + 1, NULL, 10);
+			break;
+		}
+	}
+	fclose(f);
+	return value;
+}
+
+// Allocates and touches anonymous memory that exceeds free memory by half of
+// the test file, so part of the page cache must be reclaimed.
+static int apply_memory_pressure(void)
+{
+	long free_kb = read_meminfo_kb("MemFree");
+	size_t size;
+	char *buf;
+
+	if (free_kb < 0)
+		return -1;
+	size = (size_t)(free_kb + FILE_SIZE_KB / 2) * 1024;
+
+	buf = mmap(NULL, size, PROT_READ | PROT_WRITE,
+		   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (buf == MAP_FAILED)
+		return -1;
+	for (size_t offset = 0; offset < size; offset += PAGE_SIZE)
+		buf[offset] = 1;
+
+	return 0;
+}
+
+FN_SETUP(create_file)
+{
+	char page[PAGE_SIZE];
+	int fd = CHECK(open(TEST_FILE, O_CREAT | O_WRONLY | O_TRUNC, 0600));
+
+	for (int i = 0; i < FILE_PAGES; i++) {
+		memset(page, i & 0xff, sizeof(page));
+		CHECK_WITH(write(fd, page, sizeof(page)), _ret == sizeof(page));
+	}
+
+	CHECK(fsync(fd));
+	CHECK(close(fd));
+}
+END_SETUP()
+
+FN_TEST(page_cache_reclaimed)
+{
+	long cached = TEST_RES(read_meminfo_kb("Cached"),
+			       _ret >= FILE_SIZE_KB / 2);
+	int status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0)
+		_exit(apply_memory_pressure() == 0 ? EXIT_SUCCESS :
+						     EXIT_FAILURE);
+
+	// The child must not be killed by the OOM killer, since the page cache
+	// can be reclaimed.
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+	TEST_RES(read_meminfo_kb("Cached"),
+		 _ret >= 0 && _ret <= cached - FILE_SIZE_KB / 4);
+}
+END_TEST()
+
+FN_TEST(reclaimed_pages_read_back)
+{
+	char page[PAGE_SIZE];
+	int fd = TEST_SUCC(open(TEST_FILE, O_RDONLY));
+	int nr_mismatches = 0;
+
+	for (int i = 0; i < FILE_PAGES; i++) {
+		if (read(fd, page, sizeof(page)) != sizeof(page) ||
+		    page[0] != (char)(i & 0xff) ||
+		    page[PAGE_SIZE - 1] != (char)(i & 0xff))
+			nr_mismatches++;
+	}
+	TEST_RES(nr_mismatches, _ret == 0);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(TEST_FILE));
+}
+END_SETUP()
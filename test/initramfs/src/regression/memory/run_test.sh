@@ -10,6 +10,12 @@ set -e
 ./mmap/mmap_beyond_the_file
 ./mmap/mmap_err
 ./mmap/mmap_holes
+./mmap/mmap_hugetlb
 ./mmap/mmap_readahead
 ./mmap/mmap_shared_filebacked
+./mmap/mmap_thp
 ./mmap/mmap_vmrss
+./mmap/userfaultfd
+./oom/oom_kill
+./reclaim/page_cache_reclaim
+./swap/swapon_swapoff
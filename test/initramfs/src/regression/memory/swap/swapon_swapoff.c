@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <sys/swap.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define PAGE_SIZE 4096
+#define NUM_PAGES 256
+
+#define BASE_DIR "/ext2/swap_test"
+#define SWAP_FILE BASE_DIR "/swapfile"
+#define NOT_SWAP_FILE BASE_DIR "/not_swapfile"
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/swap.h#L134-L155>
+struct swap_header_info {
+	unsigned int version;
+	unsigned int last_page;
+	unsigned int nr_badpages;
+	unsigned char uuid[16];
+	char volume_name[16];
+};
+
+static void create_file(const char *path, int with_header)
+{
+	char page[PAGE_SIZE];
+	int fd = CHECK(open(path, O_CREAT | O_WRONLY | O_TRUNC, 0600));
+
+	memset(page, 0, sizeof(page));
+	if (with_header) {
+		struct swap_header_info info = {
+			.version = 1,
+			.last_page = NUM_PAGES - 1,
+			.nr_badpages = 0,
+		};
+		memcpy(page + 1024, &info, sizeof(info));
+		memcpy(page + PAGE_SIZE - 10, "SWAPSPACE2", 10);
+	}
+	CHECK_WITH(write(fd, page, sizeof(page)), _ret == sizeof(page));
+
+	memset(page, 0, sizeof(page));
+	for (int i = 1; i < NUM_PAGES; i++)
+		CHECK_WITH(write(fd, page, sizeof(page)), _ret == sizeof(page));
+
+	CHECK(fsync(fd));
+	CHECK(close(fd));
+}
+
+static long read_meminfo_kb(const char *key)
+{
+	char line[256];
+	long value = -1;
+	size_t key_len = strlen(key);
+	FILE *f = fopen("/proc/meminfo", "r");
+
+	if (f == NULL)
+		return -1;
+	while (fgets(line, sizeof(line), f) != NULL) {
+		if (strncmp(line, key, key_len) == 0 && line[key_len] == ':') {
+			value = strtol(line + key_len + 1, NULL, 10);
+			break;
+		}
+	}
+	fclose(f);
+	return value;
+}
+
+// Returns whether `/proc/swaps` has a line for `path` with the given size and
+// priority.
+static int find_in_proc_swaps(const char *path, long size_kb, int priority)
+{
+	char line[256];
+	char name[128];
+	char type[16];
+	long size, used;
+	int prio;
+	int found = 0;
+	FILE *f = fopen("/proc/swaps", "r");
+
+	if (f == NULL)
+		return -1;
+	// Skip the header.
+	if (fgets(line, sizeof(line), f) == NULL) {
+		fclose(f);
+		return -1;
+	}
+	while (fgets(line, sizeof(line), f) != NULL) {
+		if (sscanf(line, "%127s %15s %ld %ld %d", name, type, &size,
+			   &used, &prio) != 5)
+			continue;
+		if (strcmp(name, path) == 0 && strcmp(type, "file") == 0 &&
+		    size == size_kb && prio == priority) {
+			found = 1;
+			break;
+		}
+	}
+	fclose(f);
+	return found;
+}
+
+FN_SETUP(create_files)
+{
+	CHECK_WITH(mkdir(BASE_DIR, 0755), _ret == 0 || errno == EEXIST);
+	create_file(SWAP_FILE, 1);
+	create_file(NOT_SWAP_FILE, 0);
+}
+END_SETUP()
+
+FN_TEST(swapon_invalid)
+{
+	TEST_ERRNO(swapon(NOT_SWAP_FILE, 0), EINVAL);
+	TEST_ERRNO(swapon(BASE_DIR "/nonexistent", 0), ENOENT);
+	TEST_ERRNO(swapoff(NOT_SWAP_FILE), EINVAL);
+	TEST_ERRNO(swapoff(SWAP_FILE), EINVAL);
+}
+END_TEST()
+
+FN_TEST(swapon_swapoff)
+{
+	// The first page holds the swap header.
+	long size_kb = (NUM_PAGES - 1) * (PAGE_SIZE / 1024);
+	long swap_total = TEST_RES(read_meminfo_kb("SwapTotal"), _ret >= 0);
+	int flags = SWAP_FLAG_PREFER | (5 << SWAP_FLAG_PRIO_SHIFT);
+
+	TEST_SUCC(swapon(SWAP_FILE, flags));
+	TEST_RES(find_in_proc_swaps(SWAP_FILE, size_kb, 5), _ret == 1);
+	TEST_RES(read_meminfo_kb("SwapTotal"), _ret == swap_total + size_kb);
+	TEST_ERRNO(swapon(SWAP_FILE, flags), EBUSY);
+
+	TEST_SUCC(swapoff(SWAP_FILE));
+	TEST_RES(find_in_proc_swaps(SWAP_FILE, size_kb, 5), _ret == 0);
+	TEST_RES(read_meminfo_kb("SwapTotal"), _ret == swap_total);
+	TEST_ERRNO(swapoff(SWAP_FILE), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(SWAP_FILE));
+	CHECK(unlink(NOT_SWAP_FILE));
+	CHECK(rmdir(BASE_DIR));
+}
+END_SETUP()
@@ -605,18 +605,330 @@ else
     echo -e "Verified: pids.peak retained"
 fi
 
-# --- Section 5: Teardown ------------------------------------------------------
+# -- 4.4 memory sub-controller -------------------------------------------------
 
-log_section "Section 5: Teardown"
+log_section "Section 4.4: memory sub-controller"
 
-log_step "5.1 Move process 1 back to root"
+MEMORY_FILE="/tmp/cgroup_memory_test"
+
+read_memory_field() {
+    local field=$1
+    local path=$2
+
+    while IFS=' ' read -r name value; do
+        if [ "$name" = "$field" ]; then
+            echo "$value"
+            return 0
+        fi
+    done < "$path"
+
+    echo "Error: Failed to read $field from $path"
+    exit 1
+}
+
+log_step "4.4.1 Enable memory in root"
+echo "+memory" > "$CGROUP_ROOT/cgroup.subtree_control"
+cd "$CGROUP_ROOT/$CGROUP_NAME"
+
+log_step "4.4.2 Check the default limits"
+verify "memory.max is unlimited by default" \
+    "cat memory.max" \
+    "max"
+verify "memory.high is unlimited by default" \
+    "cat memory.high" \
+    "max"
+verify "memory.min is zero by default" \
+    "cat memory.min" \
+    "0"
+verify "memory.low is zero by default" \
+    "cat memory.low" \
+    "0"
+
+log_step "4.4.3 Write the limits"
+echo "64M" > memory.max
+verify "memory.max accepts a suffixed size" \
+    "cat memory.max" \
+    "67108864"
+echo "1048576" > memory.high
+verify "memory.high accepts a size in bytes" \
+    "cat memory.high" \
+    "1048576"
+echo "max" > memory.high
+verify "memory.high accepts max" \
+    "cat memory.high" \
+    "max"
+verify "memory.max rejects invalid values" \
+    "echo abc > memory.max 2>/dev/null || echo failed" \
+    "failed"
+
+log_step "4.4.4 Charge the page cache"
+CURRENT_BEFORE=$(cat memory.current)
+sh -c "echo \$\$ > cgroup.procs; dd if=/dev/zero of=$MEMORY_FILE bs=4096 count=1024 2>/dev/null"
+verify_ge "memory.current includes the written pages" \
+    "cat memory.current" \
+    $((CURRENT_BEFORE + 4194304))
+verify_ge "memory.stat accounts the written pages as file pages" \
+    "read_memory_field file memory.stat" \
+    4194304
+verify_ge "memory.peak is at least memory.current" \
+    "cat memory.peak" \
+    $(cat memory.current)
+
+log_step "4.4.5 Uncharge the page cache"
+rm "$MEMORY_FILE"
+CURRENT_AFTER=$(cat memory.current)
+echo "memory.current after removal: $CURRENT_AFTER"
+if [ "$CURRENT_AFTER" -gt "$CURRENT_BEFORE" ]; then
+    echo "Error: memory.current is not uncharged after the file is removed"
+    exit 1
+else
+    echo "Verified"
+fi
+
+log_step "4.4.6 Enforce memory.max"
+echo $((CURRENT_AFTER + 1048576)) > memory.max
+verify "writing beyond memory.max fails" \
+    "sh -c 'echo \$\$ > cgroup.procs; dd if=/dev/zero of=$MEMORY_FILE bs=4096 count=1024 2>/dev/null' || echo failed" \
+    "failed"
+verify_ge "memory.events counts the max event" \
+    "read_memory_field max memory.events" \
+    1
+rm -f "$MEMORY_FILE"
+echo "max" > memory.max
+
+# -- 4.5 cpuset sub-controller -------------------------------------------------
+
+log_section "Section 4.5: cpuset sub-controller"
+
+log_step "4.5.1 Enable cpuset in root"
+echo "+cpuset" > "$CGROUP_ROOT/cgroup.subtree_control"
+cd "$CGROUP_ROOT/$CGROUP_NAME"
+ROOT_EFFECTIVE=$(cat "$CGROUP_ROOT/cpuset.cpus.effective")
+echo "Root cpuset.cpus.effective: $ROOT_EFFECTIVE"
+
+log_step "4.5.2 Check the defaults"
+verify "cpuset.cpus is empty by default" \
+    "cat cpuset.cpus" \
+    ""
+verify "cpuset.cpus.effective inherits from the parent" \
+    "cat cpuset.cpus.effective" \
+    "$ROOT_EFFECTIVE"
+verify "cpuset.cpus.partition is member by default" \
+    "cat cpuset.cpus.partition" \
+    "member"
+
+log_step "4.5.3 Write cpuset.cpus"
+echo "0" > cpuset.cpus
+verify "cpuset.cpus accepts a CPU list" \
+    "cat cpuset.cpus" \
+    "0"
+verify "cpuset.cpus.effective follows cpuset.cpus" \
+    "cat cpuset.cpus.effective" \
+    "0"
+verify "cpuset.cpus rejects invalid CPU lists" \
+    "echo abc > cpuset.cpus 2>/dev/null || echo failed" \
+    "failed"
+verify "cpuset.cpus rejects nonexistent CPUs" \
+    "echo 4096 > cpuset.cpus 2>/dev/null || echo failed" \
+    "failed"
+
+log_step "4.5.4 Run a task in the cpuset"
+verify "the task is placed on the allowed CPUs" \
+    "sh -c 'echo \$\$ > cgroup.procs; for i in 1 2 3 4 5 6 7 8; do cat /proc/self/stat; done' | awk '{ print \$39 }' | sort -u" \
+    "0"
+
+log_step "4.5.5 Write cpuset.cpus.partition"
+verify "cpuset.cpus.partition rejects invalid types" \
+    "echo abc > cpuset.cpus.partition 2>/dev/null || echo failed" \
+    "failed"
+echo "root" > cpuset.cpus.partition
+PARTITION=$(cat cpuset.cpus.partition)
+echo "cpuset.cpus.partition: $PARTITION"
+case "$PARTITION" in
+    "root" | "root invalid ("*")")
+        echo "Verified"
+        ;;
+    *)
+        echo "Error: unexpected partition type"
+        exit 1
+        ;;
+esac
+echo "member" > cpuset.cpus.partition
+verify "cpuset.cpus.partition is reset to member" \
+    "cat cpuset.cpus.partition" \
+    "member"
+
+log_step "4.5.6 Clear cpuset.cpus"
+echo "" > cpuset.cpus
+verify "cpuset.cpus.effective falls back to the parent" \
+    "cat cpuset.cpus.effective" \
+    "$ROOT_EFFECTIVE"
+
+# -- 4.6 io sub-controller -----------------------------------------------------
+
+log_section "Section 4.6: io sub-controller"
+
+IO_DISK="/dev/vda"
+
+log_step "4.6.1 Enable io in root"
+echo "+io" > "$CGROUP_ROOT/cgroup.subtree_control"
+cd "$CGROUP_ROOT/$CGROUP_NAME"
+
+log_step "4.6.2 Check the defaults"
+verify "io.max is empty by default" \
+    "cat io.max" \
+    ""
+verify "io.weight is 100 by default" \
+    "cat io.weight" \
+    "default 100"
+
+log_step "4.6.3 Write io.weight"
+echo "200" > io.weight
+verify "io.weight accepts a default weight" \
+    "cat io.weight" \
+    "default 200"
+verify "io.weight rejects out-of-range weights" \
+    "echo 0 > io.weight 2>/dev/null || echo failed" \
+    "failed"
+echo "default 100" > io.weight
+
+log_step "4.6.4 Write io.max"
+IO_DEV="$((0x$(stat -c %t $IO_DISK))):$((0x$(stat -c %T $IO_DISK)))"
+echo "Device number of $IO_DISK: $IO_DEV"
+echo "$IO_DEV rbps=1048576 wiops=100" > io.max
+verify "io.max accepts per-device limits" \
+    "cat io.max" \
+    "$IO_DEV rbps=1048576 wbps=max riops=max wiops=100"
+echo "$IO_DEV wiops=max" > io.max
+verify "io.max keeps the unspecified limits" \
+    "cat io.max" \
+    "$IO_DEV rbps=1048576 wbps=max riops=max wiops=max"
+verify "io.max rejects nonexistent devices" \
+    "echo '4095:1048575 rbps=1' > io.max 2>/dev/null || echo failed" \
+    "failed"
+verify "io.max rejects invalid limits" \
+    "echo '$IO_DEV rbps=abc' > io.max 2>/dev/null || echo failed" \
+    "failed"
+
+log_step "4.6.5 Throttle the reads from the device"
+START_TIME=$(date +%s)
+sh -c "echo \$\$ > cgroup.procs; dd if=$IO_DISK of=/dev/null bs=4096 count=768 2>/dev/null"
+END_TIME=$(date +%s)
+echo "Reading 3 MiB at 1 MiB/s takes $((END_TIME - START_TIME)) seconds"
+if [ $((END_TIME - START_TIME)) -lt 2 ]; then
+    echo "Error: the reads are not throttled"
+    exit 1
+else
+    echo "Verified"
+fi
+verify_ge "io.stat counts the read bytes" \
+    "grep '^$IO_DEV ' io.stat | sed 's/.*rbytes=\([0-9]*\).*/\1/'" \
+    3145728
+
+log_step "4.6.6 Remove the limits"
+echo "$IO_DEV rbps=max" > io.max
+verify "io.max is empty after removing the limits" \
+    "cat io.max" \
+    ""
+
+# --- Section 5: Freezer and kill ----------------------------------------------
+
+log_section "Section 5: Freezer and kill"
+
+FREEZER_CGROUP="$CGROUP_ROOT/freezer"
+COUNTER_FILE=$(mktemp)
+
+log_step "5.1 Create the freezer hierarchy"
+mkdir "$FREEZER_CGROUP"
+cd "$FREEZER_CGROUP"
+verify "cgroup.freeze is 0 by default" \
+    "cat cgroup.freeze" \
+    "0"
+verify "cgroup.events reports frozen 0 by default" \
+    "grep '^frozen' cgroup.events" \
+    "frozen 0"
+verify "cgroup.freeze rejects invalid values" \
+    "echo 2 > cgroup.freeze 2>/dev/null || echo failed" \
+    "failed"
+
+log_step "5.2 Freeze a running task"
+sh -c '
+echo $$ > cgroup.procs
+i=0
+while :; do
+    i=$((i + 1))
+    echo $i > "$1"
+done
+' sh "$COUNTER_FILE" &
+BUSY_PID=$!
+sleep 1
+
+echo 1 > cgroup.freeze
+sleep 1
+verify "cgroup.freeze is 1 after freezing" \
+    "cat cgroup.freeze" \
+    "1"
+verify "cgroup.events reports frozen 1 after freezing" \
+    "grep '^frozen' cgroup.events" \
+    "frozen 1"
+COUNT_BEFORE=$(cat "$COUNTER_FILE")
+sleep 1
+verify "the frozen task makes no progress" \
+    "cat $COUNTER_FILE" \
+    "$COUNT_BEFORE"
+
+log_step "5.3 Thaw the task"
+echo 0 > cgroup.freeze
+sleep 1
+verify "cgroup.events reports frozen 0 after thawing" \
+    "grep '^frozen' cgroup.events" \
+    "frozen 0"
+verify_ge "the thawed task makes progress" \
+    "cat $COUNTER_FILE" \
+    $((COUNT_BEFORE + 1))
+
+log_step "5.4 Kill all tasks, including forking ones"
+sh -c '
+echo $$ > cgroup.procs
+while :; do
+    ( : )
+done
+' &
+FORK_PID=$!
+sleep 1
+
+verify "cgroup.kill rejects values other than 1" \
+    "echo 0 > cgroup.kill 2>/dev/null || echo failed" \
+    "failed"
+echo 1 > cgroup.kill
+wait "$BUSY_PID" 2>/dev/null || true
+wait "$FORK_PID" 2>/dev/null || true
+BUSY_PID=""
+sleep 1
+verify "no tasks are left after killing" \
+    "cat cgroup.procs" \
+    ""
+verify "cgroup.events reports populated 0 after killing" \
+    "grep '^populated' cgroup.events" \
+    "populated 0"
+
+log_step "5.5 Remove the freezer hierarchy"
+cd "$CGROUP_ROOT"
+rmdir "$FREEZER_CGROUP"
+rm -f "$COUNTER_FILE"
+
+# --- Section 6: Teardown ------------------------------------------------------
+
+log_section "Section 6: Teardown"
+
+log_step "6.1 Move process 1 back to root"
 cd "$CGROUP_ROOT"
 echo $PROCESS_ID > cgroup.procs
 verify "Process 1 back in root cgroup" \
     "grep -a '0::' /proc/$PROCESS_ID/cgroup" \
     "0::/"
 
-log_step "5.2 Remove user hierarchy"
+log_step "6.2 Remove user hierarchy"
 rmdir "$CGROUP_NAME"
 verify "user hierarchy removed" \
     "ls -d $CGROUP_NAME" \
@@ -138,31 +138,31 @@ FN_TEST(clone_thread)
 END_TEST()
 
 // The init process in each PID namespaces can not specify the CLONE_PARENT flags.
-// The whole test case is commented out since Asterinas does not support PID namespace.
-// FN_TEST(clone_init_process)
-// {
-// 	struct clone_args args = { .flags = CLONE_NEWPID };
-//
-// 	int child_pid = TEST_SUCC(sys_clone3(&args));
-//
-// 	if (child_pid == 0) {
-// 		// Child process
-// 		CHECK_WITH(getpid(), _ret == 1);
-//
-// 		args.flags = CLONE_PARENT;
-// 		CHECK_WITH(sys_clone3(&args), errno == EINVAL);
-//
-// 		args.flags = CLONE_PARENT | CLONE_THREAD | CLONE_VM |
-// 			     CLONE_SIGHAND;
-// 		CHECK_WITH(sys_clone3(&args), errno == EINVAL);
-//
-// 		exit(EXIT_SUCCESS);
-// 	}
-//
-// 	int status = 0;
-// 	TEST_RES(wait4(-1, &status, 0, NULL),
-// 		 _ret == child_pid && WIFEXITED(status) &&
-// 			 WEXITSTATUS(status) == EXIT_SUCCESS);
-// 	TEST_ERRNO(wait4(-1, NULL, 0, NULL), ECHILD);
-// }
-// END_TEST()
+FN_TEST(clone_init_process)
+{
+	struct clone_args args = { .flags = CLONE_NEWPID,
+				   .exit_signal = SIGCHLD };
+
+	int child_pid = TEST_SUCC(sys_clone3(&args));
+
+	if (child_pid == 0) {
+		// Child process
+		CHECK_WITH(getpid(), _ret == 1);
+
+		args.flags = CLONE_PARENT;
+		CHECK_WITH(sys_clone3(&args), errno == EINVAL);
+
+		args.flags = CLONE_PARENT | CLONE_THREAD | CLONE_VM |
+			     CLONE_SIGHAND;
+		CHECK_WITH(sys_clone3(&args), errno == EINVAL);
+
+		exit(EXIT_SUCCESS);
+	}
+
+	int status = 0;
+	TEST_RES(wait4(-1, &status, 0, NULL),
+		 _ret == child_pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+	TEST_ERRNO(wait4(-1, NULL, 0, NULL), ECHILD);
+}
+END_TEST()
@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define BINFMT_DIR "/proc/sys/fs/binfmt_misc"
+#define INTERP_PATH "/test/process/execve/binfmt_misc_interp"
+#define MAGIC_BINARY "/tmp/binfmt_misc_magic"
+#define EXT_BINARY "/tmp/binfmt_misc_binary.bfx"
+
+static int write_file(const char *path, const char *content)
+{
+	int fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	ssize_t len = write(fd, content, strlen(content));
+	close(fd);
+
+	return len < 0 ? -1 : 0;
+}
+
+static int register_rule(const char *rule)
+{
+	return write_file(BINFMT_DIR "/register", rule);
+}
+
+static int check_file(const char *path, const char *expected)
+{
+	char buf[256];
+
+	int fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	memset(buf, 0, sizeof(buf));
+	ssize_t len = read(fd, buf, sizeof(buf) - 1);
+	close(fd);
+	if (len < 0)
+		return -1;
+
+	return strcmp(buf, expected) == 0 ? 0 : -1;
+}
+
+static int create_binary(const char *path, const char *content)
+{
+	int fd = open(path, O_WRONLY | O_CREAT | O_TRUNC, 0755);
+	if (fd < 0)
+		return -1;
+
+	ssize_t len = write(fd, content, strlen(content));
+	close(fd);
+
+	return len < 0 ? -1 : 0;
+}
+
+// Executes the binary and compares its output with `expected`. Returns the
+// error number if the binary cannot be executed.
+static int run_binary(const char *path, const char *expected)
+{
+	char buf[512];
+	int pipefd[2];
+	int status;
+
+	if (pipe(pipefd) < 0)
+		return -1;
+
+	pid_t pid = fork();
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		char *const argv[] = { "argv0", "arg1", NULL };
+
+		close(pipefd[0]);
+		dup2(pipefd[1], STDOUT_FILENO);
+		close(pipefd[1]);
+
+		execv(path, argv);
+		_exit(errno);
+	}
+
+	close(pipefd[1]);
+	memset(buf, 0, sizeof(buf));
+	size_t len = 0;
+	ssize_t ret;
+	while ((ret = read(pipefd[0], buf + len, sizeof(buf) - 1 - len)) > 0)
+		len += ret;
+	close(pipefd[0]);
+
+	if (waitpid(pid, &status, 0) != pid || !WIFEXITED(status))
+		return -1;
+	if (WEXITSTATUS(status) != 0)
+		return WEXITSTATUS(status);
+
+	return strcmp(buf, expected) == 0 ? 0 : -1;
+}
+
+FN_SETUP(mount)
+{
+	CHECK(mount("binfmt_misc", BINFMT_DIR, "binfmt_misc", 0, NULL));
+
+	CHECK(create_binary(MAGIC_BINARY, "\x7f"
+					  "BFM binary\n"));
+	CHECK(create_binary(EXT_BINARY, "extension binary\n"));
+}
+END_SETUP()
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(invalid_rules)
+{
+	TEST_RES(check_file(BINFMT_DIR "/status", "enabled\n"), _ret == 0);
+
+	// Too short
+	TEST_ERRNO(register_rule(":a:M::x::"), EINVAL);
+	// Invalid names
+	TEST_ERRNO(register_rule(":.:M::x::" INTERP_PATH ":"), EINVAL);
+	TEST_ERRNO(register_rule(":a/b:M::x::" INTERP_PATH ":"), EINVAL);
+	// Invalid type
+	TEST_ERRNO(register_rule(":a:X::x::" INTERP_PATH ":"), EINVAL);
+	// The mask does not match the magic
+	TEST_ERRNO(register_rule(":a:M::xy:\\xff:" INTERP_PATH ":"), EINVAL);
+	// The magic is out of range
+	TEST_ERRNO(register_rule(":a:M:255:xy::" INTERP_PATH ":"), EINVAL);
+	// Empty interpreter
+	TEST_ERRNO(register_rule(":abcdefg:M::x:::"), EINVAL);
+	// Invalid flags
+	TEST_ERRNO(register_rule(":a:M::x::" INTERP_PATH ":X"), EINVAL);
+	// The interpreter does not exist with the `F` flag
+	TEST_ERRNO(register_rule(":a:M::x::/nonexistent:F"), ENOENT);
+
+	TEST_ERRNO(open(BINFMT_DIR "/a", O_RDONLY), ENOENT);
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(magic_rule)
+{
+	// Without any rules, the binary is neither an ELF nor a script.
+	TEST_RES(run_binary(MAGIC_BINARY, ""), _ret == ENOEXEC);
+
+	TEST_SUCC(register_rule(":magic:M::\\x7fBFM::" INTERP_PATH ":\n"));
+	TEST_ERRNO(register_rule(":magic:M::\\x7fBFM::" INTERP_PATH ":"),
+		   EEXIST);
+	TEST_RES(check_file(BINFMT_DIR "/magic", "enabled\n"
+						 "interpreter " INTERP_PATH "\n"
+						 "flags: \n"
+						 "offset 0\n"
+						 "magic 7f42464d\n"),
+		 _ret == 0);
+
+	// `argv[0]` is replaced by the path of the binary.
+	TEST_RES(run_binary(MAGIC_BINARY, INTERP_PATH "\n" MAGIC_BINARY "\n"
+						      "arg1\n"
+						      "no execfd\n"),
+		 _ret == 0);
+
+	// Disabled rules are ignored.
+	TEST_SUCC(write_file(BINFMT_DIR "/magic", "0"));
+	TEST_RES(check_file(BINFMT_DIR "/magic", "disabled\n"
+						 "interpreter " INTERP_PATH "\n"
+						 "flags: \n"
+						 "offset 0\n"
+						 "magic 7f42464d\n"),
+		 _ret == 0);
+	TEST_RES(run_binary(MAGIC_BINARY, ""), _ret == ENOEXEC);
+	TEST_ERRNO(write_file(BINFMT_DIR "/magic", "2"), EINVAL);
+	TEST_SUCC(write_file(BINFMT_DIR "/magic", "1\n"));
+	TEST_RES(run_binary(MAGIC_BINARY, INTERP_PATH "\n" MAGIC_BINARY "\n"
+						      "arg1\n"
+						      "no execfd\n"),
+		 _ret == 0);
+
+	// Removed rules are gone.
+	TEST_SUCC(write_file(BINFMT_DIR "/magic", "-1"));
+	TEST_ERRNO(open(BINFMT_DIR "/magic", O_RDONLY), ENOENT);
+	TEST_RES(run_binary(MAGIC_BINARY, ""), _ret == ENOEXEC);
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(extension_rule)
+{
+	TEST_SUCC(register_rule(":ext:E::bfx::" INTERP_PATH ":POF"));
+	TEST_RES(check_file(BINFMT_DIR "/ext", "enabled\n"
+					       "interpreter " INTERP_PATH "\n"
+					       "flags: POF\n"
+					       "extension .bfx\n"),
+		 _ret == 0);
+
+	// `argv[0]` is preserved, and the binary is opened for the
+	// interpreter.
+	TEST_RES(run_binary(EXT_BINARY, INTERP_PATH "\n" EXT_BINARY "\n"
+					"argv0\n"
+					"arg1\n"
+					"execfd extension binary\n"),
+		 _ret == 0);
+
+	// The rule does not match other extensions.
+	TEST_RES(run_binary(MAGIC_BINARY, ""), _ret == ENOEXEC);
+
+	// Disabling binfmt_misc disables all rules.
+	TEST_SUCC(write_file(BINFMT_DIR "/status", "0"));
+	TEST_RES(check_file(BINFMT_DIR "/status", "disabled\n"), _ret == 0);
+	TEST_RES(run_binary(EXT_BINARY, ""), _ret == ENOEXEC);
+	TEST_SUCC(write_file(BINFMT_DIR "/status", "1"));
+	TEST_RES(check_file(BINFMT_DIR "/status", "enabled\n"), _ret == 0);
+
+	// Removing all rules
+	TEST_SUCC(write_file(BINFMT_DIR "/status", "-1"));
+	TEST_ERRNO(open(BINFMT_DIR "/ext", O_RDONLY), ENOENT);
+	TEST_RES(run_binary(EXT_BINARY, ""), _ret == ENOEXEC);
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(MAGIC_BINARY));
+	CHECK(unlink(EXT_BINARY));
+
+	CHECK(umount(BINFMT_DIR));
+}
+END_SETUP()
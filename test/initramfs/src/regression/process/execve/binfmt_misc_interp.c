@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+
+// An interpreter for the binaries handed over by binfmt_misc rules in
+// `binfmt_misc.c`. It prints its arguments and, if the binary has been opened
+// for it, the content of the binary.
+
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/auxv.h>
+#include <unistd.h>
+
+int main(int argc, char *argv[])
+{
+	for (int i = 0; i < argc; i++)
+		printf("%s\n", argv[i]);
+
+	errno = 0;
+	unsigned long exec_fd = getauxval(AT_EXECFD);
+	if (errno == ENOENT) {
+		printf("no execfd\n");
+		return 0;
+	}
+
+	char buf[64];
+	memset(buf, 0, sizeof(buf));
+	if (pread(exec_fd, buf, sizeof(buf) - 1, 0) < 0)
+		return 1;
+	printf("execfd %s", buf);
+
+	return 0;
+}
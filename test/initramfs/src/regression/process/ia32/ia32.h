@@ -0,0 +1,345 @@
+/* SPDX-License-Identifier: MPL-2.0 */
+
+#ifndef IA32_H
+#define IA32_H
+
+/*
+ * A minimal runtime for regression tests of 32-bit x86 programs.
+ *
+ * The tests are built as freestanding static binaries with `-m32`, so they do
+ * not depend on a 32-bit C library. System calls are issued directly with the
+ * `int 0x80` instruction or via `__kernel_vsyscall` in the 32-bit vDSO, and the
+ * user-space structures are defined here with their 32-bit layouts.
+ *
+ * The test macros follow "../../common/test.h". Since there is no C library,
+ * the system call wrappers return the negated error number on failure instead
+ * of setting `errno`, and the test functions must be called from `main()`.
+ */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define SYS_exit 1
+#define SYS_read 3
+#define SYS_write 4
+#define SYS_open 5
+#define SYS_close 6
+#define SYS_unlink 10
+#define SYS_getpid 20
+#define SYS_kill 37
+#define SYS_pipe 42
+#define SYS_readv 145
+#define SYS_writev 146
+#define SYS_nanosleep 162
+#define SYS_rt_sigreturn 173
+#define SYS_rt_sigaction 174
+#define SYS_rt_sigprocmask 175
+#define SYS_pwrite64 181
+#define SYS_mmap2 192
+#define SYS_ftruncate64 194
+#define SYS_stat64 195
+#define SYS_fstat64 197
+#define SYS_fcntl64 221
+#define SYS_gettid 224
+#define SYS_set_thread_area 243
+#define SYS_get_thread_area 244
+#define SYS_exit_group 252
+#define SYS_clock_gettime 265
+#define SYS_memfd_create 356
+#define SYS_socketpair 360
+#define SYS_sendmsg 370
+#define SYS_recvmsg 372
+
+#define EBADF 9
+#define EFAULT 14
+#define EINVAL 22
+#define ENOSYS 38
+#define EOVERFLOW 75
+
+#define AT_NULL 0
+#define AT_SYSINFO 32
+
+/*
+ * System calls
+ */
+
+/* The address of `__kernel_vsyscall`, which is passed in `AT_SYSINFO`. */
+uintptr_t kernel_vsyscall;
+
+long int80_syscall6(long nr, long a1, long a2, long a3, long a4, long a5,
+		    long a6);
+long vsyscall6(long nr, long a1, long a2, long a3, long a4, long a5, long a6);
+
+/*
+ * Both functions load all six arguments, including `ebp`, and preserve the
+ * callee-saved registers as required by the cdecl calling convention.
+ */
+__asm__(".text\n"
+	".global int80_syscall6\n"
+	"int80_syscall6:\n"
+	"	push %ebp\n"
+	"	push %edi\n"
+	"	push %esi\n"
+	"	push %ebx\n"
+	"	mov 20(%esp), %eax\n"
+	"	mov 24(%esp), %ebx\n"
+	"	mov 28(%esp), %ecx\n"
+	"	mov 32(%esp), %edx\n"
+	"	mov 36(%esp), %esi\n"
+	"	mov 40(%esp), %edi\n"
+	"	mov 44(%esp), %ebp\n"
+	"	int $0x80\n"
+	"	pop %ebx\n"
+	"	pop %esi\n"
+	"	pop %edi\n"
+	"	pop %ebp\n"
+	"	ret\n"
+	".global vsyscall6\n"
+	"vsyscall6:\n"
+	"	push %ebp\n"
+	"	push %edi\n"
+	"	push %esi\n"
+	"	push %ebx\n"
+	"	mov 20(%esp), %eax\n"
+	"	mov 24(%esp), %ebx\n"
+	"	mov 28(%esp), %ecx\n"
+	"	mov 32(%esp), %edx\n"
+	"	mov 36(%esp), %esi\n"
+	"	mov 40(%esp), %edi\n"
+	"	mov 44(%esp), %ebp\n"
+	"	call *kernel_vsyscall\n"
+	"	pop %ebx\n"
+	"	pop %esi\n"
+	"	pop %edi\n"
+	"	pop %ebp\n"
+	"	ret\n");
+
+#define __SYSCALL6(nr, a1, a2, a3, a4, a5, a6, ...)                          \
+	int80_syscall6((nr), (long)(a1), (long)(a2), (long)(a3), (long)(a4), \
+		       (long)(a5), (long)(a6))
+
+/** Issues a system call with the `int 0x80` instruction. */
+#define syscall(...) __SYSCALL6(__VA_ARGS__, 0, 0, 0, 0, 0, 0)
+
+/*
+ * Runtime
+ */
+
+int main(void);
+
+/* The compiler may emit calls to these functions. */
+
+void *memset(void *s, int c, size_t n)
+{
+	unsigned char *p = s;
+
+	while (n--)
+		*p++ = c;
+	return s;
+}
+
+void *memcpy(void *dest, const void *src, size_t n)
+{
+	unsigned char *d = dest;
+	const unsigned char *s = src;
+
+	while (n--)
+		*d++ = *s++;
+	return dest;
+}
+
+static size_t strlen(const char *s)
+{
+	size_t len = 0;
+
+	while (s[len])
+		len++;
+	return len;
+}
+
+int memcmp(const void *s1, const void *s2, size_t n)
+{
+	const unsigned char *p1 = s1;
+	const unsigned char *p2 = s2;
+
+	for (; n; n--, p1++, p2++) {
+		if (*p1 != *p2)
+			return *p1 - *p2;
+	}
+	return 0;
+}
+
+__attribute__((noreturn)) static void exit_group(int status)
+{
+	for (;;)
+		syscall(SYS_exit_group, status);
+}
+
+static void print_str(const char *s)
+{
+	syscall(SYS_write, 2, s, strlen(s));
+}
+
+static void print_int(long val)
+{
+	char buf[16];
+	char *p = buf + sizeof(buf);
+	unsigned long abs = val < 0 ? -(unsigned long)val : (unsigned long)val;
+
+	*--p = '\0';
+	do {
+		*--p = '0' + abs % 10;
+		abs /= 10;
+	} while (abs);
+	if (val < 0)
+		*--p = '-';
+	print_str(p);
+}
+
+void start_c(uint32_t *sp);
+
+void start_c(uint32_t *sp)
+{
+	uint32_t argc = sp[0];
+	uint32_t *envp = sp + 1 + argc + 1;
+	uint32_t *auxv = envp;
+
+	while (*auxv)
+		auxv++;
+	for (auxv++; auxv[0] != AT_NULL; auxv += 2) {
+		if (auxv[0] == AT_SYSINFO)
+			kernel_vsyscall = auxv[1];
+	}
+
+	exit_group(main());
+}
+
+__asm__(".text\n"
+	".global _start\n"
+	"_start:\n"
+	"	xor %ebp, %ebp\n"
+	"	mov %esp, %eax\n"
+	"	and $-16, %esp\n"
+	"	sub $12, %esp\n"
+	"	push %eax\n"
+	"	call start_c\n"
+	"	hlt\n");
+
+/*
+ * Test framework
+ */
+
+static int __total_failures;
+
+#define __TEST_SUMMARY()              \
+	print_str(__func__);          \
+	print_str(" summary: ");      \
+	print_int(__tests_passed);    \
+	print_str(" tests passed, "); \
+	print_int(__tests_failed);    \
+	print_str(" tests failed\n"); \
+	__total_failures += __tests_failed;
+
+/** Starts the definition of a test function. */
+#define FN_TEST(name)                 \
+	static void test_##name(void) \
+	{                             \
+		int __tests_passed = 0, __tests_failed = 0;
+
+/** Ends the definition of a test function. */
+#define END_TEST()        \
+	__TEST_SUMMARY(); \
+	}
+
+/** Skips the current test if the condition is true. */
+#define SKIP_TEST_IF(cond)                                            \
+	({                                                            \
+		if (cond) {                                           \
+			print_str(__func__);                          \
+			print_str(" skipped: `" #cond "` is true\n"); \
+			__TEST_SUMMARY();                             \
+			return;                                       \
+		}                                                     \
+	})
+
+/** Runs a test function. */
+#define RUN_TEST(name) test_##name()
+
+/** Returns the exit code of the test program. */
+#define TEST_EXIT_CODE() (__total_failures ? 1 : 0)
+
+/** Returns the error number if the system call fails, or zero otherwise. */
+#define __ERRNO(ret) ((ret) < 0 && (ret) > -4096 ? -(ret) : 0)
+
+#define __TEST_REPORT(func_str, result, errno) \
+	print_str(__func__);                   \
+	print_str(": `");                      \
+	print_str(func_str);                   \
+	print_str("` ");                       \
+	print_str(result);                     \
+	print_str(" [got errno ");             \
+	print_int(errno);                      \
+	print_str("]\n");
+
+#define __TEST(func, func_str, err, cond)                               \
+	__auto_type _ret = (func);                                      \
+	if (__ERRNO((long)_ret) != (err) || !(cond)) {                  \
+		__tests_failed++;                                       \
+		__TEST_REPORT(func_str, "failed", __ERRNO((long)_ret)); \
+	} else {                                                        \
+		__tests_passed++;                                       \
+		__TEST_REPORT(func_str, "passed", __ERRNO((long)_ret)); \
+	}
+
+/**
+ * Makes a system call and checks its result.
+ *
+ * A test failure will be reported if the system call does not fail with the
+ * specified error number or the specified condition does not meet.
+ *
+ * The return value of the system call can be accessed with a local variable
+ * named _ret.
+ */
+#define TEST(func, err, cond)                   \
+	({                                      \
+		__TEST(func, #func, err, cond); \
+		_ret;                           \
+	})
+
+/** Makes a system call and checks whether it succeeds. */
+#define TEST_SUCC(func)                    \
+	({                                 \
+		__TEST(func, #func, 0, 1); \
+		_ret;                      \
+	})
+
+/** Makes a system call and checks whether it fails in the specified way. */
+#define TEST_ERRNO(func, err)                \
+	({                                   \
+		__TEST(func, #func, err, 1); \
+		_ret;                        \
+	})
+
+/** Makes a system call and checks whether it produces expected results. */
+#define TEST_RES(func, cond)                  \
+	({                                    \
+		__TEST(func, #func, 0, cond); \
+		_ret;                         \
+	})
+
+/**
+ * Makes a system call and checks its return value is not an error.
+ *
+ * The execution will be aborted if the check fails.
+ */
+#define CHECK(func)                                                     \
+	({                                                              \
+		__auto_type _ret = (func);                              \
+		if (__ERRNO((long)_ret)) {                              \
+			print_str("fatal error: `" #func "` failed\n"); \
+			exit_group(1);                                  \
+		}                                                       \
+		_ret;                                                   \
+	})
+
+#endif
@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: MPL-2.0
+
+/*
+ * Tests that the user-space structures of 32-bit programs are translated
+ * correctly.
+ */
+
+#include "ia32.h"
+
+#define O_RDWR 02
+#define O_CREAT 0100
+#define S_IFMT 0170000
+#define S_IFREG 0100000
+#define S_IFDIR 0040000
+
+#define CLOCK_MONOTONIC 1
+
+#define SIGUSR1 10
+#define SA_SIGINFO 0x00000004
+#define SA_RESTORER 0x04000000
+
+#define AF_UNIX 1
+#define SOCK_STREAM 1
+#define SOL_SOCKET 1
+#define SCM_RIGHTS 1
+
+#define F_GETLK 5
+#define F_GETLK64 12
+#define F_SETLK64 13
+#define F_WRLCK 1
+#define F_UNLCK 2
+#define SEEK_SET 0
+
+#define SYS_fork 2
+#define SYS_wait4 114
+
+struct stat64 {
+	uint64_t st_dev;
+	uint8_t __pad0[4];
+	uint32_t __st_ino;
+	uint32_t st_mode;
+	uint32_t st_nlink;
+	uint32_t st_uid;
+	uint32_t st_gid;
+	uint64_t st_rdev;
+	uint8_t __pad3[4];
+	int64_t st_size;
+	uint32_t st_blksize;
+	uint64_t st_blocks;
+	uint32_t st_atime;
+	uint32_t st_atime_nsec;
+	uint32_t st_mtime;
+	uint32_t st_mtime_nsec;
+	uint32_t st_ctime;
+	uint32_t st_ctime_nsec;
+	uint64_t st_ino;
+} __attribute__((packed));
+_Static_assert(sizeof(struct stat64) == 96, "");
+
+struct timespec {
+	int32_t tv_sec;
+	int32_t tv_nsec;
+};
+
+struct sigaction {
+	uint32_t sa_handler;
+	uint32_t sa_flags;
+	uint32_t sa_restorer;
+	uint32_t sa_mask[2];
+};
+
+struct iovec {
+	void *iov_base;
+	uint32_t iov_len;
+};
+
+struct msghdr {
+	void *msg_name;
+	uint32_t msg_namelen;
+	struct iovec *msg_iov;
+	uint32_t msg_iovlen;
+	void *msg_control;
+	uint32_t msg_controllen;
+	uint32_t msg_flags;
+};
+
+struct cmsghdr {
+	uint32_t cmsg_len;
+	int32_t cmsg_level;
+	int32_t cmsg_type;
+};
+
+struct flock {
+	int16_t l_type;
+	int16_t l_whence;
+	int32_t l_start;
+	int32_t l_len;
+	int32_t l_pid;
+};
+
+struct flock64 {
+	int16_t l_type;
+	int16_t l_whence;
+	int64_t l_start;
+	int64_t l_len;
+	int32_t l_pid;
+} __attribute__((packed));
+_Static_assert(sizeof(struct flock64) == 24, "");
+
+FN_TEST(stat64)
+{
+	int fd;
+	struct stat64 st;
+
+	fd = CHECK(syscall(SYS_memfd_create, "ia32_stat64", 0));
+	CHECK(syscall(SYS_ftruncate64, fd, 12345, 0));
+
+	TEST_RES(syscall(SYS_fstat64, fd, &st),
+		 (st.st_mode & S_IFMT) == S_IFREG && st.st_size == 12345 &&
+			 st.st_blksize != 0 &&
+			 st.__st_ino == (uint32_t)st.st_ino);
+	TEST_RES(syscall(SYS_stat64, "/", &st),
+		 (st.st_mode & S_IFMT) == S_IFDIR && st.st_nlink >= 2 &&
+			 st.__st_ino == (uint32_t)st.st_ino);
+
+	TEST_SUCC(syscall(SYS_close, fd));
+}
+END_TEST()
+
+FN_TEST(timespec)
+{
+	struct timespec ts1 = { -1, -1 };
+	struct timespec ts2 = { -1, -1 };
+	struct timespec req = { 0, 1000000 };
+
+	TEST_RES(syscall(SYS_clock_gettime, CLOCK_MONOTONIC, &ts1),
+		 ts1.tv_sec >= 0 && ts1.tv_nsec >= 0 &&
+			 ts1.tv_nsec < 1000000000);
+	TEST_SUCC(syscall(SYS_nanosleep, &req, NULL));
+	TEST_RES(syscall(SYS_clock_gettime, CLOCK_MONOTONIC, &ts2),
+		 ts2.tv_sec > ts1.tv_sec ||
+			 (ts2.tv_sec == ts1.tv_sec &&
+			  ts2.tv_nsec >= ts1.tv_nsec + 1000000));
+
+	req.tv_nsec = 1000000000;
+	TEST_ERRNO(syscall(SYS_nanosleep, &req, NULL), EINVAL);
+}
+END_TEST()
+
+FN_TEST(sigaction)
+{
+	struct sigaction act = {
+		.sa_handler = 0x12345678,
+		.sa_flags = SA_SIGINFO | SA_RESTORER,
+		.sa_restorer = 0x87654321,
+		// SIGUSR2 and SIGRTMIN + 7
+		.sa_mask = { 1 << 11, 1 << 7 },
+	};
+	struct sigaction old_act;
+	struct sigaction dfl_act = {};
+
+	TEST_SUCC(syscall(SYS_rt_sigaction, SIGUSR1, &act, NULL, 8));
+	TEST_RES(syscall(SYS_rt_sigaction, SIGUSR1, &dfl_act, &old_act, 8),
+		 memcmp(&act, &old_act, sizeof(act)) == 0);
+	TEST_RES(syscall(SYS_rt_sigaction, SIGUSR1, NULL, &old_act, 8),
+		 memcmp(&dfl_act, &old_act, sizeof(dfl_act)) == 0);
+
+	TEST_ERRNO(syscall(SYS_rt_sigaction, SIGUSR1, &act, NULL, 4), EINVAL);
+}
+END_TEST()
+
+FN_TEST(iovec)
+{
+	int fds[2];
+	char buf1[3], buf2[4];
+	struct iovec write_iov[3] = {
+		{ "ab", 2 },
+		{ "", 0 },
+		{ "cdef", 4 },
+	};
+	struct iovec read_iov[2] = {
+		{ buf1, sizeof(buf1) },
+		{ buf2, sizeof(buf2) },
+	};
+
+	CHECK(syscall(SYS_pipe, fds));
+
+	TEST_RES(syscall(SYS_writev, fds[1], write_iov, 3), _ret == 6);
+	TEST_RES(syscall(SYS_readv, fds[0], read_iov, 2),
+		 _ret == 6 && memcmp(buf1, "abc", 3) == 0 &&
+			 memcmp(buf2, "def", 3) == 0);
+
+	TEST_SUCC(syscall(SYS_close, fds[0]));
+	TEST_SUCC(syscall(SYS_close, fds[1]));
+}
+END_TEST()
+
+FN_TEST(cmsg)
+{
+	int sv[2], fds[2];
+	char data = 'x', buf[2];
+	struct iovec iov = { &data, 1 };
+	struct {
+		struct cmsghdr hdr;
+		int32_t fds[2];
+	} control = {
+		.hdr = { sizeof(control), SOL_SOCKET, SCM_RIGHTS },
+	};
+	struct {
+		struct cmsghdr hdr;
+		int32_t fds[4];
+	} recv_control;
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = &control,
+		.msg_controllen = sizeof(control),
+	};
+
+	CHECK(syscall(SYS_socketpair, AF_UNIX, SOCK_STREAM, 0, sv));
+	CHECK(syscall(SYS_pipe, fds));
+
+	// The size of the header is 12 bytes for 32-bit programs, so the file
+	// descriptors start at offset 12.
+	control.fds[0] = fds[0];
+	control.fds[1] = fds[1];
+	TEST_RES(syscall(SYS_sendmsg, sv[0], &msg, 0), _ret == 1);
+
+	msg.msg_control = &recv_control;
+	msg.msg_controllen = sizeof(recv_control);
+	TEST_RES(syscall(SYS_recvmsg, sv[1], &msg, 0),
+		 _ret == 1 && msg.msg_controllen == 20 &&
+			 recv_control.hdr.cmsg_len == 20 &&
+			 recv_control.hdr.cmsg_level == SOL_SOCKET &&
+			 recv_control.hdr.cmsg_type == SCM_RIGHTS);
+
+	TEST_RES(syscall(SYS_write, recv_control.fds[1], "y", 1), _ret == 1);
+	TEST_RES(syscall(SYS_read, recv_control.fds[0], buf, 2),
+		 _ret == 1 && buf[0] == 'y');
+
+	control.hdr.cmsg_len = sizeof(struct cmsghdr) - 1;
+	msg.msg_control = &control;
+	msg.msg_controllen = sizeof(control);
+	TEST_ERRNO(syscall(SYS_sendmsg, sv[0], &msg, 0), EINVAL);
+
+	TEST_SUCC(syscall(SYS_close, recv_control.fds[0]));
+	TEST_SUCC(syscall(SYS_close, recv_control.fds[1]));
+	TEST_SUCC(syscall(SYS_close, fds[0]));
+	TEST_SUCC(syscall(SYS_close, fds[1]));
+	TEST_SUCC(syscall(SYS_close, sv[0]));
+	TEST_SUCC(syscall(SYS_close, sv[1]));
+}
+END_TEST()
+
+static int getlk_in_child(int fd)
+{
+	int __tests_passed = 0, __tests_failed = 0;
+	struct flock lock = { F_WRLCK, SEEK_SET, 0, 0, 0 };
+	struct flock64 lock64 = { F_WRLCK, SEEK_SET, 0, 0, 0 };
+
+	// The start offset of the conflicting lock does not fit in 32 bits.
+	TEST_ERRNO(syscall(SYS_fcntl64, fd, F_GETLK, &lock), EOVERFLOW);
+	TEST_RES(syscall(SYS_fcntl64, fd, F_GETLK64, &lock64),
+		 lock64.l_type == F_WRLCK && lock64.l_start == 0x100000000LL &&
+			 lock64.l_len == 10);
+
+	(void)__tests_passed;
+	return __tests_failed;
+}
+
+#define FILE_NAME "/tmp/ia32_flock"
+
+FN_TEST(flock)
+{
+	int fd, pid, status;
+	struct flock64 lock64 = { F_WRLCK, SEEK_SET, 0x100000000LL, 10, 0 };
+
+	fd = CHECK(syscall(SYS_open, FILE_NAME, O_RDWR | O_CREAT, 0644));
+	TEST_SUCC(syscall(SYS_fcntl64, fd, F_SETLK64, &lock64));
+
+	pid = CHECK(syscall(SYS_fork));
+	if (pid == 0)
+		exit_group(getlk_in_child(fd));
+	TEST_RES(syscall(SYS_wait4, pid, &status, 0, NULL),
+		 _ret == pid && status == 0);
+
+	lock64.l_type = F_UNLCK;
+	TEST_SUCC(syscall(SYS_fcntl64, fd, F_SETLK64, &lock64));
+	TEST_SUCC(syscall(SYS_close, fd));
+	TEST_SUCC(syscall(SYS_unlink, FILE_NAME));
+}
+END_TEST()
+
+int main(void)
+{
+	RUN_TEST(stat64);
+	RUN_TEST(timespec);
+	RUN_TEST(sigaction);
+	RUN_TEST(iovec);
+	RUN_TEST(cmsg);
+	RUN_TEST(flock);
+	return TEST_EXIT_CODE();
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MPL-2.0
+
+/*
+ * Tests that signals are delivered to 32-bit programs with the 32-bit signal
+ * frames, and that the user contexts are restored by `rt_sigreturn`.
+ */
+
+#include "ia32.h"
+
+#define SIGUSR1 10
+#define SA_SIGINFO 0x00000004
+#define SA_RESTORER 0x04000000
+#define SIG_BLOCK 0
+#define SIG_UNBLOCK 1
+#define SI_USER 0
+
+#define ESI_MAGIC 0x13579bdf
+#define EDI_MAGIC 0x2468ace0
+#define EDI_MODIFIED 0xdeadbeef
+
+struct sigaction {
+	void *sa_handler;
+	uint32_t sa_flags;
+	void *sa_restorer;
+	uint32_t sa_mask[2];
+};
+
+struct siginfo {
+	int32_t si_signo;
+	int32_t si_errno;
+	int32_t si_code;
+	int32_t si_pid;
+	uint32_t si_uid;
+	uint32_t __pad[27];
+};
+_Static_assert(sizeof(struct siginfo) == 128, "");
+
+struct sigcontext {
+	uint16_t gs, __gsh;
+	uint16_t fs, __fsh;
+	uint16_t es, __esh;
+	uint16_t ds, __dsh;
+	uint32_t edi;
+	uint32_t esi;
+	uint32_t ebp;
+	uint32_t esp;
+	uint32_t ebx;
+	uint32_t edx;
+	uint32_t ecx;
+	uint32_t eax;
+	uint32_t trapno;
+	uint32_t err;
+	uint32_t eip;
+	uint16_t cs, __csh;
+	uint32_t eflags;
+	uint32_t esp_at_signal;
+	uint16_t ss, __ssh;
+	uint32_t fpstate;
+	uint32_t oldmask;
+	uint32_t cr2;
+};
+
+struct ucontext {
+	uint32_t uc_flags;
+	uint32_t uc_link;
+	uint32_t uc_stack[3];
+	struct sigcontext uc_mcontext;
+	uint32_t uc_sigmask[2];
+};
+
+void restore_rt(void);
+long kill_with_magic(int pid, int sig);
+
+/* The values of `esi` and `edi` after `kill_with_magic` returns. */
+uint32_t esi_after_kill;
+uint32_t edi_after_kill;
+
+__asm__(".text\n"
+	".global restore_rt\n"
+	"restore_rt:\n"
+	"	mov $173, %eax\n" // SYS_rt_sigreturn
+	"	int $0x80\n"
+	".global kill_with_magic\n"
+	"kill_with_magic:\n"
+	"	push %ebx\n"
+	"	push %esi\n"
+	"	push %edi\n"
+	"	mov $37, %eax\n" // SYS_kill
+	"	mov 16(%esp), %ebx\n"
+	"	mov 20(%esp), %ecx\n"
+	"	mov $0x13579bdf, %esi\n" // ESI_MAGIC
+	"	mov $0x2468ace0, %edi\n" // EDI_MAGIC
+	"	int $0x80\n"
+	"	mov %esi, esi_after_kill\n"
+	"	mov %edi, edi_after_kill\n"
+	"	pop %edi\n"
+	"	pop %esi\n"
+	"	pop %ebx\n"
+	"	ret\n");
+
+static volatile int handled_count;
+static volatile int modify_context;
+static struct siginfo received_info;
+static struct sigcontext received_context;
+
+static void handler(int sig, struct siginfo *info, struct ucontext *uc)
+{
+	received_info = *info;
+	received_context = uc->uc_mcontext;
+	if (modify_context)
+		uc->uc_mcontext.edi = EDI_MODIFIED;
+	handled_count++;
+}
+
+FN_TEST(deliver)
+{
+	struct sigaction act = {
+		.sa_handler = handler,
+		.sa_flags = SA_SIGINFO | SA_RESTORER,
+		.sa_restorer = restore_rt,
+	};
+	long pid = syscall(SYS_getpid);
+
+	TEST_SUCC(syscall(SYS_rt_sigaction, SIGUSR1, &act, NULL, 8));
+
+	handled_count = 0;
+	TEST_RES(kill_with_magic(pid, SIGUSR1),
+		 handled_count == 1 && received_info.si_signo == SIGUSR1 &&
+			 received_info.si_code == SI_USER &&
+			 received_info.si_pid == pid);
+	TEST_RES(0, received_context.esi == ESI_MAGIC &&
+			    received_context.edi == EDI_MAGIC &&
+			    received_context.eax == 0);
+	TEST_RES(0, esi_after_kill == ESI_MAGIC && edi_after_kill == EDI_MAGIC);
+}
+END_TEST()
+
+FN_TEST(sigreturn)
+{
+	long pid = syscall(SYS_getpid);
+
+	handled_count = 0;
+	modify_context = 1;
+	TEST_RES(kill_with_magic(pid, SIGUSR1), handled_count == 1);
+	TEST_RES(0, esi_after_kill == ESI_MAGIC &&
+			    edi_after_kill == EDI_MODIFIED);
+	modify_context = 0;
+}
+END_TEST()
+
+FN_TEST(block)
+{
+	uint32_t set[2] = { 1 << (SIGUSR1 - 1), 0 };
+	long pid = syscall(SYS_getpid);
+
+	handled_count = 0;
+	TEST_SUCC(syscall(SYS_rt_sigprocmask, SIG_BLOCK, set, NULL, 8));
+	TEST_RES(syscall(SYS_kill, pid, SIGUSR1), handled_count == 0);
+	TEST_RES(syscall(SYS_rt_sigprocmask, SIG_UNBLOCK, set, NULL, 8),
+		 handled_count == 1);
+}
+END_TEST()
+
+int main(void)
+{
+	RUN_TEST(deliver);
+	RUN_TEST(sigreturn);
+	RUN_TEST(block);
+	return TEST_EXIT_CODE();
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include "ia32.h"
+
+#define PAGE_SIZE 4096
+#define PROT_READ 0x1
+#define MAP_SHARED 0x01
+
+static const char message[] = "Hello from 32-bit programs";
+
+FN_TEST(int80)
+{
+	int fds[2];
+	char buf[sizeof(message)];
+	long pid;
+
+	pid = TEST_RES(syscall(SYS_getpid), _ret > 0);
+	TEST_RES(syscall(SYS_gettid), _ret == pid);
+
+	TEST_SUCC(syscall(SYS_pipe, fds));
+	TEST_RES(syscall(SYS_write, fds[1], message, sizeof(message)),
+		 _ret == sizeof(message));
+	TEST_RES(syscall(SYS_read, fds[0], buf, sizeof(buf)),
+		 _ret == sizeof(message) &&
+			 memcmp(buf, message, sizeof(message)) == 0);
+	TEST_ERRNO(syscall(SYS_write, fds[1], (void *)0x1000, 1), EFAULT);
+	TEST_SUCC(syscall(SYS_close, fds[0]));
+	TEST_SUCC(syscall(SYS_close, fds[1]));
+
+	TEST_ERRNO(syscall(SYS_close, fds[0]), EBADF);
+	TEST_ERRNO(syscall(0xffff), ENOSYS);
+}
+END_TEST()
+
+FN_TEST(vsyscall)
+{
+	int fd;
+
+	SKIP_TEST_IF(kernel_vsyscall == 0);
+
+	TEST_RES(vsyscall6(SYS_getpid, 0, 0, 0, 0, 0, 0),
+		 _ret == syscall(SYS_getpid));
+	TEST_ERRNO(vsyscall6(SYS_close, -1, 0, 0, 0, 0, 0), EBADF);
+	TEST_ERRNO(vsyscall6(0xffff, 0, 0, 0, 0, 0, 0), ENOSYS);
+
+	// The sixth argument is passed in `ebp`, which is saved on the user
+	// stack if the system call is issued by the `sysenter` instruction.
+	fd = CHECK(syscall(SYS_memfd_create, "ia32_vsyscall", 0));
+	CHECK(syscall(SYS_ftruncate64, fd, 2 * PAGE_SIZE, 0));
+	CHECK(syscall(SYS_pwrite64, fd, message, sizeof(message), PAGE_SIZE,
+		      0));
+	TEST_RES(vsyscall6(SYS_mmap2, 0, PAGE_SIZE, PROT_READ, MAP_SHARED, fd,
+			   1),
+		 memcmp((char *)_ret, message, sizeof(message)) == 0);
+	TEST_SUCC(vsyscall6(SYS_close, fd, 0, 0, 0, 0, 0));
+}
+END_TEST()
+
+int main(void)
+{
+	RUN_TEST(int80);
+	RUN_TEST(vsyscall);
+	return TEST_EXIT_CODE();
+}
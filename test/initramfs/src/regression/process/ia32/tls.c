@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MPL-2.0
+
+/*
+ * Tests that the TLS entries installed by `set_thread_area` can be loaded into
+ * the segment registers.
+ */
+
+#include "ia32.h"
+
+#define ENTRY_NUMBER_ANY 0xffffffff
+
+struct user_desc {
+	uint32_t entry_number;
+	uint32_t base_addr;
+	uint32_t limit;
+	uint32_t seg_32bit : 1;
+	uint32_t contents : 2;
+	uint32_t read_exec_only : 1;
+	uint32_t limit_in_pages : 1;
+	uint32_t seg_not_present : 1;
+	uint32_t useable : 1;
+};
+
+static uint32_t tls_data[2] = { 0x11223344, 0x55667788 };
+
+static uint32_t read_gs(uint32_t offset)
+{
+	uint32_t val;
+
+	__asm__ volatile("mov %%gs:(%1), %0" : "=r"(val) : "r"(offset));
+	return val;
+}
+
+static void load_gs(uint32_t selector)
+{
+	__asm__ volatile("mov %0, %%gs" : : "r"(selector));
+}
+
+static uint32_t get_gs(void)
+{
+	uint32_t selector;
+
+	__asm__ volatile("mov %%gs, %0" : "=r"(selector));
+	return selector;
+}
+
+FN_TEST(set_thread_area)
+{
+	struct user_desc desc = {
+		.entry_number = ENTRY_NUMBER_ANY,
+		.base_addr = (uint32_t)tls_data,
+		.limit = 0xfffff,
+		.seg_32bit = 1,
+		.limit_in_pages = 1,
+		.useable = 1,
+	};
+	struct user_desc read_desc = {};
+	uint32_t entry_number;
+
+	TEST_RES(syscall(SYS_set_thread_area, &desc),
+		 desc.entry_number != ENTRY_NUMBER_ANY);
+	entry_number = desc.entry_number;
+
+	read_desc.entry_number = entry_number;
+	TEST_RES(syscall(SYS_get_thread_area, &read_desc),
+		 read_desc.base_addr == (uint32_t)tls_data &&
+			 read_desc.limit == 0xfffff &&
+			 read_desc.seg_32bit == 1 &&
+			 read_desc.limit_in_pages == 1 &&
+			 read_desc.useable == 1);
+
+	load_gs(entry_number << 3 | 3);
+	TEST_RES(0, read_gs(0) == tls_data[0] && read_gs(4) == tls_data[1]);
+
+	// The segment register is preserved across system calls.
+	TEST_RES(syscall(SYS_getpid), get_gs() == (entry_number << 3 | 3) &&
+					      read_gs(4) == tls_data[1]);
+
+	// A new base address takes effect after the segment register is
+	// reloaded.
+	desc.base_addr = (uint32_t)&tls_data[1];
+	TEST_SUCC(syscall(SYS_set_thread_area, &desc));
+	load_gs(entry_number << 3 | 3);
+	TEST_RES(0, read_gs(0) == tls_data[1]);
+
+	load_gs(0);
+	read_desc.entry_number = 100;
+	TEST_ERRNO(syscall(SYS_get_thread_area, &read_desc), EINVAL);
+}
+END_TEST()
+
+int main(void)
+{
+	RUN_TEST(set_thread_area);
+	return TEST_EXIT_CODE();
+}
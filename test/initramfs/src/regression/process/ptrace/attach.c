@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <elf.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/ptrace.h>
+#include <sys/uio.h>
+#include <sys/user.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+#include "../../common/yama_ptrace_scope.h"
+
+static pid_t fork_sleeping_child(void)
+{
+	pid_t pid = CHECK(fork());
+	if (pid == 0) {
+		for (;;) {
+			pause();
+		}
+		_exit(-1);
+	}
+
+	return pid;
+}
+
+static int ptrace_event_stop_status(int sig)
+{
+	return sig | (PTRACE_EVENT_STOP << 8);
+}
+
+FN_TEST(ptrace_attach_detach)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork_sleeping_child());
+
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, getpid(), 0, 0), EPERM);
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, 0x3c3c3c3c, 0, 0), ESRCH);
+
+	TEST_SUCC(ptrace(PTRACE_ATTACH, pid, 0, 0));
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, pid, 0, 0), EPERM);
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+
+	// `PTRACE_INTERRUPT` and `PTRACE_LISTEN` only work with `PTRACE_SEIZE`.
+	TEST_ERRNO(ptrace(PTRACE_INTERRUPT, pid, 0, 0), EIO);
+	TEST_ERRNO(ptrace(PTRACE_LISTEN, pid, 0, 0), EIO);
+
+	// The tracee must be ptrace-stopped when detaching.
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+	TEST_ERRNO(ptrace(PTRACE_DETACH, pid, 0, 0), ESRCH);
+	TEST_SUCC(kill(pid, SIGUSR1));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGUSR1);
+
+	TEST_SUCC(ptrace(PTRACE_DETACH, pid, 0, 0));
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, 0, 0), ESRCH);
+	TEST_RES(waitpid(pid, &status, WNOHANG), _ret == 0);
+
+	// The signal given to `PTRACE_DETACH` is delivered to the tracee.
+	TEST_SUCC(ptrace(PTRACE_ATTACH, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+	TEST_SUCC(ptrace(PTRACE_DETACH, pid, 0, SIGUSR1));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSIGNALED(status) &&
+						   WTERMSIG(status) == SIGUSR1);
+}
+END_TEST()
+
+FN_TEST(ptrace_seize_interrupt_listen)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork_sleeping_child());
+
+	TEST_ERRNO(ptrace(PTRACE_SEIZE, pid, 1, 0), EIO);
+	TEST_ERRNO(ptrace(PTRACE_SEIZE, pid, 0, 1ul << 31), EIO);
+
+	// The tracee keeps running after being seized.
+	TEST_SUCC(ptrace(PTRACE_SEIZE, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, WNOHANG), _ret == 0);
+
+	TEST_SUCC(ptrace(PTRACE_INTERRUPT, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 (status >> 8) == ptrace_event_stop_status(SIGTRAP));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+
+	// A signal-delivery-stop is not a group-stop.
+	TEST_SUCC(kill(pid, SIGSTOP));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   (status >> 8) == SIGSTOP);
+	TEST_ERRNO(ptrace(PTRACE_LISTEN, pid, 0, 0), EIO);
+
+	// Delivering `SIGSTOP` starts a group-stop.
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, SIGSTOP));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 (status >> 8) == ptrace_event_stop_status(SIGSTOP));
+
+	// The tracee stays in the group-stop after `PTRACE_LISTEN`.
+	TEST_SUCC(ptrace(PTRACE_LISTEN, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, WNOHANG), _ret == 0);
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, 0, 0), ESRCH);
+
+	TEST_SUCC(ptrace(PTRACE_INTERRUPT, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 (status >> 8) == ptrace_event_stop_status(SIGSTOP));
+
+	TEST_SUCC(kill(pid, SIGKILL));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSIGNALED(status) &&
+						   WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+// The register sets are only supported on x86-64 and RISC-V.
+#if defined(__x86_64__) || defined(__riscv)
+
+#ifdef __x86_64__
+#define FPREGS_SIZE sizeof(struct user_fpregs_struct)
+#else
+// The size of `struct __riscv_d_ext_state`.
+#define FPREGS_SIZE (33 * sizeof(unsigned long))
+#endif
+
+FN_TEST(ptrace_regset)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork_sleeping_child());
+
+	TEST_SUCC(ptrace(PTRACE_ATTACH, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+
+	struct user_regs_struct regs, regs2;
+	struct iovec iov = { .iov_base = &regs, .iov_len = sizeof(regs) };
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRSTATUS, &iov),
+		 iov.iov_len == sizeof(regs));
+#ifdef __x86_64__
+	TEST_RES(ptrace(PTRACE_GETREGS, pid, 0, &regs2),
+		 memcmp(&regs, &regs2, sizeof(regs)) == 0);
+	regs.r15 = 0x1234;
+#else
+	regs.s11 = 0x1234;
+#endif
+
+	// The register set is truncated to the buffer size.
+	iov.iov_base = &regs2;
+	iov.iov_len = sizeof(unsigned long);
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRSTATUS, &iov),
+		 iov.iov_len == sizeof(unsigned long));
+
+	iov.iov_base = &regs;
+	iov.iov_len = sizeof(regs);
+	TEST_SUCC(ptrace(PTRACE_SETREGSET, pid, NT_PRSTATUS, &iov));
+	iov.iov_base = &regs2;
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRSTATUS, &iov),
+		 memcmp(&regs, &regs2, sizeof(regs)) == 0);
+
+	unsigned long fpregs[64], fpregs2[64];
+	iov.iov_base = fpregs;
+	iov.iov_len = sizeof(fpregs);
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRFPREG, &iov),
+		 iov.iov_len == FPREGS_SIZE);
+#ifdef __x86_64__
+	TEST_RES(ptrace(PTRACE_GETFPREGS, pid, 0, fpregs2),
+		 memcmp(fpregs, fpregs2, FPREGS_SIZE) == 0);
+	// Set the first XMM register.
+	fpregs[20] = 0x5678;
+#else
+	// Set the first floating-point register.
+	fpregs[0] = 0x5678;
+#endif
+
+	TEST_SUCC(ptrace(PTRACE_SETREGSET, pid, NT_PRFPREG, &iov));
+	iov.iov_base = fpregs2;
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRFPREG, &iov),
+		 iov.iov_len == FPREGS_SIZE &&
+			 memcmp(fpregs, fpregs2, FPREGS_SIZE) == 0);
+
+#ifdef __x86_64__
+	// Reserved MXCSR bits cannot be set.
+	struct user_fpregs_struct *x86_fpregs = (void *)fpregs;
+	x86_fpregs->mxcsr = 0xffffffff;
+	TEST_ERRNO(ptrace(PTRACE_SETFPREGS, pid, 0, fpregs), EINVAL);
+#endif
+
+	TEST_ERRNO(ptrace(PTRACE_GETREGSET, pid, 0x3c3c, &iov), EINVAL);
+
+	TEST_SUCC(kill(pid, SIGKILL));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSIGNALED(status) &&
+						   WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+#endif
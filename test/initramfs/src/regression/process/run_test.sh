@@ -17,6 +17,7 @@ fi
 
 ./cpu_affinity/cpu_affinity
 
+./execve/binfmt_misc
 ./execve/execve
 ./execve/execve_comm
 ./execve/execve_err
@@ -33,6 +34,13 @@ fi
 
 ./getpid/getpid
 
+if [ "$(uname -m)" = "x86_64" ]; then
+    ./ia32/layout
+    ./ia32/signal
+    ./ia32/syscall
+    ./ia32/tls
+fi
+
 ./personality/personality
 
 ./prctl/capbset
@@ -43,6 +51,7 @@ fi
 ./pthread/pthread_signal_test
 ./pthread/pthread_test
 
+./ptrace/attach
 ./ptrace/ptrace
 ./ptrace/set_options
 
@@ -55,6 +64,7 @@ fi
 ./sched/sched_param_getset
 ./sched/sched_param_idle
 
+./signal/coredump
 ./signal/kill
 ./signal/parent_death_signal
 ./signal/pidfd_send_signal
@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <elf.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/prctl.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define CORE_PATTERN "/tmp/core.%p"
+#define PAGE_SIZE 4096
+
+static char old_core_pattern[128];
+
+static int read_file(const char *path, char *buf, size_t len)
+{
+	int fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	ssize_t n = read(fd, buf, len - 1);
+	close(fd);
+	if (n < 0)
+		return -1;
+
+	buf[n] = '\0';
+	return 0;
+}
+
+static int write_file(const char *path, const char *buf)
+{
+	int fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	ssize_t n = write(fd, buf, strlen(buf));
+	close(fd);
+	return n == (ssize_t)strlen(buf) ? 0 : -1;
+}
+
+static void core_path_of(pid_t pid, char *buf, size_t len)
+{
+	snprintf(buf, len, "/tmp/core.%d", pid);
+}
+
+FN_SETUP(core_pattern)
+{
+	struct rlimit rlim = { RLIM_INFINITY, RLIM_INFINITY };
+
+	CHECK(read_file("/proc/sys/kernel/core_pattern", old_core_pattern,
+			sizeof(old_core_pattern)));
+	CHECK(write_file("/proc/sys/kernel/core_pattern", CORE_PATTERN));
+	CHECK(setrlimit(RLIMIT_CORE, &rlim));
+}
+END_SETUP()
+
+FN_TEST(core_pattern)
+{
+	char buf[128];
+
+	TEST_RES(read_file("/proc/sys/kernel/core_pattern", buf, sizeof(buf)),
+		 strcmp(buf, CORE_PATTERN "\n") == 0);
+}
+END_TEST()
+
+FN_TEST(dumpable)
+{
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 0));
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 0);
+
+	// Only 0 and 1 can be set by user space.
+	TEST_ERRNO(prctl(PR_SET_DUMPABLE, 2), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_DUMPABLE, 3), EINVAL);
+
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 1));
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(coredump_filter)
+{
+	char buf[32];
+
+	TEST_RES(read_file("/proc/self/coredump_filter", buf, sizeof(buf)),
+		 strcmp(buf, "00000033\n") == 0);
+
+	TEST_SUCC(write_file("/proc/self/coredump_filter", "0x3f"));
+	TEST_RES(read_file("/proc/self/coredump_filter", buf, sizeof(buf)),
+		 strcmp(buf, "0000003f\n") == 0);
+
+	// Undefined bits are ignored.
+	TEST_SUCC(write_file("/proc/self/coredump_filter", "0xffff0001"));
+	TEST_RES(read_file("/proc/self/coredump_filter", buf, sizeof(buf)),
+		 strcmp(buf, "00000001\n") == 0);
+
+	TEST_ERRNO(write_file("/proc/self/coredump_filter", "abc"), EINVAL);
+
+	TEST_SUCC(write_file("/proc/self/coredump_filter", "51"));
+	TEST_RES(read_file("/proc/self/coredump_filter", buf, sizeof(buf)),
+		 strcmp(buf, "00000033\n") == 0);
+}
+END_TEST()
+
+FN_TEST(madvise_dump)
+{
+	char *addr = TEST_SUCC(mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+				    MAP_PRIVATE | MAP_ANONYMOUS, -1, 0));
+
+	TEST_SUCC(madvise(addr, PAGE_SIZE, MADV_DONTDUMP));
+	TEST_SUCC(madvise(addr, PAGE_SIZE, MADV_DODUMP));
+
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+}
+END_TEST()
+
+static pid_t fork_crashing_child(int dumpable, rlim_t core_limit)
+{
+	pid_t pid = CHECK(fork());
+	if (pid == 0) {
+		struct rlimit rlim = { core_limit, core_limit };
+
+		CHECK(prctl(PR_SET_DUMPABLE, dumpable));
+		CHECK(setrlimit(RLIMIT_CORE, &rlim));
+		raise(SIGSEGV);
+		_exit(-1);
+	}
+
+	return pid;
+}
+
+static int read_elf_header(int fd, Elf64_Ehdr *ehdr)
+{
+	if (pread(fd, ehdr, sizeof(*ehdr), 0) != sizeof(*ehdr))
+		return -1;
+
+	if (memcmp(ehdr->e_ident, ELFMAG, SELFMAG) != 0 ||
+	    ehdr->e_ident[EI_CLASS] != ELFCLASS64 ||
+	    ehdr->e_type != ET_CORE ||
+	    ehdr->e_phentsize != sizeof(Elf64_Phdr) || ehdr->e_phnum < 2)
+		return -1;
+
+	return 0;
+}
+
+static int find_phdr(int fd, const Elf64_Ehdr *ehdr, unsigned long vaddr,
+		     Elf64_Phdr *phdr)
+{
+	for (int i = 0; i < ehdr->e_phnum; i++) {
+		off_t offset = ehdr->e_phoff + i * sizeof(*phdr);
+
+		if (pread(fd, phdr, sizeof(*phdr), offset) != sizeof(*phdr))
+			return -1;
+		if (phdr->p_type == PT_LOAD && phdr->p_vaddr == vaddr)
+			return 0;
+	}
+
+	return -1;
+}
+
+FN_TEST(core_dumped)
+{
+	char path[64], byte;
+	int status;
+	Elf64_Ehdr ehdr;
+	Elf64_Phdr phdr;
+
+	char *dumped = TEST_SUCC(mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+				      MAP_PRIVATE | MAP_ANONYMOUS, -1, 0));
+	char *not_dumped = TEST_SUCC(mmap(NULL, PAGE_SIZE,
+					  PROT_READ | PROT_WRITE,
+					  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0));
+	memset(dumped, 0x5a, PAGE_SIZE);
+	memset(not_dumped, 0x5a, PAGE_SIZE);
+	TEST_SUCC(madvise(not_dumped, PAGE_SIZE, MADV_DONTDUMP));
+
+	pid_t pid = TEST_SUCC(fork_crashing_child(1, RLIM_INFINITY));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGSEGV && WCOREDUMP(status));
+
+	core_path_of(pid, path, sizeof(path));
+	int fd = TEST_SUCC(open(path, O_RDONLY));
+	TEST_SUCC(read_elf_header(fd, &ehdr));
+
+	// The notes come first.
+	TEST_RES(pread(fd, &phdr, sizeof(phdr), ehdr.e_phoff),
+		 _ret == sizeof(phdr) && phdr.p_type == PT_NOTE &&
+			 phdr.p_filesz > 0);
+
+	TEST_RES(find_phdr(fd, &ehdr, (unsigned long)dumped, &phdr),
+		 phdr.p_filesz == PAGE_SIZE && phdr.p_memsz == PAGE_SIZE);
+	TEST_RES(pread(fd, &byte, 1, phdr.p_offset), _ret == 1 && byte == 0x5a);
+
+	TEST_RES(find_phdr(fd, &ehdr, (unsigned long)not_dumped, &phdr),
+		 phdr.p_filesz == 0 && phdr.p_memsz == PAGE_SIZE);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(path));
+
+	TEST_SUCC(munmap(dumped, PAGE_SIZE));
+	TEST_SUCC(munmap(not_dumped, PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(core_dumped_waitid)
+{
+	char path[64];
+	siginfo_t info;
+
+	pid_t pid = TEST_SUCC(fork_crashing_child(1, RLIM_INFINITY));
+	TEST_RES(waitid(P_PID, pid, &info, WEXITED),
+		 info.si_code == CLD_DUMPED && info.si_status == SIGSEGV);
+
+	core_path_of(pid, path, sizeof(path));
+	TEST_SUCC(unlink(path));
+}
+END_TEST()
+
+FN_TEST(not_dumpable)
+{
+	char path[64];
+	int status;
+
+	pid_t pid = TEST_SUCC(fork_crashing_child(0, RLIM_INFINITY));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGSEGV && !WCOREDUMP(status));
+
+	core_path_of(pid, path, sizeof(path));
+	TEST_ERRNO(access(path, F_OK), ENOENT);
+}
+END_TEST()
+
+FN_TEST(core_limit_too_small)
+{
+	char path[64];
+	int status;
+
+	pid_t pid = TEST_SUCC(fork_crashing_child(1, 0));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGSEGV && !WCOREDUMP(status));
+
+	core_path_of(pid, path, sizeof(path));
+	TEST_ERRNO(access(path, F_OK), ENOENT);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(write_file("/proc/sys/kernel/core_pattern", old_core_pattern));
+}
+END_SETUP()
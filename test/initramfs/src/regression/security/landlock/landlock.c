@@ -0,0 +1,458 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <netinet/in.h>
+#include <stdint.h>
+#include <sys/prctl.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/capability.h"
+
+#ifndef SYS_landlock_create_ruleset
+#define SYS_landlock_create_ruleset 444
+#define SYS_landlock_add_rule 445
+#define SYS_landlock_restrict_self 446
+#endif
+
+#define LANDLOCK_CREATE_RULESET_VERSION (1U << 0)
+
+#define LANDLOCK_RULE_PATH_BENEATH 1
+#define LANDLOCK_RULE_NET_PORT 2
+
+#define ACCESS_FS_EXECUTE (1ULL << 0)
+#define ACCESS_FS_WRITE_FILE (1ULL << 1)
+#define ACCESS_FS_READ_FILE (1ULL << 2)
+#define ACCESS_FS_READ_DIR (1ULL << 3)
+#define ACCESS_FS_REMOVE_DIR (1ULL << 4)
+#define ACCESS_FS_REMOVE_FILE (1ULL << 5)
+#define ACCESS_FS_MAKE_DIR (1ULL << 7)
+#define ACCESS_FS_MAKE_REG (1ULL << 8)
+#define ACCESS_FS_REFER (1ULL << 13)
+#define ACCESS_FS_TRUNCATE (1ULL << 14)
+
+#define ACCESS_NET_BIND_TCP (1ULL << 0)
+#define ACCESS_NET_CONNECT_TCP (1ULL << 1)
+
+struct ruleset_attr {
+	uint64_t handled_access_fs;
+	uint64_t handled_access_net;
+};
+
+struct path_beneath_attr {
+	uint64_t allowed_access;
+	int32_t parent_fd;
+} __attribute__((packed));
+
+struct net_port_attr {
+	uint64_t allowed_access;
+	uint64_t port;
+};
+
+#define BASE_DIR "/tmp/landlock_test"
+#define ALLOWED_DIR BASE_DIR "/allowed"
+#define DENIED_DIR BASE_DIR "/denied"
+#define NO_REFER_DIR BASE_DIR "/no_refer"
+
+#define ACCESS_FS_HANDLED                                                   \
+	(ACCESS_FS_WRITE_FILE | ACCESS_FS_READ_FILE | ACCESS_FS_READ_DIR |  \
+	 ACCESS_FS_REMOVE_DIR | ACCESS_FS_REMOVE_FILE | ACCESS_FS_MAKE_DIR | \
+	 ACCESS_FS_MAKE_REG | ACCESS_FS_REFER | ACCESS_FS_TRUNCATE)
+
+#define BIND_PORT 10001
+#define CONNECT_PORT 10002
+#define OTHER_PORT 10003
+
+static int create_ruleset(uint64_t handled_fs, uint64_t handled_net)
+{
+	struct ruleset_attr attr = {
+		.handled_access_fs = handled_fs,
+		.handled_access_net = handled_net,
+	};
+
+	return syscall(SYS_landlock_create_ruleset, &attr, sizeof(attr), 0);
+}
+
+static int add_path_rule(int ruleset_fd, uint64_t access, int parent_fd)
+{
+	struct path_beneath_attr attr = {
+		.allowed_access = access,
+		.parent_fd = parent_fd,
+	};
+
+	return syscall(SYS_landlock_add_rule, ruleset_fd,
+		       LANDLOCK_RULE_PATH_BENEATH, &attr, 0);
+}
+
+static int add_dir_rule(int ruleset_fd, uint64_t access, const char *dir)
+{
+	int fd, ret;
+
+	fd = open(dir, O_PATH | O_DIRECTORY);
+	if (fd < 0)
+		return fd;
+	ret = add_path_rule(ruleset_fd, access, fd);
+	close(fd);
+	return ret;
+}
+
+static int add_port_rule(int ruleset_fd, uint64_t access, uint64_t port)
+{
+	struct net_port_attr attr = {
+		.allowed_access = access,
+		.port = port,
+	};
+
+	return syscall(SYS_landlock_add_rule, ruleset_fd,
+		       LANDLOCK_RULE_NET_PORT, &attr, 0);
+}
+
+static int restrict_self(int ruleset_fd)
+{
+	return syscall(SYS_landlock_restrict_self, ruleset_fd, 0);
+}
+
+static int wait_for_exit_code(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid || !WIFEXITED(status))
+		return -1;
+	return WEXITSTATUS(status);
+}
+
+static int create_file(const char *path)
+{
+	int fd;
+
+	fd = open(path, O_WRONLY | O_CREAT | O_TRUNC, 0644);
+	if (fd < 0)
+		return fd;
+	return close(fd);
+}
+
+static int tcp_socket_at(int port, int (*op)(int, const struct sockaddr *,
+					     socklen_t))
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(port),
+		.sin_addr = { .s_addr = htonl(INADDR_LOOPBACK) },
+	};
+	int fd, ret;
+
+	fd = socket(AF_INET, SOCK_STREAM, 0);
+	if (fd < 0)
+		return fd;
+	ret = op(fd, (struct sockaddr *)&addr, sizeof(addr));
+	close(fd);
+	return ret;
+}
+
+FN_SETUP(dirs)
+{
+	CHECK(mkdir(BASE_DIR, 0755));
+	CHECK(mkdir(ALLOWED_DIR, 0755));
+	CHECK(mkdir(DENIED_DIR, 0755));
+	CHECK(mkdir(NO_REFER_DIR, 0755));
+	CHECK(create_file(ALLOWED_DIR "/file"));
+	CHECK(create_file(DENIED_DIR "/file"));
+}
+END_SETUP()
+
+FN_TEST(abi_version)
+{
+	TEST_RES(syscall(SYS_landlock_create_ruleset, NULL, 0,
+			 LANDLOCK_CREATE_RULESET_VERSION),
+		 _ret >= 4);
+}
+END_TEST()
+
+FN_TEST(create_ruleset_invalid)
+{
+	struct ruleset_attr attr = { .handled_access_fs = ACCESS_FS_READ_FILE };
+	char big_attr[64] = {};
+
+	TEST_ERRNO(syscall(SYS_landlock_create_ruleset, &attr, sizeof(attr),
+			   LANDLOCK_CREATE_RULESET_VERSION),
+		   EINVAL);
+	TEST_ERRNO(syscall(SYS_landlock_create_ruleset, NULL, 0, 1U << 31),
+		   EINVAL);
+	TEST_ERRNO(syscall(SYS_landlock_create_ruleset, &attr, 4, 0), EINVAL);
+	TEST_ERRNO(syscall(SYS_landlock_create_ruleset, NULL, sizeof(attr), 0),
+		   EFAULT);
+
+	big_attr[sizeof(big_attr) - 1] = 1;
+	TEST_ERRNO(syscall(SYS_landlock_create_ruleset, big_attr,
+			   sizeof(big_attr), 0),
+		   E2BIG);
+
+	TEST_ERRNO(create_ruleset(1ULL << 63, 0), EINVAL);
+	TEST_ERRNO(create_ruleset(0, 1ULL << 63), EINVAL);
+	TEST_ERRNO(create_ruleset(0, 0), ENOMSG);
+}
+END_TEST()
+
+FN_TEST(add_rule_invalid)
+{
+	int ruleset_fd, dir_fd, file_fd, pipefds[2];
+
+	ruleset_fd = TEST_RES(create_ruleset(ACCESS_FS_READ_FILE |
+						     ACCESS_FS_READ_DIR,
+					     ACCESS_NET_BIND_TCP),
+			      fcntl(_ret, F_GETFD) == FD_CLOEXEC);
+	dir_fd = TEST_SUCC(open(ALLOWED_DIR, O_PATH | O_DIRECTORY));
+	file_fd = TEST_SUCC(open(ALLOWED_DIR "/file", O_RDONLY));
+	CHECK(pipe(pipefds));
+
+	// Invalid rulesets
+	TEST_ERRNO(add_path_rule(-1, ACCESS_FS_READ_FILE, dir_fd), EBADF);
+	TEST_ERRNO(add_path_rule(file_fd, ACCESS_FS_READ_FILE, dir_fd), EBADFD);
+
+	// Invalid rules
+	TEST_ERRNO(syscall(SYS_landlock_add_rule, ruleset_fd, 100, NULL, 0),
+		   EINVAL);
+	TEST_ERRNO(syscall(SYS_landlock_add_rule, ruleset_fd,
+			   LANDLOCK_RULE_PATH_BENEATH, NULL, 1),
+		   EINVAL);
+	TEST_ERRNO(add_path_rule(ruleset_fd, 0, dir_fd), ENOMSG);
+	TEST_ERRNO(add_path_rule(ruleset_fd, ACCESS_FS_WRITE_FILE, dir_fd),
+		   EINVAL);
+	TEST_ERRNO(add_path_rule(ruleset_fd, ACCESS_FS_READ_FILE, -1), EBADF);
+	TEST_ERRNO(add_path_rule(ruleset_fd, ACCESS_FS_READ_FILE, pipefds[0]),
+		   EBADFD);
+	TEST_ERRNO(add_path_rule(ruleset_fd, ACCESS_FS_READ_DIR, file_fd),
+		   EINVAL);
+	TEST_ERRNO(add_port_rule(ruleset_fd, 0, BIND_PORT), ENOMSG);
+	TEST_ERRNO(add_port_rule(ruleset_fd, ACCESS_NET_CONNECT_TCP, BIND_PORT),
+		   EINVAL);
+	TEST_ERRNO(add_port_rule(ruleset_fd, ACCESS_NET_BIND_TCP, 65536),
+		   EINVAL);
+
+	// Valid rules
+	TEST_SUCC(add_path_rule(ruleset_fd, ACCESS_FS_READ_DIR, dir_fd));
+	TEST_SUCC(add_path_rule(ruleset_fd, ACCESS_FS_READ_FILE, file_fd));
+	TEST_SUCC(add_port_rule(ruleset_fd, ACCESS_NET_BIND_TCP, BIND_PORT));
+
+	TEST_SUCC(close(pipefds[0]));
+	TEST_SUCC(close(pipefds[1]));
+	TEST_SUCC(close(file_fd));
+	TEST_SUCC(close(dir_fd));
+	TEST_SUCC(close(ruleset_fd));
+}
+END_TEST()
+
+FN_TEST(restrict_self_invalid)
+{
+	int ruleset_fd;
+	pid_t pid;
+
+	ruleset_fd = TEST_SUCC(create_ruleset(ACCESS_FS_READ_FILE, 0));
+
+	TEST_ERRNO(syscall(SYS_landlock_restrict_self, ruleset_fd, 1U << 31),
+		   EINVAL);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		drop_capability(CAP_SYS_ADMIN);
+		CHECK_WITH(restrict_self(ruleset_fd),
+			   _ret == -1 && errno == EPERM);
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK_WITH(restrict_self(-1), _ret == -1 && errno == EBADF);
+		CHECK_WITH(restrict_self(STDIN_FILENO),
+			   _ret == -1 && errno == EBADFD);
+		CHECK(restrict_self(ruleset_fd));
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+
+	TEST_SUCC(close(ruleset_fd));
+}
+END_TEST()
+
+FN_TEST(max_layers)
+{
+	int ruleset_fd;
+	pid_t pid;
+
+	ruleset_fd = TEST_SUCC(create_ruleset(ACCESS_FS_EXECUTE, 0));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		for (int i = 0; i < 16; i++)
+			CHECK(restrict_self(ruleset_fd));
+		CHECK_WITH(restrict_self(ruleset_fd),
+			   _ret == -1 && errno == E2BIG);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+
+	TEST_SUCC(close(ruleset_fd));
+}
+END_TEST()
+
+FN_TEST(fs_access)
+{
+	int ruleset_fd, fd;
+	pid_t pid;
+
+	ruleset_fd = TEST_SUCC(create_ruleset(ACCESS_FS_HANDLED, 0));
+	TEST_SUCC(add_dir_rule(ruleset_fd, ACCESS_FS_HANDLED, ALLOWED_DIR));
+	TEST_SUCC(add_dir_rule(ruleset_fd,
+			       ACCESS_FS_READ_FILE | ACCESS_FS_WRITE_FILE |
+				       ACCESS_FS_MAKE_REG |
+				       ACCESS_FS_REMOVE_FILE,
+			       NO_REFER_DIR));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(ruleset_fd));
+
+		// Opening files
+		CHECK(close(CHECK(open(ALLOWED_DIR "/file", O_RDWR))));
+		CHECK(close(CHECK(open(ALLOWED_DIR, O_RDONLY | O_DIRECTORY))));
+		CHECK_WITH(open(DENIED_DIR "/file", O_RDONLY),
+			   _ret == -1 && errno == EACCES);
+		CHECK_WITH(open(DENIED_DIR "/file", O_WRONLY),
+			   _ret == -1 && errno == EACCES);
+		CHECK_WITH(open(DENIED_DIR, O_RDONLY | O_DIRECTORY),
+			   _ret == -1 && errno == EACCES);
+		CHECK(close(CHECK(open(DENIED_DIR "/file", O_PATH))));
+
+		// Truncating files
+		CHECK(truncate(ALLOWED_DIR "/file", 0));
+		CHECK_WITH(truncate(DENIED_DIR "/file", 0),
+			   _ret == -1 && errno == EACCES);
+
+		// Creating and removing files
+		CHECK(create_file(ALLOWED_DIR "/new_file"));
+		CHECK(mkdir(ALLOWED_DIR "/new_dir", 0755));
+		CHECK_WITH(create_file(DENIED_DIR "/new_file"),
+			   _ret == -1 && errno == EACCES);
+		CHECK_WITH(mkdir(DENIED_DIR "/new_dir", 0755),
+			   _ret == -1 && errno == EACCES);
+		CHECK_WITH(unlink(DENIED_DIR "/file"),
+			   _ret == -1 && errno == EACCES);
+
+		// Renaming files
+		CHECK(rename(ALLOWED_DIR "/new_file",
+			     ALLOWED_DIR "/new_dir/new_file"));
+		CHECK_WITH(rename(ALLOWED_DIR "/new_dir/new_file",
+				  DENIED_DIR "/new_file"),
+			   _ret == -1 && errno == EACCES);
+		CHECK_WITH(rename(ALLOWED_DIR "/new_dir/new_file",
+				  NO_REFER_DIR "/new_file"),
+			   _ret == -1 && errno == EXDEV);
+		CHECK(create_file(NO_REFER_DIR "/new_file"));
+		CHECK(rename(NO_REFER_DIR "/new_file",
+			     NO_REFER_DIR "/renamed_file"));
+		CHECK(unlink(NO_REFER_DIR "/renamed_file"));
+
+		CHECK(unlink(ALLOWED_DIR "/new_dir/new_file"));
+		CHECK(rmdir(ALLOWED_DIR "/new_dir"));
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+
+	// The parent is not restricted.
+	fd = TEST_SUCC(open(DENIED_DIR "/file", O_RDWR));
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(close(ruleset_fd));
+}
+END_TEST()
+
+FN_TEST(domain_inheritance)
+{
+	int ruleset_fd;
+	pid_t pid, child;
+
+	ruleset_fd = TEST_SUCC(create_ruleset(ACCESS_FS_READ_FILE, 0));
+	TEST_SUCC(add_dir_rule(ruleset_fd, ACCESS_FS_READ_FILE, ALLOWED_DIR));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(ruleset_fd));
+
+		child = CHECK(fork());
+		if (child == 0) {
+			CHECK_WITH(open(DENIED_DIR "/file", O_RDONLY),
+				   _ret == -1 && errno == EACCES);
+			CHECK(close(CHECK(open(ALLOWED_DIR "/file",
+					       O_RDONLY))));
+			_exit(EXIT_SUCCESS);
+		}
+		CHECK_WITH(wait_for_exit_code(child), _ret == 0);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+
+	TEST_SUCC(close(ruleset_fd));
+}
+END_TEST()
+
+FN_TEST(net_access)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(CONNECT_PORT),
+		.sin_addr = { .s_addr = htonl(INADDR_LOOPBACK) },
+	};
+	int ruleset_fd, listen_fd, udp_fd;
+	pid_t pid;
+
+	ruleset_fd = TEST_SUCC(create_ruleset(
+		0, ACCESS_NET_BIND_TCP | ACCESS_NET_CONNECT_TCP));
+	TEST_SUCC(add_port_rule(ruleset_fd, ACCESS_NET_BIND_TCP, BIND_PORT));
+	TEST_SUCC(
+		add_port_rule(ruleset_fd, ACCESS_NET_CONNECT_TCP, CONNECT_PORT));
+
+	listen_fd = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_SUCC(bind(listen_fd, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_SUCC(listen(listen_fd, 2));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(ruleset_fd));
+
+		CHECK(tcp_socket_at(BIND_PORT, bind));
+		CHECK_WITH(tcp_socket_at(OTHER_PORT, bind),
+			   _ret == -1 && errno == EACCES);
+		CHECK(tcp_socket_at(CONNECT_PORT, connect));
+		CHECK_WITH(tcp_socket_at(OTHER_PORT, connect),
+			   _ret == -1 && errno == EACCES);
+
+		// UDP sockets are not restricted.
+		udp_fd = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+		addr.sin_port = htons(OTHER_PORT);
+		CHECK(bind(udp_fd, (struct sockaddr *)&addr, sizeof(addr)));
+		CHECK(close(udp_fd));
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+
+	TEST_SUCC(close(listen_fd));
+	TEST_SUCC(close(ruleset_fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(ALLOWED_DIR "/file"));
+	CHECK(unlink(DENIED_DIR "/file"));
+	CHECK(rmdir(ALLOWED_DIR));
+	CHECK(rmdir(DENIED_DIR));
+	CHECK(rmdir(NO_REFER_DIR));
+	CHECK(rmdir(BASE_DIR));
+}
+END_SETUP()
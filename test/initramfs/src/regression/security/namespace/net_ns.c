@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <arpa/inet.h>
+#include <fcntl.h>
+#include <net/if.h>
+#include <netinet/in.h>
+#include <sched.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define TEST_PORT 0x4321
+
+static struct sockaddr_in loopback_addr;
+
+static int listen_fd;
+
+static int new_listener(void)
+{
+	int fd = socket(AF_INET, SOCK_STREAM, 0);
+	if (fd < 0)
+		return -1;
+
+	if (bind(fd, (struct sockaddr *)&loopback_addr,
+		 sizeof(loopback_addr)) < 0 ||
+	    listen(fd, 16) < 0) {
+		close(fd);
+		return -1;
+	}
+
+	return fd;
+}
+
+static int try_connect(void)
+{
+	int fd = socket(AF_INET, SOCK_STREAM, 0);
+	if (fd < 0)
+		return -1;
+
+	int ret = connect(fd, (struct sockaddr *)&loopback_addr,
+			  sizeof(loopback_addr));
+	int saved_errno = errno;
+	close(fd);
+	errno = saved_errno;
+
+	return ret;
+}
+
+static int count_ifaces(void)
+{
+	struct if_nameindex *ifaces = if_nameindex();
+	if (ifaces == NULL)
+		return -1;
+
+	int count = 0;
+	while (ifaces[count].if_index != 0)
+		count++;
+
+	if_freenameindex(ifaces);
+	return count;
+}
+
+static ino_t net_ns_ino(const char *path)
+{
+	struct stat st;
+	if (stat(path, &st) < 0)
+		return 0;
+	return st.st_ino;
+}
+
+FN_SETUP(listener)
+{
+	loopback_addr.sin_family = AF_INET;
+	loopback_addr.sin_port = htons(TEST_PORT);
+	loopback_addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+
+	listen_fd = CHECK(new_listener());
+}
+END_SETUP()
+
+FN_TEST(same_net_ns)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		// The listener is reachable and the port is occupied.
+		CHECK(try_connect());
+		CHECK_WITH(new_listener(), _ret < 0 && errno == EADDRINUSE);
+		_exit(0);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(unshare_net_ns)
+{
+	ino_t parent_ino = TEST_RES(net_ns_ino("/proc/self/ns/net"), _ret != 0);
+
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWNET));
+
+		// The namespace file refers to a different namespace.
+		CHECK_WITH(net_ns_ino("/proc/self/ns/net"),
+			   _ret != 0 && _ret != parent_ino);
+
+		// Only a private loopback interface exists.
+		CHECK_WITH(count_ifaces(), _ret == 1);
+		CHECK_WITH(if_nametoindex("lo"), _ret != 0);
+
+		// The listener in the parent namespace is not reachable.
+		CHECK_WITH(try_connect(), _ret < 0 && errno == ECONNREFUSED);
+
+		// The same port can be bound again in the new namespace.
+		int fd = CHECK(new_listener());
+		CHECK(try_connect());
+		CHECK(close(fd));
+		_exit(0);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	// The listener in the child namespace is gone,
+	// and the parent namespace is not affected.
+	TEST_SUCC(try_connect());
+	TEST_ERRNO(new_listener(), EADDRINUSE);
+}
+END_TEST()
+
+static int clone_child_fn(void *arg)
+{
+	(void)arg;
+
+	CHECK_WITH(count_ifaces(), _ret == 1);
+	CHECK_WITH(try_connect(), _ret < 0 && errno == ECONNREFUSED);
+
+	return 0;
+}
+
+#define STACK_SIZE (1024 * 1024)
+
+FN_TEST(clone_net_ns)
+{
+	static char stack[STACK_SIZE];
+
+	pid_t pid = TEST_SUCC(clone(clone_child_fn, stack + STACK_SIZE,
+				    CLONE_NEWNET | SIGCHLD, NULL));
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(setns_net_ns)
+{
+	int parent_ns_fd = TEST_SUCC(open("/proc/self/ns/net", O_RDONLY));
+
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWNET));
+		CHECK_WITH(try_connect(), _ret < 0 && errno == ECONNREFUSED);
+
+		// A namespace file of another type is rejected.
+		int uts_fd = CHECK(open("/proc/self/ns/uts", O_RDONLY));
+		CHECK_WITH(setns(uts_fd, CLONE_NEWNET),
+			   _ret < 0 && errno == EINVAL);
+		CHECK(close(uts_fd));
+
+		// Go back to the parent namespace.
+		CHECK(setns(parent_ns_fd, CLONE_NEWNET));
+		CHECK(try_connect());
+		_exit(0);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(close(parent_ns_fd));
+}
+END_TEST()
+
+FN_TEST(socket_keeps_net_ns)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		// A socket created before `unshare` stays in the old namespace.
+		int fd = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+		CHECK(unshare(CLONE_NEWNET));
+		CHECK(connect(fd, (struct sockaddr *)&loopback_addr,
+			      sizeof(loopback_addr)));
+		CHECK(close(fd));
+		_exit(0);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(listen_fd));
+}
+END_SETUP()
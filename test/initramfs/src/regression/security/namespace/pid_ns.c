@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define STACK_SIZE (1024 * 1024)
+
+static char stack[STACK_SIZE];
+
+static pid_t clone_in_new_pid_ns(int (*fn)(void *), void *arg)
+{
+	return clone(fn, stack + STACK_SIZE, CLONE_NEWPID | SIGCHLD, arg);
+}
+
+static int wait_for_success(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+
+	return 0;
+}
+
+/* -------------------------------------------------------------------------- */
+
+static int init_ids_fn(void *arg)
+{
+	(void)arg;
+
+	// The first process in a new PID namespace is its init process,
+	// and its parent is not visible in the namespace.
+	CHECK_WITH(getpid(), _ret == 1);
+	CHECK_WITH(getppid(), _ret == 0);
+	CHECK_WITH(getpgid(0), _ret == 0);
+	CHECK_WITH(getsid(0), _ret == 0);
+
+	// The processes outside the namespace are not visible.
+	CHECK_WITH(kill(getpid() + 1000, 0), _ret < 0 && errno == ESRCH);
+
+	// The children get the next IDs in the namespace.
+	pid_t pid = CHECK_WITH(fork(), _ret == 2 || _ret == 0);
+	if (pid == 0) {
+		CHECK_WITH(getpid(), _ret == 2);
+		CHECK_WITH(getppid(), _ret == 1);
+		_exit(0);
+	}
+	CHECK(wait_for_success(pid));
+
+	return 0;
+}
+
+FN_TEST(clone_new_pid_ns)
+{
+	pid_t pid = TEST_SUCC(clone_in_new_pid_ns(init_ids_fn, NULL));
+
+	// The child is visible with a different ID in the parent namespace.
+	TEST_RES(pid, _ret != 1);
+	TEST_SUCC(kill(pid, 0));
+
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(unshare_new_pid_ns)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		pid_t old_pid = getpid();
+
+		// The caller itself does not move into the new namespace.
+		CHECK(unshare(CLONE_NEWPID));
+		CHECK_WITH(getpid(), _ret == old_pid);
+
+		// Threads cannot be created while the namespaces differ.
+		CHECK_WITH(clone(init_ids_fn, stack + STACK_SIZE,
+				 CLONE_VM | CLONE_SIGHAND | CLONE_THREAD, NULL),
+			   _ret < 0 && errno == EINVAL);
+
+		// The next child becomes the init process of the new namespace.
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			CHECK_WITH(getpid(), _ret == 1);
+			CHECK_WITH(getppid(), _ret == 0);
+			_exit(0);
+		}
+		CHECK_WITH(child, _ret != 1);
+		CHECK(wait_for_success(child));
+
+		// No more processes can be created after the init process exits.
+		CHECK_WITH(fork(), _ret < 0 && errno == ENOMEM);
+
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+static int init_signal_fn(void *arg)
+{
+	(void)arg;
+
+	pid_t pid = CHECK(fork());
+	if (pid == 0) {
+		// Signals with the default action cannot be sent to the init
+		// process from inside its namespace.
+		CHECK(kill(1, SIGTERM));
+		CHECK(kill(1, SIGKILL));
+		_exit(0);
+	}
+	CHECK(wait_for_success(pid));
+
+	return 0;
+}
+
+static int init_pause_fn(void *arg)
+{
+	(void)arg;
+
+	for (;;)
+		pause();
+
+	return 0;
+}
+
+FN_TEST(signal_init_process)
+{
+	pid_t pid = TEST_SUCC(clone_in_new_pid_ns(init_signal_fn, NULL));
+	TEST_SUCC(wait_for_success(pid));
+
+	// SIGKILL from an ancestor namespace terminates the init process.
+	pid = TEST_SUCC(clone_in_new_pid_ns(init_pause_fn, NULL));
+	TEST_SUCC(kill(pid, SIGKILL));
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+static int init_reaper_fn(void *arg)
+{
+	(void)arg;
+
+	pid_t pid = CHECK(fork());
+	if (pid == 0) {
+		pid_t grandchild = CHECK(fork());
+		if (grandchild == 0) {
+			// The orphan is adopted by the init process of the
+			// namespace.
+			while (getppid() != 1)
+				usleep(1000);
+			_exit(0);
+		}
+		_exit(0);
+	}
+	CHECK(wait_for_success(pid));
+
+	// Reap the orphaned grandchild.
+	int status;
+	CHECK_WITH(wait(&status), _ret > 1 && WIFEXITED(status) &&
+					  WEXITSTATUS(status) == 0);
+	CHECK_WITH(wait(NULL), _ret < 0 && errno == ECHILD);
+
+	return 0;
+}
+
+FN_TEST(reparent_to_init_process)
+{
+	pid_t pid = TEST_SUCC(clone_in_new_pid_ns(init_reaper_fn, NULL));
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+static int pipe_fds[2];
+
+static int init_exit_fn(void *arg)
+{
+	(void)arg;
+
+	pid_t pid = CHECK(fork());
+	if (pid == 0) {
+		// This process holds the write end of the pipe until it is
+		// killed.
+		for (;;)
+			pause();
+	}
+
+	CHECK(close(pipe_fds[1]));
+	return 0;
+}
+
+FN_TEST(init_process_exit)
+{
+	TEST_SUCC(pipe(pipe_fds));
+
+	pid_t pid = TEST_SUCC(clone_in_new_pid_ns(init_exit_fn, NULL));
+	TEST_SUCC(close(pipe_fds[1]));
+
+	// All processes in the namespace are killed when the init process
+	// exits, so the write end of the pipe is eventually closed.
+	TEST_SUCC(wait_for_success(pid));
+
+	char buf;
+	TEST_RES(read(pipe_fds[0], &buf, 1), _ret == 0);
+	TEST_SUCC(close(pipe_fds[0]));
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+#define PROC_MNT "/tmp/pid_ns_proc"
+
+static int init_procfs_fn(void *arg)
+{
+	(void)arg;
+
+	char buf[256];
+
+	CHECK(mount("proc", PROC_MNT, "proc", 0, NULL));
+
+	// The procfs instance shows the IDs in the namespace where it is mounted.
+	memset(buf, 0, sizeof(buf));
+	CHECK(readlink(PROC_MNT "/self", buf, sizeof(buf) - 1));
+	CHECK_WITH(strcmp(buf, "1"), _ret == 0);
+
+	int fd = CHECK(open(PROC_MNT "/1/status", O_RDONLY));
+	memset(buf, 0, sizeof(buf));
+	CHECK(read(fd, buf, sizeof(buf) - 1));
+	CHECK_WITH(strstr(buf, "\nPid:\t1\n") != NULL, _ret);
+	CHECK_WITH(strstr(buf, "\nPPid:\t0\n") != NULL, _ret);
+	CHECK(close(fd));
+
+	// Only the processes in the namespace are listed.
+	CHECK_WITH(access(PROC_MNT "/2", F_OK), _ret < 0 && errno == ENOENT);
+
+	CHECK(umount(PROC_MNT));
+	return 0;
+}
+
+FN_TEST(procfs_in_new_pid_ns)
+{
+	TEST_SUCC(mkdir(PROC_MNT, 0755));
+
+	pid_t pid = TEST_SUCC(clone_in_new_pid_ns(init_procfs_fn, NULL));
+
+	// The process is listed in the outer procfs instance with its outer ID.
+	char path[64];
+	snprintf(path, sizeof(path), "/proc/%d/status", pid);
+	TEST_SUCC(access(path, F_OK));
+
+	TEST_SUCC(wait_for_success(pid));
+
+	TEST_SUCC(rmdir(PROC_MNT));
+}
+END_TEST()
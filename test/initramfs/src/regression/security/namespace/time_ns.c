@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <fcntl.h>
+#include <pthread.h>
+#include <sched.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define OFFSETS_PATH "/proc/self/timens_offsets"
+
+#define MONOTONIC_OFFSET 1000
+#define BOOTTIME_OFFSET 2000
+
+static int wait_for_success(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+
+	return 0;
+}
+
+static int write_offsets(const char *offsets)
+{
+	int fd = open(OFFSETS_PATH, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	ssize_t len = write(fd, offsets, strlen(offsets));
+	close(fd);
+
+	return len < 0 ? -1 : 0;
+}
+
+static int check_offsets(long long monotonic, long long boottime)
+{
+	char expected[128];
+	char buf[128];
+
+	snprintf(expected, sizeof(expected),
+		 "%-10s %10lld %9ld\n%-10s %10lld %9ld\n", "monotonic",
+		 monotonic, 0L, "boottime", boottime, 0L);
+
+	int fd = open(OFFSETS_PATH, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	memset(buf, 0, sizeof(buf));
+	ssize_t len = read(fd, buf, sizeof(buf) - 1);
+	close(fd);
+	if (len < 0)
+		return -1;
+
+	return strcmp(buf, expected) == 0 ? 0 : -1;
+}
+
+static long long read_clock_secs(clockid_t clock_id)
+{
+	struct timespec ts;
+
+	if (clock_gettime(clock_id, &ts) < 0)
+		return -1;
+
+	return ts.tv_sec;
+}
+
+static long long read_clock_secs_syscall(clockid_t clock_id)
+{
+	struct timespec ts;
+
+	if (syscall(SYS_clock_gettime, clock_id, &ts) < 0)
+		return -1;
+
+	return ts.tv_sec;
+}
+
+static ino_t read_ns_inode(const char *path)
+{
+	struct stat st;
+
+	if (stat(path, &st) < 0)
+		return 0;
+
+	return st.st_ino;
+}
+
+static void *empty_thread(void *arg)
+{
+	return arg;
+}
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(init_ns_offsets)
+{
+	// The offsets of the initial time namespace are zero and frozen.
+	TEST_SUCC(check_offsets(0, 0));
+	TEST_ERRNO(write_offsets("monotonic 1 0"), EACCES);
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(set_offsets)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		ino_t time_ns = read_ns_inode("/proc/self/ns/time");
+
+		// The caller itself does not move into the new namespace.
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK_WITH(read_ns_inode("/proc/self/ns/time"),
+			   _ret == time_ns);
+		CHECK_WITH(read_ns_inode("/proc/self/ns/time_for_children"),
+			   _ret != 0 && _ret != time_ns);
+
+		// The new namespace inherits the offsets of the old one.
+		CHECK(check_offsets(0, 0));
+
+		// Invalid offsets are rejected.
+		CHECK_WITH(write_offsets("realtime 1 0"),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(write_offsets("monotonic 1"),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(write_offsets("monotonic 1 1000000000"),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(write_offsets("monotonic -100000000000 0"),
+			   _ret < 0 && errno == ERANGE);
+		CHECK(check_offsets(0, 0));
+
+		// Clocks can be specified by their names or IDs.
+		CHECK(write_offsets("monotonic 1 0\n7 2 0\n"));
+		CHECK(check_offsets(1, 2));
+		CHECK(write_offsets("boottime 3 0"));
+		CHECK(check_offsets(1, 3));
+
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+static int check_shifted_clocks(long long monotonic, long long boottime)
+{
+	// Both the vDSO and the system call report the shifted time.
+	CHECK_WITH(read_clock_secs(CLOCK_MONOTONIC),
+		   _ret >= monotonic + MONOTONIC_OFFSET);
+	CHECK_WITH(read_clock_secs_syscall(CLOCK_MONOTONIC),
+		   _ret >= monotonic + MONOTONIC_OFFSET);
+	CHECK_WITH(read_clock_secs(CLOCK_BOOTTIME),
+		   _ret >= boottime + BOOTTIME_OFFSET);
+	CHECK_WITH(read_clock_secs_syscall(CLOCK_BOOTTIME),
+		   _ret >= boottime + BOOTTIME_OFFSET);
+
+	// The uptime is based on `CLOCK_BOOTTIME`.
+	FILE *file = CHECK_WITH(fopen("/proc/uptime", "r"), _ret != NULL);
+	double uptime;
+	CHECK_WITH(fscanf(file, "%lf", &uptime), _ret == 1);
+	CHECK_WITH(uptime, _ret >= boottime + BOOTTIME_OFFSET);
+	CHECK(fclose(file));
+
+	return 0;
+}
+
+FN_TEST(enter_new_ns)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		long long monotonic = CHECK(read_clock_secs(CLOCK_MONOTONIC));
+		long long boottime = CHECK(read_clock_secs(CLOCK_BOOTTIME));
+		long long realtime = CHECK(read_clock_secs(CLOCK_REALTIME));
+
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0\nboottime 2000 0\n"));
+
+		// Threads cannot be created while the namespaces differ.
+		pthread_t thread;
+		CHECK_WITH(pthread_create(&thread, NULL, empty_thread, NULL),
+			   _ret == EINVAL);
+
+		ino_t time_ns =
+			read_ns_inode("/proc/self/ns/time_for_children");
+
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			CHECK_WITH(read_ns_inode("/proc/self/ns/time"),
+				   _ret == time_ns);
+			CHECK(check_shifted_clocks(monotonic, boottime));
+
+			// `CLOCK_REALTIME` is not affected.
+			CHECK_WITH(read_clock_secs(CLOCK_REALTIME),
+				   _ret >= realtime &&
+					   _ret < realtime + MONOTONIC_OFFSET);
+
+			// Threads can be created in the namespace.
+			pthread_t thread;
+			CHECK_WITH(pthread_create(&thread, NULL, empty_thread,
+						  NULL),
+				   _ret == 0);
+			CHECK_WITH(pthread_join(thread, NULL), _ret == 0);
+
+			_exit(0);
+		}
+		CHECK(wait_for_success(child));
+
+		// The offsets are frozen after a process enters the namespace.
+		CHECK_WITH(write_offsets("monotonic 1 0"),
+			   _ret < 0 && errno == EACCES);
+		CHECK(check_offsets(MONOTONIC_OFFSET, BOOTTIME_OFFSET));
+
+		// The caller is still in the old namespace.
+		CHECK_WITH(read_clock_secs(CLOCK_MONOTONIC),
+			   _ret < monotonic + MONOTONIC_OFFSET);
+
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+/* -------------------------------------------------------------------------- */
+
+FN_TEST(setns_and_execve)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		long long monotonic = CHECK(read_clock_secs(CLOCK_MONOTONIC));
+		long long boottime = CHECK(read_clock_secs(CLOCK_BOOTTIME));
+
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0\nboottime 2000 0\n"));
+
+		// Joining the namespace moves the caller into it.
+		int nsfd = CHECK(open("/proc/self/ns/time_for_children",
+				      O_RDONLY));
+		CHECK(setns(nsfd, CLONE_NEWTIME));
+		CHECK(close(nsfd));
+		CHECK(check_shifted_clocks(monotonic, boottime));
+
+		CHECK_WITH(write_offsets("monotonic 1 0"),
+			   _ret < 0 && errno == EACCES);
+
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+
+	pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0\nboottime 2000 0\n"));
+
+		// Executing a new program moves the caller into the namespace.
+		CHECK(execl("/bin/sh", "sh", "-c",
+			    "test \"$(cut -d. -f1 /proc/uptime)\" -ge 2000",
+			    NULL));
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
@@ -12,12 +12,19 @@ set -e
 ./capability/setgroups
 ./capability/trusted_xattr
 
+./landlock/landlock
+
 ./lsm/module_selection
 ./lsm/yama
 
 ./namespace/cgroup_ns
 ./namespace/ipc_ns_sem
 ./namespace/mnt_ns
+./namespace/net_ns
+./namespace/pid_ns
 ./namespace/proc_nsfs
 ./namespace/setns
+./namespace/time_ns
 ./namespace/unshare
+
+./seccomp/seccomp
@@ -0,0 +1,572 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/audit.h>
+#include <linux/filter.h>
+#include <linux/seccomp.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stddef.h>
+#include <sys/prctl.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/capability.h"
+
+#if defined(__x86_64__)
+#define CURRENT_AUDIT_ARCH AUDIT_ARCH_X86_64
+#elif defined(__riscv)
+#define CURRENT_AUDIT_ARCH AUDIT_ARCH_RISCV64
+#elif defined(__loongarch64)
+#define CURRENT_AUDIT_ARCH AUDIT_ARCH_LOONGARCH64
+#endif
+
+#ifndef SYS_SECCOMP
+#define SYS_SECCOMP 1
+#endif
+
+static char child_path[4096];
+
+FN_SETUP(child_path)
+{
+	CHECK(readlink("/proc/self/exe", child_path, sizeof(child_path) - 10));
+	strcat(child_path, "_child");
+}
+END_SETUP()
+
+static int install_filter(struct sock_filter *insns, unsigned short len,
+			  unsigned int flags)
+{
+	struct sock_fprog prog = { .len = len, .filter = insns };
+
+	return syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, flags, &prog);
+}
+
+// Installs a filter that returns `action` for the syscall `nr` and allows all
+// other syscalls.
+static int filter_syscall(long nr, unsigned int action, unsigned int flags)
+{
+	struct sock_filter insns[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+			 offsetof(struct seccomp_data, arch)),
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, CURRENT_AUDIT_ARCH, 1, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_KILL_PROCESS),
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+			 offsetof(struct seccomp_data, nr)),
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, nr, 0, 1),
+		BPF_STMT(BPF_RET | BPF_K, action),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+
+	return install_filter(insns, sizeof(insns) / sizeof(insns[0]), flags);
+}
+
+static int allow_all(unsigned int flags)
+{
+	struct sock_filter insns[] = {
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+
+	return install_filter(insns, 1, flags);
+}
+
+// Returns the value of the field in `/proc/self/task/<tid>/status`.
+static int read_status_field(pid_t tid, const char *field)
+{
+	char path[64];
+	char line[256];
+	size_t field_len = strlen(field);
+	int value = -1;
+	FILE *file;
+
+	snprintf(path, sizeof(path), "/proc/self/task/%d/status", tid);
+	file = fopen(path, "r");
+	if (file == NULL)
+		return -1;
+
+	while (fgets(line, sizeof(line), file) != NULL) {
+		if (strncmp(line, field, field_len) == 0 &&
+		    line[field_len] == ':') {
+			value = atoi(line + field_len + 1);
+			break;
+		}
+	}
+
+	fclose(file);
+	return value;
+}
+
+static int wait_for_exit_code(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid || !WIFEXITED(status))
+		return -1;
+	return WEXITSTATUS(status);
+}
+
+static int wait_for_term_signal(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid || !WIFSIGNALED(status))
+		return -1;
+	return WTERMSIG(status);
+}
+
+FN_TEST(initial_state)
+{
+	TEST_RES(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 0);
+	TEST_RES(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 0);
+	TEST_RES(read_status_field(gettid(), "Seccomp"), _ret == 0);
+	TEST_RES(read_status_field(gettid(), "Seccomp_filters"), _ret == 0);
+	TEST_RES(read_status_field(gettid(), "NoNewPrivs"), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(invalid_args)
+{
+	unsigned int action = SECCOMP_RET_ALLOW;
+
+	TEST_ERRNO(syscall(SYS_seccomp, 100, 0, NULL), EINVAL);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_SET_MODE_STRICT, 1, NULL),
+		   EINVAL);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 1, &action),
+		   EINVAL);
+	TEST_ERRNO(allow_all(1U << 31), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_SECCOMP, 3, 0, 0, 0), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_NO_NEW_PRIVS, 0, 0, 0, 0), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_NO_NEW_PRIVS, 1, 1, 0, 0), EINVAL);
+	TEST_ERRNO(prctl(PR_GET_NO_NEW_PRIVS, 1, 0, 0, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(action_avail)
+{
+	unsigned int actions[] = {
+		SECCOMP_RET_KILL_PROCESS, SECCOMP_RET_KILL_THREAD,
+		SECCOMP_RET_TRAP,	  SECCOMP_RET_ERRNO,
+		SECCOMP_RET_TRACE,	  SECCOMP_RET_LOG,
+		SECCOMP_RET_ALLOW,
+	};
+	unsigned int action;
+
+	for (size_t i = 0; i < sizeof(actions) / sizeof(actions[0]); i++) {
+		action = actions[i];
+		TEST_SUCC(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0,
+				  &action));
+	}
+
+	action = 0x12340000;
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action),
+		   EOPNOTSUPP);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, NULL),
+		   EFAULT);
+}
+END_TEST()
+
+FN_TEST(invalid_filters)
+{
+	struct sock_filter no_ret[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, 0),
+	};
+	struct sock_filter jump_out_of_range[] = {
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, 0, 1, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter load_out_of_range[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, sizeof(struct seccomp_data)),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter load_unaligned[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, 2),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter load_half_word[] = {
+		BPF_STMT(BPF_LD | BPF_H | BPF_ABS, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter load_indirect[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_IND, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter load_before_store[] = {
+		BPF_STMT(BPF_LD | BPF_MEM, 0),
+		BPF_STMT(BPF_RET | BPF_A, 0),
+	};
+	struct sock_filter store_out_of_range[] = {
+		BPF_STMT(BPF_ST, 16),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter div_by_zero[] = {
+		BPF_STMT(BPF_ALU | BPF_DIV | BPF_K, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter shift_too_large[] = {
+		BPF_STMT(BPF_ALU | BPF_LSH | BPF_K, 32),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter ret_x[] = {
+		BPF_STMT(BPF_RET | BPF_X, 0),
+	};
+
+#define TEST_INVALID_FILTER(insns)                                         \
+	TEST_ERRNO(install_filter(insns, sizeof(insns) / sizeof(insns[0]), 0), \
+		   EINVAL)
+
+	TEST_INVALID_FILTER(no_ret);
+	TEST_INVALID_FILTER(jump_out_of_range);
+	TEST_INVALID_FILTER(load_out_of_range);
+	TEST_INVALID_FILTER(load_unaligned);
+	TEST_INVALID_FILTER(load_half_word);
+	TEST_INVALID_FILTER(load_indirect);
+	TEST_INVALID_FILTER(load_before_store);
+	TEST_INVALID_FILTER(store_out_of_range);
+	TEST_INVALID_FILTER(div_by_zero);
+	TEST_INVALID_FILTER(shift_too_large);
+	TEST_INVALID_FILTER(ret_x);
+
+#undef TEST_INVALID_FILTER
+
+	TEST_ERRNO(install_filter(no_ret, 0, 0), EINVAL);
+	TEST_ERRNO(install_filter(no_ret, BPF_MAXINSNS + 1, 0), EINVAL);
+	TEST_ERRNO(install_filter(NULL, 1, 0), EINVAL);
+	TEST_ERRNO(install_filter((void *)1, 1, 0), EFAULT);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, 0, NULL),
+		   EFAULT);
+
+	TEST_RES(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(no_new_privs)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		drop_capability(CAP_SYS_ADMIN);
+		CHECK_WITH(allow_all(0), _ret == -1 && errno == EACCES);
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 1);
+		CHECK_WITH(read_status_field(gettid(), "NoNewPrivs"),
+			   _ret == 1);
+
+		CHECK(allow_all(0));
+		CHECK_WITH(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 2);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(strict_mode)
+{
+	int pipefds[2];
+	char buf[2];
+	pid_t pid;
+
+	CHECK(pipe(pipefds));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(close(pipefds[0]));
+		CHECK(prctl(PR_SET_SECCOMP, SECCOMP_MODE_STRICT, 0, 0, 0));
+		// `write` is allowed.
+		CHECK_WITH(write(pipefds[1], "ok", 2), _ret == 2);
+		// Other syscalls kill the thread.
+		syscall(SYS_getppid);
+		syscall(SYS_exit, EXIT_FAILURE);
+	}
+	CHECK(close(pipefds[1]));
+
+	TEST_RES(read(pipefds[0], buf, sizeof(buf)),
+		 _ret == 2 && memcmp(buf, "ok", 2) == 0);
+	TEST_RES(wait_for_term_signal(pid), _ret == SIGKILL);
+
+	CHECK(close(pipefds[0]));
+}
+END_TEST()
+
+FN_TEST(errno_action)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+
+		CHECK(filter_syscall(SYS_getppid,
+				     SECCOMP_RET_ERRNO | ENOTTY, 0));
+		CHECK_WITH(syscall(SYS_getppid),
+			   _ret == -1 && errno == ENOTTY);
+		CHECK_WITH(read_status_field(gettid(), "Seccomp"), _ret == 2);
+		CHECK_WITH(read_status_field(gettid(), "Seccomp_filters"),
+			   _ret == 1);
+
+		// The newer filter takes precedence for the same action.
+		CHECK(filter_syscall(SYS_getppid,
+				     SECCOMP_RET_ERRNO | EACCES, 0));
+		CHECK_WITH(syscall(SYS_getppid),
+			   _ret == -1 && errno == EACCES);
+
+		// The action with the highest precedence is taken.
+		CHECK(allow_all(0));
+		CHECK_WITH(syscall(SYS_getppid),
+			   _ret == -1 && errno == EACCES);
+
+		// The error number is capped.
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_ERRNO | 0xffff,
+				     0));
+		CHECK_WITH(syscall(SYS_getppid), _ret == -1 && errno == 4095);
+
+		CHECK_WITH(read_status_field(gettid(), "Seccomp_filters"),
+			   _ret == 4);
+
+		// The mode cannot be changed.
+		CHECK_WITH(prctl(PR_SET_SECCOMP, SECCOMP_MODE_STRICT, 0, 0, 0),
+			   _ret == -1 && errno == EINVAL);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
+
+static volatile siginfo_t sigsys_info;
+
+static void handle_sigsys(int sig, siginfo_t *info, void *ucontext)
+{
+	sigsys_info = *info;
+}
+
+FN_TEST(trap_action)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct sigaction action = {
+			.sa_sigaction = handle_sigsys,
+			.sa_flags = SA_SIGINFO,
+		};
+
+		CHECK(sigaction(SIGSYS, &action, NULL));
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_TRAP | 42, 0));
+
+		syscall(SYS_getppid);
+		CHECK_WITH(sigsys_info.si_signo, _ret == SIGSYS);
+		CHECK_WITH(sigsys_info.si_code, _ret == SYS_SECCOMP);
+		CHECK_WITH(sigsys_info.si_errno, _ret == 42);
+		CHECK_WITH(sigsys_info.si_syscall, _ret == SYS_getppid);
+		CHECK_WITH(sigsys_info.si_arch, _ret == CURRENT_AUDIT_ARCH);
+		CHECK_WITH(sigsys_info.si_call_addr, _ret != NULL);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(trace_and_log_actions)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+
+		// Without a tracer, the syscall fails with `ENOSYS`.
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_TRACE | 1, 0));
+		CHECK_WITH(syscall(SYS_getppid),
+			   _ret == -1 && errno == ENOSYS);
+
+		// The syscall is logged and allowed.
+		CHECK(filter_syscall(SYS_getpid, SECCOMP_RET_LOG, 0));
+		CHECK_WITH(syscall(SYS_getpid), _ret == getpid());
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
+
+static void *call_getppid(void *arg)
+{
+	syscall(SYS_getppid);
+	return NULL;
+}
+
+FN_TEST(kill_actions)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_KILL_PROCESS,
+				     0));
+		syscall(SYS_getppid);
+		_exit(EXIT_FAILURE);
+	}
+	TEST_RES(wait_for_term_signal(pid), _ret == SIGSYS);
+
+	// Killing the only thread kills the process.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_KILL_THREAD, 0));
+		syscall(SYS_getppid);
+		_exit(EXIT_FAILURE);
+	}
+	TEST_RES(wait_for_term_signal(pid), _ret == SIGSYS);
+
+	// Killing a thread does not affect other threads.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		pthread_t thread;
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_KILL_THREAD, 0));
+		CHECK_WITH(pthread_create(&thread, NULL, call_getppid, NULL),
+			   _ret == 0);
+		CHECK_WITH(pthread_join(thread, NULL), _ret == 0);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
+
+struct sync_thread {
+	int start_pipe[2];
+	int ready_pipe[2];
+	int install_filter;
+	pid_t tid;
+	int seccomp_mode;
+	int getppid_errno;
+};
+
+static void *sync_thread_main(void *arg)
+{
+	struct sync_thread *thread = arg;
+	char buf;
+
+	thread->tid = gettid();
+	if (thread->install_filter)
+		CHECK(allow_all(0));
+	CHECK_WITH(write(thread->ready_pipe[1], "r", 1), _ret == 1);
+
+	CHECK_WITH(read(thread->start_pipe[0], &buf, 1), _ret == 1);
+	thread->seccomp_mode = prctl(PR_GET_SECCOMP, 0, 0, 0, 0);
+	errno = 0;
+	syscall(SYS_getppid);
+	thread->getppid_errno = errno;
+	return NULL;
+}
+
+static void start_sync_thread(pthread_t *pthread, struct sync_thread *thread,
+			      int install_filter)
+{
+	char buf;
+
+	memset(thread, 0, sizeof(*thread));
+	thread->install_filter = install_filter;
+	CHECK(pipe(thread->start_pipe));
+	CHECK(pipe(thread->ready_pipe));
+	CHECK_WITH(pthread_create(pthread, NULL, sync_thread_main, thread),
+		   _ret == 0);
+	CHECK_WITH(read(thread->ready_pipe[0], &buf, 1), _ret == 1);
+}
+
+static void finish_sync_thread(pthread_t pthread, struct sync_thread *thread)
+{
+	CHECK_WITH(write(thread->start_pipe[1], "s", 1), _ret == 1);
+	CHECK_WITH(pthread_join(pthread, NULL), _ret == 0);
+}
+
+FN_TEST(tsync)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct sync_thread thread;
+		pthread_t pthread;
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		start_sync_thread(&pthread, &thread, 0);
+
+		CHECK_WITH(filter_syscall(SYS_getppid,
+					  SECCOMP_RET_ERRNO | ENOTTY,
+					  SECCOMP_FILTER_FLAG_TSYNC),
+			   _ret == 0);
+		CHECK_WITH(read_status_field(thread.tid, "Seccomp_filters"),
+			   _ret == 1);
+
+		finish_sync_thread(pthread, &thread);
+		CHECK_WITH(thread.seccomp_mode, _ret == 2);
+		CHECK_WITH(thread.getppid_errno, _ret == ENOTTY);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+
+	// The threads whose filters diverge cannot be synchronized.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct sync_thread thread;
+		pthread_t pthread;
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		start_sync_thread(&pthread, &thread, 1);
+
+		CHECK_WITH(filter_syscall(SYS_getppid,
+					  SECCOMP_RET_ERRNO | ENOTTY,
+					  SECCOMP_FILTER_FLAG_TSYNC),
+			   _ret == thread.tid);
+		CHECK_WITH(filter_syscall(SYS_getppid,
+					  SECCOMP_RET_ERRNO | ENOTTY,
+					  SECCOMP_FILTER_FLAG_TSYNC |
+						  SECCOMP_FILTER_FLAG_TSYNC_ESRCH),
+			   _ret == -1 && errno == ESRCH);
+		CHECK_WITH(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 0);
+
+		finish_sync_thread(pthread, &thread);
+		CHECK_WITH(thread.seccomp_mode, _ret == 2);
+		CHECK_WITH(thread.getppid_errno, _ret == 0);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(inherit)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		pid_t grandchild;
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(filter_syscall(SYS_getppid,
+				     SECCOMP_RET_ERRNO | ENOTTY, 0));
+
+		// The filters are inherited across `fork`.
+		grandchild = CHECK(fork());
+		if (grandchild == 0) {
+			CHECK_WITH(syscall(SYS_getppid),
+				   _ret == -1 && errno == ENOTTY);
+			CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0),
+				   _ret == 1);
+			_exit(EXIT_SUCCESS);
+		}
+		CHECK_WITH(wait_for_exit_code(grandchild), _ret == 0);
+
+		// The filters are inherited across `execve`.
+		CHECK(execl(child_path, child_path, NULL));
+	}
+	TEST_RES(wait_for_exit_code(pid), _ret == 0);
+}
+END_TEST()
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include <sys/prctl.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+// This program is executed by `seccomp` with a filter that makes `getppid`
+// fail with `ENOTTY`.
+
+FN_TEST(filters_after_execve)
+{
+	TEST_ERRNO(syscall(SYS_getppid), ENOTTY);
+	TEST_RES(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 2);
+	TEST_RES(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 1);
+}
+END_TEST()